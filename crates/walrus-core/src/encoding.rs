@@ -9,6 +9,7 @@ mod basic_encoding;
 pub use basic_encoding::{
     BLOB_TYPE_ATTRIBUTE_KEY,
     Decoder,
+    PREVIOUS_QUILT_ID_ATTRIBUTE_KEY,
     QUILT_TYPE_VALUE,
     ReedSolomonDecoder,
     ReedSolomonEncoder,
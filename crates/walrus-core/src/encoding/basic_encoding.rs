@@ -19,6 +19,9 @@ pub const BLOB_TYPE_ATTRIBUTE_KEY: &str = "_walrusBlobType";
 /// The type attribute value for quilt blobs.
 pub const QUILT_TYPE_VALUE: &str = "quilt";
 
+/// The key of blob attribute, used to record the ID of the quilt a quilt was updated from.
+pub const PREVIOUS_QUILT_ID_ATTRIBUTE_KEY: &str = "_walrusPreviousQuiltId";
+
 /// Trait implemented for all basic (1D) decoders.
 pub trait Decoder: Sized {
     /// The type of the associated encoding configuration.
@@ -219,6 +219,18 @@ pub enum QuiltError {
     /// QuiltPatchId is invalid.
     #[error("QuiltPatchId: {0} is invalid")]
     QuiltPatchIdParseError(String),
+    /// The data of a quilt patch does not match its hash.
+    #[error("the data of quilt patch '{0}' does not match its hash")]
+    PatchHashMismatch(String),
+    /// Compressing or decompressing the data of a quilt patch failed.
+    #[error("failed to compress or decompress quilt patch data: {0}")]
+    CompressionError(String),
+    /// The quilt version does not support compressed patches.
+    #[error("quilt patch '{0}' is compressed, which is not supported by this quilt version")]
+    CompressionNotSupported(String),
+    /// The query to select quilt patches is invalid.
+    #[error("invalid quilt patch query: {0}")]
+    InvalidQuery(String),
     /// Other error.
     #[error("other error: {0}")]
     Other(String),
@@ -25,7 +25,7 @@ use core::{cmp, fmt};
 use std::collections::{HashMap, HashSet};
 
 use hex;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
 use tracing::{Level, Span};
 
 use super::{EncodingConfigEnum, Primary, Secondary, SliverData, SliverPair};
@@ -38,16 +38,27 @@ use crate::{
         blob_encoding::BlobEncoder,
         config::EncodingFactory as _,
     },
+    merkle::DIGEST_LEN,
     metadata::{
         QuiltIndex,
         QuiltIndexV1,
+        QuiltIndexV2,
         QuiltMetadata,
         QuiltMetadataV1,
+        QuiltMetadataV2,
         QuiltPatchInternalIdV1,
+        QuiltPatchInternalIdV2,
         QuiltPatchV1,
+        QuiltPatchV2,
     },
 };
 
+mod compression;
+pub use compression::QuiltPatchCompression;
+
+mod query;
+pub use query::{QuiltPatchComparison, QuiltPatchField, QuiltPatchFilter};
+
 /// The number of bytes to store the size of the quilt index.
 const QUILT_INDEX_SIZE_BYTES_LENGTH: usize = 4;
 
@@ -66,7 +77,9 @@ const MAX_BLOB_IDENTIFIER_BYTES_LENGTH: usize = (1 << (8 * BLOB_IDENTIFIER_SIZE_
 /// The number of bytes stored before the quilt index data.
 const QUILT_INDEX_PREFIX_SIZE: usize = QUILT_INDEX_SIZE_BYTES_LENGTH + QUILT_VERSION_BYTES_LENGTH;
 
-/// The maximum number of slivers a quilt index can be stored in.
+/// The maximum number of slivers a quilt index can be stored in for QuiltVersionV1.
+///
+/// The index of a QuiltVersionV2 quilt is not limited to a fixed number of slivers.
 const MAX_NUM_SLIVERS_FOR_QUILT_INDEX: usize = 10;
 
 /// Gets the quilt version enum from the data.
@@ -259,6 +272,14 @@ pub trait QuiltIndexApi<V: QuiltVersion>: Clone + Into<QuiltIndex> {
             .collect()
     }
 
+    /// Returns the quilt patches matching the given filter.
+    fn get_quilt_patches_by_filter(&self, filter: &QuiltPatchFilter) -> Vec<&V::QuiltPatch> {
+        self.patches()
+            .iter()
+            .filter(|patch| filter.matches(patch.identifier(), patch.tags()))
+            .collect()
+    }
+
     /// Returns the sliver indices of the quilt patches matching the given tag.
     fn get_sliver_indices_for_tag(&self, target_tag: &str, target_value: &str) -> Vec<SliverIndex> {
         self.patches()
@@ -313,6 +334,9 @@ pub trait QuiltPatchApi<V: QuiltVersion>: Clone {
     /// Returns the identifier of the quilt patch.
     fn identifier(&self) -> &str;
 
+    /// Returns the tags of the quilt patch.
+    fn tags(&self) -> &BTreeMap<String, String>;
+
     /// Returns true if the quilt patch has the given tag.
     fn has_matched_tag(&self, target_tag: &str, target_value: &str) -> bool;
 
@@ -430,6 +454,8 @@ pub trait QuiltColumnRangeReader {
 pub enum QuiltVersionEnum {
     /// QuiltVersionV1.
     V1,
+    /// QuiltVersionV2.
+    V2,
 }
 
 impl From<QuiltVersionEnum> for u8 {
@@ -444,6 +470,7 @@ impl TryFrom<u8> for QuiltVersionEnum {
     fn try_from(value: u8) -> Result<Self, Self::Error> {
         match value {
             QuiltVersionV1::QUILT_VERSION_BYTE => Ok(QuiltVersionEnum::V1),
+            QuiltVersionV2::QUILT_VERSION_BYTE => Ok(QuiltVersionEnum::V2),
             _ => Err(QuiltError::Other(format!(
                 "Invalid quilt version byte: {value}"
             ))),
@@ -461,6 +488,7 @@ impl From<String> for QuiltVersionEnum {
     fn from(value: String) -> Self {
         match value.as_str() {
             "V1" | "v1" | "1" => QuiltVersionEnum::V1,
+            "V2" | "v2" | "2" => QuiltVersionEnum::V2,
             _ => QuiltVersionEnum::V1, // Default or consider error
         }
     }
@@ -481,6 +509,8 @@ impl QuiltVersionEnum {
 pub enum QuiltEnum {
     /// The quilt version 1.
     V1(QuiltV1),
+    /// The quilt version 2.
+    V2(QuiltV2),
 }
 
 impl QuiltEnum {
@@ -494,6 +524,9 @@ impl QuiltEnum {
             QuiltVersionEnum::V1 => {
                 QuiltV1::new_from_quilt_blob(quilt_blob, encoding_config).map(QuiltEnum::V1)
             }
+            QuiltVersionEnum::V2 => {
+                QuiltV2::new_from_quilt_blob(quilt_blob, encoding_config).map(QuiltEnum::V2)
+            }
         }
     }
 
@@ -504,6 +537,7 @@ impl QuiltEnum {
     ) -> Result<Vec<QuiltStoreBlob<'static>>, QuiltError> {
         match self {
             QuiltEnum::V1(quilt_v1) => quilt_v1.get_blobs_by_identifiers(identifiers),
+            QuiltEnum::V2(quilt_v2) => quilt_v2.get_blobs_by_identifiers(identifiers),
         }
     }
 
@@ -514,6 +548,7 @@ impl QuiltEnum {
     ) -> Result<QuiltStoreBlob<'static>, QuiltError> {
         match self {
             QuiltEnum::V1(quilt_v1) => quilt_v1.get_blob_by_patch_internal_id(patch_internal_id),
+            QuiltEnum::V2(quilt_v2) => quilt_v2.get_blob_by_patch_internal_id(patch_internal_id),
         }
     }
 
@@ -525,6 +560,7 @@ impl QuiltEnum {
     ) -> Result<Vec<QuiltStoreBlob<'static>>, QuiltError> {
         match self {
             QuiltEnum::V1(quilt_v1) => quilt_v1.get_blobs_by_tag(target_tag, target_value),
+            QuiltEnum::V2(quilt_v2) => quilt_v2.get_blobs_by_tag(target_tag, target_value),
         }
     }
 
@@ -532,6 +568,7 @@ impl QuiltEnum {
     pub fn get_all_blobs(&self) -> Result<Vec<QuiltStoreBlob<'static>>, QuiltError> {
         match self {
             QuiltEnum::V1(quilt_v1) => quilt_v1.get_all_blobs(),
+            QuiltEnum::V2(quilt_v2) => quilt_v2.get_all_blobs(),
         }
     }
 
@@ -539,6 +576,7 @@ impl QuiltEnum {
     pub fn get_quilt_index(&self) -> Result<QuiltIndex, QuiltError> {
         match self {
             QuiltEnum::V1(quilt_v1) => Ok(QuiltIndex::V1(quilt_v1.quilt_index()?.clone())),
+            QuiltEnum::V2(quilt_v2) => Ok(QuiltIndex::V2(quilt_v2.quilt_index()?.clone())),
         }
     }
 }
@@ -555,6 +593,16 @@ pub struct QuiltStoreBlob<'a> {
     identifier: String,
     /// The tags of the blob.
     pub tags: BTreeMap<String, String>,
+    /// The compression applied to the blob data.
+    #[serde(skip_serializing_if = "is_uncompressed")]
+    compression: QuiltPatchCompression,
+    /// The length of the blob data after decompression.
+    #[serde(skip)]
+    uncompressed_length: u64,
+}
+
+fn is_uncompressed(compression: &QuiltPatchCompression) -> bool {
+    !compression.is_compressed()
 }
 
 impl<'a> QuiltStoreBlob<'a> {
@@ -564,9 +612,11 @@ impl<'a> QuiltStoreBlob<'a> {
         validate_quilt_identifier(&identifier)?;
 
         Ok(Self {
+            uncompressed_length: blob.len().try_into().expect("32 or 64-bit arch"),
             blob: Cow::Borrowed(blob),
             identifier,
             tags: BTreeMap::new(),
+            compression: QuiltPatchCompression::None,
         })
     }
 
@@ -581,9 +631,11 @@ impl<'a> QuiltStoreBlob<'a> {
         validate_quilt_identifier(&identifier)?;
 
         Ok(Self {
+            uncompressed_length: blob.len().try_into().expect("32 or 64-bit arch"),
             blob: Cow::Owned(blob),
             identifier,
             tags: BTreeMap::new(),
+            compression: QuiltPatchCompression::None,
         })
     }
 
@@ -593,7 +645,53 @@ impl<'a> QuiltStoreBlob<'a> {
         self
     }
 
+    /// Compresses the blob data with the given compression.
+    ///
+    /// The compressed data is stored in the quilt, and is decompressed when the blob is read back
+    /// with [`Self::decompress`]. If the data is already compressed, it is first decompressed.
+    pub fn with_compression(self, compression: QuiltPatchCompression) -> Result<Self, QuiltError> {
+        if compression == self.compression {
+            return Ok(self);
+        }
+        let blob = self.decompress()?;
+        let data = compression.compress(&blob.blob)?;
+
+        Ok(Self {
+            blob: Cow::Owned(data),
+            compression,
+            ..blob
+        })
+    }
+
+    /// Decompresses the blob data, if it is compressed.
+    pub fn decompress(self) -> Result<Self, QuiltError> {
+        if !self.compression.is_compressed() {
+            return Ok(self);
+        }
+        let data = self
+            .compression
+            .decompress(&self.blob, self.uncompressed_length)?;
+
+        Ok(Self {
+            blob: Cow::Owned(data),
+            compression: QuiltPatchCompression::None,
+            ..self
+        })
+    }
+
+    /// Returns the compression applied to the blob data.
+    pub fn compression(&self) -> QuiltPatchCompression {
+        self.compression
+    }
+
+    /// Returns the length of the blob data after decompression.
+    pub fn uncompressed_length(&self) -> u64 {
+        self.uncompressed_length
+    }
+
     /// Returns a reference to the blob data.
+    ///
+    /// If the blob is compressed, this is the compressed data.
     pub fn data(&self) -> &[u8] {
         &self.blob
     }
@@ -625,193 +723,262 @@ impl<'a> QuiltStoreBlob<'a> {
                 blob: Cow::Owned(self.blob.into_owned()),
                 identifier: self.identifier,
                 tags: self.tags,
+                compression: self.compression,
+                uncompressed_length: self.uncompressed_length,
             },
             Cow::Owned(_) => self,
         }
     }
 }
 
-/// Quilt version 1.
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct QuiltVersionV1;
+/// The header stored in front of each blob in a quilt.
+///
+/// The headers of all versions start with the version byte, the length of the serialized blob
+/// following the header, and the mask; later versions append further fields.
+trait BlobHeaderApi: Sized {
+    /// The size of the serialized header in bytes.
+    const SIZE: usize;
 
-impl QuiltVersionV1 {
-    const QUILT_VERSION_BYTE: u8 = 0x01;
-    const BLOB_HEADER_SIZE: usize = 6;
+    /// Parses the header from its [`Self::SIZE`] serialized bytes.
+    fn parse(bytes: Vec<u8>) -> Result<Self, QuiltError>;
 
-    /// Decodes the quilt index from a column data source.
-    pub fn decode_quilt_index<T>(
-        data_source: &T,
-        column_size: usize,
-    ) -> Result<QuiltIndexV1, QuiltError>
-    where
-        T: QuiltColumnRangeReader,
-    {
-        let quilt_version_bytes =
-            data_source.range_read_from_columns(0, 0, QUILT_VERSION_BYTES_LENGTH)?;
-        assert!(quilt_version_bytes.len() == QUILT_VERSION_BYTES_LENGTH);
-        utils::check_quilt_version::<QuiltVersionV1>(&quilt_version_bytes)?;
+    /// Serializes the header.
+    fn to_bytes(&self) -> Vec<u8>;
 
-        let size_bytes = data_source.range_read_from_columns(
-            0,
-            QUILT_VERSION_BYTES_LENGTH,
-            QUILT_INDEX_SIZE_BYTES_LENGTH,
-        )?;
-        assert!(size_bytes.len() == QUILT_INDEX_SIZE_BYTES_LENGTH);
+    /// Returns the length of the serialized blob following the header.
+    fn length(&self) -> u32;
 
-        let index_size = usize::try_from(u32::from_le_bytes(
-            size_bytes
-                .try_into()
-                .map_err(|_| QuiltError::FailedToExtractQuiltIndexSize)?,
-        ))
-        .map_err(|_| QuiltError::FailedToExtractQuiltIndexSize)?;
+    /// Returns true if the blob has tags.
+    fn has_tags(&self) -> bool;
+}
 
-        let index_bytes =
-            data_source.range_read_from_columns(0, QUILT_INDEX_PREFIX_SIZE, index_size)?;
-        assert!(index_bytes.len() == index_size);
+/// The version-specific parts of the layout of a quilt.
+///
+/// All versions store the quilt index in the first columns, followed by the blobs in consecutive
+/// columns sorted by their identifiers. They differ in the header stored in front of each blob
+/// and in the information recorded in the patches of the quilt index.
+trait QuiltLayout: QuiltVersion<QuiltIndex: Serialize + DeserializeOwned> {
+    /// The header stored in front of each blob.
+    type BlobHeader: BlobHeaderApi;
+
+    /// Returns the maximum number of columns the quilt index can occupy in a quilt with
+    /// `n_columns` columns.
+    fn max_columns_for_quilt_index(n_columns: usize) -> usize;
+
+    /// Returns the quilt patch of the blob, with an empty range of columns.
+    fn new_quilt_patch(blob: &QuiltStoreBlob) -> Result<Self::QuiltPatch, QuiltError>;
+
+    /// Returns the header of the blob, where `length` is the length of the serialized blob
+    /// following the header.
+    fn new_blob_header(
+        blob: &QuiltStoreBlob,
+        quilt_patch: &Self::QuiltPatch,
+        length: u32,
+    ) -> Self::BlobHeader;
 
-        // Decode the QuiltIndexV1.
-        let mut quilt_index: QuiltIndexV1 = bcs::from_bytes(&index_bytes)?;
+    /// Returns the blob decoded from its header, identifier, tags, and data.
+    ///
+    /// If the quilt patch of the blob is known, e.g., from the quilt index, the blob is checked
+    /// against it.
+    fn new_decoded_blob(
+        header: Self::BlobHeader,
+        identifier: String,
+        tags: BTreeMap<String, String>,
+        data: Vec<u8>,
+        quilt_patch: Option<&Self::QuiltPatch>,
+    ) -> Result<QuiltStoreBlob<'static>, QuiltError>;
 
-        let total_size = index_size + QUILT_INDEX_PREFIX_SIZE;
-        let columns_needed = total_size.div_ceil(column_size);
-        quilt_index.populate_start_indices(
-            u16::try_from(columns_needed).expect("columns_needed should fit in u16"),
-        );
+    /// Returns a quilt index with the given patches.
+    fn new_quilt_index(quilt_patches: Vec<Self::QuiltPatch>) -> Self::QuiltIndex;
 
-        Ok(quilt_index)
-    }
+    /// Returns the patches of the quilt index.
+    fn quilt_patches_mut(quilt_index: &mut Self::QuiltIndex) -> &mut [Self::QuiltPatch];
 
-    /// Returns the total size of the serialized blob.
-    pub fn serialized_blob_size(blob: &QuiltStoreBlob) -> Result<usize, QuiltError> {
-        let identifier_size = bcs::serialized_size(&blob.identifier)
-            .map_err(|e| QuiltError::Other(format!("Failed to compute identifier size: {e}")))?;
+    /// Populates the start indices of the patches of a deserialized quilt index.
+    fn populate_start_indices(quilt_index: &mut Self::QuiltIndex, first_start: u16);
 
-        if identifier_size >= MAX_BLOB_IDENTIFIER_BYTES_LENGTH {
-            return Err(QuiltError::InvalidIdentifier(format!(
-                "identifier size exceeds maximum allowed value: {MAX_BLOB_IDENTIFIER_BYTES_LENGTH}"
-            )));
-        }
-        let mut prefix_size = identifier_size as usize + BLOB_IDENTIFIER_SIZE_BYTES_LENGTH;
+    /// Sets the range of columns of the quilt patch.
+    fn set_patch_range(quilt_patch: &mut Self::QuiltPatch, start_index: u16, end_index: u16);
 
-        if !blob.tags.is_empty() {
-            let tags_size = bcs::serialized_size(&blob.tags)
-                .map_err(|e| QuiltError::Other(format!("Failed to compute tags size: {e}")))?;
-            prefix_size += tags_size + TAGS_SIZE_BYTES_LENGTH;
-        }
+    /// Returns the start and end column of the patch with the given internal id.
+    fn internal_id_range(patch_internal_id: &Self::QuiltPatchInternalId) -> (u16, u16);
+
+    /// Returns a quilt with the given data and quilt index.
+    fn new_quilt(
+        data: Vec<u8>,
+        row_size: usize,
+        symbol_size: usize,
+        quilt_index: Self::QuiltIndex,
+    ) -> Self::Quilt;
+}
+
+/// Reads the blobs of a quilt of version `V` from its columns, using the quilt index.
+///
+/// Implemented by the quilts and by the quilt decoders, which may only hold some of the columns.
+trait QuiltPatchReader<V: QuiltLayout>: QuiltColumnRangeReader + Sized {
+    /// Returns the quilt index.
+    fn known_quilt_index(&self) -> Result<&V::QuiltIndex, QuiltError>;
 
-        Ok(prefix_size + blob.data().len() + QuiltVersionV1::BLOB_HEADER_SIZE)
+    /// Checks that the columns in the range `[start_col, end_col)` are available.
+    fn check_columns(&self, _start_col: usize, _end_col: usize) -> Result<(), QuiltError> {
+        Ok(())
     }
 
-    /// Decodes a blob from a column data source.
-    pub fn decode_blob<T>(
-        data_source: &T,
-        start_col: usize,
-    ) -> Result<QuiltStoreBlob<'static>, QuiltError>
+    /// Reads the blob stored in the given range of columns.
+    ///
+    /// If the quilt patch of the blob is given, the blob is checked against it.
+    fn read_blob(
+        &self,
+        (start_index, end_index): (u16, u16),
+        quilt_patch: Option<&V::QuiltPatch>,
+    ) -> Result<QuiltStoreBlob<'static>, QuiltError> {
+        let start_col = usize::from(start_index);
+        self.check_columns(start_col, usize::from(end_index))?;
+        utils::decode_blob::<V, _>(self, start_col, quilt_patch)
+    }
+
+    /// Reads the blobs of the given quilt patches.
+    fn read_blobs<'p>(
+        &self,
+        quilt_patches: impl IntoIterator<Item = &'p V::QuiltPatch>,
+    ) -> Result<Vec<QuiltStoreBlob<'static>>, QuiltError>
     where
-        T: QuiltColumnRangeReader,
+        V::QuiltPatch: 'p,
     {
-        let header_bytes =
-            data_source.range_read_from_columns(start_col, 0, Self::BLOB_HEADER_SIZE)?;
-        assert!(header_bytes.len() == Self::BLOB_HEADER_SIZE);
-        let blob_header = BlobHeaderV1::from_bytes(
-            header_bytes
-                .try_into()
-                .expect("header_bytes should be 6 bytes"),
-        );
+        quilt_patches
+            .into_iter()
+            .map(|patch| {
+                let range = V::internal_id_range(&patch.quilt_patch_internal_id());
+                self.read_blob(range, Some(patch))
+            })
+            .collect()
+    }
 
-        let mut offset = Self::BLOB_HEADER_SIZE;
-        let mut blob_bytes_size =
-            usize::try_from(blob_header.length).expect("length should fit in usize");
+    /// Reads the blobs with the given identifiers.
+    fn read_blobs_by_identifiers(
+        &self,
+        identifiers: &[&str],
+    ) -> Result<Vec<QuiltStoreBlob<'static>>, QuiltError> {
+        self.read_blobs(
+            self.known_quilt_index()?
+                .get_quilt_patches_by_identifiers(identifiers)?,
+        )
+    }
 
-        let (identifier, bytes_consumed) =
-            Self::decode_blob_identifier(data_source, start_col, offset)?;
-        offset += bytes_consumed;
-        blob_bytes_size -= bytes_consumed;
+    /// Reads the blobs matching the given tag.
+    fn read_blobs_by_tag(
+        &self,
+        target_tag: &str,
+        target_value: &str,
+    ) -> Result<Vec<QuiltStoreBlob<'static>>, QuiltError> {
+        self.read_blobs(
+            self.known_quilt_index()?
+                .get_quilt_patches_by_tag(target_tag, target_value),
+        )
+    }
 
-        let tags = if blob_header.has_tags() {
-            let (tags, bytes_consumed) = Self::decode_blob_tags(data_source, start_col, offset)?;
-            offset += bytes_consumed;
-            blob_bytes_size -= bytes_consumed;
-            tags
-        } else {
-            BTreeMap::new()
-        };
+    /// Reads all the blobs of the quilt.
+    fn read_all_blobs(&self) -> Result<Vec<QuiltStoreBlob<'static>>, QuiltError> {
+        self.read_blobs(self.known_quilt_index()?.patches())
+    }
 
-        let data_bytes = data_source.range_read_from_columns(start_col, offset, blob_bytes_size)?;
-        assert!(data_bytes.len() == blob_bytes_size);
+    /// Reads the blob with the given serialized patch internal id.
+    fn read_blob_by_patch_internal_id(
+        &self,
+        patch_internal_id: &[u8],
+    ) -> Result<QuiltStoreBlob<'static>, QuiltError> {
+        let patch_internal_id = V::QuiltPatchInternalId::from_bytes(patch_internal_id)?;
+        self.read_blob(V::internal_id_range(&patch_internal_id), None)
+    }
+}
 
-        Ok(QuiltStoreBlob::new_owned(data_bytes, identifier)?.with_tags(tags))
+/// The secondary slivers of a quilt held by a quilt decoder.
+#[derive(Debug, Default)]
+struct QuiltSlivers<'a> {
+    slivers: HashMap<SliverIndex, &'a SliverData<Secondary>>,
+    column_size: Option<usize>,
+}
+
+impl<'a> QuiltSlivers<'a> {
+    /// Creates a new set of slivers.
+    fn new(slivers: impl IntoIterator<Item = &'a SliverData<Secondary>>) -> Self {
+        let mut quilt_slivers = Self::default();
+        quilt_slivers.add(slivers);
+        quilt_slivers
     }
 
-    /// Decodes the blob identifier from a column data source.
-    /// Returns a tuple containing the decoded identifier string and the total number
-    /// of bytes consumed.
-    fn decode_blob_identifier<T>(
-        data_source: &T,
-        start_col: usize,
-        initial_offset: usize,
-    ) -> Result<(String, usize), QuiltError>
-    where
-        T: QuiltColumnRangeReader,
-    {
-        let mut offset = initial_offset;
+    /// Adds slivers to the set.
+    fn add(&mut self, slivers: impl IntoIterator<Item = &'a SliverData<Secondary>>) {
+        for sliver in slivers {
+            self.column_size
+                .get_or_insert_with(|| sliver.symbols.data().len());
+            self.slivers.insert(sliver.index, sliver);
+        }
+    }
 
-        // Read identifier size (2 bytes).
-        let size_buffer = data_source.range_read_from_columns(
-            start_col,
-            offset,
-            BLOB_IDENTIFIER_SIZE_BYTES_LENGTH,
-        )?;
-        offset += BLOB_IDENTIFIER_SIZE_BYTES_LENGTH;
+    /// Checks if any of the slivers in the range `[start_idx, end_idx)` are missing.
+    fn check_missing(&self, start_idx: usize, end_idx: usize) -> Result<(), QuiltError> {
+        utils::check_missing_slivers(&self.slivers, start_idx, end_idx)
+    }
 
-        // Parse identifier size.
-        let identifier_size = usize::from(u16::from_le_bytes(
-            size_buffer
-                .try_into()
-                .expect("size_buffer should be 2 bytes"),
-        ));
+    /// Decodes the quilt index of version `V` from the slivers.
+    fn decode_quilt_index<V: QuiltLayout>(&self) -> Result<V::QuiltIndex, QuiltError> {
+        self.check_missing(0, 1)?;
+        let column_size = self.column_size.expect("column size should be set");
+        utils::decode_quilt_index::<V, _>(self, column_size)
+    }
+}
 
-        // Read the actual identifier.
-        let identifier_bytes =
-            data_source.range_read_from_columns(start_col, offset, identifier_size)?;
-        debug_assert!(identifier_bytes.len() == identifier_size);
+impl QuiltColumnRangeReader for QuiltSlivers<'_> {
+    fn range_read_from_columns(
+        &self,
+        start_col: usize,
+        bytes_to_skip: usize,
+        bytes_to_return: usize,
+    ) -> Result<Vec<u8>, QuiltError> {
+        utils::range_read_from_slivers(
+            &self.slivers,
+            self.column_size,
+            start_col,
+            bytes_to_skip,
+            bytes_to_return,
+        )
+    }
+}
 
-        // Deserialize the identifier bytes into a String.
-        let identifier = bcs::from_bytes(&identifier_bytes).map_err(|_| {
-            QuiltError::InvalidIdentifier("Failed to deserialize identifier".into())
-        })?;
+/// Quilt version 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuiltVersionV1;
 
-        // Calculate total bytes consumed.
-        let bytes_consumed = BLOB_IDENTIFIER_SIZE_BYTES_LENGTH + identifier_size;
+impl QuiltVersionV1 {
+    const QUILT_VERSION_BYTE: u8 = 0x01;
+    const BLOB_HEADER_SIZE: usize = 6;
 
-        Ok((identifier, bytes_consumed))
+    /// Decodes the quilt index from a column data source.
+    pub fn decode_quilt_index<T>(
+        data_source: &T,
+        column_size: usize,
+    ) -> Result<QuiltIndexV1, QuiltError>
+    where
+        T: QuiltColumnRangeReader,
+    {
+        utils::decode_quilt_index::<Self, _>(data_source, column_size)
     }
 
-    /// Decodes the blob tags from a column data source.
-    fn decode_blob_tags<T>(
+    /// Returns the total size of the serialized blob.
+    pub fn serialized_blob_size(blob: &QuiltStoreBlob) -> Result<usize, QuiltError> {
+        utils::serialized_blob_size::<Self>(blob)
+    }
+
+    /// Decodes a blob from a column data source.
+    pub fn decode_blob<T>(
         data_source: &T,
         start_col: usize,
-        initial_offset: usize,
-    ) -> Result<(BTreeMap<String, String>, usize), QuiltError>
+    ) -> Result<QuiltStoreBlob<'static>, QuiltError>
     where
         T: QuiltColumnRangeReader,
     {
-        let size_bytes = data_source.range_read_from_columns(
-            start_col,
-            initial_offset,
-            TAGS_SIZE_BYTES_LENGTH,
-        )?;
-        assert!(size_bytes.len() == TAGS_SIZE_BYTES_LENGTH);
-        let tags_size =
-            u16::from_le_bytes(size_bytes.try_into().expect("size_bytes should be 2 bytes"));
-        let mut offset = initial_offset + TAGS_SIZE_BYTES_LENGTH;
-        let tags_bytes =
-            data_source.range_read_from_columns(start_col, offset, tags_size as usize)?;
-        let tags = bcs::from_bytes(&tags_bytes)
-            .map_err(|error| QuiltError::FailedToDecodeExtension("tags".into(), error))?;
-        offset += tags_size as usize;
-        Ok((tags, offset - initial_offset))
+        utils::decode_blob::<Self, _>(data_source, start_col, None)
     }
 }
 
@@ -831,19 +998,89 @@ impl QuiltVersion for QuiltVersionV1 {
     }
 }
 
-/// The header of a encoded blob in QuiltVersionV1.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
-pub struct BlobHeaderV1 {
-    /// The length of the serialized blob.
-    pub length: u32,
-    /// The mask of the blob.
-    pub mask: u8,
-}
+impl QuiltLayout for QuiltVersionV1 {
+    type BlobHeader = BlobHeaderV1;
 
-impl BlobHeaderV1 {
-    /// The number of bytes used to store the size of the serialized blob.
-    const BLOB_SIZE_BYTES_LENGTH: usize = 4;
-    /// The mask bit that indicates whether the blob has attributes.
+    fn max_columns_for_quilt_index(_n_columns: usize) -> usize {
+        MAX_NUM_SLIVERS_FOR_QUILT_INDEX
+    }
+
+    fn new_quilt_patch(blob: &QuiltStoreBlob) -> Result<QuiltPatchV1, QuiltError> {
+        // The blob header of V1 cannot record the compression of a blob.
+        if blob.compression.is_compressed() {
+            return Err(QuiltError::CompressionNotSupported(blob.identifier.clone()));
+        }
+        QuiltPatchV1::new_with_tags(blob.identifier.clone(), blob.tags.clone())
+    }
+
+    fn new_blob_header(
+        blob: &QuiltStoreBlob,
+        _quilt_patch: &QuiltPatchV1,
+        length: u32,
+    ) -> BlobHeaderV1 {
+        let mut header = BlobHeaderV1::new(length, 0);
+        header.set_has_tags(!blob.tags.is_empty());
+        header
+    }
+
+    fn new_decoded_blob(
+        _header: BlobHeaderV1,
+        identifier: String,
+        tags: BTreeMap<String, String>,
+        data: Vec<u8>,
+        _quilt_patch: Option<&QuiltPatchV1>,
+    ) -> Result<QuiltStoreBlob<'static>, QuiltError> {
+        Ok(QuiltStoreBlob::new_owned(data, identifier)?.with_tags(tags))
+    }
+
+    fn new_quilt_index(quilt_patches: Vec<QuiltPatchV1>) -> QuiltIndexV1 {
+        QuiltIndexV1 { quilt_patches }
+    }
+
+    fn quilt_patches_mut(quilt_index: &mut QuiltIndexV1) -> &mut [QuiltPatchV1] {
+        &mut quilt_index.quilt_patches
+    }
+
+    fn populate_start_indices(quilt_index: &mut QuiltIndexV1, first_start: u16) {
+        quilt_index.populate_start_indices(first_start);
+    }
+
+    fn set_patch_range(quilt_patch: &mut QuiltPatchV1, start_index: u16, end_index: u16) {
+        quilt_patch.set_range(start_index, end_index);
+    }
+
+    fn internal_id_range(patch_internal_id: &QuiltPatchInternalIdV1) -> (u16, u16) {
+        (patch_internal_id.start_index, patch_internal_id.end_index)
+    }
+
+    fn new_quilt(
+        data: Vec<u8>,
+        row_size: usize,
+        symbol_size: usize,
+        quilt_index: QuiltIndexV1,
+    ) -> QuiltV1 {
+        QuiltV1 {
+            data,
+            row_size,
+            symbol_size,
+            quilt_index: Some(quilt_index),
+        }
+    }
+}
+
+/// The header of a encoded blob in QuiltVersionV1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BlobHeaderV1 {
+    /// The length of the serialized blob.
+    pub length: u32,
+    /// The mask of the blob.
+    pub mask: u8,
+}
+
+impl BlobHeaderV1 {
+    /// The number of bytes used to store the size of the serialized blob.
+    const BLOB_SIZE_BYTES_LENGTH: usize = 4;
+    /// The mask bit that indicates whether the blob has attributes.
     const TAGS_ENABLED: u8 = 1;
 
     /// The maximum value of the length.
@@ -902,6 +1139,30 @@ impl BlobHeaderV1 {
         }
     }
 }
+
+impl BlobHeaderApi for BlobHeaderV1 {
+    const SIZE: usize = QuiltVersionV1::BLOB_HEADER_SIZE;
+
+    fn parse(bytes: Vec<u8>) -> Result<Self, QuiltError> {
+        utils::check_quilt_version::<QuiltVersionV1>(&bytes)?;
+        Ok(Self::from_bytes(
+            bytes.try_into().expect("header_bytes should be 6 bytes"),
+        ))
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        self.as_bytes().to_vec()
+    }
+
+    fn length(&self) -> u32 {
+        self.length
+    }
+
+    fn has_tags(&self) -> bool {
+        BlobHeaderV1::has_tags(self)
+    }
+}
+
 /// A quilt is a collection of blobs encoded into a single blob.
 ///
 /// For QuiltVersionV1:
@@ -935,34 +1196,16 @@ impl QuiltApi<QuiltVersionV1> for QuiltV1 {
         quilt_blob: Vec<u8>,
         encoding_config: &EncodingConfigEnum<'_>,
     ) -> Result<QuiltV1, QuiltError> {
-        let n_primary_source_symbols =
-            usize::from(encoding_config.n_source_symbols::<Primary>().get());
-        let n_secondary_source_symbols =
-            usize::from(encoding_config.n_source_symbols::<Secondary>().get());
-        let n_source_symbols: usize = n_primary_source_symbols * n_secondary_source_symbols;
-
-        if n_source_symbols == 0 {
-            return Err(QuiltError::Other(
-                "n_source_symbols cannot be zero".to_string(),
-            ));
-        }
-        if quilt_blob.len() % n_source_symbols != 0 {
-            return Err(QuiltError::InvalidFormatNotAligned(format!(
-                "quilt_blob length {} is not a multiple of n_source_symbols {}",
-                quilt_blob.len(),
-                n_source_symbols
-            )));
-        }
-
-        let symbol_size = quilt_blob.len() / n_source_symbols;
-        let row_size = symbol_size * n_secondary_source_symbols;
+        let (row_size, symbol_size) =
+            utils::quilt_matrix_dimensions(quilt_blob.len(), encoding_config)?;
+        let columns_size = quilt_blob.len() / row_size * symbol_size;
         let mut quilt = QuiltV1 {
             data: quilt_blob,
             row_size,
             symbol_size,
             quilt_index: None,
         };
-        let _ = quilt.get_or_decode_quilt_index()?;
+        quilt.quilt_index = Some(QuiltVersionV1::decode_quilt_index(&quilt, columns_size)?);
 
         Ok(quilt)
     }
@@ -971,20 +1214,14 @@ impl QuiltApi<QuiltVersionV1> for QuiltV1 {
         &self,
         identifiers: &[&str],
     ) -> Result<Vec<QuiltStoreBlob<'static>>, QuiltError> {
-        self.quilt_index()?
-            .get_quilt_patches_by_identifiers(identifiers)?
-            .iter()
-            .map(|patch| QuiltVersionV1::decode_blob(self, usize::from(patch.start_index)))
-            .collect()
+        self.read_blobs_by_identifiers(identifiers)
     }
 
     fn get_blob_by_patch_internal_id(
         &self,
         patch_internal_id: &[u8],
     ) -> Result<QuiltStoreBlob<'static>, QuiltError> {
-        let patch_internal_id = QuiltPatchInternalIdV1::from_bytes(patch_internal_id)?;
-        let start_col = usize::from(patch_internal_id.start_index);
-        QuiltVersionV1::decode_blob(self, start_col)
+        self.read_blob_by_patch_internal_id(patch_internal_id)
     }
 
     fn get_blobs_by_tag(
@@ -992,22 +1229,11 @@ impl QuiltApi<QuiltVersionV1> for QuiltV1 {
         target_tag: &str,
         target_value: &str,
     ) -> Result<Vec<QuiltStoreBlob<'static>>, QuiltError> {
-        self.quilt_index()?
-            .get_quilt_patches_by_tag(target_tag, target_value)
-            .iter()
-            .map(|patch| {
-                let start_col = usize::from(patch.start_index);
-                QuiltVersionV1::decode_blob(self, start_col)
-            })
-            .collect()
+        self.read_blobs_by_tag(target_tag, target_value)
     }
 
     fn get_all_blobs(&self) -> Result<Vec<QuiltStoreBlob<'static>>, QuiltError> {
-        self.quilt_index()?
-            .patches()
-            .iter()
-            .map(|patch| QuiltVersionV1::decode_blob(self, usize::from(patch.start_index)))
-            .collect()
+        self.read_all_blobs()
     }
 
     fn quilt_index(&self) -> Result<&QuiltIndexV1, QuiltError> {
@@ -1025,110 +1251,88 @@ impl QuiltApi<QuiltVersionV1> for QuiltV1 {
     }
 }
 
+impl QuiltPatchReader<QuiltVersionV1> for QuiltV1 {
+    fn known_quilt_index(&self) -> Result<&QuiltIndexV1, QuiltError> {
+        self.quilt_index()
+    }
+}
+
 // Implementation of QuiltColumnRangeReader for QuiltV1.
 // Returns `IndexOutOfBounds` if there is not enough data to read.
 impl QuiltColumnRangeReader for QuiltV1 {
     fn range_read_from_columns(
         &self,
         start_col: usize,
-        mut bytes_to_skip: usize,
-        mut bytes_to_return: usize,
+        bytes_to_skip: usize,
+        bytes_to_return: usize,
     ) -> Result<Vec<u8>, QuiltError> {
-        if self.symbol_size == 0 || self.row_size == 0 || self.data.is_empty() {
-            return Err(QuiltError::Other("empty quilt data".to_string()));
-        }
-
-        // Initialize state
-        let n_rows = self.data.len() / self.row_size;
-        let symbols_to_skip = bytes_to_skip / self.symbol_size;
-        let mut current_col = start_col + symbols_to_skip / n_rows;
-        let mut current_row = symbols_to_skip % n_rows;
-        let mut result = Vec::with_capacity(bytes_to_return);
-        bytes_to_skip -= symbols_to_skip * self.symbol_size;
-
-        // Helper function to calculate data slice for current position.
-        let get_slice = |col: usize, row: usize, skip: usize, limit: usize| {
-            let base_index = row * self.row_size + col * self.symbol_size;
-            let start_index = base_index + skip;
-            let end_index = (base_index + self.symbol_size)
-                .min(start_index + limit)
-                .min(self.data.len());
-            if start_index >= self.data.len() {
-                return Err(QuiltError::IndexOutOfBounds(start_index, self.data.len()));
-            }
-            Ok(&self.data[start_index..end_index])
-        };
-
-        // Helper function to advance to the next position.
-        let advance_position = |col: &mut usize, row: &mut usize| {
-            *row = (*row + 1) % n_rows;
-            if *row == 0 {
-                *col += 1;
-            }
-        };
-
-        // Process cells until we've collected enough bytes or run out of columns.
-        while bytes_to_return > 0 {
-            let slice = get_slice(current_col, current_row, bytes_to_skip, bytes_to_return)?;
-            result.extend_from_slice(slice);
-            bytes_to_return -= slice.len();
-
-            advance_position(&mut current_col, &mut current_row);
-            bytes_to_skip = 0;
-        }
-
-        Ok(result)
-    }
-}
-
-impl QuiltV1 {
-    /// Returns the quilt index.
-    ///
-    /// If the quilt index is not set, it will decode it from the quilt data, and set it.
-    fn get_or_decode_quilt_index(&mut self) -> Result<&QuiltIndexV1, QuiltError> {
-        if self.quilt_index.is_some() {
-            return Ok(self
-                .quilt_index
-                .as_ref()
-                .expect("quilt index should be set"));
-        }
-
-        let columns_size = self.data.len() / self.row_size * self.symbol_size;
-        let quilt_index = QuiltVersionV1::decode_quilt_index(self, columns_size)?;
-        self.quilt_index = Some(quilt_index);
-
-        Ok(self
-            .quilt_index
-            .as_ref()
-            .expect("quilt index should be set"))
+        utils::range_read_from_matrix(
+            &self.data,
+            self.row_size,
+            self.symbol_size,
+            start_col,
+            bytes_to_skip,
+            bytes_to_return,
+        )
     }
 }
 
 impl fmt::Debug for QuiltV1 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut ds = f.debug_struct("QuiltV1");
-
-        ds.field(
-            "\ndata",
-            &format_args!(
-                "\n{:#?}",
-                DebugMatrix {
-                    data: &self.data,
-                    row_size: self.row_size,
-                    symbol_size: self.symbol_size
-                }
-            ),
-        );
+        fmt_quilt::<QuiltVersionV1>(
+            f,
+            "QuiltV1",
+            &self.data,
+            self.row_size,
+            self.symbol_size,
+            self.quilt_index.as_ref(),
+        )
+    }
+}
 
-        ds.field(
-            "quilt_index",
-            &format_args!("\n{:#?}", DebugQuiltIndex(self.quilt_index().ok())),
-        );
+/// Formats a quilt of version `V` for debugging.
+fn fmt_quilt<V: QuiltLayout>(
+    f: &mut fmt::Formatter<'_>,
+    name: &str,
+    data: &[u8],
+    row_size: usize,
+    symbol_size: usize,
+    quilt_index: Option<&V::QuiltIndex>,
+) -> fmt::Result {
+    let mut ds = f.debug_struct(name);
+
+    ds.field(
+        "\ndata",
+        &format_args!(
+            "\n{:#?}",
+            DebugMatrix {
+                data,
+                row_size,
+                symbol_size
+            }
+        ),
+    );
+
+    ds.field(
+        "quilt_index",
+        &format_args!(
+            "\n{:#?}",
+            DebugQuiltIndex(quilt_index.map(|quilt_index| {
+                quilt_index
+                    .patches()
+                    .iter()
+                    .map(|patch| {
+                        let (_, end_index) = V::internal_id_range(&patch.quilt_patch_internal_id());
+                        (end_index, patch.identifier())
+                    })
+                    .collect()
+            }))
+        ),
+    );
 
-        ds.field("symbol_size", &self.symbol_size).finish()?;
+    ds.field("symbol_size", &symbol_size).finish()?;
 
-        writeln!(f)
-    }
+    writeln!(f)
 }
 
 struct DebugMatrix<'a> {
@@ -1189,17 +1393,18 @@ impl fmt::Debug for DebugRow<'_> {
     }
 }
 
-struct DebugQuiltIndex<'a>(Option<&'a QuiltIndexV1>);
+/// Debug representation of a quilt index, given as the end indices and identifiers of its patches.
+struct DebugQuiltIndex<'a>(Option<Vec<(u16, &'a str)>>);
 
 impl fmt::Debug for DebugQuiltIndex<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self.0 {
-            Some(quilt_index) => {
+        match &self.0 {
+            Some(patches) => {
                 let mut list = f.debug_list();
-                for patch in quilt_index.quilt_patches.iter() {
+                for (end_index, identifier) in patches {
                     list.entry(&format_args!(
                         "\nQuiltPatch {{\n    end_index: {}\n    identifier: {:?}\n}}",
-                        patch.end_index, patch.identifier
+                        end_index, identifier
                     ));
                 }
                 list.finish()?;
@@ -1240,8 +1445,14 @@ impl<'a> QuiltConfigApi<'a, QuiltVersionV1> for QuiltConfigV1 {
     where
         Secondary: 'a,
     {
-        let QuiltIndex::V1(quilt_index) = quilt_index;
-        QuiltDecoderV1::new_with_quilt_index(slivers, quilt_index.clone())
+        match quilt_index {
+            QuiltIndex::V1(quilt_index) => {
+                QuiltDecoderV1::new_with_quilt_index(slivers, quilt_index.clone())
+            }
+            // An index of a different version cannot describe this quilt; the decoder decodes the
+            // index from the slivers instead, which reports the version mismatch.
+            QuiltIndex::V2(_) => QuiltDecoderV1::new(slivers),
+        }
     }
 }
 
@@ -1268,176 +1479,1071 @@ impl<'a> QuiltEncoderV1<'a> {
 
     /// Returns the header and extension bytes of the blob.
     pub fn get_header_and_extension_bytes(blob: &QuiltStoreBlob) -> Result<Vec<u8>, QuiltError> {
-        let mut identifier_bytes = Vec::new();
-        let mut extension_bytes = Vec::new();
-        let mut header = BlobHeaderV1::default();
+        let quilt_patch = QuiltVersionV1::new_quilt_patch(blob)?;
+        utils::blob_prefix_bytes::<QuiltVersionV1>(blob, &quilt_patch)
+    }
+}
 
-        let identifier_size =
-            u16::try_from(bcs::serialized_size(&blob.identifier).map_err(|e| {
-                QuiltError::InvalidIdentifier(format!("Failed to serialize identifier: {e}"))
-            })?)
-            .map_err(|e| {
-                QuiltError::InvalidIdentifier(format!(
-                    "Failed to convert identifier size to u16: {e}"
-                ))
-            })?;
-        identifier_bytes.extend_from_slice(&identifier_size.to_le_bytes());
-        identifier_bytes.extend_from_slice(&bcs::to_bytes(&blob.identifier).map_err(|e| {
-            QuiltError::InvalidIdentifier(format!("Failed to serialize identifier: {e}"))
-        })?);
-        extension_bytes.push(identifier_bytes);
+impl QuiltEncoderApi<QuiltVersionV1> for QuiltEncoderV1<'_> {
+    /// Constructs a [`QuiltV1`].
+    fn construct_quilt(&self) -> Result<QuiltV1, QuiltError> {
+        let _guard = self.span.enter();
+        utils::construct_quilt::<QuiltVersionV1>(self.blobs, &self.config)
+    }
 
-        if !blob.tags.is_empty() {
-            header.set_has_tags(true);
-            let serialized_tags = bcs::to_bytes(&blob.tags)
-                .map_err(|e| QuiltError::Other(format!("Failed to serialize tags: {e}")))?;
+    /// Encodes the blobs into a quilt and returns the slivers.
+    fn encode(&self) -> Result<Vec<SliverPair>, QuiltError> {
+        let _guard = self.span.enter();
+        tracing::debug!("starting to encode quilt");
 
-            // This must be the same as TAGS_SIZE_BYTES_LENGTH.
-            let tags_size = u16::try_from(serialized_tags.len()).map_err(|e| {
-                QuiltError::Other(format!("Failed to convert tags size to u16: {e}"))
-            })?;
+        utils::encode_quilt::<QuiltVersionV1>(&self.construct_quilt()?, &self.config)
+    }
 
-            let mut result_bytes = Vec::with_capacity(tags_size as usize + serialized_tags.len());
-            result_bytes.extend_from_slice(&tags_size.to_le_bytes());
-            result_bytes.extend_from_slice(&serialized_tags);
-            extension_bytes.push(result_bytes);
-        }
+    /// Encodes the blobs into a quilt and returns the slivers and metadata.
+    fn encode_with_metadata(&self) -> Result<(Vec<SliverPair>, QuiltMetadata), QuiltError> {
+        let _guard = self.span.enter();
+        tracing::debug!("starting to encode quilt with metadata");
 
-        let total_size = extension_bytes.iter().map(|b| b.len()).sum::<usize>() + blob.data().len();
-        header.length = total_size as u32;
-        let header_bytes = header.as_bytes();
-        debug_assert_eq!(header_bytes.len(), QuiltVersionV1::BLOB_HEADER_SIZE);
+        utils::encode_quilt_with_metadata::<QuiltVersionV1>(&self.construct_quilt()?, &self.config)
+    }
+}
 
-        let mut result_bytes = Vec::with_capacity(header_bytes.len() + total_size);
-        result_bytes.extend_from_slice(&header_bytes);
-        for mut inner_extension_vec in extension_bytes {
-            result_bytes.append(&mut inner_extension_vec);
+/// A quilt decoder of version V1.
+#[derive(Debug)]
+pub struct QuiltDecoderV1<'a> {
+    slivers: QuiltSlivers<'a>,
+    quilt_index: Option<QuiltIndexV1>,
+}
+
+impl<'a> QuiltDecoderApi<'a, QuiltVersionV1> for QuiltDecoderV1<'a> {
+    fn get_or_decode_quilt_index(&mut self) -> Result<QuiltIndex, QuiltError> {
+        if let Some(quilt_index) = self.quilt_index.as_ref() {
+            return Ok(quilt_index.clone().into());
         }
 
-        Ok(result_bytes)
-    }
+        let quilt_index = self.slivers.decode_quilt_index::<QuiltVersionV1>()?;
+        self.quilt_index = Some(quilt_index.clone());
 
-    /// Adds a blob to the quilt as consecutive columns.
-    ///
-    /// Returns the number of columns used to store the blob.
-    ///
-    /// The blob data layout is as follows:
-    ///
-    /// ```text
-    /// +------------------+-----------------------------+----------------------+------------------+
-    /// | Blob Header      | Identifier Section          | Feature Section      | Blob Data        |
-    /// | (6 bytes)        | (variable length)           | (optional)           | (variable length)|
-    /// +------------------+-----------------------------+----------------------+------------------+
-    ///                    |                             |                      |
-    ///                    v                             v                      v
-    /// +------------------+----------------+------------+----------------------+------------------+
-    /// | BlobHeaderV1     | Identifier Size| Serialized | Feature Data         | Actual blob      |
-    /// | (version, length,| (2 bytes)      | Identifier | (Feature size +      | data             |
-    /// |  mask flags)     | u16            | (variable) | serialized features) | (variable)       |
-    /// +------------------+----------------+------------+----------------------+------------------+
-    /// ```
-    ///
-    /// - BlobHeaderV1: Contains version byte, length , and mask feature flags.
-    /// - Identifier Size: 2-byte length of the serialized identifier.
-    /// - Serialized Identifier: BCS-encoded identifier string.
-    /// - Feature Data: Optional section for feature data, such as attributes,
-    ///   (when mask flag is set).
-    /// - Blob Data: The actual blob contents.
-    fn add_blob_to_quilt(
-        data: &mut [u8],
-        blob: &QuiltStoreBlob,
-        is_meta_blob: bool, // True if `blob` is the quilt index.
-        current_col: usize,
-        column_size: usize,
-        row_size: usize,
-        symbol_size: usize,
-    ) -> Result<usize, QuiltError> {
-        assert!(column_size % symbol_size == 0);
+        Ok(quilt_index.into())
+    }
 
-        let mut total_bytes_written = 0;
-        if !is_meta_blob {
-            let prefix_bytes = Self::get_header_and_extension_bytes(blob)?;
-            total_bytes_written += prefix_bytes.len();
+    fn get_blobs_by_identifiers(
+        &self,
+        identifiers: &[&str],
+    ) -> Result<Vec<QuiltStoreBlob<'static>>, QuiltError> {
+        self.read_blobs_by_identifiers(identifiers)
+    }
 
-            Self::write_bytes_to_columns(
-                data,
-                &prefix_bytes,
-                current_col,
-                row_size,
-                column_size,
-                symbol_size,
-                0,
-            )?;
+    fn get_blob_by_patch_internal_id(
+        &self,
+        patch_internal_id: &[u8],
+    ) -> Result<QuiltStoreBlob<'static>, QuiltError> {
+        self.read_blob_by_patch_internal_id(patch_internal_id)
+    }
+
+    fn get_blobs_by_tag(
+        &self,
+        target_tag: &str,
+        target_value: &str,
+    ) -> Result<Vec<QuiltStoreBlob<'static>>, QuiltError> {
+        self.read_blobs_by_tag(target_tag, target_value)
+    }
+
+    fn add_slivers(&mut self, slivers: impl IntoIterator<Item = &'a SliverData<Secondary>>)
+    where
+        Secondary: 'a,
+    {
+        self.slivers.add(slivers);
+    }
+}
+
+impl QuiltPatchReader<QuiltVersionV1> for QuiltDecoderV1<'_> {
+    fn known_quilt_index(&self) -> Result<&QuiltIndexV1, QuiltError> {
+        self.quilt_index
+            .as_ref()
+            .ok_or(QuiltError::MissingQuiltIndex)
+    }
+
+    fn check_columns(&self, start_col: usize, end_col: usize) -> Result<(), QuiltError> {
+        self.slivers.check_missing(start_col, end_col)
+    }
+}
+
+// Implementation of QuiltColumnRangeReader for QuiltDecoderV1.
+impl QuiltColumnRangeReader for QuiltDecoderV1<'_> {
+    fn range_read_from_columns(
+        &self,
+        start_col: usize,
+        bytes_to_skip: usize,
+        bytes_to_return: usize,
+    ) -> Result<Vec<u8>, QuiltError> {
+        self.slivers
+            .range_read_from_columns(start_col, bytes_to_skip, bytes_to_return)
+    }
+}
+
+impl<'a> QuiltDecoderV1<'a> {
+    /// Creates a new QuiltDecoderV1 without slivers.
+    pub fn new(slivers: impl IntoIterator<Item = &'a SliverData<Secondary>>) -> Self
+    where
+        Secondary: 'a,
+    {
+        Self {
+            slivers: QuiltSlivers::new(slivers),
+            quilt_index: None,
         }
+    }
 
-        Self::write_bytes_to_columns(
-            data,
-            blob.data(),
-            current_col,
-            row_size,
-            column_size,
-            symbol_size,
-            total_bytes_written,
-        )?;
+    /// Creates a new QuiltDecoderV1 with the given slivers, and a quilt index.
+    pub fn new_with_quilt_index(
+        slivers: impl IntoIterator<Item = &'a SliverData<Secondary>>,
+        quilt_index: QuiltIndexV1,
+    ) -> Self
+    where
+        Secondary: 'a,
+    {
+        Self {
+            slivers: QuiltSlivers::new(slivers),
+            quilt_index: Some(quilt_index),
+        }
+    }
+}
+
+/// Quilt version 2.
+///
+/// The data layout is the same as for [`QuiltVersionV1`], with three differences:
+/// - The quilt index is not limited to [`MAX_NUM_SLIVERS_FOR_QUILT_INDEX`] columns, but can span
+///   as many columns as needed, which allows quilts with more patches and tags.
+/// - Each patch stores the hash of its data, both in the [`BlobHeaderV2`] and in the
+///   [`QuiltPatchV2`], such that a single patch can be verified without the rest of the quilt.
+/// - The data of each patch can be compressed, as recorded in the [`BlobHeaderV2`] together with
+///   the uncompressed length. The hash is computed over the stored, i.e., compressed, data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuiltVersionV2;
+
+impl QuiltVersionV2 {
+    const QUILT_VERSION_BYTE: u8 = 0x02;
+    const BLOB_HEADER_SIZE: usize =
+        QuiltVersionV1::BLOB_HEADER_SIZE + DIGEST_LEN + BlobHeaderV2::UNCOMPRESSED_LENGTH_SIZE;
 
-        total_bytes_written += blob.data().len();
+    /// Decodes the quilt index from a column data source.
+    pub fn decode_quilt_index<T>(
+        data_source: &T,
+        column_size: usize,
+    ) -> Result<QuiltIndexV2, QuiltError>
+    where
+        T: QuiltColumnRangeReader,
+    {
+        utils::decode_quilt_index::<Self, _>(data_source, column_size)
+    }
 
-        Ok(total_bytes_written.div_ceil(column_size))
+    /// Returns the total size of the serialized blob.
+    pub fn serialized_blob_size(blob: &QuiltStoreBlob) -> Result<usize, QuiltError> {
+        utils::serialized_blob_size::<Self>(blob)
     }
 
-    fn write_bytes_to_columns(
-        data: &mut [u8],
-        bytes: &[u8],
+    /// Decodes a blob from a column data source and verifies its data against its hash.
+    ///
+    /// If the quilt patch of the blob is provided, e.g., from the quilt index, the hash stored in
+    /// the blob header must match the hash of the patch. The blob data is returned as stored,
+    /// i.e., compressed if the blob was compressed; see [`QuiltStoreBlob::decompress`].
+    pub fn decode_blob<T>(
+        data_source: &T,
         start_col: usize,
+        quilt_patch: Option<&QuiltPatchV2>,
+    ) -> Result<QuiltStoreBlob<'static>, QuiltError>
+    where
+        T: QuiltColumnRangeReader,
+    {
+        utils::decode_blob::<Self, _>(data_source, start_col, quilt_patch)
+    }
+}
+
+impl QuiltVersion for QuiltVersionV2 {
+    type QuiltConfig = QuiltConfigV2;
+    type QuiltEncoder<'a> = QuiltEncoderV2<'a>;
+    type QuiltDecoder<'a> = QuiltDecoderV2<'a>;
+    type Quilt = QuiltV2;
+    type QuiltIndex = QuiltIndexV2;
+    type QuiltPatch = QuiltPatchV2;
+    type QuiltPatchInternalId = QuiltPatchInternalIdV2;
+    type QuiltMetadata = QuiltMetadataV2;
+    type SliverAxis = Secondary;
+
+    fn quilt_version_byte() -> u8 {
+        QuiltVersionV2::QUILT_VERSION_BYTE
+    }
+}
+
+impl QuiltLayout for QuiltVersionV2 {
+    type BlobHeader = BlobHeaderV2;
+
+    fn max_columns_for_quilt_index(n_columns: usize) -> usize {
+        // The index may use any number of columns not needed by the blobs.
+        n_columns
+    }
+
+    fn new_quilt_patch(blob: &QuiltStoreBlob) -> Result<QuiltPatchV2, QuiltError> {
+        QuiltPatchV2::new_with_tags(blob.identifier.clone(), blob.tags.clone(), blob.data())
+    }
+
+    fn new_blob_header(
+        blob: &QuiltStoreBlob,
+        quilt_patch: &QuiltPatchV2,
+        length: u32,
+    ) -> BlobHeaderV2 {
+        let mask = if blob.tags.is_empty() {
+            0
+        } else {
+            BlobHeaderV2::TAGS_ENABLED
+        };
+        let mut header =
+            BlobHeaderV2::new(length, mask, quilt_patch.hash, blob.uncompressed_length);
+        header.set_compression(blob.compression);
+        header
+    }
+
+    fn new_decoded_blob(
+        header: BlobHeaderV2,
+        identifier: String,
+        tags: BTreeMap<String, String>,
+        data: Vec<u8>,
+        quilt_patch: Option<&QuiltPatchV2>,
+    ) -> Result<QuiltStoreBlob<'static>, QuiltError> {
+        if quilt_patch.is_some_and(|quilt_patch| quilt_patch.hash != header.hash)
+            || QuiltPatchV2::compute_hash(&data) != header.hash
+        {
+            return Err(QuiltError::PatchHashMismatch(identifier));
+        }
+
+        let mut blob = QuiltStoreBlob::new_owned(data, identifier)?.with_tags(tags);
+        blob.compression = header.compression()?;
+        if blob.compression.is_compressed() {
+            blob.uncompressed_length = header.uncompressed_length;
+        }
+        Ok(blob)
+    }
+
+    fn new_quilt_index(quilt_patches: Vec<QuiltPatchV2>) -> QuiltIndexV2 {
+        QuiltIndexV2 { quilt_patches }
+    }
+
+    fn quilt_patches_mut(quilt_index: &mut QuiltIndexV2) -> &mut [QuiltPatchV2] {
+        &mut quilt_index.quilt_patches
+    }
+
+    fn populate_start_indices(quilt_index: &mut QuiltIndexV2, first_start: u16) {
+        quilt_index.populate_start_indices(first_start);
+    }
+
+    fn set_patch_range(quilt_patch: &mut QuiltPatchV2, start_index: u16, end_index: u16) {
+        quilt_patch.set_range(start_index, end_index);
+    }
+
+    fn internal_id_range(patch_internal_id: &QuiltPatchInternalIdV2) -> (u16, u16) {
+        (patch_internal_id.start_index, patch_internal_id.end_index)
+    }
+
+    fn new_quilt(
+        data: Vec<u8>,
         row_size: usize,
-        column_size: usize,
         symbol_size: usize,
-        bytes_to_skip: usize,
-    ) -> Result<(), QuiltError> {
-        let n_rows = column_size / symbol_size;
-        let n_cols = row_size / symbol_size;
+        quilt_index: QuiltIndexV2,
+    ) -> QuiltV2 {
+        QuiltV2 {
+            data,
+            row_size,
+            symbol_size,
+            quilt_index: Some(quilt_index),
+        }
+    }
+}
 
-        let mut current_col = start_col + bytes_to_skip / column_size;
-        assert!(current_col < n_cols);
-        let mut current_row = (bytes_to_skip / symbol_size) % n_rows;
-        let mut offset = bytes_to_skip % symbol_size;
-        assert!(offset < symbol_size);
-        let mut idx = 0;
+/// The header of a encoded blob in QuiltVersionV2.
+///
+/// The layout is: version (1 byte), length (4 bytes), mask (1 byte), hash of the blob data
+/// (32 bytes), length of the uncompressed blob data (8 bytes).
+///
+/// The lowest bit of the mask indicates whether the blob has tags, the next two bits store the
+/// [`QuiltPatchCompression`] of the blob data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BlobHeaderV2 {
+    /// The length of the serialized blob.
+    pub length: u32,
+    /// The mask of the blob.
+    pub mask: u8,
+    /// The hash of the blob data.
+    pub hash: [u8; DIGEST_LEN],
+    /// The length of the blob data after decompression.
+    ///
+    /// Decompression fails if the blob data does not decompress to exactly this length.
+    pub uncompressed_length: u64,
+}
 
-        while idx < bytes.len() {
-            let base_idx = current_row * row_size + current_col * symbol_size;
-            let start_idx = base_idx + offset;
-            let len = (symbol_size - offset).min(bytes.len() - idx);
+impl BlobHeaderV2 {
+    /// The mask bit that indicates whether the blob has attributes.
+    const TAGS_ENABLED: u8 = 1;
+    /// The position of the mask bits that store the [`QuiltPatchCompression`] of the blob data.
+    const COMPRESSION_SHIFT: u8 = 1;
+    /// The mask bits that store the [`QuiltPatchCompression`] of the blob data.
+    const COMPRESSION_MASK: u8 = 0b11 << Self::COMPRESSION_SHIFT;
+    /// The size in bytes of the length of the uncompressed blob data.
+    const UNCOMPRESSED_LENGTH_SIZE: usize = size_of::<u64>();
+    /// The offset of the length of the uncompressed blob data.
+    const UNCOMPRESSED_LENGTH_OFFSET: usize = QuiltVersionV1::BLOB_HEADER_SIZE + DIGEST_LEN;
+
+    /// Creates a new blob header with the given length, mask, hash, and uncompressed length.
+    pub fn new(length: u32, mask: u8, hash: [u8; DIGEST_LEN], uncompressed_length: u64) -> Self {
+        Self {
+            length,
+            mask,
+            hash,
+            uncompressed_length,
+        }
+    }
 
-            data[start_idx..start_idx + len].copy_from_slice(&bytes[idx..idx + len]);
-            idx += len;
-            current_row = (current_row + 1) % n_rows;
-            if current_row == 0 {
-                current_col += 1;
-            }
+    /// Creates a `BlobHeaderV2` from its serialized bytes.
+    pub fn from_bytes(bytes: [u8; QuiltVersionV2::BLOB_HEADER_SIZE]) -> Result<Self, QuiltError> {
+        utils::check_quilt_version::<QuiltVersionV2>(&bytes)?;
+
+        let length = u32::from_le_bytes([bytes[1], bytes[2], bytes[3], bytes[4]]);
+        let mask = bytes[5];
+        let hash = bytes[QuiltVersionV1::BLOB_HEADER_SIZE..Self::UNCOMPRESSED_LENGTH_OFFSET]
+            .try_into()
+            .expect("the slice has the length of the hash");
+        let uncompressed_length = u64::from_le_bytes(
+            bytes[Self::UNCOMPRESSED_LENGTH_OFFSET..]
+                .try_into()
+                .expect("the remaining bytes are the uncompressed length"),
+        );
+
+        Ok(Self {
+            length,
+            mask,
+            hash,
+            uncompressed_length,
+        })
+    }
+
+    /// Converts the `BlobHeaderV2` to its serialized bytes.
+    pub fn as_bytes(&self) -> [u8; QuiltVersionV2::BLOB_HEADER_SIZE] {
+        let mut data = [0u8; QuiltVersionV2::BLOB_HEADER_SIZE];
+
+        data[0] = QuiltVersionV2::QUILT_VERSION_BYTE;
+        data[1..5].copy_from_slice(&self.length.to_le_bytes());
+        data[5] = self.mask;
+        data[QuiltVersionV1::BLOB_HEADER_SIZE..Self::UNCOMPRESSED_LENGTH_OFFSET]
+            .copy_from_slice(&self.hash);
+        data[Self::UNCOMPRESSED_LENGTH_OFFSET..]
+            .copy_from_slice(&self.uncompressed_length.to_le_bytes());
+
+        data
+    }
+
+    /// Returns true if the blob has tags.
+    pub fn has_tags(&self) -> bool {
+        self.mask & Self::TAGS_ENABLED != 0
+    }
+
+    /// Returns the compression of the blob data.
+    pub fn compression(&self) -> Result<QuiltPatchCompression, QuiltError> {
+        QuiltPatchCompression::from_code(
+            (self.mask & Self::COMPRESSION_MASK) >> Self::COMPRESSION_SHIFT,
+        )
+    }
+
+    /// Sets the compression of the blob data.
+    pub fn set_compression(&mut self, compression: QuiltPatchCompression) {
+        self.mask = (self.mask & !Self::COMPRESSION_MASK)
+            | (compression.to_code() << Self::COMPRESSION_SHIFT);
+    }
+}
+
+impl BlobHeaderApi for BlobHeaderV2 {
+    const SIZE: usize = QuiltVersionV2::BLOB_HEADER_SIZE;
+
+    fn parse(bytes: Vec<u8>) -> Result<Self, QuiltError> {
+        Self::from_bytes(
+            bytes
+                .try_into()
+                .expect("header_bytes should be BLOB_HEADER_SIZE bytes"),
+        )
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        self.as_bytes().to_vec()
+    }
+
+    fn length(&self) -> u32 {
+        self.length
+    }
+
+    fn has_tags(&self) -> bool {
+        BlobHeaderV2::has_tags(self)
+    }
+}
+
+/// A quilt of version 2.
+///
+/// The layout is the same as for [`QuiltV1`], with the index given by a [`QuiltIndexV2`].
+//
+// INV:
+//  - `data.len()` is an integer multiple of `row_size`.
+//  - `row_size` is an integer multiple of `symbol_size`.
+//  - Blobs are stored in the order of their identifiers in the quilt.
+#[derive(Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct QuiltV2 {
+    /// The data of the quilt.
+    data: Vec<u8>,
+    /// The size of each row in bytes.
+    row_size: usize,
+    /// The size of each symbol in bytes.
+    symbol_size: usize,
+    /// The internal structure of the quilt.
+    quilt_index: Option<QuiltIndexV2>,
+}
+
+impl QuiltApi<QuiltVersionV2> for QuiltV2 {
+    fn new_from_quilt_blob(
+        quilt_blob: Vec<u8>,
+        encoding_config: &EncodingConfigEnum<'_>,
+    ) -> Result<QuiltV2, QuiltError> {
+        let (row_size, symbol_size) =
+            utils::quilt_matrix_dimensions(quilt_blob.len(), encoding_config)?;
+        let columns_size = quilt_blob.len() / row_size * symbol_size;
+        let mut quilt = QuiltV2 {
+            data: quilt_blob,
+            row_size,
+            symbol_size,
+            quilt_index: None,
+        };
+        quilt.quilt_index = Some(QuiltVersionV2::decode_quilt_index(&quilt, columns_size)?);
+
+        Ok(quilt)
+    }
+
+    fn get_blobs_by_identifiers(
+        &self,
+        identifiers: &[&str],
+    ) -> Result<Vec<QuiltStoreBlob<'static>>, QuiltError> {
+        self.read_blobs_by_identifiers(identifiers)
+    }
+
+    fn get_blob_by_patch_internal_id(
+        &self,
+        patch_internal_id: &[u8],
+    ) -> Result<QuiltStoreBlob<'static>, QuiltError> {
+        self.read_blob_by_patch_internal_id(patch_internal_id)
+    }
+
+    fn get_blobs_by_tag(
+        &self,
+        target_tag: &str,
+        target_value: &str,
+    ) -> Result<Vec<QuiltStoreBlob<'static>>, QuiltError> {
+        self.read_blobs_by_tag(target_tag, target_value)
+    }
+
+    fn get_all_blobs(&self) -> Result<Vec<QuiltStoreBlob<'static>>, QuiltError> {
+        self.read_all_blobs()
+    }
+
+    fn quilt_index(&self) -> Result<&QuiltIndexV2, QuiltError> {
+        self.quilt_index
+            .as_ref()
+            .ok_or(QuiltError::MissingQuiltIndex)
+    }
+
+    fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    fn symbol_size(&self) -> usize {
+        self.symbol_size
+    }
+}
+
+impl QuiltPatchReader<QuiltVersionV2> for QuiltV2 {
+    fn known_quilt_index(&self) -> Result<&QuiltIndexV2, QuiltError> {
+        self.quilt_index()
+    }
+}
+
+impl QuiltColumnRangeReader for QuiltV2 {
+    fn range_read_from_columns(
+        &self,
+        start_col: usize,
+        bytes_to_skip: usize,
+        bytes_to_return: usize,
+    ) -> Result<Vec<u8>, QuiltError> {
+        utils::range_read_from_matrix(
+            &self.data,
+            self.row_size,
+            self.symbol_size,
+            start_col,
+            bytes_to_skip,
+            bytes_to_return,
+        )
+    }
+}
+
+impl fmt::Debug for QuiltV2 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_quilt::<QuiltVersionV2>(
+            f,
+            "QuiltV2",
+            &self.data,
+            self.row_size,
+            self.symbol_size,
+            self.quilt_index.as_ref(),
+        )
+    }
+}
+
+/// Configuration for the quilt version 2.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug)]
+pub struct QuiltConfigV2;
+
+impl<'a> QuiltConfigApi<'a, QuiltVersionV2> for QuiltConfigV2 {
+    fn get_encoder(
+        encoding_config: EncodingConfigEnum<'a>,
+        blobs: &'a [QuiltStoreBlob<'a>],
+    ) -> QuiltEncoderV2<'a> {
+        QuiltEncoderV2::new(encoding_config, blobs)
+    }
+
+    fn get_decoder(
+        slivers: impl IntoIterator<Item = &'a SliverData<Secondary>>,
+    ) -> QuiltDecoderV2<'a>
+    where
+        Secondary: 'a,
+    {
+        QuiltDecoderV2::new(slivers)
+    }
+
+    fn get_decoder_with_quilt_index(
+        slivers: impl IntoIterator<Item = &'a SliverData<Secondary>>,
+        quilt_index: &QuiltIndex,
+    ) -> QuiltDecoderV2<'a>
+    where
+        Secondary: 'a,
+    {
+        match quilt_index {
+            QuiltIndex::V2(quilt_index) => {
+                QuiltDecoderV2::new_with_quilt_index(slivers, quilt_index.clone())
+            }
+            // An index of a different version cannot describe this quilt; the decoder decodes the
+            // index from the slivers instead, which reports the version mismatch.
+            QuiltIndex::V1(_) => QuiltDecoderV2::new(slivers),
+        }
+    }
+}
+
+/// EncoderV2.
+#[derive(Debug)]
+pub struct QuiltEncoderV2<'a> {
+    /// The blobs to encode.
+    blobs: &'a [QuiltStoreBlob<'a>],
+    /// The encoding configuration.
+    config: EncodingConfigEnum<'a>,
+    /// A tracing span associated with this quilt encoder.
+    span: Span,
+}
+
+impl<'a> QuiltEncoderV2<'a> {
+    /// Creates a new [`QuiltEncoderV2`] from a encoding config and a set of blobs.
+    pub fn new(config: EncodingConfigEnum<'a>, blobs: &'a [QuiltStoreBlob<'a>]) -> Self {
+        Self {
+            blobs,
+            config,
+            span: tracing::span!(Level::ERROR, "QuiltEncoderV2"),
+        }
+    }
+}
+
+impl QuiltEncoderApi<QuiltVersionV2> for QuiltEncoderV2<'_> {
+    /// Constructs a [`QuiltV2`].
+    fn construct_quilt(&self) -> Result<QuiltV2, QuiltError> {
+        let _guard = self.span.enter();
+        utils::construct_quilt::<QuiltVersionV2>(self.blobs, &self.config)
+    }
+
+    /// Encodes the blobs into a quilt and returns the slivers.
+    fn encode(&self) -> Result<Vec<SliverPair>, QuiltError> {
+        let _guard = self.span.enter();
+        tracing::debug!("starting to encode quilt");
+
+        utils::encode_quilt::<QuiltVersionV2>(&self.construct_quilt()?, &self.config)
+    }
+
+    /// Encodes the blobs into a quilt and returns the slivers and metadata.
+    fn encode_with_metadata(&self) -> Result<(Vec<SliverPair>, QuiltMetadata), QuiltError> {
+        let _guard = self.span.enter();
+        tracing::debug!("starting to encode quilt with metadata");
+
+        utils::encode_quilt_with_metadata::<QuiltVersionV2>(&self.construct_quilt()?, &self.config)
+    }
+}
+
+/// A quilt decoder of version V2.
+#[derive(Debug)]
+pub struct QuiltDecoderV2<'a> {
+    slivers: QuiltSlivers<'a>,
+    quilt_index: Option<QuiltIndexV2>,
+}
+
+impl<'a> QuiltDecoderApi<'a, QuiltVersionV2> for QuiltDecoderV2<'a> {
+    fn get_or_decode_quilt_index(&mut self) -> Result<QuiltIndex, QuiltError> {
+        if let Some(quilt_index) = self.quilt_index.as_ref() {
+            return Ok(quilt_index.clone().into());
+        }
+
+        let quilt_index = self.slivers.decode_quilt_index::<QuiltVersionV2>()?;
+        self.quilt_index = Some(quilt_index.clone());
+
+        Ok(quilt_index.into())
+    }
+
+    fn get_blobs_by_identifiers(
+        &self,
+        identifiers: &[&str],
+    ) -> Result<Vec<QuiltStoreBlob<'static>>, QuiltError> {
+        self.read_blobs_by_identifiers(identifiers)
+    }
+
+    fn get_blob_by_patch_internal_id(
+        &self,
+        patch_internal_id: &[u8],
+    ) -> Result<QuiltStoreBlob<'static>, QuiltError> {
+        self.read_blob_by_patch_internal_id(patch_internal_id)
+    }
+
+    fn get_blobs_by_tag(
+        &self,
+        target_tag: &str,
+        target_value: &str,
+    ) -> Result<Vec<QuiltStoreBlob<'static>>, QuiltError> {
+        self.read_blobs_by_tag(target_tag, target_value)
+    }
+
+    fn add_slivers(&mut self, slivers: impl IntoIterator<Item = &'a SliverData<Secondary>>)
+    where
+        Secondary: 'a,
+    {
+        self.slivers.add(slivers);
+    }
+}
+
+impl QuiltPatchReader<QuiltVersionV2> for QuiltDecoderV2<'_> {
+    fn known_quilt_index(&self) -> Result<&QuiltIndexV2, QuiltError> {
+        self.quilt_index
+            .as_ref()
+            .ok_or(QuiltError::MissingQuiltIndex)
+    }
+
+    fn check_columns(&self, start_col: usize, end_col: usize) -> Result<(), QuiltError> {
+        self.slivers.check_missing(start_col, end_col)
+    }
+}
+
+impl QuiltColumnRangeReader for QuiltDecoderV2<'_> {
+    fn range_read_from_columns(
+        &self,
+        start_col: usize,
+        bytes_to_skip: usize,
+        bytes_to_return: usize,
+    ) -> Result<Vec<u8>, QuiltError> {
+        self.slivers
+            .range_read_from_columns(start_col, bytes_to_skip, bytes_to_return)
+    }
+}
+
+impl<'a> QuiltDecoderV2<'a> {
+    /// Creates a new QuiltDecoderV2 with the given slivers.
+    pub fn new(slivers: impl IntoIterator<Item = &'a SliverData<Secondary>>) -> Self
+    where
+        Secondary: 'a,
+    {
+        Self {
+            slivers: QuiltSlivers::new(slivers),
+            quilt_index: None,
+        }
+    }
+
+    /// Creates a new QuiltDecoderV2 with the given slivers, and a quilt index.
+    pub fn new_with_quilt_index(
+        slivers: impl IntoIterator<Item = &'a SliverData<Secondary>>,
+        quilt_index: QuiltIndexV2,
+    ) -> Self
+    where
+        Secondary: 'a,
+    {
+        Self {
+            slivers: QuiltSlivers::new(slivers),
+            quilt_index: Some(quilt_index),
+        }
+    }
+}
+
+mod utils {
+    use super::*;
+
+    /// Finds the minimum symbol size needed to store blobs in a fixed number of columns.
+    /// Each blob must be stored in consecutive columns exclusively.
+    ///
+    /// A binary search is used to find the minimum symbol size:
+    /// 1. Compute the upper and lower bounds for the symbol size.
+    /// 2. Check if the all the blobs can be fit into the quilt with the current symbol size.
+    /// 3. Adjust the bounds based on the result and repeat until the symbol size is found.
+    ///
+    /// # Arguments
+    /// * `blobs_sizes` - Slice of blob lengths, including the index size as the first element.
+    ///   Note that the len of the blob_size should be between 1 and n_columns.
+    /// * `n_columns` - Number of columns available.
+    /// * `n_rows` - Number of rows available.
+    /// * `max_num_columns_for_quilt_index` - The maximum number of columns that can be used to
+    ///   store the quilt index.
+    /// * `required_alignment` - The alignment of the symbol size.
+    ///
+    /// # Returns
+    /// * `Result<usize, QuiltError>` - The minimum symbol size needed, or an error if impossible.
+    pub fn compute_symbol_size(
+        blobs_sizes: &[usize],
+        n_columns: usize,
+        n_rows: usize,
+        max_num_columns_for_quilt_index: usize,
+        encoding_type: EncodingType,
+    ) -> Result<usize, QuiltError> {
+        if blobs_sizes.len() > n_columns {
+            // The first column is not user data.
+            return Err(QuiltError::TooManyBlobs(
+                blobs_sizes.len() - 1,
+                n_columns - 1,
+            ));
+        }
+
+        if blobs_sizes.is_empty() {
+            return Err(QuiltError::EmptyInput("blobs".to_string()));
+        }
+
+        let mut min_val = cmp::max(
+            blobs_sizes
+                .iter()
+                .sum::<usize>()
+                .div_ceil(n_columns * n_rows),
+            blobs_sizes
+                .first()
+                .expect("blobs_sizes is not empty")
+                .div_ceil(n_rows * max_num_columns_for_quilt_index),
+        );
+        min_val = cmp::max(min_val, QUILT_INDEX_PREFIX_SIZE.div_ceil(n_rows));
+        let mut max_val = blobs_sizes
+            .iter()
+            .max()
+            .copied()
+            .expect("blobs_sizes is not empty")
+            .div_ceil(n_columns / blobs_sizes.len() * n_rows);
+
+        while min_val < max_val {
+            let mid = (min_val + max_val) / 2;
+            if can_blobs_fit_into_matrix(blobs_sizes, n_columns, mid * n_rows) {
+                max_val = mid;
+            } else {
+                min_val = mid + 1;
+            }
+        }
+
+        let symbol_size = min_val.next_multiple_of(encoding_type.required_alignment().into());
+        debug_assert!(can_blobs_fit_into_matrix(
+            blobs_sizes,
+            n_columns,
+            symbol_size * n_rows
+        ));
+        let max_symbol_size = usize::from(encoding_type.max_symbol_size());
+        if symbol_size > max_symbol_size {
+            return Err(QuiltError::QuiltOversize(format!(
+                "the resulting symbol size {symbol_size} is larger than the maximum symbol size \
+                {max_symbol_size}; remove some blobs"
+            )));
+        }
+
+        Ok(symbol_size)
+    }
+
+    /// Checks if the blobs can fit in the given number of columns.
+    ///
+    /// # Arguments
+    /// * `blobs_sizes` - The sizes of the blobs.
+    /// * `n_columns` - The number of columns available.
+    /// * `length` - The size of the column.
+    ///
+    /// # Returns
+    /// * `bool` - True if the blobs can fit in the given number of columns, false otherwise.
+    fn can_blobs_fit_into_matrix(
+        blobs_sizes: &[usize],
+        n_columns: usize,
+        column_size: usize,
+    ) -> bool {
+        let required_columns = blobs_sizes
+            .iter()
+            .map(|blob_size| blob_size.div_ceil(column_size))
+            .sum::<usize>();
+        n_columns >= required_columns
+    }
+
+    /// Reads the serialized quilt index from a column data source.
+    ///
+    /// Returns the index bytes and the number of columns occupied by the index.
+    pub fn read_quilt_index_bytes<V: QuiltVersion, T: QuiltColumnRangeReader>(
+        data_source: &T,
+        column_size: usize,
+    ) -> Result<(Vec<u8>, u16), QuiltError> {
+        let quilt_version_bytes =
+            data_source.range_read_from_columns(0, 0, QUILT_VERSION_BYTES_LENGTH)?;
+        assert!(quilt_version_bytes.len() == QUILT_VERSION_BYTES_LENGTH);
+        check_quilt_version::<V>(&quilt_version_bytes)?;
+
+        let size_bytes = data_source.range_read_from_columns(
+            0,
+            QUILT_VERSION_BYTES_LENGTH,
+            QUILT_INDEX_SIZE_BYTES_LENGTH,
+        )?;
+        assert!(size_bytes.len() == QUILT_INDEX_SIZE_BYTES_LENGTH);
+
+        let index_size = usize::try_from(u32::from_le_bytes(
+            size_bytes
+                .try_into()
+                .map_err(|_| QuiltError::FailedToExtractQuiltIndexSize)?,
+        ))
+        .map_err(|_| QuiltError::FailedToExtractQuiltIndexSize)?;
+
+        let index_bytes =
+            data_source.range_read_from_columns(0, QUILT_INDEX_PREFIX_SIZE, index_size)?;
+        assert!(index_bytes.len() == index_size);
+
+        let total_size = index_size + QUILT_INDEX_PREFIX_SIZE;
+        let columns_needed = u16::try_from(total_size.div_ceil(column_size))
+            .map_err(|_| QuiltError::FailedToExtractQuiltIndexSize)?;
+
+        Ok((index_bytes, columns_needed))
+    }
+
+    /// Decodes the quilt index of version `V` from a column data source.
+    pub fn decode_quilt_index<V: QuiltLayout, T: QuiltColumnRangeReader>(
+        data_source: &T,
+        column_size: usize,
+    ) -> Result<V::QuiltIndex, QuiltError> {
+        let (index_bytes, columns_needed) =
+            read_quilt_index_bytes::<V, _>(data_source, column_size)?;
+
+        let mut quilt_index: V::QuiltIndex = bcs::from_bytes(&index_bytes)?;
+        V::populate_start_indices(&mut quilt_index, columns_needed);
+
+        Ok(quilt_index)
+    }
+
+    /// Returns the total size of the blob serialized in a quilt of version `V`.
+    pub fn serialized_blob_size<V: QuiltLayout>(
+        blob: &QuiltStoreBlob,
+    ) -> Result<usize, QuiltError> {
+        let identifier_size = bcs::serialized_size(&blob.identifier)
+            .map_err(|e| QuiltError::Other(format!("Failed to compute identifier size: {e}")))?;
+
+        if identifier_size >= MAX_BLOB_IDENTIFIER_BYTES_LENGTH {
+            return Err(QuiltError::InvalidIdentifier(format!(
+                "identifier size exceeds maximum allowed value: {MAX_BLOB_IDENTIFIER_BYTES_LENGTH}"
+            )));
+        }
+        let mut prefix_size = identifier_size as usize + BLOB_IDENTIFIER_SIZE_BYTES_LENGTH;
+
+        if !blob.tags.is_empty() {
+            let tags_size = bcs::serialized_size(&blob.tags)
+                .map_err(|e| QuiltError::Other(format!("Failed to compute tags size: {e}")))?;
+            prefix_size += tags_size + TAGS_SIZE_BYTES_LENGTH;
+        }
+
+        Ok(prefix_size + blob.data().len() + V::BlobHeader::SIZE)
+    }
+
+    /// Returns the bytes stored in front of the blob data in a quilt of version `V`, i.e., the
+    /// blob header followed by the identifier and feature sections.
+    pub fn blob_prefix_bytes<V: QuiltLayout>(
+        blob: &QuiltStoreBlob,
+        quilt_patch: &V::QuiltPatch,
+    ) -> Result<Vec<u8>, QuiltError> {
+        let mut identifier_bytes = Vec::new();
+        let mut extension_bytes = Vec::new();
+
+        let identifier_size =
+            u16::try_from(bcs::serialized_size(&blob.identifier).map_err(|e| {
+                QuiltError::InvalidIdentifier(format!("Failed to serialize identifier: {e}"))
+            })?)
+            .map_err(|e| {
+                QuiltError::InvalidIdentifier(format!(
+                    "Failed to convert identifier size to u16: {e}"
+                ))
+            })?;
+        identifier_bytes.extend_from_slice(&identifier_size.to_le_bytes());
+        identifier_bytes.extend_from_slice(&bcs::to_bytes(&blob.identifier).map_err(|e| {
+            QuiltError::InvalidIdentifier(format!("Failed to serialize identifier: {e}"))
+        })?);
+        extension_bytes.push(identifier_bytes);
+
+        if !blob.tags.is_empty() {
+            let serialized_tags = bcs::to_bytes(&blob.tags)
+                .map_err(|e| QuiltError::Other(format!("Failed to serialize tags: {e}")))?;
+
+            // This must be the same as TAGS_SIZE_BYTES_LENGTH.
+            let tags_size = u16::try_from(serialized_tags.len()).map_err(|e| {
+                QuiltError::Other(format!("Failed to convert tags size to u16: {e}"))
+            })?;
+
+            let mut result_bytes = Vec::with_capacity(tags_size as usize + serialized_tags.len());
+            result_bytes.extend_from_slice(&tags_size.to_le_bytes());
+            result_bytes.extend_from_slice(&serialized_tags);
+            extension_bytes.push(result_bytes);
+        }
+
+        let total_size = extension_bytes.iter().map(|b| b.len()).sum::<usize>() + blob.data().len();
+        let header = V::new_blob_header(blob, quilt_patch, total_size as u32);
+        let header_bytes = header.to_bytes();
+        debug_assert_eq!(header_bytes.len(), V::BlobHeader::SIZE);
+
+        let mut result_bytes = Vec::with_capacity(header_bytes.len() + total_size);
+        result_bytes.extend_from_slice(&header_bytes);
+        for mut inner_extension_vec in extension_bytes {
+            result_bytes.append(&mut inner_extension_vec);
+        }
+
+        Ok(result_bytes)
+    }
+
+    /// Decodes a blob of a quilt of version `V` from a column data source.
+    ///
+    /// If the quilt patch of the blob is given, the blob is checked against it.
+    pub fn decode_blob<V: QuiltLayout, T: QuiltColumnRangeReader>(
+        data_source: &T,
+        start_col: usize,
+        quilt_patch: Option<&V::QuiltPatch>,
+    ) -> Result<QuiltStoreBlob<'static>, QuiltError> {
+        let header_bytes =
+            data_source.range_read_from_columns(start_col, 0, V::BlobHeader::SIZE)?;
+        assert!(header_bytes.len() == V::BlobHeader::SIZE);
+        let blob_header = V::BlobHeader::parse(header_bytes)?;
+
+        let mut offset = V::BlobHeader::SIZE;
+        let mut blob_bytes_size =
+            usize::try_from(blob_header.length()).expect("length should fit in usize");
+
+        let (identifier, bytes_consumed) = decode_blob_identifier(data_source, start_col, offset)?;
+        offset += bytes_consumed;
+        blob_bytes_size = blob_bytes_size
+            .checked_sub(bytes_consumed)
+            .ok_or_else(|| QuiltError::Other("invalid blob length in header".to_string()))?;
+
+        let tags = if blob_header.has_tags() {
+            let (tags, bytes_consumed) = decode_blob_tags(data_source, start_col, offset)?;
+            offset += bytes_consumed;
+            blob_bytes_size = blob_bytes_size
+                .checked_sub(bytes_consumed)
+                .ok_or_else(|| QuiltError::Other("invalid blob length in header".to_string()))?;
+            tags
+        } else {
+            BTreeMap::new()
+        };
+
+        let data_bytes = data_source.range_read_from_columns(start_col, offset, blob_bytes_size)?;
+        assert!(data_bytes.len() == blob_bytes_size);
+
+        V::new_decoded_blob(blob_header, identifier, tags, data_bytes, quilt_patch)
+    }
+
+    /// Decodes the blob identifier from a column data source.
+    /// Returns a tuple containing the decoded identifier string and the total number
+    /// of bytes consumed.
+    fn decode_blob_identifier<T>(
+        data_source: &T,
+        start_col: usize,
+        initial_offset: usize,
+    ) -> Result<(String, usize), QuiltError>
+    where
+        T: QuiltColumnRangeReader,
+    {
+        let mut offset = initial_offset;
+
+        // Read identifier size (2 bytes).
+        let size_buffer = data_source.range_read_from_columns(
+            start_col,
+            offset,
+            BLOB_IDENTIFIER_SIZE_BYTES_LENGTH,
+        )?;
+        offset += BLOB_IDENTIFIER_SIZE_BYTES_LENGTH;
+
+        // Parse identifier size.
+        let identifier_size = usize::from(u16::from_le_bytes(
+            size_buffer
+                .try_into()
+                .expect("size_buffer should be 2 bytes"),
+        ));
+
+        // Read the actual identifier.
+        let identifier_bytes =
+            data_source.range_read_from_columns(start_col, offset, identifier_size)?;
+        debug_assert!(identifier_bytes.len() == identifier_size);
 
-            // Only the first symbol requires offset.
-            offset = 0;
-        }
+        // Deserialize the identifier bytes into a String.
+        let identifier = bcs::from_bytes(&identifier_bytes).map_err(|_| {
+            QuiltError::InvalidIdentifier("Failed to deserialize identifier".into())
+        })?;
 
-        Ok(())
+        // Calculate total bytes consumed.
+        let bytes_consumed = BLOB_IDENTIFIER_SIZE_BYTES_LENGTH + identifier_size;
+
+        Ok((identifier, bytes_consumed))
     }
-}
 
-impl QuiltEncoderApi<QuiltVersionV1> for QuiltEncoderV1<'_> {
-    /// Constructs a [`QuiltV1`].
-    fn construct_quilt(&self) -> Result<QuiltV1, QuiltError> {
-        let _guard = self.span.enter();
+    /// Decodes the blob tags from a column data source.
+    fn decode_blob_tags<T>(
+        data_source: &T,
+        start_col: usize,
+        initial_offset: usize,
+    ) -> Result<(BTreeMap<String, String>, usize), QuiltError>
+    where
+        T: QuiltColumnRangeReader,
+    {
+        let size_bytes = data_source.range_read_from_columns(
+            start_col,
+            initial_offset,
+            TAGS_SIZE_BYTES_LENGTH,
+        )?;
+        assert!(size_bytes.len() == TAGS_SIZE_BYTES_LENGTH);
+        let tags_size =
+            u16::from_le_bytes(size_bytes.try_into().expect("size_bytes should be 2 bytes"));
+        let mut offset = initial_offset + TAGS_SIZE_BYTES_LENGTH;
+        let tags_bytes =
+            data_source.range_read_from_columns(start_col, offset, tags_size as usize)?;
+        let tags = bcs::from_bytes(&tags_bytes)
+            .map_err(|error| QuiltError::FailedToDecodeExtension("tags".into(), error))?;
+        offset += tags_size as usize;
+        Ok((tags, offset - initial_offset))
+    }
 
-        let n_rows = self.config.n_source_symbols::<Primary>().get().into();
-        let n_columns = self.config.n_source_symbols::<Secondary>().get().into();
+    /// Constructs a quilt of version `V` from the blobs.
+    ///
+    /// Note: This function returns an error if the blobs have duplicate identifiers.
+    pub fn construct_quilt<V: QuiltLayout>(
+        blobs: &[QuiltStoreBlob<'_>],
+        config: &EncodingConfigEnum<'_>,
+    ) -> Result<V::Quilt, QuiltError> {
+        let n_rows = config.n_source_symbols::<Primary>().get().into();
+        let n_columns = config.n_source_symbols::<Secondary>().get().into();
         tracing::debug!(
             "Constructing quilt with n_columns: {}, n_rows: {}",
             n_columns,
             n_rows
         );
 
-        let mut blob_pairs = self.blobs.iter().collect::<Vec<_>>();
+        let mut blob_pairs = blobs.iter().collect::<Vec<_>>();
 
         // Sort blobs by their identifiers.
         blob_pairs.sort_by(|a, b| a.identifier.cmp(&b.identifier));
@@ -1454,14 +2560,14 @@ impl QuiltEncoderApi<QuiltVersionV1> for QuiltEncoderV1<'_> {
         // Create initial QuiltPatches.
         let quilt_patches = blob_pairs
             .iter()
-            .map(|blob| QuiltPatchV1::new_with_tags(blob.identifier.clone(), blob.tags.clone()))
-            .collect::<Result<Vec<QuiltPatchV1>, QuiltError>>()?;
+            .map(|blob| V::new_quilt_patch(blob))
+            .collect::<Result<Vec<_>, QuiltError>>()?;
 
-        let mut quilt_index = QuiltIndexV1 { quilt_patches };
+        let mut quilt_index = V::new_quilt_index(quilt_patches);
 
         // Get the serialized quilt index size.
         let serialized_index_size = u32::try_from(bcs::serialized_size(&quilt_index)?)
-            .expect("serialized_index_size should fit in u32");
+            .map_err(|_| QuiltError::QuiltOversize("the quilt index is too large".to_string()))?;
 
         // Calculate total size including the size prefix and the quilt type.
         let index_total_size = QUILT_INDEX_PREFIX_SIZE
@@ -1473,16 +2579,17 @@ impl QuiltEncoderApi<QuiltVersionV1> for QuiltEncoderV1<'_> {
             .chain(
                 blob_pairs
                     .iter()
-                    .map(|blob| QuiltVersionV1::serialized_blob_size(blob)),
+                    .map(|blob| serialized_blob_size::<V>(blob)),
             )
             .collect::<Result<Vec<usize>, QuiltError>>()?;
 
-        let symbol_size = utils::compute_symbol_size(
+        let max_num_columns_for_quilt_index = V::max_columns_for_quilt_index(n_columns);
+        let symbol_size = compute_symbol_size(
             &all_sizes,
             n_columns,
             n_rows,
-            MAX_NUM_SLIVERS_FOR_QUILT_INDEX,
-            self.config.encoding_type(),
+            max_num_columns_for_quilt_index,
+            config.encoding_type(),
         )?;
 
         let row_size = symbol_size * n_columns;
@@ -1491,22 +2598,26 @@ impl QuiltEncoderApi<QuiltVersionV1> for QuiltEncoderV1<'_> {
         // Calculate columns needed for the index.
         let column_size = symbol_size * n_rows;
         let index_cols_needed = index_total_size.div_ceil(column_size);
-        assert!(index_cols_needed <= MAX_NUM_SLIVERS_FOR_QUILT_INDEX);
+        assert!(index_cols_needed <= max_num_columns_for_quilt_index);
         let mut current_col = index_cols_needed;
 
         // Fill data with actual blobs and populate quilt patches.
-        for (i, quilt_store_blob) in blob_pairs.iter().enumerate() {
-            let cols_needed = Self::add_blob_to_quilt(
+        for (quilt_store_blob, quilt_patch) in blob_pairs
+            .iter()
+            .zip(V::quilt_patches_mut(&mut quilt_index))
+        {
+            let cols_needed = add_blob_to_quilt(
                 &mut data,
-                quilt_store_blob,
-                false,
+                &blob_prefix_bytes::<V>(quilt_store_blob, quilt_patch)?,
+                quilt_store_blob.data(),
                 current_col,
                 column_size,
                 row_size,
                 symbol_size,
             )?;
 
-            quilt_index.quilt_patches[i].set_range(
+            V::set_patch_range(
+                quilt_patch,
                 u16::try_from(current_col).expect("current_col should fit in u16"),
                 u16::try_from(current_col + cols_needed)
                     .expect("current_col + cols_needed should fit in u16"),
@@ -1515,18 +2626,17 @@ impl QuiltEncoderApi<QuiltVersionV1> for QuiltEncoderV1<'_> {
         }
 
         let mut meta_blob_data = Vec::with_capacity(index_total_size);
-        meta_blob_data.push(QuiltVersionV1::quilt_version_byte());
+        meta_blob_data.push(V::quilt_version_byte());
         meta_blob_data.extend_from_slice(&serialized_index_size.to_le_bytes());
         meta_blob_data
             .extend_from_slice(&bcs::to_bytes(&quilt_index).expect("serialization should succeed"));
         assert_eq!(meta_blob_data.len(), index_total_size);
 
-        let meta_blob = QuiltStoreBlob::new(&meta_blob_data, "quilt_index")?;
         // Add the index to the quilt.
-        let index_cols_used = Self::add_blob_to_quilt(
+        let index_cols_used = add_blob_to_quilt(
             &mut data,
-            &meta_blob,
-            true,
+            &[],
+            &meta_blob_data,
             0,
             column_size,
             row_size,
@@ -1535,144 +2645,248 @@ impl QuiltEncoderApi<QuiltVersionV1> for QuiltEncoderV1<'_> {
         debug_assert_eq!(index_cols_used, index_cols_needed);
         tracing::debug!("construct quilt success {}", data.len());
 
-        Ok(QuiltV1 {
+        Ok(V::new_quilt(data, row_size, symbol_size, quilt_index))
+    }
+
+    /// Adds a blob to the quilt as consecutive columns.
+    ///
+    /// Returns the number of columns used to store the blob.
+    ///
+    /// The blob data layout is as follows:
+    ///
+    /// ```text
+    /// +------------------+-----------------------------+----------------------+------------------+
+    /// | Blob Header      | Identifier Section          | Feature Section      | Blob Data        |
+    /// | (fixed length)   | (variable length)           | (optional)           | (variable length)|
+    /// +------------------+-----------------------------+----------------------+------------------+
+    ///                    |                             |                      |
+    ///                    v                             v                      v
+    /// +------------------+----------------+------------+----------------------+------------------+
+    /// | BlobHeaderV*     | Identifier Size| Serialized | Feature Data         | Actual blob      |
+    /// | (version, length,| (2 bytes)      | Identifier | (Feature size +      | data             |
+    /// |  mask flags, ...)| u16            | (variable) | serialized features) | (variable)       |
+    /// +------------------+----------------+------------+----------------------+------------------+
+    /// ```
+    ///
+    /// - BlobHeaderV*: Contains version byte, length, mask feature flags, and any fields added by
+    ///   later versions.
+    /// - Identifier Size: 2-byte length of the serialized identifier.
+    /// - Serialized Identifier: BCS-encoded identifier string.
+    /// - Feature Data: Optional section for feature data, such as attributes,
+    ///   (when mask flag is set).
+    /// - Blob Data: The actual blob contents.
+    ///
+    /// The `prefix_bytes` contain the header and the identifier and feature sections; they are
+    /// empty for the quilt index, which is stored without a prefix.
+    fn add_blob_to_quilt(
+        data: &mut [u8],
+        prefix_bytes: &[u8],
+        blob_data: &[u8],
+        current_col: usize,
+        column_size: usize,
+        row_size: usize,
+        symbol_size: usize,
+    ) -> Result<usize, QuiltError> {
+        assert!(column_size % symbol_size == 0);
+
+        write_bytes_to_columns(
             data,
+            prefix_bytes,
+            current_col,
             row_size,
-            quilt_index: Some(quilt_index),
+            column_size,
             symbol_size,
-        })
+            0,
+        )?;
+        write_bytes_to_columns(
+            data,
+            blob_data,
+            current_col,
+            row_size,
+            column_size,
+            symbol_size,
+            prefix_bytes.len(),
+        )?;
+
+        Ok((prefix_bytes.len() + blob_data.len()).div_ceil(column_size))
     }
 
-    /// Encodes the blobs into a quilt and returns the slivers.
-    fn encode(&self) -> Result<Vec<SliverPair>, QuiltError> {
-        let _guard = self.span.enter();
-        tracing::debug!("starting to encode quilt");
+    fn write_bytes_to_columns(
+        data: &mut [u8],
+        bytes: &[u8],
+        start_col: usize,
+        row_size: usize,
+        column_size: usize,
+        symbol_size: usize,
+        bytes_to_skip: usize,
+    ) -> Result<(), QuiltError> {
+        let n_rows = column_size / symbol_size;
+        let n_cols = row_size / symbol_size;
 
-        let quilt = self.construct_quilt()?;
-        let encoder = BlobEncoder::new(self.config.clone(), quilt.data()).map_err(|_| {
-            QuiltError::QuiltOversize(format!("quilt is too large: {}", quilt.data().len()))
-        })?;
-        assert_eq!(encoder.symbol_usize(), quilt.symbol_size());
-        Ok(encoder.encode())
-    }
+        let mut current_col = start_col + bytes_to_skip / column_size;
+        assert!(current_col < n_cols);
+        let mut current_row = (bytes_to_skip / symbol_size) % n_rows;
+        let mut offset = bytes_to_skip % symbol_size;
+        assert!(offset < symbol_size);
+        let mut idx = 0;
 
-    /// Encodes the blobs into a quilt and returns the slivers and metadata.
-    fn encode_with_metadata(&self) -> Result<(Vec<SliverPair>, QuiltMetadata), QuiltError> {
-        let _guard = self.span.enter();
-        tracing::debug!("starting to encode quilt with metadata");
+        while idx < bytes.len() {
+            let base_idx = current_row * row_size + current_col * symbol_size;
+            let start_idx = base_idx + offset;
+            let len = (symbol_size - offset).min(bytes.len() - idx);
 
-        let quilt = self.construct_quilt()?;
-        let encoder = BlobEncoder::new(self.config.clone(), quilt.data()).map_err(|_| {
-            QuiltError::QuiltOversize(format!("quilt is too large: {}", quilt.data.len()))
-        })?;
+            data[start_idx..start_idx + len].copy_from_slice(&bytes[idx..idx + len]);
+            idx += len;
+            current_row = (current_row + 1) % n_rows;
+            if current_row == 0 {
+                current_col += 1;
+            }
+
+            // Only the first symbol requires offset.
+            offset = 0;
+        }
 
-        assert_eq!(encoder.symbol_usize(), quilt.symbol_size);
+        Ok(())
+    }
 
-        let (sliver_pairs, metadata) = encoder.encode_with_metadata();
-        let quilt_metadata = QuiltMetadata::V1(QuiltMetadataV1 {
-            quilt_id: *metadata.blob_id(),
-            metadata: metadata.metadata().clone(),
-            index: QuiltIndexV1 {
-                quilt_patches: quilt.quilt_index()?.quilt_patches.clone(),
-            },
-        });
+    /// Encodes the quilt and returns the slivers.
+    pub fn encode_quilt<V: QuiltVersion>(
+        quilt: &V::Quilt,
+        config: &EncodingConfigEnum<'_>,
+    ) -> Result<Vec<SliverPair>, QuiltError> {
+        Ok(blob_encoder::<V>(quilt, config)?.encode())
+    }
+
+    /// Encodes the quilt and returns the slivers and the quilt metadata.
+    pub fn encode_quilt_with_metadata<V: QuiltVersion>(
+        quilt: &V::Quilt,
+        config: &EncodingConfigEnum<'_>,
+    ) -> Result<(Vec<SliverPair>, QuiltMetadata), QuiltError> {
+        let (sliver_pairs, metadata) = blob_encoder::<V>(quilt, config)?.encode_with_metadata();
+        let quilt_metadata = QuiltMetadata::new(
+            *metadata.blob_id(),
+            metadata.metadata().clone(),
+            quilt.quilt_index()?.clone().into(),
+        );
 
         Ok((sliver_pairs, quilt_metadata))
     }
-}
 
-/// A quilt decoder of version V1.
-#[derive(Debug)]
-pub struct QuiltDecoderV1<'a> {
-    slivers: HashMap<SliverIndex, &'a SliverData<Secondary>>,
-    quilt_index: Option<QuiltIndexV1>,
-    column_size: Option<usize>,
-}
+    /// Returns the blob encoder for the data of the quilt.
+    fn blob_encoder<'a, V: QuiltVersion>(
+        quilt: &'a V::Quilt,
+        config: &EncodingConfigEnum<'a>,
+    ) -> Result<BlobEncoder<'a>, QuiltError> {
+        let encoder = BlobEncoder::new(config.clone(), quilt.data()).map_err(|_| {
+            QuiltError::QuiltOversize(format!("quilt is too large: {}", quilt.data().len()))
+        })?;
+        assert_eq!(encoder.symbol_usize(), quilt.symbol_size());
+        Ok(encoder)
+    }
 
-impl<'a> QuiltDecoderApi<'a, QuiltVersionV1> for QuiltDecoderV1<'a> {
-    fn get_or_decode_quilt_index(&mut self) -> Result<QuiltIndex, QuiltError> {
-        if let Some(quilt_index) = self.quilt_index.as_ref() {
-            return Ok(quilt_index.clone().into());
-        }
+    /// Returns the row size and the symbol size of a quilt blob of the given length.
+    pub fn quilt_matrix_dimensions(
+        quilt_blob_len: usize,
+        encoding_config: &EncodingConfigEnum<'_>,
+    ) -> Result<(usize, usize), QuiltError> {
+        let n_primary_source_symbols =
+            usize::from(encoding_config.n_source_symbols::<Primary>().get());
+        let n_secondary_source_symbols =
+            usize::from(encoding_config.n_source_symbols::<Secondary>().get());
+        let n_source_symbols: usize = n_primary_source_symbols * n_secondary_source_symbols;
 
-        self.check_missing_slivers(0, 1)?;
-        let column_size = self.column_size.expect("column size should be set");
-        let quilt_index = QuiltVersionV1::decode_quilt_index(self, column_size)?;
-        self.quilt_index = Some(quilt_index.clone());
+        if n_source_symbols == 0 {
+            return Err(QuiltError::Other(
+                "n_source_symbols cannot be zero".to_string(),
+            ));
+        }
+        if quilt_blob_len % n_source_symbols != 0 {
+            return Err(QuiltError::InvalidFormatNotAligned(format!(
+                "quilt_blob length {quilt_blob_len} is not a multiple of n_source_symbols \
+                {n_source_symbols}"
+            )));
+        }
 
-        Ok(quilt_index.into())
+        let symbol_size = quilt_blob_len / n_source_symbols;
+        let row_size = symbol_size * n_secondary_source_symbols;
+        Ok((row_size, symbol_size))
     }
 
-    fn get_blobs_by_identifiers(
-        &self,
-        identifiers: &[&str],
-    ) -> Result<Vec<QuiltStoreBlob<'static>>, QuiltError> {
-        self.quilt_index
-            .as_ref()
-            .ok_or(QuiltError::MissingQuiltIndex)
-            .and_then(|quilt_index| quilt_index.get_quilt_patches_by_identifiers(identifiers))?
-            .iter()
-            .map(|patch| self.get_blob_by_quilt_patch(patch))
-            .collect()
-    }
+    /// Reads bytes from the columns of a quilt stored as a row-major matrix.
+    ///
+    /// Returns `IndexOutOfBounds` if there is not enough data to read.
+    pub fn range_read_from_matrix(
+        data: &[u8],
+        row_size: usize,
+        symbol_size: usize,
+        start_col: usize,
+        mut bytes_to_skip: usize,
+        mut bytes_to_return: usize,
+    ) -> Result<Vec<u8>, QuiltError> {
+        if symbol_size == 0 || row_size == 0 || data.is_empty() {
+            return Err(QuiltError::Other("empty quilt data".to_string()));
+        }
 
-    fn get_blob_by_patch_internal_id(
-        &self,
-        patch_internal_id: &[u8],
-    ) -> Result<QuiltStoreBlob<'static>, QuiltError> {
-        let patch_internal_id = QuiltPatchInternalIdV1::from_bytes(patch_internal_id)?;
-        let start_col = usize::from(patch_internal_id.start_index);
-        let end_col = usize::from(patch_internal_id.end_index);
-        self.check_missing_slivers(start_col, end_col)?;
-        QuiltVersionV1::decode_blob(self, start_col)
-    }
+        // Initialize state
+        let n_rows = data.len() / row_size;
+        let symbols_to_skip = bytes_to_skip / symbol_size;
+        let mut current_col = start_col + symbols_to_skip / n_rows;
+        let mut current_row = symbols_to_skip % n_rows;
+        let mut result = Vec::with_capacity(bytes_to_return);
+        bytes_to_skip -= symbols_to_skip * symbol_size;
 
-    fn get_blobs_by_tag(
-        &self,
-        target_tag: &str,
-        target_value: &str,
-    ) -> Result<Vec<QuiltStoreBlob<'static>>, QuiltError> {
-        self.quilt_index
-            .as_ref()
-            .ok_or(QuiltError::MissingQuiltIndex)
-            .and_then(|quilt_index| {
-                quilt_index
-                    .get_quilt_patches_by_tag(target_tag, target_value)
-                    .iter()
-                    .map(|patch| self.get_blob_by_quilt_patch(patch))
-                    .collect()
-            })
-    }
+        // Helper function to calculate data slice for current position.
+        let get_slice = |col: usize, row: usize, skip: usize, limit: usize| {
+            let base_index = row * row_size + col * symbol_size;
+            let start_index = base_index + skip;
+            let end_index = (base_index + symbol_size)
+                .min(start_index + limit)
+                .min(data.len());
+            if start_index >= data.len() {
+                return Err(QuiltError::IndexOutOfBounds(start_index, data.len()));
+            }
+            Ok(&data[start_index..end_index])
+        };
 
-    fn add_slivers(&mut self, slivers: impl IntoIterator<Item = &'a SliverData<Secondary>>)
-    where
-        Secondary: 'a,
-    {
-        for sliver in slivers {
-            self.column_size
-                .get_or_insert_with(|| sliver.symbols.data().len());
-            self.slivers.insert(sliver.index, sliver);
+        // Helper function to advance to the next position.
+        let advance_position = |col: &mut usize, row: &mut usize| {
+            *row = (*row + 1) % n_rows;
+            if *row == 0 {
+                *col += 1;
+            }
+        };
+
+        // Process cells until we've collected enough bytes or run out of columns.
+        while bytes_to_return > 0 {
+            let slice = get_slice(current_col, current_row, bytes_to_skip, bytes_to_return)?;
+            result.extend_from_slice(slice);
+            bytes_to_return -= slice.len();
+
+            advance_position(&mut current_col, &mut current_row);
+            bytes_to_skip = 0;
         }
+
+        Ok(result)
     }
-}
 
-// Implementation of QuiltColumnRangeReader for QuiltDecoderV1.
-impl QuiltColumnRangeReader for QuiltDecoderV1<'_> {
-    fn range_read_from_columns(
-        &self,
+    /// Reads bytes from the columns of a quilt given as secondary slivers.
+    ///
+    /// Returns `MissingSlivers` if any of the slivers covering the range is missing.
+    pub fn range_read_from_slivers(
+        slivers: &HashMap<SliverIndex, &SliverData<Secondary>>,
+        column_size: Option<usize>,
         start_col: usize,
         mut bytes_to_skip: usize,
         mut bytes_to_return: usize,
     ) -> Result<Vec<u8>, QuiltError> {
-        self.check_missing_slivers(start_col, start_col + 1)?;
-        let column_size = self.column_size.expect("column size should be set");
+        check_missing_slivers(slivers, start_col, start_col + 1)?;
+        let column_size = column_size.expect("column size should be set");
         let end_col = start_col + (bytes_to_skip + bytes_to_return).div_ceil(column_size);
-        self.check_missing_slivers(start_col, end_col)?;
+        check_missing_slivers(slivers, start_col, end_col)?;
 
         let slivers: Vec<&SliverData<Secondary>> = (start_col..end_col)
             .map(|col| {
-                *self
-                    .slivers
+                *slivers
                     .get(&SliverIndex::new(col as u16))
                     .expect("sliver exists")
             })
@@ -1705,63 +2919,17 @@ impl QuiltColumnRangeReader for QuiltDecoderV1<'_> {
 
         Ok(result)
     }
-}
-
-impl<'a> QuiltDecoderV1<'a> {
-    /// Creates a new QuiltDecoderV1 without slivers.
-    pub fn new(slivers: impl IntoIterator<Item = &'a SliverData<Secondary>>) -> Self
-    where
-        Secondary: 'a,
-    {
-        let slivers = slivers
-            .into_iter()
-            .map(|s| (s.index, s))
-            .collect::<HashMap<_, _>>();
-        let column_size = slivers.values().next().map(|s| s.symbols.data().len());
-        Self {
-            slivers,
-            quilt_index: None,
-            column_size,
-        }
-    }
-
-    /// Creates a new QuiltDecoderV1 with the given slivers, and a quilt index.
-    pub fn new_with_quilt_index(
-        slivers: impl IntoIterator<Item = &'a SliverData<Secondary>>,
-        quilt_index: QuiltIndexV1,
-    ) -> Self
-    where
-        Secondary: 'a,
-    {
-        let slivers = slivers
-            .into_iter()
-            .map(|s| (s.index, s))
-            .collect::<HashMap<_, _>>();
-        let column_size = slivers.values().next().map(|s| s.symbols.data().len());
-        Self {
-            slivers,
-            quilt_index: Some(quilt_index),
-            column_size,
-        }
-    }
-
-    /// Gets the blob by QuiltPatchV1.
-    fn get_blob_by_quilt_patch(
-        &self,
-        quilt_patch: &QuiltPatchV1,
-    ) -> Result<QuiltStoreBlob<'static>, QuiltError> {
-        let start_col = usize::from(quilt_patch.start_index);
-        let end_col = usize::from(quilt_patch.end_index);
-        self.check_missing_slivers(start_col, end_col)?;
-        QuiltVersionV1::decode_blob(self, start_col)
-    }
 
-    /// Checks if the desired slivers are missing.
-    fn check_missing_slivers(&self, start_idx: usize, end_idx: usize) -> Result<(), QuiltError> {
+    /// Checks if any of the slivers in the range `[start_idx, end_idx)` are missing.
+    pub fn check_missing_slivers(
+        slivers: &HashMap<SliverIndex, &SliverData<Secondary>>,
+        start_idx: usize,
+        end_idx: usize,
+    ) -> Result<(), QuiltError> {
         let mut missing_slivers = Vec::new();
         for i in start_idx..end_idx {
             let sliver_idx = SliverIndex(i as u16);
-            if !self.slivers.contains_key(&sliver_idx) {
+            if !slivers.contains_key(&sliver_idx) {
                 missing_slivers.push(sliver_idx);
             }
         }
@@ -1770,113 +2938,6 @@ impl<'a> QuiltDecoderV1<'a> {
         }
         Ok(())
     }
-}
-
-mod utils {
-    use super::*;
-
-    /// Finds the minimum symbol size needed to store blobs in a fixed number of columns.
-    /// Each blob must be stored in consecutive columns exclusively.
-    ///
-    /// A binary search is used to find the minimum symbol size:
-    /// 1. Compute the upper and lower bounds for the symbol size.
-    /// 2. Check if the all the blobs can be fit into the quilt with the current symbol size.
-    /// 3. Adjust the bounds based on the result and repeat until the symbol size is found.
-    ///
-    /// # Arguments
-    /// * `blobs_sizes` - Slice of blob lengths, including the index size as the first element.
-    ///   Note that the len of the blob_size should be between 1 and n_columns.
-    /// * `n_columns` - Number of columns available.
-    /// * `n_rows` - Number of rows available.
-    /// * `max_num_columns_for_quilt_index` - The maximum number of columns that can be used to
-    ///   store the quilt index.
-    /// * `required_alignment` - The alignment of the symbol size.
-    ///
-    /// # Returns
-    /// * `Result<usize, QuiltError>` - The minimum symbol size needed, or an error if impossible.
-    pub fn compute_symbol_size(
-        blobs_sizes: &[usize],
-        n_columns: usize,
-        n_rows: usize,
-        max_num_columns_for_quilt_index: usize,
-        encoding_type: EncodingType,
-    ) -> Result<usize, QuiltError> {
-        if blobs_sizes.len() > n_columns {
-            // The first column is not user data.
-            return Err(QuiltError::TooManyBlobs(
-                blobs_sizes.len() - 1,
-                n_columns - 1,
-            ));
-        }
-
-        if blobs_sizes.is_empty() {
-            return Err(QuiltError::EmptyInput("blobs".to_string()));
-        }
-
-        let mut min_val = cmp::max(
-            blobs_sizes
-                .iter()
-                .sum::<usize>()
-                .div_ceil(n_columns * n_rows),
-            blobs_sizes
-                .first()
-                .expect("blobs_sizes is not empty")
-                .div_ceil(n_rows * max_num_columns_for_quilt_index),
-        );
-        min_val = cmp::max(min_val, QUILT_INDEX_PREFIX_SIZE.div_ceil(n_rows));
-        let mut max_val = blobs_sizes
-            .iter()
-            .max()
-            .copied()
-            .expect("blobs_sizes is not empty")
-            .div_ceil(n_columns / blobs_sizes.len() * n_rows);
-
-        while min_val < max_val {
-            let mid = (min_val + max_val) / 2;
-            if can_blobs_fit_into_matrix(blobs_sizes, n_columns, mid * n_rows) {
-                max_val = mid;
-            } else {
-                min_val = mid + 1;
-            }
-        }
-
-        let symbol_size = min_val.next_multiple_of(encoding_type.required_alignment().into());
-        debug_assert!(can_blobs_fit_into_matrix(
-            blobs_sizes,
-            n_columns,
-            symbol_size * n_rows
-        ));
-        let max_symbol_size = usize::from(encoding_type.max_symbol_size());
-        if symbol_size > max_symbol_size {
-            return Err(QuiltError::QuiltOversize(format!(
-                "the resulting symbol size {symbol_size} is larger than the maximum symbol size \
-                {max_symbol_size}; remove some blobs"
-            )));
-        }
-
-        Ok(symbol_size)
-    }
-
-    /// Checks if the blobs can fit in the given number of columns.
-    ///
-    /// # Arguments
-    /// * `blobs_sizes` - The sizes of the blobs.
-    /// * `n_columns` - The number of columns available.
-    /// * `length` - The size of the column.
-    ///
-    /// # Returns
-    /// * `bool` - True if the blobs can fit in the given number of columns, false otherwise.
-    fn can_blobs_fit_into_matrix(
-        blobs_sizes: &[usize],
-        n_columns: usize,
-        column_size: usize,
-    ) -> bool {
-        let required_columns = blobs_sizes
-            .iter()
-            .map(|blob_size| blob_size.div_ceil(column_size))
-            .sum::<usize>();
-        n_columns >= required_columns
-    }
 
     pub fn get_quilt_version_byte(data: &[u8]) -> Result<u8, QuiltError> {
         data.first()
@@ -1979,120 +3040,80 @@ mod tests {
         test_quilt_construct_quilt: [
             case_0: (
                 &[
-                    QuiltStoreBlob {
-                        blob: Cow::Borrowed(&[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11][..]),
-                        identifier: "test-blob-0".to_string(),
-                        tags: BTreeMap::from([("tag1".to_string(), "value1".to_string())]),
-                    },
-                    QuiltStoreBlob {
-                        blob: Cow::Borrowed(&[5, 68, 3, 2, 5][..]),
-                        identifier: "test-blob-1".to_string(),
-                        tags: BTreeMap::from([("tag1".to_string(), "value1".to_string())]),
-                    },
-                    QuiltStoreBlob {
-                        blob: Cow::Borrowed(&[5, 68, 3, 2, 5, 6, 78, 8][..]),
-                        identifier: "test-blob-2".to_string(),
-                        tags: BTreeMap::from([("tag1".to_string(), "value1".to_string())]),
-                    },
+                    QuiltStoreBlob::new(&[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11][..], "test-blob-0")
+                        .expect("identifier is valid")
+                        .with_tags([("tag1".to_string(), "value1".to_string())]),
+                    QuiltStoreBlob::new(&[5, 68, 3, 2, 5][..], "test-blob-1")
+                        .expect("identifier is valid")
+                        .with_tags([("tag1".to_string(), "value1".to_string())]),
+                    QuiltStoreBlob::new(&[5, 68, 3, 2, 5, 6, 78, 8][..], "test-blob-2")
+                        .expect("identifier is valid")
+                        .with_tags([("tag1".to_string(), "value1".to_string())]),
                 ],
                 7
             ),
             case_0_random_order: (
                 &[
-                    QuiltStoreBlob {
-                        blob: Cow::Borrowed(&[5, 68, 3, 2, 5, 6, 78, 8][..]),
-                        identifier: "test-blob-0".to_string(),
-                        tags: BTreeMap::from([("tag1".to_string(), "value1".to_string())]),
-                    },
-                    QuiltStoreBlob {
-                        blob: Cow::Borrowed(&[5, 68, 3, 2, 5][..]),
-                        identifier: "test-blob-1".to_string(),
-                        tags: BTreeMap::new(),
-                    },
-                    QuiltStoreBlob {
-                        blob: Cow::Borrowed(&[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11][..]),
-                        identifier: "test-blob-2".to_string(),
-                        tags: BTreeMap::from([("tag2".to_string(), "value2".to_string())]),
-                    },
+                    QuiltStoreBlob::new(&[5, 68, 3, 2, 5, 6, 78, 8][..], "test-blob-0")
+                        .expect("identifier is valid")
+                        .with_tags([("tag1".to_string(), "value1".to_string())]),
+                    QuiltStoreBlob::new(&[5, 68, 3, 2, 5][..], "test-blob-1")
+                        .expect("identifier is valid"),
+                    QuiltStoreBlob::new(&[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11][..], "test-blob-2")
+                        .expect("identifier is valid")
+                        .with_tags([("tag2".to_string(), "value2".to_string())]),
                 ],
                 7
             ),
             case_1: (
                 &[
-                    QuiltStoreBlob {
-                        blob: Cow::Borrowed(&[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11][..]),
-                        identifier: "test-blob-0".to_string(),
-                        tags: BTreeMap::from([
+                    QuiltStoreBlob::new(&[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11][..], "test-blob-0")
+                        .expect("identifier is valid")
+                        .with_tags([
                             ("tag1".to_string(), "value1".to_string()),
                             ("tag2".to_string(), "value1".to_string()),
                         ]),
-                    },
-                    QuiltStoreBlob {
-                        blob: Cow::Borrowed(&[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11][..]),
-                        identifier: "test-blob-1".to_string(),
-                        tags: BTreeMap::from([
+                    QuiltStoreBlob::new(&[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11][..], "test-blob-1")
+                        .expect("identifier is valid")
+                        .with_tags([
                             ("tag3".to_string(), "value3".to_string()),
                             ("tag2".to_string(), "value2".to_string()),
                         ]),
-                    },
-                    QuiltStoreBlob {
-                        blob: Cow::Borrowed(&[5, 68, 3, 2, 5, 6, 78, 8][..]),
-                        identifier: "test-blob-2".to_string(),
-                        tags: BTreeMap::from([
+                    QuiltStoreBlob::new(&[5, 68, 3, 2, 5, 6, 78, 8][..], "test-blob-2")
+                        .expect("identifier is valid")
+                        .with_tags([
                             ("tag3".to_string(), "value1".to_string()),
                             ("tag2".to_string(), "value3".to_string()),
                         ]),
-                    },
                 ],
                 7
             ),
             case_1_random_order: (
                 &[
-                    QuiltStoreBlob {
-                        blob: Cow::Borrowed(&[5, 68, 3, 2, 5][..]),
-                        identifier: "test-blob-0".to_string(),
-                        tags: BTreeMap::new(),
-                    },
-                    QuiltStoreBlob {
-                        blob: Cow::Borrowed(&[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11][..]),
-                        identifier: "short".to_string(),
-                        tags: BTreeMap::new(),
-                    },
-                    QuiltStoreBlob {
-                        blob: Cow::Borrowed(&[5, 68, 3, 2, 5, 6, 78, 8][..]),
-                        identifier: "test-blob-2".to_string(),
-                        tags: BTreeMap::new(),
-                    },
+                    QuiltStoreBlob::new(&[5, 68, 3, 2, 5][..], "test-blob-0")
+                        .expect("identifier is valid"),
+                    QuiltStoreBlob::new(&[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11][..], "short")
+                        .expect("identifier is valid"),
+                    QuiltStoreBlob::new(&[5, 68, 3, 2, 5, 6, 78, 8][..], "test-blob-2")
+                        .expect("identifier is valid"),
                 ],
                 7
             ),
             case_2: (
                 &[
-                    QuiltStoreBlob {
-                        blob: Cow::Borrowed(&[1, 3][..]),
-                        identifier: "test-blob-0".to_string(),
-                        tags: BTreeMap::new(),
-                    },
-                    QuiltStoreBlob {
-                        blob: Cow::Borrowed(&[255u8; 1024][..]),
-                        identifier: "test-blob-1".to_string(),
-                        tags: BTreeMap::new(),
-                    },
-                    QuiltStoreBlob {
-                        blob: Cow::Borrowed(&[1, 2, 3][..]),
-                        identifier: "test-blob-2".to_string(),
-                        tags: BTreeMap::new(),
-                    },
+                    QuiltStoreBlob::new(&[1, 3][..], "test-blob-0")
+                        .expect("identifier is valid"),
+                    QuiltStoreBlob::new(&[255u8; 1024][..], "test-blob-1")
+                        .expect("identifier is valid"),
+                    QuiltStoreBlob::new(&[1, 2, 3][..], "test-blob-2")
+                        .expect("identifier is valid"),
                 ],
                 12
             ),
             case_3: (
                 &[
-                    QuiltStoreBlob {
-                        blob: Cow::Borrowed(&[9, 8, 7, 6, 5, 4, 3, 2, 1][..]),
-                        identifier: "test-blob-0".to_string(),
-                        tags: BTreeMap::new(),
-                    },
+                    QuiltStoreBlob::new(&[9, 8, 7, 6, 5, 4, 3, 2, 1][..], "test-blob-0")
+                        .expect("identifier is valid"),
                 ],
                 7
             ),
@@ -2233,7 +3254,9 @@ mod tests {
             quilt_metadata
         );
 
-        let QuiltMetadata::V1(quilt_metadata_v1) = quilt_metadata;
+        let QuiltMetadata::V1(quilt_metadata_v1) = quilt_metadata else {
+            panic!("expected quilt metadata V1");
+        };
 
         let slivers: Vec<&SliverData<Secondary>> = sliver_pairs
             .iter()
@@ -2286,7 +3309,10 @@ mod tests {
             .as_str();
         let QuiltIndex::V1(quilt_index_v1) = quilt_decoder
             .get_or_decode_quilt_index()
-            .expect("quilt index should exist");
+            .expect("quilt index should exist")
+        else {
+            panic!("expected quilt index V1");
+        };
         let patch = quilt_index_v1
             .get_quilt_patches_by_identifiers(&[identifier])
             .expect("quilt patch should exist")
@@ -2486,4 +3512,287 @@ mod tests {
 
         Ok(())
     }
+
+    param_test! {
+        test_quilt_v2_encoder_and_decoder: [
+            case_0: (3, 5, 16, 7),
+            case_1: (3, 1024, 10240, 7),
+            case_2: (1, 10, 1000, 7),
+            case_3: (60, 1, 1000, 100),
+            case_4: (10, 0, 2, 100),
+        ]
+    }
+    fn test_quilt_v2_encoder_and_decoder(
+        num_blobs: usize,
+        min_blob_size: usize,
+        max_blob_size: usize,
+        n_shards: u16,
+    ) {
+        let _ = tracing_subscriber::fmt().try_init();
+
+        let mut test_data =
+            QuiltTestData::new_owned(num_blobs, min_blob_size, max_blob_size, 100, 10)
+                .expect("Should create test data");
+        let quilt_store_blobs = test_data.take_blobs();
+        let reed_solomon_config =
+            ReedSolomonEncodingConfig::new(NonZeroU16::try_from(n_shards).unwrap());
+        let config = EncodingConfigEnum::ReedSolomon(&reed_solomon_config);
+
+        let encoder = QuiltConfigV2::get_encoder(config.clone(), quilt_store_blobs.as_slice());
+        let (sliver_pairs, quilt_metadata) = encoder
+            .encode_with_metadata()
+            .expect("Should encode with quilt index and metadata");
+        let QuiltMetadata::V2(quilt_metadata_v2) = quilt_metadata else {
+            panic!("expected quilt metadata V2");
+        };
+        let slivers: Vec<&SliverData<Secondary>> = sliver_pairs
+            .iter()
+            .map(|sliver_pair| &sliver_pair.secondary)
+            .collect();
+
+        let first_sliver = slivers
+            .iter()
+            .find(|sliver| sliver.index == SliverIndex::new(0))
+            .expect("Should find first sliver");
+        assert_eq!(
+            get_quilt_version_enum(first_sliver.symbols.data()),
+            Ok(QuiltVersionEnum::V2)
+        );
+
+        // Decode the index from the slivers it is stored in.
+        let mut quilt_decoder = QuiltConfigV2::get_decoder(vec![*first_sliver]);
+        if let Err(QuiltError::MissingSlivers(missing_indices)) =
+            quilt_decoder.get_or_decode_quilt_index()
+        {
+            quilt_decoder.add_slivers(
+                slivers
+                    .iter()
+                    .filter(|sliver| missing_indices.contains(&sliver.index))
+                    .copied(),
+            );
+        }
+        assert_eq!(
+            quilt_decoder.get_or_decode_quilt_index(),
+            Ok(quilt_metadata_v2.index.clone().into())
+        );
+
+        quilt_decoder.add_slivers(slivers.iter().copied());
+        for patch in &quilt_metadata_v2.index.quilt_patches {
+            let expected_blob = quilt_store_blobs
+                .iter()
+                .find(|blob| blob.identifier == patch.identifier)
+                .expect("Should find blob in test data");
+            assert!(patch.verify(expected_blob.data()).is_ok());
+
+            let blob = quilt_decoder
+                .get_blobs_by_identifiers(&[patch.identifier.as_str()])
+                .expect("Should get blob by identifier")
+                .pop()
+                .expect("Should get blob by identifier");
+            assert_eq!(blob, *expected_blob);
+
+            let blob = quilt_decoder
+                .get_blob_by_patch_internal_id(&patch.quilt_patch_internal_id().to_bytes())
+                .expect("Should get blob by patch internal id");
+            assert_eq!(blob, *expected_blob);
+        }
+
+        for (tag, val) in &test_data.blob_identifiers_by_tag {
+            for (tag_value, identifiers) in val {
+                let blobs = quilt_decoder
+                    .get_blobs_by_tag(tag, tag_value)
+                    .expect("Should get blobs by tag");
+                assert_eq!(blobs.len(), identifiers.len());
+            }
+        }
+
+        // Readers that only know the version enum handle the quilt as well.
+        let quilt_blob = encoder
+            .construct_quilt()
+            .expect("Should construct quilt")
+            .data()
+            .to_vec();
+        let quilt = QuiltEnum::new(quilt_blob, &config).expect("Should create quilt");
+        assert!(matches!(quilt, QuiltEnum::V2(_)));
+        assert_eq!(
+            quilt.get_quilt_index(),
+            Ok(quilt_metadata_v2.index.clone().into())
+        );
+        let mut all_blobs = quilt.get_all_blobs().expect("Should get all blobs");
+        all_blobs.sort_by(|a, b| a.identifier.cmp(&b.identifier));
+        let mut expected_blobs = quilt_store_blobs.clone();
+        expected_blobs.sort_by(|a, b| a.identifier.cmp(&b.identifier));
+        assert_eq!(all_blobs, expected_blobs);
+    }
+
+    #[test]
+    fn test_quilt_v2_index_is_not_limited_to_v1_columns() {
+        let reed_solomon_config = ReedSolomonEncodingConfig::new(NonZeroU16::new(100).unwrap());
+        let config = EncodingConfigEnum::ReedSolomon(&reed_solomon_config);
+        let data = vec![7u8; 16];
+        let quilt_store_blobs = (0..40)
+            .map(|i| {
+                QuiltStoreBlob::new(&data, format!("blob-{i:02}"))
+                    .expect("identifier is valid")
+                    .with_tags((0..20).map(|j| (format!("tag-{j:02}"), "x".repeat(40))))
+            })
+            .collect::<Vec<_>>();
+
+        let quilt_v1 = QuiltConfigV1::get_encoder(config.clone(), &quilt_store_blobs)
+            .construct_quilt()
+            .expect("Should construct V1 quilt");
+        let quilt_v2 = QuiltConfigV2::get_encoder(config.clone(), &quilt_store_blobs)
+            .construct_quilt()
+            .expect("Should construct V2 quilt");
+
+        let first_blob_col =
+            quilt_v2.quilt_index().expect("index exists").quilt_patches[0].start_index;
+        assert!(usize::from(first_blob_col) > MAX_NUM_SLIVERS_FOR_QUILT_INDEX);
+        assert!(quilt_v2.symbol_size() < quilt_v1.symbol_size());
+        assert_eq!(
+            quilt_v2
+                .get_all_blobs()
+                .expect("Should get all blobs")
+                .len(),
+            quilt_store_blobs.len()
+        );
+    }
+
+    #[test]
+    fn test_quilt_v2_detects_corrupted_patch() {
+        let reed_solomon_config = ReedSolomonEncodingConfig::new(NonZeroU16::new(7).unwrap());
+        let config = EncodingConfigEnum::ReedSolomon(&reed_solomon_config);
+        let data = vec![1u8; 100];
+        let quilt_store_blobs = vec![
+            QuiltStoreBlob::new(&data, "corrupted").expect("identifier is valid"),
+            QuiltStoreBlob::new(&data, "intact").expect("identifier is valid"),
+        ];
+        let mut quilt = QuiltConfigV2::get_encoder(config, &quilt_store_blobs)
+            .construct_quilt()
+            .expect("Should construct quilt");
+        let patch = quilt.quilt_index().expect("index exists").quilt_patches[0].clone();
+        assert_eq!(patch.identifier, "corrupted");
+
+        // Flip the last data byte of the first patch.
+        let last_byte_offset =
+            QuiltVersionV2::serialized_blob_size(&quilt_store_blobs[0]).expect("size is valid") - 1;
+        let n_rows = quilt.data.len() / quilt.row_size;
+        let column_size = n_rows * quilt.symbol_size;
+        let col = usize::from(patch.start_index) + last_byte_offset / column_size;
+        let row = last_byte_offset % column_size / quilt.symbol_size;
+        let position =
+            row * quilt.row_size + col * quilt.symbol_size + last_byte_offset % quilt.symbol_size;
+        quilt.data[position] ^= 0xff;
+
+        let expected_error = QuiltError::PatchHashMismatch("corrupted".to_string());
+        assert_eq!(
+            quilt.get_blobs_by_identifiers(&["corrupted"]),
+            Err(expected_error.clone())
+        );
+        assert_eq!(
+            quilt.get_blob_by_patch_internal_id(&patch.quilt_patch_internal_id().to_bytes()),
+            Err(expected_error)
+        );
+        assert!(quilt.get_blobs_by_identifiers(&["intact"]).is_ok());
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn test_quilt_v2_compressed_patches() {
+        let reed_solomon_config = ReedSolomonEncodingConfig::new(NonZeroU16::new(10).unwrap());
+        let config = EncodingConfigEnum::ReedSolomon(&reed_solomon_config);
+        let data = b"<html><body>walrus walrus walrus</body></html>".repeat(50);
+        let compressions = [
+            QuiltPatchCompression::None,
+            QuiltPatchCompression::Zstd,
+            QuiltPatchCompression::Gzip,
+            QuiltPatchCompression::Brotli,
+        ];
+        let quilt_store_blobs = compressions
+            .iter()
+            .map(|compression| {
+                QuiltStoreBlob::new(&data, compression.to_string())
+                    .expect("identifier is valid")
+                    .with_compression(*compression)
+                    .expect("compression should succeed")
+            })
+            .collect::<Vec<_>>();
+
+        let encoder = QuiltConfigV2::get_encoder(config.clone(), &quilt_store_blobs);
+        let quilt = encoder.construct_quilt().expect("Should construct quilt");
+        let (sliver_pairs, _) = encoder
+            .encode_with_metadata()
+            .expect("Should encode with quilt index and metadata");
+        let mut quilt_decoder =
+            QuiltConfigV2::get_decoder(sliver_pairs.iter().map(|pair| &pair.secondary));
+        quilt_decoder
+            .get_or_decode_quilt_index()
+            .expect("Should decode quilt index");
+
+        for (compression, expected_blob) in compressions.iter().zip(&quilt_store_blobs) {
+            assert_eq!(expected_blob.compression(), *compression);
+            assert_eq!(
+                expected_blob.data().len() < data.len(),
+                compression.is_compressed()
+            );
+
+            let identifier = compression.to_string();
+            for blob in [
+                quilt.get_blobs_by_identifiers(&[identifier.as_str()]),
+                quilt_decoder.get_blobs_by_identifiers(&[identifier.as_str()]),
+            ] {
+                let blob = blob
+                    .expect("Should get blob")
+                    .pop()
+                    .expect("Should get blob");
+                assert_eq!(blob, *expected_blob);
+                assert_eq!(blob.uncompressed_length(), data.len() as u64);
+                assert_eq!(blob.decompress().expect("Should decompress").data(), data);
+            }
+        }
+
+        // The blob header of V1 cannot record the compression.
+        assert_eq!(
+            QuiltConfigV1::get_encoder(config, &quilt_store_blobs).construct_quilt(),
+            Err(QuiltError::CompressionNotSupported("brotli".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_quilt_v2_blob_header() {
+        let mut header =
+            BlobHeaderV2::new(1234, BlobHeaderV2::TAGS_ENABLED, [42; DIGEST_LEN], 4321);
+        let bytes = header.as_bytes();
+        assert_eq!(bytes.len(), QuiltVersionV2::BLOB_HEADER_SIZE);
+        assert_eq!(BlobHeaderV2::from_bytes(bytes), Ok(header));
+        assert!(header.has_tags());
+        assert_eq!(header.compression(), Ok(QuiltPatchCompression::None));
+
+        header.set_compression(QuiltPatchCompression::Brotli);
+        let decoded = BlobHeaderV2::from_bytes(header.as_bytes()).expect("header is valid");
+        assert!(decoded.has_tags());
+        assert_eq!(decoded.compression(), Ok(QuiltPatchCompression::Brotli));
+        assert_eq!(decoded.uncompressed_length, 4321);
+
+        let mut v1_bytes = bytes;
+        v1_bytes[0] = QuiltVersionV1::QUILT_VERSION_BYTE;
+        assert_eq!(
+            BlobHeaderV2::from_bytes(v1_bytes),
+            Err(QuiltError::QuiltVersionMismatch(
+                QuiltVersionV1::QUILT_VERSION_BYTE,
+                QuiltVersionV2::QUILT_VERSION_BYTE
+            ))
+        );
+    }
+
+    #[test]
+    fn test_quilt_version_enum_reads_all_versions() {
+        assert_eq!(QuiltVersionEnum::try_from(0x01), Ok(QuiltVersionEnum::V1));
+        assert_eq!(QuiltVersionEnum::try_from(0x02), Ok(QuiltVersionEnum::V2));
+        assert!(QuiltVersionEnum::try_from(0x03).is_err());
+        assert_eq!(
+            QuiltVersionEnum::from("v2".to_string()),
+            QuiltVersionEnum::V2
+        );
+    }
 }
@@ -0,0 +1,322 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Compression of the data of quilt patches.
+//!
+//! The length of the uncompressed data is recorded in the blob header of a compressed patch.
+//! Decompression stops as soon as the output exceeds that length, such that a small patch cannot
+//! expand into an arbitrarily large buffer.
+//!
+//! The codecs are only available with the `compression` feature; without it, compressed patches
+//! can be stored and read back as is, but not compressed or decompressed.
+
+use alloc::{format, vec::Vec};
+use core::{fmt, str::FromStr};
+#[cfg(feature = "compression")]
+use std::io::{Read, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::encoding::QuiltError;
+
+/// The compression applied to the data of a quilt patch.
+///
+/// Each patch is compressed independently, such that a single patch can be read and decompressed
+/// without the rest of the quilt.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum QuiltPatchCompression {
+    /// The data is stored as is.
+    #[default]
+    None,
+    /// The data is compressed with zstd.
+    Zstd,
+    /// The data is compressed with gzip.
+    Gzip,
+    /// The data is compressed with brotli.
+    Brotli,
+}
+
+impl QuiltPatchCompression {
+    /// The zstd compression level, 0 selects the default level of the library.
+    #[cfg(feature = "compression")]
+    const ZSTD_LEVEL: i32 = 0;
+    /// The brotli buffer size in bytes.
+    #[cfg(feature = "compression")]
+    const BROTLI_BUFFER_SIZE: usize = 4096;
+    /// The brotli quality, between 0 and 11.
+    #[cfg(feature = "compression")]
+    const BROTLI_QUALITY: u32 = 9;
+    /// The base-2 logarithm of the brotli window size.
+    #[cfg(feature = "compression")]
+    const BROTLI_WINDOW_SIZE_LOG2: u32 = 22;
+
+    /// Returns true if the data is compressed.
+    pub fn is_compressed(&self) -> bool {
+        *self != Self::None
+    }
+
+    /// Returns the value of the HTTP `Content-Encoding` header for the compressed data, if any.
+    pub fn content_encoding(&self) -> Option<&'static str> {
+        match self {
+            Self::None => None,
+            Self::Zstd => Some("zstd"),
+            Self::Gzip => Some("gzip"),
+            Self::Brotli => Some("br"),
+        }
+    }
+
+    /// Returns the code of the compression as stored in the blob header.
+    pub(crate) fn to_code(self) -> u8 {
+        match self {
+            Self::None => 0,
+            Self::Zstd => 1,
+            Self::Gzip => 2,
+            Self::Brotli => 3,
+        }
+    }
+
+    /// Returns the compression for a code stored in the blob header.
+    pub(crate) fn from_code(code: u8) -> Result<Self, QuiltError> {
+        match code {
+            0 => Ok(Self::None),
+            1 => Ok(Self::Zstd),
+            2 => Ok(Self::Gzip),
+            3 => Ok(Self::Brotli),
+            _ => Err(QuiltError::CompressionError(format!(
+                "unknown compression code: {code}"
+            ))),
+        }
+    }
+
+    /// Compresses the data.
+    pub fn compress(&self, data: &[u8]) -> Result<Vec<u8>, QuiltError> {
+        if !self.is_compressed() {
+            return Ok(data.to_vec());
+        }
+        self.compress_into(data, Vec::new())
+            .map_err(|error| QuiltError::CompressionError(format!("{self}: {error}")))
+    }
+
+    /// Decompresses the data, which must decompress to exactly `length` bytes.
+    ///
+    /// At most `length + 1` bytes are decompressed, such that data which expands beyond its
+    /// recorded length is rejected without decompressing it completely.
+    pub fn decompress(&self, data: &[u8], length: u64) -> Result<Vec<u8>, QuiltError> {
+        if !self.is_compressed() {
+            return Ok(data.to_vec());
+        }
+        let decompressed = self
+            .decompress_at_most(data, length.saturating_add(1))
+            .map_err(|error| QuiltError::CompressionError(format!("{self}: {error}")))?;
+        if u64::try_from(decompressed.len()).expect("32 or 64-bit arch") != length {
+            return Err(QuiltError::CompressionError(format!(
+                "{self}: the data does not decompress to the recorded length of {length} bytes"
+            )));
+        }
+        Ok(decompressed)
+    }
+
+    /// Appends the compressed data to `output`.
+    #[cfg(feature = "compression")]
+    fn compress_into(&self, data: &[u8], output: Vec<u8>) -> std::io::Result<Vec<u8>> {
+        match self {
+            Self::None => unreachable!("uncompressed data is returned as is"),
+            Self::Zstd => {
+                let mut encoder = zstd::stream::Encoder::new(output, Self::ZSTD_LEVEL)?;
+                encoder.write_all(data)?;
+                encoder.finish()
+            }
+            Self::Gzip => {
+                let mut encoder =
+                    flate2::write::GzEncoder::new(output, flate2::Compression::default());
+                encoder.write_all(data)?;
+                encoder.finish()
+            }
+            Self::Brotli => {
+                let mut encoder = brotli::CompressorWriter::new(
+                    output,
+                    Self::BROTLI_BUFFER_SIZE,
+                    Self::BROTLI_QUALITY,
+                    Self::BROTLI_WINDOW_SIZE_LOG2,
+                );
+                // Consuming the writer finishes the compressed stream.
+                encoder.write_all(data)?;
+                Ok(encoder.into_inner())
+            }
+        }
+    }
+
+    /// Decompresses the data, reading at most `limit` bytes of decompressed output.
+    #[cfg(feature = "compression")]
+    fn decompress_at_most(&self, data: &[u8], limit: u64) -> std::io::Result<Vec<u8>> {
+        let mut decompressed = Vec::new();
+        match self {
+            Self::None => unreachable!("uncompressed data is returned as is"),
+            Self::Zstd => zstd::stream::Decoder::new(data)?
+                .take(limit)
+                .read_to_end(&mut decompressed)?,
+            Self::Gzip => flate2::read::GzDecoder::new(data)
+                .take(limit)
+                .read_to_end(&mut decompressed)?,
+            Self::Brotli => brotli::Decompressor::new(data, Self::BROTLI_BUFFER_SIZE)
+                .take(limit)
+                .read_to_end(&mut decompressed)?,
+        };
+        Ok(decompressed)
+    }
+
+    #[cfg(not(feature = "compression"))]
+    fn compress_into(&self, _data: &[u8], _output: Vec<u8>) -> std::io::Result<Vec<u8>> {
+        Err(Self::codecs_not_enabled())
+    }
+
+    #[cfg(not(feature = "compression"))]
+    fn decompress_at_most(&self, _data: &[u8], _limit: u64) -> std::io::Result<Vec<u8>> {
+        Err(Self::codecs_not_enabled())
+    }
+
+    #[cfg(not(feature = "compression"))]
+    fn codecs_not_enabled() -> std::io::Error {
+        std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "walrus-core was built without the `compression` feature",
+        )
+    }
+}
+
+impl fmt::Display for QuiltPatchCompression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::None => "none",
+            Self::Zstd => "zstd",
+            Self::Gzip => "gzip",
+            Self::Brotli => "brotli",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl FromStr for QuiltPatchCompression {
+    type Err = QuiltError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "none" => Ok(Self::None),
+            "zstd" => Ok(Self::Zstd),
+            "gzip" => Ok(Self::Gzip),
+            "brotli" | "br" => Ok(Self::Brotli),
+            _ => Err(QuiltError::CompressionError(format!(
+                "unknown compression: {s}"
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+    #[cfg(feature = "compression")]
+    use alloc::vec;
+
+    #[cfg(feature = "compression")]
+    use walrus_test_utils::param_test;
+
+    use super::*;
+
+    #[cfg(feature = "compression")]
+    param_test! {
+        test_compression_roundtrip: [
+            none: (QuiltPatchCompression::None),
+            zstd: (QuiltPatchCompression::Zstd),
+            gzip: (QuiltPatchCompression::Gzip),
+            brotli: (QuiltPatchCompression::Brotli),
+        ]
+    }
+    #[cfg(feature = "compression")]
+    fn test_compression_roundtrip(compression: QuiltPatchCompression) {
+        let data =
+            b"{\"name\": \"walrus\", \"tags\": [\"quilt\", \"quilt\", \"quilt\"]}".repeat(100);
+
+        let compressed = compression
+            .compress(&data)
+            .expect("compression should succeed");
+        if compression.is_compressed() {
+            assert!(compressed.len() < data.len());
+        }
+        assert_eq!(
+            compression
+                .decompress(&compressed, data.len() as u64)
+                .expect("decompression should succeed"),
+            data
+        );
+    }
+
+    #[test]
+    fn test_compression_codes_and_names() {
+        for compression in [
+            QuiltPatchCompression::None,
+            QuiltPatchCompression::Zstd,
+            QuiltPatchCompression::Gzip,
+            QuiltPatchCompression::Brotli,
+        ] {
+            assert_eq!(
+                QuiltPatchCompression::from_code(compression.to_code()),
+                Ok(compression)
+            );
+            assert_eq!(compression.to_string().parse(), Ok(compression));
+        }
+        assert_eq!("br".parse(), Ok(QuiltPatchCompression::Brotli));
+        assert!(QuiltPatchCompression::from_code(4).is_err());
+        assert!("lz4".parse::<QuiltPatchCompression>().is_err());
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn test_decompress_truncated_data_fails() {
+        let data = vec![7u8; 1024];
+        for compression in [
+            QuiltPatchCompression::Zstd,
+            QuiltPatchCompression::Gzip,
+            QuiltPatchCompression::Brotli,
+        ] {
+            let compressed = compression
+                .compress(&data)
+                .expect("compression should succeed");
+            assert!(matches!(
+                compression.decompress(&compressed[..compressed.len() / 2], 1024),
+                Err(QuiltError::CompressionError(_))
+            ));
+        }
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn test_decompress_enforces_recorded_length() {
+        // A highly compressible payload, as used in a decompression bomb.
+        let data = vec![0u8; 1 << 20];
+        for compression in [
+            QuiltPatchCompression::Zstd,
+            QuiltPatchCompression::Gzip,
+            QuiltPatchCompression::Brotli,
+        ] {
+            let compressed = compression
+                .compress(&data)
+                .expect("compression should succeed");
+
+            for recorded_length in [0, 1024, (1 << 20) - 1, (1 << 20) + 1] {
+                assert!(matches!(
+                    compression.decompress(&compressed, recorded_length),
+                    Err(QuiltError::CompressionError(_))
+                ));
+            }
+            assert_eq!(
+                compression
+                    .decompress(&compressed, 1 << 20)
+                    .map(|data| data.len()),
+                Ok(1 << 20)
+            );
+        }
+    }
+}
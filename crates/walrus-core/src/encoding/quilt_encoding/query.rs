@@ -0,0 +1,634 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! A query language to select quilt patches by their identifiers and tags.
+//!
+//! A query is made of predicates combined with `and`, `or`, `not`, and parentheses, where `and`
+//! binds stronger than `or`. A predicate compares a field of a patch, either its `identifier` or
+//! the value of a tag `tag:<key>`, with a value:
+//!
+//! - `=` and `!=` check for equality.
+//! - `^=` checks if the field starts with the value.
+//! - `~` matches the field against a glob pattern, where `*` matches any sequence of characters
+//!   and `?` matches a single character.
+//! - `<`, `<=`, `>`, and `>=` compare the field numerically if both the field and the value are
+//!   numbers, and lexicographically otherwise.
+//!
+//! Additionally, `has tag:<key>` checks if a patch has a tag with the given key. Predicates on a
+//! tag never match patches without the tag. Keys and values that contain whitespace, parentheses,
+//! quotes, or operator characters must be quoted, e.g., `tag:"created at" >= "2024-01-01"`.
+//!
+//! For example, `tag:shard >= 10 and tag:shard < 20 and (identifier ~ "*.csv" or has tag:raw)`.
+
+use alloc::{
+    boxed::Box,
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::{
+    cmp::Ordering,
+    fmt,
+    iter::Peekable,
+    str::{Chars, FromStr},
+};
+
+use crate::encoding::QuiltError;
+
+/// The maximum nesting depth of a query, to bound the recursion when parsing it.
+const MAX_QUERY_DEPTH: usize = 32;
+
+/// A field of a quilt patch that can be compared in a query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QuiltPatchField {
+    /// The identifier of the patch.
+    Identifier,
+    /// The value of the tag with the given key.
+    Tag(String),
+}
+
+impl QuiltPatchField {
+    fn value<'a>(
+        &self,
+        identifier: &'a str,
+        tags: &'a BTreeMap<String, String>,
+    ) -> Option<&'a str> {
+        match self {
+            Self::Identifier => Some(identifier),
+            Self::Tag(key) => tags.get(key).map(String::as_str),
+        }
+    }
+}
+
+impl fmt::Display for QuiltPatchField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Identifier => write!(f, "identifier"),
+            Self::Tag(key) => write!(f, "tag:{}", Quoted(key)),
+        }
+    }
+}
+
+/// A comparison between a field of a quilt patch and a value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuiltPatchComparison {
+    /// The field is equal to the value.
+    Equal,
+    /// The field is not equal to the value.
+    NotEqual,
+    /// The field is less than the value.
+    Less,
+    /// The field is less than or equal to the value.
+    LessOrEqual,
+    /// The field is greater than the value.
+    Greater,
+    /// The field is greater than or equal to the value.
+    GreaterOrEqual,
+    /// The field starts with the value.
+    Prefix,
+    /// The field matches the glob pattern in the value.
+    Glob,
+}
+
+impl QuiltPatchComparison {
+    /// The operators, ordered such that no operator is a prefix of a preceding one.
+    const OPERATORS: [(&'static str, Self); 8] = [
+        ("!=", Self::NotEqual),
+        ("<=", Self::LessOrEqual),
+        (">=", Self::GreaterOrEqual),
+        ("^=", Self::Prefix),
+        ("=", Self::Equal),
+        ("<", Self::Less),
+        (">", Self::Greater),
+        ("~", Self::Glob),
+    ];
+
+    fn evaluate(&self, field: &str, value: &str) -> bool {
+        match self {
+            Self::Equal => field == value,
+            Self::NotEqual => field != value,
+            Self::Prefix => field.starts_with(value),
+            Self::Glob => glob_matches(value, field),
+            Self::Less => compare_values(field, value) == Ordering::Less,
+            Self::LessOrEqual => compare_values(field, value) != Ordering::Greater,
+            Self::Greater => compare_values(field, value) == Ordering::Greater,
+            Self::GreaterOrEqual => compare_values(field, value) != Ordering::Less,
+        }
+    }
+
+    fn operator(&self) -> &'static str {
+        Self::OPERATORS
+            .iter()
+            .find(|(_, comparison)| comparison == self)
+            .map(|(operator, _)| *operator)
+            .expect("all comparisons have an operator")
+    }
+}
+
+impl fmt::Display for QuiltPatchComparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.operator())
+    }
+}
+
+/// A filter selecting quilt patches by their identifiers and tags.
+///
+/// See the [module documentation][self] for the syntax of the textual representation, which can
+/// be parsed with [`FromStr`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QuiltPatchFilter {
+    /// All the filters match.
+    And(Vec<QuiltPatchFilter>),
+    /// Any of the filters matches.
+    Or(Vec<QuiltPatchFilter>),
+    /// The filter does not match.
+    Not(Box<QuiltPatchFilter>),
+    /// The patch has a tag with the given key.
+    HasTag(String),
+    /// The field of the patch compares to the value as given.
+    Compare {
+        /// The field of the patch.
+        field: QuiltPatchField,
+        /// The comparison.
+        comparison: QuiltPatchComparison,
+        /// The value to compare the field with.
+        value: String,
+    },
+}
+
+impl QuiltPatchFilter {
+    /// Returns true if a patch with the given identifier and tags matches the filter.
+    pub fn matches(&self, identifier: &str, tags: &BTreeMap<String, String>) -> bool {
+        match self {
+            Self::And(filters) => filters
+                .iter()
+                .all(|filter| filter.matches(identifier, tags)),
+            Self::Or(filters) => filters
+                .iter()
+                .any(|filter| filter.matches(identifier, tags)),
+            Self::Not(filter) => !filter.matches(identifier, tags),
+            Self::HasTag(key) => tags.contains_key(key),
+            Self::Compare {
+                field,
+                comparison,
+                value,
+            } => field
+                .value(identifier, tags)
+                .is_some_and(|field| comparison.evaluate(field, value)),
+        }
+    }
+}
+
+impl FromStr for QuiltPatchFilter {
+    type Err = QuiltError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tokens = tokenize(s)?;
+        let mut parser = Parser {
+            tokens: tokens.into_iter().peekable(),
+            depth: 0,
+        };
+        let filter = parser.parse_or()?;
+        match parser.tokens.next() {
+            None => Ok(filter),
+            Some(token) => Err(invalid_query(format!("unexpected {token}"))),
+        }
+    }
+}
+
+impl fmt::Display for QuiltPatchFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn write_joined(
+            f: &mut fmt::Formatter<'_>,
+            filters: &[QuiltPatchFilter],
+            separator: &str,
+        ) -> fmt::Result {
+            for (i, filter) in filters.iter().enumerate() {
+                if i > 0 {
+                    write!(f, " {separator} ")?;
+                }
+                match filter {
+                    QuiltPatchFilter::And(_) | QuiltPatchFilter::Or(_) => write!(f, "({filter})")?,
+                    _ => write!(f, "{filter}")?,
+                }
+            }
+            Ok(())
+        }
+
+        match self {
+            Self::And(filters) => write_joined(f, filters, "and"),
+            Self::Or(filters) => write_joined(f, filters, "or"),
+            Self::Not(filter) => write!(f, "not ({filter})"),
+            Self::HasTag(key) => write!(f, "has tag:{}", Quoted(key)),
+            Self::Compare {
+                field,
+                comparison,
+                value,
+            } => write!(f, "{field} {comparison} {}", Quoted(value)),
+        }
+    }
+}
+
+fn invalid_query(message: impl Into<String>) -> QuiltError {
+    QuiltError::InvalidQuery(message.into())
+}
+
+/// Formats a string as a quoted query literal.
+struct Quoted<'a>(&'a str);
+
+impl fmt::Display for Quoted<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\"")?;
+        for c in self.0.chars() {
+            if c == '"' || c == '\\' {
+                write!(f, "\\")?;
+            }
+            write!(f, "{c}")?;
+        }
+        write!(f, "\"")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    LeftParen,
+    RightParen,
+    Operator(QuiltPatchComparison),
+    Word(String),
+    Quoted(String),
+}
+
+impl Token {
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self, Token::Word(word) if word.eq_ignore_ascii_case(keyword))
+    }
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::LeftParen => write!(f, "'('"),
+            Token::RightParen => write!(f, "')'"),
+            Token::Operator(comparison) => write!(f, "operator '{comparison}'"),
+            Token::Word(word) => write!(f, "'{word}'"),
+            Token::Quoted(string) => write!(f, "{}", Quoted(string)),
+        }
+    }
+}
+
+fn is_operator_char(c: char) -> bool {
+    matches!(c, '=' | '!' | '<' | '>' | '^' | '~')
+}
+
+fn tokenize(query: &str) -> Result<Vec<Token>, QuiltError> {
+    let mut tokens = Vec::new();
+    let mut chars = query.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::LeftParen);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::RightParen);
+            }
+            '"' => {
+                chars.next();
+                tokens.push(Token::Quoted(tokenize_quoted(&mut chars)?));
+            }
+            c if is_operator_char(c) => {
+                let rest = chars.clone().collect::<String>();
+                let (operator, comparison) = QuiltPatchComparison::OPERATORS
+                    .iter()
+                    .find(|(operator, _)| rest.starts_with(operator))
+                    .ok_or_else(|| invalid_query(format!("unknown operator at '{rest}'")))?;
+                chars.nth(operator.len() - 1);
+                tokens.push(Token::Operator(*comparison));
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || matches!(c, '(' | ')' | '"') || is_operator_char(c) {
+                        break;
+                    }
+                    word.push(c);
+                    chars.next();
+                }
+                tokens.push(Token::Word(word));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+/// Reads a quoted string after the opening quote, resolving `\"` and `\\` escapes.
+fn tokenize_quoted(chars: &mut Peekable<Chars<'_>>) -> Result<String, QuiltError> {
+    let mut string = String::new();
+    loop {
+        match chars.next() {
+            Some('"') => return Ok(string),
+            Some('\\') => match chars.next() {
+                Some(c @ ('"' | '\\')) => string.push(c),
+                _ => return Err(invalid_query("invalid escape sequence in quoted string")),
+            },
+            Some(c) => string.push(c),
+            None => return Err(invalid_query("unterminated quoted string")),
+        }
+    }
+}
+
+struct Parser {
+    tokens: Peekable<alloc::vec::IntoIter<Token>>,
+    depth: usize,
+}
+
+impl Parser {
+    fn parse_or(&mut self) -> Result<QuiltPatchFilter, QuiltError> {
+        let mut filters = vec![self.parse_and()?];
+        while self
+            .tokens
+            .next_if(|token| token.is_keyword("or"))
+            .is_some()
+        {
+            filters.push(self.parse_and()?);
+        }
+        Ok(combine(filters, QuiltPatchFilter::Or))
+    }
+
+    fn parse_and(&mut self) -> Result<QuiltPatchFilter, QuiltError> {
+        let mut filters = vec![self.parse_unary()?];
+        while self
+            .tokens
+            .next_if(|token| token.is_keyword("and"))
+            .is_some()
+        {
+            filters.push(self.parse_unary()?);
+        }
+        Ok(combine(filters, QuiltPatchFilter::And))
+    }
+
+    fn parse_unary(&mut self) -> Result<QuiltPatchFilter, QuiltError> {
+        self.depth += 1;
+        if self.depth > MAX_QUERY_DEPTH {
+            return Err(invalid_query(format!(
+                "the query is nested more than {MAX_QUERY_DEPTH} levels deep"
+            )));
+        }
+        let filter = match self.next_token("a predicate")? {
+            token if token.is_keyword("not") => {
+                QuiltPatchFilter::Not(Box::new(self.parse_unary()?))
+            }
+            token if token.is_keyword("has") => match self.parse_field()? {
+                QuiltPatchField::Tag(key) => QuiltPatchFilter::HasTag(key),
+                QuiltPatchField::Identifier => {
+                    return Err(invalid_query("expected 'tag:<key>' after 'has'"));
+                }
+            },
+            Token::LeftParen => {
+                let filter = self.parse_or()?;
+                match self.next_token("')'")? {
+                    Token::RightParen => filter,
+                    token => return Err(invalid_query(format!("expected ')', found {token}"))),
+                }
+            }
+            token => {
+                let field = self.field_from_token(token)?;
+                let comparison = match self.next_token("an operator")? {
+                    Token::Operator(comparison) => comparison,
+                    token => {
+                        return Err(invalid_query(format!(
+                            "expected an operator, found {token}"
+                        )));
+                    }
+                };
+                let value = match self.next_token("a value")? {
+                    Token::Word(value) | Token::Quoted(value) => value,
+                    token => {
+                        return Err(invalid_query(format!("expected a value, found {token}")));
+                    }
+                };
+                QuiltPatchFilter::Compare {
+                    field,
+                    comparison,
+                    value,
+                }
+            }
+        };
+        self.depth -= 1;
+        Ok(filter)
+    }
+
+    fn parse_field(&mut self) -> Result<QuiltPatchField, QuiltError> {
+        let token = self.next_token("a field")?;
+        self.field_from_token(token)
+    }
+
+    fn field_from_token(&mut self, token: Token) -> Result<QuiltPatchField, QuiltError> {
+        match token {
+            token if token.is_keyword("identifier") => Ok(QuiltPatchField::Identifier),
+            Token::Word(word) if word.starts_with("tag:") => {
+                let key = &word["tag:".len()..];
+                if !key.is_empty() {
+                    return Ok(QuiltPatchField::Tag(key.to_string()));
+                }
+                match self.next_token("a tag key")? {
+                    Token::Quoted(key) => Ok(QuiltPatchField::Tag(key)),
+                    token => Err(invalid_query(format!("expected a tag key, found {token}"))),
+                }
+            }
+            token => Err(invalid_query(format!(
+                "expected 'identifier' or 'tag:<key>', found {token}"
+            ))),
+        }
+    }
+
+    fn next_token(&mut self, expected: &str) -> Result<Token, QuiltError> {
+        self.tokens.next().ok_or_else(|| {
+            invalid_query(format!("unexpected end of the query, expected {expected}"))
+        })
+    }
+}
+
+/// Combines the filters with the given constructor, unless there is only a single filter.
+fn combine(
+    mut filters: Vec<QuiltPatchFilter>,
+    constructor: fn(Vec<QuiltPatchFilter>) -> QuiltPatchFilter,
+) -> QuiltPatchFilter {
+    if filters.len() == 1 {
+        filters.pop().expect("there is exactly one filter")
+    } else {
+        constructor(filters)
+    }
+}
+
+/// Compares two values numerically if both are numbers, and lexicographically otherwise.
+fn compare_values(left: &str, right: &str) -> Ordering {
+    match (left.parse::<f64>(), right.parse::<f64>()) {
+        (Ok(left), Ok(right)) if left.is_finite() && right.is_finite() => left.total_cmp(&right),
+        _ => left.cmp(right),
+    }
+}
+
+/// Returns true if the text matches the glob pattern, where `*` matches any sequence of
+/// characters and `?` matches a single character.
+fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // The position of the last `*` in the pattern, and the position in the text it is matched up
+    // to, to backtrack to if the remaining pattern does not match.
+    let mut backtrack = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some('?') => {
+                p += 1;
+                t += 1;
+            }
+            Some(c) if *c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    backtrack = Some((star, matched + 1));
+                    p = star + 1;
+                    t = matched + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use walrus_test_utils::param_test;
+
+    use super::*;
+
+    fn tags(tags: &[(&str, &str)]) -> BTreeMap<String, String> {
+        tags.iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    fn compare(
+        field: QuiltPatchField,
+        comparison: QuiltPatchComparison,
+        value: &str,
+    ) -> QuiltPatchFilter {
+        QuiltPatchFilter::Compare {
+            field,
+            comparison,
+            value: value.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_query() {
+        let filter: QuiltPatchFilter =
+            "tag:shard >= 10 AND tag:\"created at\"<\"2024-02-01\" and (identifier ~ *.csv or \
+            not has tag:raw)"
+                .parse()
+                .expect("query should be valid");
+        assert_eq!(
+            filter,
+            QuiltPatchFilter::And(vec![
+                compare(
+                    QuiltPatchField::Tag("shard".to_string()),
+                    QuiltPatchComparison::GreaterOrEqual,
+                    "10"
+                ),
+                compare(
+                    QuiltPatchField::Tag("created at".to_string()),
+                    QuiltPatchComparison::Less,
+                    "2024-02-01"
+                ),
+                QuiltPatchFilter::Or(vec![
+                    compare(
+                        QuiltPatchField::Identifier,
+                        QuiltPatchComparison::Glob,
+                        "*.csv"
+                    ),
+                    QuiltPatchFilter::Not(Box::new(QuiltPatchFilter::HasTag("raw".to_string()))),
+                ]),
+            ])
+        );
+        assert_eq!(
+            filter.to_string().parse::<QuiltPatchFilter>(),
+            Ok(filter),
+            "the displayed query should parse to the same filter"
+        );
+    }
+
+    param_test! {
+        test_parse_invalid_query: [
+            empty: (""),
+            missing_value: ("identifier ="),
+            missing_operator: ("identifier \"a\""),
+            unknown_field: ("name = a"),
+            unknown_operator: ("identifier ! a"),
+            unbalanced_parens: ("(identifier = a"),
+            trailing_token: ("identifier = a b"),
+            has_identifier: ("has identifier"),
+            unterminated_quote: ("identifier = \"a"),
+            too_deep: (&"not ".repeat(MAX_QUERY_DEPTH + 1)),
+        ]
+    }
+    fn test_parse_invalid_query(query: &str) {
+        assert!(matches!(
+            query.parse::<QuiltPatchFilter>(),
+            Err(QuiltError::InvalidQuery(_))
+        ));
+    }
+
+    param_test! {
+        test_query_matches: [
+            identifier_equal: ("identifier = data/a.csv", true),
+            identifier_not_equal: ("identifier != data/a.csv", false),
+            identifier_prefix: ("identifier ^= data/", true),
+            identifier_glob: ("identifier ~ \"data/?.*\"", true),
+            identifier_glob_mismatch: ("identifier ~ *.json", false),
+            numeric_range: ("tag:shard > 9 and tag:shard <= 10", true),
+            numeric_not_lexicographic: ("tag:shard > 9.5", true),
+            date_range: ("tag:date >= 2024-01-01 and tag:date < 2024-02-01", true),
+            has_tag: ("has tag:date", true),
+            missing_tag: ("tag:missing != x", false),
+            not_missing_tag: ("not has tag:missing", true),
+            or: ("tag:shard = 1 or tag:shard = 10", true),
+            precedence: ("tag:shard = 1 and has tag:date or identifier ^= data", true),
+        ]
+    }
+    fn test_query_matches(query: &str, expected: bool) {
+        let filter: QuiltPatchFilter = query.parse().expect("query should be valid");
+        let tags = tags(&[("shard", "10"), ("date", "2024-01-15")]);
+        assert_eq!(filter.matches("data/a.csv", &tags), expected);
+    }
+
+    param_test! {
+        test_glob_matches: [
+            exact: ("abc", "abc", true),
+            star: ("a*", "abc", true),
+            star_empty: ("a*c", "ac", true),
+            star_backtrack: ("*b*c", "abxbyc", true),
+            question_mark: ("a?c", "abc", true),
+            question_mark_empty: ("a?c", "ac", false),
+            mismatch: ("a*d", "abc", false),
+            unicode: ("*ü?", "grüße", false),
+        ]
+    }
+    fn test_glob_matches(pattern: &str, text: &str, expected: bool) {
+        assert_eq!(glob_matches(pattern, text), expected);
+    }
+}
@@ -426,6 +426,15 @@ impl<U> GeneralRecoverySymbol<U> {
             EitherDecodingSymbol::Secondary(_) => SliverType::Primary,
         }
     }
+
+    /// Returns a mutable reference to the data of the decoding symbol.
+    #[cfg(any(test, feature = "test-utils"))]
+    pub fn data_mut(&mut self) -> &mut Vec<u8> {
+        match &mut self.symbol {
+            EitherDecodingSymbol::Primary(symbol) => &mut symbol.data,
+            EitherDecodingSymbol::Secondary(symbol) => &mut symbol.data,
+        }
+    }
 }
 
 impl GeneralRecoverySymbol {
@@ -3,16 +3,13 @@
 
 //! Metadata associated with a Blob and stored by storage nodes.
 
-use alloc::{
-    collections::BTreeMap,
-    string::{String, ToString},
-    vec::Vec,
-};
+use alloc::{collections::BTreeMap, format, string::String, vec::Vec};
 use core::{fmt::Debug, num::NonZeroU16};
 
 use enum_dispatch::enum_dispatch;
 use fastcrypto::hash::{Blake2b256, HashFunction};
 use serde::{Deserialize, Serialize};
+use serde_with::{IfIsHumanReadable, base64::Base64, serde_as};
 
 use crate::{
     BlobId,
@@ -33,6 +30,7 @@ use crate::{
             QuiltPatchInternalIdApi,
             QuiltVersion,
             QuiltVersionV1,
+            QuiltVersionV2,
             validate_quilt_identifier,
         },
         source_symbols_for_n_shards,
@@ -86,6 +84,10 @@ impl QuiltPatchApi<QuiltVersionV1> for QuiltPatchV1 {
         &self.identifier
     }
 
+    fn tags(&self) -> &BTreeMap<String, String> {
+        &self.tags
+    }
+
     fn has_matched_tag(&self, target_tag: &str, target_value: &str) -> bool {
         self.tags.get(target_tag).map(|s| s.as_str()) == Some(target_value)
     }
@@ -138,6 +140,8 @@ impl QuiltPatchV1 {
 pub enum QuiltIndex {
     /// QuiltIndexV1.
     V1(QuiltIndexV1),
+    /// QuiltIndexV2.
+    V2(QuiltIndexV2),
 }
 
 impl QuiltIndex {
@@ -150,15 +154,39 @@ impl QuiltIndex {
             QuiltIndex::V1(quilt_index) => {
                 quilt_index.get_sliver_indices_for_identifiers(identifiers)
             }
+            QuiltIndex::V2(quilt_index) => {
+                quilt_index.get_sliver_indices_for_identifiers(identifiers)
+            }
         }
     }
 
     /// Returns the patches of the quilt index.
+    ///
+    /// The patches of a [`QuiltIndexV2`] are of a different type, so an empty slice is returned
+    /// for them; use [`QuiltIndexApi::patches`] on the version-specific index instead.
+    #[deprecated(
+        note = "returns an empty slice for quilt indices other than V1; use `len`, `is_empty`, \
+        or `QuiltIndexApi::patches` on the version-specific index instead"
+    )]
     pub fn patches(&self) -> &[QuiltPatchV1] {
         match self {
             QuiltIndex::V1(quilt_index) => &quilt_index.quilt_patches,
+            QuiltIndex::V2(_) => &[],
         }
     }
+
+    /// Returns the number of patches in the quilt index.
+    pub fn len(&self) -> usize {
+        match self {
+            QuiltIndex::V1(quilt_index) => quilt_index.len(),
+            QuiltIndex::V2(quilt_index) => quilt_index.len(),
+        }
+    }
+
+    /// Returns true if the quilt index has no patches.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
 }
 
 /// QuiltPatchInternalIdV1.
@@ -182,39 +210,16 @@ pub struct QuiltPatchInternalIdV1 {
 /// ```
 impl QuiltPatchInternalIdApi for QuiltPatchInternalIdV1 {
     fn to_bytes(&self) -> Vec<u8> {
-        let mut bytes = Vec::with_capacity(5);
-
-        // First byte is the version byte.
-        bytes.push(QuiltVersionV1::quilt_version_byte());
-
-        bytes.extend_from_slice(&u16::to_le_bytes(self.start_index));
-        bytes.extend_from_slice(&u16::to_le_bytes(self.end_index));
-
-        bytes
+        patch_internal_id_to_bytes(
+            QuiltVersionV1::quilt_version_byte(),
+            self.start_index,
+            self.end_index,
+        )
     }
 
     fn from_bytes(bytes: &[u8]) -> Result<Self, QuiltError> {
-        if bytes.len() != 5 {
-            return Err(QuiltError::Other(
-                "QuiltPatchInternalIdV1 requires 5 bytes".to_string(),
-            ));
-        }
-
-        // Check version byte.
-        if bytes[0] != QuiltVersionV1::quilt_version_byte() {
-            return Err(QuiltError::QuiltVersionMismatch(
-                bytes[0],
-                QuiltVersionV1::quilt_version_byte(),
-            ));
-        }
-        let start_index = u16::from_le_bytes(
-            bytes[1..3]
-                .try_into()
-                .expect("start_bytes should be 2 bytes"),
-        );
-        let end_index =
-            u16::from_le_bytes(bytes[3..5].try_into().expect("end_bytes should be 2 bytes"));
-
+        let (start_index, end_index) =
+            patch_internal_id_from_bytes(bytes, QuiltVersionV1::quilt_version_byte())?;
         Ok(Self {
             start_index,
             end_index,
@@ -228,6 +233,45 @@ impl QuiltPatchInternalIdApi for QuiltPatchInternalIdV1 {
     }
 }
 
+/// The number of bytes of a serialized quilt patch internal id.
+const QUILT_PATCH_INTERNAL_ID_LENGTH: usize = 5;
+
+/// Serializes a quilt patch internal id consisting of the version byte and the sliver range.
+fn patch_internal_id_to_bytes(version_byte: u8, start_index: u16, end_index: u16) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(QUILT_PATCH_INTERNAL_ID_LENGTH);
+
+    // First byte is the version byte.
+    bytes.push(version_byte);
+
+    bytes.extend_from_slice(&u16::to_le_bytes(start_index));
+    bytes.extend_from_slice(&u16::to_le_bytes(end_index));
+
+    bytes
+}
+
+/// Deserializes the sliver range of a quilt patch internal id, checking the version byte.
+fn patch_internal_id_from_bytes(bytes: &[u8], version_byte: u8) -> Result<(u16, u16), QuiltError> {
+    if bytes.len() != QUILT_PATCH_INTERNAL_ID_LENGTH {
+        return Err(QuiltError::Other(format!(
+            "quilt patch internal id requires {QUILT_PATCH_INTERNAL_ID_LENGTH} bytes"
+        )));
+    }
+
+    // Check version byte.
+    if bytes[0] != version_byte {
+        return Err(QuiltError::QuiltVersionMismatch(bytes[0], version_byte));
+    }
+    let start_index = u16::from_le_bytes(
+        bytes[1..3]
+            .try_into()
+            .expect("start_bytes should be 2 bytes"),
+    );
+    let end_index =
+        u16::from_le_bytes(bytes[3..5].try_into().expect("end_bytes should be 2 bytes"));
+
+    Ok((start_index, end_index))
+}
+
 impl QuiltPatchInternalIdV1 {
     /// Creates a new quilt patch id.
     pub fn new(start_index: u16, end_index: u16) -> Self {
@@ -292,13 +336,40 @@ impl QuiltIndexV1 {
 pub enum QuiltMetadata {
     /// Version 1 of the quilt metadata.
     V1(QuiltMetadataV1),
+    /// Version 2 of the quilt metadata.
+    V2(QuiltMetadataV2),
 }
 
 impl QuiltMetadata {
+    /// Creates the quilt metadata matching the version of the quilt index.
+    pub fn new(quilt_id: BlobId, metadata: BlobMetadata, quilt_index: QuiltIndex) -> Self {
+        match quilt_index {
+            QuiltIndex::V1(index) => QuiltMetadata::V1(QuiltMetadataV1 {
+                quilt_id,
+                metadata,
+                index,
+            }),
+            QuiltIndex::V2(index) => QuiltMetadata::V2(QuiltMetadataV2 {
+                quilt_id,
+                metadata,
+                index,
+            }),
+        }
+    }
+
     /// Returns the verified metadata for the quilt blob.
     pub fn get_verified_metadata(&self) -> VerifiedBlobMetadataWithId {
         match self {
             QuiltMetadata::V1(quilt_metadata_v1) => quilt_metadata_v1.get_verified_metadata(),
+            QuiltMetadata::V2(quilt_metadata_v2) => quilt_metadata_v2.get_verified_metadata(),
+        }
+    }
+
+    /// Returns the quilt index.
+    pub fn index(&self) -> QuiltIndex {
+        match self {
+            QuiltMetadata::V1(quilt_metadata_v1) => quilt_metadata_v1.index.clone().into(),
+            QuiltMetadata::V2(quilt_metadata_v2) => quilt_metadata_v2.index.clone().into(),
         }
     }
 }
@@ -321,6 +392,203 @@ impl QuiltMetadataV1 {
     }
 }
 
+/// Represents a blob within a unencoded quilt of version 2.
+///
+/// In addition to the fields of [`QuiltPatchV1`], the patch stores the hash of the blob data, which
+/// allows verifying a single patch without decoding the rest of the quilt.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuiltPatchV2 {
+    /// The start sliver index of the blob.
+    #[serde(skip)]
+    pub start_index: u16,
+    /// The end sliver index of the blob.
+    pub end_index: u16,
+    /// The identifier of the blob, it can be used to locate the blob in the quilt.
+    pub identifier: String,
+    /// The tags of the blob.
+    //
+    // A BTreeMap is used to ensure deterministic serialization.
+    pub tags: BTreeMap<String, String>,
+    /// The Blake2b256 hash of the blob data.
+    #[serde_as(as = "IfIsHumanReadable<Base64>")]
+    pub hash: [u8; DIGEST_LEN],
+}
+
+impl QuiltPatchApi<QuiltVersionV2> for QuiltPatchV2 {
+    fn quilt_patch_internal_id(&self) -> QuiltPatchInternalIdV2 {
+        QuiltPatchInternalIdV2::new(self.start_index, self.end_index)
+    }
+
+    fn identifier(&self) -> &str {
+        &self.identifier
+    }
+
+    fn tags(&self) -> &BTreeMap<String, String> {
+        &self.tags
+    }
+
+    fn has_matched_tag(&self, target_tag: &str, target_value: &str) -> bool {
+        self.tags.get(target_tag).map(|s| s.as_str()) == Some(target_value)
+    }
+
+    fn sliver_indices(&self) -> Vec<SliverIndex> {
+        (self.start_index..self.end_index)
+            .map(SliverIndex::new)
+            .collect()
+    }
+}
+
+impl QuiltPatchV2 {
+    /// Creates a new [`QuiltPatchV2`] for the given blob data with tags.
+    pub fn new_with_tags<T: IntoIterator<Item = (String, String)>>(
+        identifier: String,
+        tags: T,
+        data: &[u8],
+    ) -> Result<Self, QuiltError> {
+        validate_quilt_identifier(&identifier)?;
+
+        Ok(Self {
+            identifier,
+            start_index: 0,
+            end_index: 0,
+            tags: tags.into_iter().collect(),
+            hash: Self::compute_hash(data),
+        })
+    }
+
+    /// Sets the range of the quilt patch.
+    pub fn set_range(&mut self, start_index: u16, end_index: u16) {
+        self.start_index = start_index;
+        self.end_index = end_index;
+    }
+
+    /// Computes the hash of the blob data stored in a patch.
+    pub fn compute_hash(data: &[u8]) -> [u8; DIGEST_LEN] {
+        Blake2b256::digest(data).digest
+    }
+
+    /// Verifies that the given blob data matches the hash of the patch.
+    pub fn verify(&self, data: &[u8]) -> Result<(), QuiltError> {
+        if Self::compute_hash(data) != self.hash {
+            return Err(QuiltError::PatchHashMismatch(self.identifier.clone()));
+        }
+        Ok(())
+    }
+}
+
+/// QuiltPatchInternalIdV2.
+///
+/// The layout is the same as for [`QuiltPatchInternalIdV1`], with the version byte of
+/// QuiltVersionV2.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuiltPatchInternalIdV2 {
+    /// The start index of the patch.
+    pub start_index: u16,
+    /// The end index of the patch.
+    pub end_index: u16,
+}
+
+impl QuiltPatchInternalIdApi for QuiltPatchInternalIdV2 {
+    fn to_bytes(&self) -> Vec<u8> {
+        patch_internal_id_to_bytes(
+            QuiltVersionV2::quilt_version_byte(),
+            self.start_index,
+            self.end_index,
+        )
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, QuiltError> {
+        let (start_index, end_index) =
+            patch_internal_id_from_bytes(bytes, QuiltVersionV2::quilt_version_byte())?;
+        Ok(Self {
+            start_index,
+            end_index,
+        })
+    }
+
+    fn sliver_indices(&self) -> Vec<SliverIndex> {
+        (self.start_index..self.end_index)
+            .map(SliverIndex::new)
+            .collect()
+    }
+}
+
+impl QuiltPatchInternalIdV2 {
+    /// Creates a new quilt patch id.
+    pub fn new(start_index: u16, end_index: u16) -> Self {
+        Self {
+            start_index,
+            end_index,
+        }
+    }
+}
+
+/// An index over the [patches][QuiltPatchV2] (blobs) in a quilt of version 2.
+// INV: The patches are sorted by their end indices.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuiltIndexV2 {
+    /// Location/identity index of the blob in the quilt.
+    pub quilt_patches: Vec<QuiltPatchV2>,
+}
+
+impl QuiltIndexApi<QuiltVersionV2> for QuiltIndexV2 {
+    fn patches(&self) -> &[QuiltPatchV2] {
+        &self.quilt_patches
+    }
+
+    fn identifiers(&self) -> impl Iterator<Item = &str> {
+        self.quilt_patches
+            .iter()
+            .map(|patch| patch.identifier.as_str())
+    }
+
+    fn len(&self) -> usize {
+        self.quilt_patches.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.quilt_patches.is_empty()
+    }
+}
+
+impl From<QuiltIndexV2> for QuiltIndex {
+    fn from(quilt_index: QuiltIndexV2) -> Self {
+        QuiltIndex::V2(quilt_index)
+    }
+}
+
+impl QuiltIndexV2 {
+    /// Populate start_indices of the patches, since the start index is not stored in wire format.
+    pub fn populate_start_indices(&mut self, first_start: u16) {
+        let mut prev_end_index = first_start;
+        for patch in self.quilt_patches.iter_mut() {
+            patch.start_index = prev_end_index;
+            prev_end_index = patch.end_index;
+        }
+    }
+}
+
+/// Metadata associated with a quilt of version 2.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuiltMetadataV2 {
+    /// The BlobId of the quilt blob.
+    pub quilt_id: BlobId,
+    /// The blob metadata of the quilt blob.
+    pub metadata: BlobMetadata,
+    /// The index of the quilt.
+    pub index: QuiltIndexV2,
+}
+
+impl QuiltMetadataV2 {
+    /// Returns the verified metadata for the quilt blob.
+    pub fn get_verified_metadata(&self) -> VerifiedBlobMetadataWithId {
+        VerifiedBlobMetadataWithId::new_verified_unchecked(self.quilt_id, self.metadata.clone())
+    }
+}
+
 /// [`BlobMetadataWithId`] that has been verified with [`UnverifiedBlobMetadataWithId::verify`].
 ///
 /// This ensures the following properties:
@@ -69,15 +69,19 @@ use walrus_sdk::{
     store_optimizations::StoreOptimizations,
     upload_relay::tip_config::{TipConfig, TipKind},
 };
-use walrus_service::test_utils::{
-    StorageNodeHandleTrait,
-    TestNodesConfig,
-    test_cluster::{self, FROST_PER_NODE_WEIGHT},
+use walrus_service::{
+    node::byzantine::ByzantineConfig,
+    test_utils::{
+        StorageNodeHandleTrait,
+        TestNodesConfig,
+        test_cluster::{self, FROST_PER_NODE_WEIGHT},
+    },
 };
 use walrus_storage_node_client::api::BlobStatus;
 use walrus_sui::{
     client::{
         BlobPersistence,
+        CreditsPolicy,
         ExpirySelectionPolicy,
         PostStoreAction,
         ReadClient,
@@ -288,16 +292,65 @@ async_param_test! {
 }
 /// Stores a blob that is inconsistent in 1 shard.
 async fn test_inconsistency(failed_nodes: &[usize]) -> TestResult {
+    run_inconsistency_test(failed_nodes, &[], ByzantineConfig::default()).await
+}
+
+async_param_test! {
+    #[ignore = "ignore E2E tests by default"]
+    #[walrus_simtest]
+    test_inconsistency_with_byzantine_node -> TestResult : [
+        corrupt_slivers: (ByzantineConfig {
+            corrupt_slivers: true,
+            ..Default::default()
+        }),
+        wrong_metadata: (ByzantineConfig {
+            wrong_metadata: true,
+            ..Default::default()
+        }),
+        bogus_recovery_symbols: (ByzantineConfig {
+            bogus_recovery_symbols: true,
+            ..Default::default()
+        }),
+        slow_replies: (ByzantineConfig {
+            reply_delay: Some(Duration::from_millis(200)),
+            ..Default::default()
+        }),
+    ]
+}
+/// Stores a blob that is inconsistent in 1 shard while a node with f shards is byzantine, and
+/// checks that the honest nodes still generate an inconsistency proof.
+async fn test_inconsistency_with_byzantine_node(byzantine_config: ByzantineConfig) -> TestResult {
+    run_inconsistency_test(&[], &[4], byzantine_config).await
+}
+
+/// Stores a blob that is inconsistent in 1 shard, not held by the failed or byzantine nodes.
+///
+/// The failed nodes are stopped after the blob is certified, while the byzantine nodes misbehave
+/// according to the provided config during the whole test.
+async fn run_inconsistency_test(
+    failed_nodes: &[usize],
+    byzantine_nodes: &[usize],
+    byzantine_config: ByzantineConfig,
+) -> TestResult {
     telemetry_subscribers::init_for_testing();
 
     let (_sui_cluster_handle, mut cluster, mut client, _) =
         test_cluster::E2eTestSetupBuilder::new().build().await?;
+    byzantine_nodes.iter().for_each(|&idx| {
+        cluster.nodes[idx]
+            .byzantine_mode
+            .set(byzantine_config.clone())
+    });
 
     // Store a blob and get confirmations from each node.
     let blob = walrus_test_utils::random_data(31415);
 
-    // Find the shards of the failed nodes.
-    let failed_node_names: Vec<String> = failed_nodes.iter().map(|i| format!("node-{i}")).collect();
+    // Find the shards of the failed and byzantine nodes.
+    let failed_node_names: Vec<String> = failed_nodes
+        .iter()
+        .chain(byzantine_nodes)
+        .map(|i| format!("node-{i}"))
+        .collect();
     let committees = client
         .as_ref()
         .get_committees()
@@ -403,6 +456,97 @@ async fn test_inconsistency(failed_nodes: &[usize]) -> TestResult {
     Ok(())
 }
 
+async_param_test! {
+    #[ignore = "ignore E2E tests by default"]
+    #[walrus_simtest]
+    test_store_and_read_with_byzantine_node -> TestResult : [
+        corrupt_slivers: (ByzantineConfig {
+            corrupt_slivers: true,
+            ..Default::default()
+        }),
+        wrong_metadata: (ByzantineConfig {
+            wrong_metadata: true,
+            ..Default::default()
+        }),
+        refuse_confirmations: (ByzantineConfig {
+            refuse_confirmations: true,
+            ..Default::default()
+        }),
+        confirm_unstored_blobs: (ByzantineConfig {
+            confirm_unstored_blobs: true,
+            ..Default::default()
+        }),
+        slow_replies: (ByzantineConfig {
+            reply_delay: Some(Duration::from_millis(200)),
+            ..Default::default()
+        }),
+        bogus_recovery_symbols: (ByzantineConfig {
+            bogus_recovery_symbols: true,
+            ..Default::default()
+        }),
+        all_misbehaviours: (ByzantineConfig {
+            corrupt_slivers: true,
+            wrong_metadata: true,
+            refuse_confirmations: true,
+            reply_delay: Some(Duration::from_millis(200)),
+            bogus_recovery_symbols: true,
+            ..Default::default()
+        }),
+    ]
+}
+/// Stores and reads blobs while the node with f shards is byzantine.
+///
+/// Checks that the client detects the corrupted data served by the byzantine node.
+async fn test_store_and_read_with_byzantine_node(byzantine_config: ByzantineConfig) -> TestResult {
+    telemetry_subscribers::init_for_testing();
+
+    let (_sui_cluster_handle, cluster, client, _) =
+        test_cluster::E2eTestSetupBuilder::new().build().await?;
+    let byzantine_node = &cluster.nodes[4];
+    byzantine_node.byzantine_mode.set(byzantine_config.clone());
+
+    basic_store_and_read(&client, 2, 31415, None, || Ok(())).await?;
+
+    // Store one more blob and check that the client rejects the data of the byzantine node.
+    let blob = walrus_test_utils::random_data(31415);
+    let store_result = client
+        .as_ref()
+        .reserve_and_store_blobs(
+            &[blob.as_slice()],
+            &StoreArgs::default_with_epochs(1).no_store_optimizations(),
+        )
+        .await?;
+    let blob_id = store_result[0]
+        .blob_id()
+        .expect("the blob should have been stored");
+    let encoding_config = client.as_ref().encoding_config();
+
+    if byzantine_config.wrong_metadata {
+        byzantine_node
+            .client()
+            .get_and_verify_metadata(&blob_id, encoding_config)
+            .await
+            .expect_err("the wrong metadata should be detected");
+    }
+    if byzantine_config.corrupt_slivers {
+        let metadata = cluster.nodes[0]
+            .client()
+            .get_and_verify_metadata(&blob_id, encoding_config)
+            .await?;
+        let shard = byzantine_node.storage_node().existing_shards().await[0];
+        let sliver_pair_index = shard.to_pair_index(encoding_config.n_shards(), &blob_id);
+        byzantine_node
+            .client()
+            .get_and_verify_sliver::<Primary>(sliver_pair_index, &metadata, encoding_config)
+            .await
+            .expect_err("the corrupted sliver should be detected");
+    }
+
+    assert_eq!(client.as_ref().read_blob::<Primary>(&blob_id).await?, blob);
+
+    Ok(())
+}
+
 fn error_kind_matches(actual: &ClientErrorKind, expected: &ClientErrorKind) -> bool {
     match (actual, expected) {
         (
@@ -765,7 +909,7 @@ async fn test_store_with_existing_storage_resource(
             let resource = client
                 .as_ref()
                 .sui_client()
-                .reserve_space(*encoded_size, epochs_ahead_registered)
+                .reserve_space(*encoded_size, epochs_ahead_registered, CreditsPolicy::Auto)
                 .await
                 .expect("reserve space should not fail");
             resource.id
@@ -1003,7 +1147,9 @@ async fn test_store_quilt(blobs_to_create: u32) -> TestResult {
 
     let blob_id = blob_object.blob_id;
     let quilt_metadata = quilt_client.get_quilt_metadata(&blob_id).await?;
-    let QuiltMetadata::V1(metadata_v1) = quilt_metadata;
+    let QuiltMetadata::V1(metadata_v1) = quilt_metadata else {
+        panic!("expected quilt metadata V1");
+    };
     assert_eq!(&metadata_v1.index, quilt.quilt_index()?);
 
     let mut identifiers = stored_quilt_blobs
@@ -1186,18 +1332,33 @@ async fn test_blob_operations_with_credits() -> TestResult {
     // Store a blob with credits
     let blob_data = walrus_test_utils::random_data(314);
     let blobs = vec![blob_data.as_slice()];
-    let store_args = StoreArgs::default_with_epochs(1).no_store_optimizations();
+    let store_args = StoreArgs::default_with_epochs(1)
+        .no_store_optimizations()
+        .with_credits_policy(CreditsPolicy::Force);
     let store_result = client.reserve_and_store_blobs(&blobs, &store_args).await?;
 
     let blob_object = match &store_result[0] {
-        BlobStoreResult::NewlyCreated { blob_object, .. } => blob_object.clone(),
+        BlobStoreResult::NewlyCreated {
+            blob_object,
+            credits_discount,
+            ..
+        } => {
+            assert!(
+                credits_discount.is_some(),
+                "the credits discount should be reported"
+            );
+            blob_object.clone()
+        }
         _ => panic!("Expected newly created blob"),
     };
 
     let initial_storage = blob_object.storage.clone();
 
     // Extend blob storage with credits
-    client.sui_client().extend_blob(blob_object.id, 5).await?;
+    client
+        .sui_client()
+        .extend_blob(blob_object.id, 5, CreditsPolicy::Force)
+        .await?;
 
     // Verify blob storage was extended with credits
     let extended_blob: Blob = client
@@ -1565,7 +1726,7 @@ async fn test_extend_owned_blobs() -> TestResult {
     client
         .as_ref()
         .sui_client()
-        .extend_blob(blob_object_id, 5)
+        .extend_blob(blob_object_id, 5, CreditsPolicy::Auto)
         .await?;
 
     let extended_blob_object: Blob = client
@@ -11,9 +11,10 @@ use serde::{Deserialize, Serialize};
 use crate::error::CloudProviderResult;
 
 pub mod aws;
+pub mod local;
 pub mod vultr;
 
-#[derive(Debug, Deserialize, Clone, Eq, PartialEq, Hash)]
+#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq, Hash)]
 pub enum InstanceStatus {
     Active,
     Inactive,
@@ -31,7 +32,7 @@ impl From<&str> for InstanceStatus {
 }
 
 /// Represents a cloud provider instance.
-#[derive(Debug, Deserialize, Clone, Eq, PartialEq, Hash)]
+#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq, Hash)]
 pub struct Instance {
     /// The unique identifier of the instance.
     pub id: String,
@@ -63,16 +64,38 @@ impl Instance {
         matches!(self.status, InstanceStatus::Terminated)
     }
 
+    /// Return whether the instance runs on the local machine (and thus shares its network
+    /// interfaces with the other local instances).
+    pub fn is_local(&self) -> bool {
+        self.main_ip.is_loopback()
+    }
+
     /// Return the ssh address to connect to the instance.
     pub fn ssh_address(&self) -> SocketAddr {
         SocketAddr::new(self.main_ip.into(), 22)
     }
+
+    /// Create a new instance for testing.
+    #[cfg(test)]
+    pub fn new_for_test(id: String) -> Self {
+        Self {
+            id,
+            region: String::new(),
+            main_ip: Ipv4Addr::LOCALHOST,
+            tags: Vec::new(),
+            specs: String::new(),
+            status: InstanceStatus::Active,
+        }
+    }
 }
 
 pub trait ServerProviderClient: Display {
     /// The username used to connect to the instances.
     const USERNAME: &'static str;
 
+    /// Whether the instances are remote machines reachable over ssh.
+    const REMOTE: bool = true;
+
     /// List all existing instances (regardless of their status).
     async fn list_instances(&self) -> CloudProviderResult<Vec<Instance>>;
 
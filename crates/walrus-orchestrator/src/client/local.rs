@@ -0,0 +1,274 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    env,
+    fmt::Display,
+    fs,
+    io,
+    net::Ipv4Addr,
+    os::unix,
+    path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+
+use super::{Instance, InstanceStatus, ServerProviderClient};
+use crate::{
+    error::{CloudProviderError, CloudProviderResult},
+    settings::Settings,
+    ssh::LocalConnection,
+};
+
+/// Make an error signaling the local testbed directory cannot be accessed.
+impl From<io::Error> for CloudProviderError {
+    fn from(e: io::Error) -> Self {
+        Self::LocalTestbedError(e.to_string())
+    }
+}
+
+/// Make an error signaling the local testbed holds corrupted instance files.
+impl From<serde_yaml::Error> for CloudProviderError {
+    fn from(e: serde_yaml::Error) -> Self {
+        Self::LocalTestbedError(e.to_string())
+    }
+}
+
+/// A client running the instances of the testbed as processes on the local machine.
+///
+/// Each instance is a directory of the testbed directory that serves as home directory for all
+/// the commands executed on the instance. Instances are assigned distinct loopback addresses and
+/// their description is persisted next to their home directory, so that the testbed outlives the
+/// orchestrator.
+pub struct LocalClient {
+    settings: Settings,
+    testbed_dir: PathBuf,
+}
+
+impl Display for LocalClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Local processes ({})", self.testbed_dir.display())
+    }
+}
+
+impl LocalClient {
+    /// The loopback address from which the addresses of the instances are derived.
+    const BASE_ADDRESS: Ipv4Addr = Ipv4Addr::new(127, 1, 0, 0);
+
+    /// Make a new local client.
+    pub fn new(settings: Settings) -> Self {
+        let testbed_dir = Self::testbed_dir(&settings);
+        Self {
+            settings,
+            testbed_dir,
+        }
+    }
+
+    /// The directory hosting the instances of the testbed.
+    pub fn testbed_dir(settings: &Settings) -> PathBuf {
+        let dir = settings.local_dir.join(&settings.testbed_id);
+        std::path::absolute(&dir).unwrap_or(dir)
+    }
+
+    /// The path of the file describing the specified instance.
+    fn instance_file(&self, id: &str) -> PathBuf {
+        self.testbed_dir.join(format!("{id}.yaml"))
+    }
+
+    /// The home directory of the specified instance.
+    fn instance_home(&self, id: &str) -> PathBuf {
+        self.testbed_dir.join(id)
+    }
+
+    /// Persist the description of an instance.
+    fn save(&self, instance: &Instance) -> CloudProviderResult<()> {
+        let content = serde_yaml::to_string(instance)?;
+        fs::write(self.instance_file(&instance.id), content)?;
+        Ok(())
+    }
+
+    /// Set the status of the specified instances.
+    fn set_status<'a, I>(&self, instances: I, status: InstanceStatus) -> CloudProviderResult<()>
+    where
+        I: Iterator<Item = &'a Instance>,
+    {
+        for instance in instances {
+            let mut instance = instance.clone();
+            instance.status = status.clone();
+            self.save(&instance)?;
+        }
+        Ok(())
+    }
+
+    /// Kill all processes running on the specified instance.
+    fn kill_processes(&self, instance: &Instance) {
+        let home = self.instance_home(&instance.id);
+        if home.exists() {
+            let connection = LocalConnection::new(instance.ssh_address(), home);
+            // This command fails if there is no tmux server running on the instance.
+            let _ = connection.execute("tmux kill-server".into());
+        }
+    }
+
+    /// Create the home directory of a new instance. Local instances share the toolchain and the
+    /// repository of the local machine, which should thus already be built.
+    fn setup_home(&self, home: &Path) -> CloudProviderResult<()> {
+        let working_dir = &self.settings.working_dir;
+        let working_dir = working_dir.strip_prefix("~").unwrap_or(working_dir);
+        fs::create_dir_all(home.join(working_dir))?;
+
+        if let Some(user_home) = env::var_os("HOME") {
+            let cargo_home = PathBuf::from(user_home).join(".cargo");
+            if cargo_home.exists() {
+                unix::fs::symlink(cargo_home, home.join(".cargo"))?;
+            }
+        }
+
+        let repository = env::current_dir()?;
+        unix::fs::symlink(repository, home.join(self.settings.repository_name()))?;
+        Ok(())
+    }
+}
+
+impl ServerProviderClient for LocalClient {
+    const USERNAME: &'static str = "local";
+    const REMOTE: bool = false;
+
+    async fn list_instances(&self) -> CloudProviderResult<Vec<Instance>> {
+        if !self.testbed_dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut instances = Vec::new();
+        for entry in fs::read_dir(&self.testbed_dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|x| x == "yaml") {
+                let content = fs::read(&path)?;
+                instances.push(serde_yaml::from_slice::<Instance>(&content)?);
+            }
+        }
+        instances.sort_by_key(|x| x.id.parse::<u32>().unwrap_or(u32::MAX));
+        Ok(instances)
+    }
+
+    async fn start_instances<'a, I>(&self, instances: I) -> CloudProviderResult<()>
+    where
+        I: Iterator<Item = &'a Instance> + Send,
+    {
+        self.set_status(instances, InstanceStatus::Active)
+    }
+
+    async fn stop_instances<'a, I>(&self, instances: I) -> CloudProviderResult<()>
+    where
+        I: Iterator<Item = &'a Instance> + Send,
+    {
+        let instances: Vec<_> = instances.collect();
+        for instance in &instances {
+            self.kill_processes(instance);
+        }
+        self.set_status(instances.into_iter(), InstanceStatus::Inactive)
+    }
+
+    async fn create_instance<S>(&self, region: S) -> CloudProviderResult<Instance>
+    where
+        S: Into<String> + Serialize + Send,
+    {
+        fs::create_dir_all(&self.testbed_dir)?;
+        let index = self
+            .list_instances()
+            .await?
+            .iter()
+            .filter_map(|x| x.id.parse::<u32>().ok())
+            .max()
+            .unwrap_or(0)
+            + 1;
+
+        let instance = Instance {
+            id: index.to_string(),
+            region: region.into(),
+            main_ip: Ipv4Addr::from(u32::from(Self::BASE_ADDRESS) + index),
+            tags: vec![self.settings.testbed_id.clone()],
+            specs: self.settings.specs.clone(),
+            status: InstanceStatus::Active,
+        };
+        self.setup_home(&self.instance_home(&instance.id))?;
+        self.save(&instance)?;
+        Ok(instance)
+    }
+
+    async fn delete_instance(&self, instance: Instance) -> CloudProviderResult<()> {
+        self.kill_processes(&instance);
+        let home = self.instance_home(&instance.id);
+        if home.exists() {
+            fs::remove_dir_all(home)?;
+        }
+        fs::remove_file(self.instance_file(&instance.id))?;
+        Ok(())
+    }
+
+    async fn register_ssh_public_key(&self, _public_key: String) -> CloudProviderResult<()> {
+        Ok(())
+    }
+
+    async fn instance_setup_commands(&self) -> CloudProviderResult<Vec<String>> {
+        Ok(Vec::new())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tempfile::TempDir;
+
+    use super::LocalClient;
+    use crate::{client::ServerProviderClient, settings::Settings, testbed::Testbed};
+
+    /// Returns the settings for local instances, together with the guard of their directory.
+    fn local_settings() -> (TempDir, Settings) {
+        let local_dir = tempfile::tempdir().unwrap();
+        let mut settings = Settings::new_for_test();
+        settings.local_dir = local_dir.path().to_path_buf();
+        settings.regions = vec!["local".into()];
+        (local_dir, settings)
+    }
+
+    #[tokio::test]
+    async fn deploy_and_destroy() {
+        let (_local_dir, settings) = local_settings();
+        let mut testbed = Testbed::new(settings.clone(), LocalClient::new(settings.clone()))
+            .await
+            .unwrap();
+
+        testbed.deploy(3, None).await.unwrap();
+
+        let instances = LocalClient::new(settings.clone())
+            .list_instances()
+            .await
+            .unwrap();
+        assert_eq!(instances.len(), 3);
+        for (i, instance) in instances.iter().enumerate() {
+            assert_eq!((i + 1).to_string(), instance.id);
+            assert!(instance.is_active() && instance.is_local());
+        }
+
+        testbed.destroy().await.unwrap();
+
+        let client = LocalClient::new(settings);
+        assert!(client.list_instances().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn stop_and_start() {
+        let (_local_dir, settings) = local_settings();
+        let mut testbed = Testbed::new(settings.clone(), LocalClient::new(settings))
+            .await
+            .unwrap();
+        testbed.deploy(2, None).await.unwrap();
+
+        testbed.stop().await.unwrap();
+        assert!(testbed.instances().iter().all(|x| x.is_inactive()));
+
+        testbed.start(1).await.unwrap();
+        let active = testbed.instances().iter().filter(|x| x.is_active()).count();
+        assert_eq!(active, 1);
+    }
+}
@@ -32,6 +32,9 @@ pub enum CloudProviderError {
 
     #[error("SSH key \"{0}\" not found")]
     SshKeyNotFound(String),
+
+    #[error("Failed to access local testbed: {0}")]
+    LocalTestbedError(String),
 }
 
 pub type SshResult<T> = Result<T, SshError>;
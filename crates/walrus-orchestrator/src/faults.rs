@@ -1,174 +1,414 @@
 // Copyright (c) Walrus Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use std::{
-    fmt::{Debug, Display},
-    time::Duration,
-};
+use std::{fmt::Display, time::Duration};
 
 use serde::{Deserialize, Serialize};
+use serde_with::{DurationMilliSeconds, DurationSeconds, serde_as};
 
 use crate::client::Instance;
 
-#[derive(Clone, Serialize, Deserialize, Hash, PartialEq, Eq)]
-pub enum FaultsType {
-    /// Permanently crash the maximum number of nodes from the beginning.
-    Permanent { faults: usize },
-    /// Progressively crash and recover nodes.
-    CrashRecovery {
-        max_faults: usize,
-        interval: Duration,
-    },
+/// The direction of the traffic dropped by a network partition.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PartitionDirection {
+    /// Drop the packets received from the partitioned hosts.
+    Inbound,
+    /// Drop the packets sent to the partitioned hosts.
+    Outbound,
+    /// Drop all packets exchanged with the partitioned hosts.
+    #[default]
+    Both,
 }
 
-impl Default for FaultsType {
-    fn default() -> Self {
-        Self::Permanent { faults: 0 }
-    }
+/// The network degradation to inject on an instance.
+#[serde_as]
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum NetworkFaultKind {
+    /// Delay outgoing packets by a fixed latency plus a uniformly distributed jitter.
+    Delay {
+        #[serde_as(as = "DurationMilliSeconds")]
+        latency: Duration,
+        #[serde_as(as = "DurationMilliSeconds")]
+        #[serde(default)]
+        jitter: Duration,
+    },
+    /// Drop the specified percentage of outgoing packets.
+    Loss { percent: f64 },
+    /// Cap the outgoing bandwidth (in Mbit/s).
+    Bandwidth { mbit: u32 },
+    /// Drop all packets exchanged with the target hosts in the specified direction.
+    Partition {
+        #[serde(default)]
+        direction: PartitionDirection,
+    },
 }
 
-impl Debug for FaultsType {
+impl Display for NetworkFaultKind {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::Permanent { faults } => write!(f, "{faults}"),
-            Self::CrashRecovery {
-                max_faults,
-                interval,
-            } => write!(f, "{max_faults}-{}cr", interval.as_secs()),
+            Self::Delay { latency, jitter } => write!(
+                f,
+                "delay {}ms ±{}ms",
+                latency.as_millis(),
+                jitter.as_millis()
+            ),
+            Self::Loss { percent } => write!(f, "loss {percent}%"),
+            Self::Bandwidth { mbit } => write!(f, "bandwidth {mbit}Mbit/s"),
+            Self::Partition { direction } => write!(f, "partition ({direction:?})"),
         }
     }
 }
 
-impl Display for FaultsType {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+/// When a network fault is active, relative to the beginning of the benchmark. To degrade the
+/// network during epoch changes, align the schedule with the epoch duration of the network.
+#[serde_as]
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FaultSchedule {
+    /// The fault is active during the whole benchmark.
+    #[default]
+    Always,
+    /// The fault is active once, during the specified window.
+    Window {
+        #[serde_as(as = "DurationSeconds")]
+        start: Duration,
+        #[serde_as(as = "DurationSeconds")]
+        duration: Duration,
+    },
+    /// The fault is active for `duration` every `period`, starting at `start`.
+    Periodic {
+        #[serde_as(as = "DurationSeconds")]
+        start: Duration,
+        #[serde_as(as = "DurationSeconds")]
+        period: Duration,
+        #[serde_as(as = "DurationSeconds")]
+        duration: Duration,
+    },
+    /// Starting at `start`, the fault is active on a single target instance at the time and
+    /// moves to the next target instance every `period`.
+    Rolling {
+        #[serde_as(as = "DurationSeconds")]
+        start: Duration,
+        #[serde_as(as = "DurationSeconds")]
+        period: Duration,
+    },
+}
+
+impl FaultSchedule {
+    /// Return the targets affected by the fault after the specified time since the beginning of
+    /// the benchmark.
+    pub fn active_targets(&self, elapsed: Duration, targets: &[usize]) -> Vec<usize> {
         match self {
-            Self::Permanent { faults } => {
-                if *faults == 0 {
-                    write!(f, "no faults")
+            Self::Always => targets.to_vec(),
+            Self::Window { start, duration } => {
+                if elapsed >= *start && elapsed < *start + *duration {
+                    targets.to_vec()
+                } else {
+                    Vec::new()
+                }
+            }
+            Self::Periodic {
+                start,
+                period,
+                duration,
+            } => {
+                let Some(since_start) = elapsed.checked_sub(*start) else {
+                    return Vec::new();
+                };
+                let since_period_start = match period.as_nanos() {
+                    0 => since_start,
+                    x => Duration::from_nanos((since_start.as_nanos() % x) as u64),
+                };
+                if since_period_start < *duration {
+                    targets.to_vec()
                 } else {
-                    write!(f, "{faults} crashed")
+                    Vec::new()
+                }
+            }
+            Self::Rolling { start, period } => {
+                let Some(since_start) = elapsed.checked_sub(*start) else {
+                    return Vec::new();
+                };
+                if targets.is_empty() || period.is_zero() {
+                    return targets.to_vec();
                 }
+                let round = since_start.as_nanos() / period.as_nanos();
+                vec![targets[(round % targets.len() as u128) as usize]]
             }
-            Self::CrashRecovery {
-                max_faults,
-                interval,
-            } => write!(f, "{max_faults} crash-recovery, {}s", interval.as_secs()),
         }
     }
 }
 
-impl FaultsType {
-    /// The interval between crashes. If the type is `Permanent`, the interval is 1s
-    /// to crash the nodes as fast as possible.
-    pub fn crash_interval(&self) -> Duration {
-        match self {
-            Self::Permanent { .. } => Duration::from_secs(1),
-            Self::CrashRecovery { interval, .. } => *interval,
+/// A network fault injected on the (client) instances of the testbed.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct NetworkFault {
+    /// The network degradation to inject.
+    pub kind: NetworkFaultKind,
+    /// The indices of the instances on which to inject the fault. If empty, the fault targets
+    /// all instances.
+    #[serde(default)]
+    pub instances: Vec<usize>,
+    /// The remote hosts (ip addresses or subnets) whose links with the target instances are
+    /// degraded. If empty, the fault degrades all links of the target instances.
+    #[serde(default)]
+    pub hosts: Vec<String>,
+    /// When the fault is active.
+    #[serde(default)]
+    pub schedule: FaultSchedule,
+}
+
+impl Display for NetworkFault {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.hosts.is_empty() {
+            write!(f, "{}", self.kind)
+        } else {
+            write!(f, "{} with {}", self.kind, self.hosts.join(", "))
         }
     }
 }
 
-/// The actions to apply to the testbed, i.e., which instances to crash and recover.
-#[derive(Default)]
-pub struct CrashRecoveryAction {
-    /// The instances to boot.
-    pub boot: Vec<Instance>,
-    /// The instances to kill.
-    pub kill: Vec<Instance>,
+impl NetworkFault {
+    /// The network interface of the instances on which to inject faults.
+    const INTERFACE: &'static str = "$(ip route show default | awk '{print $5; exit}')";
+    /// The number of bands of the default `prio` queuing discipline.
+    const DEFAULT_BANDS: usize = 3;
+    /// The iptables chain holding the partition rules.
+    const IPTABLES_CHAIN: &'static str = "WALRUS_FAULTS";
+
+    /// Return the hosts whose links are degraded by the fault.
+    fn target_hosts(&self) -> Vec<&str> {
+        if self.hosts.is_empty() {
+            vec!["0.0.0.0/0"]
+        } else {
+            self.hosts.iter().map(|x| x.as_str()).collect()
+        }
+    }
+
+    /// Return the netem parameters of the fault, if it shapes the traffic.
+    fn netem_parameters(&self) -> Option<String> {
+        match &self.kind {
+            NetworkFaultKind::Delay { latency, jitter } => Some(format!(
+                "delay {}ms {}ms",
+                latency.as_millis(),
+                jitter.as_millis()
+            )),
+            NetworkFaultKind::Loss { percent } => Some(format!("loss {percent}%")),
+            NetworkFaultKind::Bandwidth { mbit } => Some(format!("rate {mbit}mbit")),
+            NetworkFaultKind::Partition { .. } => None,
+        }
+    }
+
+    /// The command removing all network faults from an instance.
+    pub fn clear_command() -> String {
+        let interface = Self::INTERFACE;
+        let chain = Self::IPTABLES_CHAIN;
+        [
+            format!("(sudo tc qdisc del dev {interface} root || true)"),
+            format!("(sudo iptables -F {chain} || true)"),
+        ]
+        .join(" && ")
+    }
+
+    /// The command replacing the network faults of an instance with the specified ones. If
+    /// several traffic-shaping faults degrade the same link, only the first one applies.
+    pub fn apply_command<'a, I>(faults: I) -> String
+    where
+        I: IntoIterator<Item = &'a NetworkFault>,
+    {
+        let interface = Self::INTERFACE;
+        let chain = Self::IPTABLES_CHAIN;
+        let faults: Vec<_> = faults.into_iter().collect();
+        let mut commands = vec![Self::clear_command()];
+
+        // Shape the traffic with a netem queuing discipline per fault, attached to a dedicated
+        // band of a prio queuing discipline. The remaining traffic uses the default bands.
+        let shaping: Vec<_> = faults
+            .iter()
+            .filter_map(|fault| fault.netem_parameters().map(|x| (fault, x)))
+            .collect();
+        if !shaping.is_empty() {
+            let bands = Self::DEFAULT_BANDS + shaping.len();
+            commands.push(format!(
+                "sudo tc qdisc add dev {interface} root handle 1: prio bands {bands}"
+            ));
+            for (i, (fault, parameters)) in shaping.iter().enumerate() {
+                let band = Self::DEFAULT_BANDS + i + 1;
+                let handle = 10 + i;
+                commands.push(format!(
+                    "sudo tc qdisc add dev {interface} parent 1:{band} handle {handle}: \
+                    netem {parameters}"
+                ));
+                for host in fault.target_hosts() {
+                    commands.push(format!(
+                        "sudo tc filter add dev {interface} protocol ip parent 1: prio 1 u32 \
+                        match ip dst {host} flowid 1:{band}"
+                    ));
+                }
+            }
+        }
+
+        // Drop the partitioned traffic with iptables.
+        let partitions: Vec<_> = faults
+            .iter()
+            .filter_map(|fault| match fault.kind {
+                NetworkFaultKind::Partition { direction } => Some((fault, direction)),
+                _ => None,
+            })
+            .collect();
+        if !partitions.is_empty() {
+            commands.push(format!("(sudo iptables -N {chain} || true)"));
+            for hook in ["INPUT", "OUTPUT"] {
+                commands.push(format!(
+                    "(sudo iptables -C {hook} -j {chain} || sudo iptables -I {hook} -j {chain})"
+                ));
+            }
+            // Never cut the ssh connections with the orchestrator.
+            commands.push(format!(
+                "sudo iptables -A {chain} -p tcp --dport 22 -j ACCEPT"
+            ));
+            commands.push(format!(
+                "sudo iptables -A {chain} -p tcp --sport 22 -j ACCEPT"
+            ));
+            for (fault, direction) in partitions {
+                for host in fault.target_hosts() {
+                    if direction != PartitionDirection::Outbound {
+                        commands.push(format!("sudo iptables -A {chain} -s {host} -j DROP"));
+                    }
+                    if direction != PartitionDirection::Inbound {
+                        commands.push(format!("sudo iptables -A {chain} -d {host} -j DROP"));
+                    }
+                }
+            }
+        }
+
+        commands.join(" && ")
+    }
 }
 
-impl Display for CrashRecoveryAction {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let booted = self.boot.len();
-        let killed = self.kill.len();
+/// The network faults to apply to a single instance.
+pub struct NetworkFaultAction {
+    /// The index of the instance.
+    pub index: usize,
+    /// The instance on which to apply the faults.
+    pub instance: Instance,
+    /// The faults that should be active on the instance (replacing all previous faults).
+    pub faults: Vec<NetworkFault>,
+}
 
-        if self.boot.is_empty() {
-            write!(f, "{killed} node(s) killed")
-        } else if self.kill.is_empty() {
-            write!(f, "{booted} node(s) recovered")
+impl Display for NetworkFaultAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.faults.is_empty() {
+            write!(f, "instance {}: network healed", self.index)
         } else {
-            write!(f, "{killed} node(s) killed and {booted} node(s) recovered")
+            let faults: Vec<_> = self.faults.iter().map(|x| x.to_string()).collect();
+            write!(f, "instance {}: {}", self.index, faults.join(", "))
         }
     }
 }
 
-impl CrashRecoveryAction {
-    pub fn boot(instances: impl Iterator<Item = Instance>) -> Self {
-        Self {
-            boot: instances.collect(),
-            kill: Vec::new(),
-        }
+impl NetworkFaultAction {
+    /// The command applying the action to the instance.
+    pub fn command(&self) -> String {
+        NetworkFault::apply_command(&self.faults)
     }
+}
+
+/// A change of the network faults active on an instance, reported along with the measurements.
+#[serde_as]
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct NetworkFaultEvent {
+    /// Duration since the beginning of the benchmark.
+    #[serde_as(as = "DurationSeconds")]
+    pub timestamp: Duration,
+    /// The index of the instance.
+    pub instance: usize,
+    /// The description of the faults active on the instance from this point on.
+    pub faults: Vec<String>,
+}
 
-    pub fn kill(instances: impl Iterator<Item = Instance>) -> Self {
+impl NetworkFaultEvent {
+    /// Make a new event recording the specified action.
+    pub fn new(timestamp: Duration, action: &NetworkFaultAction) -> Self {
         Self {
-            boot: Vec::new(),
-            kill: instances.collect(),
+            timestamp,
+            instance: action.index,
+            faults: action.faults.iter().map(|x| x.to_string()).collect(),
         }
     }
-
-    pub fn no_op() -> Self {
-        Self::default()
-    }
 }
 
-pub struct CrashRecoverySchedule {
-    /// The number of faulty nodes and the crash-recovery pattern to follow.
-    faults_type: FaultsType,
-    /// The available instances.
+/// Tracks the network faults to apply to the instances over the course of a benchmark.
+pub struct NetworkFaultsSchedule {
+    /// The network faults to inject.
+    faults: Vec<NetworkFault>,
+    /// The instances on which to inject the faults.
     instances: Vec<Instance>,
-    /// The current number of dead nodes.
-    dead: usize,
+    /// The indices of the faults currently active on each instance.
+    active: Vec<Vec<usize>>,
 }
 
-impl CrashRecoverySchedule {
-    pub fn new(faults_type: FaultsType, instances: Vec<Instance>) -> Self {
+impl NetworkFaultsSchedule {
+    pub fn new(faults: Vec<NetworkFault>, instances: Vec<Instance>) -> Self {
+        let active = vec![Vec::new(); instances.len()];
         Self {
-            faults_type,
+            faults,
             instances,
-            dead: 0,
+            active,
         }
     }
-    pub fn update(&mut self) -> CrashRecoveryAction {
-        let mut instances = self.instances.clone();
-
-        match &self.faults_type {
-            // Permanently crash the specified number of nodes.
-            FaultsType::Permanent { faults } => {
-                if self.dead == 0 {
-                    self.dead = *faults;
-                    CrashRecoveryAction::kill(instances.drain(0..*faults))
-                } else {
-                    CrashRecoveryAction::no_op()
-                }
-            }
 
-            // Periodically crash and recover nodes.
-            FaultsType::CrashRecovery { max_faults, .. } => {
-                let min_faults = max_faults / 3;
+    /// Return the actions to apply to the instances whose faults change after the specified
+    /// time since the beginning of the benchmark.
+    pub fn update(&mut self, elapsed: Duration) -> Vec<NetworkFaultAction> {
+        let all_instances: Vec<_> = (0..self.instances.len()).collect();
+        let mut active = vec![Vec::new(); self.instances.len()];
+        for (i, fault) in self.faults.iter().enumerate() {
+            let targets: Vec<_> = if fault.instances.is_empty() {
+                all_instances.clone()
+            } else {
+                fault
+                    .instances
+                    .iter()
+                    .copied()
+                    .filter(|x| *x < self.instances.len())
+                    .collect()
+            };
+            for target in fault.schedule.active_targets(elapsed, &targets) {
+                active[target].push(i);
+            }
+        }
 
-                // Recover all nodes if we already crashed them all.
-                if self.dead == *max_faults {
-                    let to_recover = instances.drain(0..*max_faults);
-                    self.dead = 0;
-                    CrashRecoveryAction::boot(to_recover)
-                }
-                // Otherwise crash a few nodes at the time.
-                else {
-                    let (l, h) = if self.dead == 0 && min_faults != 0 {
-                        (0, min_faults)
-                    } else if self.dead == min_faults && min_faults != 0 {
-                        (min_faults, 2 * min_faults)
-                    } else {
-                        (2 * min_faults, *max_faults)
-                    };
-
-                    let to_kill = instances.drain(l..h);
-                    self.dead += h - l;
-                    CrashRecoveryAction::kill(to_kill)
-                }
+        let mut actions = Vec::new();
+        for (index, (previous, current)) in self.active.iter().zip(&active).enumerate() {
+            if previous != current {
+                actions.push(NetworkFaultAction {
+                    index,
+                    instance: self.instances[index].clone(),
+                    faults: current.iter().map(|i| self.faults[*i].clone()).collect(),
+                });
             }
         }
+        self.active = active;
+        actions
+    }
+
+    /// Return the actions removing all faults from the instances.
+    pub fn clear(&mut self) -> Vec<NetworkFaultAction> {
+        let actions = self
+            .active
+            .iter()
+            .enumerate()
+            .filter(|(_, faults)| !faults.is_empty())
+            .map(|(index, _)| NetworkFaultAction {
+                index,
+                instance: self.instances[index].clone(),
+                faults: Vec::new(),
+            })
+            .collect();
+        self.active = vec![Vec::new(); self.instances.len()];
+        actions
     }
 }
 
@@ -176,110 +416,122 @@ impl CrashRecoverySchedule {
 mod faults_tests {
     use std::time::Duration;
 
-    use super::{CrashRecoverySchedule, FaultsType};
+    use super::{
+        FaultSchedule,
+        NetworkFault,
+        NetworkFaultKind,
+        NetworkFaultsSchedule,
+        PartitionDirection,
+    };
     use crate::client::Instance;
 
     #[test]
-    fn crash_recovery_1_fault() {
-        let max_faults = 1;
-        let interval = Duration::from_secs(60);
-        let faulty = (0..max_faults)
-            .map(|i| Instance::new_for_test(i.to_string()))
-            .collect();
-        let mut schedule = CrashRecoverySchedule::new(
-            FaultsType::CrashRecovery {
-                max_faults,
-                interval,
-            },
-            faulty,
+    fn rolling_schedule() {
+        let schedule = FaultSchedule::Rolling {
+            start: Duration::from_secs(10),
+            period: Duration::from_secs(5),
+        };
+        let targets = [0, 2, 3];
+
+        assert!(
+            schedule
+                .active_targets(Duration::from_secs(9), &targets)
+                .is_empty()
         );
+        assert_eq!(
+            schedule.active_targets(Duration::from_secs(10), &targets),
+            [0]
+        );
+        assert_eq!(
+            schedule.active_targets(Duration::from_secs(16), &targets),
+            [2]
+        );
+        assert_eq!(
+            schedule.active_targets(Duration::from_secs(24), &targets),
+            [3]
+        );
+        assert_eq!(
+            schedule.active_targets(Duration::from_secs(25), &targets),
+            [0]
+        );
+    }
 
-        let action = schedule.update();
-        assert_eq!(action.boot.len(), 0);
-        assert_eq!(action.kill.len(), 1);
-
-        let action = schedule.update();
-        assert_eq!(action.boot.len(), 1);
-        assert_eq!(action.kill.len(), 0);
-
-        let action = schedule.update();
-        assert_eq!(action.boot.len(), 0);
-        assert_eq!(action.kill.len(), 1);
-
-        let action = schedule.update();
-        assert_eq!(action.boot.len(), 1);
-        assert_eq!(action.kill.len(), 0);
+    #[test]
+    fn periodic_schedule() {
+        let schedule = FaultSchedule::Periodic {
+            start: Duration::from_secs(10),
+            period: Duration::from_secs(30),
+            duration: Duration::from_secs(5),
+        };
+        let targets = [0, 1];
+
+        assert!(
+            schedule
+                .active_targets(Duration::from_secs(0), &targets)
+                .is_empty()
+        );
+        assert_eq!(
+            schedule.active_targets(Duration::from_secs(12), &targets),
+            [0, 1]
+        );
+        assert!(
+            schedule
+                .active_targets(Duration::from_secs(15), &targets)
+                .is_empty()
+        );
+        assert_eq!(
+            schedule.active_targets(Duration::from_secs(44), &targets),
+            [0, 1]
+        );
     }
 
     #[test]
-    fn crash_recovery_2_faults() {
-        let max_faults = 2;
-        let interval = Duration::from_secs(60);
-        let faulty = (0..max_faults)
+    fn network_faults_schedule() {
+        let delay = NetworkFault {
+            kind: NetworkFaultKind::Delay {
+                latency: Duration::from_millis(100),
+                jitter: Duration::from_millis(10),
+            },
+            instances: vec![1],
+            hosts: Vec::new(),
+            schedule: FaultSchedule::Always,
+        };
+        let partition = NetworkFault {
+            kind: NetworkFaultKind::Partition {
+                direction: PartitionDirection::Outbound,
+            },
+            instances: Vec::new(),
+            hosts: vec!["10.0.0.1".into()],
+            schedule: FaultSchedule::Window {
+                start: Duration::from_secs(10),
+                duration: Duration::from_secs(10),
+            },
+        };
+        let instances = (0..3)
             .map(|i| Instance::new_for_test(i.to_string()))
             .collect();
-        let mut schedule = CrashRecoverySchedule::new(
-            FaultsType::CrashRecovery {
-                max_faults,
-                interval,
-            },
-            faulty,
-        );
+        let mut schedule = NetworkFaultsSchedule::new(vec![delay, partition], instances);
 
-        let action = schedule.update();
-        assert_eq!(action.boot.len(), 0);
-        assert_eq!(action.kill.len(), 2);
+        let actions = schedule.update(Duration::from_secs(0));
+        assert_eq!(actions.len(), 1);
+        assert_eq!(actions[0].index, 1);
+        assert!(actions[0].command().contains("netem delay 100ms 10ms"));
 
-        let action = schedule.update();
-        assert_eq!(action.boot.len(), 2);
-        assert_eq!(action.kill.len(), 0);
+        assert!(schedule.update(Duration::from_secs(5)).is_empty());
 
-        let action = schedule.update();
-        assert_eq!(action.boot.len(), 0);
-        assert_eq!(action.kill.len(), 2);
+        let actions = schedule.update(Duration::from_secs(10));
+        assert_eq!(actions.len(), 3);
+        assert_eq!(actions[1].faults.len(), 2);
+        let command = actions[0].command();
+        assert!(command.contains("-d 10.0.0.1 -j DROP"));
+        assert!(!command.contains("-s 10.0.0.1 -j DROP"));
 
-        let action = schedule.update();
-        assert_eq!(action.boot.len(), 2);
-        assert_eq!(action.kill.len(), 0);
-    }
+        let actions = schedule.update(Duration::from_secs(20));
+        assert_eq!(actions.len(), 3);
+        assert!(actions[0].faults.is_empty());
+        assert_eq!(actions[0].command(), NetworkFault::clear_command());
 
-    #[test]
-    fn crash_recovery() {
-        let interval = Duration::from_secs(60);
-        for i in 3..33 {
-            let max_faults = i;
-            let min_faults = max_faults / 3;
-
-            let instances = (0..max_faults)
-                .map(|i| Instance::new_for_test(i.to_string()))
-                .collect();
-            let mut schedule = CrashRecoverySchedule::new(
-                FaultsType::CrashRecovery {
-                    max_faults,
-                    interval,
-                },
-                instances,
-            );
-
-            let action = schedule.update();
-            assert_eq!(action.boot.len(), 0);
-            assert_eq!(action.kill.len(), min_faults);
-
-            let action = schedule.update();
-            assert_eq!(action.boot.len(), 0);
-            assert_eq!(action.kill.len(), min_faults);
-
-            let action = schedule.update();
-            assert_eq!(action.boot.len(), 0);
-            assert_eq!(action.kill.len(), max_faults - 2 * min_faults);
-
-            let action = schedule.update();
-            assert_eq!(action.boot.len(), max_faults);
-            assert_eq!(action.kill.len(), 0);
-
-            let action = schedule.update();
-            assert_eq!(action.boot.len(), 0);
-            assert_eq!(action.kill.len(), min_faults);
-        }
+        assert_eq!(schedule.clear().len(), 1);
+        assert!(schedule.clear().is_empty());
     }
 }
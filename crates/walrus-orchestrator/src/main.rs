@@ -10,7 +10,7 @@ use std::path::PathBuf;
 
 use benchmark::BenchmarkParameters;
 use clap::Parser;
-use client::{ServerProviderClient, aws::AwsClient, vultr::VultrClient};
+use client::{ServerProviderClient, aws::AwsClient, local::LocalClient, vultr::VultrClient};
 use eyre::Context;
 use measurements::MeasurementsCollection;
 use orchestrator::Orchestrator;
@@ -26,6 +26,7 @@ mod benchmark;
 mod client;
 mod display;
 mod error;
+mod faults;
 mod logs;
 mod measurements;
 mod monitor;
@@ -157,6 +158,13 @@ async fn main() -> eyre::Result<()> {
                 .wrap_err("Failed to load cloud provider's token")?;
             let client = VultrClient::new(token, settings.clone());
 
+            // Execute the command.
+            run(settings, client, opts).await
+        }
+        CloudProvider::Local => {
+            // Create the client running instances on the local machine.
+            let client = LocalClient::new(settings.clone());
+
             // Execute the command.
             run(settings, client, opts).await
         }
@@ -209,10 +217,17 @@ async fn run<C: ServerProviderClient>(
             // Create a new orchestrator to instruct the testbed.
             let username = testbed.username();
             let private_key_file = settings.ssh_private_key_file.clone();
-            let ssh_manager = SshConnectionManager::new(username.into(), private_key_file)
+            let mut ssh_manager = SshConnectionManager::new(username.into(), private_key_file)
                 .with_timeout(settings.ssh_timeout)
                 .with_retries(settings.ssh_retries);
 
+            // Local instances share the toolchain and the (already built) repository of this
+            // machine, so there is nothing to install or update.
+            let skip_testbed_update = skip_testbed_update || !C::REMOTE;
+            if !C::REMOTE {
+                ssh_manager = ssh_manager.with_local_testbed(LocalClient::testbed_dir(&settings));
+            }
+
             let instances = testbed.instances();
 
             let setup_commands = testbed
@@ -14,7 +14,12 @@ use prettytable::{Table, row};
 use prometheus_parse::Scrape;
 use serde::{Deserialize, Serialize};
 
-use crate::{benchmark::BenchmarkParameters, display, protocol::ProtocolMetrics};
+use crate::{
+    benchmark::BenchmarkParameters,
+    display,
+    faults::NetworkFaultEvent,
+    protocol::ProtocolMetrics,
+};
 
 /// The identifier of prometheus latency buckets.
 type BucketId = String;
@@ -143,6 +148,9 @@ pub struct MeasurementsCollection {
     pub parameters: BenchmarkParameters,
     /// The data collected by each scraper.
     pub data: HashMap<Label, HashMap<ScraperId, Vec<Measurement>>>,
+    /// The changes of the network faults injected during the run.
+    #[serde(default)]
+    pub network_faults: Vec<NetworkFaultEvent>,
 }
 
 impl MeasurementsCollection {
@@ -154,6 +162,7 @@ impl MeasurementsCollection {
         Self {
             parameters,
             data: HashMap::new(),
+            network_faults: Vec::new(),
         }
     }
 
@@ -174,6 +183,11 @@ impl MeasurementsCollection {
             .push(measurement);
     }
 
+    /// Record a change of the network faults injected on an instance.
+    pub fn add_network_fault_event(&mut self, event: NetworkFaultEvent) {
+        self.network_faults.push(event);
+    }
+
     /// Get all measurements associated with the specified label.
     pub fn all_measurements(&self, label: &Label) -> Vec<Vec<Measurement>> {
         self.data
@@ -267,6 +281,15 @@ impl MeasurementsCollection {
             table.add_row(row![b->"Latency (stdev):", format!("{} ms", stdev_latency.as_millis())]);
         }
 
+        let network_faults = &self.parameters.settings.network_faults;
+        if !network_faults.is_empty() {
+            table.add_row(row![bH2->""]);
+            for fault in network_faults {
+                table.add_row(row![b->"Network fault:", fault]);
+            }
+            table.add_row(row![b->"Fault changes:", self.network_faults.len()]);
+        }
+
         display::newline();
         table.printstd();
         display::newline();
@@ -5,6 +5,7 @@ use std::{
     collections::{HashMap, VecDeque},
     fs,
     path::PathBuf,
+    time::Duration,
 };
 
 use tokio::time::{self, Instant};
@@ -15,6 +16,7 @@ use crate::{
     client::Instance,
     display,
     error::{TestbedError, TestbedResult},
+    faults::{NetworkFault, NetworkFaultAction, NetworkFaultEvent, NetworkFaultsSchedule},
     logs::LogsAnalyzer,
     measurements::{Measurement, MeasurementsCollection},
     monitor::Monitor,
@@ -45,6 +47,9 @@ pub struct Orchestrator<P> {
 }
 
 impl<P> Orchestrator<P> {
+    /// The interval between updates of the network faults injected on the testbed.
+    const NETWORK_FAULTS_UPDATE_INTERVAL: Duration = Duration::from_secs(1);
+
     /// Make a new orchestrator.
     pub fn new(
         settings: Settings,
@@ -272,6 +277,9 @@ impl<P: ProtocolCommands + ProtocolMetrics> Orchestrator<P> {
         if delete_logs {
             command.push("(rm -rf ~/*log* || true)".into());
         }
+        if !self.settings.network_faults.is_empty() {
+            command.push(NetworkFault::clear_command());
+        }
         let command = command.join(" ; ");
 
         // Execute the deletion on all machines.
@@ -353,12 +361,18 @@ impl<P: ProtocolCommands + ProtocolMetrics> Orchestrator<P> {
         // Regularly scrape the client metrics.
         let metrics_commands = self
             .protocol_commands
-            .clients_metrics_command(clients, parameters);
+            .clients_metrics_command(clients.clone(), parameters);
 
         let mut aggregator = MeasurementsCollection::new(parameters.clone());
         let mut metrics_interval = time::interval(self.settings.scrape_interval);
         metrics_interval.tick().await; // The first tick returns immediately.
 
+        // Regularly update the network faults injected on the clients.
+        let network_faults = self.settings.network_faults.clone();
+        let inject_faults = !network_faults.is_empty();
+        let mut faults_schedule = NetworkFaultsSchedule::new(network_faults, clients);
+        let mut faults_interval = time::interval(Self::NETWORK_FAULTS_UPDATE_INTERVAL);
+
         let start = Instant::now();
         loop {
             tokio::select! {
@@ -387,14 +401,50 @@ impl<P: ProtocolCommands + ProtocolMetrics> Orchestrator<P> {
                     if elapsed > benchmark_duration {
                         break;
                     }
+                },
+
+                // Inject or heal network faults.
+                now = faults_interval.tick(), if inject_faults => {
+                    let elapsed = now.duration_since(start);
+                    let actions = faults_schedule.update(elapsed);
+                    self.apply_network_faults(actions, elapsed, &mut aggregator).await?;
                 }
             }
         }
 
+        // Heal the network before returning.
+        let actions = faults_schedule.clear();
+        self.apply_network_faults(actions, start.elapsed(), &mut aggregator)
+            .await?;
+
         display::done();
         Ok(aggregator)
     }
 
+    /// Apply the specified network faults to the clients and record them along with the
+    /// measurements.
+    async fn apply_network_faults(
+        &self,
+        actions: Vec<NetworkFaultAction>,
+        elapsed: Duration,
+        aggregator: &mut MeasurementsCollection,
+    ) -> TestbedResult<()> {
+        if actions.is_empty() {
+            return Ok(());
+        }
+
+        let mut targets = Vec::new();
+        for action in actions {
+            aggregator.add_network_fault_event(NetworkFaultEvent::new(elapsed, &action));
+            let command = action.command();
+            targets.push((action.instance, command));
+        }
+        self.ssh_manager
+            .execute_per_instance(targets, CommandContext::default())
+            .await?;
+        Ok(())
+    }
+
     /// Download the log files from the nodes and clients.
     pub async fn download_logs(
         &self,
@@ -422,7 +472,7 @@ impl<P: ProtocolCommands + ProtocolMetrics> Orchestrator<P> {
         for (i, instance) in clients.iter().enumerate() {
             display::status(format!("{}/{}", i + 1, clients.len()));
 
-            let connection = self.ssh_manager.connect(instance.ssh_address()).await?;
+            let connection = self.ssh_manager.connect(instance).await?;
             let client_log_content = connection.download("client.log")?;
 
             let client_log_file = [path.clone(), format!("client-{i}.log").into()]
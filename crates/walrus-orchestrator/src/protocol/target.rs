@@ -73,6 +73,17 @@ impl ProtocolParameters for ProtocolClientParameters {}
 pub struct TargetProtocol;
 
 impl TargetProtocol {
+    /// The port on which the i-th client exposes its metrics. Local instances share the network
+    /// interfaces of the machine, so each of their clients needs a dedicated port.
+    fn metrics_port(instance: &Instance, index: usize, parameters: &BenchmarkParameters) -> u16 {
+        let port = parameters.client_parameters.metrics_port;
+        if instance.is_local() {
+            port + index as u16
+        } else {
+            port
+        }
+    }
+
     pub fn upload_yaml_file_command<P: AsRef<Path>>(source: P, destination: P) -> String {
         let file = File::open(source).expect("Failed to open file");
         let reader = BufReader::new(file);
@@ -186,7 +197,7 @@ impl ProtocolCommands for TargetProtocol {
                     format!("--n-clients {}", parameters.client_parameters.tasks),
                     format!(
                         "--metrics-port {}",
-                        parameters.client_parameters.metrics_port
+                        Self::metrics_port(&instance, i, parameters)
                     ),
                     format!(
                         "--min-size-log2 {}",
@@ -230,9 +241,10 @@ impl ProtocolMetrics for TargetProtocol {
     {
         instances
             .into_iter()
-            .map(|instance| {
+            .enumerate()
+            .map(|(i, instance)| {
                 let instance_ip = instance.main_ip;
-                let metrics_port = parameters.client_parameters.metrics_port;
+                let metrics_port = Self::metrics_port(&instance, i, parameters);
                 let metrics_path = format!("{instance_ip}:{metrics_port}/metrics");
                 (instance, metrics_path)
             })
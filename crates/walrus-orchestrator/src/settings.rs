@@ -16,6 +16,7 @@ use serde_with::{DisplayFromStr, DurationSeconds, serde_as};
 use crate::{
     client::Instance,
     error::{SettingsError, SettingsResult},
+    faults::NetworkFault,
 };
 
 /// The git repository holding the codebase.
@@ -61,6 +62,9 @@ pub enum CloudProvider {
     Aws,
     #[serde(alias = "vultr")]
     Vultr,
+    /// Run the instances as processes on the local machine.
+    #[serde(alias = "local")]
+    Local,
 }
 
 /// The testbed settings. Those are typically specified in a file.
@@ -75,8 +79,9 @@ pub struct Settings {
     /// The path to the secret token for authentication with the cloud provider.
     #[serde(skip_serializing)]
     pub token_file: PathBuf,
-    /// The ssh private key to access the instances.
+    /// The ssh private key to access the instances. Local testbeds do not need one.
     #[serde(
+        default,
         skip_serializing,
         deserialize_with = "walrus_utils::config::resolve_home_dir"
     )]
@@ -109,6 +114,9 @@ pub struct Settings {
     /// The directory (on the local machine) where to download logs files from the instances.
     #[serde(default = "defaults::default_logs_dir")]
     pub logs_dir: PathBuf,
+    /// The directory (on the local machine) hosting the instances of local testbeds.
+    #[serde(default = "defaults::default_local_dir")]
+    pub local_dir: PathBuf,
     /// Whether to use NVMe drives for data storage (if available).
     #[serde(default = "defaults::default_use_nvme")]
     pub nvme: bool,
@@ -133,6 +141,9 @@ pub struct Settings {
     /// The number of times the orchestrator should retry an ssh command.
     #[serde(default = "defaults::default_ssh_retries")]
     pub ssh_retries: usize,
+    /// The network faults to inject on the load generators during the benchmark.
+    #[serde(default)]
+    pub network_faults: Vec<NetworkFault>,
 }
 
 mod defaults {
@@ -154,6 +165,10 @@ mod defaults {
         ["./", "logs"].iter().collect()
     }
 
+    pub fn default_local_dir() -> PathBuf {
+        ["./", "local-testbed"].iter().collect()
+    }
+
     pub fn default_use_nvme() -> bool {
         true
     }
@@ -2,9 +2,11 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use std::{
+    fs,
     io::{Read, Write},
     net::SocketAddr,
     path::{Path, PathBuf},
+    process::Command,
     time::Duration,
 };
 
@@ -103,6 +105,9 @@ pub struct SshConnectionManager {
     timeout: Option<Duration>,
     /// The number of retries before giving up to execute the command.
     retries: usize,
+    /// The directory hosting the instances of a local testbed. If set, commands are executed
+    /// through the local shell instead of over ssh.
+    local_testbed: Option<PathBuf>,
 }
 
 impl SshConnectionManager {
@@ -116,9 +121,16 @@ impl SshConnectionManager {
             private_key_file,
             timeout: None,
             retries: 0,
+            local_testbed: None,
         }
     }
 
+    /// Execute all commands on local instances hosted in the specified directory.
+    pub fn with_local_testbed(mut self, testbed_dir: PathBuf) -> Self {
+        self.local_testbed = Some(testbed_dir);
+        self
+    }
+
     /// Set a timeout duration for the connections.
     pub fn with_timeout(mut self, timeout: Duration) -> Self {
         self.timeout = Some(timeout);
@@ -131,8 +143,20 @@ impl SshConnectionManager {
         self
     }
 
+    /// Create a new connection with the provided instance.
+    pub async fn connect(&self, instance: &Instance) -> SshResult<Connection> {
+        if let Some(testbed_dir) = &self.local_testbed {
+            let home = testbed_dir.join(&instance.id);
+            let connection = LocalConnection::new(instance.ssh_address(), home);
+            return Ok(Connection::Local(connection));
+        }
+        self.connect_ssh(instance.ssh_address())
+            .await
+            .map(Connection::Ssh)
+    }
+
     /// Create a new ssh connection with the provided host.
-    pub async fn connect(&self, address: SocketAddr) -> SshResult<SshConnection> {
+    async fn connect_ssh(&self, address: SocketAddr) -> SshResult<SshConnection> {
         let mut error = None;
         for _ in 0..self.retries + 1 {
             match SshConnection::new(address, &self.username, self.private_key_file.clone()).await {
@@ -196,8 +220,8 @@ impl SshConnectionManager {
                 let context = context.clone();
 
                 tokio::spawn(async move {
-                    let connection = ssh_manager.connect(instance.ssh_address()).await?;
-                    // SshConnection::execute is a blocking call, needs to go to blocking pool
+                    let connection = ssh_manager.connect(&instance).await?;
+                    // Connection::execute is a blocking call, needs to go to blocking pool
                     Handle::current()
                         .spawn_blocking(move || connection.execute(context.apply(command)))
                         .await
@@ -256,6 +280,32 @@ impl SshConnectionManager {
     }
 }
 
+/// A connection to an instance, either over ssh or through the local shell.
+pub enum Connection {
+    /// A connection to a remote instance.
+    Ssh(SshConnection),
+    /// A connection to an instance running on the local machine.
+    Local(LocalConnection),
+}
+
+impl Connection {
+    /// Execute a command on the instance.
+    pub fn execute(&self, command: String) -> SshResult<(String, String)> {
+        match self {
+            Self::Ssh(connection) => connection.execute(command),
+            Self::Local(connection) => connection.execute(command),
+        }
+    }
+
+    /// Download a file from the instance.
+    pub fn download<P: AsRef<Path>>(&self, path: P) -> SshResult<String> {
+        match self {
+            Self::Ssh(connection) => connection.download(path),
+            Self::Local(connection) => connection.download(path),
+        }
+    }
+}
+
 /// Representation of an ssh connection.
 pub struct SshConnection {
     /// The ssh session.
@@ -436,3 +486,107 @@ impl SshConnection {
         Err(error.unwrap())
     }
 }
+
+/// Representation of a connection to a local instance.
+///
+/// Local instances share the machine running the orchestrator. Each of them has its own home
+/// directory (from which relative paths and `~` are resolved) and its own tmux server, so that
+/// commands behave as if they were running on a dedicated machine.
+pub struct LocalConnection {
+    /// The address of the instance (only used to report errors).
+    address: SocketAddr,
+    /// The home directory of the instance.
+    home: PathBuf,
+}
+
+impl LocalConnection {
+    /// The shell used to execute commands; commands rely on bash-specific syntax (e.g., `|&`).
+    const SHELL: &'static str = "bash";
+
+    /// Create a new connection with a local instance living in the specified home directory.
+    pub fn new(address: SocketAddr, home: PathBuf) -> Self {
+        Self { address, home }
+    }
+
+    /// Make a useful connection error from the lower level error message.
+    fn make_connection_error(&self, error: std::io::Error) -> SshError {
+        SshError::ConnectionError {
+            address: self.address,
+            error,
+        }
+    }
+
+    /// Execute a command on the local instance and return both stdout and stderr.
+    pub fn execute(&self, command: String) -> SshResult<(String, String)> {
+        let output = Command::new(Self::SHELL)
+            .arg("-c")
+            .arg(&command)
+            .current_dir(&self.home)
+            .env("HOME", &self.home)
+            .env("TMUX_TMPDIR", &self.home)
+            // If the orchestrator runs inside tmux, `TMUX` would otherwise make tmux commands
+            // target the server of the orchestrator rather than the one of the instance.
+            .env_remove("TMUX")
+            .output()
+            .map_err(|e| self.make_connection_error(e))?;
+
+        let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+        let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+
+        ensure!(
+            output.status.success(),
+            SshError::NonZeroExitCode {
+                address: self.address,
+                code: output.status.code().unwrap_or(-1),
+                message: stderr.clone()
+            }
+        );
+
+        Ok((stdout, stderr))
+    }
+
+    /// Read a file from the home directory of the local instance.
+    pub fn download<P: AsRef<Path>>(&self, path: P) -> SshResult<String> {
+        fs::read_to_string(self.home.join(path)).map_err(|e| self.make_connection_error(e))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::net::SocketAddr;
+
+    use tempfile::TempDir;
+
+    use super::{CommandContext, LocalConnection};
+
+    /// Returns a connection to a local instance, together with the guard of its home directory.
+    fn local_connection() -> (TempDir, LocalConnection) {
+        let home = tempfile::tempdir().unwrap();
+        let address: SocketAddr = "127.1.0.1:22".parse().unwrap();
+        let connection = LocalConnection::new(address, home.path().to_path_buf());
+        (home, connection)
+    }
+
+    #[test]
+    fn local_execute_from_home() {
+        let (_home, connection) = local_connection();
+        let (stdout, _) = connection.execute("echo $HOME && pwd".into()).unwrap();
+        let home = connection.home.display().to_string();
+        assert_eq!(stdout, format!("{home}\n{home}\n"));
+    }
+
+    #[test]
+    fn local_execute_non_zero_exit_code() {
+        let (_home, connection) = local_connection();
+        assert!(connection.execute("exit 3".into()).is_err());
+    }
+
+    #[test]
+    fn local_download_logs() {
+        let (_home, connection) = local_connection();
+        let context = CommandContext::new().with_log_file("~/client.log".into());
+        connection.execute(context.apply("echo hello")).unwrap();
+
+        assert_eq!(connection.download("client.log").unwrap(), "hello\n");
+    }
+}
@@ -29,8 +29,10 @@ pub struct Testbed<C> {
 impl<C: ServerProviderClient> Testbed<C> {
     /// Create a new testbed instance with the specified settings and client.
     pub async fn new(settings: Settings, client: C) -> TestbedResult<Self> {
-        let public_key = settings.load_ssh_public_key()?;
-        client.register_ssh_public_key(public_key).await?;
+        if C::REMOTE {
+            let public_key = settings.load_ssh_public_key()?;
+            client.register_ssh_public_key(public_key).await?;
+        }
         let instances = client.list_instances().await?;
 
         Ok(Self {
@@ -98,7 +100,11 @@ impl<C: ServerProviderClient> Testbed<C> {
                 let private_key_file = self.settings.ssh_private_key_file.display();
                 let username = C::USERNAME;
                 let ip = instance.main_ip;
-                let connect = format!("ssh -i {private_key_file} {username}@{ip}");
+                let connect = if C::REMOTE {
+                    format!("ssh -i {private_key_file} {username}@{ip}")
+                } else {
+                    format!("{ip} (local)")
+                };
                 if !instance.is_terminated() {
                     if instance.is_active() {
                         table.add_row(row![bFg->format!("{j}"), connect]);
@@ -140,7 +146,7 @@ impl<C: ServerProviderClient> Testbed<C> {
         };
 
         // Wait until the instances are booted.
-        if cfg!(not(test)) {
+        if cfg!(not(test)) && C::REMOTE {
             self.wait_until_reachable(instances.iter()).await?;
         }
         self.instances = self.client.list_instances().await?;
@@ -188,7 +194,7 @@ impl<C: ServerProviderClient> Testbed<C> {
         self.client.start_instances(available.iter()).await?;
 
         // Wait until the instances are started.
-        if cfg!(not(test)) {
+        if cfg!(not(test)) && C::REMOTE {
             self.wait_until_reachable(available.iter()).await?;
         }
         self.instances = self.client.list_instances().await?;
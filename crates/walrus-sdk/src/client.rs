@@ -26,8 +26,8 @@ use futures::{
 use indicatif::{HumanDuration, MultiProgress};
 use rand::{RngCore as _, rngs::ThreadRng};
 use rayon::{iter::IntoParallelIterator, prelude::*};
-pub use store_args::StoreArgs;
-use sui_types::base_types::ObjectID;
+pub use store_args::{StoreArgs, StorePhase, StorePhaseNotifier};
+use sui_types::base_types::{ObjectID, SuiAddress};
 use tokio::{sync::Semaphore, time::Duration};
 use tracing::{Instrument as _, Level};
 use walrus_core::{
@@ -58,6 +58,7 @@ use walrus_sui::{
         ExpirySelectionPolicy,
         ReadClient,
         SuiContractClient,
+        WriteClient,
     },
     types::{
         Blob,
@@ -87,6 +88,7 @@ pub use crate::{
     config::{ClientCommunicationConfig, ClientConfig, default_configuration_paths},
 };
 
+pub mod audit;
 pub mod client_types;
 pub mod communication;
 pub mod metrics;
@@ -377,6 +379,10 @@ impl<T: ReadClient> WalrusNodeClient<T> {
             )
         };
 
+        // The storage nodes have deleted the data of blobs on their deny lists.
+        if blob_status.is_some_and(|status| status.is_deny_listed()) {
+            return Err(ClientErrorKind::BlobIdBlocked(*blob_id).into());
+        }
         // Return an error if the blob is not registered.
         if matches!(
             blob_status,
@@ -854,6 +860,20 @@ impl WalrusNodeClient<SuiContractClient> {
             .await)
     }
 
+    /// Exchanges the provided amount of SUI (in MIST) for WAL using the specified exchange.
+    pub async fn exchange_sui_for_wal(
+        &self,
+        exchange_id: ObjectID,
+        amount: u64,
+    ) -> ClientResult<()> {
+        Ok(self
+            .sui_client
+            .exchange_sui_for_wal(exchange_id, amount)
+            .await?)
+    }
+}
+
+impl<T: WriteClient> WalrusNodeClient<T> {
     /// Stores a list of blobs to Walrus, retrying if it fails because of epoch change.
     #[tracing::instrument(skip_all, fields(blob_id))]
     pub async fn reserve_and_store_blobs_retry_committees(
@@ -865,6 +885,7 @@ impl WalrusNodeClient<SuiContractClient> {
         let walrus_store_blobs =
             WalrusStoreBlob::<String>::default_unencoded_blobs_from_slice(blobs, attributes);
         let start = Instant::now();
+        store_args.maybe_notify_phase(StorePhase::Encoding);
         let encoded_blobs = self.encode_blobs(walrus_store_blobs, store_args.encoding_type)?;
         store_args.maybe_observe_encoding_latency(start.elapsed());
 
@@ -906,6 +927,7 @@ impl WalrusNodeClient<SuiContractClient> {
         let walrus_store_blobs =
             WalrusStoreBlob::<String>::default_unencoded_blobs_from_slice(&blobs, &[]);
 
+        store_args.maybe_notify_phase(StorePhase::Encoding);
         let encoded_blobs = self.encode_blobs(walrus_store_blobs, store_args.encoding_type)?;
         let (failed_blobs, encoded_blobs): (Vec<_>, Vec<_>) =
             encoded_blobs.into_iter().partition(|blob| blob.is_failed());
@@ -952,6 +974,7 @@ impl WalrusNodeClient<SuiContractClient> {
         let walrus_store_blobs =
             WalrusStoreBlob::<String>::default_unencoded_blobs_from_slice(blobs, &[]);
 
+        store_args.maybe_notify_phase(StorePhase::Encoding);
         let encoded_blobs = self.encode_blobs(walrus_store_blobs, store_args.encoding_type)?;
         let (failed_blobs, encoded_blobs): (Vec<_>, Vec<_>) =
             encoded_blobs.into_iter().partition(|blob| blob.is_failed());
@@ -1072,6 +1095,7 @@ impl WalrusNodeClient<SuiContractClient> {
         store_args: &StoreArgs,
     ) -> ClientResult<Vec<WalrusStoreBlob<'a, T>>> {
         tracing::info!("storing {} sliver pairs with metadata", encoded_blobs.len());
+        store_args.maybe_notify_phase(StorePhase::Registering);
         let status_start_timer = Instant::now();
         let committees = self.get_committees().await?;
         let num_encoded_blobs = encoded_blobs.len();
@@ -1100,6 +1124,7 @@ impl WalrusNodeClient<SuiContractClient> {
         let registered_blobs = self
             .resource_manager(&committees)
             .await
+            .with_credits_policy(store_args.credits_policy)
             .register_walrus_store_blobs(
                 encoded_blobs_with_status,
                 store_args.epochs_ahead,
@@ -1162,6 +1187,7 @@ impl WalrusNodeClient<SuiContractClient> {
             return Err(ClientError::from(ClientErrorKind::CommitteeChangeNotified));
         }
 
+        store_args.maybe_notify_phase(StorePhase::UploadingSlivers);
         let get_certificates_timer = Instant::now();
         // Get the blob certificates, possibly storing slivers, while checking if the committee has
         // changed in the meantime.
@@ -1197,10 +1223,15 @@ impl WalrusNodeClient<SuiContractClient> {
             .collect();
 
         // Certify all blobs on Sui.
+        store_args.maybe_notify_phase(StorePhase::Certifying);
         let sui_cert_timer = Instant::now();
         let cert_and_extend_results = self
             .sui_client
-            .certify_and_extend_blobs(&cert_and_extend_params, store_args.post_store)
+            .certify_and_extend_blobs(
+                &cert_and_extend_params,
+                store_args.post_store,
+                store_args.credits_policy,
+            )
             .await
             .map_err(|error| {
                 tracing::warn!(
@@ -1223,8 +1254,42 @@ impl WalrusNodeClient<SuiContractClient> {
             .map(|result| (result.blob_object_id, result))
             .collect();
 
-        // Get price computation for completing blobs
-        let price_computation = self.get_price_computation().await?;
+        // Get price computation for completing blobs, including the discount obtained through
+        // credits if any registration or extension was actually paid through credits. The blobs
+        // are already certified at this point, so failing to fetch the subsidy rate only affects
+        // the reported discount.
+        let paid_with_credits = result_map.values().any(|result| result.paid_with_credits)
+            || to_be_extended
+                .iter()
+                .chain(to_be_certified.iter())
+                .any(|blob| {
+                    matches!(
+                        blob.get_operation(),
+                        Some(StoreOp::RegisterNew {
+                            paid_with_credits: true,
+                            ..
+                        })
+                    )
+                });
+        let buyer_subsidy_rate = if paid_with_credits {
+            self.sui_client
+                .credits_buyer_subsidy_rate(store_args.credits_policy)
+                .await
+                .inspect_err(|error| {
+                    tracing::warn!(
+                        %error,
+                        "failed to get the buyer subsidy rate of the credits object"
+                    )
+                })
+                .ok()
+                .flatten()
+        } else {
+            None
+        };
+        let price_computation = self
+            .get_price_computation()
+            .await?
+            .with_buyer_subsidy_rate(buyer_subsidy_rate);
 
         // Complete to_be_extended blobs.
         for blob in to_be_extended {
@@ -1314,7 +1379,10 @@ impl WalrusNodeClient<SuiContractClient> {
                 let multi_pb_arc = Arc::clone(&multi_pb);
                 async move {
                     let operation = registered_blob.get_operation().cloned();
-                    let Some(StoreOp::RegisterNew { blob, operation }) = operation else {
+                    let Some(StoreOp::RegisterNew {
+                        blob, operation, ..
+                    }) = operation
+                    else {
                         return Err(ClientError::store_blob_internal(format!(
                             "Expected a WalrusStoreBlob::RegisterNew, got {registered_blob:?}"
                         )));
@@ -1432,7 +1500,7 @@ impl WalrusNodeClient<SuiContractClient> {
     }
 
     /// Creates a resource manager for the client.
-    pub async fn resource_manager(&self, committees: &ActiveCommittees) -> ResourceManager<'_> {
+    pub async fn resource_manager(&self, committees: &ActiveCommittees) -> ResourceManager<'_, T> {
         ResourceManager::new(&self.sui_client, committees.write_committee().epoch)
     }
 
@@ -1470,6 +1538,49 @@ impl WalrusNodeClient<SuiContractClient> {
         Ok(())
     }
 
+    // Blob transfer
+
+    /// Transfers the owned blob objects, specified by their Sui Object IDs, to the `recipient`.
+    ///
+    /// The transfers are batched into as few PTBs as possible.
+    pub async fn transfer_owned_blobs_by_object(
+        &self,
+        blob_object_ids: &[ObjectID],
+        recipient: SuiAddress,
+    ) -> ClientResult<()> {
+        tracing::debug!(
+            n_blobs = blob_object_ids.len(),
+            %recipient,
+            "transferring blob objects"
+        );
+        self.sui_client
+            .transfer_blobs(blob_object_ids, recipient)
+            .await?;
+        Ok(())
+    }
+
+    /// Transfers all valid owned blob objects that match the blob ID to the `recipient`, and
+    /// returns the object IDs of the transferred objects.
+    pub async fn transfer_owned_blobs(
+        &self,
+        blob_id: &BlobId,
+        recipient: SuiAddress,
+    ) -> ClientResult<Vec<ObjectID>> {
+        let object_ids = self
+            .sui_client
+            .owned_blobs(None, ExpirySelectionPolicy::Valid)
+            .await?
+            .into_iter()
+            .filter(|blob| blob.blob_id == *blob_id)
+            .map(|blob| blob.id)
+            .collect::<Vec<_>>();
+        if !object_ids.is_empty() {
+            self.transfer_owned_blobs_by_object(&object_ids, recipient)
+                .await?;
+        }
+        Ok(object_ids)
+    }
+
     /// For each entry in `node_ids_with_amounts`, stakes the amount of WAL specified by the
     /// second element of the pair with the node represented by the first element of the pair.
     pub async fn stake_with_node_pools(
@@ -1489,18 +1600,6 @@ impl WalrusNodeClient<SuiContractClient> {
         Ok(())
     }
 
-    /// Exchanges the provided amount of SUI (in MIST) for WAL using the specified exchange.
-    pub async fn exchange_sui_for_wal(
-        &self,
-        exchange_id: ObjectID,
-        amount: u64,
-    ) -> ClientResult<()> {
-        Ok(self
-            .sui_client
-            .exchange_sui_for_wal(exchange_id, amount)
-            .await?)
-    }
-
     /// Returns the latest committees from the chain.
     #[cfg(any(test, feature = "test-utils"))]
     pub async fn get_latest_committees_in_test(&self) -> Result<ActiveCommittees, ClientError> {
@@ -2223,7 +2322,7 @@ async fn verify_blob_status_event(
     sui_read_client: &impl ReadClient,
 ) -> Result<(), anyhow::Error> {
     let event = match status {
-        BlobStatus::Invalid { event } => event,
+        BlobStatus::Invalid { event, .. } => event,
         BlobStatus::Permanent { status_event, .. } => status_event,
         BlobStatus::Nonexistent | BlobStatus::Deletable { .. } => return Ok(()),
     };
@@ -2255,7 +2354,18 @@ async fn verify_blob_status_event(
             anyhow::ensure!(end_epoch == event.end_epoch, "end epoch mismatch");
             event.blob_id
         }
-        (BlobStatus::Invalid { .. }, BlobEvent::InvalidBlobID(event)) => event.blob_id,
+        (
+            BlobStatus::Invalid {
+                deny_listed: false, ..
+            },
+            BlobEvent::InvalidBlobID(event),
+        ) => event.blob_id,
+        (
+            BlobStatus::Invalid {
+                deny_listed: true, ..
+            },
+            BlobEvent::DenyListBlobDeleted(event),
+        ) => event.blob_id,
         (_, _) => Err(anyhow!("blob event does not match status"))?,
     };
 
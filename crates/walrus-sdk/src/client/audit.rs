@@ -0,0 +1,416 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Audits of the storage of blobs across the storage nodes of the committee.
+//!
+//! In contrast to the blob status, which only reflects the certification of a blob on chain, an
+//! audit requests a sliver from every shard and verifies it against the blob's metadata. The
+//! resulting [`BlobAuditReport`] lists the shards that are missing their sliver or serving bad
+//! data, and compares the number of shards serving valid slivers against the BFT thresholds.
+
+use std::{collections::HashMap, num::NonZeroU16, time::Duration};
+
+use chrono::{DateTime, Utc};
+use futures::{StreamExt as _, stream};
+use serde::Serialize;
+use serde_with::{DisplayFromStr, serde_as};
+use sui_types::base_types::ObjectID;
+use tracing::Level;
+use walrus_core::{
+    BlobId,
+    Epoch,
+    ShardIndex,
+    Sliver,
+    SliverType,
+    bft,
+    encoding::{EncodingAxis, EncodingFactory as _, Primary, RequiredCount, Secondary, SliverData},
+    metadata::{BlobMetadataApi as _, VerifiedBlobMetadataWithId},
+};
+use walrus_storage_node_client::api::BlobStatus;
+use walrus_sui::{client::ReadClient, types::NetworkAddress};
+
+use super::{WalrusNodeClient, communication::NodeReadCommunication};
+use crate::error::ClientResult;
+
+/// The outcome of auditing the sliver stored by a shard.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase",
+    tag = "outcome"
+)]
+pub enum ShardAuditOutcome {
+    /// The node returned a sliver matching the blob's metadata.
+    Verified,
+    /// The node does not store the sliver.
+    Missing,
+    /// The node returned a sliver that does not match the blob's metadata.
+    Invalid {
+        /// The reason why the sliver is invalid.
+        error: String,
+    },
+    /// The sliver could not be retrieved, e.g., because the node is unreachable.
+    Failed {
+        /// The error encountered when requesting the sliver.
+        error: String,
+    },
+}
+
+impl ShardAuditOutcome {
+    /// Returns true if the shard serves a valid sliver.
+    pub fn is_verified(&self) -> bool {
+        matches!(self, Self::Verified)
+    }
+}
+
+/// The audit of a single shard.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShardAudit {
+    /// The index of the shard.
+    pub shard_index: ShardIndex,
+    /// The outcome of the audit.
+    #[serde(flatten)]
+    pub outcome: ShardAuditOutcome,
+}
+
+/// The audit of the shards of a storage node.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeAudit {
+    /// The name of the storage node.
+    pub name: String,
+    /// The ID of the storage node.
+    pub node_id: ObjectID,
+    /// The network address of the storage node.
+    pub network_address: NetworkAddress,
+    /// The audits of the shards of the node, sorted by shard index.
+    pub shards: Vec<ShardAudit>,
+}
+
+impl NodeAudit {
+    /// Returns the number of shards of the node serving valid slivers.
+    pub fn n_verified(&self) -> usize {
+        self.shards
+            .iter()
+            .filter(|shard| shard.outcome.is_verified())
+            .count()
+    }
+}
+
+/// The availability of a blob, derived from the number of shards serving valid slivers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BlobAvailability {
+    /// All shards serve valid slivers.
+    Full,
+    /// Some shards do not serve valid slivers, but at most the `f` faulty shards tolerated by
+    /// the system.
+    Degraded,
+    /// More than `f` shards do not serve valid slivers, but the blob can still be reconstructed.
+    AtRisk,
+    /// Too few shards serve valid slivers to reconstruct the blob.
+    Unrecoverable,
+}
+
+/// The number of shards serving valid slivers compared to the BFT thresholds.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodabilityMargins {
+    /// The number of shards `n`.
+    pub n_shards: u16,
+    /// The maximum number of faulty shards `f` tolerated by the system.
+    pub max_faulty_shards: u16,
+    /// The minimum number of correct shards, `n - f`.
+    pub min_correct_shards: u16,
+    /// The number of slivers of the audited type required to reconstruct the blob.
+    pub slivers_required_for_reconstruction: u16,
+    /// The number of shards serving valid slivers.
+    pub verified_shards: u16,
+    /// The number of shards not serving valid slivers.
+    pub unhealthy_shards: u16,
+    /// The number of additional shards that can fail before the blob cannot be reconstructed;
+    /// negative if the blob cannot be reconstructed anymore.
+    pub reconstruction_margin: i32,
+    /// The number of additional shards that can fail before exceeding `f` unhealthy shards;
+    /// negative if `f` is already exceeded.
+    pub bft_margin: i32,
+    /// The resulting availability of the blob.
+    pub availability: BlobAvailability,
+}
+
+impl DecodabilityMargins {
+    /// Computes the margins given the number of slivers required for reconstruction and the
+    /// number of shards serving valid slivers.
+    pub fn new(n_shards: NonZeroU16, slivers_required: u16, verified_shards: u16) -> Self {
+        let max_faulty_shards = bft::max_n_faulty(n_shards);
+        let unhealthy_shards = n_shards.get().saturating_sub(verified_shards);
+        let availability = if unhealthy_shards == 0 {
+            BlobAvailability::Full
+        } else if unhealthy_shards <= max_faulty_shards {
+            BlobAvailability::Degraded
+        } else if verified_shards >= slivers_required {
+            BlobAvailability::AtRisk
+        } else {
+            BlobAvailability::Unrecoverable
+        };
+        Self {
+            n_shards: n_shards.get(),
+            max_faulty_shards,
+            min_correct_shards: bft::min_n_correct(n_shards).get(),
+            slivers_required_for_reconstruction: slivers_required,
+            verified_shards,
+            unhealthy_shards,
+            reconstruction_margin: i32::from(verified_shards) - i32::from(slivers_required),
+            bft_margin: i32::from(max_faulty_shards) - i32::from(unhealthy_shards),
+            availability,
+        }
+    }
+}
+
+/// The report of the audit of a blob.
+#[serde_as]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlobAuditReport {
+    /// The ID of the audited blob.
+    #[serde_as(as = "DisplayFromStr")]
+    pub blob_id: BlobId,
+    /// The time at which the audit started.
+    pub audited_at: DateTime<Utc>,
+    /// The epoch of the committee whose shards were audited.
+    pub epoch: Epoch,
+    /// The status of the blob reported by the storage nodes.
+    pub blob_status: BlobStatus,
+    /// The type of the audited slivers.
+    pub sliver_type: SliverType,
+    /// The number of shards serving valid slivers compared to the BFT thresholds.
+    pub margins: DecodabilityMargins,
+    /// The audits of the storage nodes holding shards in the audited committee.
+    pub nodes: Vec<NodeAudit>,
+}
+
+impl<T: ReadClient> WalrusNodeClient<T> {
+    /// Audits the storage of the blob across the committee.
+    ///
+    /// Requests the sliver of the given type from every shard, verifies each sliver against the
+    /// blob's metadata, and reports the outcome per node and shard. Unlike reads, the audit does
+    /// not stop once enough slivers are available to reconstruct the blob. Each sliver request
+    /// times out after `timeout`.
+    #[tracing::instrument(level = Level::ERROR, skip(self))]
+    pub async fn audit_blob(
+        &self,
+        blob_id: &BlobId,
+        sliver_type: SliverType,
+        timeout: Duration,
+    ) -> ClientResult<BlobAuditReport> {
+        let audited_at = Utc::now();
+        self.check_blob_id(blob_id)?;
+
+        let blob_status = self
+            .get_blob_status_with_retries(blob_id, &self.sui_client)
+            .await?;
+        let (certified_epoch, _) = self
+            .get_blob_status_and_certified_epoch(blob_id, Some(blob_status))
+            .await?;
+        let metadata = self.retrieve_metadata(certified_epoch, blob_id).await?;
+
+        let committees = self.get_committees().await?;
+        let comms = self
+            .communication_factory
+            .node_read_communications(&committees, certified_epoch)?;
+        let (slivers_required, mut shard_audits) = match sliver_type {
+            SliverType::Primary => {
+                self.audit_slivers::<Primary>(&comms, &metadata, timeout)
+                    .await
+            }
+            SliverType::Secondary => {
+                self.audit_slivers::<Secondary>(&comms, &metadata, timeout)
+                    .await
+            }
+        };
+
+        let mut nodes: Vec<_> = comms
+            .iter()
+            .map(|comm| {
+                let mut shards = shard_audits.remove(&comm.node_index).unwrap_or_default();
+                shards.sort_by_key(|shard| shard.shard_index);
+                (
+                    comm.node_index,
+                    NodeAudit {
+                        name: comm.node.name.clone(),
+                        node_id: comm.node.node_id,
+                        network_address: comm.node.network_address.clone(),
+                        shards,
+                    },
+                )
+            })
+            .collect();
+        nodes.sort_by_key(|(node_index, _)| *node_index);
+        let nodes: Vec<_> = nodes.into_iter().map(|(_, node)| node).collect();
+
+        let verified_shards = nodes.iter().map(NodeAudit::n_verified).sum::<usize>();
+        let margins = DecodabilityMargins::new(
+            committees.n_shards(),
+            slivers_required,
+            verified_shards
+                .try_into()
+                .expect("the number of shards fits into a u16"),
+        );
+        tracing::info!(?margins.availability, verified_shards, "finished auditing the blob");
+
+        Ok(BlobAuditReport {
+            blob_id: *blob_id,
+            audited_at,
+            epoch: comms
+                .first()
+                .map_or(committees.epoch(), |comm| comm.committee_epoch),
+            blob_status,
+            sliver_type,
+            margins,
+            nodes,
+        })
+    }
+
+    /// Requests and verifies the slivers of all shards of the nodes.
+    ///
+    /// Returns the number of slivers required for reconstruction, and the audits of the shards
+    /// by node index.
+    async fn audit_slivers<A: EncodingAxis>(
+        &self,
+        comms: &[NodeReadCommunication<'_>],
+        metadata: &VerifiedBlobMetadataWithId,
+        timeout: Duration,
+    ) -> (u16, HashMap<usize, Vec<ShardAudit>>)
+    where
+        SliverData<A>: TryFrom<Sliver>,
+    {
+        let encoding_type = metadata.metadata().encoding_type();
+        let RequiredCount::Exact(slivers_required) = self
+            .encoding_config
+            .get_for_type(encoding_type)
+            .n_slivers_for_reconstruction::<A>();
+        let max_concurrent_reads = self
+            .communication_limits
+            .max_concurrent_sliver_reads_for_blob_size(
+                metadata.metadata().unencoded_length(),
+                &self.encoding_config,
+                encoding_type,
+            );
+
+        let requests = comms.iter().flat_map(|comm| {
+            comm.node
+                .shard_ids
+                .iter()
+                .map(move |&shard_index| async move {
+                    let outcome = audit_sliver::<A>(comm, metadata, shard_index, timeout).await;
+                    if !outcome.is_verified() {
+                        tracing::debug!(
+                            node = comm.node_index,
+                            %shard_index,
+                            ?outcome,
+                            "the shard does not serve a valid sliver"
+                        );
+                    }
+                    (
+                        comm.node_index,
+                        ShardAudit {
+                            shard_index,
+                            outcome,
+                        },
+                    )
+                })
+        });
+        let mut shard_audits: HashMap<_, Vec<_>> = HashMap::new();
+        stream::iter(requests)
+            .buffer_unordered(max_concurrent_reads)
+            .for_each(|(node_index, shard_audit)| {
+                shard_audits
+                    .entry(node_index)
+                    .or_default()
+                    .push(shard_audit);
+                futures::future::ready(())
+            })
+            .await;
+
+        (
+            slivers_required
+                .try_into()
+                .expect("the number of slivers fits into a u16"),
+            shard_audits,
+        )
+    }
+}
+
+/// Requests the sliver of the shard from the node and verifies it against the metadata.
+async fn audit_sliver<A: EncodingAxis>(
+    comm: &NodeReadCommunication<'_>,
+    metadata: &VerifiedBlobMetadataWithId,
+    shard_index: ShardIndex,
+    timeout: Duration,
+) -> ShardAuditOutcome
+where
+    SliverData<A>: TryFrom<Sliver>,
+{
+    let sliver_pair_index = shard_index.to_pair_index(comm.n_shards(), metadata.blob_id());
+    let request = comm
+        .client
+        .get_sliver::<A>(metadata.blob_id(), sliver_pair_index);
+    let sliver = match tokio::time::timeout(timeout, request).await {
+        Ok(Ok(sliver)) => sliver,
+        Ok(Err(error)) if error.is_status_not_found() => return ShardAuditOutcome::Missing,
+        Ok(Err(error)) => {
+            return ShardAuditOutcome::Failed {
+                error: error.to_string(),
+            };
+        }
+        Err(_) => {
+            return ShardAuditOutcome::Failed {
+                error: format!("the request timed out after {timeout:?}"),
+            };
+        }
+    };
+    match sliver.verify(comm.encoding_config, metadata.metadata()) {
+        Ok(()) => ShardAuditOutcome::Verified,
+        Err(error) => ShardAuditOutcome::Invalid {
+            error: error.to_string(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use walrus_test_utils::param_test;
+
+    use super::*;
+
+    param_test! {
+        test_decodability_margins: [
+            full: (10, 10, BlobAvailability::Full, 6, 3),
+            degraded: (10, 7, BlobAvailability::Degraded, 3, 0),
+            at_risk: (10, 4, BlobAvailability::AtRisk, 0, -3),
+            unrecoverable: (10, 3, BlobAvailability::Unrecoverable, -1, -4),
+        ]
+    }
+    fn test_decodability_margins(
+        n_shards: u16,
+        verified_shards: u16,
+        expected_availability: BlobAvailability,
+        expected_reconstruction_margin: i32,
+        expected_bft_margin: i32,
+    ) {
+        // With 10 shards, f = 3 and 4 = n - 2f primary slivers are required for reconstruction.
+        let n_shards = NonZeroU16::new(n_shards).expect("n_shards is non-zero");
+        let margins = DecodabilityMargins::new(n_shards, 4, verified_shards);
+
+        assert_eq!(margins.max_faulty_shards, 3);
+        assert_eq!(margins.min_correct_shards, 7);
+        assert_eq!(margins.unhealthy_shards, 10 - verified_shards);
+        assert_eq!(margins.availability, expected_availability);
+        assert_eq!(
+            margins.reconstruction_margin,
+            expected_reconstruction_margin
+        );
+        assert_eq!(margins.bft_margin, expected_bft_margin);
+    }
+}
@@ -25,6 +25,7 @@ use walrus_sui::{
 
 use super::{
     ClientError,
+    ClientErrorKind,
     ClientResult,
     resource::{PriceComputation, RegisterBlobOp, StoreOp},
     responses::{BlobStoreResult, EventOrObjectId},
@@ -792,7 +793,14 @@ impl<'a, T: Debug + Clone + Send + Sync> WalrusStoreBlobApi<'a, T> for BlobWithS
                     WalrusStoreBlob::WithStatus(self)
                 }
             }
-            BlobStatus::Invalid { event } => {
+            // The storage nodes refuse to store blobs on their deny lists.
+            BlobStatus::Invalid {
+                deny_listed: true, ..
+            } => self.complete_with(BlobStoreResult::Error {
+                blob_id: Some(blob_id),
+                error_msg: ClientErrorKind::BlobIdBlocked(blob_id).to_string(),
+            }),
+            BlobStatus::Invalid { event, .. } => {
                 self.complete_with(BlobStoreResult::MarkedInvalid { blob_id, event })
             }
             _ => WalrusStoreBlob::WithStatus(self),
@@ -974,7 +982,10 @@ impl<'a, T: Debug + Clone + Send + Sync> WalrusStoreBlobApi<'a, T> for Registere
     }
 
     fn ready_to_store_to_nodes(&self) -> bool {
-        let StoreOp::RegisterNew { operation, blob } = &self.operation else {
+        let StoreOp::RegisterNew {
+            operation, blob, ..
+        } = &self.operation
+        else {
             return false;
         };
 
@@ -991,7 +1002,10 @@ impl<'a, T: Debug + Clone + Send + Sync> WalrusStoreBlobApi<'a, T> for Registere
     }
 
     fn ready_to_extend(&self) -> bool {
-        let StoreOp::RegisterNew { operation, blob } = &self.operation else {
+        let StoreOp::RegisterNew {
+            operation, blob, ..
+        } = &self.operation
+        else {
             return false;
         };
 
@@ -1009,6 +1023,7 @@ impl<'a, T: Debug + Clone + Send + Sync> WalrusStoreBlobApi<'a, T> for Registere
                     epochs_extended, ..
                 },
             blob,
+            ..
         } = &self.operation
         {
             Ok(CertifyAndExtendBlobParams {
@@ -1116,7 +1131,12 @@ impl<'a, T: Debug + Clone + Send + Sync> WalrusStoreBlobApi<'a, T> for Registere
             );
         });
 
-        let StoreOp::RegisterNew { operation, blob } = &self.operation else {
+        let StoreOp::RegisterNew {
+            operation,
+            blob,
+            paid_with_credits,
+        } = &self.operation
+        else {
             return Err(invalid_operation_for_blob(
                 &self,
                 format!("with_certify_and_extend_result: {:?}", self.operation),
@@ -1132,8 +1152,12 @@ impl<'a, T: Debug + Clone + Send + Sync> WalrusStoreBlobApi<'a, T> for Registere
 
         let resource_operation = operation.clone();
         let blob_object = blob.clone();
+        let credits_discount = (*paid_with_credits || certify_and_extend_result.paid_with_credits)
+            .then(|| price_computation.credits_discount(&resource_operation))
+            .flatten();
         let new_state = self.complete_with(BlobStoreResult::NewlyCreated {
             cost: price_computation.operation_cost(&resource_operation),
+            credits_discount,
             blob_object,
             resource_operation,
             // TODO: pass error back to the caller.
@@ -1260,7 +1284,10 @@ impl<'a, T: Debug + Clone + Send + Sync> WalrusStoreBlobApi<'a, T> for BlobWithC
     }
 
     fn get_certify_and_extend_params(&self) -> ClientResult<CertifyAndExtendBlobParams<'_>> {
-        let StoreOp::RegisterNew { operation, blob } = &self.operation else {
+        let StoreOp::RegisterNew {
+            operation, blob, ..
+        } = &self.operation
+        else {
             return Err(invalid_operation_for_blob(
                 &self,
                 format!("get_certify_and_extend_params: {:?}", self.operation),
@@ -1354,17 +1381,26 @@ impl<'a, T: Debug + Clone + Send + Sync> WalrusStoreBlobApi<'a, T> for BlobWithC
             );
         });
 
-        let StoreOp::RegisterNew { operation, blob } = &self.operation else {
+        let StoreOp::RegisterNew {
+            operation,
+            blob,
+            paid_with_credits,
+        } = &self.operation
+        else {
             return Err(invalid_operation_for_blob(
                 &self,
                 format!("with_certify_and_extend_result: {certify_and_extend_result:?}"),
             ));
         };
 
+        let credits_discount = (*paid_with_credits || certify_and_extend_result.paid_with_credits)
+            .then(|| price_computation.credits_discount(operation))
+            .flatten();
         let store_result = BlobStoreResult::NewlyCreated {
             blob_object: blob.clone(),
             resource_operation: operation.clone(),
             cost: price_computation.operation_cost(operation),
+            credits_discount,
             shared_blob_object: certify_and_extend_result.shared_blob_object(),
         };
 
@@ -4,7 +4,7 @@
 //! Client for storing and retrieving quilts.
 
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     fs,
     marker::PhantomData,
     path::{Path, PathBuf},
@@ -21,6 +21,7 @@ use walrus_core::{
     SliverIndex,
     encoding::{
         BLOB_TYPE_ATTRIBUTE_KEY,
+        PREVIOUS_QUILT_ID_ATTRIBUTE_KEY,
         Primary,
         QUILT_TYPE_VALUE,
         QuiltError,
@@ -28,10 +29,10 @@ use walrus_core::{
         SliverData,
         quilt_encoding::*,
     },
-    metadata::{QuiltIndex, QuiltMetadata, QuiltMetadataV1, VerifiedBlobMetadataWithId},
+    metadata::{QuiltIndex, QuiltMetadata, VerifiedBlobMetadataWithId},
 };
 use walrus_sui::{
-    client::{ReadClient, SuiContractClient},
+    client::{ReadClient, WriteClient},
     types::move_structs::BlobAttribute,
 };
 use walrus_utils::read_blob_from_file;
@@ -41,7 +42,7 @@ use crate::{
         StoreArgs,
         WalrusNodeClient,
         client_types::StoredQuiltPatch,
-        responses::QuiltStoreResult,
+        responses::{QuiltPatchChange, QuiltPatchMapping, QuiltStoreResult, QuiltUpdateResult},
     },
     error::{ClientError, ClientErrorKind, ClientResult},
 };
@@ -126,6 +127,127 @@ fn get_all_files_from_path<P: AsRef<Path>>(path: P) -> ClientResult<HashSet<Path
     Ok(collected_files)
 }
 
+/// The changes to apply to an existing quilt to build a new version of it.
+#[derive(Debug, Clone, Default)]
+pub struct QuiltUpdate<'a> {
+    /// Blobs to add to the quilt; their identifiers must not exist in the quilt.
+    pub additions: Vec<QuiltStoreBlob<'a>>,
+    /// Blobs replacing the patches with the same identifiers in the quilt.
+    pub replacements: Vec<QuiltStoreBlob<'a>>,
+    /// The identifiers of the patches to remove from the quilt.
+    pub deletions: Vec<String>,
+}
+
+impl<'a> QuiltUpdate<'a> {
+    /// Returns true if the update does not contain any changes.
+    pub fn is_empty(&self) -> bool {
+        self.additions.is_empty() && self.replacements.is_empty() && self.deletions.is_empty()
+    }
+
+    /// Checks the update against the identifiers of the patches in the existing quilt, and returns
+    /// the change of each patch of the existing and the new quilt, ordered by identifier.
+    ///
+    /// Returns an error if an added patch already exists, if a replaced or deleted patch does not
+    /// exist, or if an identifier is changed more than once.
+    pub fn changes<'b>(
+        &self,
+        existing_identifiers: impl IntoIterator<Item = &'b str>,
+    ) -> ClientResult<Vec<(String, QuiltPatchChange)>> {
+        if self.is_empty() {
+            return Err(ClientError::from(ClientErrorKind::Other(
+                "the quilt update does not contain any changes".into(),
+            )));
+        }
+
+        let mut changes: BTreeMap<String, QuiltPatchChange> = existing_identifiers
+            .into_iter()
+            .map(|identifier| (identifier.to_owned(), QuiltPatchChange::Unchanged))
+            .collect();
+        let mut changed = HashSet::new();
+        let mut missing = Vec::new();
+        let updates = self
+            .additions
+            .iter()
+            .map(|blob| (blob.identifier(), QuiltPatchChange::Added))
+            .chain(
+                self.replacements
+                    .iter()
+                    .map(|blob| (blob.identifier(), QuiltPatchChange::Replaced)),
+            )
+            .chain(
+                self.deletions
+                    .iter()
+                    .map(|identifier| (identifier.as_str(), QuiltPatchChange::Deleted)),
+            );
+        for (identifier, change) in updates {
+            if !changed.insert(identifier) {
+                return Err(QuiltError::DuplicateIdentifier(identifier.to_owned()).into());
+            }
+            match (changes.get_mut(identifier), change) {
+                (Some(_), QuiltPatchChange::Added) => {
+                    return Err(QuiltError::DuplicateIdentifier(identifier.to_owned()).into());
+                }
+                (None, QuiltPatchChange::Added) => {
+                    changes.insert(identifier.to_owned(), change);
+                }
+                (Some(existing), _) => *existing = change,
+                (None, _) => missing.push(identifier.to_owned()),
+            }
+        }
+        if !missing.is_empty() {
+            return Err(QuiltError::BlobsNotFoundInQuilt(missing).into());
+        }
+
+        Ok(changes.into_iter().collect())
+    }
+
+    /// Returns the added and replacing blobs.
+    pub fn into_blobs(self) -> impl Iterator<Item = QuiltStoreBlob<'a>> {
+        self.additions.into_iter().chain(self.replacements)
+    }
+}
+
+/// Returns the mapping of the patches of the previous quilt to the patches of the new quilt.
+fn quilt_patch_mapping(
+    changes: Vec<(String, QuiltPatchChange)>,
+    previous_patches: &[StoredQuiltPatch],
+    new_patches: &[StoredQuiltPatch],
+) -> Vec<QuiltPatchMapping> {
+    let find_patch_id = |patches: &[StoredQuiltPatch], identifier: &str| {
+        patches
+            .iter()
+            .find(|patch| patch.identifier == identifier)
+            .map(|patch| patch.quilt_patch_id.clone())
+    };
+    changes
+        .into_iter()
+        .map(|(identifier, change)| QuiltPatchMapping {
+            previous_quilt_patch_id: find_patch_id(previous_patches, &identifier),
+            new_quilt_patch_id: find_patch_id(new_patches, &identifier),
+            identifier,
+            change,
+        })
+        .collect()
+}
+
+/// Returns the identifier and quilt patch ID of each patch in the quilt index.
+fn stored_quilt_patches<V: QuiltVersion>(
+    quilt_id: &BlobId,
+    quilt_index: &V::QuiltIndex,
+) -> Vec<StoredQuiltPatch> {
+    quilt_index
+        .patches()
+        .iter()
+        .map(|patch| {
+            StoredQuiltPatch::new(
+                *quilt_id,
+                patch.identifier(),
+                patch.quilt_patch_internal_id(),
+            )
+        })
+        .collect()
+}
+
 /// A wrapper around QuiltDecoder, slivers and quilt index.
 ///
 /// This is used to cache the slivers and quilt index for a given quilt.
@@ -379,12 +501,17 @@ impl Default for QuiltClientConfig {
 pub struct QuiltClient<'a, T> {
     client: &'a WalrusNodeClient<T>,
     config: QuiltClientConfig,
+    decompress: bool,
 }
 
 impl<'a, T> QuiltClient<'a, T> {
     /// Creates a new QuiltClient.
     pub fn new(client: &'a WalrusNodeClient<T>, config: QuiltClientConfig) -> Self {
-        Self { client, config }
+        Self {
+            client,
+            config,
+            decompress: true,
+        }
     }
 
     /// Update quilt client config.
@@ -392,6 +519,33 @@ impl<'a, T> QuiltClient<'a, T> {
         self.config = config;
         self
     }
+
+    /// Sets whether compressed quilt patches are decompressed before they are returned.
+    ///
+    /// Decompression is enabled by default. If disabled, the returned blobs contain the data as
+    /// stored in the quilt, see [`QuiltStoreBlob::compression`].
+    pub fn with_decompression(mut self, decompress: bool) -> Self {
+        self.decompress = decompress;
+        self
+    }
+
+    /// Decompresses the blobs on the blocking thread pool, unless decompression is disabled.
+    async fn decompress_blobs(
+        &self,
+        blobs: Vec<QuiltStoreBlob<'static>>,
+    ) -> ClientResult<Vec<QuiltStoreBlob<'static>>> {
+        if !self.decompress || !blobs.iter().any(|blob| blob.compression().is_compressed()) {
+            return Ok(blobs);
+        }
+        tokio::task::spawn_blocking(move || {
+            blobs
+                .into_iter()
+                .map(|blob| blob.decompress().map_err(ClientError::from))
+                .collect()
+        })
+        .await
+        .map_err(ClientError::other)?
+    }
 }
 
 impl<T: ReadClient> QuiltClient<'_, T> {
@@ -426,15 +580,11 @@ impl<T: ReadClient> QuiltClient<'_, T> {
                     .get_quilt_index()?
             };
 
-        let quilt_metadata = match quilt_index {
-            QuiltIndex::V1(quilt_index) => QuiltMetadata::V1(QuiltMetadataV1 {
-                quilt_id: *quilt_id,
-                metadata: metadata.metadata().clone(),
-                index: quilt_index.clone(),
-            }),
-        };
-
-        Ok(quilt_metadata)
+        Ok(QuiltMetadata::new(
+            *quilt_id,
+            metadata.metadata().clone(),
+            quilt_index,
+        ))
     }
 
     /// Retrieves the necessary slivers and decodes the quilt index.
@@ -449,7 +599,7 @@ impl<T: ReadClient> QuiltClient<'_, T> {
         //
         // Since the quilt version is stored as the first byte of the Quilt, it doesn't matter
         // whether we get the first primary sliver or the first secondary sliver.
-        // For now since we only support QuiltV1, we use the first secondary sliver.
+        // All quilt versions are read from secondary slivers, so we use the first secondary sliver.
         let slivers = self
             .client
             .retrieve_slivers_retry_committees::<Secondary>(
@@ -473,6 +623,14 @@ impl<T: ReadClient> QuiltClient<'_, T> {
                 )
                 .await?
             }
+            QuiltVersionEnum::V2 => {
+                self.retrieve_quilt_index_internal::<QuiltVersionV2>(
+                    metadata,
+                    certified_epoch,
+                    first_sliver,
+                )
+                .await?
+            }
         };
 
         Ok(quilt_index)
@@ -521,32 +679,55 @@ impl<T: ReadClient> QuiltClient<'_, T> {
     ) -> ClientResult<Vec<QuiltStoreBlob<'static>>> {
         let metadata = self.get_quilt_metadata(quilt_id).await?;
 
-        match metadata {
+        let blobs = match metadata {
             QuiltMetadata::V1(metadata) => {
-                // Retrieve slivers for the given identifiers.
-                let sliver_indices = metadata
-                    .index
-                    .get_sliver_indices_for_identifiers(identifiers)?;
-                let (certified_epoch, _) = self
-                    .client
-                    .get_blob_status_and_certified_epoch(quilt_id, None)
-                    .await?;
-                let mut quilt_reader = QuiltReader::<'_, QuiltVersionV1, T>::new(
-                    self,
-                    self.config.clone(),
-                    Some(metadata.index.clone().into()),
+                self.get_blobs_by_identifiers_impl::<QuiltVersionV1>(
+                    quilt_id,
+                    &metadata.index,
+                    &metadata.get_verified_metadata(),
+                    identifiers,
                 )
-                .await;
-                quilt_reader
-                    .download_data(
-                        &sliver_indices,
-                        &metadata.get_verified_metadata(),
-                        certified_epoch,
-                    )
-                    .await?;
-                quilt_reader.get_blobs_by_identifiers(identifiers).await
+                .await
             }
-        }
+            QuiltMetadata::V2(metadata) => {
+                self.get_blobs_by_identifiers_impl::<QuiltVersionV2>(
+                    quilt_id,
+                    &metadata.index,
+                    &metadata.get_verified_metadata(),
+                    identifiers,
+                )
+                .await
+            }
+        }?;
+        self.decompress_blobs(blobs).await
+    }
+
+    async fn get_blobs_by_identifiers_impl<V: QuiltVersion>(
+        &self,
+        quilt_id: &BlobId,
+        quilt_index: &V::QuiltIndex,
+        metadata: &VerifiedBlobMetadataWithId,
+        identifiers: &[&str],
+    ) -> ClientResult<Vec<QuiltStoreBlob<'static>>>
+    where
+        SliverData<V::SliverAxis>: TryFrom<Sliver>,
+    {
+        // Retrieve slivers for the given identifiers.
+        let sliver_indices = quilt_index.get_sliver_indices_for_identifiers(identifiers)?;
+        let (certified_epoch, _) = self
+            .client
+            .get_blob_status_and_certified_epoch(quilt_id, None)
+            .await?;
+        let mut quilt_reader = QuiltReader::<'_, V, T>::new(
+            self,
+            self.config.clone(),
+            Some(quilt_index.clone().into()),
+        )
+        .await;
+        quilt_reader
+            .download_data(&sliver_indices, metadata, certified_epoch)
+            .await?;
+        quilt_reader.get_blobs_by_identifiers(identifiers).await
     }
 
     /// Retrieves the blobs from the quilt matching the given tag.
@@ -558,37 +739,119 @@ impl<T: ReadClient> QuiltClient<'_, T> {
     ) -> ClientResult<Vec<QuiltStoreBlob<'static>>> {
         let metadata = self.get_quilt_metadata(quilt_id).await?;
 
-        match metadata {
+        let blobs = match metadata {
             QuiltMetadata::V1(metadata) => {
-                let sliver_indices = metadata
-                    .index
-                    .get_sliver_indices_for_tag(target_tag, target_value);
-                if sliver_indices.is_empty() {
-                    return Ok(Vec::new());
-                }
+                self.get_blobs_by_tag_impl::<QuiltVersionV1>(
+                    quilt_id,
+                    &metadata.index,
+                    &metadata.get_verified_metadata(),
+                    target_tag,
+                    target_value,
+                )
+                .await
+            }
+            QuiltMetadata::V2(metadata) => {
+                self.get_blobs_by_tag_impl::<QuiltVersionV2>(
+                    quilt_id,
+                    &metadata.index,
+                    &metadata.get_verified_metadata(),
+                    target_tag,
+                    target_value,
+                )
+                .await
+            }
+        }?;
+        self.decompress_blobs(blobs).await
+    }
 
-                let (certified_epoch, _) = self
-                    .client
-                    .get_blob_status_and_certified_epoch(quilt_id, None)
-                    .await?;
-                let mut quilt_reader = QuiltReader::<'_, QuiltVersionV1, T>::new(
-                    self,
-                    self.config.clone(),
-                    Some(metadata.index.clone().into()),
+    async fn get_blobs_by_tag_impl<V: QuiltVersion>(
+        &self,
+        quilt_id: &BlobId,
+        quilt_index: &V::QuiltIndex,
+        metadata: &VerifiedBlobMetadataWithId,
+        target_tag: &str,
+        target_value: &str,
+    ) -> ClientResult<Vec<QuiltStoreBlob<'static>>>
+    where
+        SliverData<V::SliverAxis>: TryFrom<Sliver>,
+    {
+        let sliver_indices = quilt_index.get_sliver_indices_for_tag(target_tag, target_value);
+        if sliver_indices.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let (certified_epoch, _) = self
+            .client
+            .get_blob_status_and_certified_epoch(quilt_id, None)
+            .await?;
+        let mut quilt_reader = QuiltReader::<'_, V, T>::new(
+            self,
+            self.config.clone(),
+            Some(quilt_index.clone().into()),
+        )
+        .await;
+        quilt_reader
+            .download_data(&sliver_indices, metadata, certified_epoch)
+            .await?;
+        quilt_reader
+            .get_blobs_by_tag(target_tag, target_value)
+            .await
+    }
+
+    /// Retrieves the blobs from the quilt matching the given filter.
+    ///
+    /// Only the slivers containing the matching patches are downloaded.
+    pub async fn get_blobs_by_filter(
+        &self,
+        quilt_id: &BlobId,
+        filter: &QuiltPatchFilter,
+    ) -> ClientResult<Vec<QuiltStoreBlob<'static>>> {
+        let metadata = self.get_quilt_metadata(quilt_id).await?;
+
+        let blobs = match metadata {
+            QuiltMetadata::V1(metadata) => {
+                self.get_blobs_by_filter_impl::<QuiltVersionV1>(
+                    quilt_id,
+                    &metadata.index,
+                    &metadata.get_verified_metadata(),
+                    filter,
                 )
-                .await;
-                quilt_reader
-                    .download_data(
-                        &sliver_indices,
-                        &metadata.get_verified_metadata(),
-                        certified_epoch,
-                    )
-                    .await?;
-                quilt_reader
-                    .get_blobs_by_tag(target_tag, target_value)
-                    .await
+                .await
             }
+            QuiltMetadata::V2(metadata) => {
+                self.get_blobs_by_filter_impl::<QuiltVersionV2>(
+                    quilt_id,
+                    &metadata.index,
+                    &metadata.get_verified_metadata(),
+                    filter,
+                )
+                .await
+            }
+        }?;
+        self.decompress_blobs(blobs).await
+    }
+
+    async fn get_blobs_by_filter_impl<V: QuiltVersion>(
+        &self,
+        quilt_id: &BlobId,
+        quilt_index: &V::QuiltIndex,
+        metadata: &VerifiedBlobMetadataWithId,
+        filter: &QuiltPatchFilter,
+    ) -> ClientResult<Vec<QuiltStoreBlob<'static>>>
+    where
+        SliverData<V::SliverAxis>: TryFrom<Sliver>,
+    {
+        let identifiers = quilt_index
+            .get_quilt_patches_by_filter(filter)
+            .into_iter()
+            .map(|patch| patch.identifier())
+            .collect::<Vec<_>>();
+        if identifiers.is_empty() {
+            return Ok(Vec::new());
         }
+
+        self.get_blobs_by_identifiers_impl::<V>(quilt_id, quilt_index, metadata, &identifiers)
+            .await
     }
 
     /// Retrieves blobs from the quilt matching the given QuiltPatchIds.
@@ -612,7 +875,8 @@ impl<T: ReadClient> QuiltClient<'_, T> {
 
         let results = futures::future::try_join_all(futures).await?;
 
-        Ok(results.into_iter().flatten().collect())
+        self.decompress_blobs(results.into_iter().flatten().collect())
+            .await
     }
 
     async fn get_blobs_from_quilt_by_internal_ids(
@@ -648,6 +912,14 @@ impl<T: ReadClient> QuiltClient<'_, T> {
                 )
                 .await
             }
+            QuiltVersionEnum::V2 => {
+                self.get_blobs_from_quilt_by_internal_ids_impl::<QuiltVersionV2>(
+                    &metadata,
+                    certified_epoch,
+                    quilt_patch_ids,
+                )
+                .await
+            }
         }
     }
 
@@ -656,15 +928,17 @@ impl<T: ReadClient> QuiltClient<'_, T> {
         metadata: &VerifiedBlobMetadataWithId,
         certified_epoch: Epoch,
         quilt_ids: &[QuiltPatchId],
-    ) -> ClientResult<Vec<QuiltStoreBlob<'static>>> {
+    ) -> ClientResult<Vec<QuiltStoreBlob<'static>>>
+    where
+        SliverData<V::SliverAxis>: TryFrom<Sliver>,
+    {
         let mut sliver_indices = Vec::new();
         for quilt_id in quilt_ids {
             let id = V::QuiltPatchInternalId::from_bytes(&quilt_id.patch_id_bytes)?;
             sliver_indices.extend(id.sliver_indices());
         }
 
-        let mut quilt_reader =
-            QuiltReader::<'_, QuiltVersionV1, T>::new(self, self.config.clone(), None).await;
+        let mut quilt_reader = QuiltReader::<'_, V, T>::new(self, self.config.clone(), None).await;
         quilt_reader
             .download_data(&sliver_indices, metadata, certified_epoch)
             .await?;
@@ -693,7 +967,10 @@ impl<T: ReadClient> QuiltClient<'_, T> {
 
         let mut quilt_reader =
             QuiltReader::<'_, QuiltVersionV1, T>::new(self, self.config.clone(), None).await;
-        quilt_reader.get_all_blobs(&metadata, certified_epoch).await
+        let blobs = quilt_reader
+            .get_all_blobs(&metadata, certified_epoch)
+            .await?;
+        self.decompress_blobs(blobs).await
     }
 
     /// Retrieves the quilt from Walrus.
@@ -716,7 +993,7 @@ impl<T: ReadClient> QuiltClient<'_, T> {
 }
 
 /// Stores quilts.
-impl QuiltClient<'_, SuiContractClient> {
+impl<T: WriteClient> QuiltClient<'_, T> {
     /// Constructs a quilt from a list of blobs.
     pub async fn construct_quilt<V: QuiltVersion>(
         &self,
@@ -774,16 +1051,114 @@ impl QuiltClient<'_, SuiContractClient> {
     }
 
     /// Encodes the blobs to a quilt and stores it to Walrus.
-    #[tracing::instrument(skip_all, fields(blob_id))]
     pub async fn reserve_and_store_quilt<V: QuiltVersion>(
         &self,
         quilt: &V::Quilt,
         store_args: &StoreArgs,
     ) -> ClientResult<QuiltStoreResult> {
-        let attributes = vec![BlobAttribute::from([(
-            BLOB_TYPE_ATTRIBUTE_KEY,
-            QUILT_TYPE_VALUE,
-        )])];
+        let attribute = BlobAttribute::from([(BLOB_TYPE_ATTRIBUTE_KEY, QUILT_TYPE_VALUE)]);
+        self.reserve_and_store_quilt_with_attribute::<V>(quilt, attribute, store_args)
+            .await
+    }
+
+    /// Stores a new version of an existing quilt, with the changes of the update applied.
+    ///
+    /// The unchanged patches are fetched from the existing quilt and reused as stored, i.e.,
+    /// compressed patches are not decompressed. The new quilt has the same version as the existing
+    /// quilt. If `record_lineage` is true, the ID of the existing quilt is recorded in the
+    /// [`PREVIOUS_QUILT_ID_ATTRIBUTE_KEY`] attribute of the new blob object.
+    #[tracing::instrument(skip_all, fields(%quilt_id))]
+    pub async fn update_quilt(
+        &self,
+        quilt_id: &BlobId,
+        update: QuiltUpdate<'_>,
+        store_args: &StoreArgs,
+        record_lineage: bool,
+    ) -> ClientResult<QuiltUpdateResult> {
+        let metadata = self.get_quilt_metadata(quilt_id).await?;
+        let previous_patches = match &metadata {
+            QuiltMetadata::V1(metadata) => {
+                stored_quilt_patches::<QuiltVersionV1>(quilt_id, &metadata.index)
+            }
+            QuiltMetadata::V2(metadata) => {
+                stored_quilt_patches::<QuiltVersionV2>(quilt_id, &metadata.index)
+            }
+        };
+        let changes = update.changes(
+            previous_patches
+                .iter()
+                .map(|patch| patch.identifier.as_str()),
+        )?;
+
+        let unchanged = changes
+            .iter()
+            .filter(|(_, change)| *change == QuiltPatchChange::Unchanged)
+            .map(|(identifier, _)| identifier.as_str())
+            .collect::<Vec<_>>();
+        let mut blobs: Vec<QuiltStoreBlob<'_>> = if unchanged.is_empty() {
+            Vec::new()
+        } else {
+            QuiltClient::new(self.client, self.config.clone())
+                .with_decompression(false)
+                .get_blobs_by_identifiers(quilt_id, &unchanged)
+                .await?
+        };
+        blobs.extend(update.into_blobs());
+        tracing::debug!(
+            n_unchanged = unchanged.len(),
+            n_blobs = blobs.len(),
+            "constructing the updated quilt"
+        );
+
+        let mut attribute = BlobAttribute::from([(BLOB_TYPE_ATTRIBUTE_KEY, QUILT_TYPE_VALUE)]);
+        if record_lineage {
+            attribute.insert(
+                PREVIOUS_QUILT_ID_ATTRIBUTE_KEY.to_owned(),
+                quilt_id.to_string(),
+            );
+        }
+        let quilt_store_result = match metadata {
+            QuiltMetadata::V1(_) => {
+                let quilt = self
+                    .construct_quilt::<QuiltVersionV1>(&blobs, store_args.encoding_type)
+                    .await?;
+                self.reserve_and_store_quilt_with_attribute::<QuiltVersionV1>(
+                    &quilt, attribute, store_args,
+                )
+                .await?
+            }
+            QuiltMetadata::V2(_) => {
+                let quilt = self
+                    .construct_quilt::<QuiltVersionV2>(&blobs, store_args.encoding_type)
+                    .await?;
+                self.reserve_and_store_quilt_with_attribute::<QuiltVersionV2>(
+                    &quilt, attribute, store_args,
+                )
+                .await?
+            }
+        };
+
+        Ok(QuiltUpdateResult {
+            previous_quilt_id: *quilt_id,
+            patch_mapping: quilt_patch_mapping(
+                changes,
+                &previous_patches,
+                &quilt_store_result.stored_quilt_blobs,
+            ),
+            quilt_store_result,
+        })
+    }
+
+    /// Encodes the blobs to a quilt and stores it to Walrus, with the attribute set on the blob
+    /// object.
+    #[tracing::instrument(skip_all, fields(blob_id))]
+    async fn reserve_and_store_quilt_with_attribute<V: QuiltVersion>(
+        &self,
+        quilt: &V::Quilt,
+        attribute: BlobAttribute,
+        store_args: &StoreArgs,
+    ) -> ClientResult<QuiltStoreResult> {
+        let attributes = vec![attribute];
         let result = self
             .client
             .reserve_and_store_blobs_retry_committees(&[quilt.data()], &attributes, store_args)
@@ -879,6 +1254,102 @@ mod tests {
         Ok(file_contents)
     }
 
+    fn quilt_update(
+        additions: &[&str],
+        replacements: &[&str],
+        deletions: &[&str],
+    ) -> QuiltUpdate<'static> {
+        let blobs = |identifiers: &[&str]| {
+            identifiers
+                .iter()
+                .map(|&identifier| {
+                    QuiltStoreBlob::new_owned(identifier.as_bytes().to_vec(), identifier)
+                        .expect("the identifier is valid")
+                })
+                .collect()
+        };
+        QuiltUpdate {
+            additions: blobs(additions),
+            replacements: blobs(replacements),
+            deletions: deletions
+                .iter()
+                .map(|&identifier| identifier.to_owned())
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_quilt_update_changes() -> ClientResult<()> {
+        let existing = ["a.txt", "b.txt", "c.txt"];
+        let update = quilt_update(&["d.txt"], &["b.txt"], &["c.txt"]);
+
+        assert_eq!(
+            update.changes(existing)?,
+            vec![
+                ("a.txt".to_owned(), QuiltPatchChange::Unchanged),
+                ("b.txt".to_owned(), QuiltPatchChange::Replaced),
+                ("c.txt".to_owned(), QuiltPatchChange::Deleted),
+                ("d.txt".to_owned(), QuiltPatchChange::Added),
+            ]
+        );
+        assert_eq!(
+            update
+                .into_blobs()
+                .map(|blob| blob.identifier().to_owned())
+                .collect::<Vec<_>>(),
+            ["d.txt", "b.txt"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_quilt_update_rejects_invalid_changes() {
+        let existing = ["a.txt", "b.txt"];
+        for update in [
+            quilt_update(&[], &[], &[]),
+            quilt_update(&["a.txt"], &[], &[]),
+            quilt_update(&[], &["c.txt"], &[]),
+            quilt_update(&[], &[], &["c.txt"]),
+            quilt_update(&[], &["a.txt"], &["a.txt"]),
+            quilt_update(&["c.txt", "c.txt"], &[], &[]),
+        ] {
+            assert!(update.changes(existing).is_err(), "{update:?}");
+        }
+    }
+
+    #[test]
+    fn test_quilt_patch_mapping() {
+        let patch = |identifier: &str, patch_id: &str| StoredQuiltPatch {
+            identifier: identifier.to_owned(),
+            quilt_patch_id: patch_id.to_owned(),
+        };
+        let previous = [patch("a.txt", "old-a"), patch("b.txt", "old-b")];
+        let new = [patch("a.txt", "new-a"), patch("c.txt", "new-c")];
+        let changes = vec![
+            ("a.txt".to_owned(), QuiltPatchChange::Unchanged),
+            ("b.txt".to_owned(), QuiltPatchChange::Deleted),
+            ("c.txt".to_owned(), QuiltPatchChange::Added),
+        ];
+
+        let mapping = quilt_patch_mapping(changes, &previous, &new);
+
+        assert_eq!(
+            mapping
+                .iter()
+                .map(|patch| (
+                    patch.identifier.as_str(),
+                    patch.previous_quilt_patch_id.as_deref(),
+                    patch.new_quilt_patch_id.as_deref()
+                ))
+                .collect::<Vec<_>>(),
+            [
+                ("a.txt", Some("old-a"), Some("new-a")),
+                ("b.txt", Some("old-b"), None),
+                ("c.txt", None, Some("new-c")),
+            ]
+        );
+    }
+
     #[test]
     fn test_read_blobs_from_paths_complex() -> ClientResult<()> {
         // Create a temporary directory.
@@ -15,9 +15,15 @@ use walrus_core::{
     metadata::{BlobMetadataApi as _, VerifiedBlobMetadataWithId},
 };
 use walrus_sui::{
-    client::{BlobPersistence, ExpirySelectionPolicy, SuiContractClient},
+    client::{
+        BlobPersistence,
+        CreditsPolicy,
+        ExpirySelectionPolicy,
+        RegisterBlobsResult,
+        WriteClient,
+    },
     types::Blob,
-    utils::price_for_encoded_length,
+    utils::{TEN_THOUSAND_BASIS_POINTS, price_for_encoded_length},
 };
 
 use super::{
@@ -35,6 +41,8 @@ use crate::{
 pub struct PriceComputation {
     storage_price_per_unit_size: u64,
     write_price_per_unit_size: u64,
+    /// The buyer subsidy rate of the credits object in basis points, if credits are used.
+    buyer_subsidy_rate: Option<u16>,
 }
 
 impl PriceComputation {
@@ -42,9 +50,26 @@ impl PriceComputation {
         Self {
             storage_price_per_unit_size,
             write_price_per_unit_size,
+            buyer_subsidy_rate: None,
         }
     }
 
+    /// Sets the buyer subsidy rate (in basis points) applied when paying through credits.
+    pub fn with_buyer_subsidy_rate(mut self, buyer_subsidy_rate: Option<u16>) -> Self {
+        self.buyer_subsidy_rate = buyer_subsidy_rate;
+        self
+    }
+
+    /// Computes the discount obtained through credits on the cost of an operation that was paid
+    /// through credits.
+    ///
+    /// Returns `None` if no buyer subsidy rate is set.
+    pub fn credits_discount(&self, operation: &RegisterBlobOp) -> Option<u64> {
+        self.buyer_subsidy_rate.map(|rate| {
+            self.operation_cost(operation) * u64::from(rate) / TEN_THOUSAND_BASIS_POINTS
+        })
+    }
+
     /// Computes the cost of the operation.
     pub fn operation_cost(&self, operation: &RegisterBlobOp) -> u64 {
         match operation {
@@ -185,12 +210,17 @@ pub enum StoreOp {
         blob: Blob,
         /// The operation to be performed.
         operation: RegisterBlobOp,
+        /// Whether the registration was paid through Walrus credits.
+        paid_with_credits: bool,
     },
 }
 
 impl StoreOp {
     /// Creates a new store operation.
-    pub fn new(register_op: RegisterBlobOp, blob: Blob) -> Self {
+    ///
+    /// `paid_with_credits` indicates whether the registration of the blob was paid through
+    /// Walrus credits.
+    pub fn new(register_op: RegisterBlobOp, blob: Blob, paid_with_credits: bool) -> Self {
         match register_op {
             RegisterBlobOp::ReuseRegistration { .. } => {
                 if blob.certified_epoch.is_some() {
@@ -203,6 +233,7 @@ impl StoreOp {
                     StoreOp::RegisterNew {
                         blob,
                         operation: register_op,
+                        paid_with_credits,
                     }
                 }
             }
@@ -212,27 +243,56 @@ impl StoreOp {
             | RegisterBlobOp::ReuseAndExtendNonCertified { .. } => StoreOp::RegisterNew {
                 blob,
                 operation: register_op,
+                paid_with_credits,
             },
         }
     }
 }
 
+/// A blob object with the operation used to obtain it.
+#[derive(Debug)]
+struct RegisteredBlobOp {
+    blob: Blob,
+    operation: RegisterBlobOp,
+    /// Whether the registration was paid through Walrus credits.
+    paid_with_credits: bool,
+}
+
+impl RegisteredBlobOp {
+    /// Returns a blob object that was already owned by the wallet, so no registration was paid.
+    fn existing(blob: Blob, operation: RegisterBlobOp) -> Self {
+        Self {
+            blob,
+            operation,
+            paid_with_credits: false,
+        }
+    }
+}
+
 /// Manages the storage and blob resources in the Wallet on behalf of the client.
 #[derive(Debug)]
-pub struct ResourceManager<'a> {
-    sui_client: &'a SuiContractClient,
+pub struct ResourceManager<'a, C> {
+    sui_client: &'a C,
     write_committee_epoch: Epoch,
+    credits_policy: CreditsPolicy,
 }
 
-impl<'a> ResourceManager<'a> {
+impl<'a, C: WriteClient> ResourceManager<'a, C> {
     /// Creates a new resource manager.
-    pub fn new(sui_client: &'a SuiContractClient, write_committee_epoch: Epoch) -> Self {
+    pub fn new(sui_client: &'a C, write_committee_epoch: Epoch) -> Self {
         Self {
             sui_client,
             write_committee_epoch,
+            credits_policy: CreditsPolicy::default(),
         }
     }
 
+    /// Sets the policy determining whether newly purchased resources are paid for through credits.
+    pub fn with_credits_policy(mut self, credits_policy: CreditsPolicy) -> Self {
+        self.credits_policy = credits_policy;
+        self
+    }
+
     /// Returns a list of appropriate store operation for the given blobs.
     ///
     /// The function considers the requirements given to the store operation (epochs ahead,
@@ -328,7 +388,7 @@ impl<'a> ResourceManager<'a> {
                 })
                 .collect();
 
-        if store_optimizations.should_check_existing_resources() {
+        let results = if store_optimizations.should_check_existing_resources() {
             self.get_existing_or_register_with_resources(
                 &encoded_lengths?,
                 epochs_ahead,
@@ -336,7 +396,7 @@ impl<'a> ResourceManager<'a> {
                 persistence,
                 store_optimizations,
             )
-            .await
+            .await?
         } else {
             tracing::debug!(
                 "ignoring existing resources and creating a new registration from scratch"
@@ -347,8 +407,12 @@ impl<'a> ResourceManager<'a> {
                 metadata_list,
                 persistence,
             )
-            .await
-        }
+            .await?
+        };
+        Ok(results
+            .into_iter()
+            .map(|registered| (registered.blob, registered.operation))
+            .collect())
     }
 
     /// Registers or reuses resources for a list of blobs.
@@ -411,12 +475,11 @@ impl<'a> ResourceManager<'a> {
 
         // TODO(WAL-754): Check if we can make sure results and blobs have the same order.
         let mut blob_id_map = HashMap::new();
-        results.into_iter().for_each(|(blob, op)| {
-            let blob_id = blob.blob_id;
+        results.into_iter().for_each(|registered| {
             blob_id_map
-                .entry(blob_id)
+                .entry(registered.blob.blob_id)
                 .or_insert_with(Vec::new)
-                .push((blob, op));
+                .push(registered);
         });
 
         Ok(blobs
@@ -425,20 +488,24 @@ impl<'a> ResourceManager<'a> {
                 // Get the blob ID if available
                 let blob_id = blob.get_blob_id().expect("blob ID should be present");
 
-                // Get the vec of (blob, op) pairs for this blob ID
+                // Get the vec of registered blobs for this blob ID
                 let Some(entries) = blob_id_map.get_mut(&blob_id) else {
                     panic!("missing blob ID: {blob_id}");
                 };
 
-                // Pop one (blob, op) pair from the vec
-                if let Some((blob_obj, operation)) = entries.pop() {
+                // Pop one registered blob from the vec
+                if let Some(registered) = entries.pop() {
                     // If vec is now empty, remove the entry from the map
                     if entries.is_empty() {
                         blob_id_map.remove(&blob_id);
                     }
 
-                    blob.with_register_result(Ok(StoreOp::new(operation, blob_obj)))
-                        .expect("should succeed on a Ok result")
+                    blob.with_register_result(Ok(StoreOp::new(
+                        registered.operation,
+                        registered.blob,
+                        registered.paid_with_credits,
+                    )))
+                    .expect("should succeed on a Ok result")
                 } else {
                     panic!("missing blob ID: {blob_id}");
                 }
@@ -453,7 +520,7 @@ impl<'a> ResourceManager<'a> {
         metadata_list: &[&VerifiedBlobMetadataWithId],
         persistence: BlobPersistence,
         store_optimizations: StoreOptimizations,
-    ) -> ClientResult<Vec<(Blob, RegisterBlobOp)>> {
+    ) -> ClientResult<Vec<RegisteredBlobOp>> {
         let max_len = metadata_list.len();
         debug_assert!(
             encoded_lengths.len() == max_len,
@@ -503,7 +570,7 @@ impl<'a> ResourceManager<'a> {
                     let mut extended_blob = blob.clone();
                     extended_blob.storage.end_epoch = self.write_committee_epoch + epochs_ahead;
                     if blob.certified_epoch.is_some() {
-                        extended_blobs.push((
+                        extended_blobs.push(RegisteredBlobOp::existing(
                             extended_blob,
                             RegisterBlobOp::ReuseAndExtend {
                                 encoded_length: *encoded_length,
@@ -511,7 +578,7 @@ impl<'a> ResourceManager<'a> {
                             },
                         ));
                     } else {
-                        extended_blobs_noncertified.push((
+                        extended_blobs_noncertified.push(RegisteredBlobOp::existing(
                             extended_blob,
                             RegisterBlobOp::ReuseAndExtendNonCertified {
                                 encoded_length: *encoded_length,
@@ -520,7 +587,7 @@ impl<'a> ResourceManager<'a> {
                         ));
                     }
                 } else {
-                    results.push((
+                    results.push(RegisteredBlobOp::existing(
                         blob,
                         RegisterBlobOp::ReuseRegistration {
                             encoded_length: *encoded_length,
@@ -588,12 +655,23 @@ impl<'a> ResourceManager<'a> {
             num_blobs=%reused_metadata_with_storage.len(),
             "registering blobs with its storage resources"
         );
-        let blobs = self
+        let RegisterBlobsResult {
+            blobs,
+            paid_with_credits,
+        } = self
             .sui_client
-            .register_blobs(reused_metadata_with_storage, persistence)
+            .register_blobs(
+                reused_metadata_with_storage,
+                persistence,
+                self.credits_policy,
+            )
             .await?;
         results.extend(blobs.into_iter().zip(reused_encoded_lengths.iter()).map(
-            |(blob, &encoded_length)| (blob, RegisterBlobOp::ReuseStorage { encoded_length }),
+            |(blob, &encoded_length)| RegisteredBlobOp {
+                blob,
+                operation: RegisterBlobOp::ReuseStorage { encoded_length },
+                paid_with_credits,
+            },
         ));
 
         // Reserve space and register all in new_metadata_list in one ptb.
@@ -623,12 +701,15 @@ impl<'a> ResourceManager<'a> {
         epochs_ahead: EpochCount,
         metadata_list: &[&VerifiedBlobMetadataWithId],
         persistence: BlobPersistence,
-    ) -> ClientResult<Vec<(Blob, RegisterBlobOp)>> {
+    ) -> ClientResult<Vec<RegisteredBlobOp>> {
         debug_assert!(
             encoded_lengths.len() == metadata_list.len(),
             "inconsistent metadata and encoded lengths"
         );
-        let blobs = self
+        let RegisterBlobsResult {
+            blobs,
+            paid_with_credits,
+        } = self
             .sui_client
             .reserve_and_register_blobs(
                 epochs_ahead,
@@ -637,6 +718,7 @@ impl<'a> ResourceManager<'a> {
                     .map(|m| (*m).try_into())
                     .collect::<Result<Vec<_>, _>>()?,
                 persistence,
+                self.credits_policy,
             )
             .await?;
         debug_assert_eq!(
@@ -652,13 +734,14 @@ impl<'a> ResourceManager<'a> {
             .zip(encoded_lengths.iter())
             .map(|(blob, &encoded_length)| {
                 tracing::debug!(blob_id=%blob.blob_id, "registering blob from scratch");
-                (
+                RegisteredBlobOp {
                     blob,
-                    RegisterBlobOp::RegisterFromScratch {
+                    operation: RegisterBlobOp::RegisterFromScratch {
                         encoded_length,
                         epochs_ahead,
                     },
-                )
+                    paid_with_credits,
+                }
             })
             .collect())
     }
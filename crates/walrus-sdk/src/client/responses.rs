@@ -55,7 +55,7 @@ pub struct BlobStoreResultWithPath {
 
 /// Result when attempting to store a blob.
 #[serde_as]
-#[derive(Debug, Clone, Serialize, ToSchema)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase", rename_all_fields = "camelCase")]
 pub enum BlobStoreResult {
     /// The blob already exists within Walrus, was certified, and is stored for at least the
@@ -82,10 +82,15 @@ pub enum BlobStoreResult {
         /// The operation that created the blob.
         resource_operation: RegisterBlobOp,
         /// The storage cost, excluding gas.
+        ///
+        /// This is the full cost before any discount obtained through credits.
         cost: u64,
+        /// The part of the cost covered by the Walrus credits, if credits were used.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        credits_discount: Option<u64>,
         /// The shared blob object ID if created.
         #[serde_as(as = "Option<DisplayFromStr>")]
-        #[serde(skip_serializing_if = "Option::is_none")]
+        #[serde(default, skip_serializing_if = "Option::is_none")]
         #[schema(value_type = Option<ObjectIdSchema>)]
         shared_blob_object: Option<ObjectID>,
     },
@@ -150,3 +155,55 @@ pub struct QuiltStoreResult {
     /// The structure of the quilt.
     pub stored_quilt_blobs: Vec<StoredQuiltPatch>,
 }
+
+/// The change of a quilt patch when updating a quilt.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum QuiltPatchChange {
+    /// The patch was copied unchanged from the previous quilt.
+    Unchanged,
+    /// The patch was added to the quilt.
+    Added,
+    /// The patch replaced the patch with the same identifier in the previous quilt.
+    Replaced,
+    /// The patch was removed from the quilt.
+    Deleted,
+}
+
+impl Display for QuiltPatchChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let change = match self {
+            Self::Unchanged => "unchanged",
+            Self::Added => "added",
+            Self::Replaced => "replaced",
+            Self::Deleted => "deleted",
+        };
+        write!(f, "{change}")
+    }
+}
+
+/// The quilt patch IDs of a patch in the previous and the new version of an updated quilt.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct QuiltPatchMapping {
+    /// The identifier of the patch.
+    pub identifier: String,
+    /// The change of the patch.
+    pub change: QuiltPatchChange,
+    /// The quilt patch ID in the previous quilt, if the patch existed.
+    pub previous_quilt_patch_id: Option<String>,
+    /// The quilt patch ID in the new quilt, if the patch was not deleted.
+    pub new_quilt_patch_id: Option<String>,
+}
+
+/// Result when storing a new version of an existing quilt.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct QuiltUpdateResult {
+    /// The ID of the quilt that was updated.
+    pub previous_quilt_id: BlobId,
+    /// The result of storing the new quilt.
+    pub quilt_store_result: QuiltStoreResult,
+    /// The mapping of the patches of the previous quilt to the patches of the new quilt.
+    pub patch_mapping: Vec<QuiltPatchMapping>,
+}
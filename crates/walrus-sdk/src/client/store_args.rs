@@ -5,8 +5,11 @@
 
 use std::{num::NonZeroU16, sync::Arc, time::Duration};
 
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::UnboundedSender;
+use utoipa::ToSchema;
 use walrus_core::{DEFAULT_ENCODING, EncodingType, EpochCount};
-use walrus_sui::client::{BlobPersistence, PostStoreAction};
+use walrus_sui::client::{BlobPersistence, CreditsPolicy, PostStoreAction};
 
 use super::{metrics::ClientMetrics, upload_relay_client::UploadRelayClient};
 use crate::{
@@ -15,6 +18,23 @@ use crate::{
     upload_relay::tip_config::TipConfig,
 };
 
+/// The phases of a store operation, in the order in which they are entered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum StorePhase {
+    /// The blobs are being encoded.
+    Encoding,
+    /// The status of the blobs is checked and storage resources are obtained and registered.
+    Registering,
+    /// The slivers are being uploaded to the storage nodes to obtain the certificates.
+    UploadingSlivers,
+    /// The blobs are being certified on Sui.
+    Certifying,
+}
+
+/// A channel through which the phases of a store operation are notified.
+pub type StorePhaseNotifier = UnboundedSender<StorePhase>;
+
 /// Arguments for store operations that are frequently passed together.
 // NOTE: In the future, if the struct grows larger, we may need to consider using a builder.
 #[derive(Debug, Clone)]
@@ -29,10 +49,14 @@ pub struct StoreArgs {
     pub persistence: BlobPersistence,
     /// The post store action to use for the blob.
     pub post_store: PostStoreAction,
+    /// Whether to pay for storage and writes through the Walrus credits object.
+    pub credits_policy: CreditsPolicy,
     /// The metrics to use for the blob.
     pub metrics: Option<Arc<ClientMetrics>>,
     /// The optional upload relay client, that allows to store the blob via the relay.
     pub upload_relay_client: Option<UploadRelayClient>,
+    /// The optional channel through which the phases of the store operation are notified.
+    pub phase_notifier: Option<StorePhaseNotifier>,
 }
 
 impl StoreArgs {
@@ -50,8 +74,10 @@ impl StoreArgs {
             store_optimizations,
             persistence,
             post_store,
+            credits_policy: CreditsPolicy::default(),
             metrics: None,
             upload_relay_client: None,
+            phase_notifier: None,
         }
     }
 
@@ -68,8 +94,10 @@ impl StoreArgs {
             // TODO(WAL-911): Ensure this is changed to `Deletable`, once we switch the defaults.
             persistence: BlobPersistence::Permanent,
             post_store: PostStoreAction::Keep,
+            credits_policy: CreditsPolicy::default(),
             metrics: None,
             upload_relay_client: None,
+            phase_notifier: None,
         }
     }
 
@@ -114,12 +142,33 @@ impl StoreArgs {
         self
     }
 
+    /// Sets the credits policy.
+    pub fn with_credits_policy(mut self, credits_policy: CreditsPolicy) -> Self {
+        self.credits_policy = credits_policy;
+        self
+    }
+
     /// Adds metrics to the `StoreArgs`.
     pub fn with_metrics(mut self, metrics: Arc<ClientMetrics>) -> Self {
         self.metrics = Some(metrics);
         self
     }
 
+    /// Sets the channel through which the phases of the store operation are notified.
+    pub fn with_phase_notifier(mut self, phase_notifier: StorePhaseNotifier) -> Self {
+        self.phase_notifier = Some(phase_notifier);
+        self
+    }
+
+    /// Notifies the phase of the store operation, if a notifier is present.
+    ///
+    /// Failures to notify (e.g., because the receiver was dropped) are ignored.
+    pub fn maybe_notify_phase(&self, phase: StorePhase) {
+        if let Some(phase_notifier) = self.phase_notifier.as_ref() {
+            let _ = phase_notifier.send(phase);
+        }
+    }
+
     /// Returns a reference to the metrics if present.
     pub fn metrics_ref(&self) -> Option<&Arc<ClientMetrics>> {
         self.metrics.as_ref()
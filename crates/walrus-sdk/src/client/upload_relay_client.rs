@@ -18,14 +18,10 @@ use sui_sdk::rpc_types::SuiTransactionBlockResponse;
 use sui_types::{
     base_types::SuiAddress,
     digests::TransactionDigest,
-    programmable_transaction_builder::ProgrammableTransactionBuilder,
-    transaction::{Argument, Command, Transaction, TransactionData, TransactionKind},
+    transaction::{Transaction, TransactionData, TransactionKind},
 };
 use walrus_core::messages::BlobPersistenceType;
-use walrus_sui::client::{
-    SuiClientError,
-    transaction_builder::build_transaction_data_with_min_gas_balance,
-};
+use walrus_sui::client::SuiClientError;
 use walrus_utils::backoff::{self, BackoffStrategy, ExponentialBackoff, ExponentialBackoffConfig};
 
 use super::{WalrusStoreBlob, WalrusStoreBlobApi};
@@ -42,12 +38,7 @@ use crate::{
         metadata::{BlobMetadataApi, VerifiedBlobMetadataWithId},
     },
     sui::{
-        client::{
-            BlobPersistence,
-            CoinType,
-            SuiContractClient,
-            transaction_builder::WalrusPtbBuilder,
-        },
+        client::{BlobPersistence, CoinType, WriteClient, transaction_builder::WalrusPtbBuilder},
         config::WalletConfig,
         types::{BlobEvent, BlobRegistered},
         wallet::Wallet,
@@ -156,7 +147,7 @@ impl UploadRelayClient {
     /// Optionally returns the transaction ID of the payment transaction.
     pub async fn pay_tip_if_required(
         &self,
-        sui_client: &SuiContractClient,
+        sui_client: &impl WriteClient,
         auth_package: &AuthPackage,
         unencoded_length: u64,
         encoding_type: EncodingType,
@@ -186,40 +177,20 @@ impl UploadRelayClient {
     /// Returns the transaction ID of the payment transaction.
     async fn pay_tip(
         &self,
-        sui_client: &SuiContractClient,
+        sui_client: &impl WriteClient,
         relay_address: SuiAddress,
         auth_package: &AuthPackage,
         tip_amount: u64,
     ) -> Result<TransactionDigest, SuiClientError> {
-        let mut pt_builder = ProgrammableTransactionBuilder::new();
-
-        // The first input is the authentication package.
-        pt_builder.pure(auth_package.to_hashed_nonce())?;
-
-        // Pay the tip.
-        let amount_arg = pt_builder.pure(tip_amount)?;
-        let split_coin =
-            pt_builder.command(Command::SplitCoins(Argument::GasCoin, vec![amount_arg]));
-        pt_builder.transfer_arg(relay_address, split_coin);
-
-        // Sign and execute.
-        let gas_price = sui_client.read_client().get_reference_gas_price().await?;
-        let transaction_data = build_transaction_data_with_min_gas_balance(
-            pt_builder.finish(),
-            gas_price,
-            sui_client.read_client(),
-            self.user_address,
-            self.gas_budget,
-            0, // No additional gas budget.
-            tip_amount,
-        )
-        .await?;
-
-        let response = sui_client
-            .sign_and_send_transaction(transaction_data, "pay_tip")
-            .await?;
-
-        Ok(response.digest)
+        // The first input of the transaction is the hashed authentication package.
+        sui_client
+            .pay_tip(
+                relay_address,
+                &auth_package.to_hashed_nonce(),
+                tip_amount,
+                self.gas_budget,
+            )
+            .await
     }
 
     /// Sends the blob to the upload relay and waits for the certificate.
@@ -232,7 +203,7 @@ impl UploadRelayClient {
     #[tracing::instrument(level = tracing::Level::DEBUG, skip_all)]
     pub async fn send_blob_data_and_get_certificate_with_relay(
         &self,
-        sui_client: &SuiContractClient,
+        sui_client: &impl WriteClient,
         blob: &[u8],
         blob_id: BlobId,
         encoding_type: EncodingType,
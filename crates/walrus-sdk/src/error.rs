@@ -159,7 +159,7 @@ impl From<SuiClientError> for ClientError {
 }
 
 /// Inner error type, raised when the client operation fails.
-#[derive(Debug, thiserror::Error)]
+#[derive(Debug, thiserror::Error, strum::IntoStaticStr)]
 #[error(transparent)]
 pub enum ClientErrorKind {
     /// The certification of the blob failed.
@@ -245,3 +245,10 @@ pub enum ClientErrorKind {
     #[error("upload relay error: {0}")]
     UploadRelayError(#[from] UploadRelayClientError),
 }
+
+impl ClientErrorKind {
+    /// Returns the name of the error kind, e.g., for use as a label in metrics and reports.
+    pub fn name(&self) -> &'static str {
+        self.into()
+    }
+}
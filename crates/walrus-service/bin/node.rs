@@ -86,6 +86,8 @@ struct AdminArgs {
     admin_socket_path: Option<PathBuf>,
     /// Tracing handle.
     tracing_handle: WalrusTracingHandle,
+    /// The REST API server, used to reload its TLS certificate.
+    rest_api: Arc<RestApiServer<StorageNode>>,
 }
 
 #[derive(Subcommand, Debug, Clone)]
@@ -206,6 +208,11 @@ enum AdminCommands {
         #[arg(long)]
         level: String,
     },
+    /// Reload the TLS certificate and private key of the REST API from disk.
+    ///
+    /// The new certificate is used for all subsequent connections. The certificate is reloaded
+    /// even if the files have not changed.
+    ReloadTlsCertificate,
 }
 
 /// Standard response format for admin commands.
@@ -1431,12 +1438,13 @@ impl StorageNodeRuntime {
 
         let checkpoint_manager = walrus_node.checkpoint_manager();
         let admin_cancel_token = cancel_token.child_token();
-        let rest_api = RestApiServer::new(
+        let rest_api = Arc::new(RestApiServer::new(
             walrus_node,
             cancel_token.child_token(),
             RestApiConfig::from(node_config),
             &metrics_runtime.registry,
-        );
+        ));
+        let admin_rest_api = rest_api.clone();
         let rest_api_handle = tokio::spawn(async move {
             let result = rest_api
                 .run()
@@ -1457,6 +1465,7 @@ impl StorageNodeRuntime {
                 checkpoint_manager,
                 admin_socket_path: node_config.admin_socket_path.clone(),
                 tracing_handle,
+                rest_api: admin_rest_api,
             },
             admin_cancel_token,
         )?;
@@ -1562,6 +1571,20 @@ async fn handle_log_level_command(level: String, args: &AdminArgs) -> AdminComma
     }
 }
 
+/// Handle TLS certificate reload commands from admin socket.
+async fn handle_reload_tls_certificate_command(args: &AdminArgs) -> AdminCommandResponse {
+    match args.rest_api.reload_tls_certificate(true).await {
+        Ok(_) => AdminCommandResponse {
+            success: true,
+            message: "TLS certificate reloaded successfully".to_string(),
+        },
+        Err(e) => AdminCommandResponse {
+            success: false,
+            message: format!("Failed to reload TLS certificate: {e:?}"),
+        },
+    }
+}
+
 /// Handle checkpoint commands from admin socket.
 async fn handle_checkpoint_command(
     command: CheckpointCommands,
@@ -1625,6 +1648,9 @@ async fn handle_connection(stream: UnixStream, args: AdminArgs) {
                 handle_checkpoint_command(command, &args).await
             }
             Ok(AdminCommands::LogLevel { level }) => handle_log_level_command(level, &args).await,
+            Ok(AdminCommands::ReloadTlsCertificate) => {
+                handle_reload_tls_certificate_command(&args).await
+            }
             Err(e) => AdminCommandResponse {
                 success: false,
                 message: format!("Failed to parse command: {e}"),
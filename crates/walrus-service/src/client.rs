@@ -13,7 +13,19 @@ pub use walrus_sdk::{
 };
 
 mod daemon;
-pub use daemon::{ClientDaemon, PublisherQuery, WalrusWriteClient, auth::Claim};
+pub use daemon::{
+    ClientDaemon,
+    PublisherQuery,
+    PublisherServices,
+    WalrusWriteClient,
+    WriteParams,
+    auth::Claim,
+};
+
+#[cfg(feature = "local-cluster")]
+mod local_cluster;
+#[cfg(feature = "local-cluster")]
+pub use local_cluster::{LocalCluster, LocalClusterConfig};
 
 mod refill;
 pub use refill::{RefillHandles, Refiller};
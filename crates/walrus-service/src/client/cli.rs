@@ -25,8 +25,12 @@ use walrus_sui::wallet::Wallet;
 mod args;
 mod backfill;
 mod cli_output;
+mod health_watch;
+mod quilt_archive;
 mod runner;
 
+#[cfg(feature = "local-cluster")]
+pub use args::LocalClusterArgs;
 pub use args::{
     AggregatorArgs,
     App,
@@ -35,17 +39,21 @@ pub use args::{
     Commands,
     DaemonCommands,
     HealthSortBy,
+    HealthWatchArgs,
     NodeSelection,
     NodeSortBy,
     PublisherArgs,
     QuiltBlobInput,
+    QuiltCommands,
     QuiltPatchByIdentifier,
     QuiltPatchByPatchId,
+    QuiltPatchByQuery,
     QuiltPatchByTag,
     QuiltPatchSelector,
     SortBy,
 };
 pub use cli_output::CliOutput;
+pub use quilt_archive::QuiltArchiveFormat;
 pub use runner::ClientCommandRunner;
 
 /// Creates a [`WalrusNodeClient`] based on the provided [`ClientConfig`] with read-only access to
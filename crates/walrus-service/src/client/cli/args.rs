@@ -9,11 +9,13 @@ use std::{
     num::{NonZeroU16, NonZeroU32},
     path::PathBuf,
     str::FromStr,
+    sync::Arc,
     time::{Duration, SystemTime},
 };
 
 use anyhow::{Context as _, Result, anyhow};
 use clap::{Args, Parser, Subcommand};
+use futures::{StreamExt as _, TryStreamExt as _};
 use jsonwebtoken::Algorithm;
 use reqwest::Url;
 use serde::{Deserialize, Serialize};
@@ -25,18 +27,36 @@ use walrus_core::{
     Epoch,
     EpochCount,
     QuiltPatchId,
-    encoding::{EncodingConfig, EncodingFactory},
+    encoding::{EncodingConfig, EncodingFactory, quilt_encoding::QuiltPatchFilter},
     ensure,
 };
 use walrus_sui::{
-    client::{ExpirySelectionPolicy, ReadClient, SuiContractClient},
+    client::{CreditsPolicy, ExpirySelectionPolicy, ReadClient, SuiContractClient},
     types::{StorageNode, move_structs::Authorized},
     utils::SuiNetwork,
 };
 use walrus_utils::read_blob_from_file;
 
-use super::{BlobIdDecimal, HumanReadableBytes, parse_blob_id, parse_quilt_patch_id};
-use crate::client::{config::AuthConfig, daemon::CacheConfig};
+use super::{
+    BlobIdDecimal,
+    HumanReadableBytes,
+    parse_blob_id,
+    parse_quilt_patch_id,
+    quilt_archive::QuiltArchiveFormat,
+};
+#[cfg(feature = "local-cluster")]
+use crate::client::LocalClusterConfig;
+use crate::client::{
+    config::AuthConfig,
+    daemon::{
+        CacheConfig,
+        PublisherServices,
+        jobs::{JobManager, JobsConfig},
+        sites::{Sites, SitesConfig},
+        tenants::{Tenants, TenantsConfig},
+        uploads::{UploadManager, UploadsConfig},
+    },
+};
 
 /// The command-line arguments for the Walrus client.
 #[derive(Parser, Debug, Clone, Deserialize)]
@@ -245,6 +265,59 @@ pub enum CliCommands {
         #[arg(long, num_args = 0.., conflicts_with = "paths")]
         #[serde(default)]
         blobs: Vec<QuiltBlobInput>,
+        /// A tar or zip archive containing the files to include in the quilt.
+        ///
+        /// The paths of the files in the archive are used as the identifiers of the quilt patches.
+        /// If the archive contains a `_walrus_quilt.json` manifest, as created by
+        /// `walrus quilt export`, the tags listed in the manifest are applied to the patches.
+        /// Gzip-compressed tar archives are also supported.
+        #[arg(long, conflicts_with_all = ["paths", "blobs"])]
+        #[serde(
+            default,
+            deserialize_with = "walrus_utils::config::resolve_home_dir_option"
+        )]
+        from_archive: Option<PathBuf>,
+        /// Common options shared between store and store-quilt commands.
+        #[command(flatten)]
+        #[serde(flatten)]
+        common_options: CommonStoreOptions,
+    },
+    /// Store a new version of an existing quilt, with patches added, replaced, or removed.
+    ///
+    /// The unchanged patches are copied from the existing quilt, so only the changed files need
+    /// to be provided. The new quilt has the same quilt version as the existing one. The output
+    /// maps the quilt patch IDs of the existing quilt to the quilt patch IDs of the new quilt.
+    UpdateQuilt {
+        /// The ID of the quilt to update.
+        #[serde_as(as = "DisplayFromStr")]
+        #[arg(allow_hyphen_values = true, value_parser = parse_blob_id)]
+        quilt_id: BlobId,
+        /// Files to add to the quilt.
+        ///
+        /// The filenames are used as the identifiers of the new quilt patches, and must not exist
+        /// in the quilt.
+        #[arg(long, num_args = 1..)]
+        #[serde(
+            default,
+            deserialize_with = "walrus_utils::config::resolve_home_dir_vec"
+        )]
+        add: Vec<PathBuf>,
+        /// Files replacing the quilt patches with the same identifiers as their filenames.
+        #[arg(long, num_args = 1..)]
+        #[serde(
+            default,
+            deserialize_with = "walrus_utils::config::resolve_home_dir_vec"
+        )]
+        replace: Vec<PathBuf>,
+        /// Identifiers of the quilt patches to remove.
+        #[arg(long, num_args = 1..)]
+        #[serde(default)]
+        delete: Vec<String>,
+        /// Record the ID of the existing quilt in the `_walrusPreviousQuiltId` attribute of the
+        /// new quilt's blob object.
+        #[arg(long)]
+        #[serde(default)]
+        record_lineage: bool,
         /// Common options shared between store and store-quilt commands.
         #[command(flatten)]
         #[serde(flatten)]
@@ -309,6 +382,12 @@ pub enum CliCommands {
         #[serde(flatten)]
         rpc_arg: RpcArg,
     },
+    /// Commands to work with quilts as a whole.
+    Quilt {
+        /// The specific quilt command to run.
+        #[command(subcommand)]
+        command: QuiltCommands,
+    },
     /// Get the status of a blob.
     ///
     /// This queries multiple storage nodes representing more than a third of the shards for the
@@ -337,6 +416,37 @@ pub enum CliCommands {
         #[serde(flatten)]
         rpc_arg: RpcArg,
     },
+    /// Audit the storage of one or more blobs across the storage nodes of the committee.
+    ///
+    /// For each blob, the sliver of every shard is requested and verified against the blob's
+    /// metadata. The report lists the nodes that are missing slivers or serving invalid data, and
+    /// compares the number of shards serving valid slivers against the BFT thresholds of the
+    /// system. Use `--json` to obtain a report suitable for archiving.
+    Audit {
+        /// The IDs of the blobs to audit.
+        #[serde_as(as = "Vec<DisplayFromStr>")]
+        #[arg(
+            required = true,
+            num_args = 1..,
+            allow_hyphen_values = true,
+            value_parser = parse_blob_id
+        )]
+        blob_ids: Vec<BlobId>,
+        /// Audit the secondary slivers instead of the primary slivers.
+        ///
+        /// Secondary slivers are larger, but fewer of them are required to reconstruct a blob.
+        #[arg(long)]
+        #[serde(default)]
+        secondary: bool,
+        /// Timeout for each sliver request to a storage node.
+        #[arg(long, value_parser = humantime::parse_duration, default_value = "30s")]
+        #[serde(default = "default::audit_timeout")]
+        timeout: Duration,
+        /// The URL of the Sui RPC node to use.
+        #[command(flatten)]
+        #[serde(flatten)]
+        rpc_arg: RpcArg,
+    },
     /// Print information about the Walrus storage system this client is connected to.
     /// Several subcommands are available to print different information.
     ///
@@ -381,6 +491,10 @@ pub enum CliCommands {
         #[arg(long, default_value_t = default::concurrent_requests_for_health())]
         #[serde(default = "default::concurrent_requests_for_health")]
         concurrent_requests: usize,
+        /// Options of the watch mode.
+        #[command(flatten)]
+        #[serde(flatten)]
+        watch: HealthWatchArgs,
     },
     /// Encode the specified file to obtain its blob ID.
     BlobId {
@@ -519,6 +633,28 @@ pub enum CliCommands {
         #[serde(default)]
         yes: bool,
     },
+    /// Transfers one or more owned Blob objects to another address.
+    ///
+    /// The Blob objects to transfer can be selected by object ID, by blob ID, by attribute, or by
+    /// expiry. Using the flag `--all` will transfer all the Blob objects owned by the wallet. The
+    /// selected Blob objects are transferred in as few transactions as possible.
+    ///
+    /// After the transfer, the recipient has full control over the Blob objects; i.e., the
+    /// recipient can extend, share, or burn them, and delete them if they are deletable. The
+    /// wallet currently in use loses control over the transferred Blob objects.
+    TransferBlobs {
+        /// The address to which the Blob objects are transferred.
+        #[arg(long)]
+        recipient: SuiAddress,
+        /// The selection of the Blob objects to transfer.
+        #[command(flatten)]
+        #[serde(flatten)]
+        transfer_selection: TransferSelection,
+        /// Proceed to transfer the blobs without confirmation.
+        #[arg(long)]
+        #[serde(default)]
+        yes: bool,
+    },
     /// Fund a shared blob.
     FundSharedBlob {
         /// The object ID of the shared blob to fund.
@@ -542,6 +678,12 @@ pub enum CliCommands {
         // `--earliest-expiration-time`.
         #[arg(long)]
         epochs_extended: EpochCount,
+        /// Whether to pay for the extension through the Walrus credits.
+        ///
+        /// Shared blobs are always extended without credits.
+        #[command(flatten)]
+        #[serde(flatten)]
+        credits: CreditsArgs,
     },
     /// Share a blob.
     Share {
@@ -670,6 +812,38 @@ pub enum InfoCommands {
     },
 }
 
+/// Subcommands for the `quilt` command.
+#[serde_as]
+#[derive(Subcommand, Debug, Clone, Deserialize, PartialEq, Eq)]
+#[command(rename_all = "kebab-case")]
+#[serde(rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum QuiltCommands {
+    /// Export all patches of a quilt to a tar or zip archive.
+    ///
+    /// The identifiers of the patches are used as the paths of the files in the archive, and the
+    /// identifiers and tags of the patches are written to a `_walrus_quilt.json` manifest at the
+    /// root of the archive. The archive can be stored again with
+    /// `walrus store-quilt --from-archive`.
+    Export {
+        /// The ID of the quilt to export.
+        #[serde_as(as = "DisplayFromStr")]
+        #[arg(allow_hyphen_values = true, value_parser = parse_blob_id)]
+        quilt_id: BlobId,
+        /// The format of the archive.
+        #[arg(long, value_enum, default_value_t = QuiltArchiveFormat::Tar)]
+        #[serde(default)]
+        format: QuiltArchiveFormat,
+        /// The path of the archive to write.
+        #[arg(long)]
+        #[serde(deserialize_with = "walrus_utils::config::resolve_home_dir")]
+        out: PathBuf,
+        /// The URL of the Sui RPC node to use.
+        #[command(flatten)]
+        #[serde(flatten)]
+        rpc_arg: RpcArg,
+    },
+}
+
 /// Subcommands for the `node-admin` command.
 #[derive(Subcommand, Debug, Clone, Deserialize, PartialEq, Eq)]
 #[command(rename_all = "kebab-case")]
@@ -791,6 +965,19 @@ pub enum DaemonCommands {
         /// The aggregator args.
         aggregator_args: AggregatorArgs,
     },
+    /// Run a Walrus network on this machine, consisting of storage nodes and a client daemon.
+    ///
+    /// The storage nodes run in the current process against an in-memory simulation of the Walrus
+    /// contracts, so neither Sui tooling nor network access is required. The client daemon serves
+    /// the aggregator and publisher APIs and pays for storage from a pre-funded wallet. All state
+    /// is discarded when the command exits.
+    #[cfg(feature = "local-cluster")]
+    LocalCluster {
+        #[command(flatten)]
+        #[serde(flatten)]
+        /// The local cluster args.
+        args: LocalClusterArgs,
+    },
 }
 
 impl DaemonCommands {
@@ -800,6 +987,8 @@ impl DaemonCommands {
             DaemonCommands::Publisher { args } => args.daemon_args.metrics_address,
             DaemonCommands::Aggregator { daemon_args, .. } => daemon_args.metrics_address,
             DaemonCommands::Daemon { args, .. } => args.daemon_args.metrics_address,
+            #[cfg(feature = "local-cluster")]
+            DaemonCommands::LocalCluster { args } => args.daemon_args.metrics_address,
         }
     }
 }
@@ -823,6 +1012,116 @@ pub struct AggregatorArgs {
     #[arg(long, default_value_t = false)]
     #[serde(default)]
     pub allow_quilt_patch_tags_in_response: bool,
+    /// The path to a YAML file configuring static websites served from quilts.
+    ///
+    /// Each site is mounted on a set of hostnames and/or under a path prefix, and is served from a
+    /// quilt, either given directly or referenced through the attribute of a blob object.
+    #[arg(long)]
+    #[serde(
+        default,
+        deserialize_with = "walrus_utils::config::resolve_home_dir_option"
+    )]
+    pub sites_config: Option<PathBuf>,
+}
+
+impl AggregatorArgs {
+    pub(crate) fn open_sites(&self) -> Result<Option<Arc<Sites>>> {
+        let Some(path) = self.sites_config.as_ref() else {
+            return Ok(None);
+        };
+        let sites = Sites::new(SitesConfig::load(path)?)?;
+        tracing::info!(n_sites = sites.len(), "sites config applied");
+        Ok(Some(Arc::new(sites)))
+    }
+}
+
+/// The arguments for the local cluster.
+#[cfg(feature = "local-cluster")]
+#[derive(Debug, Clone, Args, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalClusterArgs {
+    /// The number of storage nodes to run.
+    #[arg(long, default_value_t = default::local_cluster_n_nodes())]
+    #[serde(default = "default::local_cluster_n_nodes")]
+    pub n_nodes: usize,
+    /// The number of shards in the system; must be at least the number of storage nodes.
+    #[arg(long, default_value_t = default::local_cluster_n_shards())]
+    #[serde(default = "default::local_cluster_n_shards")]
+    pub n_shards: NonZeroU16,
+    /// The duration of an epoch.
+    #[arg(long, value_parser = humantime::parse_duration, default_value = "1h")]
+    #[serde(default = "default::local_cluster_epoch_duration")]
+    pub epoch_duration: Duration,
+    /// The amount of WAL (in FROST) with which the wallet of the publisher is funded.
+    #[arg(long, default_value_t = default::local_cluster_publisher_wal())]
+    #[serde(default = "default::local_cluster_publisher_wal")]
+    pub publisher_wal: u64,
+    /// The seed from which the object IDs and addresses of the cluster are derived.
+    #[arg(long, default_value_t = 0)]
+    #[serde(default)]
+    pub seed: u64,
+    /// If set, the information on the running cluster is additionally written as JSON to this
+    /// file.
+    #[arg(long)]
+    #[serde(
+        default,
+        deserialize_with = "walrus_utils::config::resolve_home_dir_option"
+    )]
+    pub info_file: Option<PathBuf>,
+    #[command(flatten)]
+    #[serde(flatten)]
+    /// The daemon args.
+    pub daemon_args: DaemonArgs,
+    #[command(flatten)]
+    #[serde(flatten, default)]
+    /// The aggregator args.
+    pub aggregator_args: AggregatorArgs,
+}
+
+#[cfg(feature = "local-cluster")]
+impl LocalClusterArgs {
+    /// Returns the configuration of the cluster.
+    pub(crate) fn cluster_config(&self) -> LocalClusterConfig {
+        LocalClusterConfig {
+            n_nodes: self.n_nodes,
+            n_shards: self.n_shards,
+            epoch_duration: self.epoch_duration,
+            client_wal_balance: self.publisher_wal,
+            client_sui_balance: default::local_cluster_publisher_sui(),
+            seed: self.seed,
+        }
+    }
+
+    /// Returns the arguments of the publisher of the cluster.
+    ///
+    /// The publisher uses a single wallet, so the sub-wallet settings are unused.
+    pub(crate) fn publisher_args(&self) -> PublisherArgs {
+        PublisherArgs {
+            daemon_args: self.daemon_args.clone(),
+            max_body_size_kib: default::max_body_size_kib(),
+            max_quilt_body_size_kib: default::max_quilt_body_size_kib(),
+            max_request_buffer_size: default::max_request_buffer_size(),
+            max_concurrent_requests: default::max_concurrent_requests(),
+            n_clients: 1,
+            refill_interval: default::refill_interval(),
+            sub_wallets_dir: PathBuf::new(),
+            gas_refill_amount: default::gas_refill_amount(),
+            wal_refill_amount: default::wal_refill_amount(),
+            sub_wallets_min_balance: default::sub_wallets_min_balance(),
+            keep: false,
+            burn_after_store: false,
+            send_objects_to: None,
+            credits: CreditsArgs::default(),
+            jwt_decode_secret: None,
+            jwt_algorithm: None,
+            jwt_expiring_sec: 0,
+            jwt_verify_upload: false,
+            replay_suppression_config: Default::default(),
+            tenants_config: None,
+            jobs_config: Default::default(),
+            uploads_config: Default::default(),
+        }
+    }
 }
 
 /// The arguments for the publisher service.
@@ -902,6 +1201,21 @@ pub struct PublisherArgs {
     #[arg(long)]
     #[serde(default)]
     pub burn_after_store: bool,
+    /// If set, the publisher will transfer the created Blob objects to this address by default.
+    ///
+    /// This allows handing off all Blob objects created by the publisher in bulk to the party for
+    /// which the data is stored, instead of keeping them in the publisher's main wallet. The
+    /// `send_object_to` query parameter of a PUT request takes precedence over this option.
+    ///
+    /// Blob objects already in the main wallet can be handed off with the `transfer-blobs`
+    /// command.
+    #[arg(long, conflicts_with = "burn_after_store")]
+    #[serde(default)]
+    pub send_objects_to: Option<SuiAddress>,
+    /// Whether the publisher pays for storage through the Walrus credits.
+    #[command(flatten)]
+    #[serde(flatten)]
+    pub credits: CreditsArgs,
     /// If set, the publisher will verify the JWT token.
     ///
     /// If not specified, the verification is disabled.
@@ -942,6 +1256,26 @@ pub struct PublisherArgs {
     #[serde(flatten)]
     /// The configuration for the JWT duplicate suppression cache.
     pub replay_suppression_config: CacheConfig,
+    /// The path to a YAML file configuring the tenants of the publisher.
+    ///
+    /// If set, every store request must be attributed to a tenant, either through an API key in
+    /// the `X-Walrus-Api-Key` header or through the subject (`sub`) of the JWT (if JWT
+    /// authentication is enabled). The publisher then enforces the quotas of the tenant and
+    /// records its usage in a local database.
+    #[arg(long)]
+    #[serde(
+        default,
+        deserialize_with = "walrus_utils::config::resolve_home_dir_option"
+    )]
+    pub tenants_config: Option<PathBuf>,
+    /// The configuration for the asynchronous upload jobs.
+    #[command(flatten)]
+    #[serde(flatten)]
+    pub jobs_config: JobsConfig,
+    /// The configuration for the resumable uploads.
+    #[command(flatten)]
+    #[serde(flatten)]
+    pub uploads_config: UploadsConfig,
 }
 
 impl PublisherArgs {
@@ -993,6 +1327,38 @@ impl PublisherArgs {
             Ok(None)
         }
     }
+
+    pub(crate) fn open_tenants(&self) -> Result<Option<Arc<Tenants>>> {
+        let Some(path) = self.tenants_config.as_ref() else {
+            return Ok(None);
+        };
+        let config = TenantsConfig::load(path)?;
+        tracing::info!(
+            n_tenants = config.tenants.len(),
+            db_path = %config.db_path.display(),
+            "tenants config applied"
+        );
+        Ok(Some(Arc::new(Tenants::open(config)?)))
+    }
+
+    pub(crate) fn open_jobs(&self, tenants: Option<Arc<Tenants>>) -> Result<Option<JobManager>> {
+        JobManager::open(&self.jobs_config, tenants)
+    }
+
+    pub(crate) fn open_uploads(&self) -> Result<Option<UploadManager>> {
+        UploadManager::open(&self.uploads_config)
+    }
+
+    /// Sets up the optional services of the publisher that are configured in the arguments.
+    pub(crate) fn open_services(&self) -> Result<PublisherServices> {
+        let tenants = self.open_tenants()?;
+        Ok(PublisherServices {
+            auth_config: self.generate_auth_config()?,
+            jobs: self.open_jobs(tenants.clone())?,
+            uploads: self.open_uploads()?,
+            tenants,
+        })
+    }
 }
 
 /// The URL of the Sui RPC node to use.
@@ -1096,6 +1462,34 @@ pub struct CommonStoreOptions {
     #[arg(long)]
     #[serde(default)]
     pub skip_tip_confirmation: bool,
+    /// Whether to pay for the storage through the Walrus credits.
+    #[command(flatten)]
+    #[serde(flatten)]
+    pub credits: CreditsArgs,
+}
+
+/// Arguments to select whether storage and write fees are paid through the Walrus credits.
+#[derive(Default, Debug, Clone, Args, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct CreditsArgs {
+    /// Always pay through the Walrus credits.
+    ///
+    /// By default, the credits are used if a credits object is configured for the client. If this
+    /// flag is set, the operation fails if no credits object is configured.
+    #[arg(long, conflicts_with = "no_credits")]
+    #[serde(default)]
+    pub use_credits: bool,
+    /// Never pay through the Walrus credits, even if a credits object is configured.
+    #[arg(long)]
+    #[serde(default)]
+    pub no_credits: bool,
+}
+
+impl CreditsArgs {
+    /// Returns the [`CreditsPolicy`] corresponding to the flags.
+    pub fn credits_policy(&self) -> CreditsPolicy {
+        CreditsPolicy::from_use_and_no_credits_flags(self.use_credits, self.no_credits)
+    }
 }
 
 #[serde_as]
@@ -1216,6 +1610,25 @@ pub struct QuiltPatchQuery {
     #[serde(default)]
     tag: Vec<String>,
 
+    /// A query selecting the patches by their identifiers and tags.
+    ///
+    /// Predicates compare the `identifier` or a tag `tag:<key>` with a value using `=`, `!=`,
+    /// `^=` (prefix), `~` (glob), or `<`, `<=`, `>`, `>=` (numeric if both sides are numbers,
+    /// lexicographic otherwise); `has tag:<key>` checks if a tag exists. Predicates can be
+    /// combined with `and`, `or`, `not`, and parentheses.
+    ///
+    /// Example: `tag:date >= 2024-01-01 and tag:date < 2024-02-01 and identifier ~ "*.csv"`
+    ///
+    /// It is required to be used with `--quilt-id`.
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[arg(
+        long,
+        conflicts_with_all = ["identifiers", "tag", "quilt_patch_ids"],
+        help_heading = "Arguments"
+    )]
+    #[serde(default)]
+    query: Option<QuiltPatchFilter>,
+
     /// The quilt patch IDs.
     ///
     /// It should be used alone without other arguments.
@@ -1240,10 +1653,11 @@ impl QuiltPatchQuery {
             !self.identifiers.is_empty(),
             !self.tag.is_empty(),
             !self.quilt_patch_ids.is_empty(),
+            self.query,
             self.quilt_id,
         ) {
             // quilt_id and identifiers provided.
-            (true, false, false, Some(quilt_id)) => {
+            (true, false, false, None, Some(quilt_id)) => {
                 Ok(QuiltPatchSelector::ByIdentifier(QuiltPatchByIdentifier {
                     quilt_id,
                     identifiers: self.identifiers,
@@ -1251,7 +1665,7 @@ impl QuiltPatchQuery {
             }
 
             // quilt_id and tags provided.
-            (false, true, false, Some(quilt_id)) => {
+            (false, true, false, None, Some(quilt_id)) => {
                 if self.tag.len() != 2 {
                     return Err(anyhow!("Only one tag is supported for now."));
                 }
@@ -1264,13 +1678,23 @@ impl QuiltPatchQuery {
                 }))
             }
 
+            // quilt_id and query provided.
+            (false, false, false, Some(query), Some(quilt_id)) => {
+                Ok(QuiltPatchSelector::ByQuery(QuiltPatchByQuery {
+                    quilt_id,
+                    query,
+                }))
+            }
+
             // quilt_patch_ids provided.
-            (false, false, true, None) => Ok(QuiltPatchSelector::ByPatchId(QuiltPatchByPatchId {
-                quilt_patch_ids: self.quilt_patch_ids,
-            })),
+            (false, false, true, None, None) => {
+                Ok(QuiltPatchSelector::ByPatchId(QuiltPatchByPatchId {
+                    quilt_patch_ids: self.quilt_patch_ids,
+                }))
+            }
 
             // Only quilt_id provided.
-            (false, false, false, Some(quilt_id)) => Ok(QuiltPatchSelector::All(quilt_id)),
+            (false, false, false, None, Some(quilt_id)) => Ok(QuiltPatchSelector::All(quilt_id)),
 
             // All other combinations are invalid
             _ => Err(Self::invalid_query_error()),
@@ -1284,6 +1708,7 @@ impl QuiltPatchQuery {
             - quiltId + identifiers: {{\"quiltId\": \"<ID>\", \
             \"identifiers\": [\"<IDENTIFIER>\", ...]}}\n\
             - quiltId + tag: {{\"quiltId\": \"<ID>\", \"tag\": [\"<KEY>\", \"<VALUE>\"]}}\n\
+            - quiltId + query: {{\"quiltId\": \"<ID>\", \"query\": \"<QUERY>\"}}\n\
             - quiltPatchIds: {{\"quiltPatchIds\": [\"<PATCH_ID>\", ...]}}\n\
             - quiltId only: {{\"quiltId\": \"<ID>\"}}"
         )
@@ -1346,6 +1771,18 @@ pub struct QuiltPatchByTag {
     pub value: String,
 }
 
+/// Query for quilt patches matching a filter on their identifiers and tags.
+#[serde_as]
+#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct QuiltPatchByQuery {
+    /// The quilt ID, which is the BlobID of the quilt.
+    pub quilt_id: BlobId,
+    /// The filter selecting the patches.
+    #[serde_as(as = "DisplayFromStr")]
+    pub query: QuiltPatchFilter,
+}
+
 /// Query for quilt patches by patch ID.
 #[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
@@ -1361,6 +1798,8 @@ pub enum QuiltPatchSelector {
     ByIdentifier(QuiltPatchByIdentifier),
     /// Patches by quilt_id and tag.
     ByTag(QuiltPatchByTag),
+    /// Patches by quilt_id and a filter on their identifiers and tags.
+    ByQuery(QuiltPatchByQuery),
     /// Patches by quilt_patch_id.
     ByPatchId(QuiltPatchByPatchId),
     /// Read 'em all.
@@ -1498,6 +1937,122 @@ impl BurnSelection {
     }
 }
 
+/// Selector for the blob objects to transfer.
+#[serde_as]
+#[derive(Debug, Clone, Args, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[group(required = true, multiple = false)]
+pub struct TransferSelection {
+    /// The object IDs of the Blob objects to transfer.
+    #[arg(long, num_args = 1..)]
+    #[serde_as(as = "Vec<DisplayFromStr>")]
+    #[serde(default)]
+    object_ids: Vec<ObjectID>,
+    /// Transfer all the (non-expired) blob objects with the given blob IDs.
+    #[arg(long, num_args = 1.., allow_hyphen_values = true, value_parser = parse_blob_id)]
+    #[serde_as(as = "Vec<DisplayFromStr>")]
+    #[serde(default)]
+    blob_ids: Vec<BlobId>,
+    /// Transfer all the (non-expired) blob objects whose attribute contains the given key-value
+    /// pair.
+    ///
+    /// Example:
+    ///   --attribute "customer" "acme"
+    #[arg(long, value_names = &["KEY", "VALUE"], num_args = 2)]
+    #[serde(default)]
+    attribute: Vec<String>,
+    /// Transfer all the (non-expired) blob objects that expire before the given epoch.
+    #[arg(long)]
+    #[serde(default)]
+    expiring_before: Option<Epoch>,
+    /// Transfer all the blob objects owned by the wallet.
+    #[arg(long)]
+    #[serde(default)]
+    all: bool,
+    /// Transfer all the expired blob objects owned by the wallet.
+    #[arg(long)]
+    #[serde(default)]
+    all_expired: bool,
+}
+
+impl TransferSelection {
+    /// The number of concurrent requests used to fetch the attributes of the owned blobs.
+    const ATTRIBUTE_REQUEST_CONCURRENCY: usize = 10;
+
+    pub(crate) async fn get_object_ids(
+        &self,
+        client: &SuiContractClient,
+    ) -> anyhow::Result<Vec<ObjectID>> {
+        match (
+            !self.object_ids.is_empty(),
+            !self.blob_ids.is_empty(),
+            !self.attribute.is_empty(),
+            self.expiring_before,
+            self.all,
+            self.all_expired,
+        ) {
+            (true, false, false, None, false, false) => Ok(self.object_ids.clone()),
+            (false, true, false, None, false, false) => Ok(client
+                .owned_blobs(None, ExpirySelectionPolicy::Valid)
+                .await?
+                .into_iter()
+                .filter(|blob| self.blob_ids.contains(&blob.blob_id))
+                .map(|blob| blob.id)
+                .collect()),
+            (false, false, true, None, false, false) => {
+                let [key, value] = self.attribute.as_slice() else {
+                    return Err(anyhow!(
+                        "exactly one key-value pair must be specified for `attribute`"
+                    ));
+                };
+                let blobs = client
+                    .owned_blobs(None, ExpirySelectionPolicy::Valid)
+                    .await?;
+                let read_client = client.read_client();
+                let matching = futures::stream::iter(blobs)
+                    .map(|blob| async move {
+                        read_client
+                            .get_blob_attribute(&blob.id)
+                            .await
+                            .map(|attribute| (blob.id, attribute))
+                    })
+                    .buffer_unordered(Self::ATTRIBUTE_REQUEST_CONCURRENCY)
+                    .try_filter_map(|(object_id, attribute)| async move {
+                        Ok(attribute
+                            .is_some_and(|attribute| attribute.get(key) == Some(value.as_str()))
+                            .then_some(object_id))
+                    })
+                    .try_collect::<Vec<_>>()
+                    .await?;
+                Ok(matching)
+            }
+            (false, false, false, Some(expiring_before), false, false) => Ok(client
+                .owned_blobs(None, ExpirySelectionPolicy::Valid)
+                .await?
+                .into_iter()
+                .filter(|blob| blob.storage.end_epoch < expiring_before)
+                .map(|blob| blob.id)
+                .collect()),
+            (false, false, false, None, true, false) => Ok(client
+                .owned_blobs(None, ExpirySelectionPolicy::All)
+                .await?
+                .into_iter()
+                .map(|blob| blob.id)
+                .collect()),
+            (false, false, false, None, false, true) => Ok(client
+                .owned_blobs(None, ExpirySelectionPolicy::Expired)
+                .await?
+                .into_iter()
+                .map(|blob| blob.id)
+                .collect()),
+            _ => Err(anyhow!(
+                "exactly one of `objectIds`, `blobIds`, `attribute`, `expiringBefore`, `all`, or \
+                `allExpired` must be specified"
+            )),
+        }
+    }
+}
+
 /// Selector for the storage nodes.
 #[serde_as]
 #[derive(Debug, Clone, Args, Deserialize, PartialEq, Eq)]
@@ -1715,6 +2270,10 @@ pub(crate) mod default {
         Duration::from_secs(10)
     }
 
+    pub(crate) fn audit_timeout() -> Duration {
+        Duration::from_secs(30)
+    }
+
     pub(crate) fn bind_address() -> SocketAddr {
         "127.0.0.1:31415"
             .parse()
@@ -1743,6 +2302,33 @@ pub(crate) mod default {
         Duration::from_secs(60)
     }
 
+    #[cfg(feature = "local-cluster")]
+    pub(crate) fn local_cluster_n_nodes() -> usize {
+        4
+    }
+
+    #[cfg(feature = "local-cluster")]
+    pub(crate) fn local_cluster_n_shards() -> std::num::NonZeroU16 {
+        std::num::NonZeroU16::new(10).expect("10 is non-zero")
+    }
+
+    #[cfg(feature = "local-cluster")]
+    pub(crate) fn local_cluster_epoch_duration() -> Duration {
+        Duration::from_secs(60 * 60)
+    }
+
+    #[cfg(feature = "local-cluster")]
+    pub(crate) fn local_cluster_publisher_wal() -> u64 {
+        // 1M WAL.
+        1_000_000 * 1_000_000_000
+    }
+
+    #[cfg(feature = "local-cluster")]
+    pub(crate) fn local_cluster_publisher_sui() -> u64 {
+        // 1M SUI.
+        1_000_000 * 1_000_000_000
+    }
+
     pub(crate) fn allowed_headers() -> Vec<String> {
         vec![
             "content-type".to_string(),
@@ -1758,6 +2344,22 @@ pub(crate) mod default {
     pub(crate) fn concurrent_requests_for_health() -> usize {
         60
     }
+
+    pub(crate) fn health_trend_window() -> Duration {
+        Duration::from_secs(24 * 60 * 60)
+    }
+
+    pub(crate) fn health_history_retention() -> Duration {
+        Duration::from_secs(7 * 24 * 60 * 60)
+    }
+
+    pub(crate) fn health_max_event_lag() -> u64 {
+        1000
+    }
+
+    pub(crate) fn health_max_latency() -> Duration {
+        Duration::from_secs(2)
+    }
 }
 
 #[cfg(test)]
@@ -1774,6 +2376,10 @@ mod tests {
     const READ_STR: &str = r#"{"read": {"blobId": "4BKcDC0Ih5RJ8R0tFMz3MZVNZV8b2goT6_JiEEwNHQo"}}"#;
     const DAEMON_STR: &str =
         r#"{"daemon": {"bindAddress": "127.0.0.1:12345", "subWalletsDir": "/some/path"}}"#;
+    const TRANSFER_BLOBS_STR: &str = r#"{"transferBlobs": {
+        "recipient": "0x0000000000000000000000000000000000000000000000000000000000000001",
+        "attribute": ["customer", "acme"]
+    }}"#;
 
     // Creates the fixture for the JSON command string.
     fn make_cmd_str(command: &str) -> String {
@@ -1804,6 +2410,7 @@ mod tests {
                 encoding_type: Default::default(),
                 upload_relay: None,
                 skip_tip_confirmation: false,
+                credits: CreditsArgs::default(),
             },
         })
     }
@@ -1838,19 +2445,44 @@ mod tests {
                 sub_wallets_min_balance: default::sub_wallets_min_balance(),
                 keep: false,
                 burn_after_store: false,
+                send_objects_to: None,
+                credits: CreditsArgs::default(),
                 jwt_decode_secret: None,
                 jwt_algorithm: None,
                 jwt_expiring_sec: 0,
                 jwt_verify_upload: false,
                 replay_suppression_config: Default::default(),
+                tenants_config: None,
+                jobs_config: Default::default(),
+                uploads_config: Default::default(),
             },
             aggregator_args: AggregatorArgs {
                 allowed_headers: default::allowed_headers(),
                 allow_quilt_patch_tags_in_response: false,
+                sites_config: None,
             },
         })
     }
 
+    // Fixture for the transfer-blobs command.
+    fn transfer_blobs_command() -> Commands {
+        Commands::Cli(CliCommands::TransferBlobs {
+            recipient: SuiAddress::from_str(
+                "0x0000000000000000000000000000000000000000000000000000000000000001",
+            )
+            .unwrap(),
+            transfer_selection: TransferSelection {
+                object_ids: vec![],
+                blob_ids: vec![],
+                attribute: vec!["customer".to_string(), "acme".to_string()],
+                expiring_before: None,
+                all: false,
+                all_expired: false,
+            },
+            yes: false,
+        })
+    }
+
     param_test! {
         test_json_string_extraction -> TestResult: [
             store_max: (&make_cmd_str(STORE_STR_MAX), store_command(EpochCountOrMax::Max)),
@@ -1859,7 +2491,8 @@ mod tests {
                 store_command(EpochCountOrMax::Epochs(NonZeroU32::new(1).expect("1 > 0")))
             ),
             read: (&make_cmd_str(READ_STR), read_command()),
-            daemon: (&make_cmd_str(DAEMON_STR), daemon_command())
+            daemon: (&make_cmd_str(DAEMON_STR), daemon_command()),
+            transfer_blobs: (&make_cmd_str(TRANSFER_BLOBS_STR), transfer_blobs_command())
         ]
     }
     /// Test that the command string in JSON mode is extracted correctly.
@@ -1926,6 +2559,56 @@ pub enum NodeSortBy {
     Url,
 }
 
+/// The options of the watch mode of the `health` command.
+#[derive(Debug, Clone, Args, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthWatchArgs {
+    /// Poll the nodes periodically at the given interval (e.g., "1min") instead of printing a
+    /// single snapshot.
+    ///
+    /// The results of each poll are stored in a local database, and the trends of the nodes over
+    /// the `--trend-window` are printed after each poll.
+    #[arg(long, value_name = "INTERVAL", value_parser = humantime::parse_duration)]
+    #[serde(default)]
+    pub watch: Option<Duration>,
+    /// The directory of the database storing the health history of the nodes.
+    ///
+    /// Defaults to `~/.walrus/health_history`.
+    #[arg(long, requires = "watch")]
+    #[serde(
+        default,
+        deserialize_with = "walrus_utils::config::resolve_home_dir_option"
+    )]
+    pub history_db: Option<PathBuf>,
+    /// The window over which the trends of the nodes are computed.
+    #[arg(long, value_parser = humantime::parse_duration, default_value = "24h")]
+    #[serde(default = "default::health_trend_window")]
+    pub trend_window: Duration,
+    /// The duration for which the health samples of the nodes are kept in the history database.
+    ///
+    /// Older samples are deleted after each poll. Must not be shorter than `--trend-window`.
+    #[arg(long, value_parser = humantime::parse_duration, default_value = "7d")]
+    #[serde(default = "default::health_history_retention")]
+    pub history_retention: Duration,
+    /// The URL to which regressions of the nodes are posted as JSON.
+    ///
+    /// A regression is reported when a node becomes unreachable, leaves the active status,
+    /// restarts, has owned shards that are no longer ready, or exceeds `--max-event-lag` or
+    /// `--max-latency`.
+    #[arg(long, requires = "watch")]
+    #[serde(default)]
+    pub alert_webhook: Option<Url>,
+    /// The number of events a node can lag behind the most advanced polled node before it is
+    /// considered degraded.
+    #[arg(long, default_value_t = default::health_max_event_lag())]
+    #[serde(default = "default::health_max_event_lag")]
+    pub max_event_lag: u64,
+    /// The response latency of the health endpoint above which a node is considered degraded.
+    #[arg(long, value_parser = humantime::parse_duration, default_value = "2s")]
+    #[serde(default = "default::health_max_latency")]
+    pub max_latency: Duration,
+}
+
 /// Sort options for health information display
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum, Default)]
 #[serde(rename_all = "kebab-case")]
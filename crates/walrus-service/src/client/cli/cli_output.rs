@@ -16,20 +16,29 @@ use serde::Serialize;
 use walrus_core::{
     BlobId,
     ShardIndex,
-    encoding::quilt_encoding::QuiltPatchApi,
-    metadata::{QuiltIndex, QuiltMetadata, QuiltMetadataV1},
+    encoding::quilt_encoding::{QuiltIndexApi, QuiltPatchApi, QuiltVersion},
+    metadata::{QuiltIndex, QuiltMetadata, QuiltMetadataV1, QuiltMetadataV2},
 };
 use walrus_sdk::{
     client::{
+        audit::{BlobAuditReport, BlobAvailability, ShardAuditOutcome},
         client_types::StoredQuiltPatch,
         resource::RegisterBlobOp,
-        responses::{BlobStoreResult, BlobStoreResultWithPath, QuiltStoreResult},
+        responses::{
+            BlobStoreResult,
+            BlobStoreResultWithPath,
+            QuiltPatchMapping,
+            QuiltStoreResult,
+            QuiltUpdateResult,
+        },
     },
     format_event_id,
 };
 use walrus_storage_node_client::api::{BlobStatus, DeletableCounts, EventProgress};
 use walrus_sui::types::Blob;
 
+#[cfg(feature = "local-cluster")]
+use crate::client::responses::LocalClusterOutput;
 use crate::client::{
     cli::{
         HumanReadableBytes,
@@ -37,11 +46,13 @@ use crate::client::{
         HumanReadableMist,
         WalrusColors,
         error,
+        health_watch::HealthTrendsOutput,
         success,
         thousands_separator,
         warning,
     },
     responses::{
+        AuditOutput,
         BlobIdConversionOutput,
         BlobIdOutput,
         BlobStatusOutput,
@@ -62,6 +73,7 @@ use crate::client::{
         InfoSizeOutput,
         InfoStorageOutput,
         NodeHealthOutput,
+        QuiltExportOutput,
         ReadOutput,
         ReadQuiltOutput,
         ServiceHealthInfoOutput,
@@ -69,6 +81,7 @@ use crate::client::{
         StakeOutput,
         StorageNodeInfo,
         StoreQuiltDryRunOutput,
+        TransferBlobsOutput,
         WalletOutput,
     },
 };
@@ -96,6 +109,7 @@ impl CliOutput for Vec<BlobStoreResultWithPath> {
 
         let mut total_encoded_size = 0;
         let mut total_cost = 0;
+        let mut total_credits_discount = None;
         let mut reuse_and_extend_count = 0;
         let mut newly_certified = 0;
 
@@ -103,11 +117,15 @@ impl CliOutput for Vec<BlobStoreResultWithPath> {
             if let BlobStoreResult::NewlyCreated {
                 resource_operation,
                 cost,
+                credits_discount,
                 ..
             } = &res.blob_store_result
             {
                 total_encoded_size += resource_operation.encoded_length();
                 total_cost += cost;
+                if let Some(discount) = credits_discount {
+                    *total_credits_discount.get_or_insert(0) += discount;
+                }
                 match resource_operation {
                     RegisterBlobOp::ReuseAndExtend { .. } => {
                         reuse_and_extend_count += 1;
@@ -142,7 +160,11 @@ impl CliOutput for Vec<BlobStoreResultWithPath> {
                 "Total encoded size: {}",
                 HumanReadableBytes(total_encoded_size)
             );
-            println!("Total cost: {}", HumanReadableFrost::from(total_cost));
+            println!(
+                "Total cost: {}{}",
+                HumanReadableFrost::from(total_cost),
+                format_credits_discount(total_credits_discount)
+            );
         } else {
             println!(
                 "{}",
@@ -182,6 +204,7 @@ impl CliOutput for BlobStoreResultWithPath {
                 blob_object,
                 resource_operation,
                 cost,
+                credits_discount,
                 shared_blob_object,
             } => {
                 let operation_str = match resource_operation {
@@ -206,7 +229,7 @@ impl CliOutput for BlobStoreResultWithPath {
                     Sui object ID: {}\n\
                     Unencoded size: {}\n\
                     Encoded size (including replicated metadata): {}\n\
-                    Cost (excluding gas): {} {} \n\
+                    Cost (excluding gas): {}{} {} \n\
                     Expiry epoch (exclusive): {}{}\n\
                     Encoding type: {}\n",
                     success(),
@@ -221,6 +244,7 @@ impl CliOutput for BlobStoreResultWithPath {
                     HumanReadableBytes(blob_object.size),
                     HumanReadableBytes(resource_operation.encoded_length()),
                     HumanReadableFrost::from(*cost),
+                    format_credits_discount(*credits_discount),
                     operation_str,
                     blob_object.storage.end_epoch,
                     shared_blob_object
@@ -264,6 +288,20 @@ impl CliOutput for QuiltStoreResult {
     }
 }
 
+impl CliOutput for QuiltUpdateResult {
+    fn print_cli_output(&self) {
+        let blob_store_result = BlobStoreResultWithPath {
+            blob_store_result: self.quilt_store_result.blob_store_result.clone(),
+            path: PathBuf::from("path(s) ignored for quilt store result"),
+        };
+        blob_store_result.print_cli_output();
+
+        println!("Updated from quilt: {}", self.previous_quilt_id);
+        let table_output = construct_quilt_patch_mapping_table(&self.patch_mapping);
+        table_output.printstd();
+    }
+}
+
 impl CliOutput for ReadOutput {
     fn print_cli_output(&self) {
         if let Some(path) = &self.out {
@@ -316,11 +354,25 @@ impl CliOutput for DryRunOutput {
 
 /// Get the stored quilt patches from a quilt index.
 fn get_stored_quilt_patches(quilt_index: &QuiltIndex, quilt_id: BlobId) -> Vec<StoredQuiltPatch> {
+    match quilt_index {
+        QuiltIndex::V1(quilt_index) => get_stored_quilt_patches_for_version(quilt_index, quilt_id),
+        QuiltIndex::V2(quilt_index) => get_stored_quilt_patches_for_version(quilt_index, quilt_id),
+    }
+}
+
+fn get_stored_quilt_patches_for_version<V: QuiltVersion>(
+    quilt_index: &impl QuiltIndexApi<V>,
+    quilt_id: BlobId,
+) -> Vec<StoredQuiltPatch> {
     quilt_index
         .patches()
         .iter()
         .map(|patch| {
-            StoredQuiltPatch::new(quilt_id, &patch.identifier, patch.quilt_patch_internal_id())
+            StoredQuiltPatch::new(
+                quilt_id,
+                patch.identifier(),
+                patch.quilt_patch_internal_id(),
+            )
         })
         .collect()
 }
@@ -356,6 +408,128 @@ fn construct_stored_quilt_patch_table(quilt_patches: &[StoredQuiltPatch]) -> Tab
     table
 }
 
+fn construct_quilt_patch_mapping_table(patch_mapping: &[QuiltPatchMapping]) -> Table {
+    let mut table = Table::new();
+    table.set_format(default_table_format());
+    table.set_titles(row![
+        b->"Identifier",
+        b->"Change",
+        b->"Previous QuiltPatchId",
+        b->"New QuiltPatchId"
+    ]);
+
+    for patch in patch_mapping {
+        table.add_row(row![
+            patch.identifier,
+            patch.change,
+            patch.previous_quilt_patch_id.as_deref().unwrap_or("-"),
+            patch.new_quilt_patch_id.as_deref().unwrap_or("-")
+        ]);
+    }
+
+    table
+}
+
+impl CliOutput for AuditOutput {
+    fn print_cli_output(&self) {
+        for report in &self.reports {
+            print_blob_audit_report(report);
+        }
+        for failure in &self.failures {
+            println!(
+                "{} Blob ID {} could not be audited: {}",
+                error(),
+                failure.blob_id,
+                failure.error
+            );
+        }
+    }
+}
+
+fn print_blob_audit_report(report: &BlobAuditReport) {
+    let margins = &report.margins;
+    let availability = match margins.availability {
+        BlobAvailability::Full => "full".bold().walrus_teal(),
+        BlobAvailability::Degraded => "degraded".bold().yellow(),
+        BlobAvailability::AtRisk => "at risk".bold().red(),
+        BlobAvailability::Unrecoverable => "unrecoverable".bold().red(),
+    };
+    printdoc!(
+        "
+
+        {heading}
+        Audited at: {audited_at}
+        Committee epoch: {epoch}
+        Availability: {availability}
+        Shards serving valid {sliver_type} slivers: {verified} of {n_shards}
+        Slivers required for reconstruction: {required} (margin: {reconstruction_margin})
+        Maximum number of faulty shards (f): {max_faulty} (margin: {bft_margin})
+        ",
+        heading = format!("Audit of blob ID {}", report.blob_id)
+            .bold()
+            .walrus_purple(),
+        audited_at = report.audited_at.to_rfc3339(),
+        epoch = report.epoch,
+        sliver_type = report.sliver_type,
+        verified = margins.verified_shards,
+        n_shards = margins.n_shards,
+        required = margins.slivers_required_for_reconstruction,
+        reconstruction_margin = margins.reconstruction_margin,
+        max_faulty = margins.max_faulty_shards,
+        bft_margin = margins.bft_margin,
+    );
+
+    let unhealthy_nodes: Vec<_> = report
+        .nodes
+        .iter()
+        .filter(|node| node.n_verified() < node.shards.len())
+        .collect();
+    if unhealthy_nodes.is_empty() {
+        println!("All storage nodes serve valid slivers for all their shards.");
+        return;
+    }
+
+    let mut table = Table::new();
+    table.set_format(default_table_format());
+    table.set_titles(row![
+        b->"Node",
+        b->"# Shards",
+        b->"Verified",
+        b->"Missing",
+        b->"Invalid",
+        b->"Failed",
+        b->"First error",
+    ]);
+    for node in unhealthy_nodes {
+        let count = |predicate: fn(&ShardAuditOutcome) -> bool| {
+            node.shards
+                .iter()
+                .filter(|shard| predicate(&shard.outcome))
+                .count()
+        };
+        let first_error = node
+            .shards
+            .iter()
+            .find_map(|shard| match &shard.outcome {
+                ShardAuditOutcome::Invalid { error } | ShardAuditOutcome::Failed { error } => {
+                    Some(format!("shard {}: {error}", shard.shard_index))
+                }
+                _ => None,
+            })
+            .unwrap_or_else(|| "-".to_owned());
+        table.add_row(row![
+            node.name,
+            node.shards.len(),
+            r->node.n_verified(),
+            r->count(|outcome| matches!(outcome, ShardAuditOutcome::Missing)),
+            r->count(|outcome| matches!(outcome, ShardAuditOutcome::Invalid { .. })),
+            r->count(|outcome| matches!(outcome, ShardAuditOutcome::Failed { .. })),
+            first_error,
+        ]);
+    }
+    table.printstd();
+}
+
 impl CliOutput for BlobStatusOutput {
     fn print_cli_output(&self) {
         let blob_str = blob_and_file_str(&self.blob_id, &self.file);
@@ -386,6 +560,11 @@ impl CliOutput for BlobStatusOutput {
                     {count_deletable_total} registered{initial_certified_str})"
                 )
             }
+            BlobStatus::Invalid {
+                deny_listed: true, ..
+            } => println!(
+                "Blob ID {blob_str} was deleted from the storage nodes through a deny list."
+            ),
             BlobStatus::Invalid { .. } => println!("Blob ID {blob_str} is invalid."),
             BlobStatus::Permanent {
                 end_epoch,
@@ -812,6 +991,9 @@ fn removed_instance_string(blob_status: &BlobStatus) -> String {
             available, i.e., if someone has downloaded it before deletion)."
                 .to_owned()
         }
+        BlobStatus::Invalid {
+            deny_listed: true, ..
+        } => "The blob was deleted from the storage nodes through a deny list.".to_owned(),
         BlobStatus::Invalid { .. } => "The blob was marked as invalid.".to_owned(),
         BlobStatus::Permanent {
             is_certified,
@@ -844,6 +1026,16 @@ fn removed_instance_string(blob_status: &BlobStatus) -> String {
     }
 }
 
+/// Formats the discount obtained through credits, if any, to be appended to a cost.
+fn format_credits_discount(credits_discount: Option<u64>) -> String {
+    credits_discount.map_or_else(String::new, |discount| {
+        format!(
+            " ({} covered by credits)",
+            HumanReadableFrost::from(discount)
+        )
+    })
+}
+
 fn deletable_counts_summary(counts: &DeletableCounts) -> String {
     format!(
         "{} total, of which {} certified",
@@ -905,6 +1097,26 @@ impl CliOutput for ShareBlobOutput {
     }
 }
 
+impl CliOutput for TransferBlobsOutput {
+    fn print_cli_output(&self) {
+        if self.object_ids.is_empty() {
+            println!(
+                "{} The wallet does not own any matching blob objects; no blob objects were \
+                transferred.",
+                warning()
+            );
+            return;
+        }
+        println!(
+            "{} {} blob object(s) have been transferred to {}:\n{}",
+            success(),
+            self.object_ids.len(),
+            self.recipient,
+            self.object_ids.iter().map(|id| id.to_string()).join("\n")
+        );
+    }
+}
+
 impl CliOutput for FundSharedBlobOutput {
     fn print_cli_output(&self) {
         println!(
@@ -1087,6 +1299,101 @@ impl CliOutput for ServiceHealthInfoOutput {
     }
 }
 
+impl CliOutput for HealthTrendsOutput {
+    fn print_cli_output(&self) {
+        println!(
+            "\n{} at {} (trends over the last {})",
+            "Walrus Service Health Trends".bold(),
+            self.polled_at.to_rfc3339(),
+            humantime::format_duration(std::time::Duration::from_secs(self.trend_window_secs)),
+        );
+
+        let mut table = Table::new();
+        table.set_format(default_table_format());
+        table.set_titles(row![
+            b->"Idx",
+            b->"Name",
+            b->"Node ID",
+            bc->"# Shards\n(Committee)",
+            b->"Condition",
+            bc->"Availability\n(%)",
+            bc->"Healthy\n(%)",
+            bc->"Restarts",
+            bc->"Event lag\n(Now / Max)",
+            bc->"# Shards\n(Ready / Owned)",
+            bc->"Latency [ms]\n(p50 / p95)",
+        ]);
+        for (idx, node) in self.nodes.iter().enumerate() {
+            let or_na = |value: Option<u64>| value.map_or("N/A".to_owned(), |v| v.to_string());
+            let latest = &node.latest;
+            let shards = if latest.error.is_none() {
+                format!("{} / {}", latest.shards_ready, latest.shards_owned)
+            } else {
+                "N/A".to_owned()
+            };
+            table.add_row(row![
+                r->idx,
+                node.node_name,
+                node.node_id,
+                r->node.n_shards,
+                node.condition,
+                r->format!("{:.1}", node.availability_percent),
+                r->format!("{:.1}", node.healthy_percent),
+                r->node.restarts,
+                c->format!("{} / {}", or_na(latest.event_lag), or_na(node.max_event_lag)),
+                c->shards,
+                c->format!("{} / {}", or_na(node.p50_latency_ms), or_na(node.p95_latency_ms)),
+            ]);
+        }
+        table.printstd();
+
+        let regressions: Vec<_> = self
+            .nodes
+            .iter()
+            .flat_map(|node| {
+                node.regressions
+                    .iter()
+                    .map(move |regression| (&node.node_name, regression))
+            })
+            .collect();
+        if !regressions.is_empty() {
+            println!(
+                "\n{}",
+                "Regressions since the previous poll".bold().walrus_purple()
+            );
+            for (node_name, regression) in regressions {
+                println!("{} {node_name}: {regression}", warning());
+            }
+        }
+
+        let summary = &self.shard_ownership;
+        let percent_of_shards =
+            |count: usize| count as f64 / f64::from(summary.n_shards.get()) * 100.0;
+        printdoc!(
+            "
+
+            {heading}
+            Shards held by healthy nodes: {healthy} ({healthy_percent:.2} %)
+            Shards held by degraded nodes: {degraded} ({degraded_percent:.2} %)
+            Shards held by unreachable nodes: {unreachable} ({unreachable_percent:.2} %)
+            Healthy nodes hold a quorum of shards: {healthy_quorum}
+            ",
+            heading = "Shard Ownership".bold().walrus_purple(),
+            healthy = summary.healthy,
+            healthy_percent = percent_of_shards(summary.healthy),
+            degraded = summary.degraded,
+            degraded_percent = percent_of_shards(summary.degraded),
+            unreachable = summary.unreachable,
+            unreachable_percent = percent_of_shards(summary.unreachable),
+            healthy_quorum = if summary.healthy_quorum {
+                "yes".walrus_teal()
+            } else {
+                "no".red()
+            },
+        );
+    }
+}
+
 /// Default style for tables printed to stdout.
 fn default_table_format() -> format::TableFormat {
     format::FormatBuilder::new()
@@ -1205,6 +1512,7 @@ impl CliOutput for QuiltMetadata {
     fn print_cli_output(&self) {
         match self {
             QuiltMetadata::V1(metadata_v1) => metadata_v1.print_cli_output(),
+            QuiltMetadata::V2(metadata_v2) => metadata_v2.print_cli_output(),
         }
     }
 }
@@ -1225,6 +1533,80 @@ impl CliOutput for QuiltMetadataV1 {
     }
 }
 
+impl CliOutput for QuiltMetadataV2 {
+    fn print_cli_output(&self) {
+        println!(
+            "{}: {}",
+            "Quilt Metadata V2".bold().walrus_purple(),
+            self.quilt_id
+        );
+
+        construct_stored_quilt_patch_table(&get_stored_quilt_patches(
+            &self.index.clone().into(),
+            self.quilt_id,
+        ))
+        .printstd();
+    }
+}
+
+#[cfg(feature = "local-cluster")]
+impl CliOutput for LocalClusterOutput {
+    fn print_cli_output(&self) {
+        println!(
+            "{} Local Walrus cluster running in epoch {} with {} storage nodes and {} shards.",
+            success(),
+            self.epoch,
+            self.nodes.len(),
+            self.n_shards,
+        );
+
+        let mut table = Table::new();
+        table.set_format(default_table_format());
+        table.set_titles(row![b->"Name", b->"REST API address", b->"Public key"]);
+        for node in &self.nodes {
+            table.add_row(row![node.name, node.rest_api_address, node.public_key]);
+        }
+
+        printdoc!(
+            "
+
+            {nodes_heading}
+            {table}
+            {daemon_heading}
+            Aggregator and publisher: {daemon_url}
+            Publisher wallet: {publisher_address} ({publisher_balance})
+
+            {usage_heading}
+            export WALRUS_AGGREGATOR_URL={daemon_url}
+            export WALRUS_PUBLISHER_URL={daemon_url}
+            curl -X PUT \"$WALRUS_PUBLISHER_URL/v1/blobs?epochs=1\" --upload-file <FILE>
+            curl \"$WALRUS_AGGREGATOR_URL/v1/blobs/<BLOB_ID>\"
+
+            Press Ctrl-C to stop the cluster; all stored data is discarded.
+            ",
+            nodes_heading = "Storage nodes".bold().walrus_teal(),
+            daemon_heading = "Client daemon".bold().walrus_teal(),
+            usage_heading = "Usage".bold().walrus_teal(),
+            daemon_url = self.daemon_url.bold().walrus_purple(),
+            publisher_address = self.publisher_address,
+            publisher_balance = HumanReadableFrost::from(self.publisher_wal_balance),
+        );
+    }
+}
+
+impl CliOutput for QuiltExportOutput {
+    fn print_cli_output(&self) {
+        println!(
+            "{} Exported {} patches of quilt {} to the {} archive: {}",
+            success(),
+            self.identifiers.len().to_string().bold().walrus_purple(),
+            self.quilt_id,
+            self.format,
+            self.out.display().to_string().bold().walrus_purple()
+        );
+    }
+}
+
 impl CliOutput for ReadQuiltOutput {
     fn print_cli_output(&self) {
         if let Some(out) = &self.out {
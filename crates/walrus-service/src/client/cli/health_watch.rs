@@ -0,0 +1,974 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Watch mode of the `health` command.
+//!
+//! In watch mode, the health endpoints of the selected storage nodes are polled periodically. The
+//! result of each poll is stored in a local database, from which the trends of the nodes over a
+//! sliding window are computed. Regressions of the nodes between two consecutive polls can be
+//! posted to a webhook.
+
+use std::{
+    cmp::Ordering,
+    fmt,
+    num::NonZeroU16,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use futures::{StreamExt as _, stream};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use sui_types::base_types::ObjectID;
+use typed_store::{
+    Map,
+    TypedStoreError,
+    rocks::{self, DBMap, MetricConf, ReadWriteOptions},
+};
+use walrus_core::{Epoch, bft};
+use walrus_sdk::{
+    client::NodeCommunicationFactory,
+    sui::{client::SuiReadClient, types::StorageNode},
+};
+use walrus_storage_node_client::api::ServiceHealthInfo;
+
+use super::{CliOutput, HealthSortBy, HealthWatchArgs, NodeSelection, SortBy};
+use crate::client::responses::{HealthInfoError, NodeHealthOutput};
+
+/// The name of the column family storing the health samples of the nodes.
+const HEALTH_SAMPLES_STORE: &str = "health_samples";
+
+/// The status reported by storage nodes that are fully operational.
+const ACTIVE_NODE_STATUS: &str = "Active";
+
+/// The timeout for the requests posting alerts to the webhook.
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The thresholds above which a node is considered degraded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct HealthThresholds {
+    /// The maximum number of events a node can lag behind the most advanced polled node.
+    pub max_event_lag: u64,
+    /// The maximum response latency of the health endpoint.
+    pub max_latency: Duration,
+}
+
+/// The result of polling the health endpoint of a node.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct HealthSample {
+    /// The time of the poll, in milliseconds since the UNIX epoch.
+    pub timestamp_ms: u64,
+    /// The response latency of the health endpoint, in milliseconds.
+    pub latency_ms: u64,
+    /// The error returned when polling the node, if any.
+    ///
+    /// If set, the remaining fields are not meaningful.
+    pub error: Option<String>,
+    /// The status of the node.
+    pub node_status: Option<String>,
+    /// The epoch of the node.
+    pub epoch: Option<Epoch>,
+    /// The uptime of the node, in seconds.
+    pub uptime_secs: Option<u64>,
+    /// The highest event index processed by the node.
+    pub event_index: Option<u64>,
+    /// The number of events the node lags behind the most advanced node of the same poll.
+    pub event_lag: Option<u64>,
+    /// The number of shards owned by the node.
+    pub shards_owned: usize,
+    /// The number of owned shards that are ready.
+    pub shards_ready: usize,
+    /// The number of owned shards that are being transferred to the node.
+    pub shards_in_transfer: usize,
+    /// The number of owned shards that are being recovered.
+    pub shards_in_recovery: usize,
+}
+
+impl HealthSample {
+    /// Creates the sample from the result of a poll.
+    ///
+    /// The event lag is computed with respect to `highest_event_index`, the highest event index
+    /// reported by the nodes polled at the same time.
+    fn new(
+        polled_at: DateTime<Utc>,
+        latency: Duration,
+        health_info: &Result<ServiceHealthInfo, HealthInfoError>,
+        highest_event_index: Option<u64>,
+    ) -> Self {
+        let timestamp_ms = u64::try_from(polled_at.timestamp_millis()).unwrap_or_default();
+        let latency_ms = u64::try_from(latency.as_millis()).unwrap_or(u64::MAX);
+        let health_info = match health_info {
+            Ok(health_info) => health_info,
+            Err(error) => {
+                return Self {
+                    timestamp_ms,
+                    latency_ms,
+                    error: Some(error.to_string()),
+                    ..Default::default()
+                };
+            }
+        };
+
+        let event_index = health_info.event_progress.highest_finished_event_index;
+        let shard_status = &health_info.shard_summary.owned_shard_status;
+        Self {
+            timestamp_ms,
+            latency_ms,
+            error: None,
+            node_status: Some(health_info.node_status.clone()),
+            epoch: Some(health_info.epoch),
+            uptime_secs: Some(health_info.uptime.as_secs()),
+            event_index,
+            event_lag: highest_event_index
+                .map(|highest| highest.saturating_sub(event_index.unwrap_or_default())),
+            shards_owned: health_info.shard_summary.owned,
+            shards_ready: shard_status.ready,
+            shards_in_transfer: shard_status.in_transfer,
+            shards_in_recovery: shard_status.in_recovery,
+        }
+    }
+
+    fn is_reachable(&self) -> bool {
+        self.error.is_none()
+    }
+
+    fn is_active(&self) -> bool {
+        self.node_status.as_deref() == Some(ACTIVE_NODE_STATUS)
+    }
+
+    fn is_lagging(&self, thresholds: &HealthThresholds) -> bool {
+        self.event_lag
+            .is_some_and(|lag| lag > thresholds.max_event_lag)
+    }
+
+    fn is_slow(&self, thresholds: &HealthThresholds) -> bool {
+        u128::from(self.latency_ms) > thresholds.max_latency.as_millis()
+    }
+
+    fn all_shards_ready(&self) -> bool {
+        self.shards_ready == self.shards_owned
+    }
+
+    /// Returns the condition of the node at the time of the sample.
+    fn condition(&self, thresholds: &HealthThresholds) -> NodeCondition {
+        if !self.is_reachable() {
+            NodeCondition::Unreachable
+        } else if !self.is_active()
+            || self.is_lagging(thresholds)
+            || self.is_slow(thresholds)
+            || !self.all_shards_ready()
+        {
+            NodeCondition::Degraded
+        } else {
+            NodeCondition::Healthy
+        }
+    }
+
+    /// Returns the regressions of the node since the `previous` sample.
+    ///
+    /// Only transitions are reported, such that a degraded node does not trigger an alert on every
+    /// poll.
+    fn regressions_since(
+        &self,
+        previous: &HealthSample,
+        thresholds: &HealthThresholds,
+    ) -> Vec<Regression> {
+        match (&previous.error, &self.error) {
+            (None, Some(error)) => {
+                return vec![Regression::Unreachable {
+                    error: error.clone(),
+                }];
+            }
+            (None, None) => (),
+            // The node was unreachable, so there is nothing to compare against.
+            (Some(_), _) => return vec![],
+        }
+
+        let mut regressions = vec![];
+        if previous.is_active() && !self.is_active() {
+            regressions.push(Regression::StatusChanged {
+                from: ACTIVE_NODE_STATUS.to_owned(),
+                to: self.node_status.clone().unwrap_or_default(),
+            });
+        }
+        if let (Some(previous_uptime), Some(uptime)) = (previous.uptime_secs, self.uptime_secs)
+            && uptime < previous_uptime
+        {
+            regressions.push(Regression::Restarted);
+        }
+        if self.is_lagging(thresholds) && !previous.is_lagging(thresholds) {
+            regressions.push(Regression::EventLag {
+                lag: self.event_lag.unwrap_or_default(),
+            });
+        }
+        if previous.all_shards_ready() && !self.all_shards_ready() {
+            regressions.push(Regression::ShardsNotReady {
+                ready: self.shards_ready,
+                owned: self.shards_owned,
+            });
+        }
+        if self.is_slow(thresholds) && !previous.is_slow(thresholds) {
+            regressions.push(Regression::SlowResponse {
+                latency_ms: self.latency_ms,
+            });
+        }
+        regressions
+    }
+}
+
+/// The condition of a node, derived from its latest health sample.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum NodeCondition {
+    /// The node does not respond to health requests.
+    Unreachable,
+    /// The node responds, but is not active, lags behind, responds slowly, or has shards that are
+    /// not ready.
+    Degraded,
+    /// The node is active, up to date, and responsive.
+    Healthy,
+}
+
+impl fmt::Display for NodeCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unreachable => write!(f, "unreachable"),
+            Self::Degraded => write!(f, "degraded"),
+            Self::Healthy => write!(f, "healthy"),
+        }
+    }
+}
+
+/// A regression of a node between two consecutive polls.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(
+    tag = "kind",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub(crate) enum Regression {
+    /// The node stopped responding to health requests.
+    Unreachable {
+        /// The error returned when polling the node.
+        error: String,
+    },
+    /// The node left the active status.
+    StatusChanged {
+        /// The previous status of the node.
+        from: String,
+        /// The current status of the node.
+        to: String,
+    },
+    /// The node restarted since the previous poll.
+    Restarted,
+    /// The event lag of the node exceeded the threshold.
+    EventLag {
+        /// The current event lag of the node.
+        lag: u64,
+    },
+    /// Some of the shards owned by the node are no longer ready.
+    ShardsNotReady {
+        /// The number of owned shards that are ready.
+        ready: usize,
+        /// The number of owned shards.
+        owned: usize,
+    },
+    /// The response latency of the node exceeded the threshold.
+    SlowResponse {
+        /// The response latency of the node, in milliseconds.
+        latency_ms: u64,
+    },
+}
+
+impl fmt::Display for Regression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unreachable { error } => write!(f, "unreachable: {error}"),
+            Self::StatusChanged { from, to } => write!(f, "status changed from {from} to {to}"),
+            Self::Restarted => write!(f, "restarted"),
+            Self::EventLag { lag } => write!(f, "lagging {lag} events behind"),
+            Self::ShardsNotReady { ready, owned } => {
+                write!(f, "only {ready} of {owned} shards ready")
+            }
+            Self::SlowResponse { latency_ms } => write!(f, "slow response ({latency_ms} ms)"),
+        }
+    }
+}
+
+/// The trend of the health of a node over the trend window.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct NodeHealthTrend {
+    pub node_id: ObjectID,
+    pub node_name: String,
+    pub node_url: String,
+    /// The number of shards assigned to the node in the current committee.
+    pub n_shards: usize,
+    /// The condition of the node in the latest poll.
+    pub condition: NodeCondition,
+    /// The number of polls in the window.
+    pub n_samples: usize,
+    /// The percentage of polls in the window in which the node responded.
+    pub availability_percent: f64,
+    /// The percentage of polls in the window in which the node was healthy.
+    pub healthy_percent: f64,
+    /// The number of restarts of the node observed in the window.
+    pub restarts: usize,
+    /// The maximum event lag of the node in the window.
+    pub max_event_lag: Option<u64>,
+    /// The median response latency of the node in the window, in milliseconds.
+    pub p50_latency_ms: Option<u64>,
+    /// The 95th percentile of the response latency of the node in the window, in milliseconds.
+    pub p95_latency_ms: Option<u64>,
+    /// The latest sample of the node.
+    pub latest: HealthSample,
+    /// The regressions of the node since the previous poll.
+    pub regressions: Vec<Regression>,
+}
+
+impl NodeHealthTrend {
+    /// Computes the trend of the node from its samples in the window, ordered by time.
+    ///
+    /// Returns `None` if there are no samples.
+    fn from_samples(
+        node: &PolledNode,
+        samples: &[HealthSample],
+        regressions: Vec<Regression>,
+        thresholds: &HealthThresholds,
+    ) -> Option<Self> {
+        let latest = samples.last()?.clone();
+        let percent_of_samples = |count: usize| count as f64 / samples.len() as f64 * 100.0;
+
+        let mut latencies: Vec<_> = samples
+            .iter()
+            .filter(|sample| sample.is_reachable())
+            .map(|sample| sample.latency_ms)
+            .collect();
+        latencies.sort_unstable();
+        // Restarts are detected by a decrease of the uptime, also across unreachable samples.
+        let uptimes: Vec<_> = samples
+            .iter()
+            .filter_map(|sample| sample.uptime_secs)
+            .collect();
+        let restarts = uptimes.windows(2).filter(|pair| pair[1] < pair[0]).count();
+
+        Some(Self {
+            node_id: node.node_id,
+            node_name: node.node_name.clone(),
+            node_url: node.node_url.clone(),
+            n_shards: node.n_shards,
+            condition: latest.condition(thresholds),
+            n_samples: samples.len(),
+            availability_percent: percent_of_samples(
+                samples
+                    .iter()
+                    .filter(|sample| sample.is_reachable())
+                    .count(),
+            ),
+            healthy_percent: percent_of_samples(
+                samples
+                    .iter()
+                    .filter(|sample| sample.condition(thresholds) == NodeCondition::Healthy)
+                    .count(),
+            ),
+            restarts,
+            max_event_lag: samples.iter().filter_map(|sample| sample.event_lag).max(),
+            p50_latency_ms: walrus_utils::nearest_rank(&latencies, 50.0),
+            p95_latency_ms: walrus_utils::nearest_rank(&latencies, 95.0),
+            latest,
+            regressions,
+        })
+    }
+
+    fn cmp_by(&self, other: &Self, sort_by: Option<&HealthSortBy>) -> Ordering {
+        let by_name = || {
+            self.node_name
+                .to_lowercase()
+                .cmp(&other.node_name.to_lowercase())
+        };
+        match sort_by {
+            Some(HealthSortBy::Name) => by_name(),
+            Some(HealthSortBy::Id) => self.node_id.cmp(&other.node_id),
+            Some(HealthSortBy::Url) => self
+                .node_url
+                .to_lowercase()
+                .cmp(&other.node_url.to_lowercase()),
+            Some(HealthSortBy::Status) | None => self
+                .condition
+                .cmp(&other.condition)
+                .then_with(|| self.healthy_percent.total_cmp(&other.healthy_percent))
+                .then_with(by_name),
+        }
+    }
+}
+
+/// The number of shards held by nodes in each condition.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ShardOwnershipSummary {
+    /// The number of shards in the committee.
+    pub n_shards: NonZeroU16,
+    /// The number of shards held by healthy nodes.
+    pub healthy: usize,
+    /// The number of shards held by degraded nodes.
+    pub degraded: usize,
+    /// The number of shards held by unreachable nodes.
+    pub unreachable: usize,
+    /// Whether the healthy nodes hold a quorum (`n - f`) of the shards of the committee.
+    pub healthy_quorum: bool,
+}
+
+impl ShardOwnershipSummary {
+    fn new(n_shards: NonZeroU16, nodes: &[NodeHealthTrend]) -> Self {
+        let shards_in_condition = |condition| {
+            nodes
+                .iter()
+                .filter(|node| node.condition == condition)
+                .map(|node| node.n_shards)
+                .sum::<usize>()
+        };
+        let healthy = shards_in_condition(NodeCondition::Healthy);
+        Self {
+            n_shards,
+            healthy,
+            degraded: shards_in_condition(NodeCondition::Degraded),
+            unreachable: shards_in_condition(NodeCondition::Unreachable),
+            healthy_quorum: healthy >= usize::from(bft::min_n_correct(n_shards).get()),
+        }
+    }
+}
+
+/// The output of a poll of the `health --watch` command.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct HealthTrendsOutput {
+    /// The time of the poll.
+    pub polled_at: DateTime<Utc>,
+    /// The window over which the trends are computed, in seconds.
+    pub trend_window_secs: u64,
+    /// The trends of the polled nodes.
+    pub nodes: Vec<NodeHealthTrend>,
+    /// The shards held by nodes in each condition.
+    pub shard_ownership: ShardOwnershipSummary,
+}
+
+/// An alert about the regressions of a node, posted to the webhook.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NodeAlert<'a> {
+    node_id: ObjectID,
+    node_name: &'a str,
+    node_url: &'a str,
+    regressions: &'a [Regression],
+}
+
+/// The body of the requests posted to the webhook.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct WebhookPayload<'a> {
+    /// A human-readable summary of the alerts, for chat integrations.
+    text: String,
+    polled_at: DateTime<Utc>,
+    alerts: Vec<NodeAlert<'a>>,
+}
+
+impl<'a> WebhookPayload<'a> {
+    /// Creates the payload for the regressions in `output`, or `None` if there are none.
+    fn new(output: &'a HealthTrendsOutput) -> Option<Self> {
+        let alerts: Vec<_> = output
+            .nodes
+            .iter()
+            .filter(|node| !node.regressions.is_empty())
+            .map(|node| NodeAlert {
+                node_id: node.node_id,
+                node_name: &node.node_name,
+                node_url: &node.node_url,
+                regressions: &node.regressions,
+            })
+            .collect();
+        if alerts.is_empty() {
+            return None;
+        }
+
+        let text = alerts
+            .iter()
+            .map(|alert| {
+                let regressions = alert
+                    .regressions
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!("{} ({}): {regressions}", alert.node_name, alert.node_id)
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        Some(Self {
+            text: format!("Walrus storage node regressions:\n{text}"),
+            polled_at: output.polled_at,
+            alerts,
+        })
+    }
+}
+
+/// The persistent history of the health samples of the nodes.
+#[derive(Debug)]
+struct HealthHistory {
+    /// The samples, keyed by node ID and timestamp in milliseconds.
+    samples: DBMap<(ObjectID, u64), HealthSample>,
+}
+
+impl HealthHistory {
+    fn open(path: &Path) -> Result<Self> {
+        let mut db_opts = rocksdb::Options::default();
+        db_opts.create_missing_column_families(true);
+        db_opts.create_if_missing(true);
+        let database = rocks::open_cf_opts(
+            path,
+            Some(db_opts),
+            MetricConf::new("health_history"),
+            &[(HEALTH_SAMPLES_STORE, rocksdb::Options::default())],
+        )?;
+        let samples = DBMap::reopen(
+            &database,
+            Some(HEALTH_SAMPLES_STORE),
+            &ReadWriteOptions::default(),
+            false,
+        )?;
+        Ok(Self { samples })
+    }
+
+    fn record(&self, node_id: ObjectID, sample: &HealthSample) -> Result<(), TypedStoreError> {
+        self.samples.insert(&(node_id, sample.timestamp_ms), sample)
+    }
+
+    /// Returns the samples of the node with timestamps in `[from_ms, to_ms]`, ordered by time.
+    fn samples(
+        &self,
+        node_id: ObjectID,
+        from_ms: u64,
+        to_ms: u64,
+    ) -> Result<Vec<HealthSample>, TypedStoreError> {
+        self.samples
+            .safe_range_iter((node_id, from_ms)..=(node_id, to_ms))?
+            .map(|entry| entry.map(|(_, sample)| sample))
+            .collect()
+    }
+
+    /// Deletes the samples of the node with timestamps before `before_ms`.
+    fn prune(&self, node_id: ObjectID, before_ms: u64) -> Result<(), TypedStoreError> {
+        let expired = self
+            .samples
+            .safe_range_iter((node_id, 0)..(node_id, before_ms))?
+            .map(|entry| entry.map(|(key, _)| key))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.samples.multi_remove(expired)
+    }
+}
+
+/// The identity of a polled node.
+#[derive(Debug, Clone)]
+struct PolledNode {
+    node_id: ObjectID,
+    node_name: String,
+    node_url: String,
+    n_shards: usize,
+}
+
+/// Polls the health of the storage nodes periodically and reports their trends.
+#[derive(Debug)]
+pub(crate) struct HealthWatcher {
+    history: HealthHistory,
+    thresholds: HealthThresholds,
+    trend_window: Duration,
+    history_retention: Duration,
+    webhook: Option<Url>,
+    http_client: reqwest::Client,
+    sort: SortBy<HealthSortBy>,
+    concurrent_requests: usize,
+    json: bool,
+}
+
+impl HealthWatcher {
+    /// Creates the watcher, opening the history database.
+    ///
+    /// The trends are printed in the given `sort` order, as JSON if `json` is set.
+    pub fn new(
+        args: &HealthWatchArgs,
+        sort: SortBy<HealthSortBy>,
+        concurrent_requests: usize,
+        json: bool,
+    ) -> Result<Self> {
+        anyhow::ensure!(
+            args.history_retention >= args.trend_window,
+            "the history retention must not be shorter than the trend window"
+        );
+        let db_path = match &args.history_db {
+            Some(path) => path.clone(),
+            None => home::home_dir()
+                .context("unable to determine the home directory; set `--history-db`")?
+                .join(".walrus")
+                .join("health_history"),
+        };
+        let history = HealthHistory::open(&db_path).with_context(|| {
+            format!(
+                "unable to open the health history database at {}",
+                db_path.display()
+            )
+        })?;
+        Ok(Self {
+            history,
+            thresholds: HealthThresholds {
+                max_event_lag: args.max_event_lag,
+                max_latency: args.max_latency,
+            },
+            trend_window: args.trend_window,
+            history_retention: args.history_retention,
+            webhook: args.alert_webhook.clone(),
+            http_client: reqwest::Client::builder()
+                .timeout(WEBHOOK_TIMEOUT)
+                .build()
+                .context("unable to build the HTTP client for the webhook")?,
+            sort,
+            concurrent_requests,
+            json,
+        })
+    }
+
+    /// Polls the selected nodes every `interval` and prints their trends, until interrupted.
+    pub async fn run(
+        &self,
+        interval: Duration,
+        sui_read_client: &SuiReadClient,
+        node_selection: &NodeSelection,
+        communication_factory: &NodeCommunicationFactory,
+        n_shards: NonZeroU16,
+    ) -> Result<()> {
+        anyhow::ensure!(!interval.is_zero(), "the watch interval must not be zero");
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            // The nodes are fetched on every poll, to follow changes of the committee.
+            let nodes = match node_selection.get_nodes(sui_read_client).await {
+                Ok(nodes) => nodes,
+                Err(error) => {
+                    tracing::warn!(?error, "failed to fetch the storage nodes; skipping poll");
+                    continue;
+                }
+            };
+            let mut output = self.poll(nodes, communication_factory, n_shards).await?;
+            output
+                .nodes
+                .sort_by(|a, b| a.cmp_by(b, self.sort.sort_by.as_ref()));
+            if self.sort.desc {
+                output.nodes.reverse();
+            }
+
+            output.print_output(self.json)?;
+            if self.json {
+                println!();
+            }
+            self.send_alerts(&output).await;
+        }
+    }
+
+    /// Polls the nodes once, records the results, and computes the trends of the nodes.
+    async fn poll(
+        &self,
+        nodes: Vec<StorageNode>,
+        communication_factory: &NodeCommunicationFactory,
+        n_shards: NonZeroU16,
+    ) -> Result<HealthTrendsOutput> {
+        let polled_at = Utc::now();
+        let results: Vec<_> = stream::iter(nodes)
+            .map(|node| async move {
+                let n_shards = node.shard_ids.len();
+                let start = Instant::now();
+                let output =
+                    NodeHealthOutput::get_for_node(node, false, communication_factory).await;
+                let node = PolledNode {
+                    node_id: output.node_id,
+                    node_name: output.node_name,
+                    node_url: output.node_url,
+                    n_shards,
+                };
+                (node, start.elapsed(), output.health_info)
+            })
+            .buffer_unordered(self.concurrent_requests)
+            .collect()
+            .await;
+
+        let highest_event_index = results
+            .iter()
+            .filter_map(|(_, _, health_info)| {
+                health_info
+                    .as_ref()
+                    .ok()?
+                    .event_progress
+                    .highest_finished_event_index
+            })
+            .max();
+        let trend_window_ms = u64::try_from(self.trend_window.as_millis()).unwrap_or(u64::MAX);
+        let retention_ms = u64::try_from(self.history_retention.as_millis()).unwrap_or(u64::MAX);
+
+        let mut trends = Vec::with_capacity(results.len());
+        for (node, latency, health_info) in results {
+            let sample = HealthSample::new(polled_at, latency, &health_info, highest_event_index);
+            let mut samples = self.history.samples(
+                node.node_id,
+                sample.timestamp_ms.saturating_sub(trend_window_ms),
+                sample.timestamp_ms.saturating_sub(1),
+            )?;
+            let regressions = samples
+                .last()
+                .map(|previous| sample.regressions_since(previous, &self.thresholds))
+                .unwrap_or_default();
+            self.history.record(node.node_id, &sample)?;
+            self.history.prune(
+                node.node_id,
+                sample.timestamp_ms.saturating_sub(retention_ms),
+            )?;
+            samples.push(sample);
+            trends.extend(NodeHealthTrend::from_samples(
+                &node,
+                &samples,
+                regressions,
+                &self.thresholds,
+            ));
+        }
+
+        Ok(HealthTrendsOutput {
+            polled_at,
+            trend_window_secs: self.trend_window.as_secs(),
+            shard_ownership: ShardOwnershipSummary::new(n_shards, &trends),
+            nodes: trends,
+        })
+    }
+
+    /// Posts the regressions in `output` to the webhook, if any.
+    ///
+    /// Failures are logged, as they should not interrupt the monitoring.
+    async fn send_alerts(&self, output: &HealthTrendsOutput) {
+        let (Some(webhook), Some(payload)) = (&self.webhook, WebhookPayload::new(output)) else {
+            return;
+        };
+        let result = self
+            .http_client
+            .post(webhook.clone())
+            .json(&payload)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+        if let Err(error) = result {
+            tracing::warn!(?error, %webhook, "failed to post the health alerts to the webhook");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const THRESHOLDS: HealthThresholds = HealthThresholds {
+        max_event_lag: 100,
+        max_latency: Duration::from_secs(1),
+    };
+
+    fn healthy_sample(timestamp_ms: u64) -> HealthSample {
+        HealthSample {
+            timestamp_ms,
+            latency_ms: 50,
+            error: None,
+            node_status: Some(ACTIVE_NODE_STATUS.to_owned()),
+            epoch: Some(1),
+            uptime_secs: Some(timestamp_ms / 1000),
+            event_index: Some(1000),
+            event_lag: Some(0),
+            shards_owned: 10,
+            shards_ready: 10,
+            shards_in_transfer: 0,
+            shards_in_recovery: 0,
+        }
+    }
+
+    fn unreachable_sample(timestamp_ms: u64) -> HealthSample {
+        HealthSample {
+            timestamp_ms,
+            latency_ms: 5000,
+            error: Some("connection refused".to_owned()),
+            ..Default::default()
+        }
+    }
+
+    fn polled_node(n_shards: usize) -> PolledNode {
+        PolledNode {
+            node_id: ObjectID::random(),
+            node_name: "node".to_owned(),
+            node_url: "node.example.com:9185".to_owned(),
+            n_shards,
+        }
+    }
+
+    #[test]
+    fn reports_only_transitions_as_regressions() {
+        let previous = healthy_sample(100_000);
+        let mut current = healthy_sample(160_000);
+        assert!(current.regressions_since(&previous, &THRESHOLDS).is_empty());
+
+        current.node_status = Some("RecoveryCatchUp".to_owned());
+        current.uptime_secs = Some(10);
+        current.event_lag = Some(500);
+        current.shards_ready = 8;
+        current.latency_ms = 2000;
+        assert_eq!(
+            current.regressions_since(&previous, &THRESHOLDS),
+            vec![
+                Regression::StatusChanged {
+                    from: ACTIVE_NODE_STATUS.to_owned(),
+                    to: "RecoveryCatchUp".to_owned(),
+                },
+                Regression::Restarted,
+                Regression::EventLag { lag: 500 },
+                Regression::ShardsNotReady {
+                    ready: 8,
+                    owned: 10
+                },
+                Regression::SlowResponse { latency_ms: 2000 },
+            ]
+        );
+
+        // A node that stays degraded does not trigger new alerts.
+        let mut next = current.clone();
+        next.timestamp_ms = 220_000;
+        next.uptime_secs = Some(70);
+        assert!(next.regressions_since(&current, &THRESHOLDS).is_empty());
+    }
+
+    #[test]
+    fn reports_unreachable_nodes_once() {
+        let previous = healthy_sample(100_000);
+        let current = unreachable_sample(160_000);
+        assert_eq!(
+            current.regressions_since(&previous, &THRESHOLDS),
+            vec![Regression::Unreachable {
+                error: "connection refused".to_owned()
+            }]
+        );
+        assert!(
+            unreachable_sample(220_000)
+                .regressions_since(&current, &THRESHOLDS)
+                .is_empty()
+        );
+        assert!(
+            healthy_sample(280_000)
+                .regressions_since(&current, &THRESHOLDS)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn computes_node_trends() {
+        // The node restarts while it is unreachable.
+        let mut restarted = healthy_sample(300_000);
+        restarted.uptime_secs = Some(5);
+        restarted.latency_ms = 1500;
+        restarted.event_lag = Some(20);
+        let samples = vec![
+            healthy_sample(100_000),
+            unreachable_sample(200_000),
+            restarted,
+            healthy_sample(400_000),
+        ];
+
+        let trend = NodeHealthTrend::from_samples(&polled_node(10), &samples, vec![], &THRESHOLDS)
+            .expect("there are samples");
+        assert_eq!(trend.condition, NodeCondition::Healthy);
+        assert_eq!(trend.n_samples, 4);
+        assert_eq!(trend.availability_percent, 75.0);
+        // The sample after the restart is slow, and therefore degraded.
+        assert_eq!(trend.healthy_percent, 50.0);
+        assert_eq!(trend.restarts, 1);
+        assert_eq!(trend.max_event_lag, Some(20));
+        // Latencies of unreachable nodes are not taken into account.
+        assert_eq!(trend.p50_latency_ms, Some(50));
+        assert_eq!(trend.p95_latency_ms, Some(1500));
+
+        assert!(
+            NodeHealthTrend::from_samples(&polled_node(10), &[], vec![], &THRESHOLDS).is_none()
+        );
+    }
+
+    #[test]
+    fn summarizes_shard_ownership() {
+        let mut lagging = healthy_sample(100_000);
+        lagging.event_lag = Some(1000);
+        let trends: Vec<_> = [
+            (600, healthy_sample(100_000)),
+            (300, lagging),
+            (100, unreachable_sample(100_000)),
+        ]
+        .into_iter()
+        .map(|(n_shards, sample)| {
+            NodeHealthTrend::from_samples(&polled_node(n_shards), &[sample], vec![], &THRESHOLDS)
+                .expect("there are samples")
+        })
+        .collect();
+
+        let n_shards = NonZeroU16::new(1000).expect("1000 is not zero");
+        let summary = ShardOwnershipSummary::new(n_shards, &trends);
+        assert_eq!(
+            summary,
+            ShardOwnershipSummary {
+                n_shards,
+                healthy: 600,
+                degraded: 300,
+                unreachable: 100,
+                healthy_quorum: false,
+            }
+        );
+    }
+
+    #[test]
+    fn stores_and_retrieves_samples_by_node_and_time() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let history = HealthHistory::open(dir.path())?;
+        let node_a = ObjectID::random();
+        let node_b = ObjectID::random();
+        for timestamp_ms in [100, 200, 300] {
+            history.record(node_a, &healthy_sample(timestamp_ms))?;
+            history.record(node_b, &unreachable_sample(timestamp_ms))?;
+        }
+
+        let samples = history.samples(node_a, 150, 300)?;
+        assert_eq!(samples, vec![healthy_sample(200), healthy_sample(300)]);
+        assert_eq!(
+            history.samples(node_b, 0, 100)?,
+            vec![unreachable_sample(100)]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn prunes_samples_of_the_node_before_the_cutoff() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let history = HealthHistory::open(dir.path())?;
+        let node_a = ObjectID::random();
+        let node_b = ObjectID::random();
+        for timestamp_ms in [100, 200, 300] {
+            history.record(node_a, &healthy_sample(timestamp_ms))?;
+            history.record(node_b, &healthy_sample(timestamp_ms))?;
+        }
+
+        history.prune(node_a, 200)?;
+        assert_eq!(
+            history.samples(node_a, 0, 300)?,
+            vec![healthy_sample(200), healthy_sample(300)]
+        );
+        assert_eq!(history.samples(node_b, 0, 300)?.len(), 3);
+        Ok(())
+    }
+}
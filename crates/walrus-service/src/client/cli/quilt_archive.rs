@@ -0,0 +1,414 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Export and import of quilts as tar or zip archives.
+//!
+//! The patches of a quilt are stored as the files of the archive, using their identifiers as
+//! paths. The tags of the patches are stored in a sidecar manifest at the root of the archive,
+//! named [`QUILT_ARCHIVE_MANIFEST`].
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt,
+    fs::File,
+    io::{BufWriter, Cursor, Read, Seek, Write},
+    path::{Component, Path},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use serde_with::{DisplayFromStr, serde_as};
+use walrus_core::{BlobId, encoding::quilt_encoding::QuiltStoreBlob};
+
+/// The path of the manifest holding the identifiers and tags of the patches in the archive.
+pub const QUILT_ARCHIVE_MANIFEST: &str = "_walrus_quilt.json";
+
+/// The magic bytes at the start of a zip archive.
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+/// The magic bytes at the start of an empty zip archive.
+const EMPTY_ZIP_MAGIC: &[u8] = b"PK\x05\x06";
+/// The magic bytes at the start of gzip-compressed data.
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+
+/// The format of a quilt archive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum QuiltArchiveFormat {
+    /// A tar archive.
+    #[default]
+    Tar,
+    /// A zip archive, with deflate-compressed files.
+    Zip,
+}
+
+impl fmt::Display for QuiltArchiveFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tar => write!(f, "tar"),
+            Self::Zip => write!(f, "zip"),
+        }
+    }
+}
+
+/// The manifest of a quilt archive.
+#[serde_as]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuiltArchiveManifest {
+    /// The ID of the quilt the archive was exported from, if any.
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quilt_id: Option<BlobId>,
+    /// The patches in the archive.
+    pub patches: Vec<QuiltArchivePatch>,
+}
+
+/// A patch in the manifest of a quilt archive.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuiltArchivePatch {
+    /// The identifier of the patch, which is also its path in the archive.
+    pub identifier: String,
+    /// The tags of the patch.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tags: BTreeMap<String, String>,
+}
+
+/// Writes the blobs of a quilt to an archive at `path`.
+pub fn export_quilt_archive(
+    path: &Path,
+    format: QuiltArchiveFormat,
+    quilt_id: BlobId,
+    blobs: &[QuiltStoreBlob<'_>],
+) -> Result<()> {
+    let file = File::create(path)
+        .with_context(|| format!("failed to create the archive '{}'", path.display()))?;
+    let mut writer = BufWriter::new(file);
+    write_quilt_archive(&mut writer, format, Some(quilt_id), blobs)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Reads the blobs for a quilt from the tar or zip archive at `path`.
+///
+/// The identifiers of the blobs are the paths of the files in the archive, and the tags are taken
+/// from the manifest of the archive, if present. Gzip-compressed tar archives are supported.
+pub fn import_quilt_archive(path: &Path) -> Result<Vec<QuiltStoreBlob<'static>>> {
+    let data = std::fs::read(path)
+        .with_context(|| format!("failed to read the archive '{}'", path.display()))?;
+    read_quilt_archive(&data)
+        .with_context(|| format!("failed to import the archive '{}'", path.display()))
+}
+
+/// Writes the blobs of a quilt and the corresponding manifest as an archive to the writer.
+fn write_quilt_archive<W: Write + Seek>(
+    writer: W,
+    format: QuiltArchiveFormat,
+    quilt_id: Option<BlobId>,
+    blobs: &[QuiltStoreBlob<'_>],
+) -> Result<()> {
+    let mut manifest = QuiltArchiveManifest {
+        quilt_id,
+        patches: Vec::with_capacity(blobs.len()),
+    };
+    let mut files = Vec::with_capacity(blobs.len() + 1);
+    for blob in blobs {
+        check_archive_path(blob.identifier())?;
+        manifest.patches.push(QuiltArchivePatch {
+            identifier: blob.identifier().to_owned(),
+            tags: blob.tags().clone(),
+        });
+        files.push((blob.identifier(), blob.data()));
+    }
+    let manifest = serde_json::to_vec_pretty(&manifest)?;
+    files.push((QUILT_ARCHIVE_MANIFEST, manifest.as_slice()));
+
+    match format {
+        QuiltArchiveFormat::Tar => write_tar(writer, &files),
+        QuiltArchiveFormat::Zip => write_zip(writer, &files),
+    }
+}
+
+fn write_tar<W: Write>(writer: W, files: &[(&str, &[u8])]) -> Result<()> {
+    let mtime = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs());
+    let mut builder = tar::Builder::new(writer);
+    for (path, data) in files {
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(tar::EntryType::Regular);
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(mtime);
+        builder.append_data(&mut header, path, *data)?;
+    }
+    builder.into_inner()?;
+    Ok(())
+}
+
+fn write_zip<W: Write + Seek>(writer: W, files: &[(&str, &[u8])]) -> Result<()> {
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+    let mut zip = zip::ZipWriter::new(writer);
+    for (path, data) in files {
+        zip.start_file(*path, options)?;
+        zip.write_all(data)?;
+    }
+    zip.finish()?;
+    Ok(())
+}
+
+/// Reads the blobs from a tar, gzip-compressed tar, or zip archive.
+fn read_quilt_archive(data: &[u8]) -> Result<Vec<QuiltStoreBlob<'static>>> {
+    let mut files = if data.starts_with(ZIP_MAGIC) || data.starts_with(EMPTY_ZIP_MAGIC) {
+        read_zip(data)?
+    } else if data.starts_with(GZIP_MAGIC) {
+        read_tar(flate2::read::GzDecoder::new(data))?
+    } else {
+        read_tar(data)?
+    };
+
+    let manifest = match files
+        .iter()
+        .position(|(path, _)| path == QUILT_ARCHIVE_MANIFEST)
+    {
+        Some(position) => {
+            let (_, manifest) = files.remove(position);
+            serde_json::from_slice::<QuiltArchiveManifest>(&manifest)
+                .context("failed to parse the archive manifest")?
+        }
+        None => QuiltArchiveManifest::default(),
+    };
+    if files.is_empty() {
+        bail!("the archive does not contain any files");
+    }
+
+    let mut tags = HashMap::with_capacity(manifest.patches.len());
+    for patch in manifest.patches {
+        if tags.insert(patch.identifier.clone(), patch.tags).is_some() {
+            bail!(
+                "the manifest lists the patch '{}' more than once",
+                patch.identifier
+            );
+        }
+    }
+    let mut blobs = Vec::with_capacity(files.len());
+    for (identifier, data) in files {
+        let blob_tags = tags.remove(&identifier).unwrap_or_default();
+        blobs.push(QuiltStoreBlob::new_owned(data, identifier)?.with_tags(blob_tags));
+    }
+    if !tags.is_empty() {
+        let mut missing: Vec<_> = tags.into_keys().collect();
+        missing.sort();
+        bail!(
+            "the patches listed in the manifest are missing from the archive: {}",
+            missing.join(", ")
+        );
+    }
+    Ok(blobs)
+}
+
+fn read_tar<R: Read>(reader: R) -> Result<Vec<(String, Vec<u8>)>> {
+    let mut archive = tar::Archive::new(reader);
+    let mut files = Vec::new();
+    let mut identifiers = HashSet::new();
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let entry_type = entry.header().entry_type();
+        let path = entry.path()?.into_owned();
+        if entry_type.is_dir() {
+            continue;
+        }
+        if !entry_type.is_file() {
+            tracing::warn!(path = %path.display(), ?entry_type, "skipping non-file archive entry");
+            continue;
+        }
+        let identifier = identifier_from_archive_path(&path)?;
+        if !identifiers.insert(identifier.clone()) {
+            bail!("the archive contains the file '{identifier}' more than once");
+        }
+        let mut data = Vec::with_capacity(usize::try_from(entry.size()).unwrap_or_default());
+        entry.read_to_end(&mut data)?;
+        files.push((identifier, data));
+    }
+    Ok(files)
+}
+
+fn read_zip(data: &[u8]) -> Result<Vec<(String, Vec<u8>)>> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(data))?;
+    let mut files = Vec::with_capacity(archive.len());
+    let mut identifiers = HashSet::new();
+    for index in 0..archive.len() {
+        let mut file = archive.by_index(index)?;
+        if file.is_dir() {
+            continue;
+        }
+        if !file.is_file() {
+            tracing::warn!(path = file.name(), "skipping non-file archive entry");
+            continue;
+        }
+        let Some(path) = file.enclosed_name() else {
+            bail!("the archive contains the invalid path '{}'", file.name());
+        };
+        let identifier = identifier_from_archive_path(&path)?;
+        if !identifiers.insert(identifier.clone()) {
+            bail!("the archive contains the file '{identifier}' more than once");
+        }
+        let mut data = Vec::with_capacity(usize::try_from(file.size()).unwrap_or_default());
+        file.read_to_end(&mut data)?;
+        files.push((identifier, data));
+    }
+    Ok(files)
+}
+
+/// Converts the path of a file in an archive to a quilt patch identifier.
+///
+/// The components of the path are joined with `/`, and leading `./` components are ignored.
+fn identifier_from_archive_path(path: &Path) -> Result<String> {
+    let mut components = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(component) => components.push(
+                component
+                    .to_str()
+                    .with_context(|| format!("the path '{}' is not UTF-8", path.display()))?,
+            ),
+            Component::CurDir => (),
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => {
+                bail!("the archive contains the unsafe path '{}'", path.display())
+            }
+        }
+    }
+    if components.is_empty() {
+        bail!("the archive contains the empty path '{}'", path.display());
+    }
+    Ok(components.join("/"))
+}
+
+/// Checks that the identifier of a patch can be used as a relative path in an archive.
+fn check_archive_path(identifier: &str) -> Result<()> {
+    let is_valid = identifier != QUILT_ARCHIVE_MANIFEST
+        && !identifier.contains('\\')
+        && identifier
+            .split('/')
+            .all(|component| !component.is_empty() && component != "." && component != "..");
+    if !is_valid {
+        bail!(
+            "the identifier '{identifier}' cannot be used as a path in an archive; use \
+            `walrus read-quilt` to read the patch instead"
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use walrus_test_utils::param_test;
+
+    use super::*;
+
+    fn blobs() -> Vec<QuiltStoreBlob<'static>> {
+        vec![
+            QuiltStoreBlob::new_owned(b"<html></html>".to_vec(), "index.html")
+                .expect("identifier is valid")
+                .with_tags([("content-type".to_string(), "text/html".to_string())]),
+            QuiltStoreBlob::new_owned(b"body {}".to_vec(), "css/style.css")
+                .expect("identifier is valid"),
+        ]
+    }
+
+    param_test! {
+        test_archive_roundtrip: [
+            tar: (QuiltArchiveFormat::Tar),
+            zip: (QuiltArchiveFormat::Zip),
+        ]
+    }
+    fn test_archive_roundtrip(format: QuiltArchiveFormat) {
+        let blobs = blobs();
+        let mut archive = Cursor::new(Vec::new());
+        write_quilt_archive(&mut archive, format, Some(BlobId([7; 32])), &blobs)
+            .expect("writing the archive should succeed");
+
+        let imported =
+            read_quilt_archive(archive.get_ref()).expect("reading the archive should succeed");
+        assert_eq!(imported, blobs);
+    }
+
+    #[test]
+    fn test_import_gzip_tar_without_manifest() {
+        let mut tar = tar::Builder::new(flate2::write::GzEncoder::new(
+            Vec::new(),
+            flate2::Compression::default(),
+        ));
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(tar::EntryType::Directory);
+        header.set_size(0);
+        tar.append_data(&mut header, "./data/", std::io::empty())
+            .expect("appending should succeed");
+        let mut header = tar::Header::new_gnu();
+        header.set_size(3);
+        tar.append_data(&mut header, "./data/a.csv", &b"1,2"[..])
+            .expect("appending should succeed");
+        let data = tar
+            .into_inner()
+            .and_then(|encoder| encoder.finish())
+            .expect("finishing the archive should succeed");
+
+        let imported = read_quilt_archive(&data).expect("reading the archive should succeed");
+        assert_eq!(
+            imported,
+            vec![QuiltStoreBlob::new_owned(b"1,2".to_vec(), "data/a.csv").expect("valid")]
+        );
+    }
+
+    #[test]
+    fn test_import_rejects_missing_manifest_patches() {
+        let manifest = QuiltArchiveManifest {
+            quilt_id: None,
+            patches: vec![QuiltArchivePatch {
+                identifier: "missing.txt".to_string(),
+                tags: BTreeMap::new(),
+            }],
+        };
+        let manifest = serde_json::to_vec(&manifest).expect("serialization should succeed");
+        let mut archive = Cursor::new(Vec::new());
+        write_zip(
+            &mut archive,
+            &[
+                ("a.txt", &b"a"[..]),
+                (QUILT_ARCHIVE_MANIFEST, manifest.as_slice()),
+            ],
+        )
+        .expect("writing the archive should succeed");
+
+        let error = read_quilt_archive(archive.get_ref()).expect_err("import should fail");
+        assert!(error.to_string().contains("missing.txt"));
+    }
+
+    param_test! {
+        test_check_archive_path: [
+            file: ("index.html", true),
+            nested: ("a/b/c.txt", true),
+            parent: ("../etc/passwd", false),
+            absolute: ("/etc/passwd", false),
+            current: ("./a.txt", false),
+            trailing_slash: ("dir/", false),
+            backslash: ("a\\b", false),
+            manifest: (QUILT_ARCHIVE_MANIFEST, false),
+        ]
+    }
+    fn test_check_archive_path(identifier: &str, is_valid: bool) {
+        assert_eq!(check_archive_path(identifier).is_ok(), is_valid);
+    }
+
+    #[test]
+    fn test_identifier_from_archive_path() {
+        assert_eq!(
+            identifier_from_archive_path(Path::new("./a/b.txt")).expect("path is valid"),
+            "a/b.txt"
+        );
+        assert!(identifier_from_archive_path(Path::new("a/../../b.txt")).is_err());
+        assert!(identifier_from_archive_path(Path::new("/b.txt")).is_err());
+    }
+}
@@ -20,13 +20,14 @@ use itertools::Itertools as _;
 use rand::seq::SliceRandom;
 use reqwest::Url;
 use sui_config::{SUI_CLIENT_CONFIG, sui_config_dir};
-use sui_types::base_types::ObjectID;
+use sui_types::base_types::{ObjectID, SuiAddress};
 use walrus_core::{
     BlobId,
     DEFAULT_ENCODING,
     EncodingType,
     EpochCount,
     SUPPORTED_ENCODING_TYPES,
+    SliverType,
     encoding::{
         EncodingConfig,
         EncodingFactory as _,
@@ -43,6 +44,7 @@ use walrus_sdk::{
         StoreArgs,
         WalrusNodeClient,
         quilt_client::{
+            QuiltUpdate,
             assign_identifiers_with_paths,
             generate_identifier_from_path,
             read_blobs_from_paths,
@@ -56,6 +58,7 @@ use walrus_sdk::{
     sui::{
         client::{
             BlobPersistence,
+            CreditsPolicy,
             ExpirySelectionPolicy,
             PostStoreAction,
             ReadClient,
@@ -78,20 +81,33 @@ use super::{
         BlobIdentity,
         BurnSelection,
         CliCommands,
+        CommonStoreOptions,
         DaemonArgs,
         DaemonCommands,
         EpochArg,
         FileOrBlobId,
         HealthSortBy,
+        HealthWatchArgs,
         InfoCommands,
         NodeAdminCommands,
         NodeSelection,
         PublisherArgs,
+        QuiltCommands,
         RpcArg,
         SortBy,
+        TransferSelection,
         UserConfirmation,
     },
     backfill::{pull_archive_blobs, run_blob_backfill},
+    health_watch::HealthWatcher,
+    quilt_archive::{QuiltArchiveFormat, export_quilt_archive, import_quilt_archive},
+};
+#[cfg(feature = "local-cluster")]
+use crate::client::{
+    LocalCluster,
+    PublisherServices,
+    cli::LocalClusterArgs,
+    responses::LocalClusterOutput,
 };
 use crate::{
     client::{
@@ -105,6 +121,7 @@ use crate::{
             QuiltBlobInput,
             QuiltPatchByIdentifier,
             QuiltPatchByPatchId,
+            QuiltPatchByQuery,
             QuiltPatchByTag,
             QuiltPatchSelector,
             get_contract_client,
@@ -115,6 +132,8 @@ use crate::{
         },
         multiplexer::ClientMultiplexer,
         responses::{
+            AuditFailure,
+            AuditOutput,
             BlobIdConversionOutput,
             BlobIdOutput,
             BlobStatusOutput,
@@ -131,12 +150,14 @@ use crate::{
             InfoPriceOutput,
             InfoSizeOutput,
             InfoStorageOutput,
+            QuiltExportOutput,
             ReadOutput,
             ReadQuiltOutput,
             ServiceHealthInfoOutput,
             ShareBlobOutput,
             StakeOutput,
             StoreQuiltDryRunOutput,
+            TransferBlobsOutput,
             WalletOutput,
         },
     },
@@ -232,6 +253,7 @@ impl ClientCommandRunner {
                         common_options.permanent,
                     )?,
                     PostStoreAction::from_share(common_options.share),
+                    common_options.credits.credits_policy(),
                     common_options.encoding_type,
                     common_options.upload_relay,
                     common_options.skip_tip_confirmation.into(),
@@ -242,11 +264,13 @@ impl ClientCommandRunner {
             CliCommands::StoreQuilt {
                 paths,
                 blobs,
+                from_archive,
                 common_options,
             } => {
                 self.store_quilt(
                     paths,
                     blobs,
+                    from_archive,
                     common_options.epoch_arg,
                     common_options.dry_run,
                     StoreOptimizations::from_force_and_ignore_resources_flags(
@@ -258,6 +282,7 @@ impl ClientCommandRunner {
                         common_options.permanent,
                     )?,
                     PostStoreAction::from_share(common_options.share),
+                    common_options.credits.credits_policy(),
                     common_options.encoding_type,
                     common_options.upload_relay,
                     common_options.skip_tip_confirmation.into(),
@@ -265,6 +290,27 @@ impl ClientCommandRunner {
                 .await
             }
 
+            CliCommands::UpdateQuilt {
+                quilt_id,
+                add,
+                replace,
+                delete,
+                record_lineage,
+                common_options,
+            } => {
+                self.update_quilt(
+                    quilt_id,
+                    add,
+                    replace,
+                    delete,
+                    record_lineage,
+                    common_options,
+                )
+                .await
+            }
+
+            CliCommands::Quilt { command } => self.run_quilt_command(command).await,
+
             CliCommands::BlobStatus {
                 file_or_blob_id,
                 timeout,
@@ -275,6 +321,13 @@ impl ClientCommandRunner {
                     .await
             }
 
+            CliCommands::Audit {
+                blob_ids,
+                secondary,
+                timeout,
+                rpc_arg: RpcArg { rpc_url },
+            } => self.audit(blob_ids, secondary, timeout, rpc_url).await,
+
             CliCommands::Info {
                 rpc_arg: RpcArg { rpc_url },
                 command,
@@ -286,9 +339,17 @@ impl ClientCommandRunner {
                 sort,
                 rpc_arg: RpcArg { rpc_url },
                 concurrent_requests,
+                watch,
             } => {
-                self.health(rpc_url, node_selection, detail, sort, concurrent_requests)
-                    .await
+                self.health(
+                    rpc_url,
+                    node_selection,
+                    detail,
+                    sort,
+                    concurrent_requests,
+                    watch,
+                )
+                .await
             }
 
             CliCommands::BlobId {
@@ -352,6 +413,15 @@ impl ClientCommandRunner {
                 yes,
             } => self.burn_blobs(burn_selection, yes.into()).await,
 
+            CliCommands::TransferBlobs {
+                recipient,
+                transfer_selection,
+                yes,
+            } => {
+                self.transfer_blobs(recipient, transfer_selection, yes.into())
+                    .await
+            }
+
             CliCommands::FundSharedBlob {
                 shared_blob_obj_id,
                 amount,
@@ -375,7 +445,11 @@ impl ClientCommandRunner {
                 blob_obj_id,
                 shared,
                 epochs_extended,
+                credits,
             } => {
+                if shared && credits.use_credits {
+                    anyhow::bail!("shared blobs cannot be extended with credits");
+                }
                 let sui_client = self
                     .config?
                     .new_contract_client(self.wallet?, self.gas_budget)
@@ -388,7 +462,9 @@ impl ClientCommandRunner {
                         .extend_shared_blob(blob_obj_id, epochs_extended)
                         .await?;
                 } else {
-                    sui_client.extend_blob(blob_obj_id, epochs_extended).await?;
+                    sui_client
+                        .extend_blob(blob_obj_id, epochs_extended, credits.credits_policy())
+                        .await?;
                 }
 
                 spinner.finish_with_message("done");
@@ -542,6 +618,11 @@ impl ClientCommandRunner {
                 self.daemon(&metrics_runtime.registry, args, aggregator_args)
                     .await
             }
+
+            #[cfg(feature = "local-cluster")]
+            DaemonCommands::LocalCluster { args } => {
+                self.local_cluster(&metrics_runtime.registry, args).await
+            }
         }
     }
 
@@ -621,6 +702,11 @@ impl ClientCommandRunner {
                     .get_blobs_by_tag(&quilt_id, &tag, &value)
                     .await?
             }
+            QuiltPatchSelector::ByQuery(QuiltPatchByQuery { quilt_id, query }) => {
+                quilt_read_client
+                    .get_blobs_by_filter(&quilt_id, &query)
+                    .await?
+            }
             QuiltPatchSelector::ByPatchId(QuiltPatchByPatchId { quilt_patch_ids }) => {
                 quilt_read_client.get_blobs_by_ids(&quilt_patch_ids).await?
             }
@@ -636,6 +722,46 @@ impl ClientCommandRunner {
         ReadQuiltOutput::new(out.clone(), retrieved_blobs).print_output(self.json)
     }
 
+    pub(crate) async fn run_quilt_command(self, command: QuiltCommands) -> Result<()> {
+        match command {
+            QuiltCommands::Export {
+                quilt_id,
+                format,
+                out,
+                rpc_arg: RpcArg { rpc_url },
+            } => self.export_quilt(quilt_id, format, out, rpc_url).await,
+        }
+    }
+
+    pub(crate) async fn export_quilt(
+        self,
+        quilt_id: BlobId,
+        format: QuiltArchiveFormat,
+        out: PathBuf,
+        rpc_url: Option<String>,
+    ) -> Result<()> {
+        let config = self.config?;
+        let sui_read_client =
+            get_sui_read_client_from_rpc_node_or_wallet(&config, rpc_url, self.wallet).await?;
+        let read_client =
+            WalrusNodeClient::new_read_client_with_refresher(config, sui_read_client).await?;
+
+        let blobs = read_client.quilt_client().get_all_blobs(&quilt_id).await?;
+        tracing::info!("retrieved {} blobs from quilt", blobs.len());
+        export_quilt_archive(&out, format, quilt_id, &blobs)?;
+
+        QuiltExportOutput {
+            quilt_id,
+            format,
+            out,
+            identifiers: blobs
+                .iter()
+                .map(|blob| blob.identifier().to_owned())
+                .collect(),
+        }
+        .print_output(self.json)
+    }
+
     pub(crate) async fn list_patches_in_quilt(
         self,
         quilt_id: BlobId,
@@ -664,6 +790,7 @@ impl ClientCommandRunner {
         store_optimizations: StoreOptimizations,
         persistence: BlobPersistence,
         post_store: PostStoreAction,
+        credits_policy: CreditsPolicy,
         encoding_type: Option<EncodingType>,
         upload_relay: Option<Url>,
         confirmation: UserConfirmation,
@@ -705,7 +832,8 @@ impl ClientCommandRunner {
             store_optimizations,
             persistence,
             post_store,
-        );
+        )
+        .with_credits_policy(credits_policy);
 
         if let Some(upload_relay) = upload_relay {
             let upload_relay_client = UploadRelayClient::new(
@@ -805,11 +933,13 @@ impl ClientCommandRunner {
         self,
         paths: Vec<PathBuf>,
         blobs: Vec<QuiltBlobInput>,
+        from_archive: Option<PathBuf>,
         epoch_arg: EpochArg,
         dry_run: bool,
         store_optimizations: StoreOptimizations,
         persistence: BlobPersistence,
         post_store: PostStoreAction,
+        credits_policy: CreditsPolicy,
         encoding_type: Option<EncodingType>,
         upload_relay: Option<Url>,
         confirmation: UserConfirmation,
@@ -831,7 +961,15 @@ impl ClientCommandRunner {
         let epochs_ahead =
             get_epochs_ahead(epoch_arg, system_object.max_epochs_ahead(), &client).await?;
 
-        let quilt_store_blobs = Self::load_blobs_for_quilt(&paths, blobs).await?;
+        let quilt_store_blobs = match from_archive {
+            Some(archive) => {
+                if !paths.is_empty() || !blobs.is_empty() {
+                    anyhow::bail!("cannot provide both an archive and paths or blob_inputs");
+                }
+                import_quilt_archive(&archive)?
+            }
+            None => Self::load_blobs_for_quilt(&paths, blobs).await?,
+        };
 
         if dry_run {
             return Self::store_quilt_dry_run(
@@ -855,7 +993,8 @@ impl ClientCommandRunner {
             store_optimizations,
             persistence,
             post_store,
-        );
+        )
+        .with_credits_policy(credits_policy);
 
         if let Some(upload_relay) = upload_relay {
             let upload_relay_client = UploadRelayClient::new(
@@ -895,6 +1034,89 @@ impl ClientCommandRunner {
         result.print_output(self.json)
     }
 
+    pub(crate) async fn update_quilt(
+        self,
+        quilt_id: BlobId,
+        add: Vec<PathBuf>,
+        replace: Vec<PathBuf>,
+        delete: Vec<String>,
+        record_lineage: bool,
+        common_options: CommonStoreOptions,
+    ) -> Result<()> {
+        common_options.epoch_arg.exactly_one_is_some()?;
+        if common_options.dry_run {
+            anyhow::bail!("dry runs are not supported when updating a quilt");
+        }
+        if common_options.upload_relay.is_some() {
+            anyhow::bail!("upload relays are not supported when updating a quilt");
+        }
+        let persistence = BlobPersistence::from_deletable_and_permanent(
+            common_options.deletable,
+            common_options.permanent,
+        )?;
+        let post_store = PostStoreAction::from_share(common_options.share);
+        if persistence.is_deletable() && post_store == PostStoreAction::Share {
+            anyhow::bail!("deletable blobs cannot be shared");
+        }
+        let encoding_type = common_options.encoding_type.unwrap_or(DEFAULT_ENCODING);
+        if !encoding_type.is_supported() {
+            anyhow::bail!(ClientErrorKind::UnsupportedEncodingType(encoding_type));
+        }
+
+        let update = QuiltUpdate {
+            additions: Self::load_blobs_for_quilt_update(&add)?,
+            replacements: Self::load_blobs_for_quilt_update(&replace)?,
+            deletions: delete,
+        };
+        let client = get_contract_client(self.config?, self.wallet, self.gas_budget, &None).await?;
+        let system_object = client.sui_client().read_client.get_system_object().await?;
+        let epochs_ahead = get_epochs_ahead(
+            common_options.epoch_arg,
+            system_object.max_epochs_ahead(),
+            &client,
+        )
+        .await?;
+        let store_args = StoreArgs::new(
+            encoding_type,
+            epochs_ahead,
+            StoreOptimizations::from_force_and_ignore_resources_flags(
+                common_options.force,
+                common_options.ignore_resources,
+            ),
+            persistence,
+            post_store,
+        )
+        .with_credits_policy(common_options.credits.credits_policy());
+
+        let start_timer = std::time::Instant::now();
+        let result = client
+            .quilt_client()
+            .update_quilt(&quilt_id, update, &store_args, record_lineage)
+            .await?;
+        tracing::info!(
+            duration = ?start_timer.elapsed(),
+            "{} blobs stored in the updated quilt",
+            result.quilt_store_result.stored_quilt_blobs.len(),
+        );
+
+        result.print_output(self.json)
+    }
+
+    /// Reads the files for a quilt update, using the filenames as identifiers.
+    fn load_blobs_for_quilt_update(paths: &[PathBuf]) -> Result<Vec<QuiltStoreBlob<'static>>> {
+        paths
+            .iter()
+            .enumerate()
+            .map(|(i, path)| {
+                let blob = read_blob_from_file(path)?;
+                Ok(QuiltStoreBlob::new_owned(
+                    blob,
+                    generate_identifier_from_path(path, i),
+                )?)
+            })
+            .collect()
+    }
+
     async fn load_blobs_for_quilt(
         paths: &[PathBuf],
         blob_inputs: Vec<QuiltBlobInput>,
@@ -979,6 +1201,39 @@ impl ClientCommandRunner {
         output.print_output(json)
     }
 
+    pub(crate) async fn audit(
+        self,
+        blob_ids: Vec<BlobId>,
+        secondary: bool,
+        timeout: Duration,
+        rpc_url: Option<String>,
+    ) -> Result<()> {
+        let client = get_read_client(self.config?, rpc_url, self.wallet, &None).await?;
+        let sliver_type = if secondary {
+            SliverType::Secondary
+        } else {
+            SliverType::Primary
+        };
+
+        let mut output = AuditOutput {
+            reports: Vec::with_capacity(blob_ids.len()),
+            failures: vec![],
+        };
+        for blob_id in blob_ids {
+            match client.audit_blob(&blob_id, sliver_type, timeout).await {
+                Ok(report) => output.reports.push(report),
+                Err(error) => {
+                    tracing::warn!(%blob_id, ?error, "auditing the blob failed");
+                    output.failures.push(AuditFailure {
+                        blob_id,
+                        error: error.to_string(),
+                    });
+                }
+            }
+        }
+        output.print_output(self.json)
+    }
+
     pub(crate) async fn blob_status(
         self,
         file_or_blob_id: FileOrBlobId,
@@ -1091,6 +1346,7 @@ impl ClientCommandRunner {
         detail: bool,
         sort: SortBy<HealthSortBy>,
         concurrent_requests: usize,
+        watch: HealthWatchArgs,
     ) -> Result<()> {
         node_selection.exactly_one_is_set()?;
 
@@ -1101,14 +1357,25 @@ impl ClientCommandRunner {
         let sui_read_client =
             get_sui_read_client_from_rpc_node_or_wallet(&config, rpc_url.clone(), self.wallet)
                 .await?;
+        let n_shards = sui_read_client.current_committee().await?.n_shards();
         let communication_factory = NodeCommunicationFactory::new(
             config.communication_config.clone(),
-            Arc::new(EncodingConfig::new(
-                sui_read_client.current_committee().await?.n_shards(),
-            )),
+            Arc::new(EncodingConfig::new(n_shards)),
             None,
         )?;
 
+        if let Some(interval) = watch.watch {
+            return HealthWatcher::new(&watch, sort, concurrent_requests, self.json)?
+                .run(
+                    interval,
+                    &sui_read_client,
+                    &node_selection,
+                    &communication_factory,
+                    n_shards,
+                )
+                .await;
+        }
+
         ServiceHealthInfoOutput::get_for_nodes(
             node_selection.get_nodes(&sui_read_client).await?,
             &communication_factory,
@@ -1175,9 +1442,9 @@ impl ClientCommandRunner {
             &args,
         )
         .await?;
-        let auth_config = args.generate_auth_config()?;
+        let services = args.open_services()?;
 
-        ClientDaemon::new_publisher(client, auth_config, &args, registry)
+        ClientDaemon::new_publisher(client, services, &args, registry)
             .run()
             .await?;
         Ok(())
@@ -1193,12 +1460,14 @@ impl ClientCommandRunner {
         tracing::debug!(?rpc_url, "attempting to run the Walrus aggregator");
         let client =
             get_read_client(self.config?, rpc_url, self.wallet, &daemon_args.blocklist).await?;
+        let sites = aggregator_args.open_sites()?;
         ClientDaemon::new_aggregator(
             client,
             daemon_args.bind_address,
             registry,
             aggregator_args.allowed_headers,
             aggregator_args.allow_quilt_patch_tags_in_response,
+            sites,
         )
         .run()
         .await?;
@@ -1220,14 +1489,47 @@ impl ClientCommandRunner {
             &args,
         )
         .await?;
-        let auth_config = args.generate_auth_config()?;
+        let services = args.open_services()?;
+        let sites = aggregator_args.open_sites()?;
 
-        ClientDaemon::new_daemon(client, auth_config, registry, &args, &aggregator_args)
+        ClientDaemon::new_daemon(client, services, sites, registry, &args, &aggregator_args)
             .run()
             .await?;
         Ok(())
     }
 
+    #[cfg(feature = "local-cluster")]
+    pub(crate) async fn local_cluster(
+        self,
+        registry: &Registry,
+        args: LocalClusterArgs,
+    ) -> Result<()> {
+        let cluster = LocalCluster::start(&args.cluster_config()).await?;
+        let publisher_args = args.publisher_args();
+        let sites = args.aggregator_args.open_sites()?;
+        let daemon = ClientDaemon::new_daemon(
+            cluster.client().clone(),
+            PublisherServices::default(),
+            sites,
+            registry,
+            &publisher_args,
+            &args.aggregator_args,
+        );
+
+        let output = LocalClusterOutput::new(&cluster, args.daemon_args.bind_address).await?;
+        if let Some(info_file) = &args.info_file {
+            std::fs::write(info_file, serde_json::to_vec_pretty(&output)?)?;
+        }
+        output.print_output(self.json)?;
+
+        let result = daemon.run().await;
+        cluster.shutdown();
+        if let Some(info_file) = &args.info_file {
+            let _ = std::fs::remove_file(info_file);
+        }
+        Ok(result?)
+    }
+
     pub(crate) fn convert_blob_id(self, blob_id_decimal: BlobIdDecimal) -> Result<()> {
         BlobIdConversionOutput::from(blob_id_decimal).print_output(self.json)
     }
@@ -1425,6 +1727,54 @@ impl ClientCommandRunner {
         Ok(())
     }
 
+    pub(crate) async fn transfer_blobs(
+        self,
+        recipient: SuiAddress,
+        transfer_selection: TransferSelection,
+        confirmation: UserConfirmation,
+    ) -> Result<()> {
+        let sui_client = self
+            .config?
+            .new_contract_client(self.wallet?, self.gas_budget)
+            .await?;
+        let object_ids = transfer_selection.get_object_ids(&sui_client).await?;
+
+        if object_ids.is_empty() {
+            return TransferBlobsOutput {
+                recipient,
+                object_ids,
+            }
+            .print_output(self.json);
+        }
+
+        if confirmation.is_required() && !self.json {
+            let object_list = object_ids.iter().map(|id| id.to_string()).join("\n");
+            println!(
+                "{} You are about to transfer the following blob object(s) to {}:\n{}\n\
+                ({} total). \nIf unsure, please enter `No` and check the `--help` manual.",
+                warning(),
+                recipient,
+                object_list,
+                object_ids.len()
+            );
+            if !ask_for_confirmation()? {
+                println!("{} Aborting. No blobs were transferred.", success());
+                return Ok(());
+            }
+        }
+
+        let spinner = styled_spinner();
+        spinner.set_message("transferring blobs...");
+        sui_client.transfer_blobs(&object_ids, recipient).await?;
+        spinner.finish_with_message("done");
+
+        TransferBlobsOutput {
+            recipient,
+            object_ids,
+        }
+        .print_output(self.json)
+    }
+
     pub(crate) async fn run_admin_command(self, command: NodeAdminCommands) -> Result<()> {
         let sui_client = self
             .config?
@@ -8,12 +8,11 @@ use std::{collections::HashSet, fmt::Debug, net::SocketAddr, sync::Arc};
 use axum::{
     BoxError,
     Router,
-    body::HttpBody,
     error_handling::HandleErrorLayer,
     extract::{DefaultBodyLimit, Query, Request, State},
     middleware::{self, Next},
     response::{IntoResponse, Response},
-    routing::{get, put},
+    routing::{get, post, put},
 };
 use axum_extra::{
     TypedHeader,
@@ -25,11 +24,15 @@ use routes::{
     BLOB_GET_ENDPOINT,
     BLOB_OBJECT_GET_ENDPOINT,
     BLOB_PUT_ENDPOINT,
+    JOB_GET_ENDPOINT,
     LIST_PATCHES_IN_QUILT_ENDPOINT,
     QUILT_PATCH_BY_ID_GET_ENDPOINT,
     QUILT_PATCH_BY_IDENTIFIER_GET_ENDPOINT,
     QUILT_PUT_ENDPOINT,
     STATUS_ENDPOINT,
+    UPLOAD_ENDPOINT,
+    UPLOAD_FINALIZE_ENDPOINT,
+    UPLOADS_ENDPOINT,
     daemon_cors_layer,
 };
 pub use routes::{PublisherQuery, QuiltPatchItem};
@@ -57,6 +60,7 @@ use walrus_core::{
 use walrus_sdk::{
     client::{
         StoreArgs,
+        StorePhaseNotifier,
         WalrusNodeClient,
         responses::{BlobStoreResult, QuiltStoreResult},
     },
@@ -64,7 +68,7 @@ use walrus_sdk::{
     store_optimizations::StoreOptimizations,
 };
 use walrus_sui::{
-    client::{BlobPersistence, PostStoreAction, ReadClient, SuiContractClient},
+    client::{BlobPersistence, CreditsPolicy, PostStoreAction, ReadClient, WriteClient},
     types::move_structs::BlobWithAttribute,
 };
 use walrus_utils::metrics::Registry;
@@ -73,7 +77,13 @@ use crate::{
     client::{
         cli::{AggregatorArgs, PublisherArgs},
         config::AuthConfig,
-        daemon::auth::verify_jwt_claim,
+        daemon::{
+            auth::{verify_jwt_claim, verify_jwt_identity},
+            jobs::JobManager,
+            sites::Sites,
+            tenants::{TENANT_USAGE_ENDPOINT, Tenants, tenant_identity_layer, tenant_layer},
+            uploads::UploadManager,
+        },
     },
     common::telemetry::{MakeHttpSpan, MetricsMiddlewareState, metrics_middleware},
 };
@@ -81,8 +91,12 @@ use crate::{
 pub mod auth;
 pub(crate) mod cache;
 pub(crate) use cache::{CacheConfig, CacheHandle};
+pub mod jobs;
 mod openapi;
 mod routes;
+pub mod sites;
+pub mod tenants;
+pub mod uploads;
 
 pub trait WalrusReadClient {
     /// Reads a blob from Walrus.
@@ -99,7 +113,9 @@ pub trait WalrusReadClient {
     ) -> impl std::future::Future<Output = ClientResult<BlobWithAttribute>> + Send;
 
     /// Retrieves blobs from quilt by their patch IDs.
-    /// Default implementation returns an error indicating quilt is not supported.
+    ///
+    /// The blobs are returned as stored in the quilt, i.e., compressed patches are not
+    /// decompressed. Default implementation returns an error indicating quilt is not supported.
     fn get_blobs_by_quilt_patch_ids(
         &self,
         _quilt_patch_ids: &[QuiltPatchId],
@@ -113,6 +129,8 @@ pub trait WalrusReadClient {
     }
 
     /// Retrieves a blob from quilt by quilt ID and identifier.
+    ///
+    /// The blob is returned as stored in the quilt, i.e., a compressed patch is not decompressed.
     /// Default implementation returns an error indicating quilt is not supported.
     fn get_blob_by_quilt_id_and_identifier(
         &self,
@@ -141,17 +159,45 @@ pub trait WalrusReadClient {
     }
 }
 
+/// The parameters of a store operation of a [`WalrusWriteClient`].
+#[derive(Debug, Clone, Copy)]
+pub struct WriteParams {
+    /// The encoding type to use, or `None` to use the default encoding.
+    pub encoding_type: Option<EncodingType>,
+    /// The number of epochs ahead for which to store the blob.
+    pub epochs_ahead: EpochCount,
+    /// The optimizations to apply to the store operation.
+    pub store_optimizations: StoreOptimizations,
+    /// Whether the blob is stored as permanent or deletable.
+    pub persistence: BlobPersistence,
+    /// The action to take on the blob object after it is stored.
+    pub post_store: PostStoreAction,
+    /// Whether and how to pay for the operation through credits.
+    pub credits_policy: CreditsPolicy,
+}
+
+impl WriteParams {
+    /// Returns the [`StoreArgs`] for these parameters.
+    fn store_args(&self) -> StoreArgs {
+        StoreArgs::new(
+            self.encoding_type.unwrap_or(DEFAULT_ENCODING),
+            self.epochs_ahead,
+            self.store_optimizations,
+            self.persistence,
+            self.post_store,
+        )
+        .with_credits_policy(self.credits_policy)
+    }
+}
+
 /// Trait representing a client that can write blobs to Walrus.
 pub trait WalrusWriteClient: WalrusReadClient {
     /// Writes a blob to Walrus.
     fn write_blob(
         &self,
         blob: &[u8],
-        encoding_type: Option<EncodingType>,
-        epochs_ahead: EpochCount,
-        store_optimizations: StoreOptimizations,
-        persistence: BlobPersistence,
-        post_store: PostStoreAction,
+        params: WriteParams,
+        phase_notifier: Option<StorePhaseNotifier>,
     ) -> impl std::future::Future<Output = ClientResult<BlobStoreResult>> + Send;
 
     /// Constructs a quilt from blobs.
@@ -165,15 +211,14 @@ pub trait WalrusWriteClient: WalrusReadClient {
     fn write_quilt<V: QuiltVersion>(
         &self,
         quilt: V::Quilt,
-        encoding_type: Option<EncodingType>,
-        epochs_ahead: EpochCount,
-        store_optimizations: StoreOptimizations,
-        persistence: BlobPersistence,
-        post_store: PostStoreAction,
+        params: WriteParams,
     ) -> impl std::future::Future<Output = ClientResult<QuiltStoreResult>> + Send;
 
     /// Returns the default [`PostStoreAction`] for this client.
     fn default_post_store_action(&self) -> PostStoreAction;
+
+    /// Returns the default [`CreditsPolicy`] for this client.
+    fn default_credits_policy(&self) -> CreditsPolicy;
 }
 
 impl<T: ReadClient> WalrusReadClient for WalrusNodeClient<T> {
@@ -192,7 +237,10 @@ impl<T: ReadClient> WalrusReadClient for WalrusNodeClient<T> {
         &self,
         quilt_patch_ids: &[QuiltPatchId],
     ) -> ClientResult<Vec<QuiltStoreBlob<'static>>> {
-        self.quilt_client().get_blobs_by_ids(quilt_patch_ids).await
+        self.quilt_client()
+            .with_decompression(false)
+            .get_blobs_by_ids(quilt_patch_ids)
+            .await
     }
 
     async fn get_blob_by_quilt_id_and_identifier(
@@ -202,6 +250,7 @@ impl<T: ReadClient> WalrusReadClient for WalrusNodeClient<T> {
     ) -> ClientResult<QuiltStoreBlob<'static>> {
         let blobs = self
             .quilt_client()
+            .with_decompression(false)
             .get_blobs_by_identifiers(quilt_id, &[identifier])
             .await?;
 
@@ -215,52 +264,58 @@ impl<T: ReadClient> WalrusReadClient for WalrusNodeClient<T> {
 
     async fn list_patches_in_quilt(&self, quilt_id: &BlobId) -> ClientResult<Vec<QuiltPatchItem>> {
         use walrus_core::{
-            encoding::quilt_encoding::{QuiltIndexApi, QuiltPatchApi, QuiltPatchInternalIdApi},
+            encoding::quilt_encoding::{QuiltVersionV1, QuiltVersionV2},
             metadata::QuiltMetadata,
         };
 
         let metadata = self.quilt_client().get_quilt_metadata(quilt_id).await?;
 
         let patches = match metadata {
-            QuiltMetadata::V1(metadata_v1) => metadata_v1
-                .index
-                .patches()
-                .iter()
-                .map(|patch| {
-                    let patch_id =
-                        QuiltPatchId::new(*quilt_id, patch.quilt_patch_internal_id().to_bytes());
-                    QuiltPatchItem {
-                        identifier: patch.identifier().to_string(),
-                        patch_id,
-                        tags: patch.tags.clone(),
-                    }
-                })
-                .collect(),
+            QuiltMetadata::V1(metadata_v1) => {
+                quilt_patch_items::<QuiltVersionV1>(quilt_id, &metadata_v1.index)
+            }
+            QuiltMetadata::V2(metadata_v2) => {
+                quilt_patch_items::<QuiltVersionV2>(quilt_id, &metadata_v2.index)
+            }
         };
 
         Ok(patches)
     }
 }
 
-impl WalrusWriteClient for WalrusNodeClient<SuiContractClient> {
+/// Returns a [`QuiltPatchItem`] for each patch in the quilt index.
+fn quilt_patch_items<V: QuiltVersion>(
+    quilt_id: &BlobId,
+    quilt_index: &V::QuiltIndex,
+) -> Vec<QuiltPatchItem> {
+    use walrus_core::encoding::quilt_encoding::{
+        QuiltIndexApi,
+        QuiltPatchApi,
+        QuiltPatchInternalIdApi,
+    };
+
+    quilt_index
+        .patches()
+        .iter()
+        .map(|patch| QuiltPatchItem {
+            identifier: patch.identifier().to_string(),
+            patch_id: QuiltPatchId::new(*quilt_id, patch.quilt_patch_internal_id().to_bytes()),
+            tags: patch.tags().clone(),
+        })
+        .collect()
+}
+
+impl<C: WriteClient> WalrusWriteClient for WalrusNodeClient<C> {
     async fn write_blob(
         &self,
         blob: &[u8],
-        encoding_type: Option<EncodingType>,
-        epochs_ahead: EpochCount,
-        store_optimizations: StoreOptimizations,
-        persistence: BlobPersistence,
-        post_store: PostStoreAction,
+        params: WriteParams,
+        phase_notifier: Option<StorePhaseNotifier>,
     ) -> ClientResult<BlobStoreResult> {
-        let encoding_type = encoding_type.unwrap_or(DEFAULT_ENCODING);
-
-        let store_args = StoreArgs::new(
-            encoding_type,
-            epochs_ahead,
-            store_optimizations,
-            persistence,
-            post_store,
-        );
+        let mut store_args = params.store_args();
+        if let Some(phase_notifier) = phase_notifier {
+            store_args = store_args.with_phase_notifier(phase_notifier);
+        }
         let result = self
             .reserve_and_store_blobs_retry_committees(&[blob], &[], &store_args)
             .await?;
@@ -287,29 +342,20 @@ impl WalrusWriteClient for WalrusNodeClient<SuiContractClient> {
     async fn write_quilt<V: QuiltVersion>(
         &self,
         quilt: V::Quilt,
-        encoding_type: Option<EncodingType>,
-        epochs_ahead: EpochCount,
-        store_optimizations: StoreOptimizations,
-        persistence: BlobPersistence,
-        post_store: PostStoreAction,
+        params: WriteParams,
     ) -> ClientResult<QuiltStoreResult> {
-        let encoding_type = encoding_type.unwrap_or(DEFAULT_ENCODING);
-
-        let store_args = StoreArgs::new(
-            encoding_type,
-            epochs_ahead,
-            store_optimizations,
-            persistence,
-            post_store,
-        );
         self.quilt_client()
-            .reserve_and_store_quilt::<V>(&quilt, &store_args)
+            .reserve_and_store_quilt::<V>(&quilt, &params.store_args())
             .await
     }
 
     fn default_post_store_action(&self) -> PostStoreAction {
         PostStoreAction::Keep
     }
+
+    fn default_credits_policy(&self) -> CreditsPolicy {
+        CreditsPolicy::Auto
+    }
 }
 
 /// Configuration for the response headers of the aggregator.
@@ -342,12 +388,14 @@ impl<T: WalrusReadClient + Send + Sync + 'static> ClientDaemon<T> {
         registry: &Registry,
         allowed_headers: Vec<String>,
         allow_quilt_patch_tags_in_response: bool,
+        sites: Option<Arc<Sites>>,
     ) -> Self {
         Self::new::<AggregatorApiDoc>(client, network_address, registry).with_aggregator(
             AggregatorResponseHeaderConfig {
                 allowed_headers: allowed_headers.into_iter().collect(),
                 allow_quilt_patch_tags_in_response,
             },
+            sites,
         )
     }
 
@@ -369,7 +417,14 @@ impl<T: WalrusReadClient + Send + Sync + 'static> ClientDaemon<T> {
     }
 
     /// Specifies that the daemon should expose the aggregator interface (read blobs).
-    fn with_aggregator(mut self, response_header_config: AggregatorResponseHeaderConfig) -> Self {
+    ///
+    /// If `sites` are provided, the static websites are served on all paths that do not match an
+    /// aggregator endpoint.
+    fn with_aggregator(
+        mut self,
+        response_header_config: AggregatorResponseHeaderConfig,
+        sites: Option<Arc<Sites>>,
+    ) -> Self {
         self.response_header_config = Arc::new(response_header_config);
         tracing::info!(
             "Aggregator response header config: {:?}",
@@ -397,6 +452,11 @@ impl<T: WalrusReadClient + Send + Sync + 'static> ClientDaemon<T> {
                 LIST_PATCHES_IN_QUILT_ENDPOINT,
                 get(routes::list_patches_in_quilt).with_state(self.client.clone()),
             );
+        if let Some(sites) = sites {
+            self.router = self
+                .router
+                .fallback(get(sites::serve_site).with_state((self.client.clone(), sites)));
+        }
         self
     }
 
@@ -428,60 +488,70 @@ impl<T: WalrusReadClient + Send + Sync + 'static> ClientDaemon<T> {
     }
 }
 
+/// The optional services of the publisher, which are set up from the [`PublisherArgs`].
+#[derive(Debug, Default)]
+pub struct PublisherServices {
+    /// The JWT authentication of the store requests.
+    pub auth_config: Option<AuthConfig>,
+    /// The tenants to which the store requests are attributed.
+    pub tenants: Option<Arc<Tenants>>,
+    /// The manager of the asynchronous upload jobs.
+    pub jobs: Option<JobManager>,
+    /// The manager of the resumable uploads.
+    pub uploads: Option<UploadManager>,
+}
+
 impl<T: WalrusWriteClient + Send + Sync + 'static> ClientDaemon<T> {
     /// Constructs a new [`ClientDaemon`] with publisher functionality.
     pub fn new_publisher(
         client: T,
-        auth_config: Option<AuthConfig>,
+        services: PublisherServices,
         args: &PublisherArgs,
         registry: &Registry,
     ) -> Self {
         Self::new::<PublisherApiDoc>(client, args.daemon_args.bind_address, registry)
-            .with_publisher(
-                auth_config,
-                args.max_body_size(),
-                args.max_request_buffer_size,
-                args.max_concurrent_requests,
-                args.max_quilt_body_size(),
-            )
+            .with_publisher(services, args)
     }
 
     /// Constructs a new [`ClientDaemon`] with combined aggregator and publisher functionality.
     pub fn new_daemon(
         client: T,
-        auth_config: Option<AuthConfig>,
+        services: PublisherServices,
+        sites: Option<Arc<Sites>>,
         registry: &Registry,
         publisher_args: &PublisherArgs,
         aggregator_args: &AggregatorArgs,
     ) -> Self {
         Self::new::<DaemonApiDoc>(client, publisher_args.daemon_args.bind_address, registry)
-            .with_aggregator(AggregatorResponseHeaderConfig {
-                allowed_headers: aggregator_args
-                    .allowed_headers
-                    .clone()
-                    .into_iter()
-                    .collect(),
-                allow_quilt_patch_tags_in_response: aggregator_args
-                    .allow_quilt_patch_tags_in_response,
-            })
-            .with_publisher(
-                auth_config,
-                publisher_args.max_body_size_kib,
-                publisher_args.max_request_buffer_size,
-                publisher_args.max_concurrent_requests,
-                publisher_args.max_quilt_body_size(),
+            .with_aggregator(
+                AggregatorResponseHeaderConfig {
+                    allowed_headers: aggregator_args
+                        .allowed_headers
+                        .clone()
+                        .into_iter()
+                        .collect(),
+                    allow_quilt_patch_tags_in_response: aggregator_args
+                        .allow_quilt_patch_tags_in_response,
+                },
+                sites,
             )
+            .with_publisher(services, publisher_args)
     }
 
     /// Specifies that the daemon should expose the publisher interface (store blobs).
-    fn with_publisher(
-        mut self,
-        auth_config: Option<AuthConfig>,
-        max_body_limit: usize,
-        max_request_buffer_size: usize,
-        max_concurrent_requests: usize,
-        max_quilt_body_limit: usize,
-    ) -> Self {
+    ///
+    /// The request limits of the publisher are taken from the `args`.
+    fn with_publisher(mut self, services: PublisherServices, args: &PublisherArgs) -> Self {
+        let PublisherServices {
+            auth_config,
+            tenants,
+            jobs,
+            uploads,
+        } = services;
+        let max_body_limit = args.max_body_size();
+        let max_request_buffer_size = args.max_request_buffer_size;
+        let max_concurrent_requests = args.max_concurrent_requests;
+        let max_quilt_body_limit = args.max_quilt_body_size();
         tracing::debug!(
             %max_body_limit,
             %max_request_buffer_size,
@@ -489,50 +559,108 @@ impl<T: WalrusWriteClient + Send + Sync + 'static> ClientDaemon<T> {
             "configuring the publisher endpoint",
         );
 
-        let base_layers = ServiceBuilder::new()
+        let auth_config = auth_config.map(Arc::new);
+        let auth_layer = auth_config.clone().map(|auth_config| {
+            // Create and run the cache to track the used JWT tokens.
+            let replay_suppression_cache = auth_config.replay_suppression_config.build_and_run();
+            axum::middleware::from_fn_with_state(
+                (auth_config, Arc::new(replay_suppression_cache)),
+                auth_layer,
+            )
+        });
+        let identity_layer = auth_config
+            .map(|auth_config| axum::middleware::from_fn_with_state(auth_config, identity_layer));
+
+        let jobs = jobs.map(Arc::new);
+
+        if let Some(tenants) = tenants.as_ref().filter(|tenants| tenants.has_admin_token()) {
+            self.router = self.router.route(
+                TENANT_USAGE_ENDPOINT,
+                get(tenants::get_usage).with_state(tenants.clone()),
+            );
+        }
+        // The tenant layers must come after the authentication layers, as they rely on the verified
+        // JWT claim.
+        let tenant_identity_layer = tenants
+            .clone()
+            .map(|tenants| middleware::from_fn_with_state(tenants, tenant_identity_layer));
+        let tenant_layer =
+            tenants.map(|tenants| middleware::from_fn_with_state(tenants, tenant_layer));
+
+        let request_layers = ServiceBuilder::new()
             .layer(HandleErrorLayer::new(handle_publisher_error))
             .layer(LoadShedLayer::new())
             .layer(BufferLayer::new(max_request_buffer_size))
             .layer(ConcurrencyLimitLayer::new(max_concurrent_requests))
             .layer(DefaultBodyLimit::max(max_body_limit));
+        let publisher_layers = ServiceBuilder::new()
+            .option_layer(auth_layer)
+            .option_layer(tenant_layer)
+            .layer(request_layers.clone());
+        // Requests to existing jobs and uploads only identify the client, which must be the one
+        // that created the job or upload. They are neither checked against the store parameters
+        // nor admitted against the quotas of the tenant, which was done when creating the job or
+        // upload.
+        let follow_up_layers = ServiceBuilder::new()
+            .option_layer(identity_layer)
+            .option_layer(tenant_identity_layer)
+            .layer(request_layers);
+
+        if let Some(jobs) = jobs.as_ref() {
+            jobs.start(self.client.clone());
+            // Jobs are only served to the client that created them.
+            self.router = self.router.route(
+                JOB_GET_ENDPOINT,
+                get(routes::get_job)
+                    .route_layer(follow_up_layers.clone())
+                    .with_state(jobs.clone()),
+            );
+        }
 
-        if let Some(auth_config) = auth_config {
-            // Create and run the cache to track the used JWT tokens.
-            let replay_suppression_cache = auth_config.replay_suppression_config.build_and_run();
-
-            let auth_layers = ServiceBuilder::new()
-                .layer(axum::middleware::from_fn_with_state(
-                    (Arc::new(auth_config), Arc::new(replay_suppression_cache)),
-                    auth_layer,
-                ))
-                .layer(base_layers.clone());
-
+        if let Some(uploads) = uploads.map(Arc::new) {
+            uploads.start();
+            let upload_state = (self.client.clone(), jobs.clone(), uploads.clone());
+            // All requests to an upload are authenticated and attributed to a tenant, and the
+            // upload manager only serves them on behalf of the owner of the upload. The quotas of
+            // the tenant are checked when creating the upload and when storing the blob.
             self.router = self
                 .router
                 .route(
-                    BLOB_PUT_ENDPOINT,
-                    put(routes::put_blob).route_layer(auth_layers.clone()),
+                    UPLOADS_ENDPOINT,
+                    post(routes::create_upload)
+                        .route_layer(publisher_layers.clone())
+                        .with_state(upload_state.clone()),
                 )
                 .route(
-                    QUILT_PUT_ENDPOINT,
-                    put(routes::put_quilt)
-                        .route_layer(DefaultBodyLimit::max(max_quilt_body_limit))
-                        .route_layer(auth_layers),
-                );
-        } else {
-            self.router = self
-                .router
-                .route(
-                    BLOB_PUT_ENDPOINT,
-                    put(routes::put_blob).route_layer(base_layers.clone()),
+                    UPLOAD_ENDPOINT,
+                    get(routes::get_upload)
+                        .put(routes::put_upload_chunk)
+                        .delete(routes::delete_upload)
+                        .route_layer(follow_up_layers.clone())
+                        .with_state(uploads),
                 )
                 .route(
-                    QUILT_PUT_ENDPOINT,
-                    put(routes::put_quilt)
-                        .route_layer(DefaultBodyLimit::max(max_quilt_body_limit))
-                        .route_layer(base_layers),
+                    UPLOAD_FINALIZE_ENDPOINT,
+                    post(routes::finalize_upload)
+                        .route_layer(follow_up_layers)
+                        .with_state(upload_state),
                 );
         }
+
+        self.router = self
+            .router
+            .route(
+                BLOB_PUT_ENDPOINT,
+                put(routes::put_blob)
+                    .route_layer(publisher_layers.clone())
+                    .with_state((self.client.clone(), jobs)),
+            )
+            .route(
+                QUILT_PUT_ENDPOINT,
+                put(routes::put_quilt)
+                    .route_layer(DefaultBodyLimit::max(max_quilt_body_limit))
+                    .route_layer(publisher_layers),
+            );
         self
     }
 }
@@ -541,7 +669,7 @@ pub(crate) async fn auth_layer(
     State((auth_config, token_cache)): State<(Arc<AuthConfig>, Arc<CacheHandle<String>>)>,
     query: Query<PublisherQuery>,
     TypedHeader(bearer_header): TypedHeader<Authorization<Bearer>>,
-    request: Request,
+    mut request: Request,
     next: Next,
 ) -> Response {
     // Get a hint on the body size if possible.
@@ -550,18 +678,38 @@ pub(crate) async fn auth_layer(
     // Walrus.
     tracing::debug!(query = ?query.0, "authenticating a request to store a blob");
 
-    if let Err(resp) = verify_jwt_claim(
+    match verify_jwt_claim(
         query,
         bearer_header,
         &auth_config,
         token_cache.as_ref(),
-        request.body().size_hint(),
+        routes::blob_size_hint(&request),
     )
     .await
     {
-        resp
-    } else {
-        next.run(request).await
+        Ok(claim) => {
+            // Make the verified claim available to the subsequent layers (e.g., to identify the
+            // tenant through the subject).
+            request.extensions_mut().insert(claim);
+            next.run(request).await
+        }
+        Err(resp) => resp,
+    }
+}
+
+/// Identifies the client of a request to an existing upload or upload job through its JWT.
+pub(crate) async fn identity_layer(
+    State(auth_config): State<Arc<AuthConfig>>,
+    TypedHeader(bearer_header): TypedHeader<Authorization<Bearer>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    match verify_jwt_identity(bearer_header, &auth_config) {
+        Ok(claim) => {
+            request.extensions_mut().insert(claim);
+            next.run(request).await
+        }
+        Err(resp) => resp,
     }
 }
 
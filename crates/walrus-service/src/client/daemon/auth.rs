@@ -40,6 +40,13 @@ pub struct Claim {
     /// [rfc7519s4.1.7]: https://datatracker.ietf.org/doc/html/rfc7519#section-4.1.7
     pub jti: String,
 
+    /// The subject of the token, identifying the tenant on whose behalf the blob is stored.
+    ///
+    /// This is only used if the publisher is configured with tenants; see
+    /// [`TenantsConfig`][crate::client::daemon::tenants::TenantsConfig].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sub: Option<String>,
+
     /// The owner address of the sui blob object.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub send_object_to: Option<SuiAddress>,
@@ -268,34 +275,15 @@ impl Claim {
     }
 }
 
+/// Verifies the JWT in the bearer header against the query and returns the verified claim.
 pub async fn verify_jwt_claim(
     query: Query<PublisherQuery>,
     bearer: Authorization<Bearer>,
     auth_config: &AuthConfig,
     token_cache: &CacheHandle<String>,
     body_size_hint: http_body::SizeHint,
-) -> Result<(), Response<Body>> {
-    let mut validation = if auth_config.decoding_key.is_some() {
-        auth_config
-            .algorithm
-            .map(Validation::new)
-            .unwrap_or_default()
-    } else {
-        Validation::default()
-    };
-
-    let default_key = DecodingKey::from_secret(&[]);
-    let decode_key = auth_config.decoding_key.as_ref().unwrap_or_else(|| {
-        // No decoding key is provided in the configuration, so we disable signature validation.
-        validation.insecure_disable_signature_validation();
-        &default_key
-    });
-
-    if auth_config.expiring_sec > 0 {
-        validation.set_required_spec_claims(&["exp", "iat"]);
-    }
-
-    match Claim::from_token(bearer.token().trim(), decode_key, &validation) {
+) -> Result<Claim, Response<Body>> {
+    match decode_claim(&bearer, auth_config) {
         Ok(claim) => {
             // To avoid race conditions between store requests, we insert the token into the cache
             // now, and later remove it if the JWT verification fails.
@@ -326,13 +314,55 @@ pub async fn verify_jwt_claim(
                     });
                 Err(error.to_response())
             } else {
-                Ok(())
+                Ok(claim)
             }
         }
         Err(code) => Err(code.to_response()),
     }
 }
 
+/// Verifies the signature of the JWT in the bearer header and returns the claim.
+///
+/// This only identifies the client, e.g., for requests to a resumable upload or upload job that
+/// was created by a store request. In contrast to [`verify_jwt_claim`], the token is neither
+/// checked against the store parameters nor recorded to suppress replays, such that the same token
+/// can be used for all requests to the upload.
+pub fn verify_jwt_identity(
+    bearer: Authorization<Bearer>,
+    auth_config: &AuthConfig,
+) -> Result<Claim, Response<Body>> {
+    decode_claim(&bearer, auth_config).map_err(|error| error.to_response())
+}
+
+/// Decodes the claim of the JWT in the bearer header, verifying its signature if a decoding key is
+/// configured.
+fn decode_claim(
+    bearer: &Authorization<Bearer>,
+    auth_config: &AuthConfig,
+) -> Result<Claim, PublisherAuthError> {
+    let mut validation = if auth_config.decoding_key.is_some() {
+        auth_config
+            .algorithm
+            .map(Validation::new)
+            .unwrap_or_default()
+    } else {
+        Validation::default()
+    };
+
+    let default_key = DecodingKey::from_secret(&[]);
+    let decode_key = auth_config.decoding_key.as_ref().unwrap_or_else(|| {
+        // No decoding key is provided in the configuration, so we disable signature validation.
+        validation.insecure_disable_signature_validation();
+        &default_key
+    });
+
+    if auth_config.expiring_sec > 0 {
+        validation.set_required_spec_claims(&["exp", "iat"]);
+    }
+
+    Claim::from_token(bearer.token().trim(), decode_key, &validation)
+}
+
 /// Type representing the possible errors that can occur during the authentication process.
 #[derive(Debug, thiserror::Error, RestApiError)]
 #[rest_api_error(domain = PUBLISHER_AUTH_DOMAIN)]
@@ -411,7 +441,7 @@ mod tests {
     use super::*;
     use crate::client::{
         config::AuthConfig,
-        daemon::{auth_layer, cache::CacheConfig},
+        daemon::{auth_layer, cache::CacheConfig, identity_layer},
     };
 
     // Fixtures and helpers for tests.
@@ -657,6 +687,68 @@ mod tests {
         execute_requests(&router, requests).await;
     }
 
+    #[tokio::test]
+    async fn identity_layer_accepts_reused_tokens() {
+        let secret = Alphanumeric.sample_string(&mut rand::thread_rng(), 16);
+        let auth_config = auth_config_for_tests(Some(&secret), None, 0, true);
+        let router = Router::new().route(
+            "/v1/uploads/test",
+            get(|| async {}).route_layer(axum::middleware::from_fn_with_state(
+                Arc::new(auth_config),
+                identity_layer,
+            )),
+        );
+
+        // The store parameters in the claim do not need to match the query.
+        let claim = Claim {
+            jti: "test".to_string(),
+            exp: FAR_EXP,
+            sub: Some("subject".to_string()),
+            send_object_to: Some(SuiAddress::from_bytes(ADDRESS).expect("valid address")),
+            epochs: Some(1),
+            ..Default::default()
+        };
+        let token = encode(
+            &Header::default(),
+            &claim,
+            &EncodingKey::from_secret(secret.as_bytes()),
+        )
+        .unwrap();
+        let other_token = encode(
+            &Header::default(),
+            &claim,
+            &EncodingKey::from_secret(b"another secret"),
+        )
+        .unwrap();
+
+        let requests = vec![
+            (
+                RequestHeadersAndData::new(
+                    "/v1/uploads/test",
+                    correct_auth_header(token.clone()),
+                    None,
+                ),
+                StatusCode::OK,
+            ),
+            (
+                // The same token can be used again.
+                RequestHeadersAndData::new("/v1/uploads/test", correct_auth_header(token), None),
+                StatusCode::OK,
+            ),
+            (
+                // The signature is still checked.
+                RequestHeadersAndData::new(
+                    "/v1/uploads/test",
+                    correct_auth_header(other_token),
+                    None,
+                ),
+                StatusCode::UNAUTHORIZED,
+            ),
+        ];
+
+        execute_requests(&router, requests).await;
+    }
+
     #[tokio::test]
     async fn verify_upload_skip_check_signature() {
         let claim = Claim {
@@ -0,0 +1,687 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Asynchronous upload jobs of the publisher.
+//!
+//! When a blob is stored with `async=true`, the publisher persists the blob and the parameters of
+//! the store operation to the jobs directory, immediately responds with the ID of the new job, and
+//! stores the blob in the background. The status of the job can be polled by the client that
+//! created it, and an optional callback URL on one of the hosts allowed by the operator is notified
+//! once the job is finished. Jobs that were not finished when the publisher stopped are resumed
+//! when it restarts.
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use anyhow::Context;
+use axum::body::Bytes;
+use chrono::{DateTime, Utc};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use serde_with::{DurationSeconds, serde_as};
+use tokio::sync::{Semaphore, mpsc};
+use utoipa::ToSchema;
+use uuid::Uuid;
+use walrus_core::{EncodingType, EpochCount};
+use walrus_sdk::{
+    client::{StorePhase, responses::BlobStoreResult},
+    store_optimizations::StoreOptimizations,
+};
+use walrus_sui::client::{BlobPersistence, CreditsPolicy, PostStoreAction, with_gas_accounting};
+
+use super::{
+    WalrusWriteClient,
+    WriteParams,
+    tenants::{TenantContext, TenantUsage, Tenants},
+    uploads::UploadOwner,
+};
+
+/// The interval at which finished jobs are checked for expiration.
+const PRUNING_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// The timeout for the requests to the callback URLs.
+const CALLBACK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The number of attempts to notify a callback URL.
+const CALLBACK_ATTEMPTS: u32 = 3;
+
+/// The configuration of the asynchronous upload jobs.
+#[serde_as]
+#[derive(Debug, Clone, clap::Parser, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+#[command(rename_all = "kebab-case")]
+#[serde(rename_all = "camelCase")]
+pub struct JobsConfig {
+    /// The directory in which asynchronous upload jobs are persisted.
+    ///
+    /// If not set, asynchronous uploads (`async=true`) are disabled.
+    #[arg(long)]
+    #[serde(deserialize_with = "walrus_utils::config::resolve_home_dir_option")]
+    pub jobs_dir: Option<PathBuf>,
+    /// The maximum number of asynchronous upload jobs that are processed concurrently.
+    ///
+    /// Additional jobs are queued until a slot becomes available.
+    #[arg(long, default_value_t = default::max_concurrent_jobs())]
+    pub max_concurrent_jobs: usize,
+    /// The duration for which finished jobs can still be queried.
+    #[serde(rename = "job_retention_secs")]
+    #[arg(long, value_parser = humantime::parse_duration, default_value = "24h")]
+    #[serde_as(as = "DurationSeconds")]
+    pub job_retention: Duration,
+    /// The hosts to which the callbacks of upload jobs may be sent.
+    ///
+    /// Requests with a callback URL on any other host are rejected. If empty, callbacks are
+    /// disabled.
+    #[arg(long, num_args = 1..)]
+    pub job_callback_allowed_hosts: Vec<String>,
+}
+
+impl Default for JobsConfig {
+    fn default() -> Self {
+        Self {
+            jobs_dir: None,
+            max_concurrent_jobs: default::max_concurrent_jobs(),
+            job_retention: default::job_retention(),
+            job_callback_allowed_hosts: vec![],
+        }
+    }
+}
+
+mod default {
+    use std::time::Duration;
+
+    pub(crate) fn max_concurrent_jobs() -> usize {
+        8
+    }
+
+    pub(crate) fn job_retention() -> Duration {
+        Duration::from_secs(24 * 60 * 60)
+    }
+}
+
+/// The status of an asynchronous upload job.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum JobStatus {
+    /// The job is waiting to be processed.
+    Queued,
+    /// The blob is being encoded.
+    Encoding,
+    /// The status of the blob is checked and its storage resources are registered.
+    Registering,
+    /// The slivers are being uploaded to the storage nodes.
+    UploadingSlivers,
+    /// The blob is being certified on Sui.
+    Certifying,
+    /// The blob was stored successfully.
+    Done,
+    /// The job failed; the error is reported with the job.
+    Failed,
+}
+
+impl JobStatus {
+    /// Returns true if the job is finished, either successfully or not.
+    pub fn is_finished(&self) -> bool {
+        matches!(self, Self::Done | Self::Failed)
+    }
+}
+
+impl From<StorePhase> for JobStatus {
+    fn from(phase: StorePhase) -> Self {
+        match phase {
+            StorePhase::Encoding => Self::Encoding,
+            StorePhase::Registering => Self::Registering,
+            StorePhase::UploadingSlivers => Self::UploadingSlivers,
+            StorePhase::Certifying => Self::Certifying,
+        }
+    }
+}
+
+/// The information on an asynchronous upload job.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct JobInfo {
+    /// The ID of the job.
+    pub job_id: String,
+    /// The current status of the job.
+    pub status: JobStatus,
+    /// The time at which the job was created.
+    #[schema(value_type = String, format = DateTime)]
+    pub created_at: DateTime<Utc>,
+    /// The time at which the status of the job was last updated.
+    #[schema(value_type = String, format = DateTime)]
+    pub updated_at: DateTime<Utc>,
+    /// The result of storing the blob, once the job is done.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<BlobStoreResult>,
+    /// The error that caused the job to fail.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// The access token of the job, if it was created by an anonymous client.
+    ///
+    /// The token must be provided in the `X-Walrus-Access-Token` header when querying the job. It
+    /// is not sent to the callback URL.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub access_token: Option<String>,
+}
+
+/// The parameters of a store operation requested by a client.
+///
+/// These are persisted with the jobs and resumable uploads, to store the blob later.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct StoreParameters {
+    pub encoding_type: Option<EncodingType>,
+    pub epochs: EpochCount,
+    pub persistence: BlobPersistence,
+    pub check_status: bool,
+    pub post_store: PostStoreAction,
+    pub credits_policy: CreditsPolicy,
+}
+
+impl StoreParameters {
+    /// Returns the [`WriteParams`] to pass to the write client for the store operation.
+    pub(crate) fn write_params(&self) -> WriteParams {
+        WriteParams {
+            encoding_type: self.encoding_type,
+            epochs_ahead: self.epochs,
+            store_optimizations: StoreOptimizations::none().with_check_status(self.check_status),
+            persistence: self.persistence,
+            post_store: self.post_store,
+            credits_policy: self.credits_policy,
+        }
+    }
+}
+
+/// A job, as persisted in the jobs directory.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JobRecord {
+    info: JobInfo,
+    parameters: StoreParameters,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    callback_url: Option<String>,
+    /// The owner of the job, to whose tenant the usage of the job is attributed.
+    #[serde(flatten)]
+    owner: UploadOwner,
+}
+
+impl JobRecord {
+    /// Returns the information on the job served to its owner, including the access token.
+    fn info_for_owner(&self) -> JobInfo {
+        JobInfo {
+            access_token: self.owner.access_token.clone(),
+            ..self.info.clone()
+        }
+    }
+}
+
+/// Manages the asynchronous upload jobs of the publisher.
+#[derive(Debug)]
+pub struct JobManager {
+    jobs_dir: PathBuf,
+    jobs: Mutex<HashMap<String, JobRecord>>,
+    job_slots: Arc<Semaphore>,
+    job_retention: Duration,
+    callback_allowed_hosts: Vec<String>,
+    tenants: Option<Arc<Tenants>>,
+    http_client: reqwest::Client,
+}
+
+impl JobManager {
+    /// Opens the job manager, loading the jobs persisted in the jobs directory.
+    ///
+    /// Returns `None` if no jobs directory is configured.
+    pub fn open(
+        config: &JobsConfig,
+        tenants: Option<Arc<Tenants>>,
+    ) -> anyhow::Result<Option<Self>> {
+        let Some(jobs_dir) = config.jobs_dir.clone() else {
+            return Ok(None);
+        };
+        std::fs::create_dir_all(&jobs_dir).with_context(|| {
+            format!("unable to create the jobs directory {}", jobs_dir.display())
+        })?;
+
+        let mut jobs = HashMap::new();
+        for entry in std::fs::read_dir(&jobs_dir)? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|extension| extension != "json") {
+                continue;
+            }
+            match load_record(&path) {
+                Ok(record) => {
+                    jobs.insert(record.info.job_id.clone(), record);
+                }
+                Err(error) => {
+                    tracing::warn!(?error, path = %path.display(), "ignoring invalid job record")
+                }
+            }
+        }
+        tracing::info!(
+            n_jobs = jobs.len(),
+            jobs_dir = %jobs_dir.display(),
+            "loaded the asynchronous upload jobs"
+        );
+
+        Ok(Some(Self {
+            jobs_dir,
+            jobs: Mutex::new(jobs),
+            job_slots: Arc::new(Semaphore::new(config.max_concurrent_jobs)),
+            job_retention: config.job_retention,
+            callback_allowed_hosts: config
+                .job_callback_allowed_hosts
+                .iter()
+                .map(|host| host.to_lowercase())
+                .collect(),
+            tenants,
+            http_client: reqwest::Client::builder()
+                .timeout(CALLBACK_TIMEOUT)
+                // Redirects could lead the callbacks to hosts that are not allowed.
+                .redirect(reqwest::redirect::Policy::none())
+                .build()
+                .context("unable to build the HTTP client for the job callbacks")?,
+        }))
+    }
+
+    /// Resumes the unfinished jobs and starts the periodic pruning of expired jobs.
+    pub(crate) fn start<T: WalrusWriteClient + Send + Sync + 'static>(
+        self: &Arc<Self>,
+        client: Arc<T>,
+    ) {
+        let unfinished = self
+            .lock_jobs()
+            .values()
+            .filter(|record| !record.info.status.is_finished())
+            .map(|record| record.info.job_id.clone())
+            .collect::<Vec<_>>();
+        for job_id in unfinished {
+            tracing::info!(%job_id, "resuming unfinished job");
+            let this = self.clone();
+            let client = client.clone();
+            tokio::spawn(async move {
+                this.update_status(&job_id, |info| info.status = JobStatus::Queued)
+                    .await;
+                this.run(client, job_id, None).await
+            });
+        }
+
+        let this = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(PRUNING_INTERVAL);
+            loop {
+                interval.tick().await;
+                this.prune_expired().await;
+            }
+        });
+    }
+
+    /// Returns true if the callbacks of jobs may be sent to the given URL.
+    pub(crate) fn is_allowed_callback(&self, callback_url: &Url) -> bool {
+        callback_url.host_str().is_some_and(|host| {
+            let host = host.to_lowercase();
+            self.callback_allowed_hosts.contains(&host)
+        })
+    }
+
+    /// Persists a new job for the blob and starts processing it in the background.
+    ///
+    /// The tenant context of the request, if any, is kept by the job until it is finished.
+    pub(crate) async fn submit<T: WalrusWriteClient + Send + Sync + 'static>(
+        self: &Arc<Self>,
+        client: Arc<T>,
+        blob: Bytes,
+        parameters: StoreParameters,
+        callback_url: Option<Url>,
+        owner: UploadOwner,
+        tenant: Option<TenantContext>,
+    ) -> anyhow::Result<JobInfo> {
+        let now = Utc::now();
+        let job_id = Uuid::now_v7().to_string();
+        let record = JobRecord {
+            info: JobInfo {
+                job_id: job_id.clone(),
+                status: JobStatus::Queued,
+                created_at: now,
+                updated_at: now,
+                result: None,
+                error: None,
+                access_token: None,
+            },
+            parameters,
+            callback_url: callback_url.map(String::from),
+            owner,
+        };
+
+        tokio::fs::write(self.blob_path(&job_id), &blob)
+            .await
+            .context("unable to persist the blob of the job")?;
+        self.persist(&record).await?;
+        let info = record.info_for_owner();
+        self.lock_jobs().insert(job_id.clone(), record);
+
+        tracing::debug!(%job_id, size = blob.len(), "submitted asynchronous upload job");
+        tokio::spawn(self.clone().run(client, job_id, tenant));
+        Ok(info)
+    }
+
+    /// Returns the information on the job, if it exists and is owned by `owner`.
+    pub(crate) fn get(&self, job_id: &str, owner: &UploadOwner) -> Option<JobInfo> {
+        self.lock_jobs()
+            .get(job_id)
+            .filter(|record| &record.owner == owner)
+            .map(JobRecord::info_for_owner)
+    }
+
+    /// Stores the blob of the job.
+    ///
+    /// If the job is resumed after a restart, `tenant` is `None` and the context of the tenant is
+    /// obtained from the owner of the job.
+    async fn run<T: WalrusWriteClient + Send + Sync + 'static>(
+        self: Arc<Self>,
+        client: Arc<T>,
+        job_id: String,
+        tenant: Option<TenantContext>,
+    ) {
+        let _permit = self
+            .job_slots
+            .acquire()
+            .await
+            .expect("the semaphore is never closed");
+
+        let Some((parameters, tenant_name)) = self
+            .lock_jobs()
+            .get(&job_id)
+            .map(|record| (record.parameters.clone(), record.owner.tenant.clone()))
+        else {
+            return;
+        };
+
+        let blob = match tokio::fs::read(self.blob_path(&job_id)).await {
+            Ok(blob) => blob,
+            Err(error) => {
+                self.finish(
+                    &job_id,
+                    Err(format!("the blob of the job is lost: {error}")),
+                )
+                .await;
+                return;
+            }
+        };
+
+        let tenant = tenant.or_else(|| {
+            let tenants = self.tenants.as_ref()?;
+            tenants.context(tenant_name.as_deref()?)
+        });
+        if let Some(tenant) = tenant.as_ref()
+            && let Err(error) = tenant.reserve_bytes(blob.len() as u64)
+        {
+            self.finish(&job_id, Err(error.to_string())).await;
+            return;
+        }
+
+        let (phase_tx, mut phase_rx) = mpsc::unbounded_channel();
+        let store = with_gas_accounting(client.write_blob(
+            &blob,
+            parameters.write_params(),
+            Some(phase_tx),
+        ));
+        tokio::pin!(store);
+
+        let (result, sui_spent) = loop {
+            tokio::select! {
+                output = &mut store => break output,
+                Some(phase) = phase_rx.recv() => {
+                    self.update_status(&job_id, |info| info.status = phase.into()).await;
+                }
+            }
+        };
+
+        let result = match result {
+            Ok(BlobStoreResult::MarkedInvalid { .. }) => Err(
+                "the blob was marked invalid, which is likely a system error, please report it"
+                    .to_owned(),
+            ),
+            Ok(result) => {
+                if let Some(tenant) = tenant.as_ref() {
+                    tenant.record_usage(&TenantUsage::from_upload(blob.len(), &result, sui_spent));
+                }
+                Ok(result)
+            }
+            Err(error) => {
+                tracing::warn!(?error, %job_id, "asynchronous upload job failed");
+                Err(error.to_string())
+            }
+        };
+        self.finish(&job_id, result).await;
+    }
+
+    /// Records the final outcome of the job, removes its blob, and notifies the callback URL.
+    async fn finish(&self, job_id: &str, result: Result<BlobStoreResult, String>) {
+        let info = self
+            .update_status(job_id, |info| match result {
+                Ok(result) => {
+                    info.status = JobStatus::Done;
+                    info.result = Some(result);
+                }
+                Err(error) => {
+                    info.status = JobStatus::Failed;
+                    info.error = Some(error);
+                }
+            })
+            .await;
+        if let Err(error) = tokio::fs::remove_file(self.blob_path(job_id)).await {
+            tracing::debug!(?error, %job_id, "unable to remove the blob of the job");
+        }
+
+        let callback_url = self
+            .lock_jobs()
+            .get(job_id)
+            .and_then(|record| record.callback_url.clone());
+        if let (Some(info), Some(callback_url)) = (info, callback_url) {
+            self.notify_callback(&callback_url, &info).await;
+        }
+    }
+
+    async fn notify_callback(&self, callback_url: &str, info: &JobInfo) {
+        for attempt in 1..=CALLBACK_ATTEMPTS {
+            match self
+                .http_client
+                .post(callback_url)
+                .json(info)
+                .send()
+                .await
+                .and_then(|response| response.error_for_status())
+            {
+                Ok(_) => return,
+                Err(error) => {
+                    tracing::warn!(
+                        ?error,
+                        job_id = %info.job_id,
+                        attempt,
+                        "failed to notify the callback URL of the job"
+                    );
+                    if attempt < CALLBACK_ATTEMPTS {
+                        tokio::time::sleep(Duration::from_secs(1 << attempt)).await;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Updates the job and persists it, returning the updated information.
+    ///
+    /// The status of a job is only updated by the task running it, so the updates of a job are
+    /// persisted in order.
+    async fn update_status(
+        &self,
+        job_id: &str,
+        update: impl FnOnce(&mut JobInfo),
+    ) -> Option<JobInfo> {
+        let record = {
+            let mut jobs = self.lock_jobs();
+            let record = jobs.get_mut(job_id)?;
+            update(&mut record.info);
+            record.info.updated_at = Utc::now();
+            record.clone()
+        };
+        if let Err(error) = self.persist(&record).await {
+            tracing::error!(?error, %job_id, "failed to persist the job");
+        }
+        Some(record.info)
+    }
+
+    /// Removes the finished jobs that are older than the retention period.
+    async fn prune_expired(&self) {
+        let Ok(retention) = chrono::Duration::from_std(self.job_retention) else {
+            return;
+        };
+        let expiration = Utc::now() - retention;
+        let mut expired_jobs = vec![];
+        self.lock_jobs().retain(|job_id, record| {
+            let expired = record.info.status.is_finished() && record.info.updated_at < expiration;
+            if expired {
+                expired_jobs.push(job_id.clone());
+            }
+            !expired
+        });
+        for job_id in expired_jobs {
+            tracing::debug!(%job_id, "removing expired job");
+            let _ = tokio::fs::remove_file(self.record_path(&job_id)).await;
+        }
+    }
+
+    async fn persist(&self, record: &JobRecord) -> anyhow::Result<()> {
+        let path = self.record_path(&record.info.job_id);
+        let tmp_path = path.with_extension("json.tmp");
+        tokio::fs::write(&tmp_path, serde_json::to_vec(record)?).await?;
+        tokio::fs::rename(tmp_path, path).await?;
+        Ok(())
+    }
+
+    fn lock_jobs(&self) -> std::sync::MutexGuard<'_, HashMap<String, JobRecord>> {
+        self.jobs.lock().expect("the lock is never poisoned")
+    }
+
+    fn record_path(&self, job_id: &str) -> PathBuf {
+        self.jobs_dir.join(format!("{job_id}.json"))
+    }
+
+    fn blob_path(&self, job_id: &str) -> PathBuf {
+        self.jobs_dir.join(format!("{job_id}.blob"))
+    }
+}
+
+fn load_record(path: &Path) -> anyhow::Result<JobRecord> {
+    Ok(serde_json::from_slice(&std::fs::read(path)?)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(jobs_dir: &Path) -> JobsConfig {
+        JobsConfig {
+            jobs_dir: Some(jobs_dir.to_owned()),
+            ..Default::default()
+        }
+    }
+
+    fn record(job_id: &str, status: JobStatus, updated_at: DateTime<Utc>) -> JobRecord {
+        JobRecord {
+            info: JobInfo {
+                job_id: job_id.to_owned(),
+                status,
+                created_at: updated_at,
+                updated_at,
+                result: None,
+                error: None,
+                access_token: None,
+            },
+            parameters: StoreParameters {
+                encoding_type: None,
+                epochs: 1,
+                persistence: BlobPersistence::Deletable,
+                check_status: true,
+                post_store: PostStoreAction::Keep,
+                credits_policy: CreditsPolicy::Auto,
+            },
+            callback_url: None,
+            owner: UploadOwner::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn jobs_are_reloaded_and_expired_jobs_pruned() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let old = Utc::now() - chrono::Duration::days(2);
+        {
+            let manager = JobManager::open(&config(dir.path()), None)?.expect("jobs are enabled");
+            for record in [
+                record("pending", JobStatus::UploadingSlivers, old),
+                record("expired", JobStatus::Done, old),
+                record("recent", JobStatus::Failed, Utc::now()),
+            ] {
+                manager.persist(&record).await?;
+            }
+        }
+
+        let manager = JobManager::open(&config(dir.path()), None)?.expect("jobs are enabled");
+        let owner = UploadOwner::default();
+        assert_eq!(
+            manager.get("pending", &owner).map(|info| info.status),
+            Some(JobStatus::UploadingSlivers)
+        );
+        manager.prune_expired().await;
+        assert!(manager.get("expired", &owner).is_none());
+        assert!(!dir.path().join("expired.json").exists());
+        assert!(manager.get("recent", &owner).is_some());
+        assert!(manager.get("pending", &owner).is_some());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn jobs_are_only_served_to_their_owner() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let manager = JobManager::open(&config(dir.path()), None)?.expect("jobs are enabled");
+        let mut job = record("job", JobStatus::Queued, Utc::now());
+        job.owner = UploadOwner {
+            tenant: Some("alice".to_owned()),
+            ..Default::default()
+        };
+        manager.persist(&job).await?;
+
+        let manager = JobManager::open(&config(dir.path()), None)?.expect("jobs are enabled");
+        assert!(manager.get("job", &job.owner).is_some());
+        assert!(manager.get("job", &UploadOwner::default()).is_none());
+
+        let mut anonymous = record("anonymous", JobStatus::Queued, Utc::now());
+        anonymous.owner = UploadOwner::default().with_new_access_token();
+        manager.persist(&anonymous).await?;
+        let manager = JobManager::open(&config(dir.path()), None)?.expect("jobs are enabled");
+        let info = manager
+            .get("anonymous", &anonymous.owner)
+            .expect("the job is served to its owner");
+        assert_eq!(info.access_token, anonymous.owner.access_token);
+        assert!(manager.get("anonymous", &UploadOwner::default()).is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn only_allowed_callback_hosts_are_accepted() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let config = JobsConfig {
+            job_callback_allowed_hosts: vec!["Hooks.example.com".to_owned()],
+            ..config(dir.path())
+        };
+        let manager = JobManager::open(&config, None)?.expect("jobs are enabled");
+        assert!(manager.is_allowed_callback(&Url::parse("https://hooks.example.com/done")?));
+        assert!(!manager.is_allowed_callback(&Url::parse("http://169.254.169.254/")?));
+        assert!(!manager.is_allowed_callback(&Url::parse("https://example.com/done")?));
+        Ok(())
+    }
+}
@@ -35,7 +35,16 @@ pub(super) struct AggregatorApiDoc;
 #[derive(OpenApi)]
 #[openapi(
     info(title = "Walrus Publisher"),
-    paths(routes::put_blob, routes::put_quilt),
+    paths(
+        routes::put_blob,
+        routes::put_quilt,
+        routes::get_job,
+        routes::create_upload,
+        routes::get_upload,
+        routes::put_upload_chunk,
+        routes::delete_upload,
+        routes::finalize_upload,
+    ),
     components(schemas(
         Blob,
         BlobId,
@@ -61,6 +70,12 @@ pub(super) struct PublisherApiDoc;
         routes::get_blob,
         routes::put_blob,
         routes::put_quilt,
+        routes::get_job,
+        routes::create_upload,
+        routes::get_upload,
+        routes::put_upload_chunk,
+        routes::delete_upload,
+        routes::finalize_upload,
         routes::get_blob_by_object_id,
         routes::get_blob_by_quilt_patch_id,
         routes::get_blob_by_quilt_id_and_identifier,
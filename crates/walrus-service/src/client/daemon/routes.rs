@@ -11,9 +11,9 @@ use std::{
 use anyhow::anyhow;
 use axum::{
     Json,
-    body::Bytes,
-    extract::{Path, Query, State},
-    http::{HeaderMap, HeaderName, HeaderValue, StatusCode},
+    body::{Bytes, HttpBody},
+    extract::{Extension, Path, Query, Request, State},
+    http::{HeaderMap, HeaderName, HeaderValue, Method, StatusCode},
     response::{IntoResponse, Response},
 };
 use axum_extra::{
@@ -21,8 +21,21 @@ use axum_extra::{
     extract::Multipart,
     headers::{Authorization, authorization::Bearer},
 };
+use http_body::SizeHint;
 use jsonwebtoken::{DecodingKey, Validation};
-use reqwest::header::{CACHE_CONTROL, CONTENT_TYPE, ETAG, X_CONTENT_TYPE_OPTIONS};
+use reqwest::{
+    Url,
+    header::{
+        ACCEPT_ENCODING,
+        CACHE_CONTROL,
+        CONTENT_ENCODING,
+        CONTENT_TYPE,
+        ETAG,
+        LOCATION,
+        VARY,
+        X_CONTENT_TYPE_OPTIONS,
+    },
+};
 use serde::{Deserialize, Serialize};
 use serde_with::{DisplayFromStr, serde_as};
 use sui_types::base_types::{ObjectID, SuiAddress};
@@ -36,7 +49,16 @@ use walrus_core::{
     QuiltPatchId,
     encoding::{
         QuiltError,
-        quilt_encoding::{QuiltApi, QuiltStoreBlob, QuiltVersionEnum, QuiltVersionV1},
+        quilt_encoding::{
+            QuiltApi,
+            QuiltPatchCompression,
+            QuiltPatchFilter,
+            QuiltStoreBlob,
+            QuiltVersion,
+            QuiltVersionEnum,
+            QuiltVersionV1,
+            QuiltVersionV2,
+        },
     },
 };
 use walrus_proc_macros::RestApiError;
@@ -49,15 +71,18 @@ use walrus_storage_node_client::api::errors::DAEMON_ERROR_DOMAIN as ERROR_DOMAIN
 use walrus_sui::{
     ObjectIdSchema,
     SuiAddressSchema,
-    client::{BlobPersistence, InvalidBlobPersistenceError},
+    client::{BlobPersistence, InvalidBlobPersistenceError, with_gas_accounting},
     types::move_structs::{BlobAttribute, BlobWithAttribute},
 };
 
-use super::{AggregatorResponseHeaderConfig, WalrusReadClient, WalrusWriteClient};
+use super::{AggregatorResponseHeaderConfig, WalrusReadClient, WalrusWriteClient, WriteParams};
 use crate::{
     client::daemon::{
         PostStoreAction,
         auth::{Claim, PublisherAuthError},
+        jobs::{JobInfo, JobManager, StoreParameters},
+        tenants::{TenantContext, TenantUsage},
+        uploads::{UploadError, UploadInfo, UploadManager, UploadOwner},
     },
     common::api::{Binary, BlobIdString, QuiltPatchIdString, RestApiError},
 };
@@ -74,6 +99,14 @@ pub const BLOB_OBJECT_GET_ENDPOINT: &str = "/v1/blobs/by-object-id/{blob_object_
 pub const BLOB_PUT_ENDPOINT: &str = "/v1/blobs";
 /// The path to store multiple files as a quilt using multipart/form-data.
 pub const QUILT_PUT_ENDPOINT: &str = "/v1/quilts";
+/// The path to get the status of an asynchronous upload job.
+pub const JOB_GET_ENDPOINT: &str = "/v1/jobs/{job_id}";
+/// The path to create a resumable upload.
+pub const UPLOADS_ENDPOINT: &str = "/v1/uploads";
+/// The path to get the status of, upload chunks to, and cancel a resumable upload.
+pub const UPLOAD_ENDPOINT: &str = "/v1/uploads/{upload_id}";
+/// The path to finalize a resumable upload.
+pub const UPLOAD_FINALIZE_ENDPOINT: &str = "/v1/uploads/{upload_id}/finalize";
 /// The path to get blobs from quilt by IDs.
 pub const QUILT_PATCH_BY_ID_GET_ENDPOINT: &str = "/v1/blobs/by-quilt-patch-id/{quilt_patch_id}";
 /// The path to get blob from quilt by quilt ID and identifier.
@@ -85,6 +118,15 @@ pub const LIST_PATCHES_IN_QUILT_ENDPOINT: &str = "/v1/quilts/{quilt_id}/patches"
 const X_QUILT_PATCH_IDENTIFIER: &str = "X-Quilt-Patch-Identifier";
 
 const WALRUS_NATIVE_METADATA_FIELD_NAME: &str = "_metadata";
+/// Header specifying the total length of a resumable upload.
+const UPLOAD_LENGTH_HEADER: &str = "upload-length";
+/// Header specifying the offset of a chunk of a resumable upload.
+const UPLOAD_OFFSET_HEADER: &str = "upload-offset";
+/// Header specifying the access token of an upload or upload job created by an anonymous client.
+const ACCESS_TOKEN_HEADER: &str = "x-walrus-access-token";
+
+/// The state of the endpoints for resumable uploads that store blobs.
+pub(super) type UploadState<T> = (Arc<T>, Option<Arc<JobManager>>, Arc<UploadManager>);
 
 /// Retrieve a Walrus blob.
 ///
@@ -269,6 +311,16 @@ pub(crate) enum GetBlobError {
     Internal(#[from] anyhow::Error),
 }
 
+/// The query to select quilt patches is invalid.
+#[derive(Debug, thiserror::Error, RestApiError)]
+#[error(transparent)]
+#[rest_api_error(
+    domain = ERROR_DOMAIN,
+    reason = "INVALID_QUILT_PATCH_QUERY",
+    status = ApiStatusCode::InvalidArgument
+)]
+pub(crate) struct InvalidQuiltPatchQueryError(#[from] QuiltError);
+
 impl From<ClientError> for GetBlobError {
     fn from(error: ClientError) -> Self {
         match error.kind() {
@@ -286,6 +338,9 @@ impl From<ClientError> for GetBlobError {
 ///
 /// Store a (potentially deletable) blob on Walrus for 1 or more epochs. The associated on-Sui
 /// object can be sent to a specified Sui address.
+///
+/// If `async` is true, the publisher responds immediately with an upload job, whose status can
+/// be retrieved while the blob is stored in the background.
 #[tracing::instrument(level = Level::ERROR, skip_all, fields(epochs=%query.epochs))]
 #[utoipa::path(
     put,
@@ -297,15 +352,18 @@ impl From<ClientError> for GetBlobError {
     params(PublisherQuery),
     responses(
         (status = 200, description = "The blob was stored successfully", body = BlobStoreResult),
+        (status = 202, description = "The upload job was created", body = JobInfo),
         (status = 400, description = "The request is malformed"),
         (status = 413, description = "The blob is too large"),
         StoreBlobError,
     ),
 )]
-pub(super) async fn put_blob<T: WalrusWriteClient>(
-    State(client): State<Arc<T>>,
+pub(super) async fn put_blob<T: WalrusWriteClient + Send + Sync + 'static>(
+    State((client, jobs)): State<(Arc<T>, Option<Arc<JobManager>>)>,
     Query(query): Query<PublisherQuery>,
     bearer_header: Option<TypedHeader<Authorization<Bearer>>>,
+    tenant: Option<Extension<TenantContext>>,
+    claim: Option<Extension<Claim>>,
     blob: Bytes,
 ) -> Response {
     // Check if there is an authorization claim, and use it to check the size.
@@ -315,24 +373,64 @@ pub(super) async fn put_blob<T: WalrusWriteClient>(
         return error.into_response();
     }
 
-    let blob_persistence = match query.blob_persistence() {
-        Ok(blob_persistence) => blob_persistence,
+    let parameters = match query.store_parameters(client.as_ref()) {
+        Ok(parameters) => parameters,
         Err(error) => return error.into_response(),
     };
 
-    tracing::debug!("starting to store received blob");
-    match client
-        .write_blob(
-            &blob[..],
-            query.encoding_type,
-            query.epochs,
-            query.optimizations(),
-            blob_persistence,
-            query.post_store_action(client.default_post_store_action()),
+    // Reject uploads exceeding the daily quota of the tenant before accepting them.
+    if let Some(Extension(tenant)) = tenant.as_ref()
+        && let Err(error) = tenant.reserve_bytes(blob.len() as u64)
+    {
+        return error.into_response();
+    }
+
+    if query.run_async {
+        let callback_url = match query.callback_url(jobs.as_deref()) {
+            Ok(callback_url) => callback_url,
+            Err(error) => return error.into_response(),
+        };
+        let tenant = tenant.map(|Extension(tenant)| tenant);
+        let owner = UploadOwner::new(
+            tenant.as_ref(),
+            claim.as_ref().map(|Extension(claim)| claim),
+            None,
         )
-        .await
+        .with_new_access_token();
+        return submit_upload_job(client, jobs, parameters, callback_url, owner, tenant, blob)
+            .await;
+    }
+
+    tracing::debug!("starting to store received blob");
+    store_blob(
+        client.as_ref(),
+        parameters,
+        tenant.as_ref().map(|Extension(tenant)| tenant),
+        &blob,
+    )
+    .await
+}
+
+/// Stores the blob on Walrus and records the usage of the tenant, if any.
+async fn store_blob<T: WalrusWriteClient>(
+    client: &T,
+    parameters: StoreParameters,
+    tenant: Option<&TenantContext>,
+    blob: &[u8],
+) -> Response {
+    if let Some(tenant) = tenant
+        && let Err(error) = tenant.reserve_bytes(blob.len() as u64)
     {
+        return error.into_response();
+    }
+
+    let (result, sui_spent) =
+        with_gas_accounting(client.write_blob(blob, parameters.write_params(), None)).await;
+    match result {
         Ok(result) => {
+            if let Some(tenant) = tenant {
+                tenant.record_usage(&TenantUsage::from_upload(blob.len(), &result, sui_spent));
+            }
             if let BlobStoreResult::MarkedInvalid { .. } = result {
                 StoreBlobError::Internal(anyhow!(
                     "the blob was marked invalid, which is likely a system error, please report it"
@@ -349,6 +447,408 @@ pub(super) async fn put_blob<T: WalrusWriteClient>(
     }
 }
 
+/// The error returned if asynchronous uploads are requested but not enabled on this publisher.
+fn async_uploads_disabled() -> StoreBlobError {
+    StoreBlobError::MalformedRequest {
+        message: "asynchronous uploads are not enabled on this publisher".to_owned(),
+    }
+}
+
+/// Persists the blob as an asynchronous upload job and responds with the job information.
+async fn submit_upload_job<T: WalrusWriteClient + Send + Sync + 'static>(
+    client: Arc<T>,
+    jobs: Option<Arc<JobManager>>,
+    parameters: StoreParameters,
+    callback_url: Option<Url>,
+    owner: UploadOwner,
+    tenant: Option<TenantContext>,
+    blob: Bytes,
+) -> Response {
+    let Some(jobs) = jobs else {
+        return async_uploads_disabled().into_response();
+    };
+    match jobs
+        .submit(client, blob, parameters, callback_url, owner, tenant)
+        .await
+    {
+        Ok(job) => (StatusCode::ACCEPTED, Json(job)).into_response(),
+        Err(error) => {
+            tracing::error!(?error, "error submitting upload job");
+            StoreBlobError::Internal(error).into_response()
+        }
+    }
+}
+
+/// Get the status of an upload job.
+///
+/// Returns the status of an upload job created by storing a blob with `async=true`. Once the job
+/// is done, the result of storing the blob is included. Jobs are only returned to the client that
+/// created them; anonymous clients must provide the access token returned when creating the job.
+#[tracing::instrument(level = Level::ERROR, skip_all, fields(%job_id))]
+#[utoipa::path(
+    get,
+    path = JOB_GET_ENDPOINT,
+    params(
+        ("job_id" = String, Path, description = "The ID of the upload job."),
+        (
+            "X-Walrus-Access-Token" = Option<String>,
+            Header,
+            description = "The access token of a job created by an anonymous client.",
+        ),
+    ),
+    responses(
+        (status = 200, description = "The status of the upload job", body = JobInfo),
+        GetJobError,
+    ),
+)]
+pub(super) async fn get_job(
+    State(jobs): State<Arc<JobManager>>,
+    Path(job_id): Path<String>,
+    tenant: Option<Extension<TenantContext>>,
+    claim: Option<Extension<Claim>>,
+    headers: HeaderMap,
+) -> Response {
+    match jobs.get(&job_id, &upload_owner(tenant, claim, &headers)) {
+        Some(job) => (StatusCode::OK, Json(job)).into_response(),
+        None => GetJobError::JobNotFound.to_response(),
+    }
+}
+
+#[derive(Debug, thiserror::Error, RestApiError)]
+#[rest_api_error(domain = ERROR_DOMAIN)]
+pub(crate) enum GetJobError {
+    /// The requested upload job does not exist or has expired.
+    #[error("the requested upload job does not exist or has expired")]
+    #[rest_api_error(reason = "JOB_NOT_FOUND", status = ApiStatusCode::NotFound)]
+    JobNotFound,
+}
+
+/// Create a resumable upload.
+///
+/// Creates an upload for a blob whose total length in bytes is specified in the `Upload-Length`
+/// header. The blob can then be uploaded in chunks and, once all chunks are received, stored on
+/// Walrus by finalizing the upload. The blob is stored with the parameters specified in the query
+/// of this request.
+///
+/// If the client is anonymous, i.e., neither identified by a tenant nor by a JWT subject, the
+/// response contains an access token that must be provided in all further requests to the upload.
+#[tracing::instrument(level = Level::ERROR, skip_all, fields(epochs=%query.epochs))]
+#[utoipa::path(
+    post,
+    path = UPLOADS_ENDPOINT,
+    params(
+        PublisherQuery,
+        ("Upload-Length" = u64, Header, description = "The total length of the blob in bytes."),
+    ),
+    responses(
+        (status = 201, description = "The upload was created", body = UploadInfo),
+        UploadError,
+    ),
+)]
+pub(super) async fn create_upload<T: WalrusWriteClient + Send + Sync + 'static>(
+    State((client, jobs, uploads)): State<UploadState<T>>,
+    Query(query): Query<PublisherQuery>,
+    headers: HeaderMap,
+    bearer_header: Option<TypedHeader<Authorization<Bearer>>>,
+    tenant: Option<Extension<TenantContext>>,
+    claim: Option<Extension<Claim>>,
+) -> Response {
+    let length = match parse_upload_header(&headers, UPLOAD_LENGTH_HEADER) {
+        Ok(length) => length,
+        Err(error) => return error.into_response(),
+    };
+    // Check if there is an authorization claim, and use it to check the size.
+    if let Some(TypedHeader(header)) = bearer_header
+        && let Err(error) = check_blob_size(header, usize::try_from(length).unwrap_or(usize::MAX))
+    {
+        return error.into_response();
+    }
+
+    let parameters = match query.store_parameters(client.as_ref()) {
+        Ok(parameters) => parameters,
+        Err(error) => return error.into_response(),
+    };
+    if query.run_async && jobs.is_none() {
+        return async_uploads_disabled().into_response();
+    }
+    let callback_url = match query.callback_url(jobs.as_deref()) {
+        Ok(callback_url) => callback_url,
+        Err(error) => return error.into_response(),
+    };
+
+    let owner = upload_owner(tenant, claim, &headers).with_new_access_token();
+    match uploads
+        .create(length, parameters, query.run_async, callback_url, owner)
+        .await
+    {
+        Ok(upload) => {
+            let location = UPLOAD_ENDPOINT.replace("{upload_id}", &upload.upload_id);
+            let mut response = upload_response(StatusCode::CREATED, upload);
+            if let Ok(location) = HeaderValue::from_str(&location) {
+                response.headers_mut().insert(LOCATION, location);
+            }
+            response
+        }
+        Err(error) => {
+            tracing::debug!(?error, "unable to create upload");
+            error.into_response()
+        }
+    }
+}
+
+/// Get the status of a resumable upload.
+///
+/// Returns the total length of the blob and the ranges of bytes received so far, which allows
+/// resuming an interrupted upload. Similar to the tus protocol, the number of bytes received
+/// contiguously from the start of the blob is also returned in the `Upload-Offset` header.
+#[tracing::instrument(level = Level::ERROR, skip_all, fields(%upload_id))]
+#[utoipa::path(
+    get,
+    path = UPLOAD_ENDPOINT,
+    params(
+        ("upload_id" = String, Path, description = "The ID of the upload."),
+        (
+            "X-Walrus-Access-Token" = Option<String>,
+            Header,
+            description = "The access token of an upload created by an anonymous client.",
+        ),
+    ),
+    responses(
+        (status = 200, description = "The status of the upload", body = UploadInfo),
+        UploadError,
+    ),
+)]
+pub(super) async fn get_upload(
+    State(uploads): State<Arc<UploadManager>>,
+    Path(upload_id): Path<String>,
+    tenant: Option<Extension<TenantContext>>,
+    claim: Option<Extension<Claim>>,
+    headers: HeaderMap,
+) -> Response {
+    match uploads.get(&upload_id, &upload_owner(tenant, claim, &headers)) {
+        Some(upload) => upload_response(StatusCode::OK, upload),
+        None => UploadError::UploadNotFound.into_response(),
+    }
+}
+
+/// Upload a chunk of a resumable upload.
+///
+/// Writes the chunk at the offset of the blob specified in the `Upload-Offset` header. Chunks can
+/// be uploaded in any order, and a chunk can safely be uploaded again if the response to a
+/// previous attempt was not received.
+#[tracing::instrument(level = Level::ERROR, skip_all, fields(%upload_id))]
+#[utoipa::path(
+    put,
+    path = UPLOAD_ENDPOINT,
+    request_body(
+        content = Binary,
+        content_type = "application/octet-stream",
+        description = "Binary data of the chunk."),
+    params(
+        ("upload_id" = String, Path, description = "The ID of the upload."),
+        ("Upload-Offset" = u64, Header, description = "The offset of the chunk in the blob."),
+        (
+            "X-Walrus-Access-Token" = Option<String>,
+            Header,
+            description = "The access token of an upload created by an anonymous client.",
+        ),
+    ),
+    responses(
+        (status = 200, description = "The chunk was received", body = UploadInfo),
+        (status = 413, description = "The chunk is too large"),
+        UploadError,
+    ),
+)]
+pub(super) async fn put_upload_chunk(
+    State(uploads): State<Arc<UploadManager>>,
+    Path(upload_id): Path<String>,
+    tenant: Option<Extension<TenantContext>>,
+    claim: Option<Extension<Claim>>,
+    headers: HeaderMap,
+    chunk: Bytes,
+) -> Response {
+    let offset = match parse_upload_header(&headers, UPLOAD_OFFSET_HEADER) {
+        Ok(offset) => offset,
+        Err(error) => return error.into_response(),
+    };
+    tracing::debug!(offset, size = chunk.len(), "received chunk");
+    let owner = upload_owner(tenant, claim, &headers);
+    match uploads.write_chunk(&upload_id, &owner, offset, chunk).await {
+        Ok(upload) => upload_response(StatusCode::OK, upload),
+        Err(error) => {
+            tracing::debug!(?error, "unable to write chunk");
+            error.into_response()
+        }
+    }
+}
+
+/// Cancel a resumable upload.
+///
+/// Removes the upload and all data received for it.
+#[tracing::instrument(level = Level::ERROR, skip_all, fields(%upload_id))]
+#[utoipa::path(
+    delete,
+    path = UPLOAD_ENDPOINT,
+    params(
+        ("upload_id" = String, Path, description = "The ID of the upload."),
+        (
+            "X-Walrus-Access-Token" = Option<String>,
+            Header,
+            description = "The access token of an upload created by an anonymous client.",
+        ),
+    ),
+    responses(
+        (status = 204, description = "The upload was cancelled"),
+        UploadError,
+    ),
+)]
+pub(super) async fn delete_upload(
+    State(uploads): State<Arc<UploadManager>>,
+    Path(upload_id): Path<String>,
+    tenant: Option<Extension<TenantContext>>,
+    claim: Option<Extension<Claim>>,
+    headers: HeaderMap,
+) -> Response {
+    match uploads
+        .cancel(&upload_id, &upload_owner(tenant, claim, &headers))
+        .await
+    {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(error) => error.into_response(),
+    }
+}
+
+/// Finalize a resumable upload.
+///
+/// Stores the completely received blob on Walrus with the parameters specified when creating the
+/// upload. If the upload was created with `async=true`, an upload job is created instead. The
+/// upload is removed once the blob is stored (or the job is created); otherwise, finalizing can be
+/// retried.
+#[tracing::instrument(level = Level::ERROR, skip_all, fields(%upload_id))]
+#[utoipa::path(
+    post,
+    path = UPLOAD_FINALIZE_ENDPOINT,
+    params(
+        ("upload_id" = String, Path, description = "The ID of the upload."),
+        (
+            "X-Walrus-Access-Token" = Option<String>,
+            Header,
+            description = "The access token of an upload created by an anonymous client.",
+        ),
+    ),
+    responses(
+        (status = 200, description = "The blob was stored successfully", body = BlobStoreResult),
+        (status = 202, description = "The upload job was created", body = JobInfo),
+        UploadError,
+    ),
+)]
+pub(super) async fn finalize_upload<T: WalrusWriteClient + Send + Sync + 'static>(
+    State((client, jobs, uploads)): State<UploadState<T>>,
+    Path(upload_id): Path<String>,
+    tenant: Option<Extension<TenantContext>>,
+    claim: Option<Extension<Claim>>,
+    headers: HeaderMap,
+) -> Response {
+    let tenant = tenant.map(|Extension(tenant)| tenant);
+    let owner = UploadOwner::new(
+        tenant.as_ref(),
+        claim.as_ref().map(|Extension(claim)| claim),
+        access_token(&headers),
+    );
+    // The usage of the upload is attributed to the tenant of the owner of the upload, which is
+    // also the tenant of this request.
+    let upload = match uploads.begin_finalize(&upload_id, &owner).await {
+        Ok(upload) => upload,
+        Err(error) => return error.into_response(),
+    };
+
+    tracing::debug!(size = upload.blob.len(), "finalizing upload");
+    let response = if upload.run_async {
+        submit_upload_job(
+            client,
+            jobs,
+            upload.parameters,
+            upload.callback_url,
+            owner,
+            tenant,
+            upload.blob,
+        )
+        .await
+    } else {
+        store_blob(
+            client.as_ref(),
+            upload.parameters,
+            tenant.as_ref(),
+            &upload.blob,
+        )
+        .await
+    };
+
+    if response.status().is_success() {
+        uploads.remove(&upload_id).await;
+    } else {
+        uploads.release(&upload_id);
+    }
+    response
+}
+
+/// Returns the owner of a request to a resumable upload or upload job.
+fn upload_owner(
+    tenant: Option<Extension<TenantContext>>,
+    claim: Option<Extension<Claim>>,
+    headers: &HeaderMap,
+) -> UploadOwner {
+    UploadOwner::new(
+        tenant.as_ref().map(|Extension(tenant)| tenant),
+        claim.as_ref().map(|Extension(claim)| claim),
+        access_token(headers),
+    )
+}
+
+/// Returns the access token of an anonymous client provided in the request headers.
+fn access_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(ACCESS_TOKEN_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+}
+
+/// Parses the numeric value of a header of the resumable upload protocol.
+fn parse_upload_header(headers: &HeaderMap, header: &'static str) -> Result<u64, UploadError> {
+    headers
+        .get(header)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse().ok())
+        .ok_or(UploadError::InvalidHeader { header })
+}
+
+/// Returns a hint on the size of the blob stored by the request.
+///
+/// For the creation of a resumable upload, this is the declared length of the upload. Otherwise,
+/// it is the size hint of the request body.
+pub(crate) fn blob_size_hint(request: &Request) -> SizeHint {
+    match parse_upload_header(request.headers(), UPLOAD_LENGTH_HEADER) {
+        Ok(length) if request.method() == Method::POST => SizeHint::with_exact(length),
+        _ => request.body().size_hint(),
+    }
+}
+
+/// Returns the response with the upload information, including the tus-like headers.
+fn upload_response(status: StatusCode, upload: UploadInfo) -> Response {
+    let mut response = (status, Json(&upload)).into_response();
+    let headers = response.headers_mut();
+    headers.insert(CACHE_CONTROL, HeaderValue::from_static("no-store"));
+    headers.insert(
+        HeaderName::from_static(UPLOAD_OFFSET_HEADER),
+        HeaderValue::from(upload.contiguous_offset()),
+    );
+    headers.insert(
+        HeaderName::from_static(UPLOAD_LENGTH_HEADER),
+        HeaderValue::from(upload.length),
+    );
+    response
+}
+
 /// Checks if the JWT claim has a maximum size and if the blob exceeds it.
 ///
 /// IMPORTANT: This function does _not_ check the validity of the claim (i.e., does not
@@ -444,6 +944,8 @@ pub(super) fn daemon_cors_layer() -> CorsLayer {
 /// Takes a quilt patch ID and returns the corresponding blob from the quilt.
 /// The blob content is returned as raw bytes in the response body, while metadata
 /// such as the patch identifier and tags are returned in response headers.
+/// Compressed patches are returned with the corresponding `Content-Encoding` if the request
+/// accepts it, and are decompressed otherwise.
 ///
 /// # Example
 /// ```bash
@@ -501,6 +1003,7 @@ pub(super) async fn get_blob_by_quilt_patch_id<T: WalrusReadClient>(
                     &quilt_patch_id_str,
                     &response_header_config,
                 )
+                .await
             } else {
                 tracing::debug!(
                     ?quilt_patch_id_str,
@@ -538,17 +1041,45 @@ pub(super) async fn get_blob_by_quilt_patch_id<T: WalrusReadClient>(
 }
 
 /// Builds a response for a quilt patch.
-fn build_quilt_patch_response(
+///
+/// A compressed patch is served as stored, with the corresponding `Content-Encoding`, if the
+/// request accepts that encoding; otherwise, it is decompressed. The `ETag` of an encoded
+/// response is suffixed with the encoding, such that the representations are validated separately.
+async fn build_quilt_patch_response(
     blob: QuiltStoreBlob<'static>,
     request_headers: &HeaderMap,
     etag: &str,
     response_header_config: &AggregatorResponseHeaderConfig,
 ) -> Response {
+    let compression = blob.compression();
+    let (blob, content_encoding) = match negotiate_content_encoding(blob, request_headers).await {
+        Ok(negotiated) => negotiated,
+        Err(error) => {
+            tracing::error!(?error, "failed to decompress quilt patch");
+            return GetBlobError::Internal(error).to_response();
+        }
+    };
+
     let identifier = blob.identifier().to_string();
     let blob_attribute: BlobAttribute = blob.tags().clone().into();
     let blob_data = blob.into_data();
     let mut response = (StatusCode::OK, blob_data).into_response();
-    populate_response_headers_from_request(request_headers, etag, response.headers_mut());
+    let etag = match content_encoding {
+        Some(content_encoding) => format!("{etag}.{content_encoding}"),
+        None => etag.to_owned(),
+    };
+    populate_response_headers_from_request(request_headers, &etag, response.headers_mut());
+    if let Some(content_encoding) = content_encoding {
+        response
+            .headers_mut()
+            .insert(CONTENT_ENCODING, HeaderValue::from_static(content_encoding));
+    }
+    if compression.is_compressed() {
+        // The response body depends on the accepted encodings.
+        response
+            .headers_mut()
+            .insert(VARY, HeaderValue::from_static("accept-encoding"));
+    }
     populate_response_headers_from_attributes(
         response.headers_mut(),
         &blob_attribute,
@@ -567,11 +1098,66 @@ fn build_quilt_patch_response(
     response
 }
 
+/// Returns the quilt patch together with the `Content-Encoding` to serve it with.
+///
+/// A compressed patch is served as stored if the request accepts its encoding; otherwise, it is
+/// decompressed on the blocking thread pool.
+pub(super) async fn negotiate_content_encoding(
+    blob: QuiltStoreBlob<'static>,
+    request_headers: &HeaderMap,
+) -> anyhow::Result<(QuiltStoreBlob<'static>, Option<&'static str>)> {
+    let compression = blob.compression();
+    if !compression.is_compressed() {
+        return Ok((blob, None));
+    }
+    if let Some(content_encoding) = compression
+        .content_encoding()
+        .filter(|encoding| accepts_encoding(request_headers, encoding))
+    {
+        return Ok((blob, Some(content_encoding)));
+    }
+    let blob = tokio::task::spawn_blocking(move || blob.decompress()).await??;
+    Ok((blob, None))
+}
+
+/// Returns true if the `Accept-Encoding` header of the request accepts the given content coding.
+///
+/// An explicit entry for the coding takes precedence over the `*` wildcard, and a quality value
+/// of zero rejects the coding.
+pub(super) fn accepts_encoding(request_headers: &HeaderMap, encoding: &str) -> bool {
+    let mut wildcard_accepted = false;
+    for coding in request_headers
+        .get_all(ACCEPT_ENCODING)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+    {
+        let mut parts = coding.split(';');
+        let name = parts.next().unwrap_or_default().trim();
+        let accepted = !parts.any(|param| {
+            param
+                .trim()
+                .strip_prefix("q=")
+                .and_then(|quality| quality.trim().parse::<f32>().ok())
+                .is_some_and(|quality| quality <= 0.0)
+        });
+        if name.eq_ignore_ascii_case(encoding) {
+            return accepted;
+        }
+        if name == "*" {
+            wildcard_accepted = accepted;
+        }
+    }
+    wildcard_accepted
+}
+
 /// Retrieve a blob by quilt ID and identifier.
 ///
 /// Takes a quilt ID and an identifier and returns the corresponding blob from the quilt.
 /// The blob content is returned as raw bytes in the response body, while metadata
 /// such as the blob identifier and tags are returned in response headers.
+/// Compressed patches are returned with the corresponding `Content-Encoding` if the request
+/// accepts it, and are decompressed otherwise.
 ///
 /// # Example
 /// ```bash
@@ -634,12 +1220,15 @@ pub(super) async fn get_blob_by_quilt_id_and_identifier<T: WalrusReadClient>(
         .get_blob_by_quilt_id_and_identifier(&quilt_id, &identifier)
         .await
     {
-        Ok(blob) => build_quilt_patch_response(
-            blob,
-            &request_headers,
-            &quilt_id.to_string(),
-            &response_header_config,
-        ),
+        Ok(blob) => {
+            build_quilt_patch_response(
+                blob,
+                &request_headers,
+                &quilt_id.to_string(),
+                &response_header_config,
+            )
+            .await
+        }
         Err(error) => {
             let error = GetBlobError::from(error);
 
@@ -678,10 +1267,23 @@ pub struct QuiltPatchItem {
     pub tags: BTreeMap<String, String>,
 }
 
+/// The query parameters to filter the patches of a quilt.
+#[derive(Debug, Default, Deserialize, Serialize, IntoParams, PartialEq, Eq)]
+#[into_params(parameter_in = Query, style = Form)]
+#[serde(deny_unknown_fields)]
+pub struct QuiltPatchesQuery {
+    /// A query selecting the patches by their identifiers and tags.
+    ///
+    /// For example, `tag:shard >= 10 and identifier ~ "*.csv"`. See the documentation of the
+    /// `read-quilt --query` CLI option for the syntax.
+    #[serde(default)]
+    pub query: Option<String>,
+}
+
 /// List patches in a quilt.
 ///
 /// Returns a list of identifiers and QuiltPatchIds for all patches contained in the specified
-/// quilt.
+/// quilt. If the `query` parameter is set, only the patches matching the query are returned.
 ///
 /// # Example
 /// ```bash
@@ -711,7 +1313,8 @@ pub struct QuiltPatchItem {
             "quilt_id" = BlobId,
             description = "The quilt ID encoded as URL-safe base64",
             example = "rkcHpHQrornOymttgvSq3zvcmQEsMqzmeUM1HSY4ShU"
-        )
+        ),
+        QuiltPatchesQuery,
     ),
     responses(
         (
@@ -720,20 +1323,40 @@ pub struct QuiltPatchItem {
             body = Vec<QuiltPatchItem>
         ),
         GetBlobError,
+        InvalidQuiltPatchQueryError,
     ),
     summary = "List patches in a quilt",
     description = "Retrieve a list of patches contained in a quilt with their identifiers and \
-                QuiltPatchIds. Each QuiltPatchId can be used with the \
-                /v1/blobs/by-quilt-patch-id endpoint to retrieve the actual blob data.",
+                QuiltPatchIds, optionally filtered by a query on their identifiers and tags. \
+                Each QuiltPatchId can be used with the /v1/blobs/by-quilt-patch-id endpoint to \
+                retrieve the actual blob data.",
 )]
 pub(super) async fn list_patches_in_quilt<T: WalrusReadClient>(
     State(client): State<Arc<T>>,
     Path(BlobIdString(quilt_id)): Path<BlobIdString>,
+    Query(QuiltPatchesQuery { query }): Query<QuiltPatchesQuery>,
 ) -> Response {
     tracing::debug!("starting to list patches in quilt: {}", quilt_id);
 
+    let filter = match query
+        .as_deref()
+        .map(str::parse::<QuiltPatchFilter>)
+        .transpose()
+    {
+        Ok(filter) => filter,
+        Err(error) => {
+            tracing::debug!(?error, "invalid quilt patch query");
+            return InvalidQuiltPatchQueryError(error).to_response();
+        }
+    };
+
     match client.list_patches_in_quilt(&quilt_id).await {
-        Ok(patches) => (StatusCode::OK, Json(patches)).into_response(),
+        Ok(mut patches) => {
+            if let Some(filter) = filter {
+                patches.retain(|patch| filter.matches(&patch.identifier, &patch.tags));
+            }
+            (StatusCode::OK, Json(patches)).into_response()
+        }
         Err(error) => {
             let error = GetBlobError::from(error);
 
@@ -805,10 +1428,25 @@ pub struct PublisherQuery {
     #[serde(default)]
     pub force: bool,
     /// The quilt version to use (for quilt endpoints only).
-    /// Valid values: "v1", "V1", or "1". Defaults to "v1" if not specified.
+    /// Valid values: "v1", "V1", "1", "v2", "V2", or "2". Defaults to "v1" if not specified.
     #[serde(default)]
     #[serde(deserialize_with = "deserialize_quilt_version")]
     pub quilt_version: Option<QuiltVersionEnum>,
+    /// The compression to apply to each patch of the quilt (for quilt endpoints only).
+    /// Valid values: "none", "zstd", "gzip", or "brotli". Compressed patches require quilt
+    /// version "v2".
+    #[serde(default)]
+    pub quilt_patch_compression: Option<QuiltPatchCompression>,
+    /// If true, the publisher immediately responds with an upload job and stores the blob in the
+    /// background (for the blob endpoint only).
+    ///
+    /// The status of the job can be retrieved at `/v1/jobs/{job_id}`.
+    #[serde(default, rename = "async")]
+    pub run_async: bool,
+    /// The URL to which the final status of the upload job is sent in a POST request (only if
+    /// `async` is true).
+    #[serde(default)]
+    pub callback_url: Option<String>,
 
     #[serde(flatten, default)]
     #[param(inline)]
@@ -828,6 +1466,9 @@ impl Default for PublisherQuery {
             permanent: false,
             force: false,
             quilt_version: None,
+            quilt_patch_compression: None,
+            run_async: false,
+            callback_url: None,
             send_or_share: None,
         }
     }
@@ -841,6 +1482,22 @@ impl PublisherQuery {
         StoreOptimizations::none().with_check_status(!self.force)
     }
 
+    /// Returns the parameters of the store operation based on the query parameters and the
+    /// defaults of the client.
+    fn store_parameters<T: WalrusWriteClient>(
+        &self,
+        client: &T,
+    ) -> Result<StoreParameters, StoreBlobError> {
+        Ok(StoreParameters {
+            encoding_type: self.encoding_type,
+            epochs: self.epochs,
+            persistence: self.blob_persistence()?,
+            check_status: !self.force,
+            post_store: self.post_store_action(client.default_post_store_action()),
+            credits_policy: client.default_credits_policy(),
+        })
+    }
+
     /// Returns the [`BlobPersistence`] value based on the query parameters.
     fn blob_persistence(&self) -> Result<BlobPersistence, StoreBlobError> {
         BlobPersistence::from_deletable_and_permanent(self.deletable, self.permanent)
@@ -868,6 +1525,29 @@ impl PublisherQuery {
         }
     }
 
+    /// Returns the parsed callback URL, which must use HTTP(S), if any.
+    ///
+    /// The host of the callback URL must be allowed by the configuration of the jobs.
+    fn callback_url(&self, jobs: Option<&JobManager>) -> Result<Option<Url>, StoreBlobError> {
+        let Some(callback_url) = self.callback_url.as_deref() else {
+            return Ok(None);
+        };
+        match Url::parse(callback_url) {
+            Ok(url) if matches!(url.scheme(), "http" | "https") => {
+                if jobs.is_some_and(|jobs| jobs.is_allowed_callback(&url)) {
+                    Ok(Some(url))
+                } else {
+                    Err(StoreBlobError::MalformedRequest {
+                        message: format!("callback URL not allowed by this publisher: {url}"),
+                    })
+                }
+            }
+            _ => Err(StoreBlobError::MalformedRequest {
+                message: format!("invalid callback URL: {callback_url}"),
+            }),
+        }
+    }
+
     /// Returns the value for the `send_or_share` field.
     pub fn send_or_share(&self) -> Option<SendOrShare> {
         self.send_or_share.clone()
@@ -914,6 +1594,13 @@ pub struct QuiltPatchMetadata {
 ///     {"identifier": "logo-2025", "tags": {"type": "logo", "format": "png"}}
 ///   ]'
 /// ```
+///
+/// ## Blobs compressed with zstd, with quilt version V2
+/// ```bash
+/// curl -X PUT "http://localhost:8080/v1/quilts?quilt_version=V2&quilt_patch_compression=zstd" \
+///   -F "index.html=@index.html" \
+///   -F "style.css=@style.css"
+/// ```
 #[tracing::instrument(level = Level::ERROR, skip_all, fields(epochs=%query.epochs))]
 #[utoipa::path(
     put,
@@ -933,14 +1620,22 @@ pub(super) async fn put_quilt<T: WalrusWriteClient>(
     State(client): State<Arc<T>>,
     Query(query): Query<PublisherQuery>,
     bearer_header: Option<TypedHeader<Authorization<Bearer>>>,
+    tenant: Option<Extension<TenantContext>>,
     multipart: Multipart,
 ) -> Response {
     tracing::debug!("starting to process quilt upload");
 
+    if query.run_async {
+        return StoreBlobError::MalformedRequest {
+            message: "asynchronous uploads are only supported for blobs".to_owned(),
+        }
+        .into_response();
+    }
+
     // Parse the quilt version, defaulting to V1 if not specified.
     let quilt_version = query.quilt_version.clone().unwrap_or(QuiltVersionEnum::V1);
 
-    let quilt_store_blobs = match parse_multipart_quilt(multipart).await {
+    let mut quilt_store_blobs = match parse_multipart_quilt(multipart).await {
         Ok(blobs) => blobs,
         Err(error) => {
             tracing::debug!(?error, "failed to parse multipart form");
@@ -958,16 +1653,77 @@ pub(super) async fn put_quilt<T: WalrusWriteClient>(
         .into_response();
     }
 
+    if let Some(compression) = query
+        .quilt_patch_compression
+        .filter(QuiltPatchCompression::is_compressed)
+    {
+        if quilt_version == QuiltVersionEnum::V1 {
+            return StoreBlobError::MalformedRequest {
+                message: "compressed quilt patches require quilt version V2".to_string(),
+            }
+            .into_response();
+        }
+        let compressed = tokio::task::spawn_blocking(move || {
+            quilt_store_blobs
+                .into_iter()
+                .map(|blob| blob.with_compression(compression))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .await;
+        quilt_store_blobs = match compressed {
+            Ok(Ok(blobs)) => blobs,
+            Ok(Err(error)) => {
+                return StoreBlobError::MalformedRequest {
+                    message: format!("failed to compress quilt patches: {error}"),
+                }
+                .into_response();
+            }
+            Err(error) => return StoreBlobError::Internal(error.into()).into_response(),
+        };
+    }
+
     let blob_persistence = match query.blob_persistence() {
         Ok(blob_persistence) => blob_persistence,
         Err(error) => return error.into_response(),
     };
 
-    // For now, we only support V1 quilts.
-    assert_eq!(quilt_version, QuiltVersionEnum::V1);
+    match quilt_version {
+        QuiltVersionEnum::V1 => {
+            store_quilt::<QuiltVersionV1, T>(
+                &client,
+                &query,
+                &quilt_store_blobs,
+                blob_persistence,
+                bearer_header,
+                tenant,
+            )
+            .await
+        }
+        QuiltVersionEnum::V2 => {
+            store_quilt::<QuiltVersionV2, T>(
+                &client,
+                &query,
+                &quilt_store_blobs,
+                blob_persistence,
+                bearer_header,
+                tenant,
+            )
+            .await
+        }
+    }
+}
 
+/// Constructs a quilt of version `V` from the blobs and stores it.
+async fn store_quilt<V: QuiltVersion, T: WalrusWriteClient>(
+    client: &T,
+    query: &PublisherQuery,
+    quilt_store_blobs: &[QuiltStoreBlob<'static>],
+    blob_persistence: BlobPersistence,
+    bearer_header: Option<TypedHeader<Authorization<Bearer>>>,
+    tenant: Option<Extension<TenantContext>>,
+) -> Response {
     let quilt = match client
-        .construct_quilt::<QuiltVersionV1>(&quilt_store_blobs, query.encoding_type)
+        .construct_quilt::<V>(quilt_store_blobs, query.encoding_type)
         .await
     {
         Ok(quilt) => quilt,
@@ -985,19 +1741,34 @@ pub(super) async fn put_quilt<T: WalrusWriteClient>(
         return error.into_response();
     }
 
-    let result = client
-        .write_quilt::<QuiltVersionV1>(
-            quilt,
-            query.encoding_type,
-            query.epochs,
-            query.optimizations(),
-            blob_persistence,
-            query.post_store_action(client.default_post_store_action()),
-        )
-        .await;
+    let quilt_size = quilt.data().len();
+    if let Some(Extension(tenant)) = tenant.as_ref()
+        && let Err(error) = tenant.reserve_bytes(quilt_size as u64)
+    {
+        return error.into_response();
+    }
+
+    let params = WriteParams {
+        encoding_type: query.encoding_type,
+        epochs_ahead: query.epochs,
+        store_optimizations: query.optimizations(),
+        persistence: blob_persistence,
+        post_store: query.post_store_action(client.default_post_store_action()),
+        credits_policy: client.default_credits_policy(),
+    };
+    let (result, sui_spent) = with_gas_accounting(client.write_quilt::<V>(quilt, params)).await;
 
     match result {
-        Ok(result) => (StatusCode::OK, Json(result)).into_response(),
+        Ok(result) => {
+            if let Some(Extension(tenant)) = tenant {
+                tenant.record_usage(&TenantUsage::from_upload(
+                    quilt_size,
+                    &result.blob_store_result,
+                    sui_spent,
+                ));
+            }
+            (StatusCode::OK, Json(result)).into_response()
+        }
         Err(error) => {
             tracing::error!(?error, "error storing quilt");
             StoreBlobError::from(error).into_response()
@@ -1246,4 +2017,52 @@ mod tests {
             "3"
         );
     }
+
+    #[tokio::test]
+    async fn quilt_patch_etag_depends_on_content_encoding() {
+        let data = vec![7u8; 1024];
+        let blob = QuiltStoreBlob::new_owned(data.clone(), "patch")
+            .expect("identifier is valid")
+            .with_compression(QuiltPatchCompression::Gzip)
+            .expect("data can be compressed");
+        let config = AggregatorResponseHeaderConfig::default();
+
+        let mut request_headers = HeaderMap::new();
+        let plain =
+            build_quilt_patch_response(blob.clone(), &request_headers, "patch-id", &config).await;
+        request_headers.insert(ACCEPT_ENCODING, HeaderValue::from_static("gzip"));
+        let encoded = build_quilt_patch_response(blob, &request_headers, "patch-id", &config).await;
+
+        assert_eq!(plain.headers()[ETAG], "patch-id");
+        assert!(plain.headers().get(CONTENT_ENCODING).is_none());
+        assert_eq!(encoded.headers()[ETAG], "patch-id.gzip");
+        assert_eq!(encoded.headers()[CONTENT_ENCODING], "gzip");
+        for response in [&plain, &encoded] {
+            assert_eq!(response.headers()[VARY], "accept-encoding");
+        }
+    }
+
+    param_test! {
+        test_accepts_encoding: [
+            missing: (None, "zstd", false),
+            exact: (Some("zstd"), "zstd", true),
+            listed: (Some("gzip, deflate, br"), "br", true),
+            not_listed: (Some("gzip, deflate"), "br", false),
+            case_insensitive: (Some("GZip"), "gzip", true),
+            with_quality: (Some("br;q=0.5, gzip"), "br", true),
+            rejected: (Some("gzip;q=0, *"), "gzip", false),
+            wildcard: (Some("*"), "zstd", true),
+            rejected_wildcard: (Some("*;q=0"), "zstd", false),
+        ]
+    }
+    fn test_accepts_encoding(accept_encoding: Option<&str>, encoding: &str, expected: bool) {
+        let mut headers = HeaderMap::new();
+        if let Some(accept_encoding) = accept_encoding {
+            headers.insert(
+                ACCEPT_ENCODING,
+                HeaderValue::from_str(accept_encoding).expect("valid header value"),
+            );
+        }
+        assert_eq!(accepts_encoding(&headers, encoding), expected);
+    }
 }
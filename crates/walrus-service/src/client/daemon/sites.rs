@@ -0,0 +1,842 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Hosting of static websites stored in quilts.
+//!
+//! An aggregator can be configured to serve the patches of a quilt as a static website, mounted on
+//! a set of hostnames and/or under a path prefix. The quilt is either fixed in the configuration or
+//! referenced through the attribute of a blob object, which allows updating a site without changing
+//! the configuration of the aggregator.
+//!
+//! A site can optionally contain a manifest patch with the identifier [`SITE_MANIFEST_IDENTIFIER`],
+//! which defines a custom 404 page and redirects.
+
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+    str::FromStr,
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::anyhow;
+use axum::{
+    extract::State,
+    http::{HeaderMap, HeaderValue, StatusCode, Uri, uri::Authority},
+    response::{IntoResponse, Response},
+};
+use moka::future::Cache;
+use reqwest::header::{
+    CACHE_CONTROL,
+    CONTENT_ENCODING,
+    CONTENT_TYPE,
+    ETAG,
+    HOST,
+    IF_NONE_MATCH,
+    LOCATION,
+    VARY,
+    X_CONTENT_TYPE_OPTIONS,
+};
+use serde::{Deserialize, Serialize};
+use serde_with::{DisplayFromStr, DurationSeconds, serde_as};
+use sui_types::base_types::ObjectID;
+use tracing::Level;
+use walrus_core::{BlobId, QuiltPatchId, encoding::quilt_encoding::QuiltStoreBlob};
+
+use super::{
+    WalrusReadClient,
+    routes::{GetBlobError, accepts_encoding, negotiate_content_encoding},
+};
+use crate::common::api::RestApiError;
+
+/// The key of the blob attribute holding the ID of the quilt containing a site.
+pub const SITE_QUILT_ATTRIBUTE_KEY: &str = "site-quilt-id";
+
+/// The identifier of the patch containing the manifest of a site.
+pub const SITE_MANIFEST_IDENTIFIER: &str = "_site.json";
+
+/// The document served for paths ending in a slash.
+const INDEX_DOCUMENT: &str = "index.html";
+
+/// The quilt patch tag overriding the content type inferred from the identifier.
+const CONTENT_TYPE_TAG: &str = "content-type";
+
+/// The maximum number of sites whose patch listing and manifest are cached.
+const MAX_CACHED_SITE_INDICES: u64 = 1024;
+
+/// The configuration of the static websites served by an aggregator.
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SitesConfig {
+    /// The interval after which the quilt referenced by a blob object is looked up again.
+    #[serde(
+        rename = "object_refresh_interval_secs",
+        default = "default::object_refresh_interval"
+    )]
+    #[serde_as(as = "DurationSeconds")]
+    pub object_refresh_interval: Duration,
+    /// The sites served by the aggregator.
+    pub sites: Vec<SiteConfig>,
+}
+
+impl SitesConfig {
+    /// Loads the sites configuration from the YAML file at the provided path.
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        walrus_utils::load_from_yaml(path)
+    }
+
+    /// Checks that site names are unique and that the sites can be told apart.
+    fn check_consistency(&self) -> anyhow::Result<()> {
+        let mut names = HashSet::new();
+        let mut mounts = HashSet::new();
+        for site in &self.sites {
+            anyhow::ensure!(
+                names.insert(&site.name),
+                "duplicate site name '{}'",
+                site.name
+            );
+            anyhow::ensure!(
+                !site.hostnames.is_empty() || site.path_prefix.is_some(),
+                "site '{}' must specify hostnames, a path prefix, or both",
+                site.name
+            );
+            if let Some(prefix) = &site.path_prefix {
+                anyhow::ensure!(
+                    prefix.len() > 1 && prefix.starts_with('/') && !prefix.ends_with('/'),
+                    "the path prefix of site '{}' must start and must not end with a '/'",
+                    site.name
+                );
+            }
+            let hostnames = if site.hostnames.is_empty() {
+                vec![None]
+            } else {
+                site.hostnames
+                    .iter()
+                    .map(|hostname| Some(hostname.to_ascii_lowercase()))
+                    .collect()
+            };
+            for hostname in hostnames {
+                anyhow::ensure!(
+                    mounts.insert((hostname, site.path_prefix.clone())),
+                    "site '{}' is mounted at the same location as another site",
+                    site.name
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The configuration of a single static website.
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SiteConfig {
+    /// The name of the site, used for logging.
+    pub name: String,
+    /// The hostnames on which the site is served.
+    ///
+    /// If empty, the site is served on all hostnames under its path prefix.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hostnames: Vec<String>,
+    /// The path prefix under which the site is served, e.g., `/docs`.
+    ///
+    /// If not set, the site is served at the root of its hostnames.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path_prefix: Option<String>,
+    /// The quilt containing the site.
+    #[serde(flatten)]
+    pub source: SiteSource,
+    /// The maximum age for which clients and caches may reuse the responses of the site.
+    #[serde(rename = "cache_max_age_secs", default = "default::cache_max_age")]
+    #[serde_as(as = "DurationSeconds")]
+    pub cache_max_age: Duration,
+}
+
+/// The quilt containing a site.
+#[serde_as]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SiteSource {
+    /// The ID of the quilt.
+    QuiltId(#[serde_as(as = "DisplayFromStr")] BlobId),
+    /// The ID of a blob object.
+    ///
+    /// The site is served from the quilt whose ID is stored in the [`SITE_QUILT_ATTRIBUTE_KEY`]
+    /// attribute of the blob object, or from the blob itself if the attribute is not set.
+    BlobObjectId(ObjectID),
+}
+
+mod default {
+    use std::time::Duration;
+
+    pub(crate) fn object_refresh_interval() -> Duration {
+        Duration::from_secs(60)
+    }
+
+    pub(crate) fn cache_max_age() -> Duration {
+        Duration::from_secs(60 * 60)
+    }
+}
+
+/// The manifest of a site, stored in the patch [`SITE_MANIFEST_IDENTIFIER`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SiteManifest {
+    /// The identifier of the patch served with status code 404 for paths not found in the site.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub not_found_page: Option<String>,
+    /// The redirects of the site, applied in order before looking up the patches.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub redirects: Vec<SiteRedirect>,
+}
+
+/// A redirect defined in the manifest of a site.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SiteRedirect {
+    /// The path relative to the site root to redirect, e.g., `/old.html`.
+    ///
+    /// A trailing `*` matches all paths starting with the preceding prefix.
+    pub from: String,
+    /// The target of the redirect, either a path relative to the site root or an absolute URL.
+    ///
+    /// If both `from` and `to` end with a `*`, the part of the path matched by the `*` is
+    /// appended to the target.
+    pub to: String,
+    /// The status code of the redirect; one of 301, 302, 303, 307, or 308.
+    #[serde(default = "SiteRedirect::default_status")]
+    pub status: u16,
+}
+
+impl SiteRedirect {
+    fn default_status() -> u16 {
+        StatusCode::MOVED_PERMANENTLY.as_u16()
+    }
+
+    /// Returns the target of the redirect if it applies to the path.
+    fn target(&self, path: &str) -> Option<String> {
+        match self.from.strip_suffix('*') {
+            Some(prefix) => path
+                .strip_prefix(prefix)
+                .map(|rest| match self.to.strip_suffix('*') {
+                    Some(target) => format!("{target}{rest}"),
+                    None => self.to.clone(),
+                }),
+            None => (path == self.from).then(|| self.to.clone()),
+        }
+    }
+}
+
+impl SiteManifest {
+    /// Parses and checks the manifest.
+    fn parse(data: &[u8]) -> anyhow::Result<Self> {
+        let manifest: Self = serde_json::from_slice(data)?;
+        for redirect in &manifest.redirects {
+            anyhow::ensure!(
+                matches!(redirect.status, 301 | 302 | 303 | 307 | 308),
+                "invalid status code {} of the redirect from '{}'",
+                redirect.status,
+                redirect.from
+            );
+            anyhow::ensure!(
+                redirect.from.starts_with('/'),
+                "the redirect from '{}' does not start with a '/'",
+                redirect.from
+            );
+        }
+        Ok(manifest)
+    }
+}
+
+/// The patches and the manifest of a site.
+#[derive(Debug, Default)]
+struct SiteIndex {
+    /// The IDs of the patches of the site, by identifier.
+    patches: HashMap<String, QuiltPatchId>,
+    manifest: SiteManifest,
+}
+
+/// How a request path is served from a site.
+#[derive(Debug, PartialEq, Eq)]
+enum Resolution<'a> {
+    /// Serve the patch with the identifier.
+    Patch(String),
+    /// Redirect to the target with the status code.
+    Redirect(StatusCode, String),
+    /// Redirect to the request path followed by a slash, to serve the index of a directory.
+    AppendSlash,
+    /// The path does not exist; serve the 404 page if any.
+    NotFound(Option<&'a str>),
+}
+
+impl SiteIndex {
+    async fn load(client: &impl WalrusReadClient, quilt_id: &BlobId) -> Result<Self, GetBlobError> {
+        let mut patches: HashMap<_, _> = client
+            .list_patches_in_quilt(quilt_id)
+            .await?
+            .into_iter()
+            .map(|patch| (patch.identifier, patch.patch_id))
+            .collect();
+        let manifest = if patches.remove(SITE_MANIFEST_IDENTIFIER).is_some() {
+            let blob = client
+                .get_blob_by_quilt_id_and_identifier(quilt_id, SITE_MANIFEST_IDENTIFIER)
+                .await?;
+            let blob = tokio::task::spawn_blocking(move || blob.decompress())
+                .await
+                .map_err(|error| anyhow!(error))?
+                .map_err(|error| anyhow!(error))?;
+            // An invalid manifest should not make the whole site unavailable.
+            SiteManifest::parse(blob.data()).unwrap_or_else(|error| {
+                tracing::warn!(%quilt_id, ?error, "ignoring the invalid site manifest");
+                SiteManifest::default()
+            })
+        } else {
+            SiteManifest::default()
+        };
+        Ok(Self { patches, manifest })
+    }
+
+    /// Resolves a decoded request path relative to the site root.
+    fn resolve(&self, path: &str) -> Resolution<'_> {
+        for redirect in &self.manifest.redirects {
+            if let Some(target) = redirect.target(path) {
+                let status = StatusCode::from_u16(redirect.status)
+                    .expect("the status code is checked when parsing the manifest");
+                return Resolution::Redirect(status, target);
+            }
+        }
+
+        let relative_path = path.trim_start_matches('/');
+        if relative_path.is_empty() || relative_path.ends_with('/') {
+            let index = format!("{relative_path}{INDEX_DOCUMENT}");
+            if self.patches.contains_key(&index) {
+                return Resolution::Patch(index);
+            }
+        } else if self.patches.contains_key(relative_path) {
+            return Resolution::Patch(relative_path.to_owned());
+        } else if self
+            .patches
+            .contains_key(&format!("{relative_path}/{INDEX_DOCUMENT}"))
+        {
+            return Resolution::AppendSlash;
+        }
+        Resolution::NotFound(self.manifest.not_found_page.as_deref())
+    }
+}
+
+/// The static websites served by an aggregator.
+#[derive(Debug)]
+pub struct Sites {
+    sites: Vec<SiteConfig>,
+    /// The quilt IDs referenced by blob objects.
+    quilt_ids: Cache<ObjectID, BlobId>,
+    /// The site indices by quilt ID; as quilts are immutable, these never expire.
+    indices: Cache<BlobId, Arc<SiteIndex>>,
+}
+
+impl Sites {
+    /// Creates the sites from the configuration.
+    pub fn new(config: SitesConfig) -> anyhow::Result<Self> {
+        config.check_consistency()?;
+        Ok(Self {
+            sites: config.sites,
+            quilt_ids: Cache::builder()
+                .name("site_quilt_ids")
+                .time_to_live(config.object_refresh_interval)
+                .build(),
+            indices: Cache::builder()
+                .name("site_indices")
+                .max_capacity(MAX_CACHED_SITE_INDICES)
+                .build(),
+        })
+    }
+
+    /// Returns the number of configured sites.
+    pub fn len(&self) -> usize {
+        self.sites.len()
+    }
+
+    /// Returns true if no site is configured.
+    pub fn is_empty(&self) -> bool {
+        self.sites.is_empty()
+    }
+
+    /// Returns the site serving the request, together with the request path relative to the site
+    /// root, which is empty if the path is equal to the path prefix of the site.
+    ///
+    /// Sites mounted on the hostname take precedence over sites served on all hostnames, and
+    /// longer path prefixes take precedence over shorter ones.
+    fn find_site<'a>(&self, host: Option<&str>, path: &'a str) -> Option<(&SiteConfig, &'a str)> {
+        self.sites
+            .iter()
+            .filter_map(|site| {
+                let on_host = !site.hostnames.is_empty();
+                if on_host
+                    && !host.is_some_and(|host| {
+                        site.hostnames
+                            .iter()
+                            .any(|hostname| hostname.eq_ignore_ascii_case(host))
+                    })
+                {
+                    return None;
+                }
+                let prefix = site.path_prefix.as_deref().unwrap_or_default();
+                let relative_path = path.strip_prefix(prefix)?;
+                if !relative_path.is_empty() && !relative_path.starts_with('/') {
+                    return None;
+                }
+                Some(((on_host, prefix.len()), site, relative_path))
+            })
+            .max_by_key(|(precedence, ..)| *precedence)
+            .map(|(_, site, relative_path)| (site, relative_path))
+    }
+
+    async fn quilt_id(
+        &self,
+        client: &impl WalrusReadClient,
+        site: &SiteConfig,
+    ) -> Result<BlobId, GetBlobError> {
+        let object_id = match site.source {
+            SiteSource::QuiltId(quilt_id) => return Ok(quilt_id),
+            SiteSource::BlobObjectId(object_id) => object_id,
+        };
+        if let Some(quilt_id) = self.quilt_ids.get(&object_id).await {
+            return Ok(quilt_id);
+        }
+
+        let blob = client.get_blob_by_object_id(&object_id).await?;
+        let quilt_id = match blob
+            .attribute
+            .as_ref()
+            .and_then(|attribute| attribute.get(SITE_QUILT_ATTRIBUTE_KEY))
+        {
+            Some(quilt_id) => BlobId::from_str(quilt_id).map_err(|error| {
+                anyhow!("invalid quilt ID in the attribute of blob object {object_id}: {error}")
+            })?,
+            None => blob.blob.blob_id,
+        };
+        self.quilt_ids.insert(object_id, quilt_id).await;
+        Ok(quilt_id)
+    }
+
+    async fn index(
+        &self,
+        client: &impl WalrusReadClient,
+        quilt_id: &BlobId,
+    ) -> Result<Arc<SiteIndex>, GetBlobError> {
+        if let Some(index) = self.indices.get(quilt_id).await {
+            return Ok(index);
+        }
+        let index = Arc::new(SiteIndex::load(client, quilt_id).await?);
+        self.indices.insert(*quilt_id, index.clone()).await;
+        Ok(index)
+    }
+}
+
+/// Serves the static websites configured on the aggregator.
+///
+/// Registered as the fallback of the aggregator, such that the API endpoints take precedence over
+/// the paths of the sites.
+#[tracing::instrument(level = Level::ERROR, skip_all, fields(%uri))]
+pub(super) async fn serve_site<T: WalrusReadClient>(
+    State((client, sites)): State<(Arc<T>, Arc<Sites>)>,
+    request_headers: HeaderMap,
+    uri: Uri,
+) -> Response {
+    let host = request_host(&uri, &request_headers);
+    let Some((site, path)) = sites.find_site(host.as_deref(), uri.path()) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let prefix = site.path_prefix.as_deref().unwrap_or_default();
+    if path.is_empty() {
+        // Relative links in the site only resolve correctly if the root ends with a slash.
+        return redirect(
+            StatusCode::PERMANENT_REDIRECT,
+            &with_query(&format!("{}/", uri.path()), &uri),
+        );
+    }
+
+    match serve_site_path(client.as_ref(), &sites, site, path, &request_headers).await {
+        Ok(SiteResponse::Response(response)) => response,
+        Ok(SiteResponse::Redirect(status, target)) => {
+            let target = if target.starts_with('/') {
+                format!("{prefix}{target}")
+            } else {
+                target
+            };
+            redirect(status, &target)
+        }
+        Ok(SiteResponse::AppendSlash) => redirect(
+            StatusCode::PERMANENT_REDIRECT,
+            &with_query(&format!("{}/", uri.path()), &uri),
+        ),
+        Err(error) => {
+            match &error {
+                GetBlobError::Internal(error) => {
+                    tracing::error!(site = %site.name, ?error, "error serving site")
+                }
+                _ => tracing::debug!(site = %site.name, ?error, "unable to serve site"),
+            }
+            error.to_response()
+        }
+    }
+}
+
+enum SiteResponse {
+    Response(Response),
+    Redirect(StatusCode, String),
+    AppendSlash,
+}
+
+async fn serve_site_path(
+    client: &impl WalrusReadClient,
+    sites: &Sites,
+    site: &SiteConfig,
+    path: &str,
+    request_headers: &HeaderMap,
+) -> Result<SiteResponse, GetBlobError> {
+    let quilt_id = sites.quilt_id(client, site).await?;
+    let index = sites.index(client, &quilt_id).await?;
+    let path = urlencoding::decode(path).map_err(|_| GetBlobError::QuiltPatchNotFound)?;
+    let (status, identifier) = match index.resolve(&path) {
+        Resolution::Patch(identifier) => (StatusCode::OK, identifier),
+        Resolution::Redirect(status, target) => {
+            return Ok(SiteResponse::Redirect(status, target));
+        }
+        Resolution::AppendSlash => return Ok(SiteResponse::AppendSlash),
+        Resolution::NotFound(Some(not_found_page)) => {
+            (StatusCode::NOT_FOUND, not_found_page.to_owned())
+        }
+        Resolution::NotFound(None) => return Err(GetBlobError::QuiltPatchNotFound),
+    };
+    // The 404 page configured in the manifest may not exist.
+    let patch_id = index
+        .patches
+        .get(&identifier)
+        .ok_or(GetBlobError::QuiltPatchNotFound)?;
+
+    if status == StatusCode::OK
+        && let Some(etag) = matching_etag(request_headers, patch_id)
+    {
+        let mut response = StatusCode::NOT_MODIFIED.into_response();
+        insert_cache_headers(response.headers_mut(), site, &etag);
+        return Ok(SiteResponse::Response(response));
+    }
+
+    let blob = client
+        .get_blob_by_quilt_id_and_identifier(&quilt_id, &identifier)
+        .await?;
+    build_site_response(blob, status, request_headers, site, patch_id)
+        .await
+        .map(SiteResponse::Response)
+}
+
+async fn build_site_response(
+    blob: QuiltStoreBlob<'static>,
+    status: StatusCode,
+    request_headers: &HeaderMap,
+    site: &SiteConfig,
+    patch_id: &QuiltPatchId,
+) -> Result<Response, GetBlobError> {
+    let (blob, content_encoding) = negotiate_content_encoding(blob, request_headers).await?;
+
+    let content_type = blob
+        .tags()
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(CONTENT_TYPE_TAG))
+        .and_then(|(_, value)| HeaderValue::from_str(value).ok())
+        .unwrap_or_else(|| {
+            let mime = mime_guess::from_path(blob.identifier()).first_or_octet_stream();
+            HeaderValue::from_str(mime.as_ref()).expect("MIME types are valid header values")
+        });
+
+    let mut response = (status, blob.into_data()).into_response();
+    let headers = response.headers_mut();
+    headers.insert(CONTENT_TYPE, content_type);
+    // Prevent the browser from trying to guess the MIME type to avoid dangerous inferences.
+    headers.insert(X_CONTENT_TYPE_OPTIONS, HeaderValue::from_static("nosniff"));
+    insert_cache_headers(headers, site, &site_etag(patch_id, content_encoding));
+    if let Some(content_encoding) = content_encoding {
+        headers.insert(CONTENT_ENCODING, HeaderValue::from_static(content_encoding));
+    }
+    Ok(response)
+}
+
+/// Inserts the caching headers of a site response.
+///
+/// The representation of a patch depends on the content encodings accepted by the client, so
+/// caches have to key the responses by the `Accept-Encoding` header.
+fn insert_cache_headers(headers: &mut HeaderMap, site: &SiteConfig, etag: &str) {
+    headers.insert(
+        CACHE_CONTROL,
+        HeaderValue::from_str(&format!("public, max-age={}", site.cache_max_age.as_secs()))
+            .expect("the cache control value only contains visible ASCII characters"),
+    );
+    headers.insert(
+        ETAG,
+        HeaderValue::from_str(etag).expect("the ETag only contains visible ASCII characters"),
+    );
+    headers.insert(VARY, HeaderValue::from_static("accept-encoding"));
+}
+
+/// Returns the ETag of the patch served with the given content encoding.
+///
+/// Quilts are immutable, so the patch ID, which contains the quilt ID, and the content encoding
+/// identify the representation of the response.
+fn site_etag(patch_id: &QuiltPatchId, content_encoding: Option<&str>) -> String {
+    match content_encoding {
+        Some(content_encoding) => format!("\"{patch_id}.{content_encoding}\""),
+        None => format!("\"{patch_id}\""),
+    }
+}
+
+/// Returns the ETag in the `If-None-Match` header of the request that matches the patch, if any.
+///
+/// An ETag with a content encoding only matches if the request still accepts that encoding, as
+/// the patch would otherwise be served decompressed.
+fn matching_etag(request_headers: &HeaderMap, patch_id: &QuiltPatchId) -> Option<String> {
+    let patch_id_string = patch_id.to_string();
+    request_headers
+        .get_all(IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|tag| tag.trim().trim_start_matches("W/").trim_matches('"'))
+        .find_map(|tag| {
+            if tag == "*" {
+                return Some(site_etag(patch_id, None));
+            }
+            let content_encoding = match tag.strip_prefix(&patch_id_string)? {
+                "" => None,
+                suffix => Some(suffix.strip_prefix('.')?),
+            };
+            content_encoding
+                .is_none_or(|encoding| accepts_encoding(request_headers, encoding))
+                .then(|| site_etag(patch_id, content_encoding))
+        })
+}
+
+/// Returns the lowercase hostname of the request, without the port.
+fn request_host(uri: &Uri, request_headers: &HeaderMap) -> Option<String> {
+    let host = match uri.host() {
+        Some(host) => host.to_owned(),
+        None => {
+            let host = request_headers.get(HOST)?.to_str().ok()?;
+            Authority::from_str(host).ok()?.host().to_owned()
+        }
+    };
+    Some(host.to_ascii_lowercase())
+}
+
+fn with_query(path: &str, uri: &Uri) -> String {
+    match uri.query() {
+        Some(query) => format!("{path}?{query}"),
+        None => path.to_owned(),
+    }
+}
+
+fn redirect(status: StatusCode, location: &str) -> Response {
+    match HeaderValue::from_str(location) {
+        Ok(location) => (status, [(LOCATION, location)]).into_response(),
+        Err(_) => {
+            GetBlobError::Internal(anyhow!("invalid redirect target '{location}'")).to_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::ACCEPT_ENCODING;
+    use walrus_test_utils::param_test;
+
+    use super::*;
+
+    const QUILT_ID: &str = "rkcHpHQrornOymttgvSq3zvcmQEsMqzmeUM1HSY4ShU";
+    const OBJECT_ID: &str = "0x1111111111111111111111111111111111111111111111111111111111111111";
+
+    fn sites() -> Sites {
+        let config: SitesConfig = serde_yaml::from_str(&indoc::formatdoc! {"
+            sites:
+              - name: docs
+                hostnames: [docs.example.com]
+                quilt_id: {QUILT_ID}
+                cache_max_age_secs: 600
+              - name: blog
+                path_prefix: /blog
+                blob_object_id: '{OBJECT_ID}'
+              - name: docs-v1
+                hostnames: [docs.example.com]
+                path_prefix: /v1
+                quilt_id: {QUILT_ID}
+        "})
+        .expect("the config is valid");
+        Sites::new(config).expect("the config is consistent")
+    }
+
+    #[test]
+    fn parses_sites_config() {
+        let sites = sites();
+        assert_eq!(sites.len(), 3);
+        assert_eq!(
+            sites.sites[0].source,
+            SiteSource::QuiltId(QUILT_ID.parse().expect("valid blob ID"))
+        );
+        assert_eq!(sites.sites[0].cache_max_age, Duration::from_secs(600));
+        assert_eq!(
+            sites.sites[1].source,
+            SiteSource::BlobObjectId(OBJECT_ID.parse().expect("valid object ID"))
+        );
+        assert_eq!(sites.sites[1].cache_max_age, default::cache_max_age());
+    }
+
+    fn site(name: &str, hostnames: &[&str], path_prefix: Option<&str>) -> SiteConfig {
+        SiteConfig {
+            name: name.to_owned(),
+            hostnames: hostnames
+                .iter()
+                .map(|&hostname| hostname.to_owned())
+                .collect(),
+            path_prefix: path_prefix.map(str::to_owned),
+            source: SiteSource::QuiltId(QUILT_ID.parse().expect("valid blob ID")),
+            cache_max_age: default::cache_max_age(),
+        }
+    }
+
+    param_test! {
+        rejects_inconsistent_sites_config: [
+            duplicate_name: (vec![site("a", &[], Some("/a")), site("a", &[], Some("/b"))]),
+            no_mount: (vec![site("a", &[], None)]),
+            root_prefix: (vec![site("a", &[], Some("/"))]),
+            trailing_slash: (vec![site("a", &[], Some("/a/"))]),
+            relative_prefix: (vec![site("a", &[], Some("a"))]),
+            same_mount: (vec![site("a", &["x.com"], None), site("b", &["X.com", "y.com"], None)]),
+        ]
+    }
+    fn rejects_inconsistent_sites_config(sites: Vec<SiteConfig>) {
+        let config = SitesConfig {
+            object_refresh_interval: default::object_refresh_interval(),
+            sites,
+        };
+        assert!(Sites::new(config).is_err());
+    }
+
+    param_test! {
+        finds_site: [
+            host_root: (Some("docs.example.com"), "/guide.html", Some(("docs", "/guide.html"))),
+            host_case_insensitive: (Some("Docs.Example.com"), "/", Some(("docs", "/"))),
+            longer_prefix: (Some("docs.example.com"), "/v1/", Some(("docs-v1", "/"))),
+            prefix_root: (Some("docs.example.com"), "/v1", Some(("docs-v1", ""))),
+            not_a_prefix: (Some("docs.example.com"), "/v10", Some(("docs", "/v10"))),
+            any_host: (Some("other.com"), "/blog/post", Some(("blog", "/post"))),
+            no_host: (None, "/blog/", Some(("blog", "/"))),
+            host_over_any_host: (Some("docs.example.com"), "/blog", Some(("docs", "/blog"))),
+            unknown: (Some("other.com"), "/v1/", None),
+        ]
+    }
+    fn finds_site(host: Option<&str>, path: &str, expected: Option<(&str, &str)>) {
+        let sites = sites();
+        let host = host.map(|host| host.to_ascii_lowercase());
+        assert_eq!(
+            sites
+                .find_site(host.as_deref(), path)
+                .map(|(site, path)| (site.name.as_str(), path)),
+            expected
+        );
+    }
+
+    fn patch_id(index: u8) -> QuiltPatchId {
+        QuiltPatchId::new(
+            BlobId::from_str(QUILT_ID).expect("the quilt ID is valid"),
+            vec![1, index],
+        )
+    }
+
+    fn site_index() -> SiteIndex {
+        let manifest = SiteManifest::parse(
+            br#"{
+                "notFoundPage": "404.html",
+                "redirects": [
+                    {"from": "/old.html", "to": "/new.html"},
+                    {"from": "/posts/*", "to": "/blog/*", "status": 302},
+                    {"from": "/external", "to": "https://walrus.xyz", "status": 307}
+                ]
+            }"#,
+        )
+        .expect("the manifest is valid");
+        SiteIndex {
+            patches: ["index.html", "404.html", "new.html", "guide/index.html"]
+                .into_iter()
+                .zip(0..)
+                .map(|(identifier, index)| (identifier.to_owned(), patch_id(index)))
+                .collect(),
+            manifest,
+        }
+    }
+
+    param_test! {
+        resolves_paths: [
+            root: ("/", Resolution::Patch("index.html".to_owned())),
+            file: ("/new.html", Resolution::Patch("new.html".to_owned())),
+            directory: ("/guide/", Resolution::Patch("guide/index.html".to_owned())),
+            directory_without_slash: ("/guide", Resolution::AppendSlash),
+            redirect: (
+                "/old.html",
+                Resolution::Redirect(StatusCode::MOVED_PERMANENTLY, "/new.html".to_owned())
+            ),
+            splat_redirect: (
+                "/posts/2025/hello",
+                Resolution::Redirect(StatusCode::FOUND, "/blog/2025/hello".to_owned())
+            ),
+            external_redirect: (
+                "/external",
+                Resolution::Redirect(
+                    StatusCode::TEMPORARY_REDIRECT,
+                    "https://walrus.xyz".to_owned()
+                )
+            ),
+            missing: ("/missing.html", Resolution::NotFound(Some("404.html"))),
+            missing_index: ("/missing/", Resolution::NotFound(Some("404.html"))),
+        ]
+    }
+    fn resolves_paths(path: &str, expected: Resolution) {
+        assert_eq!(site_index().resolve(path), expected);
+    }
+
+    #[test]
+    fn rejects_invalid_manifest() {
+        assert!(SiteManifest::parse(br#"{"redirects": [{"from": "/a", "to": "/b"}]}"#).is_ok());
+        assert!(
+            SiteManifest::parse(br#"{"redirects": [{"from": "/a", "to": "/b", "status": 200}]}"#)
+                .is_err()
+        );
+        assert!(SiteManifest::parse(br#"{"redirects": [{"from": "a", "to": "/b"}]}"#).is_err());
+        assert!(SiteManifest::parse(b"not json").is_err());
+    }
+
+    #[test]
+    fn checks_if_none_match() {
+        let patch = patch_id(0);
+        let mut headers = HeaderMap::new();
+        assert_eq!(matching_etag(&headers, &patch), None);
+
+        headers.insert(
+            IF_NONE_MATCH,
+            HeaderValue::from_str(&format!("\"other\", W/{}", site_etag(&patch, None)))
+                .expect("valid header value"),
+        );
+        assert_eq!(
+            matching_etag(&headers, &patch),
+            Some(site_etag(&patch, None))
+        );
+        assert_eq!(matching_etag(&headers, &patch_id(1)), None);
+
+        // An ETag with a content encoding only matches if the encoding is still accepted.
+        let gzip_etag = site_etag(&patch, Some("gzip"));
+        headers.insert(
+            IF_NONE_MATCH,
+            HeaderValue::from_str(&gzip_etag).expect("valid header value"),
+        );
+        assert_eq!(matching_etag(&headers, &patch), None);
+        headers.insert(ACCEPT_ENCODING, HeaderValue::from_static("gzip, br"));
+        assert_eq!(matching_etag(&headers, &patch), Some(gzip_etag));
+    }
+}
@@ -0,0 +1,916 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tenants of a shared publisher.
+//!
+//! A publisher can be configured with a set of tenants, each identified by API keys or by the
+//! subjects of the JWTs used to authenticate uploads. Every store request is then attributed to a
+//! tenant, checked against the tenant's quotas, and accounted for in a local usage database.
+//!
+//! To enforce the daily quota of uploaded bytes across concurrent requests, the size of a blob is
+//! reserved before it is stored: when the request is admitted if its length is known in advance,
+//! and otherwise at the latest once the blob has been received. The reservation is turned into
+//! usage when the upload succeeds, and released otherwise.
+
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use anyhow::Context;
+use axum::{
+    Json,
+    extract::{Query, Request, State},
+    http::HeaderMap,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use axum_extra::{
+    TypedHeader,
+    headers::{Authorization, authorization::Bearer},
+};
+use chrono::{DateTime, Utc};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use subtle::ConstantTimeEq as _;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use typed_store::{
+    Map,
+    TypedStoreError,
+    rocks::{self, DBMap, MetricConf, ReadWriteOptions},
+};
+use walrus_core::EpochCount;
+use walrus_proc_macros::RestApiError;
+use walrus_sdk::client::responses::BlobStoreResult;
+
+use super::{
+    auth::Claim,
+    routes::{PublisherQuery, blob_size_hint},
+};
+use crate::common::api::RestApiError;
+
+/// The header in which clients can pass their tenant API key.
+pub const TENANT_API_KEY_HEADER: &str = "x-walrus-api-key";
+
+/// The endpoint reporting the usage of the tenants.
+pub const TENANT_USAGE_ENDPOINT: &str = "/v1/admin/usage";
+
+const TENANTS_DOMAIN: &str = "tenants.publisher.walrus.space";
+
+/// The name of the column family storing the daily usage of the tenants.
+const TENANT_USAGE_STORE: &str = "tenant_usage";
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// The default number of days covered by the usage report.
+const DEFAULT_USAGE_REPORT_DAYS: u32 = 30;
+
+/// The configuration of the tenants of a publisher.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TenantsConfig {
+    /// The directory of the database in which the usage of the tenants is stored.
+    #[serde(deserialize_with = "walrus_utils::config::resolve_home_dir")]
+    pub db_path: PathBuf,
+    /// The bearer token required to access the usage report.
+    ///
+    /// If not set, the usage report endpoint is not exposed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub admin_token: Option<String>,
+    /// The tenants of the publisher.
+    pub tenants: Vec<TenantConfig>,
+}
+
+impl TenantsConfig {
+    /// Loads the tenants configuration from the YAML file at the provided path.
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        walrus_utils::load_from_yaml(path)
+    }
+
+    /// Checks that tenant names, API keys, and JWT subjects are unique.
+    fn check_consistency(&self) -> anyhow::Result<()> {
+        let mut names = HashSet::new();
+        let mut api_keys = HashSet::new();
+        let mut subjects = HashSet::new();
+        for tenant in &self.tenants {
+            anyhow::ensure!(
+                names.insert(&tenant.name),
+                "duplicate tenant name '{}'",
+                tenant.name
+            );
+            for api_key in &tenant.api_keys {
+                anyhow::ensure!(
+                    api_keys.insert(api_key),
+                    "API key of tenant '{}' is used by multiple tenants",
+                    tenant.name
+                );
+            }
+            for subject in &tenant.jwt_subjects {
+                anyhow::ensure!(
+                    subjects.insert(subject),
+                    "JWT subject '{subject}' is used by multiple tenants",
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The configuration of a single tenant.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TenantConfig {
+    /// The name of the tenant, used for reporting.
+    pub name: String,
+    /// The API keys identifying the tenant, passed in the `X-Walrus-Api-Key` header.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub api_keys: Vec<String>,
+    /// The subjects (`sub` claim) of the JWTs identifying the tenant.
+    ///
+    /// These are only taken into account if JWT authentication is enabled on the publisher.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub jwt_subjects: Vec<String>,
+    /// The quotas of the tenant.
+    #[serde(default)]
+    pub quota: TenantQuota,
+}
+
+/// The quotas of a tenant. Unset values are unlimited.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TenantQuota {
+    /// The maximum number of bytes the tenant can upload per (UTC) day.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_bytes_per_day: Option<u64>,
+    /// The maximum number of epochs for which the tenant can store a blob.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_epochs: Option<EpochCount>,
+    /// The maximum number of concurrent uploads of the tenant.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_concurrent_uploads: Option<usize>,
+}
+
+/// The usage of a tenant over a period of time.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TenantUsage {
+    /// The number of successful uploads.
+    pub uploads: u64,
+    /// The number of bytes uploaded (unencoded).
+    pub bytes_uploaded: u64,
+    /// The WAL (in FROST) spent for storage, after deducting the discount obtained through credits.
+    pub wal_spent: u64,
+    /// The WAL (in FROST) covered by credits.
+    pub credits_discount: u64,
+    /// The net SUI (in MIST) spent for gas.
+    ///
+    /// This may be negative if storage rebates exceed the gas costs.
+    pub sui_spent: i64,
+}
+
+impl TenantUsage {
+    /// Returns the usage corresponding to a single successful upload.
+    pub fn from_upload(bytes: usize, result: &BlobStoreResult, sui_spent: i64) -> Self {
+        let (wal_spent, credits_discount) = match result {
+            BlobStoreResult::NewlyCreated {
+                cost,
+                credits_discount,
+                ..
+            } => {
+                let credits_discount = credits_discount.unwrap_or_default();
+                (cost.saturating_sub(credits_discount), credits_discount)
+            }
+            _ => (0, 0),
+        };
+        Self {
+            uploads: 1,
+            bytes_uploaded: bytes as u64,
+            wal_spent,
+            credits_discount,
+            sui_spent,
+        }
+    }
+
+    fn add(&mut self, other: &Self) {
+        self.uploads = self.uploads.saturating_add(other.uploads);
+        self.bytes_uploaded = self.bytes_uploaded.saturating_add(other.bytes_uploaded);
+        self.wal_spent = self.wal_spent.saturating_add(other.wal_spent);
+        self.credits_discount = self.credits_discount.saturating_add(other.credits_discount);
+        self.sui_spent = self.sui_spent.saturating_add(other.sui_spent);
+    }
+}
+
+/// The persistent store of the daily usage of the tenants.
+#[derive(Debug)]
+struct UsageStore {
+    /// The usage, keyed by tenant name and day since the UNIX epoch.
+    usage: DBMap<(String, u32), TenantUsage>,
+    /// Serializes the read-modify-write updates of the usage.
+    update_lock: Mutex<()>,
+}
+
+impl UsageStore {
+    fn open(path: &Path) -> anyhow::Result<Self> {
+        let mut db_opts = rocksdb::Options::default();
+        db_opts.create_missing_column_families(true);
+        db_opts.create_if_missing(true);
+        let database = rocks::open_cf_opts(
+            path,
+            Some(db_opts),
+            MetricConf::new("publisher_tenants"),
+            &[(TENANT_USAGE_STORE, rocksdb::Options::default())],
+        )?;
+        let usage = DBMap::reopen(
+            &database,
+            Some(TENANT_USAGE_STORE),
+            &ReadWriteOptions::default(),
+            false,
+        )?;
+        Ok(Self {
+            usage,
+            update_lock: Mutex::new(()),
+        })
+    }
+
+    fn get(&self, tenant: &str, day: u32) -> Result<TenantUsage, TypedStoreError> {
+        Ok(self
+            .usage
+            .get(&(tenant.to_owned(), day))?
+            .unwrap_or_default())
+    }
+
+    fn record(&self, tenant: &str, day: u32, usage: &TenantUsage) -> Result<(), TypedStoreError> {
+        let _guard = self.update_lock.lock().expect("the lock is never poisoned");
+        let key = (tenant.to_owned(), day);
+        let mut current = self.usage.get(&key)?.unwrap_or_default();
+        current.add(usage);
+        self.usage.insert(&key, &current)
+    }
+
+    /// Returns the daily usage of the tenant for the days in `[from_day, to_day]`.
+    fn daily_usage(
+        &self,
+        tenant: &str,
+        from_day: u32,
+        to_day: u32,
+    ) -> Result<Vec<(u32, TenantUsage)>, TypedStoreError> {
+        self.usage
+            .safe_range_iter((tenant.to_owned(), from_day)..=(tenant.to_owned(), to_day))?
+            .map(|entry| entry.map(|((_, day), usage)| (day, usage)))
+            .collect()
+    }
+}
+
+#[derive(Debug)]
+struct Tenant {
+    config: TenantConfig,
+    upload_slots: Option<Arc<Semaphore>>,
+    /// The bytes reserved for the blobs that are currently being stored.
+    ///
+    /// The lock is also held while recording usage, such that the sum of the recorded usage and
+    /// the reserved bytes is consistent.
+    reserved_bytes: Mutex<u64>,
+}
+
+/// The tenants of a publisher, together with their usage.
+#[derive(Debug)]
+pub struct Tenants {
+    tenants: Vec<Tenant>,
+    by_api_key: HashMap<String, usize>,
+    by_jwt_subject: HashMap<String, usize>,
+    admin_token: Option<String>,
+    store: UsageStore,
+}
+
+impl Tenants {
+    /// Creates the tenants from the configuration, opening the usage database.
+    pub fn open(config: TenantsConfig) -> anyhow::Result<Self> {
+        config.check_consistency()?;
+        let store = UsageStore::open(&config.db_path).with_context(|| {
+            format!(
+                "unable to open the tenant usage database at {}",
+                config.db_path.display()
+            )
+        })?;
+
+        let mut by_api_key = HashMap::new();
+        let mut by_jwt_subject = HashMap::new();
+        let tenants = config
+            .tenants
+            .into_iter()
+            .enumerate()
+            .map(|(index, config)| {
+                by_api_key.extend(config.api_keys.iter().map(|key| (key.clone(), index)));
+                by_jwt_subject.extend(
+                    config
+                        .jwt_subjects
+                        .iter()
+                        .map(|subject| (subject.clone(), index)),
+                );
+                Tenant {
+                    upload_slots: config
+                        .quota
+                        .max_concurrent_uploads
+                        .map(|max| Arc::new(Semaphore::new(max))),
+                    config,
+                    reserved_bytes: Mutex::new(0),
+                }
+            })
+            .collect();
+
+        Ok(Self {
+            tenants,
+            by_api_key,
+            by_jwt_subject,
+            admin_token: config.admin_token,
+            store,
+        })
+    }
+
+    /// Returns true if the usage report should be exposed.
+    pub fn has_admin_token(&self) -> bool {
+        self.admin_token.is_some()
+    }
+
+    /// Identifies the tenant of a request, first through the API key header, then through the
+    /// subject of the verified JWT, if any.
+    fn identify(&self, headers: &HeaderMap, claim: Option<&Claim>) -> Result<usize, TenantError> {
+        if let Some(api_key) = headers.get(TENANT_API_KEY_HEADER) {
+            let api_key = api_key.to_str().map_err(|_| TenantError::UnknownTenant)?;
+            return self
+                .by_api_key
+                .get(api_key.trim())
+                .copied()
+                .ok_or(TenantError::UnknownTenant);
+        }
+        match claim.and_then(|claim| claim.sub.as_ref()) {
+            Some(subject) => self
+                .by_jwt_subject
+                .get(subject)
+                .copied()
+                .ok_or(TenantError::UnknownTenant),
+            None => Err(TenantError::MissingTenant),
+        }
+    }
+
+    /// Checks the quotas of the tenant for an upload and reserves an upload slot if needed.
+    ///
+    /// The upload slot is held by the returned context. If the size of the blob is known from the
+    /// request, it is reserved against the daily quota of the tenant.
+    fn admit(
+        self: &Arc<Self>,
+        index: usize,
+        epochs: EpochCount,
+        body_size_hint: http_body::SizeHint,
+    ) -> Result<TenantContext, TenantError> {
+        let tenant = &self.tenants[index];
+
+        if let Some(max_epochs) = tenant.config.quota.max_epochs
+            && epochs > max_epochs
+        {
+            return Err(TenantError::EpochsQuotaExceeded { max_epochs });
+        }
+
+        let upload_slot = tenant
+            .upload_slots
+            .as_ref()
+            .map(|slots| {
+                slots
+                    .clone()
+                    .try_acquire_owned()
+                    .map_err(|_| TenantError::TooManyConcurrentUploads)
+            })
+            .transpose()?;
+
+        // For bodies without a known length, the size is reserved once the blob is received.
+        let context = self.new_context(index, upload_slot);
+        context.reserve_bytes(body_size_hint.exact().unwrap_or(0))?;
+        Ok(context)
+    }
+
+    /// Returns the context of the tenant with the given name, if it exists.
+    pub(crate) fn context(self: &Arc<Self>, name: &str) -> Option<TenantContext> {
+        self.tenants
+            .iter()
+            .position(|tenant| tenant.config.name == name)
+            .map(|index| self.new_context(index, None))
+    }
+
+    fn new_context(
+        self: &Arc<Self>,
+        index: usize,
+        upload_slot: Option<OwnedSemaphorePermit>,
+    ) -> TenantContext {
+        TenantContext {
+            tenants: self.clone(),
+            index,
+            upload_slot: upload_slot.map(Arc::new),
+            reservation: Arc::new(Reservation {
+                tenants: self.clone(),
+                index,
+                bytes: Mutex::new(0),
+            }),
+        }
+    }
+
+    /// Atomically checks that `bytes` additional bytes fit in the daily quota of the tenant,
+    /// taking into account the bytes reserved for concurrent uploads, and reserves them.
+    fn reserve(&self, index: usize, bytes: u64) -> Result<(), TenantError> {
+        let tenant = &self.tenants[index];
+        let Some(max_bytes_per_day) = tenant.config.quota.max_bytes_per_day else {
+            return Ok(());
+        };
+        let mut reserved = tenant
+            .reserved_bytes
+            .lock()
+            .expect("the lock is never poisoned");
+        let used = self.store.get(&tenant.config.name, today())?.bytes_uploaded;
+        if used.saturating_add(*reserved).saturating_add(bytes) > max_bytes_per_day {
+            return Err(TenantError::DailyBytesQuotaExceeded { max_bytes_per_day });
+        }
+        *reserved += bytes;
+        Ok(())
+    }
+
+    /// Records the usage of the tenant, moving `reserved` bytes from the reservations to the usage.
+    fn record_usage(&self, index: usize, usage: &TenantUsage, reserved: u64) {
+        let tenant = &self.tenants[index];
+        let name = &tenant.config.name;
+        tracing::debug!(tenant = %name, ?usage, "recording tenant usage");
+        let mut reserved_bytes = tenant
+            .reserved_bytes
+            .lock()
+            .expect("the lock is never poisoned");
+        if let Err(error) = self.store.record(name, today(), usage) {
+            tracing::error!(?error, tenant = %name, "failed to record the tenant usage");
+        }
+        *reserved_bytes = reserved_bytes.saturating_sub(reserved);
+    }
+
+    /// Releases bytes that were reserved for an upload that did not succeed.
+    fn release(&self, index: usize, bytes: u64) {
+        let mut reserved_bytes = self.tenants[index]
+            .reserved_bytes
+            .lock()
+            .expect("the lock is never poisoned");
+        *reserved_bytes = reserved_bytes.saturating_sub(bytes);
+    }
+
+    fn usage_report(&self, days: u32) -> Result<UsageReport, TypedStoreError> {
+        let to_day = today();
+        let from_day = to_day.saturating_sub(days.saturating_sub(1));
+        let tenants = self
+            .tenants
+            .iter()
+            .map(|tenant| {
+                let daily = self
+                    .store
+                    .daily_usage(&tenant.config.name, from_day, to_day)?;
+                let mut total = TenantUsage::default();
+                daily.iter().for_each(|(_, usage)| total.add(usage));
+                Ok(TenantUsageReport {
+                    tenant: tenant.config.name.clone(),
+                    total,
+                    daily: daily
+                        .into_iter()
+                        .map(|(day, usage)| DailyUsage {
+                            date: format_day(day),
+                            usage,
+                        })
+                        .collect(),
+                })
+            })
+            .collect::<Result<_, TypedStoreError>>()?;
+        Ok(UsageReport {
+            from: format_day(from_day),
+            to: format_day(to_day),
+            tenants,
+        })
+    }
+}
+
+/// The bytes reserved against the daily quota of a tenant, released when dropped.
+#[derive(Debug)]
+struct Reservation {
+    tenants: Arc<Tenants>,
+    index: usize,
+    bytes: Mutex<u64>,
+}
+
+impl Drop for Reservation {
+    fn drop(&mut self) {
+        let bytes = *self.bytes.get_mut().expect("the lock is never poisoned");
+        if bytes > 0 {
+            self.tenants.release(self.index, bytes);
+        }
+    }
+}
+
+/// The tenant on whose behalf a request is served.
+///
+/// Inserted into the request extensions by the [`tenant_layer`]. The context holds the upload slot
+/// and the bytes reserved for the request, which are released once all clones of the context are
+/// dropped. Asynchronous upload jobs therefore keep the context until the blob is stored.
+#[derive(Debug, Clone)]
+pub(crate) struct TenantContext {
+    tenants: Arc<Tenants>,
+    index: usize,
+    upload_slot: Option<Arc<OwnedSemaphorePermit>>,
+    reservation: Arc<Reservation>,
+}
+
+impl TenantContext {
+    /// Returns the name of the tenant.
+    pub(crate) fn name(&self) -> &str {
+        &self.tenants.tenants[self.index].config.name
+    }
+
+    /// Ensures that at least `bytes` are reserved against the daily quota of the tenant.
+    ///
+    /// Must be called with the exact size of the blob before storing it.
+    pub(crate) fn reserve_bytes(&self, bytes: u64) -> Result<(), TenantError> {
+        let mut reserved = self
+            .reservation
+            .bytes
+            .lock()
+            .expect("the lock is never poisoned");
+        if bytes > *reserved {
+            self.tenants.reserve(self.index, bytes - *reserved)?;
+            *reserved = bytes;
+        }
+        Ok(())
+    }
+
+    /// Records the usage of a successful upload, consuming the corresponding reservation.
+    pub(crate) fn record_usage(&self, usage: &TenantUsage) {
+        let mut reserved = self
+            .reservation
+            .bytes
+            .lock()
+            .expect("the lock is never poisoned");
+        if usage.bytes_uploaded > *reserved && self.has_bytes_quota() {
+            tracing::error!(
+                tenant = %self.name(),
+                bytes_uploaded = usage.bytes_uploaded,
+                reserved = *reserved,
+                "recording the usage of an upload that was not reserved against the quota"
+            );
+        }
+        let consumed = usage.bytes_uploaded.min(*reserved);
+        self.tenants.record_usage(self.index, usage, consumed);
+        *reserved -= consumed;
+    }
+
+    fn has_bytes_quota(&self) -> bool {
+        self.tenants.tenants[self.index]
+            .config
+            .quota
+            .max_bytes_per_day
+            .is_some()
+    }
+}
+
+/// Identifies the tenant of a store request and enforces its quotas.
+///
+/// Must be applied after the authentication layer, so that the subject of the JWT can be trusted.
+pub(crate) async fn tenant_layer(
+    State(tenants): State<Arc<Tenants>>,
+    Query(query): Query<PublisherQuery>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let index = match tenants.identify(request.headers(), request.extensions().get::<Claim>()) {
+        Ok(index) => index,
+        Err(error) => return error.into_response(),
+    };
+    let tenant_name = &tenants.tenants[index].config.name;
+    tracing::debug!(tenant = %tenant_name, "identified the tenant of a store request");
+
+    // The context holds the upload slot until the request has been fully served, or until the
+    // upload job created by the request is finished.
+    let context = match tenants.admit(index, query.epochs, blob_size_hint(&request)) {
+        Ok(context) => context,
+        Err(error) => {
+            tracing::debug!(?error, tenant = %tenant_name, "rejecting store request");
+            return error.into_response();
+        }
+    };
+
+    request.extensions_mut().insert(context);
+    next.run(request).await
+}
+
+/// Identifies the tenant of a request to an existing upload or upload job.
+///
+/// In contrast to the [`tenant_layer`], the request is not admitted against the quotas of the
+/// tenant, as it does not store a blob by itself. Must be applied after the authentication layer,
+/// so that the subject of the JWT can be trusted.
+pub(crate) async fn tenant_identity_layer(
+    State(tenants): State<Arc<Tenants>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let index = match tenants.identify(request.headers(), request.extensions().get::<Claim>()) {
+        Ok(index) => index,
+        Err(error) => return error.into_response(),
+    };
+    let context = tenants.new_context(index, None);
+    tracing::debug!(tenant = %context.name(), "identified the tenant of a request");
+
+    request.extensions_mut().insert(context);
+    next.run(request).await
+}
+
+/// The query parameters of the usage report.
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct UsageReportQuery {
+    /// The number of days (including today) covered by the report.
+    #[serde(default)]
+    days: Option<u32>,
+}
+
+/// The usage of all tenants over a period of time.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageReport {
+    /// The first (UTC) day of the report.
+    pub from: String,
+    /// The last (UTC) day of the report.
+    pub to: String,
+    /// The usage of the individual tenants.
+    pub tenants: Vec<TenantUsageReport>,
+}
+
+/// The usage of a single tenant over a period of time.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TenantUsageReport {
+    /// The name of the tenant.
+    pub tenant: String,
+    /// The total usage over the period.
+    pub total: TenantUsage,
+    /// The usage on the days with activity.
+    pub daily: Vec<DailyUsage>,
+}
+
+/// The usage of a tenant on a single (UTC) day.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DailyUsage {
+    /// The day, formatted as `YYYY-MM-DD`.
+    pub date: String,
+    /// The usage on that day.
+    pub usage: TenantUsage,
+}
+
+/// Reports the usage of all tenants. Requires the admin bearer token.
+pub(crate) async fn get_usage(
+    State(tenants): State<Arc<Tenants>>,
+    Query(query): Query<UsageReportQuery>,
+    bearer_header: Option<TypedHeader<Authorization<Bearer>>>,
+) -> Response {
+    let authorized = matches!(
+        (&tenants.admin_token, bearer_header),
+        (Some(admin_token), Some(TypedHeader(header)))
+            if bool::from(header.token().trim().as_bytes().ct_eq(admin_token.as_bytes()))
+    );
+    if !authorized {
+        return TenantError::Unauthorized.into_response();
+    }
+
+    let days = query
+        .days
+        .unwrap_or(DEFAULT_USAGE_REPORT_DAYS)
+        .clamp(1, 366);
+    match tenants.usage_report(days) {
+        Ok(report) => (StatusCode::OK, Json(report)).into_response(),
+        Err(error) => TenantError::from(error).into_response(),
+    }
+}
+
+/// Returns the current (UTC) day, counted since the UNIX epoch.
+fn today() -> u32 {
+    u32::try_from(Utc::now().timestamp().div_euclid(SECONDS_PER_DAY))
+        .expect("we are between 1970 and the year 11 million")
+}
+
+fn format_day(day: u32) -> String {
+    DateTime::from_timestamp(i64::from(day) * SECONDS_PER_DAY, 0)
+        .map(|date| date.date_naive().to_string())
+        .unwrap_or_else(|| day.to_string())
+}
+
+/// The errors returned when a store request cannot be attributed to a tenant or exceeds its
+/// quotas.
+#[derive(Debug, thiserror::Error, RestApiError)]
+#[rest_api_error(domain = TENANTS_DOMAIN)]
+pub(crate) enum TenantError {
+    /// The request does not identify a tenant.
+    #[error(
+        "the request does not identify a tenant; provide an API key in the \
+        `X-Walrus-Api-Key` header or a JWT with a subject"
+    )]
+    #[rest_api_error(reason = "MISSING_TENANT", status = ApiStatusCode::Unauthenticated)]
+    MissingTenant,
+
+    /// The API key or JWT subject does not belong to any tenant.
+    #[error("the API key or JWT subject does not belong to any tenant")]
+    #[rest_api_error(reason = "UNKNOWN_TENANT", status = ApiStatusCode::PermissionDenied)]
+    UnknownTenant,
+
+    /// The requested number of epochs exceeds the quota of the tenant.
+    #[error("the tenant can store blobs for at most {max_epochs} epochs")]
+    #[rest_api_error(reason = "EPOCHS_QUOTA_EXCEEDED", status = ApiStatusCode::PermissionDenied)]
+    EpochsQuotaExceeded {
+        /// The maximum number of epochs allowed for the tenant.
+        max_epochs: EpochCount,
+    },
+
+    /// The tenant has exhausted its daily upload quota.
+    #[error("the tenant has exhausted its daily quota of {max_bytes_per_day} bytes")]
+    #[rest_api_error(
+        reason = "DAILY_BYTES_QUOTA_EXCEEDED",
+        status = ApiStatusCode::ResourceExhausted
+    )]
+    DailyBytesQuotaExceeded {
+        /// The maximum number of bytes the tenant can upload per day.
+        max_bytes_per_day: u64,
+    },
+
+    /// The tenant has reached its maximum number of concurrent uploads.
+    #[error("the tenant has reached its maximum number of concurrent uploads")]
+    #[rest_api_error(
+        reason = "TOO_MANY_CONCURRENT_UPLOADS",
+        status = ApiStatusCode::ResourceExhausted
+    )]
+    TooManyConcurrentUploads,
+
+    /// The admin token is missing or invalid.
+    #[error("the admin token is missing or invalid")]
+    #[rest_api_error(reason = "UNAUTHORIZED", status = ApiStatusCode::Unauthenticated)]
+    Unauthorized,
+
+    /// Other errors that are not covered by the other variants.
+    #[error("an internal error occurred")]
+    #[rest_api_error(delegate)]
+    Internal(#[from] anyhow::Error),
+}
+
+impl From<TypedStoreError> for TenantError {
+    fn from(error: TypedStoreError) -> Self {
+        Self::Internal(error.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::HeaderValue;
+
+    use super::*;
+
+    fn tenants_config(db_path: PathBuf) -> TenantsConfig {
+        TenantsConfig {
+            db_path,
+            admin_token: Some("admin".to_owned()),
+            tenants: vec![
+                TenantConfig {
+                    name: "team-a".to_owned(),
+                    api_keys: vec!["key-a".to_owned()],
+                    jwt_subjects: vec![],
+                    quota: TenantQuota {
+                        max_bytes_per_day: Some(100),
+                        max_epochs: Some(5),
+                        max_concurrent_uploads: Some(1),
+                    },
+                },
+                TenantConfig {
+                    name: "team-b".to_owned(),
+                    api_keys: vec![],
+                    jwt_subjects: vec!["subject-b".to_owned()],
+                    quota: TenantQuota::default(),
+                },
+            ],
+        }
+    }
+
+    fn headers_with_api_key(api_key: &'static str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(TENANT_API_KEY_HEADER, HeaderValue::from_static(api_key));
+        headers
+    }
+
+    #[test]
+    fn identifies_tenants_by_api_key_and_subject() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let tenants = Arc::new(Tenants::open(tenants_config(dir.path().to_owned()))?);
+
+        assert_eq!(tenants.identify(&headers_with_api_key("key-a"), None)?, 0);
+        assert!(matches!(
+            tenants.identify(&headers_with_api_key("key-c"), None),
+            Err(TenantError::UnknownTenant)
+        ));
+
+        let claim = Claim {
+            sub: Some("subject-b".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(tenants.identify(&HeaderMap::new(), Some(&claim))?, 1);
+        assert!(matches!(
+            tenants.identify(&HeaderMap::new(), None),
+            Err(TenantError::MissingTenant)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn enforces_quotas_and_accounts_usage() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let tenants = Arc::new(Tenants::open(tenants_config(dir.path().to_owned()))?);
+
+        assert!(matches!(
+            tenants.admit(0, 6, http_body::SizeHint::with_exact(10)),
+            Err(TenantError::EpochsQuotaExceeded { max_epochs: 5 })
+        ));
+
+        let context = tenants.admit(0, 5, http_body::SizeHint::with_exact(10))?;
+        assert!(matches!(
+            tenants.admit(0, 5, http_body::SizeHint::with_exact(10)),
+            Err(TenantError::TooManyConcurrentUploads)
+        ));
+        // The upload slot is held by all clones of the context.
+        let job_context = context.clone();
+        drop(context);
+        assert!(matches!(
+            tenants.admit(0, 5, http_body::SizeHint::with_exact(10)),
+            Err(TenantError::TooManyConcurrentUploads)
+        ));
+
+        let usage = TenantUsage {
+            uploads: 1,
+            bytes_uploaded: 95,
+            wal_spent: 1_000,
+            credits_discount: 100,
+            sui_spent: 2_000,
+        };
+        job_context.reserve_bytes(95)?;
+        job_context.record_usage(&usage);
+        drop(job_context);
+        assert!(matches!(
+            tenants.admit(0, 5, http_body::SizeHint::with_exact(10)),
+            Err(TenantError::DailyBytesQuotaExceeded { .. })
+        ));
+        // Tenants without quotas are not limited.
+        assert!(
+            tenants
+                .admit(1, 1_000, http_body::SizeHint::with_exact(1_000))?
+                .upload_slot
+                .is_none()
+        );
+
+        let report = tenants.usage_report(7)?;
+        assert_eq!(report.tenants.len(), 2);
+        assert_eq!(report.tenants[0].total, usage);
+        assert_eq!(report.tenants[0].daily.len(), 1);
+        assert!(report.tenants[1].daily.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn reserves_bytes_of_concurrent_and_chunked_uploads() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let mut config = tenants_config(dir.path().to_owned());
+        config.tenants[0].quota.max_concurrent_uploads = None;
+        let tenants = Arc::new(Tenants::open(config)?);
+
+        // Bodies with a known length are reserved at admission, so concurrent uploads cannot
+        // exceed the quota together.
+        let first = tenants.admit(0, 5, http_body::SizeHint::with_exact(60))?;
+        assert!(matches!(
+            tenants.admit(0, 5, http_body::SizeHint::with_exact(60)),
+            Err(TenantError::DailyBytesQuotaExceeded { .. })
+        ));
+
+        // Chunked bodies are admitted, but their size is reserved before storing the blob.
+        let chunked = tenants.admit(0, 5, http_body::SizeHint::default())?;
+        assert!(matches!(
+            chunked.reserve_bytes(60),
+            Err(TenantError::DailyBytesQuotaExceeded { .. })
+        ));
+        chunked.reserve_bytes(40)?;
+
+        // Failed uploads release their reservation.
+        drop(first);
+        let second = tenants.admit(0, 5, http_body::SizeHint::with_exact(60))?;
+        second.record_usage(&TenantUsage {
+            uploads: 1,
+            bytes_uploaded: 60,
+            ..Default::default()
+        });
+        drop(second);
+        assert!(matches!(
+            tenants.admit(0, 5, http_body::SizeHint::with_exact(1)),
+            Err(TenantError::DailyBytesQuotaExceeded { .. })
+        ));
+        drop(chunked);
+        tenants.admit(0, 5, http_body::SizeHint::with_exact(40))?;
+        Ok(())
+    }
+}
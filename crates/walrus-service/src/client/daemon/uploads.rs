@@ -0,0 +1,1057 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Resumable uploads of blobs to the publisher.
+//!
+//! Clients that cannot upload a blob in a single request, e.g., because of unreliable connections
+//! or because the blob exceeds the maximum body size, create an upload session for the total length
+//! of the blob, upload the blob in chunks at arbitrary offsets, query the received byte ranges to
+//! resume after an interruption, and finally finalize the upload to store the blob on Walrus. The
+//! protocol loosely follows the semantics of the [tus](https://tus.io) resumable upload protocol.
+//!
+//! The received data is staged on disk in the uploads directory, and sessions that are not updated
+//! within the configured expiry are removed. The number of unfinished sessions of a client is
+//! limited, so that a single client cannot fill the uploads directory.
+//!
+//! All requests to an upload are only served if they are made on behalf of the same tenant and JWT
+//! subject as the request creating it. Uploads created by anonymous clients, i.e., without a tenant
+//! and JWT subject, are bound to an unguessable access token that is returned when creating them
+//! and that must be presented in all further requests to the upload.
+
+use std::{
+    collections::HashMap,
+    io::SeekFrom,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use anyhow::Context;
+use axum::body::Bytes;
+use chrono::{DateTime, Utc};
+use rand::{Rng, distributions::Alphanumeric};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use serde_with::{DurationSeconds, serde_as};
+use tokio::{
+    io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt},
+    sync::RwLock,
+};
+use utoipa::ToSchema;
+use uuid::Uuid;
+use walrus_proc_macros::RestApiError;
+use walrus_storage_node_client::api::errors::DAEMON_ERROR_DOMAIN as ERROR_DOMAIN;
+
+use super::{auth::Claim, jobs::StoreParameters, tenants::TenantContext};
+use crate::common::api::RestApiError;
+
+/// The interval at which upload sessions are checked for expiration.
+const PRUNING_INTERVAL: Duration = Duration::from_secs(10 * 60);
+/// The length of the access tokens of anonymous clients, about 256 bits of entropy.
+const ACCESS_TOKEN_LENGTH: usize = 43;
+
+/// The configuration of the resumable uploads.
+#[serde_as]
+#[derive(Debug, Clone, clap::Parser, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+#[command(rename_all = "kebab-case")]
+#[serde(rename_all = "camelCase")]
+pub struct UploadsConfig {
+    /// The directory in which the data of resumable uploads is staged.
+    ///
+    /// If not set, resumable uploads are disabled.
+    #[arg(long)]
+    #[serde(deserialize_with = "walrus_utils::config::resolve_home_dir_option")]
+    pub uploads_dir: Option<PathBuf>,
+    /// The maximum size of a blob stored through a resumable upload, in MiB.
+    ///
+    /// The blob is held in memory while it is stored on Walrus.
+    #[arg(long, default_value_t = default::max_upload_size_mib())]
+    pub max_upload_size_mib: u64,
+    /// The maximum number of unfinished uploads of a single client.
+    ///
+    /// Clients are distinguished by their tenant and JWT subject; anonymous clients share the
+    /// limit.
+    #[arg(long, default_value_t = default::max_uploads_per_client())]
+    pub max_uploads_per_client: usize,
+    /// The duration after the last received chunk after which an unfinished upload expires.
+    #[serde(rename = "upload_expiry_secs")]
+    #[arg(long, value_parser = humantime::parse_duration, default_value = "24h")]
+    #[serde_as(as = "DurationSeconds")]
+    pub upload_expiry: Duration,
+}
+
+impl Default for UploadsConfig {
+    fn default() -> Self {
+        Self {
+            uploads_dir: None,
+            max_upload_size_mib: default::max_upload_size_mib(),
+            max_uploads_per_client: default::max_uploads_per_client(),
+            upload_expiry: default::upload_expiry(),
+        }
+    }
+}
+
+mod default {
+    use std::time::Duration;
+
+    pub(crate) fn max_upload_size_mib() -> u64 {
+        1024
+    }
+
+    pub(crate) fn max_uploads_per_client() -> usize {
+        16
+    }
+
+    pub(crate) fn upload_expiry() -> Duration {
+        Duration::from_secs(24 * 60 * 60)
+    }
+}
+
+/// A range of bytes of an upload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct ByteRange {
+    /// The offset of the first byte of the range.
+    pub start: u64,
+    /// The offset after the last byte of the range.
+    pub end: u64,
+}
+
+impl ByteRange {
+    fn len(&self) -> u64 {
+        self.end - self.start
+    }
+}
+
+/// The information on a resumable upload.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UploadInfo {
+    /// The ID of the upload.
+    pub upload_id: String,
+    /// The total length of the blob in bytes.
+    pub length: u64,
+    /// The number of bytes received so far.
+    pub received_bytes: u64,
+    /// The sorted, non-overlapping ranges of bytes received so far.
+    pub received_ranges: Vec<ByteRange>,
+    /// The time at which the upload was created.
+    #[schema(value_type = String, format = DateTime)]
+    pub created_at: DateTime<Utc>,
+    /// The time at which the upload expires unless further data is received.
+    #[schema(value_type = String, format = DateTime)]
+    pub expires_at: DateTime<Utc>,
+    /// The access token of the upload, if it was created by an anonymous client.
+    ///
+    /// The token must be provided in the `X-Walrus-Access-Token` header of all further requests to
+    /// the upload and to the upload job created by finalizing it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub access_token: Option<String>,
+}
+
+impl UploadInfo {
+    /// Returns the number of bytes received contiguously from the start of the blob.
+    pub fn contiguous_offset(&self) -> u64 {
+        self.received_ranges
+            .first()
+            .filter(|range| range.start == 0)
+            .map_or(0, |range| range.end)
+    }
+}
+
+/// The client on whose behalf an upload was created.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct UploadOwner {
+    /// The tenant to which the usage of the upload is attributed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) tenant: Option<String>,
+    /// The subject of the JWT authenticating the requests.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) subject: Option<String>,
+    /// The access token of an anonymous client.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) access_token: Option<String>,
+}
+
+impl UploadOwner {
+    /// Returns the owner of a request with the given tenant, verified JWT claim, and access token.
+    ///
+    /// The access token is only considered for anonymous clients.
+    pub(crate) fn new(
+        tenant: Option<&TenantContext>,
+        claim: Option<&Claim>,
+        access_token: Option<&str>,
+    ) -> Self {
+        let mut owner = Self {
+            tenant: tenant.map(|tenant| tenant.name().to_owned()),
+            subject: claim.and_then(|claim| claim.sub.clone()),
+            access_token: None,
+        };
+        if owner.is_anonymous() {
+            owner.access_token = access_token.map(str::to_owned);
+        }
+        owner
+    }
+
+    /// Binds an anonymous owner to a new, unguessable access token.
+    ///
+    /// This is called when creating an upload or job, so that only the client that receives the
+    /// token can access it.
+    pub(crate) fn with_new_access_token(mut self) -> Self {
+        if self.is_anonymous() {
+            self.access_token = Some(
+                rand::thread_rng()
+                    .sample_iter(Alphanumeric)
+                    .take(ACCESS_TOKEN_LENGTH)
+                    .map(char::from)
+                    .collect(),
+            );
+        }
+        self
+    }
+
+    /// Returns true if the owner is neither identified by a tenant nor by a JWT subject.
+    fn is_anonymous(&self) -> bool {
+        self.tenant.is_none() && self.subject.is_none()
+    }
+
+    /// Returns true if both owners are the same client, regardless of their access tokens.
+    ///
+    /// Anonymous clients cannot be told apart and are therefore considered the same client.
+    fn is_same_client(&self, other: &Self) -> bool {
+        self.tenant == other.tenant && self.subject == other.subject
+    }
+}
+
+/// An upload session, as persisted in the uploads directory.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UploadRecord {
+    upload_id: String,
+    length: u64,
+    received_ranges: Vec<ByteRange>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+    parameters: StoreParameters,
+    run_async: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    callback_url: Option<String>,
+    #[serde(flatten)]
+    owner: UploadOwner,
+    /// Whether the upload is currently being finalized, in which case no chunks are accepted.
+    #[serde(skip)]
+    finalizing: bool,
+    /// Held shared while a chunk is written to the data file, and exclusively while the data file
+    /// is read to finalize the upload.
+    #[serde(skip)]
+    data_lock: Arc<RwLock<()>>,
+    /// Held while the record is persisted, such that its updates are persisted in order.
+    #[serde(skip)]
+    persist_lock: Arc<tokio::sync::Mutex<()>>,
+}
+
+impl UploadRecord {
+    fn received_bytes(&self) -> u64 {
+        self.received_ranges.iter().map(ByteRange::len).sum()
+    }
+}
+
+/// A complete upload that is being finalized.
+#[derive(Debug)]
+pub(crate) struct FinalizingUpload {
+    /// The data of the blob.
+    pub blob: Bytes,
+    /// The parameters of the store operation.
+    pub parameters: StoreParameters,
+    /// Whether the blob should be stored in an asynchronous upload job.
+    pub run_async: bool,
+    /// The URL to notify once the asynchronous upload job is finished.
+    pub callback_url: Option<Url>,
+}
+
+/// Manages the resumable uploads of the publisher.
+#[derive(Debug)]
+pub struct UploadManager {
+    uploads_dir: PathBuf,
+    uploads: Mutex<HashMap<String, UploadRecord>>,
+    max_upload_size: u64,
+    max_uploads_per_client: usize,
+    upload_expiry: Duration,
+}
+
+impl UploadManager {
+    /// Opens the upload manager, loading the uploads persisted in the uploads directory.
+    ///
+    /// Returns `None` if no uploads directory is configured.
+    pub fn open(config: &UploadsConfig) -> anyhow::Result<Option<Self>> {
+        let Some(uploads_dir) = config.uploads_dir.clone() else {
+            return Ok(None);
+        };
+        std::fs::create_dir_all(&uploads_dir).with_context(|| {
+            format!(
+                "unable to create the uploads directory {}",
+                uploads_dir.display()
+            )
+        })?;
+
+        let manager = Self {
+            uploads_dir,
+            uploads: Mutex::new(HashMap::new()),
+            max_upload_size: config.max_upload_size_mib.saturating_mul(1024 * 1024),
+            max_uploads_per_client: config.max_uploads_per_client,
+            upload_expiry: config.upload_expiry,
+        };
+        let mut uploads = HashMap::new();
+        for entry in std::fs::read_dir(&manager.uploads_dir)? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|extension| extension != "json") {
+                continue;
+            }
+            match load_record(&path) {
+                Ok(record) if manager.data_path(&record.upload_id).exists() => {
+                    uploads.insert(record.upload_id.clone(), record);
+                }
+                Ok(record) => {
+                    tracing::warn!(upload_id = %record.upload_id, "ignoring upload without data");
+                    let _ = std::fs::remove_file(&path);
+                }
+                Err(error) => {
+                    tracing::warn!(?error, path = %path.display(), "ignoring invalid upload record")
+                }
+            }
+        }
+        tracing::info!(
+            n_uploads = uploads.len(),
+            uploads_dir = %manager.uploads_dir.display(),
+            "loaded the resumable uploads"
+        );
+        *manager.lock_uploads() = uploads;
+
+        Ok(Some(manager))
+    }
+
+    /// Starts the periodic removal of expired uploads.
+    pub(crate) fn start(self: &Arc<Self>) {
+        let this = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(PRUNING_INTERVAL);
+            loop {
+                interval.tick().await;
+                this.prune_expired().await;
+            }
+        });
+    }
+
+    /// Creates a new upload session for a blob of the given length.
+    pub(crate) async fn create(
+        &self,
+        length: u64,
+        parameters: StoreParameters,
+        run_async: bool,
+        callback_url: Option<Url>,
+        owner: UploadOwner,
+    ) -> Result<UploadInfo, UploadError> {
+        if length > self.max_upload_size {
+            return Err(UploadError::UploadTooLarge {
+                max_size: self.max_upload_size,
+            });
+        }
+
+        let now = Utc::now();
+        let upload_id = Uuid::now_v7().to_string();
+        let record = UploadRecord {
+            upload_id: upload_id.clone(),
+            length,
+            received_ranges: vec![],
+            created_at: now,
+            updated_at: now,
+            parameters,
+            run_async,
+            callback_url: callback_url.map(String::from),
+            owner,
+            finalizing: false,
+            data_lock: Arc::default(),
+            persist_lock: Arc::default(),
+        };
+        let info = self.info_from_record(&record);
+
+        // The session is registered before its files are created, so that concurrent requests of
+        // the same client cannot exceed the limit.
+        {
+            let mut uploads = self.lock_uploads();
+            let n_uploads = uploads
+                .values()
+                .filter(|other| {
+                    other.owner.is_same_client(&record.owner)
+                        && (other.finalizing || self.is_live(other))
+                })
+                .count();
+            if n_uploads >= self.max_uploads_per_client {
+                return Err(UploadError::TooManyUploads {
+                    max_uploads: self.max_uploads_per_client,
+                });
+            }
+            uploads.insert(upload_id.clone(), record);
+        }
+        if let Err(error) = self.create_files(&upload_id, length).await {
+            self.remove(&upload_id).await;
+            return Err(error.into());
+        }
+
+        tracing::debug!(%upload_id, length, "created resumable upload");
+        Ok(info)
+    }
+
+    /// Creates the data file and the record of a new upload.
+    async fn create_files(&self, upload_id: &str, length: u64) -> anyhow::Result<()> {
+        // The data file is allocated sparsely with the full length of the blob, so that chunks can
+        // be written at arbitrary offsets.
+        let data = tokio::fs::File::create(self.data_path(upload_id))
+            .await
+            .context("unable to create the data file of the upload")?;
+        data.set_len(length)
+            .await
+            .context("unable to allocate the data file of the upload")?;
+        self.persist(upload_id).await
+    }
+
+    /// Returns the information on the upload, if it exists and has not expired.
+    pub(crate) fn get(&self, upload_id: &str, owner: &UploadOwner) -> Option<UploadInfo> {
+        self.lock_uploads()
+            .get(upload_id)
+            .filter(|record| record.owner == *owner)
+            .map(|record| self.info_from_record(record))
+            .filter(|info| info.expires_at > Utc::now())
+    }
+
+    /// Writes the chunk at the given offset of the upload and returns the updated information.
+    ///
+    /// Chunks may be written in any order and may overlap previously received data, which allows
+    /// clients to simply retry chunks for which they did not receive a response.
+    pub(crate) async fn write_chunk(
+        &self,
+        upload_id: &str,
+        owner: &UploadOwner,
+        offset: u64,
+        chunk: Bytes,
+    ) -> Result<UploadInfo, UploadError> {
+        let (length, data_lock) = {
+            let uploads = self.lock_uploads();
+            let record = self.live_record(&uploads, upload_id, owner)?;
+            if record.finalizing {
+                return Err(UploadError::UploadFinalizing);
+            }
+            (record.length, record.data_lock.clone())
+        };
+        let range = ByteRange {
+            start: offset,
+            end: offset.saturating_add(chunk.len() as u64),
+        };
+        if range.end > length {
+            return Err(UploadError::ChunkOutOfBounds {
+                offset,
+                chunk_length: chunk.len() as u64,
+                length,
+            });
+        }
+
+        // Finalizing the upload waits for the chunks being written; as the upload may have started
+        // finalizing while waiting for the lock, this is checked again once the lock is held.
+        let _writing = data_lock.read().await;
+        let finalizing = self
+            .live_record(&self.lock_uploads(), upload_id, owner)?
+            .finalizing;
+        if finalizing {
+            return Err(UploadError::UploadFinalizing);
+        }
+
+        if !chunk.is_empty() {
+            let mut data = tokio::fs::OpenOptions::new()
+                .write(true)
+                .open(self.data_path(upload_id))
+                .await
+                .context("unable to open the data file of the upload")?;
+            data.seek(SeekFrom::Start(offset))
+                .await
+                .context("unable to seek in the data file of the upload")?;
+            data.write_all(&chunk)
+                .await
+                .context("unable to write to the data file of the upload")?;
+            // The range is only recorded once the data is durably stored.
+            data.sync_data()
+                .await
+                .context("unable to sync the data file of the upload")?;
+        }
+
+        let info = {
+            let mut uploads = self.lock_uploads();
+            let record = uploads
+                .get_mut(upload_id)
+                .ok_or(UploadError::UploadNotFound)?;
+            insert_range(&mut record.received_ranges, range);
+            record.updated_at = Utc::now();
+            self.info_from_record(record)
+        };
+        self.persist(upload_id).await?;
+        Ok(info)
+    }
+
+    /// Marks the complete upload as being finalized and returns its data and parameters.
+    ///
+    /// Afterwards, the upload must either be removed with [`Self::remove`] or, if storing the
+    /// blob failed, released with [`Self::release`] to allow retrying.
+    pub(crate) async fn begin_finalize(
+        &self,
+        upload_id: &str,
+        owner: &UploadOwner,
+    ) -> Result<FinalizingUpload, UploadError> {
+        let (length, parameters, run_async, callback_url, data_lock) = {
+            let mut uploads = self.lock_uploads();
+            let record = self.live_record(&uploads, upload_id, owner)?;
+            if record.finalizing {
+                return Err(UploadError::UploadFinalizing);
+            }
+            let received_bytes = record.received_bytes();
+            if received_bytes < record.length {
+                return Err(UploadError::UploadIncomplete {
+                    received_bytes,
+                    length: record.length,
+                });
+            }
+            let record = uploads
+                .get_mut(upload_id)
+                .expect("the upload exists as checked above");
+            record.finalizing = true;
+            (
+                record.length,
+                record.parameters.clone(),
+                record.run_async,
+                record.callback_url.clone(),
+                record.data_lock.clone(),
+            )
+        };
+
+        let _reading = data_lock.write().await;
+        match self.read_data(upload_id, length).await {
+            Ok(blob) => Ok(FinalizingUpload {
+                blob,
+                parameters,
+                run_async,
+                // The callback URL was validated when creating the upload.
+                callback_url: callback_url.and_then(|url| Url::parse(&url).ok()),
+            }),
+            Err(error) => {
+                self.release(upload_id);
+                Err(error
+                    .context("unable to read the data of the upload")
+                    .into())
+            }
+        }
+    }
+
+    /// Reads the `length` bytes of data of the upload, without reading beyond them.
+    async fn read_data(&self, upload_id: &str, length: u64) -> anyhow::Result<Bytes> {
+        let data = tokio::fs::File::open(self.data_path(upload_id)).await?;
+        let mut blob = Vec::with_capacity(usize::try_from(length)?);
+        data.take(length).read_to_end(&mut blob).await?;
+        anyhow::ensure!(
+            blob.len() as u64 == length,
+            "the data file of the upload is shorter than the upload"
+        );
+        Ok(blob.into())
+    }
+
+    /// Releases an upload that could not be finalized, so that finalizing can be retried.
+    pub(crate) fn release(&self, upload_id: &str) {
+        if let Some(record) = self.lock_uploads().get_mut(upload_id) {
+            record.finalizing = false;
+            record.updated_at = Utc::now();
+        }
+    }
+
+    /// Cancels the upload and removes its staged data.
+    pub(crate) async fn cancel(
+        &self,
+        upload_id: &str,
+        owner: &UploadOwner,
+    ) -> Result<(), UploadError> {
+        let record = {
+            let mut uploads = self.lock_uploads();
+            if self.live_record(&uploads, upload_id, owner)?.finalizing {
+                return Err(UploadError::UploadFinalizing);
+            }
+            uploads.remove(upload_id)
+        };
+        if let Some(record) = record {
+            self.remove_files(record).await;
+        }
+        Ok(())
+    }
+
+    /// Removes the upload and its staged data after it was finalized.
+    pub(crate) async fn remove(&self, upload_id: &str) {
+        let record = self.lock_uploads().remove(upload_id);
+        if let Some(record) = record {
+            self.remove_files(record).await;
+        }
+    }
+
+    /// Removes the uploads that have not been updated within the expiry period.
+    async fn prune_expired(&self) {
+        let mut expired = vec![];
+        self.lock_uploads().retain(|_, record| {
+            if record.finalizing || self.is_live(record) {
+                return true;
+            }
+            expired.push(record.clone());
+            false
+        });
+        for record in expired {
+            tracing::debug!(upload_id = %record.upload_id, "removing expired upload");
+            self.remove_files(record).await;
+        }
+    }
+
+    /// Returns the upload if it exists, has not expired, and belongs to the owner.
+    ///
+    /// Uploads of other owners are reported as not found, to not reveal their existence.
+    fn live_record<'a>(
+        &self,
+        uploads: &'a HashMap<String, UploadRecord>,
+        upload_id: &str,
+        owner: &UploadOwner,
+    ) -> Result<&'a UploadRecord, UploadError> {
+        uploads
+            .get(upload_id)
+            .filter(|record| record.owner == *owner)
+            .filter(|record| record.finalizing || self.is_live(record))
+            .ok_or(UploadError::UploadNotFound)
+    }
+
+    fn is_live(&self, record: &UploadRecord) -> bool {
+        self.expires_at(record) > Utc::now()
+    }
+
+    fn info_from_record(&self, record: &UploadRecord) -> UploadInfo {
+        UploadInfo {
+            upload_id: record.upload_id.clone(),
+            length: record.length,
+            received_bytes: record.received_bytes(),
+            received_ranges: record.received_ranges.clone(),
+            created_at: record.created_at,
+            expires_at: self.expires_at(record),
+            access_token: record.owner.access_token.clone(),
+        }
+    }
+
+    fn expires_at(&self, record: &UploadRecord) -> DateTime<Utc> {
+        chrono::Duration::from_std(self.upload_expiry)
+            .ok()
+            .and_then(|expiry| record.updated_at.checked_add_signed(expiry))
+            .unwrap_or(DateTime::<Utc>::MAX_UTC)
+    }
+
+    /// Persists the current state of the upload, unless it has been removed.
+    ///
+    /// The state is read while holding the persistence lock of the upload, so that the latest
+    /// state of concurrently updated uploads is persisted last.
+    async fn persist(&self, upload_id: &str) -> anyhow::Result<()> {
+        let Some(persist_lock) = self
+            .lock_uploads()
+            .get(upload_id)
+            .map(|record| record.persist_lock.clone())
+        else {
+            return Ok(());
+        };
+        let _persisting = persist_lock.lock().await;
+        let Some(serialized) = self
+            .lock_uploads()
+            .get(upload_id)
+            .map(serde_json::to_vec)
+            .transpose()?
+        else {
+            return Ok(());
+        };
+        let path = self.record_path(upload_id);
+        let tmp_path = path.with_extension("json.tmp");
+        tokio::fs::write(&tmp_path, serialized).await?;
+        tokio::fs::rename(tmp_path, path).await?;
+        Ok(())
+    }
+
+    /// Removes the files of an upload that was removed from the uploads.
+    ///
+    /// Waits for the upload to be persisted if this is in progress, so that the record is not
+    /// written again after its removal.
+    async fn remove_files(&self, record: UploadRecord) {
+        let _persisting = record.persist_lock.lock().await;
+        for path in [
+            self.record_path(&record.upload_id),
+            self.data_path(&record.upload_id),
+        ] {
+            if let Err(error) = tokio::fs::remove_file(&path).await {
+                tracing::debug!(?error, path = %path.display(), "unable to remove upload file");
+            }
+        }
+    }
+
+    fn lock_uploads(&self) -> std::sync::MutexGuard<'_, HashMap<String, UploadRecord>> {
+        self.uploads.lock().expect("the lock is never poisoned")
+    }
+
+    fn record_path(&self, upload_id: &str) -> PathBuf {
+        self.uploads_dir.join(format!("{upload_id}.json"))
+    }
+
+    fn data_path(&self, upload_id: &str) -> PathBuf {
+        self.uploads_dir.join(format!("{upload_id}.data"))
+    }
+}
+
+fn load_record(path: &Path) -> anyhow::Result<UploadRecord> {
+    Ok(serde_json::from_slice(&std::fs::read(path)?)?)
+}
+
+/// Inserts the range into the sorted, non-overlapping ranges, merging adjacent ranges.
+fn insert_range(ranges: &mut Vec<ByteRange>, range: ByteRange) {
+    if range.len() == 0 {
+        return;
+    }
+    ranges.push(range);
+    ranges.sort_by_key(|range| range.start);
+    let mut merged: Vec<ByteRange> = Vec::with_capacity(ranges.len());
+    for range in ranges.drain(..) {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+    *ranges = merged;
+}
+
+/// The errors returned by the endpoints for resumable uploads.
+#[derive(Debug, thiserror::Error, RestApiError)]
+#[rest_api_error(domain = ERROR_DOMAIN)]
+pub(crate) enum UploadError {
+    /// The requested upload does not exist or has expired.
+    #[error("the requested upload does not exist or has expired")]
+    #[rest_api_error(reason = "UPLOAD_NOT_FOUND", status = ApiStatusCode::NotFound)]
+    UploadNotFound,
+
+    /// A required header of the upload protocol is missing or invalid.
+    #[error("the `{header}` header is missing or invalid")]
+    #[rest_api_error(reason = "INVALID_UPLOAD_HEADER", status = ApiStatusCode::InvalidArgument)]
+    InvalidHeader {
+        /// The name of the header.
+        header: &'static str,
+    },
+
+    /// The blob exceeds the maximum size of resumable uploads.
+    #[error("the blob exceeds the maximum size of {max_size} bytes for resumable uploads")]
+    #[rest_api_error(reason = "UPLOAD_TOO_LARGE", status = ApiStatusCode::InvalidArgument)]
+    UploadTooLarge {
+        /// The maximum size of a blob in bytes.
+        max_size: u64,
+    },
+
+    /// The chunk exceeds the length of the upload.
+    #[error(
+        "the chunk of {chunk_length} bytes at offset {offset} exceeds the upload length of \
+        {length} bytes"
+    )]
+    #[rest_api_error(reason = "CHUNK_OUT_OF_BOUNDS", status = ApiStatusCode::InvalidArgument)]
+    ChunkOutOfBounds {
+        /// The offset of the chunk.
+        offset: u64,
+        /// The length of the chunk.
+        chunk_length: u64,
+        /// The length of the upload.
+        length: u64,
+    },
+
+    /// The upload cannot be finalized before all data has been received.
+    #[error("the upload is incomplete: {received_bytes} of {length} bytes were received")]
+    #[rest_api_error(reason = "UPLOAD_INCOMPLETE", status = ApiStatusCode::FailedPrecondition)]
+    UploadIncomplete {
+        /// The number of bytes received so far.
+        received_bytes: u64,
+        /// The length of the upload.
+        length: u64,
+    },
+
+    /// The client has reached the maximum number of unfinished uploads.
+    #[error("the maximum number of {max_uploads} unfinished uploads has been reached")]
+    #[rest_api_error(reason = "TOO_MANY_UPLOADS", status = ApiStatusCode::ResourceExhausted)]
+    TooManyUploads {
+        /// The maximum number of unfinished uploads of a client.
+        max_uploads: usize,
+    },
+
+    /// The upload is currently being finalized.
+    #[error("the upload is currently being finalized")]
+    #[rest_api_error(reason = "UPLOAD_FINALIZING", status = ApiStatusCode::FailedPrecondition)]
+    UploadFinalizing,
+
+    #[error(transparent)]
+    #[rest_api_error(delegate)]
+    Internal(#[from] anyhow::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use walrus_sui::client::{BlobPersistence, CreditsPolicy, PostStoreAction};
+
+    use super::*;
+
+    fn range(start: u64, end: u64) -> ByteRange {
+        ByteRange { start, end }
+    }
+
+    fn parameters() -> StoreParameters {
+        StoreParameters {
+            encoding_type: None,
+            epochs: 1,
+            persistence: BlobPersistence::Deletable,
+            check_status: true,
+            post_store: PostStoreAction::Keep,
+            credits_policy: CreditsPolicy::Auto,
+        }
+    }
+
+    #[test]
+    fn ranges_are_merged() {
+        let mut ranges = vec![];
+        for new in [
+            range(10, 20),
+            range(30, 40),
+            range(0, 5),
+            range(5, 10),
+            range(15, 35),
+        ] {
+            insert_range(&mut ranges, new);
+        }
+        assert_eq!(ranges, vec![range(0, 40)]);
+
+        insert_range(&mut ranges, range(50, 60));
+        insert_range(&mut ranges, range(45, 45));
+        assert_eq!(ranges, vec![range(0, 40), range(50, 60)]);
+    }
+
+    #[tokio::test]
+    async fn chunks_are_staged_and_survive_restarts() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let config = UploadsConfig {
+            uploads_dir: Some(dir.path().to_owned()),
+            ..Default::default()
+        };
+        let owner = UploadOwner::default();
+        let manager = UploadManager::open(&config)?.expect("uploads are enabled");
+        let upload = manager
+            .create(10, parameters(), false, None, owner.clone())
+            .await?;
+        manager
+            .write_chunk(&upload.upload_id, &owner, 5, Bytes::from_static(b"world"))
+            .await?;
+        assert!(matches!(
+            manager
+                .write_chunk(&upload.upload_id, &owner, 8, Bytes::from_static(b"oops"))
+                .await,
+            Err(UploadError::ChunkOutOfBounds { .. })
+        ));
+        assert!(matches!(
+            manager.begin_finalize(&upload.upload_id, &owner).await,
+            Err(UploadError::UploadIncomplete {
+                received_bytes: 5,
+                length: 10
+            })
+        ));
+        drop(manager);
+
+        let manager = UploadManager::open(&config)?.expect("uploads are enabled");
+        let info = manager
+            .write_chunk(&upload.upload_id, &owner, 0, Bytes::from_static(b"hello"))
+            .await?;
+        assert_eq!(info.received_ranges, vec![range(0, 10)]);
+        assert_eq!(info.contiguous_offset(), 10);
+
+        let finalizing = manager.begin_finalize(&upload.upload_id, &owner).await?;
+        assert_eq!(&finalizing.blob[..], b"helloworld");
+        assert!(matches!(
+            manager
+                .write_chunk(&upload.upload_id, &owner, 0, Bytes::from_static(b"h"))
+                .await,
+            Err(UploadError::UploadFinalizing)
+        ));
+
+        manager.remove(&upload.upload_id).await;
+        assert!(manager.get(&upload.upload_id, &owner).is_none());
+        assert_eq!(std::fs::read_dir(dir.path())?.count(), 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn uploads_are_only_served_to_their_owner() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let config = UploadsConfig {
+            uploads_dir: Some(dir.path().to_owned()),
+            ..Default::default()
+        };
+        let manager = UploadManager::open(&config)?.expect("uploads are enabled");
+        let owner = UploadOwner {
+            tenant: Some("alice".to_owned()),
+            subject: Some("alice-app".to_owned()),
+            access_token: None,
+        };
+        let upload = manager
+            .create(5, parameters(), false, None, owner.clone())
+            .await?;
+
+        for other in [
+            UploadOwner::default(),
+            UploadOwner {
+                tenant: Some("bob".to_owned()),
+                ..owner.clone()
+            },
+            UploadOwner {
+                subject: Some("bob-app".to_owned()),
+                ..owner.clone()
+            },
+        ] {
+            assert!(manager.get(&upload.upload_id, &other).is_none());
+            assert!(matches!(
+                manager
+                    .write_chunk(&upload.upload_id, &other, 0, Bytes::from_static(b"hello"))
+                    .await,
+                Err(UploadError::UploadNotFound)
+            ));
+            assert!(matches!(
+                manager.begin_finalize(&upload.upload_id, &other).await,
+                Err(UploadError::UploadNotFound)
+            ));
+            assert!(matches!(
+                manager.cancel(&upload.upload_id, &other).await,
+                Err(UploadError::UploadNotFound)
+            ));
+        }
+
+        manager
+            .write_chunk(&upload.upload_id, &owner, 0, Bytes::from_static(b"hello"))
+            .await?;
+        let finalizing = manager.begin_finalize(&upload.upload_id, &owner).await?;
+        assert_eq!(&finalizing.blob[..], b"hello");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn anonymous_uploads_are_bound_to_their_access_token() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let config = UploadsConfig {
+            uploads_dir: Some(dir.path().to_owned()),
+            ..Default::default()
+        };
+        let manager = UploadManager::open(&config)?.expect("uploads are enabled");
+        let owner = UploadOwner::default().with_new_access_token();
+        let upload = manager
+            .create(5, parameters(), false, None, owner.clone())
+            .await?;
+        assert!(upload.access_token.is_some());
+        assert_eq!(upload.access_token, owner.access_token);
+
+        for other in [
+            UploadOwner::default(),
+            UploadOwner::default().with_new_access_token(),
+        ] {
+            assert!(manager.get(&upload.upload_id, &other).is_none());
+            assert!(matches!(
+                manager.cancel(&upload.upload_id, &other).await,
+                Err(UploadError::UploadNotFound)
+            ));
+        }
+        let same = UploadOwner::new(None, None, owner.access_token.as_deref());
+        assert!(manager.get(&upload.upload_id, &same).is_some());
+
+        // Identified clients do not need an access token.
+        let identified = UploadOwner {
+            tenant: Some("alice".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(identified.clone().with_new_access_token(), identified);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn limits_the_uploads_per_client() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let config = UploadsConfig {
+            uploads_dir: Some(dir.path().to_owned()),
+            max_uploads_per_client: 2,
+            ..Default::default()
+        };
+        let manager = UploadManager::open(&config)?.expect("uploads are enabled");
+        let owner = UploadOwner::default();
+        let mut uploads = vec![];
+        for _ in 0..2 {
+            uploads.push(
+                manager
+                    .create(5, parameters(), false, None, owner.clone())
+                    .await?,
+            );
+        }
+        assert!(matches!(
+            manager
+                .create(5, parameters(), false, None, owner.clone())
+                .await,
+            Err(UploadError::TooManyUploads { max_uploads: 2 })
+        ));
+
+        // Other clients are not affected.
+        let other = UploadOwner {
+            tenant: Some("bob".to_owned()),
+            ..owner.clone()
+        };
+        manager.create(5, parameters(), false, None, other).await?;
+
+        manager.cancel(&uploads[0].upload_id, &owner).await?;
+        manager.create(5, parameters(), false, None, owner).await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn finalizing_waits_for_chunks_being_written() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let config = UploadsConfig {
+            uploads_dir: Some(dir.path().to_owned()),
+            ..Default::default()
+        };
+        let manager = UploadManager::open(&config)?.expect("uploads are enabled");
+        let owner = UploadOwner::default();
+        let upload = manager
+            .create(5, parameters(), false, None, owner.clone())
+            .await?;
+        manager
+            .write_chunk(&upload.upload_id, &owner, 0, Bytes::from_static(b"hello"))
+            .await?;
+
+        // Simulate a chunk that is being written while the upload starts finalizing.
+        let data_lock = manager.lock_uploads()[&upload.upload_id].data_lock.clone();
+        let writing = data_lock.read().await;
+        let finalize = manager.begin_finalize(&upload.upload_id, &owner);
+        tokio::pin!(finalize);
+        assert!(
+            tokio::time::timeout(Duration::from_millis(100), &mut finalize)
+                .await
+                .is_err(),
+            "finalizing must wait for the chunk to be written"
+        );
+        assert!(matches!(
+            manager
+                .write_chunk(&upload.upload_id, &owner, 0, Bytes::from_static(b"h"))
+                .await,
+            Err(UploadError::UploadFinalizing)
+        ));
+
+        drop(writing);
+        assert_eq!(&finalize.await?.blob[..], b"hello");
+        Ok(())
+    }
+}
@@ -0,0 +1,261 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! A Walrus network running on a single machine.
+//!
+//! The [`LocalCluster`] runs a set of storage nodes in the current process against a
+//! [`SimulatedChain`], which replaces the Walrus contracts on Sui. Together with a client daemon
+//! serving the aggregator and publisher APIs, this provides a complete network that requires
+//! neither Sui tooling nor network access.
+
+use std::{num::NonZeroU16, sync::Arc, time::Duration};
+
+use anyhow::Context as _;
+use sui_types::base_types::ObjectID;
+use walrus_core::PublicKey;
+use walrus_sdk::{
+    client::WalrusNodeClient,
+    config::{ClientCommunicationConfig, ClientConfig},
+};
+use walrus_sui::{
+    client::{CoinType, MIN_STAKING_THRESHOLD, contract_config::ContractConfig},
+    test_utils::simulated_chain::{SimulatedChain, SimulatedChainConfig, SimulatedContractClient},
+    utils::generate_proof_of_possession_for_address,
+};
+
+use crate::{
+    node::committee::{CommitteeService, DefaultNodeServiceFactory, NodeCommitteeService},
+    test_utils::{StorageNodeHandle, TestCluster},
+};
+
+/// The amount of WAL (in FROST) staked with each storage node.
+const STAKE_PER_NODE: u64 = 1_000 * MIN_STAKING_THRESHOLD;
+/// The time the storage nodes are given to process the initial epoch change.
+const NODE_STARTUP_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Configuration of a [`LocalCluster`].
+#[derive(Debug, Clone)]
+pub struct LocalClusterConfig {
+    /// The number of storage nodes.
+    pub n_nodes: usize,
+    /// The number of shards in the system.
+    pub n_shards: NonZeroU16,
+    /// The duration of an epoch.
+    pub epoch_duration: Duration,
+    /// The amount of WAL (in FROST) with which the wallet of the client is funded.
+    pub client_wal_balance: u64,
+    /// The amount of SUI (in MIST) with which the wallet of the client is funded.
+    pub client_sui_balance: u64,
+    /// The seed from which the object IDs and addresses on the simulated chain are derived.
+    pub seed: u64,
+}
+
+/// A Walrus network of storage nodes running in the current process.
+///
+/// The storage nodes are stopped when the cluster is dropped.
+#[derive(Debug)]
+pub struct LocalCluster {
+    chain: SimulatedChain,
+    nodes: TestCluster<StorageNodeHandle>,
+    client: WalrusNodeClient<SimulatedContractClient>,
+}
+
+impl LocalCluster {
+    /// Starts the storage nodes and returns the running cluster.
+    ///
+    /// The storage nodes are registered and staked on a new simulated chain, which is then
+    /// advanced to epoch 1, such that all nodes are part of the committee. The client of the
+    /// cluster is funded according to the `config`.
+    pub async fn start(config: &LocalClusterConfig) -> anyhow::Result<Self> {
+        anyhow::ensure!(
+            config.n_nodes > 0,
+            "the cluster requires at least one storage node"
+        );
+        anyhow::ensure!(
+            usize::from(config.n_shards.get()) >= config.n_nodes,
+            "the number of shards must be at least the number of storage nodes"
+        );
+
+        let chain = SimulatedChain::new(SimulatedChainConfig {
+            n_shards: config.n_shards,
+            epoch_duration: config.epoch_duration,
+            seed: config.seed,
+            ..Default::default()
+        });
+
+        let cluster_builder = TestCluster::<StorageNodeHandle>::builder()
+            .with_empty_shard_assignment(config.n_nodes, config.n_shards.get());
+
+        let mut node_clients = Vec::with_capacity(config.n_nodes);
+        let mut storage_capabilities = Vec::with_capacity(config.n_nodes);
+        let mut public_keys: Vec<PublicKey> = Vec::with_capacity(config.n_nodes);
+        for (i, node_config) in cluster_builder
+            .storage_node_test_configs()
+            .iter()
+            .enumerate()
+        {
+            let node_client = chain.new_client();
+            chain.fund(node_client.address(), CoinType::Wal, STAKE_PER_NODE);
+
+            let proof_of_possession = generate_proof_of_possession_for_address(
+                node_config.key_pair(),
+                node_client.address(),
+                chain.current_epoch(),
+            );
+            let capability = node_client
+                .register_candidate(
+                    &node_config.to_node_registration_params(&format!("node-{i}")),
+                    proof_of_possession,
+                )
+                .await
+                .with_context(|| format!("failed to register storage node {i}"))?;
+            node_client
+                .stake_with_pools(&[(capability.node_id, STAKE_PER_NODE)])
+                .await
+                .with_context(|| format!("failed to stake with storage node {i}"))?;
+
+            public_keys.push(node_config.key_pair().public().clone());
+            storage_capabilities.push(capability);
+            node_clients.push(node_client);
+        }
+
+        let epoch = chain.advance_epoch()?;
+        tracing::info!(epoch, "registered the storage nodes on the simulated chain");
+
+        let mut committee_services = Vec::with_capacity(config.n_nodes);
+        for (node_client, public_key) in node_clients.iter().zip(public_keys) {
+            let service: Arc<dyn CommitteeService> = Arc::new(
+                NodeCommitteeService::builder()
+                    .local_identity(public_key)
+                    .build_with_factory(
+                        node_client.clone(),
+                        DefaultNodeServiceFactory::avoid_system_services(),
+                    )
+                    .await?,
+            );
+            committee_services.push(service);
+        }
+
+        // The event blob writer certifies blobs of checkpoints, which do not exist on the
+        // simulated chain.
+        let nodes = cluster_builder
+            .with_individual_system_event_providers(&node_clients)
+            .with_committee_services(&committee_services)
+            .with_system_contract_services(node_clients.clone())
+            .with_storage_capabilities(storage_capabilities)
+            .with_disable_event_blob_writer(vec![true; config.n_nodes])
+            .build::<StorageNodeHandle>()
+            .await?;
+        tokio::time::timeout(
+            NODE_STARTUP_TIMEOUT,
+            nodes.wait_for_nodes_to_reach_epoch(epoch),
+        )
+        .await
+        .context("the storage nodes did not reach the initial epoch in time")?;
+
+        let contract_client = chain.new_client();
+        chain.fund(
+            contract_client.address(),
+            CoinType::Wal,
+            config.client_wal_balance,
+        );
+        chain.fund(
+            contract_client.address(),
+            CoinType::Sui,
+            config.client_sui_balance,
+        );
+        let client =
+            WalrusNodeClient::new_read_client_with_refresher(client_config(), contract_client)
+                .await?;
+
+        Ok(Self {
+            chain,
+            nodes,
+            client,
+        })
+    }
+
+    /// Returns the simulated chain of the cluster.
+    pub fn chain(&self) -> &SimulatedChain {
+        &self.chain
+    }
+
+    /// Returns the running storage nodes.
+    pub fn nodes(&self) -> &[StorageNodeHandle] {
+        &self.nodes.nodes
+    }
+
+    /// Returns the funded client of the cluster.
+    pub fn client(&self) -> &WalrusNodeClient<SimulatedContractClient> {
+        &self.client
+    }
+
+    /// Stops all storage nodes of the cluster.
+    pub fn shutdown(mut self) {
+        for idx in 0..self.nodes.nodes.len() {
+            self.nodes.cancel_node(idx);
+        }
+        tracing::info!("stopped the storage nodes of the local cluster");
+    }
+}
+
+/// Returns the configuration of the client of a [`LocalCluster`].
+///
+/// The contract objects are not used by the simulated chain and are therefore set to zero.
+fn client_config() -> ClientConfig {
+    let mut config =
+        ClientConfig::new_from_contract_config(ContractConfig::new(ObjectID::ZERO, ObjectID::ZERO));
+    config.communication_config = ClientCommunicationConfig {
+        disable_proxy: true,
+        disable_native_certs: true,
+        ..Default::default()
+    };
+    config
+}
+
+#[cfg(test)]
+mod tests {
+    use walrus_sdk::{client::responses::BlobStoreResult, store_optimizations::StoreOptimizations};
+    use walrus_sui::client::{BlobPersistence, CreditsPolicy, PostStoreAction, ReadClient as _};
+    use walrus_test_utils::{Result as TestResult, random_data};
+
+    use super::*;
+    use crate::client::{WalrusWriteClient as _, WriteParams, daemon::WalrusReadClient as _};
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn stores_and_reads_blob() -> TestResult {
+        let cluster = LocalCluster::start(&LocalClusterConfig {
+            n_nodes: 4,
+            n_shards: NonZeroU16::new(10).expect("10 is non-zero"),
+            epoch_duration: Duration::from_secs(3600),
+            client_wal_balance: 1_000 * MIN_STAKING_THRESHOLD,
+            client_sui_balance: 1_000 * MIN_STAKING_THRESHOLD,
+            seed: 42,
+        })
+        .await?;
+        let client = cluster.client();
+        let blob = random_data(31_415);
+
+        let params = WriteParams {
+            encoding_type: None,
+            epochs_ahead: 1,
+            store_optimizations: StoreOptimizations::none(),
+            persistence: BlobPersistence::Permanent,
+            post_store: PostStoreAction::Keep,
+            credits_policy: CreditsPolicy::Disable,
+        };
+        let result = client.write_blob(&blob, params, None).await?;
+        let BlobStoreResult::NewlyCreated { blob_object, .. } = result else {
+            panic!("the blob should be newly created, got {result:?}");
+        };
+        let stored = client
+            .sui_client()
+            .get_blob_by_object_id(&blob_object.id)
+            .await?;
+        assert_eq!(stored.blob.certified_epoch, Some(1));
+        assert_eq!(client.read_blob(&blob_object.blob_id).await?, blob);
+
+        cluster.shutdown();
+        Ok(())
+    }
+}
@@ -16,11 +16,11 @@ use sui_types::base_types::ObjectID;
 use walrus_core::{
     BlobId,
     EncodingType,
-    EpochCount,
     encoding::quilt_encoding::{QuiltStoreBlob, QuiltVersion},
 };
 use walrus_sdk::{
     client::{
+        StorePhaseNotifier,
         WalrusNodeClient,
         metrics::ClientMetrics,
         refresh::CommitteesRefresherHandle,
@@ -28,11 +28,10 @@ use walrus_sdk::{
     },
     config::ClientConfig,
     error::ClientResult,
-    store_optimizations::StoreOptimizations,
 };
 use walrus_sui::{
     client::{
-        BlobPersistence,
+        CreditsPolicy,
         PostStoreAction,
         SuiContractClient,
         SuiReadClient,
@@ -47,7 +46,7 @@ use walrus_utils::metrics::Registry;
 
 use super::{
     cli::PublisherArgs,
-    daemon::{WalrusReadClient, WalrusWriteClient},
+    daemon::{WalrusReadClient, WalrusWriteClient, WriteParams},
     refill::{RefillHandles, Refiller},
 };
 use crate::client::refill::should_refill;
@@ -57,6 +56,7 @@ pub struct ClientMultiplexer {
     read_client: WalrusNodeClient<SuiReadClient>,
     _refill_handles: RefillHandles,
     default_post_store_action: PostStoreAction,
+    default_credits_policy: CreditsPolicy,
 }
 
 impl ClientMultiplexer {
@@ -116,20 +116,28 @@ impl ClientMultiplexer {
         );
 
         // If the user has specified `burn_after_store == true`, the default post store action is to
-        // burn the created objects after storing. Otherwise, they are sent to the main wallet.
+        // burn the created objects after storing. Otherwise, they are sent to the address specified
+        // with `send_objects_to`, or to the main wallet if none is specified.
         let default_post_store_action = if args.burn_after_store {
             PostStoreAction::Burn
         } else {
-            PostStoreAction::TransferTo(main_address)
+            PostStoreAction::TransferTo(args.send_objects_to.unwrap_or(main_address))
         };
 
-        tracing::info!(?default_post_store_action, "client multiplexer initialized");
+        let default_credits_policy = args.credits.credits_policy();
+
+        tracing::info!(
+            ?default_post_store_action,
+            ?default_credits_policy,
+            "client multiplexer initialized"
+        );
 
         Ok(Self {
             client_pool,
             read_client,
             _refill_handles: refill_handles,
             default_post_store_action,
+            default_credits_policy,
         })
     }
 
@@ -138,25 +146,13 @@ impl ClientMultiplexer {
     pub async fn submit_write(
         &self,
         blob: &[u8],
-        encoding_type: Option<EncodingType>,
-        epochs_ahead: EpochCount,
-        store_optimizations: StoreOptimizations,
-        persistence: BlobPersistence,
-        post_store: PostStoreAction,
+        params: WriteParams,
+        phase_notifier: Option<StorePhaseNotifier>,
     ) -> ClientResult<BlobStoreResult> {
         let client = self.client_pool.next_client().await;
         tracing::debug!("submitting write request to client in pool");
 
-        let result = client
-            .write_blob(
-                blob,
-                encoding_type,
-                epochs_ahead,
-                store_optimizations,
-                persistence,
-                post_store,
-            )
-            .await?;
+        let result = client.write_blob(blob, params, phase_notifier).await?;
 
         Ok(result)
     }
@@ -179,21 +175,10 @@ impl WalrusWriteClient for ClientMultiplexer {
     async fn write_blob(
         &self,
         blob: &[u8],
-        encoding_type: Option<EncodingType>,
-        epochs_ahead: EpochCount,
-        store_optimizations: StoreOptimizations,
-        persistence: BlobPersistence,
-        post_store: PostStoreAction,
+        params: WriteParams,
+        phase_notifier: Option<StorePhaseNotifier>,
     ) -> ClientResult<BlobStoreResult> {
-        self.submit_write(
-            blob,
-            encoding_type,
-            epochs_ahead,
-            store_optimizations,
-            persistence,
-            post_store,
-        )
-        .await
+        self.submit_write(blob, params, phase_notifier).await
     }
 
     async fn construct_quilt<V: QuiltVersion>(
@@ -211,25 +196,12 @@ impl WalrusWriteClient for ClientMultiplexer {
     async fn write_quilt<V: QuiltVersion>(
         &self,
         quilt: V::Quilt,
-        encoding_type: Option<EncodingType>,
-        epochs_ahead: EpochCount,
-        store_optimizations: StoreOptimizations,
-        persistence: BlobPersistence,
-        post_store: PostStoreAction,
+        params: WriteParams,
     ) -> ClientResult<QuiltStoreResult> {
         let client = self.client_pool.next_client().await;
         tracing::debug!("submitting write quilt request to client in pool");
 
-        let result = client
-            .write_quilt::<V>(
-                quilt,
-                encoding_type,
-                epochs_ahead,
-                store_optimizations,
-                persistence,
-                post_store,
-            )
-            .await?;
+        let result = client.write_quilt::<V>(quilt, params).await?;
 
         Ok(result)
     }
@@ -237,6 +209,10 @@ impl WalrusWriteClient for ClientMultiplexer {
     fn default_post_store_action(&self) -> PostStoreAction {
         self.default_post_store_action
     }
+
+    fn default_credits_policy(&self) -> CreditsPolicy {
+        self.default_credits_policy
+    }
 }
 
 /// The configuration for a [`WriteClientPool`].
@@ -38,7 +38,7 @@ use walrus_core::{
     metadata::{BlobMetadataApi as _, QuiltIndex, VerifiedBlobMetadataWithId},
 };
 use walrus_sdk::{
-    client::NodeCommunicationFactory,
+    client::{NodeCommunicationFactory, audit::BlobAuditReport},
     sui::{
         client::ReadClient,
         types::{
@@ -58,7 +58,13 @@ use walrus_storage_node_client::{
 };
 
 use super::cli::{BlobIdDecimal, BlobIdentity, HumanReadableBytes};
-use crate::client::cli::{HealthSortBy, HumanReadableFrost, NodeSortBy, SortBy};
+use crate::client::cli::{
+    HealthSortBy,
+    HumanReadableFrost,
+    NodeSortBy,
+    QuiltArchiveFormat,
+    SortBy,
+};
 
 /// The output of the `read` command.
 #[serde_as]
@@ -167,6 +173,28 @@ pub(crate) struct BlobStatusOutput {
     pub estimated_expiry_timestamp: Option<DateTime<Utc>>,
 }
 
+/// The output of the `audit` command.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AuditOutput {
+    /// The reports of the blobs that were audited.
+    pub reports: Vec<BlobAuditReport>,
+    /// The blobs that could not be audited, e.g., because they are not certified.
+    pub failures: Vec<AuditFailure>,
+}
+
+/// A blob that could not be audited.
+#[serde_as]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AuditFailure {
+    /// The blob ID.
+    #[serde_as(as = "DisplayFromStr")]
+    pub blob_id: BlobId,
+    /// The error that prevented the audit.
+    pub error: String,
+}
+
 /// The output of the `info` command.
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -637,6 +665,16 @@ pub struct ShareBlobOutput {
     pub amount: Option<u64>,
 }
 
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+/// The output of the `walrus transfer-blobs` command.
+pub struct TransferBlobsOutput {
+    /// The address to which the blob objects were transferred.
+    pub recipient: SuiAddress,
+    /// The object IDs of the transferred blob objects.
+    pub object_ids: Vec<ObjectID>,
+}
+
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 /// The output of the `walrus get-blob-attribute` command.
@@ -812,3 +850,84 @@ impl ReadQuiltOutput {
         }
     }
 }
+
+/// The output of the `local-cluster` command.
+#[cfg(feature = "local-cluster")]
+#[serde_as]
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalClusterOutput {
+    /// The URL at which the aggregator and publisher APIs are served.
+    pub daemon_url: String,
+    /// The current epoch of the cluster.
+    pub epoch: Epoch,
+    /// The number of shards in the system.
+    pub n_shards: NonZeroU16,
+    /// The storage nodes of the cluster.
+    pub nodes: Vec<LocalClusterNodeOutput>,
+    /// The address of the wallet used by the publisher.
+    #[serde_as(as = "DisplayFromStr")]
+    pub publisher_address: SuiAddress,
+    /// The WAL balance of the publisher's wallet in FROST.
+    pub publisher_wal_balance: u64,
+}
+
+/// A storage node of a local cluster.
+#[cfg(feature = "local-cluster")]
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalClusterNodeOutput {
+    /// The name of the storage node.
+    pub name: String,
+    /// The address of the REST API of the storage node.
+    pub rest_api_address: std::net::SocketAddr,
+    /// The public key of the storage node.
+    pub public_key: PublicKey,
+}
+
+#[cfg(feature = "local-cluster")]
+impl LocalClusterOutput {
+    /// Creates the output for the running `cluster`, whose client daemon binds to
+    /// `daemon_address`.
+    pub async fn new(
+        cluster: &crate::client::LocalCluster,
+        daemon_address: std::net::SocketAddr,
+    ) -> anyhow::Result<Self> {
+        let publisher = cluster.client().sui_client();
+        Ok(Self {
+            daemon_url: format!("http://{daemon_address}"),
+            epoch: cluster.chain().current_epoch(),
+            n_shards: cluster.client().encoding_config().n_shards(),
+            nodes: cluster
+                .nodes()
+                .iter()
+                .enumerate()
+                .map(|(i, node)| LocalClusterNodeOutput {
+                    name: format!("node-{i}"),
+                    rest_api_address: node.rest_api_address,
+                    public_key: node.public_key.clone(),
+                })
+                .collect(),
+            publisher_address: publisher.address(),
+            publisher_wal_balance: publisher
+                .balance(walrus_sdk::sui::client::CoinType::Wal)
+                .await?,
+        })
+    }
+}
+
+/// The output of the `quilt export` command.
+#[serde_as]
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct QuiltExportOutput {
+    /// The ID of the exported quilt.
+    #[serde_as(as = "DisplayFromStr")]
+    pub quilt_id: BlobId,
+    /// The format of the archive.
+    pub format: QuiltArchiveFormat,
+    /// The path of the archive.
+    pub out: PathBuf,
+    /// The identifiers of the exported patches.
+    pub identifiers: Vec<String>,
+}
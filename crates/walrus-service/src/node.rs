@@ -4,11 +4,13 @@
 //! Walrus storage node.
 
 use std::{
+    collections::HashSet,
     future::Future,
     num::{NonZero, NonZeroU16},
     pin::Pin,
     sync::{
         Arc,
+        RwLock,
         atomic::{AtomicBool, Ordering},
     },
 };
@@ -27,6 +29,7 @@ use futures::{
     TryFutureExt as _,
     stream::{self, FuturesOrdered},
 };
+use garbage_collector::GarbageCollector;
 use itertools::Either;
 use node_recovery::NodeRecoveryHandler;
 use rand::{Rng, SeedableRng, rngs::StdRng, thread_rng};
@@ -94,6 +97,7 @@ use walrus_sdk::{
         types::{
             BlobEvent,
             ContractEvent,
+            DenyListEvent,
             EpochChangeDone,
             EpochChangeEvent,
             EpochChangeStart,
@@ -172,6 +176,8 @@ use crate::{
 
 pub(crate) mod db_checkpoint;
 
+#[cfg(any(test, feature = "test-utils"))]
+pub mod byzantine;
 pub mod committee;
 pub mod config;
 pub(crate) mod consistency_check;
@@ -187,6 +193,7 @@ mod blob_event_processor;
 mod blob_retirement_notifier;
 mod blob_sync;
 mod epoch_change_driver;
+mod garbage_collector;
 mod node_recovery;
 mod recovery_symbol_service;
 mod shard_sync;
@@ -308,6 +315,9 @@ pub trait ServiceState {
         public_key: PublicKey,
         signed_request: SignedSyncShardRequest,
     ) -> impl Future<Output = Result<SyncShardResponse, SyncShardServiceError>> + Send;
+
+    /// Returns true if the public key belongs to a member of the current committee.
+    fn is_committee_member(&self, public_key: &PublicKey) -> bool;
 }
 
 /// Builder to construct a [`StorageNode`].
@@ -510,6 +520,7 @@ pub struct StorageNode {
     start_epoch_change_finisher: StartEpochChangeFinisher,
     node_recovery_handler: NodeRecoveryHandler,
     blob_event_processor: BlobEventProcessor,
+    garbage_collector: Arc<GarbageCollector>,
     event_blob_writer_factory: Option<EventBlobWriterFactory>,
     config_synchronizer: Option<Arc<ConfigSynchronizer>>,
 }
@@ -529,7 +540,12 @@ pub struct StorageNodeInner {
     current_epoch: watch::Sender<Epoch>,
     is_shutting_down: AtomicBool,
     blocklist: Arc<Blocklist>,
+    /// The IDs of blobs whose data was deleted through a deny list.
+    ///
+    /// Kept in memory to avoid a database read on every request to serve or store data.
+    deny_listed_blobs: RwLock<HashSet<BlobId>>,
     node_capability: ObjectID,
+    node_id: ObjectID,
     blob_retirement_notifier: Arc<BlobRetirementNotifier>,
     symbol_service: RecoverySymbolService,
     thread_pool: BoundedThreadPool,
@@ -643,6 +659,13 @@ impl StorageNode {
             &config.blocklist_path,
             Some(registry),
         )?);
+        let deny_listed_blobs = storage
+            .deny_listed_blob_ids()
+            .context("could not load the deny-listed blobs")?;
+        tracing::info!(
+            count = deny_listed_blobs.len(),
+            "loaded the deny-listed blobs"
+        );
         let checkpoint_manager = match DbCheckpointManager::new(
             storage.get_db(),
             config.checkpoint_config.clone(),
@@ -673,7 +696,9 @@ impl StorageNode {
             start_time,
             is_shutting_down: false.into(),
             blocklist: blocklist.clone(),
+            deny_listed_blobs: RwLock::new(deny_listed_blobs),
             node_capability: node_capability.id,
+            node_id: node_capability.node_id,
             blob_retirement_notifier: Arc::new(BlobRetirementNotifier::new()),
             symbol_service: RecoverySymbolService::new(
                 config.blob_recovery.max_proof_cache_elements,
@@ -721,9 +746,16 @@ impl StorageNode {
         );
         node_recovery_handler.restart_recovery().await?;
 
+        let garbage_collector = Arc::new(GarbageCollector::new(
+            inner.clone(),
+            blob_sync_handler.clone(),
+            config.garbage_collection.clone(),
+        ));
+
         let blob_event_processor = BlobEventProcessor::new(
             inner.clone(),
             blob_sync_handler.clone(),
+            garbage_collector.clone(),
             config.blob_event_processor_config.num_workers,
         );
 
@@ -774,6 +806,7 @@ impl StorageNode {
             start_epoch_change_finisher,
             node_recovery_handler,
             blob_event_processor,
+            garbage_collector,
             event_blob_writer_factory,
             config_synchronizer,
         })
@@ -799,6 +832,9 @@ impl StorageNode {
             () = self.epoch_change_driver.run() => {
                 unreachable!("epoch change driver never completes");
             },
+            () = self.garbage_collector.run() => {
+                unreachable!("garbage collector never completes");
+            },
             result = self.process_events() => match result {
                 Ok(()) => unreachable!("process_events should never return successfully"),
                 Err(err) => {
@@ -1374,9 +1410,8 @@ impl StorageNode {
                 self.process_package_event(event_handle, package_event)
                     .await?;
             }
-            EventStreamElement::ContractEvent(ContractEvent::DenyListEvent(_event)) => {
-                // TODO: Implement DenyListEvent handling (WAL-424)
-                event_handle.mark_as_complete();
+            EventStreamElement::ContractEvent(ContractEvent::DenyListEvent(event)) => {
+                self.process_deny_list_event(event_handle, event);
             }
             EventStreamElement::ContractEvent(ContractEvent::ProtocolEvent(event)) => {
                 panic!(
@@ -1519,6 +1554,33 @@ impl StorageNode {
         Ok(())
     }
 
+    /// Processes an update of a storage node's deny list on chain.
+    ///
+    /// Only the root and sequence number of each deny list are stored on chain; the deletion of
+    /// deny-listed blobs is triggered by separate `DenyListBlobDeleted` events. For this node's own
+    /// deny list, the sequence number is exported as a metric such that operators can check that
+    /// their local and on-chain deny lists are in sync.
+    #[tracing::instrument(skip_all)]
+    fn process_deny_list_event(&self, event_handle: EventHandle, event: DenyListEvent) {
+        let _scope = monitored_scope::monitored_scope("ProcessEvent::DenyListEvent");
+
+        match &event {
+            DenyListEvent::DenyListUpdate(update) if update.node_id == self.inner.node_id => {
+                tracing::info!(
+                    sequence_number = update.sequence_number,
+                    root = hex::encode(update.root),
+                    "the deny list of this storage node was updated on chain"
+                );
+                self.inner
+                    .metrics
+                    .deny_list_sequence_number
+                    .set(i64::try_from(update.sequence_number).unwrap_or(i64::MAX));
+            }
+            _ => tracing::debug!(?event, "{} event received", event.name()),
+        }
+        event_handle.mark_as_complete();
+    }
+
     #[tracing::instrument(skip_all)]
     async fn process_epoch_change_start_event(
         &self,
@@ -2360,6 +2422,26 @@ impl StorageNodeInner {
         self.blocklist.is_blocked(blob_id)
     }
 
+    /// Returns true if the blob is blocked on this node or was deleted through a deny list.
+    ///
+    /// The data of such blobs is neither served nor stored by this node.
+    fn is_deny_listed(&self, blob_id: &BlobId) -> bool {
+        self.is_blocked(blob_id)
+            || self
+                .deny_listed_blobs
+                .read()
+                .expect("mutex should not be poisoned")
+                .contains(blob_id)
+    }
+
+    /// Records that the data of the blob was deleted through a deny list.
+    pub(crate) fn mark_deny_listed(&self, blob_id: BlobId) {
+        self.deny_listed_blobs
+            .write()
+            .expect("mutex should not be poisoned")
+            .insert(blob_id);
+    }
+
     async fn get_shard_for_sliver_pair(
         &self,
         sliver_pair_index: SliverPairIndex,
@@ -2594,7 +2676,7 @@ impl StorageNodeInner {
     where
         E: From<anyhow::Error>,
     {
-        ensure!(!self.is_blocked(blob_id), forbidden_error);
+        ensure!(!self.is_deny_listed(blob_id), forbidden_error);
         ensure!(self.is_blob_registered(blob_id)?, unavailable_error,);
         Ok(())
     }
@@ -2741,6 +2823,10 @@ impl ServiceState for StorageNode {
     ) -> impl Future<Output = Result<SyncShardResponse, SyncShardServiceError>> + Send {
         self.inner.sync_shard(public_key, signed_request)
     }
+
+    fn is_committee_member(&self, public_key: &PublicKey) -> bool {
+        self.inner.is_committee_member(public_key)
+    }
 }
 
 impl ServiceState for StorageNodeInner {
@@ -2789,6 +2875,11 @@ impl ServiceState for StorageNodeInner {
             return Err(StoreMetadataError::InvalidBlob(event));
         }
 
+        ensure!(
+            blob_info.deny_list_event().is_none() && !self.is_blocked(metadata.blob_id()),
+            StoreMetadataError::Forbidden,
+        );
+
         ensure!(
             blob_info.is_registered(self.current_epoch()),
             StoreMetadataError::NotCurrentlyRegistered,
@@ -2872,6 +2963,7 @@ impl ServiceState for StorageNodeInner {
     ) -> Result<bool, StoreSliverError> {
         self.check_index(sliver_pair_index)?;
 
+        ensure!(!self.is_deny_listed(&blob_id), StoreSliverError::Forbidden);
         ensure!(
             self.is_blob_registered(&blob_id)?,
             StoreSliverError::NotCurrentlyRegistered,
@@ -3162,6 +3254,10 @@ impl ServiceState for StorageNodeInner {
             .handle_sync_shard_request(request, self.current_epoch())
             .await
     }
+
+    fn is_committee_member(&self, public_key: &PublicKey) -> bool {
+        self.committee_service.is_walrus_storage_node(public_key)
+    }
 }
 
 #[tracing::instrument(skip_all, err)]
@@ -3213,6 +3309,7 @@ mod tests {
             BlobCertified,
             BlobDeleted,
             BlobRegistered,
+            DenyListBlobDeleted,
             InvalidBlobId,
             StorageNodeCap,
             move_structs::EpochState,
@@ -3455,13 +3552,19 @@ mod tests {
         deletes_blob_data_on_event -> TestResult: [
             invalid_blob_event_registered: (InvalidBlobId::for_testing(BLOB_ID).into(), false),
             invalid_blob_event_certified: (InvalidBlobId::for_testing(BLOB_ID).into(), true),
-            // TODO (WAL-201): Uncomment the following tests as soon as we actually delete blob
-            // data.
-            // blob_deleted_event_registered: (
-            //     BlobDeleted{was_certified: false, ..BlobDeleted::for_testing(BLOB_ID)}.into(),
-            //     false
-            // ),
-            // blob_deleted_event_certified: (BlobDeleted::for_testing(BLOB_ID).into(), true),
+            blob_deleted_event_registered: (
+                BlobDeleted{was_certified: false, ..BlobDeleted::for_testing(BLOB_ID)}.into(),
+                false
+            ),
+            blob_deleted_event_certified: (BlobDeleted::for_testing(BLOB_ID).into(), true),
+            deny_list_blob_deleted_event_registered: (
+                DenyListBlobDeleted::for_testing(BLOB_ID).into(),
+                false
+            ),
+            deny_list_blob_deleted_event_certified: (
+                DenyListBlobDeleted::for_testing(BLOB_ID).into(),
+                true
+            ),
         ]
     }
     async fn deletes_blob_data_on_event(event: BlobEvent, is_certified: bool) -> TestResult {
@@ -3515,6 +3618,68 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn deny_listed_blob_is_neither_served_nor_stored() -> TestResult {
+        let events = Sender::new(48);
+        let node = StorageNodeHandle::builder()
+            .with_storage(
+                populated_storage(&[
+                    (SHARD_INDEX, vec![(BLOB_ID, WhichSlivers::Both)]),
+                    (OTHER_SHARD_INDEX, vec![(BLOB_ID, WhichSlivers::Both)]),
+                ])
+                .await?,
+            )
+            .with_system_event_provider(events.clone())
+            .with_node_started(true)
+            .build()
+            .await?;
+        let inner = node.as_ref().inner.clone();
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        events.send(BlobRegistered::for_testing(BLOB_ID).into())?;
+        events.send(BlobCertified::for_testing(BLOB_ID).into())?;
+        let deny_list_event = DenyListBlobDeleted::for_testing(BLOB_ID);
+        events.send(deny_list_event.clone().into())?;
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        assert_eq!(
+            inner.blob_status(&BLOB_ID)?,
+            BlobStatus::Invalid {
+                event: deny_list_event.event_id,
+                deny_listed: true,
+            }
+        );
+        assert!(matches!(
+            inner.retrieve_metadata(&BLOB_ID),
+            Err(RetrieveMetadataError::Forbidden)
+        ));
+
+        // The blob cannot be registered again after it was deleted through a deny list.
+        events.send(BlobRegistered::for_testing(BLOB_ID).into())?;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        assert_eq!(
+            inner.blob_status(&BLOB_ID)?,
+            BlobStatus::Invalid {
+                event: deny_list_event.event_id,
+                deny_listed: true,
+            }
+        );
+        assert!(matches!(
+            inner
+                .store_sliver(
+                    BLOB_ID,
+                    SliverPairIndex(0),
+                    walrus_core::test_utils::sliver()
+                )
+                .await,
+            Err(StoreSliverError::Forbidden)
+        ));
+        Ok(())
+    }
+
     async_param_test! {
         correctly_handles_blob_deletions_with_concurrent_instances -> TestResult: [
             same_epoch: (1),
@@ -4126,6 +4291,221 @@ mod tests {
         Ok(())
     }
 
+    mod byzantine_node {
+        use super::*;
+        use crate::node::byzantine::ByzantineConfig;
+
+        const SHARDS: &[&[u16]] = &[&[1], &[0, 2, 3, 4, 5, 6], &[7, 8, 9]];
+        const BYZANTINE_NODE: usize = 2;
+        const BYZANTINE_SHARD: ShardIndex = ShardIndex(7);
+        const SYNC_TIMEOUT: Duration = Duration::from_secs(10);
+
+        async fn cluster_with_certified_blob() -> TestResult<(TestCluster, EncodedBlob)> {
+            let (cluster, _, mut blobs) =
+                cluster_with_initial_epoch_and_certified_blob(SHARDS, &[BLOB], 1, None).await?;
+            Ok((cluster, blobs.remove(0)))
+        }
+
+        async_param_test! {
+            recovers_blob_despite_byzantine_node -> TestResult: [
+                corrupt_slivers: (ByzantineConfig {
+                    corrupt_slivers: true,
+                    ..Default::default()
+                }),
+                wrong_metadata: (ByzantineConfig {
+                    wrong_metadata: true,
+                    ..Default::default()
+                }),
+                bogus_recovery_symbols: (ByzantineConfig {
+                    bogus_recovery_symbols: true,
+                    ..Default::default()
+                }),
+                slow_replies: (ByzantineConfig {
+                    reply_delay: Some(Duration::from_millis(100)),
+                    ..Default::default()
+                }),
+            ]
+        }
+        async fn recovers_blob_despite_byzantine_node(config: ByzantineConfig) -> TestResult {
+            let test_shard = ShardIndex(1);
+            let (cluster, events, blob) =
+                cluster_with_partially_stored_blob(SHARDS, BLOB, |&shard, _| shard != test_shard)
+                    .await?;
+            cluster.nodes[BYZANTINE_NODE].byzantine_mode.set(config);
+            let node_client = cluster.client(0);
+
+            events.send(BlobCertified::for_testing(*blob.blob_id()).into())?;
+
+            let synced_metadata = retry_until_success_or_timeout(SYNC_TIMEOUT, || {
+                node_client.get_and_verify_metadata(blob.blob_id(), &blob.config)
+            })
+            .await
+            .expect("metadata should be recovered from the honest nodes");
+            assert_eq!(synced_metadata, blob.metadata);
+
+            let pair_to_sync = blob.assigned_sliver_pair(test_shard);
+            for sliver_type in [SliverType::Primary, SliverType::Secondary] {
+                let synced_sliver = retry_until_success_or_timeout(SYNC_TIMEOUT, || {
+                    node_client.get_sliver_by_type(
+                        blob.blob_id(),
+                        pair_to_sync.index(),
+                        sliver_type,
+                    )
+                })
+                .await
+                .expect("sliver should be recovered from the honest nodes");
+
+                let expected: Sliver = match sliver_type {
+                    SliverType::Primary => pair_to_sync.primary.clone().into(),
+                    SliverType::Secondary => pair_to_sync.secondary.clone().into(),
+                };
+                assert_eq!(synced_sliver, expected);
+            }
+
+            Ok(())
+        }
+
+        #[tokio::test]
+        async fn clients_detect_corrupted_data() -> TestResult {
+            let (cluster, blob) = cluster_with_certified_blob().await?;
+            let node = &cluster.nodes[BYZANTINE_NODE];
+            let pair_index = blob.assigned_sliver_pair(BYZANTINE_SHARD).index();
+            let n_shards = blob.config.n_shards();
+            let target_pair_index = blob.assigned_sliver_pair(ShardIndex(0)).index();
+            let target_index = target_pair_index.to_sliver_index::<Primary>(n_shards);
+            let list_and_verify_recovery_symbols = || {
+                node.client.list_and_verify_recovery_symbols(
+                    RecoverySymbolsFilter::recovers(target_index, SliverType::Primary),
+                    Arc::new(blob.metadata.clone()),
+                    Arc::new(blob.config.clone()),
+                    target_index,
+                    SliverType::Primary,
+                )
+            };
+
+            node.byzantine_mode.set(ByzantineConfig {
+                corrupt_slivers: true,
+                wrong_metadata: true,
+                bogus_recovery_symbols: true,
+                ..Default::default()
+            });
+
+            node.client
+                .get_and_verify_metadata(blob.blob_id(), &blob.config)
+                .await
+                .expect_err("wrong metadata should be detected");
+            node.client
+                .get_and_verify_sliver::<Primary>(pair_index, &blob.metadata, &blob.config)
+                .await
+                .expect_err("corrupted sliver should be detected");
+            list_and_verify_recovery_symbols()
+                .await
+                .expect_err("bogus recovery symbols should be detected");
+
+            node.byzantine_mode.reset();
+
+            node.client
+                .get_and_verify_metadata(blob.blob_id(), &blob.config)
+                .await?;
+            node.client
+                .get_and_verify_sliver::<Primary>(pair_index, &blob.metadata, &blob.config)
+                .await?;
+            list_and_verify_recovery_symbols().await?;
+
+            Ok(())
+        }
+
+        #[tokio::test]
+        async fn refuses_to_sign_confirmations() -> TestResult {
+            let (cluster, blob) = cluster_with_certified_blob().await?;
+            let node = &cluster.nodes[BYZANTINE_NODE];
+            let epoch = node.as_ref().inner.current_epoch();
+            let get_and_verify_confirmation = || {
+                node.client.get_and_verify_confirmation(
+                    blob.blob_id(),
+                    epoch,
+                    &node.public_key,
+                    BlobPersistenceType::Permanent,
+                )
+            };
+
+            node.byzantine_mode.set(ByzantineConfig {
+                refuse_confirmations: true,
+                ..Default::default()
+            });
+            get_and_verify_confirmation()
+                .await
+                .expect_err("the node should refuse to sign the confirmation");
+
+            node.byzantine_mode.reset();
+            get_and_verify_confirmation().await?;
+
+            Ok(())
+        }
+
+        #[tokio::test]
+        async fn confirms_blobs_it_did_not_store() -> TestResult {
+            let (cluster, events) = cluster_at_epoch1_without_blobs(SHARDS, None).await?;
+            let node = &cluster.nodes[BYZANTINE_NODE];
+            node.byzantine_mode.set(ByzantineConfig {
+                confirm_unstored_blobs: true,
+                ..Default::default()
+            });
+
+            let blob = EncodedBlob::new(BLOB, cluster.encoding_config());
+            events.send(BlobRegistered::for_testing(*blob.blob_id()).into())?;
+            store_at_shards(&blob, &cluster, |_, _| true).await?;
+
+            let epoch = node.as_ref().inner.current_epoch();
+            node.client
+                .get_and_verify_confirmation(
+                    blob.blob_id(),
+                    epoch,
+                    &node.public_key,
+                    BlobPersistenceType::Permanent,
+                )
+                .await?;
+
+            assert!(matches!(
+                node.as_ref().retrieve_metadata(blob.blob_id()),
+                Err(RetrieveMetadataError::Unavailable)
+            ));
+            assert!(matches!(
+                node.as_ref()
+                    .retrieve_sliver(
+                        blob.blob_id(),
+                        blob.assigned_sliver_pair(BYZANTINE_SHARD).index(),
+                        SliverType::Primary,
+                    )
+                    .await,
+                Err(RetrieveSliverError::Unavailable)
+            ));
+
+            Ok(())
+        }
+
+        #[tokio::test]
+        async fn delays_replies() -> TestResult {
+            let (cluster, blob) = cluster_with_certified_blob().await?;
+            let node = &cluster.nodes[BYZANTINE_NODE];
+            let pair_index = blob.assigned_sliver_pair(BYZANTINE_SHARD).index();
+            let delay = Duration::from_millis(500);
+
+            node.byzantine_mode.set(ByzantineConfig {
+                reply_delay: Some(delay),
+                ..Default::default()
+            });
+
+            let start = Instant::now();
+            node.client
+                .get_and_verify_sliver::<Primary>(pair_index, &blob.metadata, &blob.config)
+                .await?;
+            assert!(start.elapsed() >= delay);
+
+            Ok(())
+        }
+    }
+
     #[tokio::test]
     async fn does_not_start_blob_sync_for_already_expired_blob() -> TestResult {
         let shards: &[&[u16]] = &[&[1], &[0, 2, 3, 4]];
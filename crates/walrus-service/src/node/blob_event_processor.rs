@@ -14,10 +14,22 @@ use tokio::{
     sync::mpsc::{self, UnboundedReceiver, UnboundedSender},
     task::JoinHandle,
 };
-use walrus_sui::types::{BlobCertified, BlobDeleted, BlobEvent, InvalidBlobId};
+use walrus_sui::types::{
+    BlobCertified,
+    BlobDeleted,
+    BlobEvent,
+    DenyListBlobDeleted,
+    InvalidBlobId,
+};
 use walrus_utils::metrics::monitored_scope;
 
-use super::{StorageNodeInner, blob_sync::BlobSyncHandler, metrics, system_events::EventHandle};
+use super::{
+    StorageNodeInner,
+    blob_sync::BlobSyncHandler,
+    garbage_collector::GarbageCollector,
+    metrics,
+    system_events::EventHandle,
+};
 use crate::node::{
     storage::blob_info::{BlobInfoApi, CertifiedBlobInfoApi},
     system_events::CompletableHandle,
@@ -77,6 +89,7 @@ struct TrackedEvent {
 struct BackgroundEventProcessor {
     node: Arc<StorageNodeInner>,
     blob_sync_handler: Arc<BlobSyncHandler>,
+    garbage_collector: Arc<GarbageCollector>,
     event_receiver: UnboundedReceiver<TrackedEvent>,
     worker_index: usize,
 }
@@ -85,12 +98,14 @@ impl BackgroundEventProcessor {
     fn new(
         node: Arc<StorageNodeInner>,
         blob_sync_handler: Arc<BlobSyncHandler>,
+        garbage_collector: Arc<GarbageCollector>,
         event_receiver: UnboundedReceiver<TrackedEvent>,
         worker_index: usize,
     ) -> Self {
         Self {
             node,
             blob_sync_handler,
+            garbage_collector,
             event_receiver,
             worker_index,
         }
@@ -139,9 +154,12 @@ impl BackgroundEventProcessor {
                     monitored_scope::monitored_scope("ProcessEvent::BlobEvent::InvalidBlobID");
                 self.process_blob_invalid_event(event_handle, event).await?;
             }
-            BlobEvent::DenyListBlobDeleted(_) => {
-                // TODO (WAL-424): Implement DenyListBlobDeleted event handling.
-                todo!("DenyListBlobDeleted event handling is not yet implemented");
+            BlobEvent::DenyListBlobDeleted(event) => {
+                let _scope = monitored_scope::monitored_scope(
+                    "ProcessEvent::BlobEvent::DenyListBlobDeleted",
+                );
+                self.process_deny_list_blob_deleted_event(event_handle, event)
+                    .await?;
             }
             BlobEvent::Registered(_) => {
                 unreachable!("registered event should be processed immediately");
@@ -166,6 +184,8 @@ impl BackgroundEventProcessor {
             // For blob extension events, the original blob certified event should already recover
             // the entire blob, and we can skip the recovery.
             || event.is_extension
+            // The data of blocked blobs is not stored on this node.
+            || self.node.is_blocked(&event.blob_id)
             || self.node.storage.node_status()?.is_catching_up()
             || self
                 .node
@@ -222,7 +242,9 @@ impl BackgroundEventProcessor {
             // it even if it is no longer valid in the *current* epoch
             if !blob_info.is_registered(event.epoch) {
                 tracing::debug!(walrus.blob_id = %blob_id, "deleting data for deleted blob");
-                // TODO (WAL-201): Actually delete blob data.
+                self.garbage_collector
+                    .collect_deleted_blob(&blob_id, event.epoch)
+                    .await?;
             }
         } else if self
             .node
@@ -265,6 +287,31 @@ impl BackgroundEventProcessor {
         event_handle.mark_as_complete();
         Ok(())
     }
+
+    /// Processes a blob deleted through a deny list.
+    ///
+    /// The blob info has already been marked as deny-listed at this point, which prevents the blob
+    /// from being stored, synced, or recovered again.
+    #[tracing::instrument(skip_all)]
+    async fn process_deny_list_blob_deleted_event(
+        &self,
+        event_handle: EventHandle,
+        event: DenyListBlobDeleted,
+    ) -> anyhow::Result<()> {
+        tracing::info!(walrus.blob_id = %event.blob_id, "deleting data for deny-listed blob");
+        self.node
+            .blob_retirement_notifier
+            .notify_blob_retirement(&event.blob_id);
+        self.blob_sync_handler
+            .cancel_sync_and_mark_event_complete(&event.blob_id)
+            .await?;
+        self.garbage_collector
+            .collect_deny_listed_blob(&event.blob_id, event.epoch)
+            .await?;
+
+        event_handle.mark_as_complete();
+        Ok(())
+    }
 }
 
 /// Blob event processor that processes blob events. It can be configured to process events
@@ -294,6 +341,7 @@ impl BlobEventProcessor {
     pub fn new(
         node: Arc<StorageNodeInner>,
         blob_sync_handler: Arc<BlobSyncHandler>,
+        garbage_collector: Arc<GarbageCollector>,
         num_workers: usize,
     ) -> Self {
         let mut senders = Vec::with_capacity(num_workers);
@@ -307,6 +355,7 @@ impl BlobEventProcessor {
             let mut background_processor = BackgroundEventProcessor::new(
                 node.clone(),
                 blob_sync_handler.clone(),
+                garbage_collector.clone(),
                 rx,
                 worker_index,
             );
@@ -324,6 +373,7 @@ impl BlobEventProcessor {
             Some(Arc::new(BackgroundEventProcessor::new(
                 node.clone(),
                 blob_sync_handler.clone(),
+                garbage_collector.clone(),
                 rx,
                 0, // worker_index for sequential processor
             )))
@@ -352,6 +402,9 @@ impl BlobEventProcessor {
         self.node
             .storage
             .update_blob_info(event_handle.index(), &blob_event)?;
+        if let BlobEvent::DenyListBlobDeleted(event) = &blob_event {
+            self.node.mark_deny_listed(event.blob_id);
+        }
 
         if let BlobEvent::Registered(_) = &blob_event {
             // Registered event is marked as complete immediately. We need to process registered
@@ -0,0 +1,351 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Byzantine behaviours of storage nodes, used to test the resilience of the system.
+//!
+//! A [`ByzantineStorageNode`] serves the API of a [`StorageNode`] and misbehaves according to its
+//! [`ByzantineMode`]. The mode can be switched at runtime, so that tests can turn a running node
+//! byzantine and back.
+
+use std::{
+    num::NonZeroU16,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use walrus_core::{
+    BlobId,
+    InconsistencyProof,
+    PublicKey,
+    Sliver,
+    SliverPairIndex,
+    SliverType,
+    SymbolId,
+    encoding::{EncodingAxis, GeneralRecoverySymbol},
+    merkle::Node,
+    messages::{
+        BlobPersistenceType,
+        Confirmation,
+        InvalidBlobIdAttestation,
+        SignedSyncShardRequest,
+        StorageConfirmation,
+        SyncShardResponse,
+    },
+    metadata::{UnverifiedBlobMetadataWithId, VerifiedBlobMetadataWithId},
+};
+use walrus_storage_node_client::{
+    RecoverySymbolsFilter,
+    api::{BlobStatus, ServiceHealthInfo, StoredOnNodeStatus},
+};
+
+use super::{
+    ServiceState,
+    StorageNode,
+    StorageNodeInner,
+    errors::{
+        BlobStatusError,
+        ComputeStorageConfirmationError,
+        InconsistencyProofError,
+        ListSymbolsError,
+        RetrieveMetadataError,
+        RetrieveSliverError,
+        RetrieveSymbolError,
+        StoreMetadataError,
+        StoreSliverError,
+        SyncShardServiceError,
+    },
+    sign_message,
+};
+
+/// The misbehaviours enabled on a byzantine storage node.
+///
+/// The default configuration corresponds to an honest node.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ByzantineConfig {
+    /// Serve slivers whose symbols are corrupted.
+    pub corrupt_slivers: bool,
+    /// Serve metadata that does not match the requested blob ID.
+    pub wrong_metadata: bool,
+    /// Refuse to sign storage confirmations.
+    pub refuse_confirmations: bool,
+    /// Discard the received metadata and slivers, but sign storage confirmations for them.
+    pub confirm_unstored_blobs: bool,
+    /// Delay all asynchronous replies by the specified duration.
+    pub reply_delay: Option<Duration>,
+    /// Serve recovery symbols whose data is corrupted.
+    pub bogus_recovery_symbols: bool,
+}
+
+/// A handle to switch the byzantine behaviour of a storage node at runtime.
+#[derive(Debug, Clone, Default)]
+pub struct ByzantineMode(Arc<RwLock<ByzantineConfig>>);
+
+impl ByzantineMode {
+    /// Enables the misbehaviours in the provided config, replacing the previous ones.
+    pub fn set(&self, config: ByzantineConfig) {
+        tracing::info!(?config, "setting the byzantine mode of the storage node");
+        *self
+            .0
+            .write()
+            .expect("byzantine mode lock should not be poisoned") = config;
+    }
+
+    /// Makes the node honest again.
+    pub fn reset(&self) {
+        self.set(ByzantineConfig::default());
+    }
+
+    /// Returns the misbehaviours currently enabled.
+    ///
+    /// In simulation tests, the config can be overridden for each simulator node through the
+    /// `storage_node_byzantine_config` fail point.
+    pub fn get(&self) -> ByzantineConfig {
+        #[cfg(msim)]
+        if let Some(config) = simulated_node_config() {
+            return config;
+        }
+
+        self.0
+            .read()
+            .expect("byzantine mode lock should not be poisoned")
+            .clone()
+    }
+}
+
+#[cfg(msim)]
+fn simulated_node_config() -> Option<ByzantineConfig> {
+    let mut config = None;
+    sui_macros::fail_point_arg!(
+        "storage_node_byzantine_config",
+        |simulated: ByzantineConfig| {
+            config = Some(simulated);
+        }
+    );
+    config
+}
+
+/// A storage node that serves its API according to a [`ByzantineMode`].
+///
+/// The misbehaviours only affect the requests served through this wrapper, i.e., those received
+/// from clients and other storage nodes; the wrapped node itself keeps operating honestly.
+#[derive(Debug, Clone)]
+pub struct ByzantineStorageNode {
+    node: Arc<StorageNode>,
+    mode: ByzantineMode,
+}
+
+impl ByzantineStorageNode {
+    /// Creates a new wrapper around the node, which misbehaves according to the provided mode.
+    pub fn new(node: Arc<StorageNode>, mode: ByzantineMode) -> Self {
+        Self { node, mode }
+    }
+
+    /// Returns the current config of the node, after delaying the reply if required.
+    async fn config_after_delay(&self) -> ByzantineConfig {
+        let config = self.mode.get();
+        if let Some(delay) = config.reply_delay {
+            tokio::time::sleep(delay).await;
+        }
+        config
+    }
+}
+
+impl ServiceState for ByzantineStorageNode {
+    fn retrieve_metadata(
+        &self,
+        blob_id: &BlobId,
+    ) -> Result<VerifiedBlobMetadataWithId, RetrieveMetadataError> {
+        let metadata = self.node.retrieve_metadata(blob_id)?;
+        if !self.mode.get().wrong_metadata {
+            return Ok(metadata);
+        }
+
+        tracing::debug!(%blob_id, "serving wrong metadata");
+        let mut metadata = metadata.metadata().to_owned();
+        metadata.mut_inner().hashes[0].primary_hash = Node::Digest([0; 32]);
+        Ok(VerifiedBlobMetadataWithId::new_verified_unchecked(
+            *blob_id, metadata,
+        ))
+    }
+
+    async fn store_metadata(
+        &self,
+        metadata: UnverifiedBlobMetadataWithId,
+    ) -> Result<bool, StoreMetadataError> {
+        if self.config_after_delay().await.confirm_unstored_blobs {
+            tracing::debug!(blob_id = %metadata.blob_id(), "discarding metadata");
+            return Ok(true);
+        }
+        self.node.store_metadata(metadata).await
+    }
+
+    fn metadata_status(
+        &self,
+        blob_id: &BlobId,
+    ) -> Result<StoredOnNodeStatus, RetrieveMetadataError> {
+        self.node.metadata_status(blob_id)
+    }
+
+    async fn retrieve_sliver(
+        &self,
+        blob_id: &BlobId,
+        sliver_pair_index: SliverPairIndex,
+        sliver_type: SliverType,
+    ) -> Result<Sliver, RetrieveSliverError> {
+        let config = self.config_after_delay().await;
+        let mut sliver = self
+            .node
+            .retrieve_sliver(blob_id, sliver_pair_index, sliver_type)
+            .await?;
+        if config.corrupt_slivers {
+            tracing::debug!(%blob_id, %sliver_pair_index, "serving corrupted sliver");
+            match &mut sliver {
+                Sliver::Primary(sliver) => corrupt(sliver.symbols.data_mut()),
+                Sliver::Secondary(sliver) => corrupt(sliver.symbols.data_mut()),
+            }
+        }
+        Ok(sliver)
+    }
+
+    async fn store_sliver(
+        &self,
+        blob_id: BlobId,
+        sliver_pair_index: SliverPairIndex,
+        sliver: Sliver,
+    ) -> Result<bool, StoreSliverError> {
+        if self.config_after_delay().await.confirm_unstored_blobs {
+            tracing::debug!(%blob_id, %sliver_pair_index, "discarding sliver");
+            return Ok(true);
+        }
+        self.node
+            .store_sliver(blob_id, sliver_pair_index, sliver)
+            .await
+    }
+
+    async fn compute_storage_confirmation(
+        &self,
+        blob_id: &BlobId,
+        blob_persistence_type: &BlobPersistenceType,
+    ) -> Result<StorageConfirmation, ComputeStorageConfirmationError> {
+        let config = self.config_after_delay().await;
+        if config.refuse_confirmations {
+            tracing::debug!(%blob_id, "refusing to sign a storage confirmation");
+            return Err(ComputeStorageConfirmationError::NotFullyStored);
+        }
+        if config.confirm_unstored_blobs {
+            return self
+                .node
+                .inner
+                .sign_confirmation_unchecked(blob_id, blob_persistence_type)
+                .await;
+        }
+        self.node
+            .compute_storage_confirmation(blob_id, blob_persistence_type)
+            .await
+    }
+
+    async fn verify_inconsistency_proof(
+        &self,
+        blob_id: &BlobId,
+        inconsistency_proof: InconsistencyProof,
+    ) -> Result<InvalidBlobIdAttestation, InconsistencyProofError> {
+        self.config_after_delay().await;
+        self.node
+            .verify_inconsistency_proof(blob_id, inconsistency_proof)
+            .await
+    }
+
+    async fn retrieve_recovery_symbol(
+        &self,
+        blob_id: &BlobId,
+        symbol_id: SymbolId,
+        sliver_type: Option<SliverType>,
+    ) -> Result<GeneralRecoverySymbol, RetrieveSymbolError> {
+        let config = self.config_after_delay().await;
+        let mut symbol = self
+            .node
+            .retrieve_recovery_symbol(blob_id, symbol_id, sliver_type)
+            .await?;
+        if config.bogus_recovery_symbols {
+            tracing::debug!(%blob_id, %symbol_id, "serving a bogus recovery symbol");
+            corrupt(symbol.data_mut());
+        }
+        Ok(symbol)
+    }
+
+    async fn retrieve_multiple_recovery_symbols(
+        &self,
+        blob_id: &BlobId,
+        filter: RecoverySymbolsFilter,
+    ) -> Result<Vec<GeneralRecoverySymbol>, ListSymbolsError> {
+        let config = self.config_after_delay().await;
+        let mut symbols = self
+            .node
+            .retrieve_multiple_recovery_symbols(blob_id, filter)
+            .await?;
+        if config.bogus_recovery_symbols {
+            tracing::debug!(%blob_id, "serving bogus recovery symbols");
+            symbols
+                .iter_mut()
+                .for_each(|symbol| corrupt(symbol.data_mut()));
+        }
+        Ok(symbols)
+    }
+
+    fn blob_status(&self, blob_id: &BlobId) -> Result<BlobStatus, BlobStatusError> {
+        self.node.blob_status(blob_id)
+    }
+
+    fn n_shards(&self) -> NonZeroU16 {
+        self.node.n_shards()
+    }
+
+    fn health_info(&self, detailed: bool) -> impl Future<Output = ServiceHealthInfo> + Send {
+        self.node.health_info(detailed)
+    }
+
+    async fn sliver_status<A: EncodingAxis>(
+        &self,
+        blob_id: &BlobId,
+        sliver_pair_index: SliverPairIndex,
+    ) -> Result<StoredOnNodeStatus, RetrieveSliverError> {
+        self.config_after_delay().await;
+        self.node
+            .sliver_status::<A>(blob_id, sliver_pair_index)
+            .await
+    }
+
+    async fn sync_shard(
+        &self,
+        public_key: PublicKey,
+        signed_request: SignedSyncShardRequest,
+    ) -> Result<SyncShardResponse, SyncShardServiceError> {
+        self.config_after_delay().await;
+        self.node.sync_shard(public_key, signed_request).await
+    }
+
+    fn is_committee_member(&self, public_key: &PublicKey) -> bool {
+        self.node.is_committee_member(public_key)
+    }
+}
+
+impl StorageNodeInner {
+    /// Signs a storage confirmation for the blob, without checking that the blob is registered
+    /// and stored.
+    async fn sign_confirmation_unchecked(
+        &self,
+        blob_id: &BlobId,
+        blob_persistence_type: &BlobPersistenceType,
+    ) -> Result<StorageConfirmation, ComputeStorageConfirmationError> {
+        tracing::debug!(%blob_id, "signing a storage confirmation without checking storage");
+        let confirmation =
+            Confirmation::new(self.current_epoch(), *blob_id, *blob_persistence_type);
+        let signed = sign_message(confirmation, self.protocol_key_pair.clone()).await?;
+        Ok(StorageConfirmation::Signed(signed))
+    }
+}
+
+/// Flips all bits of the data.
+fn corrupt(data: &mut [u8]) {
+    data.iter_mut().for_each(|byte| *byte = !*byte);
+}
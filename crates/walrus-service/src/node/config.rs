@@ -18,6 +18,7 @@ use p256::pkcs8::DecodePrivateKey;
 use serde::{Deserialize, Serialize};
 use serde_with::{
     DeserializeAs,
+    DurationMilliSeconds,
     DurationSeconds,
     SerializeAs,
     base64::Base64,
@@ -192,6 +193,9 @@ pub struct StorageNodeConfig {
     /// Configuration for the blob event processor.
     #[serde(default, skip_serializing_if = "defaults::is_default")]
     pub blob_event_processor_config: BlobEventProcessorConfig,
+    /// Configuration for the garbage collection of the data of expired and deleted blobs.
+    #[serde(default, skip_serializing_if = "defaults::is_default")]
+    pub garbage_collection: GarbageCollectionConfig,
 }
 
 impl Default for StorageNodeConfig {
@@ -235,6 +239,7 @@ impl Default for StorageNodeConfig {
             admin_socket_path: None,
             node_recovery_config: Default::default(),
             blob_event_processor_config: Default::default(),
+            garbage_collection: Default::default(),
         }
     }
 }
@@ -534,7 +539,8 @@ impl MetricsPushConfig {
 }
 
 /// Configuration for TLS of the rest API.
-#[derive(Debug, Default, Clone, Deserialize, Serialize, PartialEq)]
+#[serde_as]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 #[serde(default)]
 pub struct TlsConfig {
     /// Do not use TLS on the REST API.
@@ -544,6 +550,24 @@ pub struct TlsConfig {
     pub disable_tls: bool,
     /// Path to the PEM-encoded x509 certificate.
     pub certificate_path: Option<PathBuf>,
+    /// The interval at which the certificate and its private key are checked for changes.
+    ///
+    /// Changed certificates are loaded without restarting the node and used for all new
+    /// connections. Only applies if the certificate is loaded from `certificate_path`. If `None`,
+    /// the certificate is only reloaded on request through the admin socket.
+    #[serde_as(as = "Option<DurationSeconds<u64>>")]
+    #[serde(rename = "certificate_reload_interval_secs")]
+    pub certificate_reload_interval: Option<Duration>,
+}
+
+impl Default for TlsConfig {
+    fn default() -> Self {
+        Self {
+            disable_tls: false,
+            certificate_path: None,
+            certificate_reload_interval: Some(Duration::from_secs(60)),
+        }
+    }
 }
 
 /// Configuration of a Walrus storage node.
@@ -728,6 +752,39 @@ impl Default for BlobEventProcessorConfig {
     }
 }
 
+/// Configuration for the garbage collection of the data of expired and deleted blobs.
+///
+/// The garbage collector periodically scans the blob info table in batches and deletes the
+/// metadata and slivers of blobs that are no longer registered in the current epoch.
+#[serde_as]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
+pub struct GarbageCollectionConfig {
+    /// Whether to run the background garbage collection.
+    pub enabled: bool,
+    /// The number of blob info entries scanned in a single batch.
+    pub batch_size: usize,
+    /// The pause between two consecutive batches, to limit the load on the database.
+    #[serde_as(as = "DurationMilliSeconds<u64>")]
+    #[serde(rename = "batch_interval_millis")]
+    pub batch_interval: Duration,
+    /// The interval at which garbage-collection passes over the blob info table are started.
+    #[serde_as(as = "DurationSeconds<u64>")]
+    #[serde(rename = "pass_interval_secs")]
+    pub pass_interval: Duration,
+}
+
+impl Default for GarbageCollectionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            batch_size: 1000,
+            batch_interval: Duration::from_millis(100),
+            pass_interval: Duration::from_secs(60 * 60),
+        }
+    }
+}
+
 /// Default values for the storage-node configuration.
 pub mod defaults {
     use std::net::Ipv4Addr;
@@ -1037,6 +1094,72 @@ pub struct RestServerConfig {
     /// An unset value means it is unlimited.
     #[serde(skip_serializing_if = "defaults::is_none")]
     pub experimental_max_active_recovery_symbols_requests: Option<usize>,
+
+    /// Per-client rate limits applied to the requests served by the REST API.
+    pub rate_limits: RateLimitConfig,
+}
+
+/// Configuration of the per-client rate limits of the REST API.
+///
+/// Each client, identified by its IP address (or its /64 prefix for IPv6), is assigned a separate
+/// token bucket for each class of requests. Shard-sync requests whose signature verifies under the
+/// public key of a committee member are attributed to that key instead.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RateLimitConfig {
+    /// Whether requests are rate limited.
+    pub enabled: bool,
+    /// The budget for requests reading blob data, metadata, or status information.
+    pub read: TokenBucketConfig,
+    /// The budget for requests storing metadata or slivers and for inconsistency proofs.
+    pub write: TokenBucketConfig,
+    /// The budget for requests for recovery symbols and for shard-sync requests.
+    pub recovery: TokenBucketConfig,
+    /// Whether shard-sync requests of members of the current committee are exempt from the rate
+    /// limits.
+    pub exempt_committee_members: bool,
+    /// The duration after which the token buckets of idle clients are discarded.
+    #[serde_as(as = "DurationSeconds<u64>")]
+    #[serde(rename = "idle_client_timeout_secs")]
+    pub idle_client_timeout: Duration,
+    /// The maximum number of clients tracked individually.
+    ///
+    /// Additional clients share a single budget per class of requests until idle clients are
+    /// discarded.
+    pub max_tracked_clients: usize,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            read: TokenBucketConfig {
+                requests_per_second: 1000,
+                burst_size: 5000,
+            },
+            write: TokenBucketConfig {
+                requests_per_second: 500,
+                burst_size: 2500,
+            },
+            recovery: TokenBucketConfig {
+                requests_per_second: 500,
+                burst_size: 2500,
+            },
+            exempt_committee_members: true,
+            idle_client_timeout: Duration::from_secs(10 * 60),
+            max_tracked_clients: 100_000,
+        }
+    }
+}
+
+/// Configuration of a token bucket.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenBucketConfig {
+    /// The rate at which the bucket is refilled, in requests per second.
+    pub requests_per_second: u32,
+    /// The capacity of the bucket, i.e., the number of requests that can be served in a burst.
+    pub burst_size: u32,
 }
 
 /// Configuration of the HTTP/2 connections established by the REST API.
@@ -479,6 +479,7 @@ mod tests {
             tls: TlsConfig {
                 disable_tls: false,
                 certificate_path: Some(cert_path.clone()),
+                ..Default::default()
             },
             ..Default::default()
         };
@@ -1,7 +1,7 @@
 // Copyright (c) Walrus Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use std::collections::HashMap;
+use std::{collections::HashMap, time::Duration};
 
 use anyhow::anyhow;
 use opentelemetry::trace::TraceContextExt as _;
@@ -105,6 +105,17 @@ pub struct IndexOutOfRange {
 )]
 pub struct Unavailable;
 
+/// The client exceeded its rate limit for the class of the request.
+#[derive(Debug, thiserror::Error, RestApiError)]
+#[error("the client exceeded its rate limit; please retry later")]
+#[rest_api_error(
+    reason = "RATE_LIMITED", status = ApiStatusCode::ResourceExhausted, domain = ERROR_DOMAIN
+)]
+pub struct RateLimited {
+    /// The duration after which the client may retry, if it can retry at all.
+    pub retry_after: Option<Duration>,
+}
+
 #[derive(Debug, thiserror::Error, RestApiError)]
 #[rest_api_error(domain = ERROR_DOMAIN)]
 pub enum RetrieveMetadataError {
@@ -191,6 +202,12 @@ pub enum StoreMetadataError {
     #[rest_api_error(reason = "INVALID_BLOB", status = ApiStatusCode::FailedPrecondition)]
     InvalidBlob(EventID),
 
+    /// The metadata cannot be stored, as the associated blob has been blocked or deleted through
+    /// a deny list on this storage node.
+    #[error("the blob for this metadata is forbidden")]
+    #[rest_api_error(reason = "FORBIDDEN_BLOB", status = ApiStatusCode::UnavailableForLegalReasons)]
+    Forbidden,
+
     #[error("unsupported encoding type {0}, supported types are: {SUPPORTED_ENCODING_TYPES:?}")]
     #[rest_api_error(reason = "UNSUPPORTED_ENCODING_TYPE", status = ApiStatusCode::InvalidArgument)]
     UnsupportedEncodingType(EncodingType),
@@ -270,6 +287,12 @@ pub enum StoreSliverError {
     #[rest_api_error(reason = "INVALID_SLIVER", status = ApiStatusCode::InvalidArgument)]
     InvalidSliver(#[from] SliverVerificationError),
 
+    /// The sliver cannot be stored, as the associated blob has been blocked or deleted through a
+    /// deny list on this storage node.
+    #[error("the blob for this sliver is forbidden")]
+    #[rest_api_error(reason = "FORBIDDEN_BLOB", status = ApiStatusCode::UnavailableForLegalReasons)]
+    Forbidden,
+
     #[error(transparent)]
     #[rest_api_error(delegate)]
     ShardNotAssigned(#[from] ShardNotAssigned),
@@ -0,0 +1,254 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Garbage collection of the data of blobs that are no longer registered.
+//!
+//! Blobs that were deleted, either by their owner or through a deny list, are cleaned up directly
+//! when the corresponding event is processed. The data of expired blobs, however, is never touched
+//! by any event. The [`GarbageCollector`] therefore periodically scans the blob info table and
+//! deletes the metadata and slivers of all blobs that are no longer registered in the current event
+//! epoch.
+//!
+//! Each pass over the table is split into rate-limited batches. After each batch, the last
+//! processed blob ID is persisted in the same atomic write as the deletions, such that an
+//! interrupted pass resumes where it left off after a restart. As a blob may be registered again
+//! while a batch is processed, the storage only deletes the data of blobs that are still
+//! unregistered when the deletions are written.
+
+use std::{collections::HashMap, sync::Arc};
+
+use anyhow::Context as _;
+use walrus_core::{
+    BlobId,
+    Epoch,
+    encoding::{EncodingFactory as _, Primary, Secondary},
+    metadata::BlobMetadataApi as _,
+};
+
+use super::{
+    NodeStatus,
+    StorageNodeInner,
+    blob_sync::BlobSyncHandler,
+    config::GarbageCollectionConfig,
+    storage::blob_info::BlobInfoApi as _,
+};
+
+/// Metric label for blob data deleted upon a `BlobDeleted` event.
+const REASON_DELETED: &str = "deleted";
+/// Metric label for blob data deleted by the background garbage collection.
+const REASON_EXPIRED: &str = "expired";
+/// Metric label for blob data deleted upon a `DenyListBlobDeleted` event.
+const REASON_DENY_LISTED: &str = "deny-listed";
+
+/// Background task deleting the data of blobs that are no longer registered.
+#[derive(Debug, Clone)]
+pub(super) struct GarbageCollector {
+    node: Arc<StorageNodeInner>,
+    blob_sync_handler: Arc<BlobSyncHandler>,
+    config: GarbageCollectionConfig,
+}
+
+impl GarbageCollector {
+    pub fn new(
+        node: Arc<StorageNodeInner>,
+        blob_sync_handler: Arc<BlobSyncHandler>,
+        config: GarbageCollectionConfig,
+    ) -> Self {
+        Self {
+            node,
+            blob_sync_handler,
+            config,
+        }
+    }
+
+    /// Runs garbage-collection passes at the configured interval.
+    ///
+    /// Never returns if garbage collection is enabled, and never completes if it is disabled.
+    /// Errors in individual passes are logged and the pass is retried at the next interval.
+    pub async fn run(&self) {
+        if !self.config.enabled {
+            tracing::info!("blob data garbage collection is disabled");
+            return std::future::pending().await;
+        }
+
+        let mut interval = tokio::time::interval(self.config.pass_interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            if let Err(error) = self.run_pass().await {
+                tracing::warn!(?error, "blob data garbage-collection pass failed");
+            }
+        }
+    }
+
+    /// Runs a single pass over the blob info table, starting after the persisted cursor.
+    ///
+    /// The pass stops early if the node is shutting down or not in a state in which blob data can
+    /// safely be deleted; it is then resumed from the cursor by the next pass.
+    async fn run_pass(&self) -> anyhow::Result<()> {
+        let mut cursor = self.node.storage.garbage_collection_cursor()?;
+        tracing::info!(
+            walrus.garbage_collection.cursor = ?cursor,
+            "starting blob data garbage-collection pass"
+        );
+
+        loop {
+            if self.node.is_shutting_down() {
+                return Ok(());
+            }
+            // During recovery, the blob info table may not be up to date yet. Blobs that appear to
+            // be unregistered may therefore still be registered on chain.
+            if !matches!(
+                self.node.storage.node_status()?,
+                NodeStatus::Active | NodeStatus::Standby
+            ) {
+                tracing::debug!("node is recovering; pausing blob data garbage collection");
+                return Ok(());
+            }
+            // The event epoch is used rather than the current epoch, as the blob info table only
+            // reflects the events processed so far.
+            let Some(epoch) = self.node.try_get_current_event_epoch() else {
+                return Ok(());
+            };
+
+            let entries = self
+                .node
+                .storage
+                .blob_info_batch_after(cursor.as_ref(), self.config.batch_size)?;
+            let next_cursor = if entries.len() < self.config.batch_size {
+                None
+            } else {
+                entries.last().map(|(blob_id, _)| *blob_id)
+            };
+
+            let syncs_in_progress = self.blob_sync_handler.blob_sync_in_progress();
+            let mut candidates = Vec::new();
+            let mut data_sizes = HashMap::new();
+            for (blob_id, blob_info) in entries {
+                if !blob_info.is_metadata_stored()
+                    || blob_info.is_registered(epoch)
+                    || syncs_in_progress.contains(&blob_id)
+                {
+                    continue;
+                }
+                data_sizes.insert(blob_id, self.stored_blob_data_size(&blob_id).await?);
+                candidates.push(blob_id);
+            }
+
+            // The blobs may have been registered again since the table was scanned. The storage
+            // checks this again atomically with the deletion and only collects unregistered blobs.
+            let collected = self
+                .node
+                .storage
+                .garbage_collect_batch(&candidates, epoch, next_cursor.as_ref())
+                .await?;
+            let reclaimed_bytes: u64 = collected
+                .iter()
+                .filter_map(|blob_id| data_sizes.get(blob_id))
+                .sum();
+
+            walrus_utils::with_label!(
+                self.node.metrics.blob_data_garbage_collected_total,
+                REASON_EXPIRED
+            )
+            .inc_by(collected.len() as u64);
+            walrus_utils::with_label!(
+                self.node.metrics.blob_data_garbage_collected_bytes_total,
+                REASON_EXPIRED
+            )
+            .inc_by(reclaimed_bytes);
+
+            let Some(next_cursor) = next_cursor else {
+                break;
+            };
+            self.node
+                .metrics
+                .garbage_collection_progress
+                .set(i64::from(next_cursor.first_two_bytes()));
+            cursor = Some(next_cursor);
+
+            tokio::time::sleep(self.config.batch_interval).await;
+        }
+
+        self.node.metrics.garbage_collection_passes_total.inc();
+        tracing::info!("completed blob data garbage-collection pass");
+        Ok(())
+    }
+
+    /// Deletes the data of a blob that was deleted, unless it is registered in `epoch`.
+    pub async fn collect_deleted_blob(&self, blob_id: &BlobId, epoch: Epoch) -> anyhow::Result<()> {
+        self.collect_blob(blob_id, epoch, REASON_DELETED).await
+    }
+
+    /// Deletes the data of a blob that was deleted through a deny list.
+    ///
+    /// Deny-listed blobs are never considered registered, so their data is always deleted.
+    pub async fn collect_deny_listed_blob(
+        &self,
+        blob_id: &BlobId,
+        epoch: Epoch,
+    ) -> anyhow::Result<()> {
+        self.collect_blob(blob_id, epoch, REASON_DENY_LISTED).await
+    }
+
+    async fn collect_blob(
+        &self,
+        blob_id: &BlobId,
+        epoch: Epoch,
+        reason: &str,
+    ) -> anyhow::Result<()> {
+        let reclaimed_bytes = self.stored_blob_data_size(blob_id).await?;
+        if !self
+            .node
+            .storage
+            .garbage_collect_blob_data(blob_id, epoch)
+            .await?
+        {
+            return Ok(());
+        }
+
+        walrus_utils::with_label!(self.node.metrics.blob_data_garbage_collected_total, reason)
+            .inc();
+        walrus_utils::with_label!(
+            self.node.metrics.blob_data_garbage_collected_bytes_total,
+            reason
+        )
+        .inc_by(reclaimed_bytes);
+        Ok(())
+    }
+
+    /// Returns the number of bytes occupied by the slivers of the blob stored on this node.
+    ///
+    /// The size is derived from the blob's metadata; the (comparatively small) size of the
+    /// metadata itself is not included.
+    async fn stored_blob_data_size(&self, blob_id: &BlobId) -> anyhow::Result<u64> {
+        let Some(metadata) = self.node.storage.get_metadata(blob_id)? else {
+            return Ok(0);
+        };
+        let metadata = metadata.metadata();
+        let encoding_config = self
+            .node
+            .encoding_config
+            .get_for_type(metadata.encoding_type());
+        let unencoded_length = metadata.unencoded_length();
+        let sliver_pair_size = u64::from(
+            encoding_config
+                .sliver_size_for_blob::<Primary>(unencoded_length)
+                .context("blob metadata with invalid length")?
+                .get(),
+        ) + u64::from(
+            encoding_config
+                .sliver_size_for_blob::<Secondary>(unencoded_length)
+                .context("blob metadata with invalid length")?
+                .get(),
+        );
+        let stored_pairs = self
+            .node
+            .storage
+            .shards_with_sliver_pairs(blob_id)
+            .await?
+            .len();
+
+        Ok(sliver_pair_size * stored_pairs as u64)
+    }
+}
@@ -161,6 +161,22 @@ walrus_utils::metrics::define_metric_set! {
         the blob info consistency check."]
         node_blob_data_consistency_check_existence_error: IntCounterVec["epoch"],
 
+        #[help = "The number of blobs whose data was garbage-collected, by reason"]
+        blob_data_garbage_collected_total: IntCounterVec["reason"],
+
+        #[help = "The number of bytes reclaimed by garbage-collecting blob data, by reason"]
+        blob_data_garbage_collected_bytes_total: IntCounterVec["reason"],
+
+        #[help = "The progress of the ongoing garbage-collection pass. It is represented by the \
+        first two bytes of the blob ID since the pass is sequential over blob IDs."]
+        garbage_collection_progress: IntGauge[],
+
+        #[help = "The number of completed garbage-collection passes over the blob info table"]
+        garbage_collection_passes_total: IntCounter[],
+
+        #[help = "The sequence number of the latest on-chain update of this node's deny list"]
+        deny_list_sequence_number: IntGauge[],
+
         #[help = "Status metric indicating the node's ID"]
         node_id: IntGaugeVec["walrus_node_id"],
 
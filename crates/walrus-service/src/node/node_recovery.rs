@@ -133,6 +133,11 @@ impl NodeRecoveryHandler {
                         continue;
                     }
 
+                    if node.is_blocked(&blob_id) {
+                        tracing::debug!(walrus.blob_id = %blob_id, "skip blocked blob");
+                        continue;
+                    }
+
                     // The node will only enter recovery mode if it has caught up to the latest
                     // epoch. So we only need to check the latest epoch for the shard assignment.
                     if let Ok(stored_at_all_shards) =
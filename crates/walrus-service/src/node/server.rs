@@ -5,7 +5,7 @@
 
 use std::{net::SocketAddr, ops::Deref, sync::Arc, time::Duration};
 
-use anyhow::{Context, anyhow};
+use anyhow::{Context, anyhow, bail};
 use axum::{
     Router,
     extract::DefaultBodyLimit,
@@ -14,7 +14,7 @@ use axum::{
 };
 use axum_server::{Handle, tls_rustls::RustlsConfig};
 use fastcrypto::{secp256r1::Secp256r1PrivateKey, traits::ToFromBytes};
-use futures::{FutureExt, future::Either};
+use futures::future::Either;
 use openapi::RestApiDoc;
 use p256::{SecretKey, elliptic_curve::pkcs8::EncodePrivateKey as _};
 use rcgen::{CertificateParams, CertifiedKey, DnType, KeyPair as RcGenKeyPair};
@@ -32,8 +32,15 @@ use walrus_core::{encoding, keys::NetworkKeyPair};
 use walrus_utils::metrics::Registry;
 use x509_cert::{Certificate, der::Decode};
 
-use self::telemetry::MetricsMiddlewareState;
-use super::config::{Http2Config, PathOrInPlace, StorageNodeConfig, TlsConfig, defaults};
+use self::{rate_limit::RateLimiter, telemetry::MetricsMiddlewareState};
+use super::config::{
+    Http2Config,
+    PathOrInPlace,
+    RateLimitConfig,
+    StorageNodeConfig,
+    TlsConfig,
+    defaults,
+};
 use crate::{
     common::telemetry::{self, MakeHttpSpan},
     node::ServiceState,
@@ -41,6 +48,7 @@ use crate::{
 
 mod extract;
 mod openapi;
+mod rate_limit;
 mod responses;
 mod routes;
 
@@ -64,6 +72,12 @@ pub struct RestApiConfig {
     /// and TLS is not possible between the middleware and the server.
     pub tls_certificate: Option<TlsCertificateSource>,
 
+    /// Interval at which a TLS certificate loaded from PEM files is checked for changes.
+    ///
+    /// If None, the certificate is only reloaded through
+    /// [`RestApiServer::reload_tls_certificate`].
+    pub tls_certificate_reload_interval: Option<Duration>,
+
     /// Duration for which to wait for connections to close, when shutting down the server.
     ///
     /// Zero waits indefinitely and None immediately closes the connections.
@@ -74,6 +88,9 @@ pub struct RestApiConfig {
 
     /// Limit on the number of active recovery symbol requests.
     pub max_active_recovery_symbols_requests: Option<usize>,
+
+    /// Per-client rate limits of the requests.
+    pub rate_limits: RateLimitConfig,
 }
 
 impl From<&StorageNodeConfig> for RestApiConfig {
@@ -110,11 +127,13 @@ impl From<&StorageNodeConfig> for RestApiConfig {
         RestApiConfig {
             bind_address: config.rest_api_address,
             tls_certificate,
+            tls_certificate_reload_interval: config.tls.certificate_reload_interval,
             graceful_shutdown_period,
             http2_config: config.rest_server.http2_config.clone(),
             max_active_recovery_symbols_requests: config
                 .rest_server
                 .experimental_max_active_recovery_symbols_requests,
+            rate_limits: config.rest_server.rate_limits.clone(),
         }
     }
 }
@@ -125,8 +144,8 @@ pub enum TlsCertificateSource {
     /// Load PEM encoded x509 certificate and a PKCS8 encoded private key from the specified paths.
     ///
     /// These ideally should be certificates issued to by a public CA such as Let's Encrypt,
-    /// but can also be self-signed certificates.
-    // TODO(jsmith): Reload the certificate on change (#709)
+    /// but can also be self-signed certificates. Certificates loaded from paths are reloaded when
+    /// the files change, see [`RestApiConfig::tls_certificate_reload_interval`].
     Pem {
         /// Path to the x509 PEM encoded certificate.
         certificate: PathOrInPlace<Vec<u8>>,
@@ -156,15 +175,17 @@ pub(crate) struct RestApiState<S> {
     service: Arc<S>,
     config: Arc<RestApiConfig>,
     recovery_symbols_limit: Option<Arc<Semaphore>>,
+    rate_limiter: Option<Arc<RateLimiter>>,
 }
 
 impl<S> RestApiState<S> {
-    fn new(service: Arc<S>, config: Arc<RestApiConfig>) -> Self {
+    fn new(service: Arc<S>, config: Arc<RestApiConfig>, registry: &Registry) -> Self {
         Self {
             service,
             recovery_symbols_limit: config
                 .max_active_recovery_symbols_requests
                 .map(|limit| Arc::new(Semaphore::new(limit))),
+            rate_limiter: RateLimiter::new(&config.rate_limits, registry).map(Arc::new),
             config,
         }
     }
@@ -176,10 +197,19 @@ impl<S> Clone for RestApiState<S> {
             service: self.service.clone(),
             config: self.config.clone(),
             recovery_symbols_limit: self.recovery_symbols_limit.clone(),
+            rate_limiter: self.rate_limiter.clone(),
         }
     }
 }
 
+/// The TLS configuration currently used by the server.
+#[derive(Debug)]
+struct ActiveTlsConfig {
+    config: RustlsConfig,
+    /// The certificate and key from which the configuration was loaded, if loaded from PEM.
+    pem: Option<(Vec<u8>, Vec<u8>)>,
+}
+
 /// Represents a server for the Walrus REST API.
 #[derive(Debug)]
 pub struct RestApiServer<S> {
@@ -187,6 +217,7 @@ pub struct RestApiServer<S> {
     metrics: MetricsMiddlewareState,
     cancel_token: CancellationToken,
     handle: Mutex<Option<Handle>>,
+    tls: Mutex<Option<ActiveTlsConfig>>,
 }
 
 impl<S> RestApiServer<S>
@@ -201,10 +232,11 @@ where
         registry: &Registry,
     ) -> Self {
         Self {
-            state: RestApiState::new(service, Arc::new(config)),
+            state: RestApiState::new(service, Arc::new(config), registry),
             metrics: MetricsMiddlewareState::new(registry),
             cancel_token,
             handle: Default::default(),
+            tls: Default::default(),
         }
     }
 
@@ -257,10 +289,24 @@ where
             .in_current_span(),
         );
 
-        server
-            .inspect(|_| tracing::info!("server run has completed"))
-            .await
-            .map_err(|error| anyhow!(error))
+        tokio::select! {
+            result = server => {
+                tracing::info!("server run has completed");
+                result.map_err(|error| anyhow!(error))
+            }
+            () = self.watch_tls_certificate() => unreachable!("watching never completes"),
+            () = self.evict_idle_rate_limited_clients() => {
+                unreachable!("evicting never completes")
+            }
+        }
+    }
+
+    /// Periodically discards the rate-limit budgets of idle clients; never completes.
+    async fn evict_idle_rate_limited_clients(&self) {
+        match self.state.rate_limiter.as_deref() {
+            Some(rate_limiter) => rate_limiter.evict_idle_clients_periodically().await,
+            None => std::future::pending().await,
+        }
     }
 
     fn configure_server<A>(&self, mut server: axum_server::Server<A>) -> axum_server::Server<A> {
@@ -312,17 +358,105 @@ where
             return Ok(None);
         };
 
-        let (tls_config, certificate) = match tls_certificate {
+        let (tls_config, certificate, pem) = match tls_certificate {
             TlsCertificateSource::Pem { certificate, key } => {
-                configure_tls_from_pem(certificate, key).await?
+                let (certificate_pem, key_pem) = load_pem(certificate, key)?;
+                let (tls_config, certificate) =
+                    configure_tls_from_pem(certificate_pem.clone(), key_pem.clone()).await?;
+                (tls_config, certificate, Some((certificate_pem, key_pem)))
             }
 
             TlsCertificateSource::GenerateSelfSigned {
                 server_name,
                 network_key_pair,
-            } => configure_self_signed_tls(server_name, network_key_pair).await?,
+            } => {
+                let (tls_config, certificate) =
+                    configure_self_signed_tls(server_name, network_key_pair).await?;
+                (tls_config, certificate, None)
+            }
+        };
+
+        self.set_tls_certificate_expiration_time(&certificate);
+        *self.tls.lock().await = Some(ActiveTlsConfig {
+            config: tls_config.clone(),
+            pem,
+        });
+
+        Ok(Some(tls_config))
+    }
+
+    /// Reloads the TLS certificate and private key from their PEM files.
+    ///
+    /// The new certificate is used for all subsequent connections, while established connections
+    /// are unaffected. Unless `force` is set, the certificate is only reloaded if the contents of
+    /// the files changed. Returns true if the certificate was reloaded.
+    ///
+    /// On error, the server continues to use the previous certificate.
+    pub async fn reload_tls_certificate(&self, force: bool) -> Result<bool, anyhow::Error> {
+        let Some(TlsCertificateSource::Pem { certificate, key }) = &self.config().tls_certificate
+        else {
+            bail!("the server does not use a TLS certificate loaded from PEM files");
         };
+        let mut active_tls = self.tls.lock().await;
+        let Some(active_tls) = active_tls.as_mut() else {
+            bail!("the server has not yet been started");
+        };
+
+        let (certificate_pem, key_pem) = load_pem(certificate, key)?;
+        if !force
+            && active_tls
+                .pem
+                .as_ref()
+                .is_some_and(|(loaded_certificate, loaded_key)| {
+                    loaded_certificate == &certificate_pem && loaded_key == &key_pem
+                })
+        {
+            return Ok(false);
+        }
+
+        let certificate = parse_first_certificate(&certificate_pem)?;
+        active_tls
+            .config
+            .reload_from_pem(certificate_pem.clone(), key_pem.clone())
+            .await
+            .context("failed to reload certificate and key from in-memory contents")?;
+        active_tls.pem = Some((certificate_pem, key_pem));
 
+        self.set_tls_certificate_expiration_time(&certificate);
+        tracing::info!(
+            not_after = ?certificate.tbs_certificate.validity.not_after,
+            "reloaded the TLS certificate of the REST API"
+        );
+        Ok(true)
+    }
+
+    /// Periodically reloads the TLS certificate if its PEM files changed.
+    ///
+    /// Never completes.
+    async fn watch_tls_certificate(&self) {
+        let (Some(TlsCertificateSource::Pem { .. }), Some(reload_interval)) = (
+            &self.config().tls_certificate,
+            self.config().tls_certificate_reload_interval,
+        ) else {
+            return std::future::pending().await;
+        };
+
+        let mut interval = tokio::time::interval(reload_interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        // The first tick completes immediately, at which point the certificate was just loaded.
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            if let Err(error) = self.reload_tls_certificate(false).await {
+                tracing::warn!(
+                    ?error,
+                    "failed to reload the TLS certificate of the REST API"
+                );
+            }
+        }
+    }
+
+    fn set_tls_certificate_expiration_time(&self, certificate: &Certificate) {
         self.metrics.set_tls_certificate_expiration_time(
             certificate
                 .tbs_certificate
@@ -330,8 +464,6 @@ where
                 .not_after
                 .to_unix_duration(),
         );
-
-        Ok(Some(tls_config))
     }
 
     #[cfg(test)]
@@ -413,6 +545,10 @@ where
             .route(routes::BLOB_STATUS_ENDPOINT, get(routes::get_blob_status))
             .route(routes::HEALTH_ENDPOINT, get(routes::health_info))
             .route(routes::SYNC_SHARD_ENDPOINT, post(routes::sync_shard))
+            .route_layer(middleware::from_fn_with_state(
+                self.state.clone(),
+                rate_limit::rate_limit_layer,
+            ))
     }
 
     /// Returns the CORS leayer for the server.
@@ -449,10 +585,11 @@ async fn configure_self_signed_tls(
     Ok((tls_config, certificate))
 }
 
-async fn configure_tls_from_pem(
+/// Loads the PEM-encoded certificate and private key.
+fn load_pem(
     certificate: &PathOrInPlace<Vec<u8>>,
     key: &PathOrInPlace<Vec<u8>>,
-) -> Result<(RustlsConfig, Certificate), anyhow::Error> {
+) -> Result<(Vec<u8>, Vec<u8>), anyhow::Error> {
     let certificate_pem = certificate
         .load_transient()
         .context("failed to load TLS PEM certificate")?;
@@ -461,16 +598,27 @@ async fn configure_tls_from_pem(
         .load_transient()
         .context("failed to load TLS private key")?;
 
-    let tls_config = RustlsConfig::from_pem(certificate_pem.clone(), key_pem)
+    Ok((certificate_pem, key_pem))
+}
+
+async fn configure_tls_from_pem(
+    certificate_pem: Vec<u8>,
+    key_pem: Vec<u8>,
+) -> Result<(RustlsConfig, Certificate), anyhow::Error> {
+    let certificate = parse_first_certificate(&certificate_pem)?;
+
+    let tls_config = RustlsConfig::from_pem(certificate_pem, key_pem)
         .await
         .context("failed to load certificate and key from in-memory contents")?;
 
-    let certificate = x509_cert::Certificate::load_pem_chain(&certificate_pem)?
+    Ok((tls_config, certificate))
+}
+
+fn parse_first_certificate(certificate_pem: &[u8]) -> Result<Certificate, anyhow::Error> {
+    x509_cert::Certificate::load_pem_chain(certificate_pem)?
         .into_iter()
         .next()
-        .ok_or_else(|| anyhow!("there must be at least one certificate present"))?;
-
-    Ok((tls_config, certificate))
+        .ok_or_else(|| anyhow!("there must be at least one certificate present"))
 }
 
 fn create_self_signed_certificate(
@@ -509,18 +657,22 @@ pub(crate) fn to_pkcs8_key_pair(keypair: &NetworkKeyPair) -> RcGenKeyPair {
 
 #[cfg(test)]
 mod tests {
+    use std::sync::LazyLock;
+
     use anyhow::anyhow;
     use axum::http::StatusCode;
-    use fastcrypto::traits::KeyPair;
+    use fastcrypto::traits::{EncodeDecodeBase64 as _, KeyPair};
     use p256::pkcs8::LineEnding;
     use rcgen::{BasicConstraints, Certificate as RcGenCertificate, CertifiedKey, IsCa};
     use tokio::{task::JoinHandle, time::Duration};
     use tokio_util::sync::CancellationToken;
+    use tower::ServiceExt as _;
     use walrus_core::{
         BlobId,
         InconsistencyProof,
         PublicKey,
         RecoverySymbol,
+        ShardIndex,
         Sliver,
         SliverIndex,
         SliverPairIndex,
@@ -539,6 +691,7 @@ mod tests {
             SignedMessage,
             StorageConfirmation,
             SyncShardMsg,
+            SyncShardRequest,
             SyncShardResponse,
         },
         metadata::{UnverifiedBlobMetadataWithId, VerifiedBlobMetadataWithId},
@@ -577,6 +730,10 @@ mod tests {
         test_utils,
     };
 
+    /// The key pair of the only committee member known to the mock service.
+    static COMMITTEE_MEMBER_KEY_PAIR: LazyLock<ProtocolKeyPair> =
+        LazyLock::new(ProtocolKeyPair::generate);
+
     pub struct MockServiceState;
 
     impl ServiceState for MockServiceState {
@@ -779,6 +936,10 @@ mod tests {
         ) -> Result<SyncShardResponse, SyncShardServiceError> {
             Ok(SyncShardResponse::V1(vec![]))
         }
+
+        fn is_committee_member(&self, public_key: &PublicKey) -> bool {
+            public_key == COMMITTEE_MEMBER_KEY_PAIR.public()
+        }
     }
 
     async fn start_rest_api_with_config(
@@ -1335,6 +1496,81 @@ mod tests {
 
             Ok(())
         }
+
+        #[tokio::test]
+        async fn server_reloads_changed_certificates() -> TestResult {
+            let mut config = test_utils::storage_node_config();
+            let network_key_pair = config.as_ref().network_key_pair().clone();
+            let rest_api_address = config.as_ref().rest_api_address;
+
+            let (certified_key_pair, _) = create_non_self_signed_certificate(
+                &network_key_pair,
+                rest_api_address.ip().to_string(),
+            )?;
+            configure_certificates_from_disk(certified_key_pair, &mut config)?;
+
+            let server = Arc::new(RestApiServer::new(
+                Arc::new(MockServiceState),
+                CancellationToken::new(),
+                RestApiConfig::from(config.as_ref()),
+                &Registry::default(),
+            ));
+            let server_copy = server.clone();
+            let _handle = tokio::spawn(async move { server_copy.run().await });
+            server.ready().await;
+
+            assert!(
+                !server.reload_tls_certificate(false).await?,
+                "an unchanged certificate must not be reloaded"
+            );
+
+            // Replace the certificate with one from a different issuer.
+            let (new_certified_key_pair, new_issuer_cert) = create_non_self_signed_certificate(
+                &network_key_pair,
+                rest_api_address.ip().to_string(),
+            )?;
+            let certificate_path = config
+                .as_ref()
+                .tls
+                .certificate_path
+                .clone()
+                .expect("certificate path is set");
+            std::fs::write(
+                &certificate_path,
+                new_certified_key_pair.cert.pem().as_bytes(),
+            )?;
+
+            assert!(server.reload_tls_certificate(false).await?);
+
+            let client = default_storage_node_client_builder()
+                .add_root_certificate(new_issuer_cert.der())
+                .authenticate_with_public_key(network_key_pair.public().clone())
+                .build(&rest_api_address.to_string())
+                .expect("must be able to construct client in tests");
+            try_tls_request(client).await?;
+
+            Ok(())
+        }
+
+        #[tokio::test]
+        async fn reloading_self_signed_certificate_fails() -> TestResult {
+            let mut config = test_utils::storage_node_config();
+            use_self_signed_certificates(config.as_mut());
+
+            let server = RestApiServer::new(
+                Arc::new(MockServiceState),
+                CancellationToken::new(),
+                RestApiConfig::from(config.as_ref()),
+                &Registry::default(),
+            );
+
+            server
+                .reload_tls_certificate(true)
+                .await
+                .expect_err("self-signed certificates are not loaded from files");
+
+            Ok(())
+        }
     }
 
     async_param_test! {
@@ -1398,6 +1634,107 @@ mod tests {
         assert_eq!(error_status.code(), ApiStatusCode::Unavailable)
     }
 
+    #[tokio::test]
+    async fn rate_limits_clients_but_not_committee_members() -> TestResult {
+        let mut config = test_utils::storage_node_config();
+        let rate_limits = &mut config.as_mut().rest_server.rate_limits;
+        rate_limits.enabled = true;
+        for bucket in [&mut rate_limits.read, &mut rate_limits.recovery] {
+            bucket.requests_per_second = 0;
+            bucket.burst_size = 1;
+        }
+        let _handle = start_rest_api_with_config(config.as_ref()).await;
+
+        let client = storage_node_client(config.as_ref());
+        let blob_id = blob_id_for_valid_response();
+
+        client.get_blob_status(&blob_id).await?;
+        let error = client
+            .get_blob_status(&blob_id)
+            .await
+            .expect_err("request should fail due to the rate limit");
+        assert_eq!(
+            error.http_status_code(),
+            Some(StatusCode::TOO_MANY_REQUESTS)
+        );
+        let error_status = error.status().expect("there should be a structured error");
+        assert_eq!(error_status.code(), ApiStatusCode::ResourceExhausted);
+
+        for _ in 0..3 {
+            client
+                .sync_shard::<Primary>(ShardIndex(0), blob_id, 10, 0, &COMMITTEE_MEMBER_KEY_PAIR)
+                .await?;
+        }
+
+        let key_pair = ProtocolKeyPair::generate();
+        client
+            .sync_shard::<Primary>(ShardIndex(0), blob_id, 10, 0, &key_pair)
+            .await?;
+        let error = client
+            .sync_shard::<Primary>(ShardIndex(0), blob_id, 10, 0, &key_pair)
+            .await
+            .expect_err("nodes outside of the committee are rate limited");
+        assert_eq!(
+            error.http_status_code(),
+            Some(StatusCode::TOO_MANY_REQUESTS)
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn throttles_shard_syncs_with_spoofed_committee_keys() -> TestResult {
+        let mut config = test_utils::storage_node_config();
+        let rate_limits = &mut config.as_mut().rest_server.rate_limits;
+        rate_limits.enabled = true;
+        rate_limits.recovery.requests_per_second = 0;
+        rate_limits.recovery.burst_size = 1;
+        let server = RestApiServer::new(
+            Arc::new(MockServiceState),
+            CancellationToken::new(),
+            RestApiConfig::from(config.as_ref()),
+            &Registry::default(),
+        );
+        let app = server.define_routes().with_state(server.state.clone());
+
+        // Sends a shard-sync request claiming to be from the committee member, signed by `signer`.
+        let sync_shard = |signer: &ProtocolKeyPair| {
+            let message = SyncShardMsg::new(
+                0,
+                SyncShardRequest::new(ShardIndex(0), SliverType::Primary, BlobId::ZERO, 10, 0),
+            );
+            let mut request = axum::extract::Request::post(routes::SYNC_SHARD_ENDPOINT)
+                .header(
+                    reqwest::header::AUTHORIZATION,
+                    COMMITTEE_MEMBER_KEY_PAIR.public().encode_base64(),
+                )
+                .body(axum::body::Body::from(
+                    bcs::to_bytes(&signer.sign_message(&message)).expect("serialization succeeds"),
+                ))
+                .expect("the request is valid");
+            request
+                .extensions_mut()
+                .insert(axum::extract::ConnectInfo(SocketAddr::from((
+                    [10, 0, 0, 1],
+                    1234,
+                ))));
+            app.clone().oneshot(request)
+        };
+
+        for _ in 0..3 {
+            let response = sync_shard(&COMMITTEE_MEMBER_KEY_PAIR).await?;
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+
+        let attacker = ProtocolKeyPair::generate();
+        let response = sync_shard(&attacker).await?;
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = sync_shard(&attacker).await?;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn rustls_reads_serialized_pem_network_keypair() -> TestResult {
         let mut config_with_dir = test_utils::storage_node_config();
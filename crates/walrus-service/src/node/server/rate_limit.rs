@@ -0,0 +1,674 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Per-client rate limiting of the REST API.
+//!
+//! Requests are grouped into [`RequestClass`]es, and each client is assigned a separate token
+//! bucket per class. A client is identified by its IP address, where IPv6 clients are identified by
+//! their /64 prefix. Shard-sync requests of committee members are instead attributed to the public
+//! key of the requesting node, but only once the signature of the request has been verified against
+//! that key; requests with an invalid signature are charged to the bucket of their IP address.
+
+use std::{
+    collections::HashMap,
+    hash::{BuildHasher, RandomState},
+    net::{IpAddr, Ipv6Addr, SocketAddr},
+    sync::{Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    body::{self, Body},
+    extract::{ConnectInfo, MatchedPath, Request, State},
+    http::{HeaderValue, Method, StatusCode, header::RETRY_AFTER},
+    middleware::Next,
+    response::{IntoResponse as _, Response},
+};
+use fastcrypto::traits::EncodeDecodeBase64 as _;
+use reqwest::header::AUTHORIZATION;
+use walrus_core::{PublicKey, messages::SignedSyncShardRequest};
+use walrus_utils::metrics::Registry;
+
+use super::{
+    RestApiState,
+    routes::{self, SyncServiceState},
+};
+use crate::node::{
+    config::{RateLimitConfig, TokenBucketConfig},
+    errors::RateLimited,
+};
+
+/// The number of independently locked shards of the token buckets.
+const N_BUCKET_SHARDS: usize = 16;
+
+/// The maximum size of the body of a shard-sync request that is read to verify its signature.
+///
+/// Shard-sync requests consist of a short signed message, so this is far above their actual size.
+const MAX_SYNC_SHARD_REQUEST_SIZE: usize = 64 * 1024;
+
+walrus_utils::metrics::define_metric_set! {
+    #[namespace = "walrus_rest_api"]
+    /// Metrics of the per-client rate limits of the REST API.
+    struct RateLimitMetrics {
+        #[help = "The total number of requests rejected due to the per-client rate limits"]
+        rate_limited_requests_total: IntCounterVec["request_class"],
+
+        #[help = "The total number of requests exempt from the per-client rate limits"]
+        rate_limit_exempt_requests_total: IntCounterVec["request_class"],
+
+        #[help = "The number of clients for which token buckets are currently tracked"]
+        rate_limited_clients: IntGauge[],
+    }
+}
+
+/// The classes of requests, each of which is limited by a separate budget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(super) enum RequestClass {
+    /// Requests for blob data, metadata, confirmations, and status information.
+    Read,
+    /// Requests storing metadata and slivers, and inconsistency proofs.
+    Write,
+    /// Requests for recovery symbols and shard syncs.
+    Recovery,
+}
+
+impl RequestClass {
+    /// Returns the class of the request for the matched route, or `None` if the route is not
+    /// rate limited.
+    fn from_route(method: &Method, route: &str) -> Option<Self> {
+        match route {
+            routes::METADATA_ENDPOINT | routes::SLIVER_ENDPOINT if method == Method::PUT => {
+                Some(Self::Write)
+            }
+            routes::INCONSISTENCY_PROOF_ENDPOINT => Some(Self::Write),
+            routes::RECOVERY_ENDPOINT
+            | routes::RECOVERY_SYMBOL_ENDPOINT
+            | routes::RECOVERY_SYMBOL_LIST_ENDPOINT
+            | routes::SYNC_SHARD_ENDPOINT => Some(Self::Recovery),
+            routes::METADATA_ENDPOINT
+            | routes::METADATA_STATUS_ENDPOINT
+            | routes::SLIVER_ENDPOINT
+            | routes::SLIVER_STATUS_ENDPOINT
+            | routes::PERMANENT_BLOB_CONFIRMATION_ENDPOINT
+            | routes::DELETABLE_BLOB_CONFIRMATION_ENDPOINT
+            | routes::BLOB_STATUS_ENDPOINT
+            | routes::HEALTH_ENDPOINT => Some(Self::Read),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Read => "read",
+            Self::Write => "write",
+            Self::Recovery => "recovery",
+        }
+    }
+}
+
+/// The identity of a client to which a budget is assigned.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum ClientId {
+    Ip(IpAddr),
+    StorageNode(PublicKey),
+    /// All clients that are not tracked individually, as the maximum number of tracked clients
+    /// is reached.
+    Untracked,
+}
+
+impl ClientId {
+    /// Returns the identity of the client with the given IP address.
+    ///
+    /// IPv6 clients are identified by their /64 prefix, as a single host can typically use any of
+    /// the addresses in its prefix.
+    fn from_ip(ip: IpAddr) -> Self {
+        match ip.to_canonical() {
+            IpAddr::V6(ip) => {
+                let prefix = u128::from(ip) & (u128::MAX << 64);
+                Self::Ip(IpAddr::V6(Ipv6Addr::from(prefix)))
+            }
+            ip => Self::Ip(ip),
+        }
+    }
+}
+
+/// A token bucket, refilled continuously at the configured rate.
+#[derive(Debug)]
+struct TokenBucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(config: &TokenBucketConfig, now: Instant) -> Self {
+        Self {
+            tokens: f64::from(config.burst_size),
+            last_refill: now,
+        }
+    }
+
+    /// Takes a token from the bucket.
+    ///
+    /// If the bucket is empty, returns the duration after which the next token is available, or
+    /// `None` if the bucket is never refilled.
+    fn try_acquire(
+        &mut self,
+        config: &TokenBucketConfig,
+        now: Instant,
+    ) -> Result<(), Option<Duration>> {
+        let rate = f64::from(config.requests_per_second);
+        let elapsed = now.saturating_duration_since(self.last_refill);
+        self.tokens =
+            (self.tokens + elapsed.as_secs_f64() * rate).min(f64::from(config.burst_size));
+        self.last_refill = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else if rate > 0.0 {
+            Err(Some(Duration::from_secs_f64((1.0 - self.tokens) / rate)))
+        } else {
+            Err(None)
+        }
+    }
+}
+
+type Buckets = HashMap<(ClientId, RequestClass), TokenBucket>;
+
+/// Tracks the budgets of the clients of the REST API.
+///
+/// The buckets are split into independently locked shards, such that requests of different
+/// clients rarely contend for the same lock, and such that evicting idle clients only blocks a
+/// fraction of the requests at a time.
+#[derive(Debug)]
+pub(super) struct RateLimiter {
+    config: RateLimitConfig,
+    shards: Vec<Mutex<Buckets>>,
+    hasher: RandomState,
+    metrics: RateLimitMetrics,
+}
+
+impl RateLimiter {
+    /// Creates a new rate limiter, or returns `None` if rate limiting is disabled.
+    pub fn new(config: &RateLimitConfig, registry: &Registry) -> Option<Self> {
+        if !config.enabled {
+            return None;
+        }
+        Some(Self {
+            config: config.clone(),
+            shards: (0..N_BUCKET_SHARDS)
+                .map(|_| Mutex::new(HashMap::new()))
+                .collect(),
+            hasher: RandomState::new(),
+            metrics: RateLimitMetrics::new(registry),
+        })
+    }
+
+    /// Takes a token from the client's bucket for the given class of requests.
+    ///
+    /// Returns [`RateLimited`] if the client exhausted its budget.
+    fn check(&self, client: ClientId, class: RequestClass) -> Result<(), RateLimited> {
+        self.check_at(client, class, Instant::now())
+    }
+
+    fn check_at(
+        &self,
+        client: ClientId,
+        class: RequestClass,
+        now: Instant,
+    ) -> Result<(), RateLimited> {
+        let config = self.bucket_config(class);
+        let max_clients_per_shard = self.config.max_tracked_clients.div_ceil(N_BUCKET_SHARDS);
+
+        let result = {
+            let mut key = (client, class);
+            let mut buckets = self.lock_shard(&key);
+            if !buckets.contains_key(&key) && buckets.len() >= max_clients_per_shard {
+                // Clients beyond the maximum share a single budget, which keeps the memory used by
+                // the rate limiter bounded.
+                key = (ClientId::Untracked, class);
+                drop(buckets);
+                buckets = self.lock_shard(&key);
+            }
+            let bucket = buckets.entry(key).or_insert_with(|| {
+                self.metrics.rate_limited_clients.inc();
+                TokenBucket::new(config, now)
+            });
+            bucket.try_acquire(config, now)
+        };
+
+        result.map_err(|retry_after| {
+            walrus_utils::with_label!(self.metrics.rate_limited_requests_total, class.as_str())
+                .inc();
+            RateLimited { retry_after }
+        })
+    }
+
+    fn lock_shard(&self, key: &(ClientId, RequestClass)) -> MutexGuard<'_, Buckets> {
+        // The modulus is below `N_BUCKET_SHARDS`, so the conversion is lossless.
+        #[allow(clippy::cast_possible_truncation)]
+        let index = (self.hasher.hash_one(key) % N_BUCKET_SHARDS as u64) as usize;
+        self.shards[index]
+            .lock()
+            .expect("mutex should not be poisoned")
+    }
+
+    /// Periodically discards the buckets of idle clients; never returns.
+    pub async fn evict_idle_clients_periodically(&self) {
+        let mut interval = tokio::time::interval(
+            (self.config.idle_client_timeout / 2).max(Duration::from_secs(1)),
+        );
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            self.evict_idle_clients(Instant::now());
+        }
+    }
+
+    /// Discards the buckets of clients that have not sent requests for the configured timeout.
+    ///
+    /// Unless the timeout is shorter than the time needed to refill a bucket, the buckets of these
+    /// clients are full again, such that discarding them does not change the budget of any client.
+    fn evict_idle_clients(&self, now: Instant) {
+        let timeout = self.config.idle_client_timeout;
+        for shard in &self.shards {
+            let mut buckets = shard.lock().expect("mutex should not be poisoned");
+            let n_buckets = buckets.len();
+            buckets.retain(|_, bucket| now.saturating_duration_since(bucket.last_refill) < timeout);
+            let n_evicted = n_buckets - buckets.len();
+            drop(buckets);
+            self.metrics
+                .rate_limited_clients
+                .sub(i64::try_from(n_evicted).unwrap_or(i64::MAX));
+        }
+    }
+
+    fn bucket_config(&self, class: RequestClass) -> &TokenBucketConfig {
+        match class {
+            RequestClass::Read => &self.config.read,
+            RequestClass::Write => &self.config.write,
+            RequestClass::Recovery => &self.config.recovery,
+        }
+    }
+}
+
+/// Rejects requests of clients that exceeded their budget with a 429 response.
+///
+/// Requests to unknown routes are not limited, and neither are requests whose peer address is
+/// unknown.
+pub(super) async fn rate_limit_layer<S: SyncServiceState>(
+    State(state): State<RestApiState<S>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(limiter) = state.rate_limiter.as_deref() else {
+        return next.run(request).await;
+    };
+    let Some(class) = request
+        .extensions()
+        .get::<MatchedPath>()
+        .and_then(|path| RequestClass::from_route(request.method(), path.as_str()))
+    else {
+        return next.run(request).await;
+    };
+    let Some(ConnectInfo(peer_address)) = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .copied()
+    else {
+        return next.run(request).await;
+    };
+
+    let (committee_member, request) = match verified_committee_member(request, |public_key| {
+        state.service.is_committee_member(public_key)
+    })
+    .await
+    {
+        Ok(result) => result,
+        Err(status) => return status.into_response(),
+    };
+    let client = match committee_member {
+        Some(_) if limiter.config.exempt_committee_members => {
+            walrus_utils::with_label!(
+                limiter.metrics.rate_limit_exempt_requests_total,
+                class.as_str()
+            )
+            .inc();
+            return next.run(request).await;
+        }
+        Some(public_key) => ClientId::StorageNode(public_key),
+        None => ClientId::from_ip(peer_address.ip()),
+    };
+
+    if let Err(error) = limiter.check(client.clone(), class) {
+        tracing::debug!(
+            ?client,
+            request_class = class.as_str(),
+            "rate limiting request"
+        );
+        let retry_after = error.retry_after;
+        let mut response = error.into_response();
+        if let Some(retry_after) = retry_after {
+            // Retry-After is specified in whole seconds, so round up to not retry too early.
+            let seconds = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+            response
+                .headers_mut()
+                .insert(RETRY_AFTER, HeaderValue::from(seconds));
+        }
+        return response;
+    }
+
+    next.run(request).await
+}
+
+/// Returns the public key of the committee member that signed the shard-sync request, together
+/// with the request to forward.
+///
+/// The key is `None` for all other requests, for requests of nodes that are not members of the
+/// current committee, and for requests whose signature does not verify under the key in the
+/// `Authorization` header. As the signature is part of the body, the body of shard-sync requests
+/// with the key of a committee member is buffered; a `413` status is returned if it is too large.
+async fn verified_committee_member(
+    request: Request,
+    is_committee_member: impl FnOnce(&PublicKey) -> bool,
+) -> Result<(Option<PublicKey>, Request), StatusCode> {
+    if request.uri().path() != routes::SYNC_SHARD_ENDPOINT {
+        return Ok((None, request));
+    }
+    let Some(public_key) = request
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|header| header.to_str().ok())
+        .and_then(|value| PublicKey::decode_base64(value).ok())
+        .filter(|public_key| is_committee_member(public_key))
+    else {
+        return Ok((None, request));
+    };
+
+    let (parts, request_body) = request.into_parts();
+    let bytes = body::to_bytes(request_body, MAX_SYNC_SHARD_REQUEST_SIZE)
+        .await
+        .map_err(|_| StatusCode::PAYLOAD_TOO_LARGE)?;
+    let is_verified = bcs::from_bytes::<SignedSyncShardRequest>(&bytes)
+        .is_ok_and(|signed| signed.verify_signature_and_get_message(&public_key).is_ok());
+
+    Ok((
+        is_verified.then_some(public_key),
+        Request::from_parts(parts, Body::from(bytes)),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use walrus_core::{
+        BlobId,
+        ShardIndex,
+        SliverType,
+        keys::ProtocolKeyPair,
+        messages::{SyncShardMsg, SyncShardRequest},
+    };
+    use walrus_test_utils::param_test;
+
+    use super::*;
+
+    fn rate_limiter(read: TokenBucketConfig) -> RateLimiter {
+        RateLimiter::new(
+            &RateLimitConfig {
+                enabled: true,
+                read,
+                ..Default::default()
+            },
+            &Registry::default(),
+        )
+        .expect("rate limiting is enabled")
+    }
+
+    fn ip_client(last_byte: u8) -> ClientId {
+        ClientId::Ip(IpAddr::from([10, 0, 0, last_byte]))
+    }
+
+    #[test]
+    fn disabled_rate_limiter_is_not_created() {
+        assert!(RateLimiter::new(&RateLimitConfig::default(), &Registry::default()).is_none());
+    }
+
+    param_test! {
+        classifies_routes: [
+            get_metadata: (Method::GET, routes::METADATA_ENDPOINT, Some(RequestClass::Read)),
+            put_metadata: (Method::PUT, routes::METADATA_ENDPOINT, Some(RequestClass::Write)),
+            get_sliver: (Method::GET, routes::SLIVER_ENDPOINT, Some(RequestClass::Read)),
+            put_sliver: (Method::PUT, routes::SLIVER_ENDPOINT, Some(RequestClass::Write)),
+            blob_status: (Method::GET, routes::BLOB_STATUS_ENDPOINT, Some(RequestClass::Read)),
+            inconsistency_proof: (
+                Method::POST, routes::INCONSISTENCY_PROOF_ENDPOINT, Some(RequestClass::Write)
+            ),
+            recovery_symbols: (
+                Method::GET, routes::RECOVERY_SYMBOL_LIST_ENDPOINT, Some(RequestClass::Recovery)
+            ),
+            sync_shard: (Method::POST, routes::SYNC_SHARD_ENDPOINT, Some(RequestClass::Recovery)),
+            api_docs: (Method::GET, routes::API_DOCS_ENDPOINT, None),
+        ]
+    }
+    fn classifies_routes(method: Method, route: &str, expected: Option<RequestClass>) {
+        assert_eq!(RequestClass::from_route(&method, route), expected);
+    }
+
+    #[test]
+    fn rejects_requests_after_burst_until_refilled() {
+        let limiter = rate_limiter(TokenBucketConfig {
+            requests_per_second: 2,
+            burst_size: 3,
+        });
+        let now = Instant::now();
+
+        for _ in 0..3 {
+            assert!(
+                limiter
+                    .check_at(ip_client(1), RequestClass::Read, now)
+                    .is_ok()
+            );
+        }
+        let error = limiter
+            .check_at(ip_client(1), RequestClass::Read, now)
+            .expect_err("the burst is exhausted");
+        assert_eq!(error.retry_after, Some(Duration::from_millis(500)));
+
+        let later = now + Duration::from_millis(500);
+        assert!(
+            limiter
+                .check_at(ip_client(1), RequestClass::Read, later)
+                .is_ok()
+        );
+        assert!(
+            limiter
+                .check_at(ip_client(1), RequestClass::Read, later)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn budgets_are_separate_per_client_and_class() {
+        let limiter = rate_limiter(TokenBucketConfig {
+            requests_per_second: 1,
+            burst_size: 1,
+        });
+        let now = Instant::now();
+        let node = ClientId::StorageNode(ProtocolKeyPair::generate().public().clone());
+
+        assert!(
+            limiter
+                .check_at(ip_client(1), RequestClass::Read, now)
+                .is_ok()
+        );
+        assert!(
+            limiter
+                .check_at(ip_client(1), RequestClass::Read, now)
+                .is_err()
+        );
+
+        assert!(
+            limiter
+                .check_at(ip_client(2), RequestClass::Read, now)
+                .is_ok()
+        );
+        assert!(limiter.check_at(node, RequestClass::Read, now).is_ok());
+        assert!(
+            limiter
+                .check_at(ip_client(1), RequestClass::Write, now)
+                .is_ok()
+        );
+        assert!(
+            limiter
+                .check_at(ip_client(1), RequestClass::Recovery, now)
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn empty_bucket_without_refill_has_no_retry_time() {
+        let limiter = rate_limiter(TokenBucketConfig {
+            requests_per_second: 0,
+            burst_size: 1,
+        });
+        let now = Instant::now();
+
+        assert!(
+            limiter
+                .check_at(ip_client(1), RequestClass::Read, now)
+                .is_ok()
+        );
+        let error = limiter
+            .check_at(
+                ip_client(1),
+                RequestClass::Read,
+                now + Duration::from_secs(60),
+            )
+            .expect_err("the bucket is never refilled");
+        assert_eq!(error.retry_after, None);
+    }
+
+    #[test]
+    fn evicts_idle_clients() {
+        let limiter = rate_limiter(TokenBucketConfig {
+            requests_per_second: 1,
+            burst_size: 1,
+        });
+        let now = Instant::now();
+        let timeout = limiter.config.idle_client_timeout;
+
+        limiter
+            .check_at(ip_client(1), RequestClass::Read, now)
+            .expect("first request succeeds");
+        limiter
+            .check_at(ip_client(2), RequestClass::Read, now + timeout / 2)
+            .expect("first request succeeds");
+
+        limiter.evict_idle_clients(now + timeout);
+        let is_tracked = |client| {
+            let key = (client, RequestClass::Read);
+            limiter.lock_shard(&key).contains_key(&key)
+        };
+        assert!(!is_tracked(ip_client(1)));
+        assert!(is_tracked(ip_client(2)));
+        assert_eq!(limiter.metrics.rate_limited_clients.get(), 1);
+    }
+
+    #[test]
+    fn identifies_ipv6_clients_by_prefix() {
+        let address = |last_segment| IpAddr::from([0x2001, 0xdb8, 0, 1, 0, 0, 0, last_segment]);
+        assert_eq!(ClientId::from_ip(address(1)), ClientId::from_ip(address(2)));
+        assert_ne!(
+            ClientId::from_ip(address(1)),
+            ClientId::from_ip(IpAddr::from([0x2001, 0xdb8, 0, 2, 0, 0, 0, 1]))
+        );
+        // IPv4-mapped IPv6 addresses are identified by their IPv4 address.
+        assert_eq!(
+            ClientId::from_ip(IpAddr::from([0, 0, 0, 0, 0, 0xffff, 0x0a00, 0x0001])),
+            ip_client(1)
+        );
+    }
+
+    #[test]
+    fn clients_beyond_the_maximum_share_a_budget() {
+        let limiter = RateLimiter::new(
+            &RateLimitConfig {
+                enabled: true,
+                read: TokenBucketConfig {
+                    requests_per_second: 0,
+                    burst_size: 1,
+                },
+                max_tracked_clients: N_BUCKET_SHARDS,
+                ..Default::default()
+            },
+            &Registry::default(),
+        )
+        .expect("rate limiting is enabled");
+        let now = Instant::now();
+
+        // With one client per shard, some of the clients must share the untracked budget.
+        let n_accepted = (0..=u8::try_from(4 * N_BUCKET_SHARDS).expect("fits into a u8"))
+            .filter(|&client| {
+                limiter
+                    .check_at(ip_client(client), RequestClass::Read, now)
+                    .is_ok()
+            })
+            .count();
+        assert!(n_accepted <= N_BUCKET_SHARDS + 1);
+        let n_tracked: usize = limiter
+            .shards
+            .iter()
+            .map(|shard| shard.lock().unwrap().len())
+            .sum();
+        assert!(n_tracked <= N_BUCKET_SHARDS + 1);
+    }
+
+    fn sync_shard_request(public_key: &PublicKey, signer: &ProtocolKeyPair) -> Request {
+        let message = SyncShardMsg::new(
+            1,
+            SyncShardRequest::new(ShardIndex(0), SliverType::Primary, BlobId::ZERO, 10, 1),
+        );
+        let body = bcs::to_bytes(&signer.sign_message(&message)).expect("serialization succeeds");
+        Request::post(routes::SYNC_SHARD_ENDPOINT)
+            .header(AUTHORIZATION, public_key.encode_base64())
+            .body(Body::from(body))
+            .expect("the request is valid")
+    }
+
+    #[tokio::test]
+    async fn only_verified_requests_are_attributed_to_committee_members() {
+        let member = ProtocolKeyPair::generate();
+        let attacker = ProtocolKeyPair::generate();
+        let is_member = |public_key: &PublicKey| public_key == member.public();
+
+        let (verified, request) =
+            verified_committee_member(sync_shard_request(member.public(), &member), is_member)
+                .await
+                .expect("the body is small enough");
+        assert_eq!(verified.as_ref(), Some(member.public()));
+        // The buffered body is forwarded unchanged.
+        let forwarded = body::to_bytes(request.into_body(), usize::MAX)
+            .await
+            .expect("the body is buffered");
+        let original = body::to_bytes(
+            sync_shard_request(member.public(), &member).into_body(),
+            usize::MAX,
+        )
+        .await
+        .expect("the body is in memory");
+        assert_eq!(forwarded, original);
+
+        // A request with the key of a committee member, signed by another key.
+        let (verified, _) =
+            verified_committee_member(sync_shard_request(member.public(), &attacker), is_member)
+                .await
+                .expect("the body is small enough");
+        assert_eq!(verified, None);
+
+        // A correctly signed request of a node that is not in the committee.
+        let (verified, _) =
+            verified_committee_member(sync_shard_request(attacker.public(), &attacker), is_member)
+                .await
+                .expect("the body is small enough");
+        assert_eq!(verified, None);
+    }
+}
@@ -150,6 +150,9 @@ impl ShardSyncHandler {
 
         for blob_info in blob_infos {
             let (blob_id, blob_info) = blob_info?;
+            if self.node.is_blocked(&blob_id) {
+                continue;
+            }
             let node_clone = self.node.clone();
 
             // TODO(WAL-478):
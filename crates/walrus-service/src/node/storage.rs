@@ -3,9 +3,9 @@
 
 use core::fmt::{self, Display};
 use std::{
-    collections::{HashMap, hash_map::Entry},
+    collections::{HashMap, HashSet, hash_map::Entry},
     fmt::Debug,
-    ops::Bound::{Excluded, Included},
+    ops::Bound::{Excluded, Included, Unbounded},
     path::Path,
     sync::Arc,
     time::Instant,
@@ -43,6 +43,7 @@ use self::{
         PerObjectBlobInfoIterator,
     },
     constants::{
+        garbage_collection_cursor_cf_name,
         metadata_cf_name,
         node_status_cf_name,
         pending_recover_slivers_column_family_name,
@@ -90,6 +91,10 @@ pub(crate) fn node_status_options(db_config: &DatabaseConfig) -> Options {
     db_config.node_status().to_options()
 }
 
+pub(crate) fn garbage_collection_cursor_options(db_config: &DatabaseConfig) -> Options {
+    db_config.garbage_collection_cursor().to_options()
+}
+
 /// The status of the node.
 ///
 /// ```text
@@ -190,6 +195,13 @@ pub struct Storage {
     metadata: DBMap<BlobId, BlobMetadata>,
     blob_info: BlobInfoTable,
     event_cursor: EventCursorTable,
+    garbage_collection_cursor: DBMap<(), BlobId>,
+    /// Held shared while the blob-info table is updated with an event, and exclusively while the
+    /// data of unregistered blobs is garbage-collected.
+    ///
+    /// This ensures that the data of a blob is only deleted if the blob is still unregistered when
+    /// the deletion is written, even if it is registered again concurrently.
+    garbage_collection_lock: Arc<std::sync::RwLock<()>>,
     shards: Arc<RwLock<HashMap<ShardIndex, Arc<ShardStorage>>>>,
     config: DatabaseConfig,
     metrics: Arc<CommonDatabaseMetrics>,
@@ -266,6 +278,8 @@ impl Storage {
         let metadata_cf_name = metadata_cf_name();
         let blob_info_column_families = BlobInfoTable::options(&db_config);
         let (event_cursor_cf_name, event_cursor_options) = EventCursorTable::options(&db_config);
+        let garbage_collection_cursor_cf_name = garbage_collection_cursor_cf_name();
+        let garbage_collection_cursor_options = garbage_collection_cursor_options(&db_config);
 
         let expected_column_families: Vec<_> = shard_column_families
             .iter_mut()
@@ -274,6 +288,10 @@ impl Storage {
                 (node_status_cf_name, node_status_options),
                 (metadata_cf_name, metadata_options),
                 (event_cursor_cf_name, event_cursor_options),
+                (
+                    garbage_collection_cursor_cf_name,
+                    garbage_collection_cursor_options,
+                ),
             ])
             .chain(blob_info_column_families)
             .collect::<Vec<_>>();
@@ -302,6 +320,13 @@ impl Storage {
             false,
         )?;
 
+        let garbage_collection_cursor = DBMap::reopen(
+            &database,
+            Some(garbage_collection_cursor_cf_name),
+            &ReadWriteOptions::default(),
+            false,
+        )?;
+
         let event_cursor = EventCursorTable::reopen(&database)?;
         let blob_info = BlobInfoTable::reopen(&database)?;
         let shards = Arc::new(RwLock::new(
@@ -320,6 +345,8 @@ impl Storage {
             metadata,
             blob_info,
             event_cursor,
+            garbage_collection_cursor,
+            garbage_collection_lock: Default::default(),
             shards,
             config: db_config,
             metrics: Arc::new(CommonDatabaseMetrics::new_with_id(
@@ -565,6 +592,10 @@ impl Storage {
         event_index: u64,
         event: &BlobEvent,
     ) -> Result<(), TypedStoreError> {
+        let _guard = self
+            .garbage_collection_lock
+            .read()
+            .expect("the lock is never poisoned");
         if let NodeStatus::RecoveryCatchUpWithIncompleteHistory { epoch_at_start, .. } =
             self.node_status()?
         {
@@ -658,6 +689,118 @@ impl Storage {
         Ok(())
     }
 
+    /// Deletes the metadata and slivers of a blob that is no longer registered in `epoch` and marks
+    /// its metadata as no longer stored in the blob-info table.
+    ///
+    /// The registration of the blob is checked atomically with the deletion. Returns false, and
+    /// does not delete anything, if the blob is registered. If the blob is registered again later,
+    /// this allows the node to recover its data again.
+    #[tracing::instrument(skip_all)]
+    pub(crate) async fn garbage_collect_blob_data(
+        &self,
+        blob_id: &BlobId,
+        epoch: Epoch,
+    ) -> Result<bool, TypedStoreError> {
+        let collected = self
+            .garbage_collect_unregistered(std::slice::from_ref(blob_id), epoch, None)
+            .await?;
+        Ok(!collected.is_empty())
+    }
+
+    /// Garbage-collects the data of a batch of blobs that are no longer registered in `epoch`, see
+    /// [`Self::garbage_collect_blob_data`], and returns the IDs of the blobs that were collected.
+    ///
+    /// In the same atomic write, the garbage-collection cursor is set to `next_cursor`, such that
+    /// an interrupted garbage-collection pass can be resumed after a restart. Setting the cursor
+    /// to `None` marks the end of the pass.
+    #[tracing::instrument(skip_all)]
+    pub(crate) async fn garbage_collect_batch(
+        &self,
+        blob_ids: &[BlobId],
+        epoch: Epoch,
+        next_cursor: Option<&BlobId>,
+    ) -> Result<Vec<BlobId>, TypedStoreError> {
+        self.garbage_collect_unregistered(blob_ids, epoch, Some(next_cursor))
+            .await
+    }
+
+    /// Deletes the data of the blobs not registered in `epoch` and optionally updates the
+    /// garbage-collection cursor, in a single atomic write.
+    async fn garbage_collect_unregistered(
+        &self,
+        blob_ids: &[BlobId],
+        epoch: Epoch,
+        next_cursor: Option<Option<&BlobId>>,
+    ) -> Result<Vec<BlobId>, TypedStoreError> {
+        let shards = self.existing_shard_storages().await;
+
+        // No events can be applied to the blob-info table until the batch is written, so the blobs
+        // cannot be registered between the check below and the deletion.
+        let _guard = self
+            .garbage_collection_lock
+            .write()
+            .expect("the lock is never poisoned");
+        let mut batch = self.metadata.batch();
+        let mut collected = Vec::with_capacity(blob_ids.len());
+        for blob_id in blob_ids {
+            if self
+                .blob_info
+                .get(blob_id)?
+                .is_some_and(|blob_info| blob_info.is_registered(epoch))
+            {
+                tracing::debug!(
+                    walrus.blob_id = %blob_id,
+                    "blob was registered again; not garbage-collecting its data"
+                );
+                continue;
+            }
+            self.delete_metadata(&mut batch, blob_id, true)?;
+            for shard in &shards {
+                shard.delete_sliver_pair(&mut batch, blob_id)?;
+            }
+            collected.push(*blob_id);
+        }
+        match next_cursor {
+            Some(Some(cursor)) => {
+                batch.insert_batch(&self.garbage_collection_cursor, [(&(), cursor)])?;
+            }
+            Some(None) => {
+                batch.delete_batch(&self.garbage_collection_cursor, [()])?;
+            }
+            None => (),
+        }
+        batch.write()?;
+        Ok(collected)
+    }
+
+    /// Returns the last blob ID processed by the ongoing garbage-collection pass.
+    ///
+    /// Returns `None` if no pass is in progress.
+    pub(crate) fn garbage_collection_cursor(&self) -> Result<Option<BlobId>, TypedStoreError> {
+        self.garbage_collection_cursor.get(&())
+    }
+
+    /// Returns up to `limit` entries of the blob-info table, starting after the blob ID
+    /// `cursor`, or at the beginning of the table if `cursor` is `None`.
+    pub(crate) fn blob_info_batch_after(
+        &self,
+        cursor: Option<&BlobId>,
+        limit: usize,
+    ) -> Result<Vec<(BlobId, BlobInfo)>, TypedStoreError> {
+        let lower_bound = cursor.map_or(Unbounded, |blob_id| Excluded(*blob_id));
+        self.blob_info.iter_from(lower_bound)?.take(limit).collect()
+    }
+
+    /// Returns the IDs of all blobs whose data was deleted through a deny list.
+    pub(crate) fn deny_listed_blob_ids(&self) -> Result<HashSet<BlobId>, TypedStoreError> {
+        self.blob_info
+            .iter_from(Unbounded)?
+            .filter_map_ok(|(blob_id, blob_info)| {
+                blob_info.deny_list_event().is_some().then_some(blob_id)
+            })
+            .collect()
+    }
+
     /// Deletes the metadata for the provided [`BlobId`].
     fn delete_metadata(
         &self,
@@ -880,6 +1023,7 @@ pub(crate) mod tests {
     }
 
     pub(crate) const BLOB_ID: BlobId = BlobId([7; 32]);
+    const OTHER_BLOB_ID: BlobId = BlobId([8; 32]);
     pub(crate) const SHARD_INDEX: ShardIndex = ShardIndex(3);
     pub(crate) const OTHER_SHARD_INDEX: ShardIndex = ShardIndex(9);
 
@@ -990,6 +1134,96 @@ pub(crate) mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn garbage_collects_batch_and_persists_cursor() -> TestResult {
+        let storage = populated_storage(&[(
+            SHARD_INDEX,
+            vec![
+                (BLOB_ID, WhichSlivers::Both),
+                (OTHER_BLOB_ID, WhichSlivers::Both),
+            ],
+        )])
+        .await?;
+        let storage = storage.as_ref();
+        let shard = storage
+            .shard_storage(SHARD_INDEX)
+            .await
+            .expect("shard storage should exist");
+        for blob_id in [BLOB_ID, OTHER_BLOB_ID] {
+            storage.update_blob_info(0, &BlobRegistered::for_testing(blob_id).into())?;
+        }
+        assert_eq!(storage.blob_info_batch_after(None, 10)?.len(), 2);
+        assert_eq!(storage.garbage_collection_cursor()?, None);
+
+        // The blobs registered by the test events expire at epoch 42.
+        let collected = storage
+            .garbage_collect_batch(&[BLOB_ID], 42, Some(&BLOB_ID))
+            .await?;
+
+        assert_eq!(collected, vec![BLOB_ID]);
+        assert!(!shard.is_sliver_pair_stored(&BLOB_ID)?);
+        assert!(shard.is_sliver_pair_stored(&OTHER_BLOB_ID)?);
+        assert_eq!(storage.garbage_collection_cursor()?, Some(BLOB_ID));
+        let remaining = storage.blob_info_batch_after(Some(&BLOB_ID), 10)?;
+        assert_eq!(
+            remaining
+                .into_iter()
+                .map(|(blob_id, _)| blob_id)
+                .collect::<Vec<_>>(),
+            vec![OTHER_BLOB_ID]
+        );
+
+        storage.garbage_collect_batch(&[], 42, None).await?;
+        assert_eq!(storage.garbage_collection_cursor()?, None);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn does_not_garbage_collect_blob_registered_after_the_scan() -> TestResult {
+        let storage =
+            populated_storage(&[(SHARD_INDEX, vec![(BLOB_ID, WhichSlivers::Both)])]).await?;
+        let storage = storage.as_ref();
+        let shard = storage
+            .shard_storage(SHARD_INDEX)
+            .await
+            .expect("shard storage should exist");
+        storage.update_blob_info(0, &BlobRegistered::for_testing(BLOB_ID).into())?;
+
+        // The garbage collector scans the blob-info table and finds the expired blob.
+        let epoch = 50;
+        let candidates = storage
+            .blob_info_batch_after(None, 10)?
+            .into_iter()
+            .filter(|(_, blob_info)| !blob_info.is_registered(epoch))
+            .map(|(blob_id, _)| blob_id)
+            .collect::<Vec<_>>();
+        assert_eq!(candidates, vec![BLOB_ID]);
+
+        // Before the data is deleted, the blob is registered again.
+        storage.update_blob_info(
+            1,
+            &BlobRegistered {
+                end_epoch: 100,
+                ..BlobRegistered::for_testing(BLOB_ID)
+            }
+            .into(),
+        )?;
+
+        let collected = storage
+            .garbage_collect_batch(&candidates, epoch, None)
+            .await?;
+        assert!(collected.is_empty());
+        assert!(shard.is_sliver_pair_stored(&BLOB_ID)?);
+        assert!(storage.get_metadata(&BLOB_ID)?.is_some());
+        assert!(!storage.garbage_collect_blob_data(&BLOB_ID, epoch).await?);
+
+        // Once the blob has expired again, its data is collected.
+        assert!(storage.garbage_collect_blob_data(&BLOB_ID, 100).await?);
+        assert!(!shard.is_sliver_pair_stored(&BLOB_ID)?);
+        assert!(storage.get_metadata(&BLOB_ID)?.is_none());
+        Ok(())
+    }
+
     #[tokio::test]
     async fn delete_on_empty_metadata_does_not_error() -> TestResult {
         let storage = empty_storage().await;
@@ -1093,6 +1327,39 @@ pub(crate) mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn returns_deny_listed_blob_ids() -> TestResult {
+        let storage = empty_storage().await;
+        let storage = storage.as_ref();
+
+        for blob_id in [BLOB_ID, OTHER_BLOB_ID] {
+            storage.blob_info.merge_blob_info(
+                &blob_id,
+                &BlobInfoMergeOperand::new_change_for_testing(
+                    BlobStatusChangeType::Register,
+                    false,
+                    1,
+                    42,
+                    event_id_for_testing(),
+                ),
+            )?;
+        }
+        assert!(storage.deny_listed_blob_ids()?.is_empty());
+
+        storage.blob_info.merge_blob_info(
+            &OTHER_BLOB_ID,
+            &BlobInfoMergeOperand::MarkDenyListed {
+                epoch: 2,
+                status_event: event_id_for_testing(),
+            },
+        )?;
+        assert_eq!(
+            storage.deny_listed_blob_ids()?,
+            HashSet::from([OTHER_BLOB_ID])
+        );
+        Ok(())
+    }
+
     #[tokio::test]
     async fn update_blob_info_metadata_stored() -> TestResult {
         let storage = empty_storage().await;
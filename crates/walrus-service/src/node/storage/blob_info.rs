@@ -22,7 +22,14 @@ use typed_store::{
 };
 use walrus_core::{BlobId, Epoch};
 use walrus_storage_node_client::api::{BlobStatus, DeletableCounts};
-use walrus_sui::types::{BlobCertified, BlobDeleted, BlobEvent, BlobRegistered, InvalidBlobId};
+use walrus_sui::types::{
+    BlobCertified,
+    BlobDeleted,
+    BlobEvent,
+    BlobRegistered,
+    DenyListBlobDeleted,
+    InvalidBlobId,
+};
 
 use self::per_object_blob_info::PerObjectBlobInfoMergeOperand;
 pub(crate) use self::per_object_blob_info::{PerObjectBlobInfo, PerObjectBlobInfoApi};
@@ -336,6 +343,19 @@ impl BlobInfoTable {
         )
     }
 
+    /// Returns an iterator over all entries in the aggregate blob info table starting with the
+    /// `starting_blob_id` bound.
+    pub fn iter_from(
+        &self,
+        starting_blob_id_bound: Bound<BlobId>,
+    ) -> Result<
+        impl Iterator<Item = Result<(BlobId, BlobInfo), TypedStoreError>> + '_,
+        TypedStoreError,
+    > {
+        self.aggregate_blob_info
+            .safe_range_iter((starting_blob_id_bound, Unbounded))
+    }
+
     /// Returns the blob info for `blob_id`.
     pub fn get(&self, blob_id: &BlobId) -> Result<Option<BlobInfo>, TypedStoreError> {
         self.aggregate_blob_info.get(blob_id)
@@ -534,6 +554,11 @@ pub(crate) trait BlobInfoApi: CertifiedBlobInfoApi {
     /// Returns `None` if it isn't invalid.
     fn invalidation_event(&self) -> Option<EventID>;
 
+    /// Returns the event through which this blob was deleted through a deny list.
+    ///
+    /// Returns `None` if it isn't deny-listed.
+    fn deny_list_event(&self) -> Option<EventID>;
+
     /// Converts the blob information to a `BlobStatus` object.
     fn to_blob_status(&self, current_epoch: Epoch) -> BlobStatus;
 }
@@ -549,6 +574,10 @@ pub(super) enum BlobInfoMergeOperand {
         change_type: BlobStatusChangeType,
         change_info: BlobStatusChangeInfo,
     },
+    MarkDenyListed {
+        epoch: Epoch,
+        status_event: EventID,
+    },
 }
 
 impl ToBytes for BlobInfoMergeOperand {}
@@ -685,6 +714,20 @@ impl From<&InvalidBlobId> for BlobInfoMergeOperand {
     }
 }
 
+impl From<&DenyListBlobDeleted> for BlobInfoMergeOperand {
+    fn from(value: &DenyListBlobDeleted) -> Self {
+        let DenyListBlobDeleted {
+            epoch,
+            blob_id: _,
+            event_id,
+        } = value;
+        Self::MarkDenyListed {
+            epoch: *epoch,
+            status_event: *event_id,
+        }
+    }
+}
+
 impl From<&BlobEvent> for BlobInfoMergeOperand {
     fn from(value: &BlobEvent) -> Self {
         match value {
@@ -692,13 +735,7 @@ impl From<&BlobEvent> for BlobInfoMergeOperand {
             BlobEvent::Certified(event) => event.into(),
             BlobEvent::Deleted(event) => event.into(),
             BlobEvent::InvalidBlobID(event) => event.into(),
-            BlobEvent::DenyListBlobDeleted(_) => {
-                // TODO (WAL-424): Implement DenyListBlobDeleted event handling.
-                // Note: It's fine to panic here with a todo!, because in order to trigger this
-                // event, we need f+1 signatures and until the Rust integration is implemented no
-                // such event should be emitted.
-                todo!("DenyListBlobDeleted event handling is not yet implemented");
-            }
+            BlobEvent::DenyListBlobDeleted(event) => event.into(),
         }
     }
 }
@@ -707,6 +744,7 @@ impl From<&BlobEvent> for BlobInfoMergeOperand {
 pub(crate) enum BlobInfoV1 {
     Invalid { epoch: Epoch, event: EventID },
     Valid(ValidBlobInfoV1),
+    DenyListed { epoch: Epoch, event: EventID },
 }
 
 impl ToBytes for BlobInfoV1 {}
@@ -1226,10 +1264,25 @@ impl BlobInfoApi for BlobInfoV1 {
         }
     }
 
+    fn deny_list_event(&self) -> Option<EventID> {
+        if let Self::DenyListed { event, .. } = self {
+            Some(*event)
+        } else {
+            None
+        }
+    }
+
     fn to_blob_status(&self, current_epoch: Epoch) -> BlobStatus {
         match self {
-            BlobInfoV1::Invalid { event, .. } => BlobStatus::Invalid { event: *event },
+            BlobInfoV1::Invalid { event, .. } => BlobStatus::Invalid {
+                event: *event,
+                deny_listed: false,
+            },
             BlobInfoV1::Valid(valid_blob_info) => valid_blob_info.to_blob_status(current_epoch),
+            BlobInfoV1::DenyListed { event, .. } => BlobStatus::Invalid {
+                event: *event,
+                deny_listed: true,
+            },
         }
     }
 }
@@ -1253,6 +1306,20 @@ impl Mergeable for BlobInfoV1 {
                     event: status_event,
                 };
             }
+            // Blobs deleted through a deny list can no longer be registered or stored.
+            (Self::DenyListed { .. }, _) => (),
+            (
+                _,
+                BlobInfoMergeOperand::MarkDenyListed {
+                    epoch,
+                    status_event,
+                },
+            ) => {
+                return Self::DenyListed {
+                    epoch,
+                    event: status_event,
+                };
+            }
             (
                 Self::Valid(ValidBlobInfoV1 {
                     is_metadata_stored, ..
@@ -1309,6 +1376,13 @@ impl Mergeable for BlobInfoV1 {
                 epoch,
                 event: status_event,
             }),
+            BlobInfoMergeOperand::MarkDenyListed {
+                epoch,
+                status_event,
+            } => Some(BlobInfoV1::DenyListed {
+                epoch,
+                event: status_event,
+            }),
             BlobInfoMergeOperand::ChangeStatus { .. }
             | BlobInfoMergeOperand::MarkMetadataStored(_) => {
                 tracing::error!(
@@ -1731,6 +1805,10 @@ mod tests {
                 epoch: 0,
                 status_event: event_id_for_testing()
             }),
+            deny_list: (BlobInfoMergeOperand::MarkDenyListed {
+                epoch: 0,
+                status_event: event_id_for_testing()
+            }),
         ]
     }
     fn test_merge_new_expected_success_cases_invariants(operand: BlobInfoMergeOperand) {
@@ -1744,6 +1822,10 @@ mod tests {
                 epoch: 0,
                 status_event: event_id_for_testing()
             }),
+            deny_list: (BlobInfoMergeOperand::MarkDenyListed {
+                epoch: 0,
+                status_event: event_id_for_testing()
+            }),
             metadata_true: (BlobInfoMergeOperand::MarkMetadataStored(true)),
             metadata_false: (BlobInfoMergeOperand::MarkMetadataStored(false)),
             register_permanent: (BlobInfoMergeOperand::new_change_for_testing(
@@ -1856,6 +1938,75 @@ mod tests {
         assert_eq!(BlobInfoV1::Invalid { epoch: 2, event }, updated_info);
     }
 
+    param_test! {
+        test_deny_listed_status_is_not_changed: [
+            deny_list: (BlobInfoMergeOperand::MarkDenyListed {
+                epoch: 0,
+                status_event: event_id_for_testing()
+            }),
+            metadata_true: (BlobInfoMergeOperand::MarkMetadataStored(true)),
+            metadata_false: (BlobInfoMergeOperand::MarkMetadataStored(false)),
+            register_permanent: (BlobInfoMergeOperand::new_change_for_testing(
+                BlobStatusChangeType::Register, false, 42, 314, event_id_for_testing()
+            )),
+            register_deletable: (BlobInfoMergeOperand::new_change_for_testing(
+                BlobStatusChangeType::Register, true, 42, 314, event_id_for_testing()
+            )),
+            certify_permanent: (BlobInfoMergeOperand::new_change_for_testing(
+                BlobStatusChangeType::Certify, false, 42, 314, event_id_for_testing()
+            )),
+            extend: (BlobInfoMergeOperand::new_change_for_testing(
+                BlobStatusChangeType::Extend, false, 42, 314, event_id_for_testing()
+            )),
+        ]
+    }
+    fn test_deny_listed_status_is_not_changed(operand: BlobInfoMergeOperand) {
+        let blob_info = BlobInfoV1::DenyListed {
+            epoch: 42,
+            event: event_id_for_testing(),
+        };
+        assert_eq!(blob_info, blob_info.clone().merge_with(operand));
+    }
+
+    param_test! {
+        test_mark_deny_listed_marks_everything_deny_listed: [
+            default: (Default::default()),
+            deletable_certified: (ValidBlobInfoV1{
+                count_deletable_total: 2,
+                count_deletable_certified: 1,
+                initial_certified_epoch: Some(0),
+                ..Default::default()
+            }),
+            permanent_certified: (ValidBlobInfoV1{
+                is_metadata_stored: true,
+                permanent_total: Some(PermanentBlobInfoV1::new_fixed_for_testing(2, 3, 0)),
+                permanent_certified: Some(PermanentBlobInfoV1::new_fixed_for_testing(1, 2, 0)),
+                initial_certified_epoch: Some(1),
+                ..Default::default()
+            }),
+        ]
+    }
+    fn test_mark_deny_listed_marks_everything_deny_listed(preexisting_info: ValidBlobInfoV1) {
+        let preexisting_info = preexisting_info.into();
+        check_invariants(&preexisting_info);
+        let event = event_id_for_testing();
+        let updated_info = preexisting_info.merge_with(BlobInfoMergeOperand::MarkDenyListed {
+            epoch: 2,
+            status_event: event,
+        });
+        assert_eq!(BlobInfoV1::DenyListed { epoch: 2, event }, updated_info);
+        assert!(!updated_info.is_registered(1));
+        assert!(!updated_info.is_metadata_stored());
+        assert_eq!(updated_info.deny_list_event(), Some(event));
+        assert_eq!(
+            updated_info.to_blob_status(1),
+            BlobStatus::Invalid {
+                event,
+                deny_listed: true,
+            }
+        );
+    }
+
     param_test! {
         test_merge_preexisting_expected_successes: [
             register_first_deletable: (
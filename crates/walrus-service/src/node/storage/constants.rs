@@ -11,6 +11,7 @@ const METADATA_COLUMN_FAMILY_NAME: &str = "metadata";
 const EVENT_INDEX_COLUMN_FAMILY_NAME: &str = "latest_handled_event_index";
 const EVENT_CURSOR_COLUMN_FAMILY_NAME: &str = "event_cursor";
 const EVENT_CURSOR_KEY: [u8; 6] = *b"cursor";
+const GARBAGE_COLLECTION_CURSOR_COLUMN_FAMILY_NAME: &str = "garbage_collection_cursor";
 
 // Base name for shard-related column families
 const SHARD_BASE_COLUMN_FAMILY_NAME: &str = "shard";
@@ -59,6 +60,11 @@ pub fn event_cursor_key() -> &'static [u8; 6] {
     &EVENT_CURSOR_KEY
 }
 
+/// Returns the name of the garbage-collection cursor column family.
+pub fn garbage_collection_cursor_cf_name() -> &'static str {
+    GARBAGE_COLLECTION_CURSOR_COLUMN_FAMILY_NAME
+}
+
 /// Returns the column family name for primary slivers of a shard.
 pub fn primary_slivers_column_family_name(id: ShardIndex) -> String {
     format!(
@@ -116,6 +122,10 @@ mod tests {
         assert_eq!(per_object_blob_info_cf_name(), "per_object_blob_info");
         assert_eq!(node_status_cf_name(), "node_status");
         assert_eq!(event_index_cf_name(), "latest_handled_event_index");
+        assert_eq!(
+            garbage_collection_cursor_cf_name(),
+            "garbage_collection_cursor"
+        );
 
         let shard = ShardIndex(900);
         assert_eq!(base_column_family_name(shard), "shard-900");
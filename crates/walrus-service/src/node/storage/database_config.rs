@@ -376,6 +376,8 @@ pub struct DatabaseConfig {
     pub(super) per_object_blob_info: Option<DatabaseTableOptions>,
     /// Event cursor database options.
     pub(super) event_cursor: Option<DatabaseTableOptions>,
+    /// Garbage-collection cursor database options.
+    pub(super) garbage_collection_cursor: Option<DatabaseTableOptions>,
     /// Shard database options.
     pub(super) shard: Option<DatabaseTableOptions>,
     /// Shard status database options.
@@ -467,6 +469,11 @@ impl DatabaseConfig {
         Self::inherit_from_or_use_template(&self.event_cursor, self.standard())
     }
 
+    /// Returns the garbage-collection cursor database option.
+    pub fn garbage_collection_cursor(&self) -> DatabaseTableOptions {
+        Self::inherit_from_or_use_template(&self.garbage_collection_cursor, self.standard())
+    }
+
     /// Returns the shard database option.
     pub fn shard(&self) -> DatabaseTableOptions {
         Self::inherit_from_or_use_template(&self.shard, self.optimized_for_blobs())
@@ -545,6 +552,7 @@ impl Default for DatabaseConfig {
             blob_info: None,
             per_object_blob_info: None,
             event_cursor: None,
+            garbage_collection_cursor: None,
             shard: None,
             shard_status: None,
             shard_sync_progress: None,
@@ -805,6 +813,10 @@ mod tests {
         // Verify optional fields with instance defaults (inherit from standard)
         assert_eq!(default_config.node_status(), default_config.standard());
         assert_eq!(default_config.event_cursor(), default_config.standard());
+        assert_eq!(
+            default_config.garbage_collection_cursor(),
+            default_config.standard()
+        );
         assert_eq!(default_config.certified(), default_config.standard());
 
         // Verify shard inherits from optimized_for_blobs
@@ -909,7 +909,7 @@ impl ShardStorage {
     fn batch_fetched_slivers_and_check_missing_blobs(
         &self,
         epoch: Epoch,
-        _node: &Arc<StorageNodeInner>,
+        node: &Arc<StorageNodeInner>,
         fetched_slivers: &[(BlobId, Sliver)],
         sliver_type: SliverType,
         mut next_blob_info: Option<(BlobId, BlobInfo)>,
@@ -930,6 +930,8 @@ impl ShardStorage {
             //  - metadata is correct
 
             match sliver {
+                // Slivers of blobs blocked on this node are not stored.
+                _ if node.is_blocked(blob_id) => (),
                 Sliver::Primary(primary) => {
                     assert_eq!(sliver_type, SliverType::Primary);
                     batch.insert_batch(
@@ -1083,6 +1085,8 @@ impl ShardStorage {
                 self.skip_recover_blob(blob_id, sliver_type, &node, "already_stored")?;
             } else if !skip_certified_check_in_test && !node.is_blob_certified(&blob_id)? {
                 self.skip_recover_blob(blob_id, sliver_type, &node, "not_certified")?;
+            } else if node.is_blocked(&blob_id) {
+                self.skip_recover_blob(blob_id, sliver_type, &node, "blocked")?;
             } else {
                 futures.push(self.recover_blob(blob_id, sliver_type, node.clone(), epoch));
             }
@@ -60,10 +60,14 @@ use walrus_sui::{
     client::{
         BlobObjectMetadata,
         FixedSystemParameters,
+        ReadClient,
         SuiClientError,
         retry_client::RetriableRpcClient,
     },
-    test_utils::system_setup::{DEFAULT_MAX_EPOCHS_AHEAD, SystemContext},
+    test_utils::{
+        simulated_chain::SimulatedContractClient,
+        system_setup::{DEFAULT_MAX_EPOCHS_AHEAD, SystemContext},
+    },
     types::{
         Committee,
         ContractEvent,
@@ -95,6 +99,7 @@ use crate::{
         DatabaseConfig,
         Storage,
         StorageNode,
+        byzantine::{ByzantineMode, ByzantineStorageNode},
         committee::{
             BeginCommitteeChangeError,
             CommitteeLookupService,
@@ -250,7 +255,9 @@ pub struct StorageNodeHandle {
     /// The address of the metric service.
     pub metrics_address: SocketAddr,
     /// Handle the REST API.
-    pub rest_api: Arc<RestApiServer<StorageNode>>,
+    pub rest_api: Arc<RestApiServer<ByzantineStorageNode>>,
+    /// Switch for the byzantine behaviour of the node, as observed through its REST API.
+    pub byzantine_mode: ByzantineMode,
     /// Cancellation token for the REST API.
     pub cancel: CancellationToken,
     /// Client that can be used to communicate with the node.
@@ -623,9 +630,13 @@ impl SimStorageNodeHandle {
             .await?;
         let node = Arc::new(node);
 
-        // Starts rest api and node threads.
+        // Starts rest api and node threads. The byzantine behaviour of the node is controlled
+        // through the `storage_node_byzantine_config` fail point in simulation tests.
         let rest_api = Arc::new(RestApiServer::new(
-            node.clone(),
+            Arc::new(ByzantineStorageNode::new(
+                node.clone(),
+                ByzantineMode::default(),
+            )),
             cancel_token.clone(),
             RestApiConfig::from(&config),
             &metrics_registry,
@@ -1033,8 +1044,12 @@ impl StorageNodeHandleBuilder {
             .await?;
         let node = Arc::new(node);
 
+        let byzantine_mode = ByzantineMode::default();
         let rest_api = Arc::new(RestApiServer::new(
-            node.clone(),
+            Arc::new(ByzantineStorageNode::new(
+                node.clone(),
+                byzantine_mode.clone(),
+            )),
             cancel_token.clone(),
             RestApiConfig::from(&config),
             &metrics_registry,
@@ -1096,6 +1111,7 @@ impl StorageNodeHandleBuilder {
             rest_api_address: config.rest_api_address,
             metrics_address: config.metrics_address,
             rest_api,
+            byzantine_mode,
             cancel: cancel_token,
             client,
             storage_node_capability: self.storage_node_capability,
@@ -1763,6 +1779,151 @@ impl SystemEventProvider for tokio::sync::broadcast::Sender<ContractEvent> {
     }
 }
 
+#[async_trait::async_trait]
+impl SystemEventProvider for SimulatedContractClient {
+    async fn events(
+        &self,
+        cursor: EventStreamCursor,
+    ) -> Result<Box<dyn Stream<Item = PositionedStreamEvent> + Send + Sync + 'life0>, anyhow::Error>
+    {
+        Ok(Box::new(self.chain().subscribe(cursor.event_id).map(
+            |event| PositionedStreamEvent::new(event, CheckpointEventPosition::new(0, 0)),
+        )))
+    }
+
+    async fn init_state(
+        &self,
+        _from: EventStreamCursor,
+    ) -> Result<Option<InitState>, anyhow::Error> {
+        Ok(None)
+    }
+
+    fn as_event_processor(&self) -> Option<&EventProcessor> {
+        None
+    }
+}
+
+#[async_trait]
+impl CommitteeLookupService for SimulatedContractClient {
+    async fn get_active_committees(&self) -> Result<ActiveCommittees, anyhow::Error> {
+        ActiveCommittees::try_from(self.get_committees_and_state().await?)
+    }
+}
+
+#[async_trait]
+impl SystemContractService for SimulatedContractClient {
+    async fn sync_node_params(
+        &self,
+        _config: &StorageNodeConfig,
+        _node_capability_object_id: ObjectID,
+    ) -> Result<(), SyncNodeConfigError> {
+        Ok(())
+    }
+
+    async fn get_epoch_and_state(&self) -> Result<(Epoch, EpochState), anyhow::Error> {
+        let state = self.epoch_state().await?;
+        Ok((self.chain().current_epoch(), state))
+    }
+
+    fn current_epoch(&self) -> Epoch {
+        self.chain().current_epoch()
+    }
+
+    async fn fixed_system_parameters(&self) -> Result<FixedSystemParameters, anyhow::Error> {
+        Ok(ReadClient::fixed_system_parameters(self).await?)
+    }
+
+    async fn invalidate_blob_id(&self, certificate: &InvalidBlobCertificate) {
+        if let Err(error) = SimulatedContractClient::invalidate_blob_id(self, certificate).await {
+            tracing::warn!(
+                ?error,
+                "failed to invalidate blob ID on the simulated chain"
+            );
+        }
+    }
+
+    async fn epoch_sync_done(&self, epoch: Epoch, node_capability_object_id: ObjectID) {
+        if let Err(error) =
+            SimulatedContractClient::epoch_sync_done(self, epoch, node_capability_object_id).await
+        {
+            tracing::warn!(
+                ?error,
+                "failed to signal epoch sync done on the simulated chain"
+            );
+        }
+    }
+
+    async fn end_voting(&self) -> Result<(), anyhow::Error> {
+        Ok(self.voting_end().await?)
+    }
+
+    async fn initiate_epoch_change(&self) -> Result<(), anyhow::Error> {
+        Ok(SimulatedContractClient::initiate_epoch_change(self).await?)
+    }
+
+    async fn process_subsidies(&self) -> Result<(), anyhow::Error> {
+        Err(SuiClientError::WalrusSubsidiesNotConfigured.into())
+    }
+
+    async fn last_walrus_subsidies_call(&self) -> Result<DateTime<Utc>, SuiClientError> {
+        Err(SuiClientError::WalrusSubsidiesNotConfigured)
+    }
+
+    async fn certify_event_blob(
+        &self,
+        blob_metadata: BlobObjectMetadata,
+        ending_checkpoint_seq_num: u64,
+        epoch: u32,
+        node_capability_object_id: ObjectID,
+    ) -> Result<(), SuiClientError> {
+        SimulatedContractClient::certify_event_blob(
+            self,
+            blob_metadata,
+            ending_checkpoint_seq_num,
+            epoch,
+            node_capability_object_id,
+        )
+        .await
+    }
+
+    async fn refresh_contract_package(&self) -> Result<(), anyhow::Error> {
+        Ok(())
+    }
+
+    async fn get_node_capability_object(
+        &self,
+        node_capability_object_id: Option<ObjectID>,
+    ) -> Result<StorageNodeCap, SuiClientError> {
+        let mut caps = self.owned_storage_node_caps().await?;
+        match node_capability_object_id {
+            Some(cap_id) => caps
+                .into_iter()
+                .find(|cap| cap.id == cap_id)
+                .ok_or(SuiClientError::StorageNodeCapabilityObjectNotSet),
+            None if caps.len() > 1 => Err(SuiClientError::MultipleStorageNodeCapabilities),
+            None => caps
+                .pop()
+                .ok_or(SuiClientError::StorageNodeCapabilityObjectNotSet),
+        }
+    }
+
+    async fn get_system_object_version(&self) -> Result<u64, SuiClientError> {
+        self.system_object_version().await
+    }
+
+    fn is_subsidies_object_configured(&self) -> bool {
+        false
+    }
+
+    async fn last_certified_event_blob(&self) -> Result<Option<EventBlob>, SuiClientError> {
+        ReadClient::last_certified_event_blob(self).await
+    }
+
+    async fn flush_cache(&self) {
+        // No-op
+    }
+}
+
 /// A cluster of [`StorageNodeHandle`]s corresponding to several running storage nodes.
 #[derive(Debug)]
 pub struct TestCluster<T: StorageNodeHandleTrait = StorageNodeHandle> {
@@ -2233,6 +2394,11 @@ impl StorageNodeTestConfig {
         }
     }
 
+    /// Returns the protocol key pair of the storage node.
+    pub fn key_pair(&self) -> &ProtocolKeyPair {
+        &self.key_pair
+    }
+
     /// Creates a `SuiStorageNode` from `self`.
     pub fn to_storage_node_info(&self, name: &str) -> SuiStorageNode {
         SuiStorageNode {
@@ -3013,6 +3179,7 @@ pub fn storage_node_config() -> WithTempDir<StorageNodeConfig> {
             node_recovery_config: Default::default(),
             // Uses smaller number of workers in tests to avoid overwhelming the tests.
             blob_event_processor_config: BlobEventProcessorConfig { num_workers: 3 },
+            garbage_collection: Default::default(),
         },
         temp_dir,
     }
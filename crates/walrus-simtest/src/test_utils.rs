@@ -7,6 +7,7 @@
 pub mod simtest_utils {
     use std::{
         collections::{HashMap, HashSet},
+        ops::Range,
         sync::{Arc, Mutex, atomic::AtomicBool},
         time::{Duration, Instant},
     };
@@ -14,6 +15,11 @@ pub mod simtest_utils {
     use anyhow::Context;
     use itertools::Itertools;
     use rand::{Rng, seq::IteratorRandom};
+    use sui_simulator::{
+        configs::{env_config, uniform_latency_ms},
+        net::NetSim,
+        task::NodeId,
+    };
     use sui_types::base_types::ObjectID;
     use tokio::{sync::RwLock, task::JoinHandle};
     use walrus_core::{
@@ -22,10 +28,13 @@ pub mod simtest_utils {
         encoding::{Primary, Secondary},
     };
     use walrus_sdk::client::{StoreArgs, WalrusNodeClient, responses::BlobStoreResult};
-    use walrus_service::test_utils::{SimStorageNodeHandle, TestCluster};
+    use walrus_service::{
+        node::byzantine::ByzantineConfig,
+        test_utils::{SimStorageNodeHandle, TestCluster},
+    };
     use walrus_storage_node_client::api::ServiceHealthInfo;
     use walrus_sui::{
-        client::{BlobPersistence, ReadClient, SuiContractClient},
+        client::{BlobPersistence, CreditsPolicy, ReadClient, SuiContractClient},
         types::move_structs::EventBlob,
     };
     use walrus_test_utils::WithTempDir;
@@ -252,7 +261,7 @@ pub mod simtest_utils {
             let result = client
                 .as_ref()
                 .sui_client()
-                .extend_blob(*blob_obj_id, 5)
+                .extend_blob(*blob_obj_id, 5, CreditsPolicy::Auto)
                 .await;
             // TODO(zhewu): account for already expired blobs.
             tracing::info!("extend blob {:?} result: {:?}", blob_obj_id, result);
@@ -723,4 +732,159 @@ pub mod simtest_utils {
         // Do not put any code after this point, as it won't be executed.
         // kill_current_node is implemented using a panic.
     }
+
+    /// Returns a simulator configuration for a degraded network, in which the latency of every
+    /// message between nodes is uniformly distributed in the given range (in milliseconds).
+    pub fn degraded_network_config(latency_ms: Range<u64>) -> sui_simulator::SimConfig {
+        env_config(uniform_latency_ms(latency_ms), [])
+    }
+
+    /// Returns the simulation node ids of all storage nodes in the cluster.
+    pub fn storage_node_ids(walrus_cluster: &TestCluster<SimStorageNodeHandle>) -> Vec<NodeId> {
+        walrus_cluster
+            .nodes
+            .iter()
+            .map(|node| node.node_id.expect("simtest must set node id"))
+            .collect()
+    }
+
+    /// A network partition between simulation nodes; the partition is healed when dropped.
+    #[derive(Debug)]
+    #[must_use = "the partition is healed when dropped"]
+    pub struct NetworkPartition {
+        /// The clogged (directed) links.
+        links: Vec<(NodeId, NodeId)>,
+    }
+
+    impl NetworkPartition {
+        /// Drops all messages sent from the nodes in `from` to the nodes in `to`; messages sent
+        /// in the other direction are still delivered.
+        pub fn one_way(from: &[NodeId], to: &[NodeId]) -> Self {
+            let links = from
+                .iter()
+                .cartesian_product(to)
+                .filter(|(src, dst)| src != dst)
+                .map(|(src, dst)| (*src, *dst))
+                .collect::<Vec<_>>();
+            let network = sui_simulator::plugin::simulator::<NetSim>();
+            for (src, dst) in &links {
+                network.clog_link(*src, *dst);
+            }
+            Self { links }
+        }
+
+        /// Drops all messages exchanged between the nodes in `left` and the nodes in `right`.
+        pub fn between(left: &[NodeId], right: &[NodeId]) -> Self {
+            let mut partition = Self::one_way(left, right);
+            partition
+                .links
+                .extend(Self::one_way(right, left).links.drain(..));
+            partition
+        }
+
+        /// Drops all messages exchanged between the node and the specified peers.
+        pub fn isolate(node: NodeId, peers: &[NodeId]) -> Self {
+            Self::between(&[node], peers)
+        }
+
+        /// Heals the partition.
+        pub fn heal(self) {
+            drop(self);
+        }
+    }
+
+    impl Drop for NetworkPartition {
+        fn drop(&mut self) {
+            let network = sui_simulator::plugin::simulator::<NetSim>();
+            for (src, dst) in self.links.drain(..) {
+                network.unclog_link(src, dst);
+            }
+        }
+    }
+
+    /// Configuration for rolling network partitions.
+    #[derive(Debug, Clone)]
+    pub struct RollingPartitionConfig {
+        /// How long each node stays isolated.
+        pub isolation_duration: Duration,
+        /// How long the network stays healed between two isolations.
+        pub healed_duration: Duration,
+    }
+
+    /// Starts a background task isolating the target nodes from their peers, one at a time and
+    /// in turn, until the task is aborted. Running it across epoch changes checks that the
+    /// committee keeps making progress while some of its members are unreachable.
+    pub fn start_rolling_partitions(
+        targets: Vec<NodeId>,
+        peers: Vec<NodeId>,
+        config: RollingPartitionConfig,
+    ) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            for target in targets.iter().cycle() {
+                tracing::info!(
+                    "isolating node {target} for {} seconds",
+                    config.isolation_duration.as_secs()
+                );
+                let all_nodes = targets.iter().chain(&peers).copied().collect::<Vec<_>>();
+                let partition = NetworkPartition::isolate(*target, &all_nodes);
+                tokio::time::sleep(config.isolation_duration).await;
+                partition.heal();
+                tokio::time::sleep(config.healed_duration).await;
+            }
+        })
+    }
+
+    /// The byzantine behaviour of the storage nodes in a simulation test.
+    ///
+    /// Storage nodes look up their config through the `storage_node_byzantine_config` fail point,
+    /// using the ID of the simulation node they run on. Nodes without a config are honest. The fail
+    /// point is cleared when dropped.
+    #[derive(Debug)]
+    #[must_use = "the nodes become honest again when dropped"]
+    pub struct ByzantineNodes {
+        configs: Arc<Mutex<HashMap<NodeId, ByzantineConfig>>>,
+    }
+
+    impl ByzantineNodes {
+        /// Makes the specified nodes misbehave according to their config.
+        pub fn new(configs: HashMap<NodeId, ByzantineConfig>) -> Self {
+            let configs = Arc::new(Mutex::new(configs));
+            let configs_clone = configs.clone();
+            sui_macros::register_fail_point_arg(
+                "storage_node_byzantine_config",
+                move || -> Option<ByzantineConfig> {
+                    configs_clone
+                        .lock()
+                        .expect("failed to lock the byzantine configs")
+                        .get(&sui_simulator::current_simnode_id())
+                        .cloned()
+                },
+            );
+            Self { configs }
+        }
+
+        /// Sets the byzantine config of the node, replacing the previous one.
+        pub fn set(&self, node_id: NodeId, config: ByzantineConfig) {
+            tracing::info!(?config, "setting the byzantine config of node {node_id}");
+            self.configs
+                .lock()
+                .expect("failed to lock the byzantine configs")
+                .insert(node_id, config);
+        }
+
+        /// Makes the node honest again.
+        pub fn reset(&self, node_id: NodeId) {
+            tracing::info!("node {node_id} is honest again");
+            self.configs
+                .lock()
+                .expect("failed to lock the byzantine configs")
+                .remove(&node_id);
+        }
+    }
+
+    impl Drop for ByzantineNodes {
+        fn drop(&mut self) {
+            sui_macros::clear_fail_point("storage_node_byzantine_config");
+        }
+    }
 }
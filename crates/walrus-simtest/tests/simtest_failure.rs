@@ -8,7 +8,7 @@
 #[cfg(msim)]
 mod tests {
     use std::{
-        collections::HashSet,
+        collections::{HashMap, HashSet},
         sync::{
             Arc,
             Mutex,
@@ -21,14 +21,16 @@ mod tests {
     use walrus_proc_macros::walrus_simtest;
     use walrus_service::{
         client::ClientCommunicationConfig,
-        node::config::NodeRecoveryConfig,
+        node::{byzantine::ByzantineConfig, config::NodeRecoveryConfig},
         test_utils::{SimStorageNodeHandle, TestNodesConfig, test_cluster},
     };
     use walrus_simtest::test_utils::simtest_utils::{
         self,
         BlobInfoConsistencyCheck,
+        ByzantineNodes,
         CRASH_NODE_FAIL_POINTS,
         NodeCrashConfig,
+        RollingPartitionConfig,
         repeatedly_crash_target_node,
     };
     use walrus_storage_node_client::api::ShardStatus;
@@ -859,4 +861,124 @@ mod tests {
         sui_macros::clear_fail_point("failpoint_sui_client_build_client");
         sui_macros::clear_fail_point("failpoint_rpc_client_build_client");
     }
+
+    // Tests that the committee keeps changing epochs while storage nodes are in turn isolated from
+    // the rest of the network, on top of a network with high latency.
+    #[ignore = "ignore integration simtests by default"]
+    #[walrus_simtest(config = "simtest_utils::degraded_network_config(20..80)")]
+    async fn walrus_with_rolling_partitions_during_epoch_change() {
+        let (sui_cluster, walrus_cluster, client, _) = test_cluster::E2eTestSetupBuilder::new()
+            .with_epoch_duration(Duration::from_secs(30))
+            .with_test_nodes_config(TestNodesConfig {
+                node_weights: vec![1, 2, 3, 3, 4],
+                ..Default::default()
+            })
+            .build_generic::<SimStorageNodeHandle>()
+            .await
+            .unwrap();
+
+        let blob_info_consistency_check = BlobInfoConsistencyCheck::new();
+
+        let client_arc = Arc::new(client);
+        let workload_handle =
+            simtest_utils::start_background_workload(client_arc.clone(), false, None, None);
+
+        // Isolate the storage nodes in turn, both from each other and from the Sui cluster.
+        let mut sui_nodes = sui_cluster
+            .lock()
+            .await
+            .cluster()
+            .all_node_handles()
+            .iter()
+            .map(|n| n.with(|n| n.get_sim_node_id()))
+            .collect::<Vec<_>>();
+        sui_nodes.push(sui_cluster.lock().await.sim_node_handle().id());
+        let partitions_handle = simtest_utils::start_rolling_partitions(
+            simtest_utils::storage_node_ids(&walrus_cluster),
+            sui_nodes,
+            RollingPartitionConfig {
+                isolation_duration: Duration::from_secs(20),
+                healed_duration: Duration::from_secs(10),
+            },
+        );
+
+        let start_epoch = simtest_utils::get_min_epoch_from_nodes(&walrus_cluster.nodes).await;
+        simtest_utils::wait_for_nodes_to_reach_epoch(
+            &walrus_cluster.nodes,
+            start_epoch + 3,
+            Duration::from_secs(300),
+        )
+        .await;
+
+        partitions_handle.abort();
+        workload_handle.abort();
+
+        // Give the nodes some time to catch up after the network is healed.
+        tokio::time::sleep(Duration::from_secs(30)).await;
+        blob_info_consistency_check.check_storage_node_consistency();
+    }
+
+    // Tests that blobs can be written and read while the node holding f shards is byzantine, and
+    // that the committee keeps changing epochs. The node first serves corrupted data and refuses to
+    // sign confirmations, then confirms blobs without storing them.
+    #[ignore = "ignore integration simtests by default"]
+    #[walrus_simtest]
+    async fn walrus_with_byzantine_node() {
+        let (_sui_cluster, walrus_cluster, client, _) = test_cluster::E2eTestSetupBuilder::new()
+            .with_epoch_duration(Duration::from_secs(30))
+            .with_test_nodes_config(TestNodesConfig {
+                node_weights: vec![1, 2, 3, 3, 4],
+                ..Default::default()
+            })
+            .build_generic::<SimStorageNodeHandle>()
+            .await
+            .unwrap();
+
+        let byzantine_node_id = walrus_cluster.nodes[4]
+            .node_id
+            .expect("simtest must set node id");
+        let byzantine_nodes = ByzantineNodes::new(HashMap::from([(
+            byzantine_node_id,
+            ByzantineConfig {
+                corrupt_slivers: true,
+                wrong_metadata: true,
+                refuse_confirmations: true,
+                reply_delay: Some(Duration::from_millis(500)),
+                bogus_recovery_symbols: true,
+                ..Default::default()
+            },
+        )]));
+
+        // The workload reads back and checks every blob it writes.
+        let client_arc = Arc::new(client);
+        let workload_handle =
+            simtest_utils::start_background_workload(client_arc.clone(), false, None, None);
+
+        let start_epoch = simtest_utils::get_min_epoch_from_nodes(&walrus_cluster.nodes).await;
+        simtest_utils::wait_for_nodes_to_reach_epoch(
+            &walrus_cluster.nodes,
+            start_epoch + 1,
+            Duration::from_secs(120),
+        )
+        .await;
+
+        byzantine_nodes.set(
+            byzantine_node_id,
+            ByzantineConfig {
+                confirm_unstored_blobs: true,
+                ..Default::default()
+            },
+        );
+        simtest_utils::wait_for_nodes_to_reach_epoch(
+            &walrus_cluster.nodes,
+            start_epoch + 2,
+            Duration::from_secs(120),
+        )
+        .await;
+
+        // The workload panics if any write or read fails.
+        assert!(!workload_handle.is_finished());
+        workload_handle.abort();
+        byzantine_nodes.reset(byzantine_node_id);
+    }
 }
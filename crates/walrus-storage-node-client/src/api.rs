@@ -49,6 +49,11 @@ pub enum BlobStatus {
         /// The ID of the Sui event in which the blob was marked as invalid.
         #[schema(schema_with = event_id_schema)]
         event: EventID,
+        /// Whether the blob was deleted from the storage nodes through a deny list.
+        ///
+        /// In that case, `event` is the ID of the corresponding deny-list event.
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        deny_listed: bool,
     },
     /// The blob exists within Walrus in a permanent state.
     Permanent {
@@ -98,6 +103,17 @@ impl BlobStatus {
         }
     }
 
+    /// Returns true iff the blob has been deleted from the storage nodes through a deny list.
+    pub fn is_deny_listed(&self) -> bool {
+        matches!(
+            self,
+            Self::Invalid {
+                deny_listed: true,
+                ..
+            }
+        )
+    }
+
     /// Returns true iff the blob is registered within Walrus.
     pub fn is_registered(&self) -> bool {
         matches!(self, Self::Deletable { .. } | Self::Permanent { .. })
@@ -4,3 +4,4 @@
 //! Stress testing for Walrus.
 
 pub mod single_client_workload;
+pub mod trace_replay;
@@ -17,11 +17,18 @@ use clap::{Parser, Subcommand};
 use generator::blob::WriteBlobConfig;
 use rand::{RngCore, seq::SliceRandom};
 use sui_types::base_types::ObjectID;
-use walrus_sdk::client::{WalrusNodeClient, metrics::ClientMetrics};
+use walrus_sdk::client::{
+    WalrusNodeClient,
+    metrics::ClientMetrics,
+    upload_relay_client::UploadRelayClient,
+};
 use walrus_service::client::{ClientConfig, Refiller};
-use walrus_stress::single_client_workload::{
-    SingleClientWorkload,
-    single_client_workload_arg::SingleClientWorkloadArgs,
+use walrus_stress::{
+    single_client_workload::{
+        SingleClientWorkload,
+        single_client_workload_arg::SingleClientWorkloadArgs,
+    },
+    trace_replay::{TraceReplay, trace::load_trace, trace_replay_arg::TraceReplayArgs},
 };
 use walrus_sui::{
     client::{CoinType, MIN_STAKING_THRESHOLD, ReadClient, SuiContractClient},
@@ -77,6 +84,8 @@ enum Commands {
     Staking,
     /// Run a single client with a specified workload.
     SingleClient(SingleClientWorkloadArgs),
+    /// Replay a trace of timestamped operations and report the latencies per operation type.
+    TraceReplay(TraceReplayArgs),
 }
 
 #[derive(Parser, Debug, Clone)]
@@ -155,6 +164,9 @@ async fn main() -> anyhow::Result<()> {
         Commands::SingleClient(single_client_args) => {
             run_single_client_workload(client_config, metrics, single_client_args).await
         }
+        Commands::TraceReplay(trace_replay_args) => {
+            run_trace_replay(client_config, metrics, trace_replay_args).await
+        }
     }
 }
 
@@ -357,6 +369,9 @@ async fn run_single_client_workload(
     data_size_config.validate()?;
     store_length_config.validate()?;
     request_type_distribution.validate()?;
+    if request_type_distribution.upload_relay_write_weight > 0 && args.upload_relay_url.is_none() {
+        anyhow::bail!("upload relay writes require an upload relay URL");
+    }
 
     // Create the client to run the workload.
     let wallet = WalletConfig::load_wallet(
@@ -371,7 +386,21 @@ async fn run_single_client_workload(
         WalrusNodeClient::new_contract_client_with_refresher(client_config, contract_client)
             .await?;
 
-    let single_client_workload = SingleClientWorkload::new(
+    let upload_relay_client = match args.upload_relay_url {
+        Some(upload_relay_url) => Some(
+            UploadRelayClient::new(
+                client.sui_client().address(),
+                client.encoding_config().n_shards(),
+                upload_relay_url,
+                None,
+                client.config().backoff_config().clone(),
+            )
+            .await?,
+        ),
+        None => None,
+    };
+
+    let mut single_client_workload = SingleClientWorkload::new(
         client,
         args.target_requests_per_minute,
         args.check_read_result,
@@ -379,10 +408,52 @@ async fn run_single_client_workload(
         data_size_config,
         store_length_config,
         request_type_distribution,
+        args.max_quilt_patches,
+        upload_relay_client,
+        args.duration,
+        args.seed,
         metrics,
     );
 
-    single_client_workload.run().await?;
+    let slo_report = single_client_workload.run().await?;
+    let slo_report = serde_json::to_string_pretty(&slo_report)?;
+    tracing::info!("single client workload completed, SLO report: {slo_report}");
+    if let Some(slo_report_path) = args.slo_report_path {
+        std::fs::write(&slo_report_path, slo_report).with_context(|| {
+            format!(
+                "failed to write the SLO report to '{}'",
+                slo_report_path.display()
+            )
+        })?;
+    }
+
+    Ok(())
+}
+
+async fn run_trace_replay(
+    client_config: ClientConfig,
+    metrics: Arc<ClientMetrics>,
+    args: TraceReplayArgs,
+) -> anyhow::Result<()> {
+    tracing::info!("starting the trace replay, args: {:?}", args);
+
+    let config = args.to_config()?;
+    let trace = load_trace(&args.trace_path, args.trace_format)?;
+
+    let wallet = WalletConfig::load_wallet(
+        client_config.wallet_config.as_ref(),
+        client_config
+            .communication_config
+            .sui_client_request_timeout,
+    )
+    .context("Failed to load wallet context")?;
+    let contract_client = client_config.new_contract_client(wallet, None).await?;
+    let client =
+        WalrusNodeClient::new_contract_client_with_refresher(client_config, contract_client)
+            .await?;
+
+    let report = TraceReplay::new(client, config, metrics).run(trace).await?;
+    println!("{report}");
 
     Ok(())
 }
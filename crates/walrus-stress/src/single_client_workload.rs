@@ -4,10 +4,12 @@
 //! Single client workload.
 
 use std::{
+    num::NonZeroUsize,
     sync::Arc,
     time::{Duration, Instant},
 };
 
+use anyhow::Context;
 use blob_pool::BlobPool;
 use client_op_generator::{ClientOpGenerator, WalrusNodeClientOp};
 use rand::SeedableRng;
@@ -16,11 +18,16 @@ use single_client_workload_config::{
     SizeDistributionConfig,
     StoreLengthDistributionConfig,
 };
+use slo_report::{SloRecorder, SloReport};
 use tokio::time::MissedTickBehavior;
 use walrus_core::{
     DEFAULT_ENCODING,
     SliverType,
-    encoding::{Primary, Secondary},
+    encoding::{
+        Primary,
+        Secondary,
+        quilt_encoding::{QuiltStoreBlob, QuiltVersionV1},
+    },
 };
 use walrus_sdk::{
     client::{
@@ -28,10 +35,18 @@ use walrus_sdk::{
         WalrusNodeClient,
         metrics::{self, ClientMetrics},
         responses::BlobStoreResult,
+        upload_relay_client::UploadRelayClient,
     },
     store_optimizations::StoreOptimizations,
 };
-use walrus_sui::client::{BlobPersistence, PostStoreAction, ReadClient, SuiContractClient};
+use walrus_sui::client::{
+    BlobPersistence,
+    CoinType,
+    CreditsPolicy,
+    PostStoreAction,
+    ReadClient,
+    SuiContractClient,
+};
 
 pub(crate) mod blob_generator;
 pub(crate) mod blob_pool;
@@ -39,6 +54,7 @@ pub(crate) mod client_op_generator;
 pub(crate) mod epoch_length_generator;
 pub mod single_client_workload_arg;
 pub mod single_client_workload_config;
+pub mod slo_report;
 
 /// A single client workload.
 ///
@@ -64,6 +80,15 @@ pub struct SingleClientWorkload {
     store_length_distribution_config: StoreLengthDistributionConfig,
     /// The request type distribution configuration.
     request_type_distribution: RequestTypeDistributionConfig,
+    /// The maximum number of patches in a quilt.
+    max_quilt_patches: NonZeroUsize,
+    /// The upload relay client used for upload-relay writes.
+    upload_relay_client: Option<UploadRelayClient>,
+    /// The duration of the workload. If `None`, the workload runs until interrupted.
+    duration: Option<Duration>,
+    /// The seed from which the operations and the blob data are derived. If `None`, the
+    /// workload is random.
+    seed: Option<u64>,
     /// Metrics tracks workload.
     metrics: Arc<ClientMetrics>,
 }
@@ -79,6 +104,10 @@ impl SingleClientWorkload {
         size_distribution_config: SizeDistributionConfig,
         store_length_distribution_config: StoreLengthDistributionConfig,
         request_type_distribution: RequestTypeDistributionConfig,
+        max_quilt_patches: NonZeroUsize,
+        upload_relay_client: Option<UploadRelayClient>,
+        duration: Option<Duration>,
+        seed: Option<u64>,
         metrics: Arc<ClientMetrics>,
     ) -> Self {
         Self {
@@ -89,20 +118,39 @@ impl SingleClientWorkload {
             size_distribution_config,
             store_length_distribution_config,
             request_type_distribution,
+            max_quilt_patches,
+            upload_relay_client,
+            duration,
+            seed,
             metrics,
         }
     }
 
-    /// Runs the single client workload.
-    pub async fn run(&self) -> anyhow::Result<()> {
-        let mut rng = rand::rngs::StdRng::from_entropy();
-
+    /// Runs the single client workload until the configured duration elapses or the workload is
+    /// interrupted, and returns the SLO report of the run.
+    ///
+    /// Failed client operations are recorded in the report and do not stop the workload.
+    pub async fn run(&mut self) -> anyhow::Result<SloReport> {
         // Use a blob pool to manage existing blobs.
-        let mut blob_pool = BlobPool::new(self.check_read_result, self.max_blobs_in_pool);
+        let (mut rng, mut blob_pool) = match self.seed {
+            Some(seed) => {
+                tracing::info!(seed, "running the workload with seeded blob data");
+                (
+                    rand::rngs::StdRng::seed_from_u64(seed),
+                    BlobPool::new_seeded(seed, self.max_blobs_in_pool),
+                )
+            }
+            None => (
+                rand::rngs::StdRng::from_entropy(),
+                BlobPool::new(self.check_read_result, self.max_blobs_in_pool),
+            ),
+        };
         let client_op_generator = ClientOpGenerator::new(
             self.request_type_distribution.clone(),
             self.size_distribution_config.clone(),
             self.store_length_distribution_config.clone(),
+            self.max_quilt_patches,
+            self.seed,
         );
 
         let mut request_interval = tokio::time::interval(Duration::from_millis(
@@ -115,8 +163,30 @@ impl SingleClientWorkload {
 
         let mut current_epoch = 0;
 
+        let mut recorder = SloRecorder::default();
+        let initial_wal_balance = self.client.sui_client().balance(CoinType::Wal).await?;
+        let initial_sui_balance = self.client.sui_client().balance(CoinType::Sui).await?;
+        let start = Instant::now();
+        let duration = self.duration;
+        let stop = async move {
+            let elapsed = async {
+                match duration {
+                    Some(duration) => tokio::time::sleep(duration).await,
+                    None => std::future::pending().await,
+                }
+            };
+            tokio::select! {
+                _ = elapsed => tracing::info!("workload duration elapsed"),
+                _ = tokio::signal::ctrl_c() => tracing::info!("workload interrupted"),
+            }
+        };
+        tokio::pin!(stop);
+
         loop {
-            request_interval.tick().await;
+            tokio::select! {
+                _ = request_interval.tick() => (),
+                _ = &mut stop => break,
+            }
 
             // TODO(WAL-937): tracking epoch more efficiently. This one reads per request is not
             // necessary.
@@ -126,12 +196,31 @@ impl SingleClientWorkload {
                 current_epoch = epoch;
             }
             let client_op = client_op_generator.generate_client_op(&blob_pool, &mut rng);
-            self.execute_client_op(&client_op, &mut blob_pool).await?;
+            let Some(op_name) = client_op.name() else {
+                tracing::info!("none op received, skipping");
+                continue;
+            };
+            let now = Instant::now();
+            match self.execute_client_op(&client_op, &mut blob_pool).await {
+                Ok(()) => recorder.record_success(op_name, now.elapsed(), client_op.stored_bytes()),
+                Err(error) => {
+                    tracing::warn!(?error, op_name, seed = ?self.seed, "client op failed");
+                    recorder.record_failure(op_name, &error);
+                }
+            }
         }
+
+        let wal_balance = self.client.sui_client().balance(CoinType::Wal).await?;
+        let sui_balance = self.client.sui_client().balance(CoinType::Sui).await?;
+        Ok(recorder.report(
+            start.elapsed(),
+            initial_wal_balance.saturating_sub(wal_balance),
+            initial_sui_balance.saturating_sub(sui_balance),
+        ))
     }
 
     async fn execute_client_op(
-        &self,
+        &mut self,
         client_op: &WalrusNodeClientOp,
         blob_pool: &mut BlobPool,
     ) -> anyhow::Result<()> {
@@ -147,7 +236,9 @@ impl SingleClientWorkload {
                 };
                 self.metrics
                     .observe_latency(metrics::READ_WORKLOAD, now.elapsed());
-                if self.check_read_result {
+                if self.seed.is_some() {
+                    blob_pool.verify_blob_data(*blob_id, &blob)?;
+                } else if self.check_read_result {
                     blob_pool.assert_blob_data(*blob_id, &blob);
                 }
             }
@@ -155,6 +246,11 @@ impl SingleClientWorkload {
                 blob,
                 deletable,
                 store_epoch_ahead,
+            }
+            | WalrusNodeClientOp::UploadRelayWrite {
+                blob,
+                deletable,
+                store_epoch_ahead,
             } => {
                 let now = Instant::now();
                 // TODO(WAL-945): test more StoreOptimizations.
@@ -167,11 +263,22 @@ impl SingleClientWorkload {
                     PostStoreAction::Keep,
                 );
                 store_args = store_args.with_metrics(self.metrics.clone());
+                let latency_name =
+                    if matches!(client_op, WalrusNodeClientOp::UploadRelayWrite { .. }) {
+                        let upload_relay_client = self
+                            .upload_relay_client
+                            .clone()
+                            .context("upload relay writes require an upload relay")?;
+                        store_args = store_args.with_upload_relay_client(upload_relay_client);
+                        "store_blob_upload_relay"
+                    } else {
+                        "store_blob"
+                    };
                 let store_result = self
                     .client
                     .reserve_and_store_blobs_retry_committees(&[blob.as_slice()], &[], &store_args)
                     .await?;
-                self.metrics.observe_latency("store_blob", now.elapsed());
+                self.metrics.observe_latency(latency_name, now.elapsed());
                 match &store_result[0] {
                     BlobStoreResult::NewlyCreated { blob_object, .. } => {
                         blob_pool.update_blob_pool(
@@ -203,11 +310,92 @@ impl SingleClientWorkload {
                 let now = Instant::now();
                 self.client
                     .sui_client()
-                    .extend_blob(*object_id, *store_epoch_ahead)
+                    .extend_blob(*object_id, *store_epoch_ahead, CreditsPolicy::Auto)
                     .await?;
                 self.metrics.observe_latency("extend_blob", now.elapsed());
                 blob_pool.update_blob_pool(*blob_id, Some(*object_id), client_op.clone());
             }
+            WalrusNodeClientOp::WriteQuilt {
+                patches,
+                store_epoch_ahead,
+            } => {
+                let now = Instant::now();
+                let quilt_blobs = patches
+                    .iter()
+                    .map(|(identifier, data)| QuiltStoreBlob::new(data, identifier.as_str()))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let quilt_client = self.client.quilt_client();
+                let quilt = quilt_client
+                    .construct_quilt::<QuiltVersionV1>(&quilt_blobs, DEFAULT_ENCODING)
+                    .await?;
+                let store_args = StoreArgs::new(
+                    DEFAULT_ENCODING,
+                    *store_epoch_ahead,
+                    StoreOptimizations::none(),
+                    BlobPersistence::from_deletable_and_permanent(false, true)?,
+                    PostStoreAction::Keep,
+                )
+                .with_metrics(self.metrics.clone());
+                let store_result = quilt_client
+                    .reserve_and_store_quilt::<QuiltVersionV1>(&quilt, &store_args)
+                    .await?;
+                self.metrics.observe_latency("store_quilt", now.elapsed());
+                match &store_result.blob_store_result {
+                    BlobStoreResult::NewlyCreated { blob_object, .. } => {
+                        blob_pool.update_blob_pool(
+                            blob_object.blob_id,
+                            Some(blob_object.id),
+                            client_op.clone(),
+                        );
+                    }
+                    result => {
+                        anyhow::bail!(
+                            "client op {:?} received unexpected store result: {:?}",
+                            client_op,
+                            result
+                        );
+                    }
+                }
+            }
+            WalrusNodeClientOp::ReadQuiltPatch {
+                quilt_id,
+                identifier,
+            } => {
+                let now = Instant::now();
+                let patches = self
+                    .client
+                    .quilt_client()
+                    .get_blobs_by_identifiers(quilt_id, &[identifier.as_str()])
+                    .await?;
+                self.metrics
+                    .observe_latency("read_quilt_patch", now.elapsed());
+                if self.seed.is_some() || self.check_read_result {
+                    let patch = patches.first().context("no quilt patch returned")?;
+                    if self.seed.is_some() {
+                        blob_pool.verify_quilt_patch_data(*quilt_id, identifier, patch.data())?;
+                    } else {
+                        blob_pool.assert_quilt_patch_data(*quilt_id, identifier, patch.data());
+                    }
+                }
+            }
+            WalrusNodeClientOp::UpdateAttribute {
+                object_id,
+                key,
+                value,
+                ..
+            } => {
+                let now = Instant::now();
+                self.client
+                    .sui_client_mut()
+                    .insert_or_update_blob_attribute_pairs(
+                        *object_id,
+                        [(key.as_str(), value.as_str())],
+                        true,
+                    )
+                    .await?;
+                self.metrics
+                    .observe_latency("update_attribute", now.elapsed());
+            }
             WalrusNodeClientOp::None => {
                 tracing::info!("none op received, skipping");
             }
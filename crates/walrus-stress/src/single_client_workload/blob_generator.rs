@@ -3,15 +3,26 @@
 
 //! Blob generator.
 
-use rand::Rng;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use rand::{Rng, SeedableRng, rngs::StdRng};
 use rand_distr::{Distribution, Poisson};
 use walrus_test_utils::random_data_from_rng;
 
 use super::single_client_workload_config::SizeDistributionConfig;
 
+/// The length of the header of a seeded blob, which contains the index of the blob.
+pub(crate) const SEEDED_BLOB_HEADER_LENGTH: usize = 8;
+
 /// A trait for generating random blobs.
 pub trait RandomBlobGenerator {
-    fn generate_blob<R: Rng>(&self, rng: &mut R) -> Vec<u8>;
+    /// Generates the size of a blob.
+    fn generate_size<R: Rng>(&self, rng: &mut R) -> usize;
+
+    fn generate_blob<R: Rng>(&self, rng: &mut R) -> Vec<u8> {
+        let size = self.generate_size(rng);
+        random_data_from_rng(size, rng)
+    }
 }
 
 pub enum BlobGenerator {
@@ -36,6 +47,13 @@ impl BlobGenerator {
         }
     }
 
+    pub fn generate_size<R: Rng>(&self, rng: &mut R) -> usize {
+        match self {
+            Self::Uniform(generator) => generator.generate_size(rng),
+            Self::Poisson(generator) => generator.generate_size(rng),
+        }
+    }
+
     pub fn generate_blob<R: Rng>(&self, rng: &mut R) -> Vec<u8> {
         match self {
             Self::Uniform(generator) => generator.generate_blob(rng),
@@ -44,15 +62,70 @@ impl BlobGenerator {
     }
 }
 
+/// Generates blob data that is derived from a seed and the index of the blob.
+///
+/// Each blob starts with its index as a little-endian `u64`, followed by bytes drawn from an RNG
+/// seeded with the seed and the index. Anyone knowing the seed can therefore regenerate the
+/// expected data of a blob from the blob itself, without keeping the data in memory. Blobs
+/// shorter than [`SEEDED_BLOB_HEADER_LENGTH`] only contain a prefix of their index.
+///
+/// The data is stable for a given version of the stress client, but may change between versions.
+#[derive(Debug)]
+pub(crate) struct SeededBlobData {
+    seed: u64,
+    next_index: AtomicU64,
+}
+
+impl SeededBlobData {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            next_index: AtomicU64::new(0),
+        }
+    }
+
+    /// Generates the data of the next blob, with the given size.
+    pub fn next_blob(&self, size: usize) -> Vec<u8> {
+        let index = self.next_index.fetch_add(1, Ordering::Relaxed);
+        Self::blob(self.seed, index, size)
+    }
+
+    /// Regenerates the data of the blob with the given seed, index, and size.
+    pub fn blob(seed: u64, index: u64, size: usize) -> Vec<u8> {
+        let mut rng_seed = [0; 32];
+        rng_seed[..8].copy_from_slice(&seed.to_le_bytes());
+        rng_seed[8..16].copy_from_slice(&index.to_le_bytes());
+        let mut rng = StdRng::from_seed(rng_seed);
+
+        let mut blob = index.to_le_bytes().to_vec();
+        blob.truncate(size);
+        blob.extend(random_data_from_rng(
+            size.saturating_sub(SEEDED_BLOB_HEADER_LENGTH),
+            &mut rng,
+        ));
+        blob
+    }
+
+    /// Returns the index embedded in the header of a seeded blob, or `None` if the blob is
+    /// shorter than the header.
+    pub fn index_of(blob: &[u8]) -> Option<u64> {
+        let header = blob.get(..SEEDED_BLOB_HEADER_LENGTH)?;
+        Some(u64::from_le_bytes(
+            header
+                .try_into()
+                .expect("the header has the length of a u64"),
+        ))
+    }
+}
+
 pub(crate) struct UniformBlobGenerator {
     min_size_bytes: usize,
     max_size_bytes: usize,
 }
 
 impl RandomBlobGenerator for UniformBlobGenerator {
-    fn generate_blob<R: Rng>(&self, rng: &mut R) -> Vec<u8> {
-        let size = rng.gen_range(self.min_size_bytes..=self.max_size_bytes);
-        random_data_from_rng(size, rng)
+    fn generate_size<R: Rng>(&self, rng: &mut R) -> usize {
+        rng.gen_range(self.min_size_bytes..=self.max_size_bytes)
     }
 }
 
@@ -72,14 +145,41 @@ impl PoissonBlobGenerator {
 }
 
 impl RandomBlobGenerator for PoissonBlobGenerator {
-    fn generate_blob<R: Rng>(&self, rng: &mut R) -> Vec<u8> {
+    fn generate_size<R: Rng>(&self, rng: &mut R) -> usize {
         #[allow(clippy::cast_possible_truncation)]
         let size_base = self
             .poisson
             .sample(rng)
             .round()
             .clamp(1.0, usize::MAX as f64) as usize;
-        let size = size_base * self.size_multiplier;
-        random_data_from_rng(size, rng)
+        size_base * self.size_multiplier
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seeded_blobs_are_reproducible() {
+        let seeded_blob_data = SeededBlobData::new(42);
+        let first = seeded_blob_data.next_blob(1000);
+        let second = seeded_blob_data.next_blob(1000);
+
+        assert_eq!(first.len(), 1000);
+        assert_ne!(first, second);
+        assert_eq!(SeededBlobData::index_of(&first), Some(0));
+        assert_eq!(SeededBlobData::index_of(&second), Some(1));
+        assert_eq!(first, SeededBlobData::blob(42, 0, 1000));
+        assert_eq!(second, SeededBlobData::blob(42, 1, 1000));
+        assert_ne!(first, SeededBlobData::blob(43, 0, 1000));
+    }
+
+    #[test]
+    fn test_short_seeded_blobs() {
+        let blob = SeededBlobData::blob(42, 1, 3);
+        assert_eq!(blob, vec![1, 0, 0]);
+        assert_eq!(SeededBlobData::index_of(&blob), None);
+        assert!(SeededBlobData::blob(42, 1, 0).is_empty());
     }
 }
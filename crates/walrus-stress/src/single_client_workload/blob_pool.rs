@@ -9,12 +9,62 @@ use rand::{Rng, seq::IteratorRandom};
 use walrus_core::{BlobId, Epoch, EpochCount};
 use walrus_sdk::ObjectID;
 
-use super::client_op_generator::WalrusNodeClientOp;
+use super::{
+    blob_generator::{SEEDED_BLOB_HEADER_LENGTH, SeededBlobData},
+    client_op_generator::WalrusNodeClientOp,
+};
+
+/// The data of a blob or quilt patch kept in the pool to check reads.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum StoredData {
+    /// The full data.
+    Full(Vec<u8>),
+    /// The index and length of a blob derived from the seed of the pool, see [`SeededBlobData`].
+    Seeded { index: u64, length: usize },
+}
+
+/// The data read from Walrus does not match the data written.
+///
+/// The seed and blob index allow regenerating the expected data with [`SeededBlobData::blob`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error(
+    "the data read for {target} does not match the data written [seed={seed}, \
+    blob_index={blob_index:?}, expected_length={expected_length}, read_length={read_length}, \
+    first_mismatch={first_mismatch}]"
+)]
+pub(crate) struct DataMismatchError {
+    /// The blob or quilt patch that was read.
+    target: String,
+    /// The seed from which the data was derived.
+    seed: u64,
+    /// The index of the blob, `None` if the blob is too short to contain its index.
+    blob_index: Option<u64>,
+    expected_length: usize,
+    read_length: usize,
+    /// The offset of the first byte that differs.
+    first_mismatch: usize,
+}
+
+#[cfg(test)]
+impl DataMismatchError {
+    /// Returns a mismatch of the first byte of a one-byte blob.
+    pub(crate) fn for_testing() -> Self {
+        Self {
+            target: "blob".to_owned(),
+            seed: 0,
+            blob_index: None,
+            expected_length: 1,
+            read_length: 1,
+            first_mismatch: 0,
+        }
+    }
+}
 
 /// Data and info of a blob.
 pub(crate) struct BlobDataAndInfo {
-    /// The user blob data. Only stored if `store_blob_data` is true in BlobPool.
-    blob: Option<Vec<u8>>,
+    /// The user blob data. Only stored if `store_blob_data` is true in BlobPool, or if the pool
+    /// has a seed.
+    blob: Option<StoredData>,
     /// The object id of the blob.
     blob_object_id: ObjectID,
     /// Whether the blob is deletable.
@@ -23,12 +73,27 @@ pub(crate) struct BlobDataAndInfo {
     end_epoch: Epoch,
 }
 
+/// Data and info of a quilt.
+pub(crate) struct QuiltDataAndInfo {
+    /// The data of the patches by identifier. The data is only stored if `store_blob_data` is
+    /// true in BlobPool, or if the pool has a seed.
+    patches: HashMap<String, Option<StoredData>>,
+    /// The epoch at which the quilt will be deleted.
+    end_epoch: Epoch,
+}
+
 /// Manages a pool of blobs that are live in the system.
 pub(crate) struct BlobPool {
     // TODO(WAL-946): when writing the same blob but with different properties (e.g. deletable or
     // permanent), we may want to keep the object id in the map as well.
     blobs: HashMap<BlobId, BlobDataAndInfo>,
+    quilts: HashMap<BlobId, QuiltDataAndInfo>,
     store_blob_data: bool,
+    /// The seed from which the blob data is derived, if any.
+    ///
+    /// With a seed, only the index and length of the blobs are kept, and the expected data is
+    /// regenerated when checking reads.
+    seed: Option<u64>,
     max_blobs_in_pool: usize,
 }
 
@@ -36,11 +101,21 @@ impl BlobPool {
     pub fn new(store_blob_data: bool, max_blobs_in_pool: usize) -> Self {
         Self {
             blobs: HashMap::new(),
+            quilts: HashMap::new(),
             store_blob_data,
+            seed: None,
             max_blobs_in_pool,
         }
     }
 
+    /// Creates a pool for blobs whose data is derived from the given seed.
+    pub fn new_seeded(seed: u64, max_blobs_in_pool: usize) -> Self {
+        Self {
+            seed: Some(seed),
+            ..Self::new(false, max_blobs_in_pool)
+        }
+    }
+
     pub fn select_random_blob_id<R: Rng>(&self, rng: &mut R) -> Option<BlobId> {
         self.blobs.keys().choose(rng).cloned()
     }
@@ -53,6 +128,13 @@ impl BlobPool {
             .map(|(blob_id, _)| *blob_id)
     }
 
+    /// Selects a random patch of a random quilt, returning the quilt ID and the patch identifier.
+    pub fn select_random_quilt_patch<R: Rng>(&self, rng: &mut R) -> Option<(BlobId, String)> {
+        let (quilt_id, quilt) = self.quilts.iter().choose(rng)?;
+        let identifier = quilt.patches.keys().choose(rng)?;
+        Some((*quilt_id, identifier.clone()))
+    }
+
     /// Updates the blob pool with a client operation.
     pub fn update_blob_pool(
         &mut self,
@@ -65,6 +147,11 @@ impl BlobPool {
                 blob,
                 deletable,
                 store_epoch_ahead,
+            }
+            | WalrusNodeClientOp::UploadRelayWrite {
+                blob,
+                deletable,
+                store_epoch_ahead,
             } => {
                 self.add_new_blob(
                     blob_id,
@@ -90,7 +177,15 @@ impl BlobPool {
             } => {
                 // Do nothing.
             }
-            WalrusNodeClientOp::None => {
+            WalrusNodeClientOp::WriteQuilt {
+                patches,
+                store_epoch_ahead,
+            } => {
+                self.add_new_quilt(blob_id, patches, store_epoch_ahead);
+            }
+            WalrusNodeClientOp::ReadQuiltPatch { .. }
+            | WalrusNodeClientOp::UpdateAttribute { .. }
+            | WalrusNodeClientOp::None => {
                 // Do nothing.
             }
         }
@@ -108,11 +203,7 @@ impl BlobPool {
         self.blobs.insert(
             blob_id,
             BlobDataAndInfo {
-                blob: if self.store_blob_data {
-                    Some(blob)
-                } else {
-                    None
-                },
+                blob: self.stored_data(blob),
                 blob_object_id,
                 deletable,
                 end_epoch,
@@ -120,6 +211,35 @@ impl BlobPool {
         );
     }
 
+    /// Adds a new quilt to the pool.
+    fn add_new_quilt(
+        &mut self,
+        quilt_id: BlobId,
+        patches: Vec<(String, Vec<u8>)>,
+        end_epoch: Epoch,
+    ) {
+        let patches = patches
+            .into_iter()
+            .map(|(identifier, data)| (identifier, self.stored_data(data)))
+            .collect();
+        self.quilts
+            .insert(quilt_id, QuiltDataAndInfo { patches, end_epoch });
+    }
+
+    /// Returns the data to keep for a new blob or quilt patch.
+    fn stored_data(&self, data: Vec<u8>) -> Option<StoredData> {
+        if self.seed.is_some() && data.len() >= SEEDED_BLOB_HEADER_LENGTH {
+            Some(StoredData::Seeded {
+                index: SeededBlobData::index_of(&data).expect("the blob contains the header"),
+                length: data.len(),
+            })
+        } else if self.seed.is_some() || self.store_blob_data {
+            Some(StoredData::Full(data))
+        } else {
+            None
+        }
+    }
+
     /// Deletes a blob from the pool.
     fn delete_blob(&mut self, blob_id: BlobId) {
         self.blobs.remove(&blob_id);
@@ -135,7 +255,79 @@ impl BlobPool {
     pub fn assert_blob_data(&self, blob_id: BlobId, blob: &[u8]) {
         assert!(self.store_blob_data);
         let blob_data = self.blobs.get(&blob_id).expect("blob must exist");
-        assert_eq!(blob_data.blob.as_ref().expect("blob must be stored"), blob);
+        assert_eq!(
+            blob_data.blob.as_ref().expect("blob must be stored"),
+            &StoredData::Full(blob.to_vec())
+        );
+    }
+
+    /// Asserts that the data of the quilt patch matches the expected data.
+    pub fn assert_quilt_patch_data(&self, quilt_id: BlobId, identifier: &str, data: &[u8]) {
+        assert!(self.store_blob_data);
+        let quilt = self.quilts.get(&quilt_id).expect("quilt must exist");
+        let patch = quilt.patches.get(identifier).expect("patch must exist");
+        assert_eq!(
+            patch.as_ref().expect("patch data must be stored"),
+            &StoredData::Full(data.to_vec())
+        );
+    }
+
+    /// Verifies that the data read for a blob matches the data derived from the seed of the pool.
+    pub fn verify_blob_data(&self, blob_id: BlobId, blob: &[u8]) -> Result<(), DataMismatchError> {
+        let blob_data = self.blobs.get(&blob_id).expect("blob must exist");
+        self.verify_data(
+            format!("blob {blob_id}"),
+            blob_data.blob.as_ref().expect("blob must be stored"),
+            blob,
+        )
+    }
+
+    /// Verifies that the data read for a quilt patch matches the data derived from the seed of the
+    /// pool.
+    pub fn verify_quilt_patch_data(
+        &self,
+        quilt_id: BlobId,
+        identifier: &str,
+        data: &[u8],
+    ) -> Result<(), DataMismatchError> {
+        let quilt = self.quilts.get(&quilt_id).expect("quilt must exist");
+        let patch = quilt.patches.get(identifier).expect("patch must exist");
+        self.verify_data(
+            format!("patch {identifier} of quilt {quilt_id}"),
+            patch.as_ref().expect("patch data must be stored"),
+            data,
+        )
+    }
+
+    fn verify_data(
+        &self,
+        target: String,
+        stored_data: &StoredData,
+        data: &[u8],
+    ) -> Result<(), DataMismatchError> {
+        let seed = self.seed.expect("verifying data requires a seed");
+        let (expected, blob_index) = match stored_data {
+            StoredData::Full(expected) => (expected.clone(), None),
+            StoredData::Seeded { index, length } => {
+                (SeededBlobData::blob(seed, *index, *length), Some(*index))
+            }
+        };
+        if expected == data {
+            return Ok(());
+        }
+        let first_mismatch = expected
+            .iter()
+            .zip(data)
+            .position(|(expected, read)| expected != read)
+            .unwrap_or_else(|| expected.len().min(data.len()));
+        Err(DataMismatchError {
+            target,
+            seed,
+            blob_index,
+            expected_length: expected.len(),
+            read_length: data.len(),
+            first_mismatch,
+        })
     }
 
     /// Expire blobs that have expired at the given epoch.
@@ -150,6 +342,7 @@ impl BlobPool {
         for blob_id in expired_blob_ids {
             self.blobs.remove(&blob_id);
         }
+        self.quilts.retain(|_, quilt| quilt.end_epoch > epoch);
     }
 
     /// Returns the object id of a blob.
@@ -165,8 +358,10 @@ impl BlobPool {
     }
 
     /// Returns true if the blob pool is full.
+    ///
+    /// Both blobs and quilts count towards the capacity of the pool.
     pub fn is_full(&self) -> bool {
-        self.blobs.len() >= self.max_blobs_in_pool
+        self.blobs.len() + self.quilts.len() >= self.max_blobs_in_pool
     }
 }
 
@@ -210,7 +405,7 @@ mod tests {
         assert!(pool.blobs.contains_key(&blob_id));
 
         let stored_blob = &pool.blobs[&blob_id];
-        assert_eq!(stored_blob.blob, Some(blob_data));
+        assert_eq!(stored_blob.blob, Some(StoredData::Full(blob_data)));
         assert_eq!(stored_blob.blob_object_id, object_id);
         assert!(stored_blob.deletable);
         assert_eq!(stored_blob.end_epoch, 10);
@@ -404,4 +599,99 @@ mod tests {
             Some(deletable_blob_id)
         );
     }
+
+    #[test]
+    fn test_quilts_in_blob_pool() {
+        let mut pool = BlobPool::new(true, 2);
+        let mut rng = thread_rng();
+        let quilt_id = create_test_blob_id();
+
+        let write_quilt_op = WalrusNodeClientOp::WriteQuilt {
+            patches: vec![("patch-0".to_string(), vec![1, 2, 3])],
+            store_epoch_ahead: 5,
+        };
+        pool.update_blob_pool(quilt_id, Some(create_test_object_id()), write_quilt_op);
+
+        // Quilts count towards the capacity of the pool, but are not read as blobs.
+        assert!(pool.is_empty());
+        assert!(!pool.is_full());
+        assert_eq!(pool.select_random_blob_id(&mut rng), None);
+        assert_eq!(
+            pool.select_random_quilt_patch(&mut rng),
+            Some((quilt_id, "patch-0".to_string()))
+        );
+        pool.assert_quilt_patch_data(quilt_id, "patch-0", &[1, 2, 3]);
+
+        pool.update_blob_pool(
+            BlobId([3; 32]),
+            Some(create_test_object_id()),
+            WalrusNodeClientOp::UploadRelayWrite {
+                blob: create_test_blob_data(),
+                deletable: false,
+                store_epoch_ahead: 10,
+            },
+        );
+        assert!(pool.is_full());
+
+        pool.expire_blobs_in_new_epoch(5);
+        assert_eq!(pool.select_random_quilt_patch(&mut rng), None);
+        assert!(!pool.is_full());
+    }
+
+    #[test]
+    fn test_verify_seeded_blob_data() {
+        let seed = 42;
+        let mut pool = BlobPool::new_seeded(seed, 1000);
+        let seeded_blob_data = SeededBlobData::new(seed);
+        let blob_id = create_test_blob_id();
+        let quilt_id = BlobId([3; 32]);
+        let blob = seeded_blob_data.next_blob(100);
+        let patch = seeded_blob_data.next_blob(50);
+
+        pool.update_blob_pool(
+            blob_id,
+            Some(create_test_object_id()),
+            WalrusNodeClientOp::Write {
+                blob: blob.clone(),
+                deletable: true,
+                store_epoch_ahead: 10,
+            },
+        );
+        pool.update_blob_pool(
+            quilt_id,
+            Some(create_test_object_id()),
+            WalrusNodeClientOp::WriteQuilt {
+                patches: vec![("patch-0".to_string(), patch.clone())],
+                store_epoch_ahead: 10,
+            },
+        );
+
+        // Only the index and length of the blob are kept.
+        assert_eq!(
+            pool.blobs[&blob_id].blob,
+            Some(StoredData::Seeded {
+                index: 0,
+                length: 100
+            })
+        );
+        assert_eq!(pool.verify_blob_data(blob_id, &blob), Ok(()));
+        assert_eq!(
+            pool.verify_quilt_patch_data(quilt_id, "patch-0", &patch),
+            Ok(())
+        );
+
+        let mut corrupted = blob.clone();
+        corrupted[42] ^= 1;
+        let error = pool
+            .verify_blob_data(blob_id, &corrupted)
+            .expect_err("corrupted data must be detected");
+        assert_eq!((error.seed, error.blob_index), (seed, Some(0)));
+        assert_eq!(error.first_mismatch, 42);
+
+        let error = pool
+            .verify_quilt_patch_data(quilt_id, "patch-0", &patch[..49])
+            .expect_err("truncated data must be detected");
+        assert_eq!((error.blob_index, error.read_length), (Some(1), 49));
+        assert_eq!(error.first_mismatch, 49);
+    }
 }
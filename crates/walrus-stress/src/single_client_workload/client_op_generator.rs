@@ -3,12 +3,14 @@
 
 //! Client operation generator.
 
+use std::num::NonZeroUsize;
+
 use rand::Rng;
 use walrus_core::{BlobId, EpochCount, SliverType};
 use walrus_sdk::ObjectID;
 
 use super::{
-    blob_generator::BlobGenerator,
+    blob_generator::{BlobGenerator, SeededBlobData},
     blob_pool::BlobPool,
     epoch_length_generator::EpochLengthGenerator,
     single_client_workload_config::{
@@ -38,13 +40,67 @@ pub(crate) enum WalrusNodeClientOp {
         object_id: ObjectID,
         store_epoch_ahead: EpochCount,
     },
+    WriteQuilt {
+        /// The identifiers and data of the patches.
+        patches: Vec<(String, Vec<u8>)>,
+        store_epoch_ahead: EpochCount,
+    },
+    ReadQuiltPatch {
+        quilt_id: BlobId,
+        identifier: String,
+    },
+    UpdateAttribute {
+        blob_id: BlobId,
+        object_id: ObjectID,
+        key: String,
+        value: String,
+    },
+    UploadRelayWrite {
+        blob: Vec<u8>,
+        deletable: bool,
+        store_epoch_ahead: EpochCount,
+    },
     None,
 }
 
+impl WalrusNodeClientOp {
+    /// Returns the name of the operation, or `None` for the none operation.
+    pub fn name(&self) -> Option<&'static str> {
+        let name = match self {
+            Self::Read { .. } => "read",
+            Self::Write { .. } => "write",
+            Self::Delete { .. } => "delete",
+            Self::Extend { .. } => "extend",
+            Self::WriteQuilt { .. } => "write_quilt",
+            Self::ReadQuiltPatch { .. } => "read_quilt_patch",
+            Self::UpdateAttribute { .. } => "update_attribute",
+            Self::UploadRelayWrite { .. } => "upload_relay_write",
+            Self::None => return None,
+        };
+        Some(name)
+    }
+
+    /// Returns the number of unencoded bytes stored by the operation.
+    pub fn stored_bytes(&self) -> u64 {
+        let bytes = match self {
+            Self::Write { blob, .. } | Self::UploadRelayWrite { blob, .. } => blob.len(),
+            Self::WriteQuilt { patches, .. } => patches.iter().map(|(_, data)| data.len()).sum(),
+            _ => 0,
+        };
+        bytes.try_into().expect("usize fits into u64")
+    }
+}
+
+/// The number of distinct attribute keys set by attribute updates.
+const N_ATTRIBUTE_KEYS: u32 = 4;
+
 pub(crate) struct ClientOpGenerator {
     request_type_distribution: RequestTypeDistributionConfig,
     blob_generator: BlobGenerator,
+    /// If set, the data of the blobs is derived from a seed instead of being random.
+    seeded_blob_data: Option<SeededBlobData>,
     epoch_length_generator: EpochLengthGenerator,
+    max_quilt_patches: NonZeroUsize,
 }
 
 impl ClientOpGenerator {
@@ -52,13 +108,17 @@ impl ClientOpGenerator {
         request_type_distribution: RequestTypeDistributionConfig,
         size_distribution: SizeDistributionConfig,
         store_length_distribution: StoreLengthDistributionConfig,
+        max_quilt_patches: NonZeroUsize,
+        seed: Option<u64>,
     ) -> Self {
         let blob_generator = BlobGenerator::new(size_distribution);
         let epoch_length_generator = EpochLengthGenerator::new(store_length_distribution);
         Self {
             request_type_distribution,
             blob_generator,
+            seeded_blob_data: seed.map(SeededBlobData::new),
             epoch_length_generator,
+            max_quilt_patches,
         }
     }
 
@@ -94,13 +154,61 @@ impl ClientOpGenerator {
             }
             RequestType::Delete => self.generate_delete_op(blob_pool, rng),
             RequestType::Extend => self.generate_extend_op(blob_pool, rng),
+            RequestType::QuiltWrite => {
+                if blob_pool.is_full() {
+                    tracing::info!("pool is full, generating read op instead of quilt write");
+                    self.generate_read_op(blob_pool, rng)
+                } else {
+                    self.generate_quilt_write_op(rng)
+                }
+            }
+            RequestType::QuiltRead => {
+                if let Some((quilt_id, identifier)) = blob_pool.select_random_quilt_patch(rng) {
+                    WalrusNodeClientOp::ReadQuiltPatch {
+                        quilt_id,
+                        identifier,
+                    }
+                } else if !blob_pool.is_full() {
+                    self.generate_quilt_write_op(rng)
+                } else {
+                    self.generate_read_op(blob_pool, rng)
+                }
+            }
+            RequestType::UpdateAttribute => self.generate_update_attribute_op(blob_pool, rng),
+            RequestType::UploadRelayWrite => {
+                if blob_pool.is_full() {
+                    tracing::info!(
+                        "pool is full, generating read op instead of upload relay write"
+                    );
+                    self.generate_read_op(blob_pool, rng)
+                } else {
+                    let blob = self.generate_blob(rng);
+                    let store_epoch_ahead = self.epoch_length_generator.generate_epoch_length(rng);
+                    WalrusNodeClientOp::UploadRelayWrite {
+                        blob,
+                        deletable: rng.gen_bool(0.5),
+                        store_epoch_ahead,
+                    }
+                }
+            }
+        }
+    }
+
+    /// Generates the data of a blob, derived from the seed if one is set.
+    fn generate_blob<R: Rng>(&self, rng: &mut R) -> Vec<u8> {
+        match &self.seeded_blob_data {
+            Some(seeded_blob_data) => {
+                seeded_blob_data.next_blob(self.blob_generator.generate_size(rng))
+            }
+            None => self.blob_generator.generate_blob(rng),
         }
     }
 
     fn generate_read_op<R: Rng>(&self, blob_pool: &BlobPool, rng: &mut R) -> WalrusNodeClientOp {
-        let blob_id = blob_pool
-            .select_random_blob_id(rng)
-            .expect("blob must exist");
+        let Some(blob_id) = blob_pool.select_random_blob_id(rng) else {
+            tracing::info!("no blob found, generating none op");
+            return WalrusNodeClientOp::None;
+        };
         let sliver_type = if rng.gen_bool(0.5) {
             SliverType::Primary
         } else {
@@ -114,7 +222,7 @@ impl ClientOpGenerator {
 
     // TODO(WAL-946): generate write to existing blob.
     fn generate_write_op<R: Rng>(&self, deletable: bool, rng: &mut R) -> WalrusNodeClientOp {
-        let blob = self.blob_generator.generate_blob(rng);
+        let blob = self.generate_blob(rng);
         let store_epoch_ahead = self.epoch_length_generator.generate_epoch_length(rng);
         WalrusNodeClientOp::Write {
             blob,
@@ -123,6 +231,37 @@ impl ClientOpGenerator {
         }
     }
 
+    fn generate_quilt_write_op<R: Rng>(&self, rng: &mut R) -> WalrusNodeClientOp {
+        let n_patches = rng.gen_range(1..=self.max_quilt_patches.get());
+        let patches = (0..n_patches)
+            .map(|index| (format!("patch-{index}"), self.generate_blob(rng)))
+            .collect();
+        let store_epoch_ahead = self.epoch_length_generator.generate_epoch_length(rng);
+        WalrusNodeClientOp::WriteQuilt {
+            patches,
+            store_epoch_ahead,
+        }
+    }
+
+    fn generate_update_attribute_op<R: Rng>(
+        &self,
+        blob_pool: &BlobPool,
+        rng: &mut R,
+    ) -> WalrusNodeClientOp {
+        let Some(blob_id) = blob_pool.select_random_blob_id(rng) else {
+            tracing::info!("no blob found, generating none op");
+            return WalrusNodeClientOp::None;
+        };
+        WalrusNodeClientOp::UpdateAttribute {
+            blob_id,
+            object_id: blob_pool
+                .get_blob_object_id(blob_id)
+                .expect("blob should exist in the blob pool"),
+            key: format!("stress-key-{}", rng.gen_range(0..N_ATTRIBUTE_KEYS)),
+            value: format!("{:016x}", rng.r#gen::<u64>()),
+        }
+    }
+
     fn generate_delete_op<R: Rng>(&self, blob_pool: &BlobPool, rng: &mut R) -> WalrusNodeClientOp {
         let blob_id = blob_pool.select_random_deletable_blob_id(rng);
         if let Some(blob_id) = blob_id {
@@ -197,12 +336,22 @@ mod tests {
     }
 
     fn create_client_op_generator_favoring_writes() -> ClientOpGenerator {
+        create_client_op_generator_favoring_writes_with_seed(None)
+    }
+
+    fn create_client_op_generator_favoring_writes_with_seed(
+        seed: Option<u64>,
+    ) -> ClientOpGenerator {
         let request_type_distribution = RequestTypeDistributionConfig {
             read_weight: 1,
             write_permanent_weight: 100, // Heavy weight for write permanent
             write_deletable_weight: 100, // Heavy weight for write deletable
             delete_weight: 1,
             extend_weight: 1,
+            quilt_write_weight: 100,
+            quilt_read_weight: 1,
+            update_attribute_weight: 1,
+            upload_relay_write_weight: 100,
         };
 
         let size_distribution = SizeDistributionConfig::Uniform {
@@ -219,6 +368,8 @@ mod tests {
             request_type_distribution,
             size_distribution,
             store_length_distribution,
+            NonZeroUsize::new(3).expect("3 is non-zero"),
+            seed,
         )
     }
 
@@ -236,7 +387,9 @@ mod tests {
             // When pool is full, WritePermanent and WriteDeletable should become Read operations
             // Only Read, Delete, and Extend operations should be generated
             match op {
-                WalrusNodeClientOp::Write { .. } => {
+                WalrusNodeClientOp::Write { .. }
+                | WalrusNodeClientOp::WriteQuilt { .. }
+                | WalrusNodeClientOp::UploadRelayWrite { .. } => {
                     panic!("Write operation generated when blob pool is full");
                 }
                 WalrusNodeClientOp::Read { .. } => {
@@ -249,10 +402,43 @@ mod tests {
                 WalrusNodeClientOp::Extend { .. } => {
                     // This is fine - extend operations are still allowed when pool is full
                 }
+                WalrusNodeClientOp::ReadQuiltPatch { .. }
+                | WalrusNodeClientOp::UpdateAttribute { .. } => {
+                    // This is fine - quilt reads and attribute updates are still allowed when
+                    // pool is full
+                }
                 WalrusNodeClientOp::None => {
                     // This is fine - none operations are still allowed when pool is full
                 }
             }
         }
     }
+
+    #[test]
+    fn test_seeded_write_ops_are_reproducible() {
+        let generate_blobs = || {
+            let generator = create_client_op_generator_favoring_writes_with_seed(Some(7));
+            let blob_pool = BlobPool::new_seeded(7, 1000);
+            let mut rng = StdRng::seed_from_u64(7);
+            (0..20)
+                .flat_map(
+                    |_| match generator.generate_client_op(&blob_pool, &mut rng) {
+                        WalrusNodeClientOp::Write { blob, .. }
+                        | WalrusNodeClientOp::UploadRelayWrite { blob, .. } => vec![blob],
+                        WalrusNodeClientOp::WriteQuilt { patches, .. } => {
+                            patches.into_iter().map(|(_, data)| data).collect()
+                        }
+                        _ => vec![],
+                    },
+                )
+                .collect::<Vec<_>>()
+        };
+
+        let blobs = generate_blobs();
+        assert!(!blobs.is_empty());
+        assert_eq!(blobs, generate_blobs());
+        for (index, blob) in (0..).zip(&blobs) {
+            assert_eq!(SeededBlobData::index_of(blob), Some(index));
+        }
+    }
 }
@@ -3,7 +3,10 @@
 
 //! Command line arguments for the single client workload.
 
+use std::{num::NonZeroUsize, path::PathBuf, time::Duration};
+
 use clap::Parser;
+use url::Url;
 
 use super::single_client_workload_config::{
     RequestTypeDistributionConfig,
@@ -25,7 +28,32 @@ pub struct SingleClientWorkloadArgs {
     /// that the pool will hold blob data in memory.
     #[arg(long, default_value_t = 10000)]
     pub max_blobs_in_pool: usize,
-    /// Define the distribution of request types: read, write, delete, extend
+    /// The maximum number of patches in a quilt. The size of each patch follows the size
+    /// distribution of the workload.
+    #[arg(long, default_value = "10")]
+    pub max_quilt_patches: NonZeroUsize,
+    /// The URL of the upload relay used for upload-relay writes.
+    ///
+    /// Required if the weight of upload-relay writes is greater than 0.
+    #[arg(long)]
+    pub upload_relay_url: Option<Url>,
+    /// The duration of the workload. If not set, the workload runs until interrupted.
+    #[arg(long, value_parser = humantime::parse_duration)]
+    pub duration: Option<Duration>,
+    /// The path to which the SLO report is written in JSON format at the end of the run.
+    ///
+    /// The report is always logged at the end of the run.
+    #[arg(long)]
+    pub slo_report_path: Option<PathBuf>,
+    /// The seed of the workload.
+    ///
+    /// If set, the random choices of the workload are derived from the seed, and the data of each
+    /// blob is derived from the seed and the index of the blob. Reads are then verified against the
+    /// regenerated data without keeping the blobs in memory, and mismatches are logged with the
+    /// seed and blob index to reproduce them.
+    #[arg(long)]
+    pub seed: Option<u64>,
+    /// Define the distribution of request types
     #[command(flatten)]
     pub request_type_distribution: RequestTypeDistributionArgs,
     /// Define the workload configuration including size and store length distributions
@@ -53,6 +81,18 @@ pub struct RequestTypeDistributionArgs {
     /// Weight for extend requests
     #[arg(long, default_value_t = 1)]
     pub extend_weight: u32,
+    /// Weight for quilt write requests
+    #[arg(long, default_value_t = 2)]
+    pub quilt_write_weight: u32,
+    /// Weight for quilt patch read requests
+    #[arg(long, default_value_t = 5)]
+    pub quilt_read_weight: u32,
+    /// Weight for blob attribute update requests
+    #[arg(long, default_value_t = 1)]
+    pub update_attribute_weight: u32,
+    /// Weight for write requests through the upload relay
+    #[arg(long, default_value_t = 0)]
+    pub upload_relay_write_weight: u32,
     // TODO(WAL-938): allow sending inconsistent blob data.
 }
 
@@ -65,6 +105,10 @@ impl RequestTypeDistributionArgs {
             write_deletable_weight: self.write_deletable_weight,
             delete_weight: self.delete_weight,
             extend_weight: self.extend_weight,
+            quilt_write_weight: self.quilt_write_weight,
+            quilt_read_weight: self.quilt_read_weight,
+            update_attribute_weight: self.update_attribute_weight,
+            upload_relay_write_weight: self.upload_relay_write_weight,
         }
     }
 }
@@ -19,10 +19,18 @@ pub struct RequestTypeDistributionConfig {
     pub delete_weight: u32,
     /// The weight for extend requests.
     pub extend_weight: u32,
+    /// The weight for quilt write requests.
+    pub quilt_write_weight: u32,
+    /// The weight for quilt patch read requests.
+    pub quilt_read_weight: u32,
+    /// The weight for blob attribute update requests.
+    pub update_attribute_weight: u32,
+    /// The weight for write requests through an upload relay.
+    pub upload_relay_write_weight: u32,
 }
 
 /// The type of request.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum RequestType {
     /// Read request.
     Read,
@@ -34,16 +42,38 @@ pub enum RequestType {
     Delete,
     /// Extend request.
     Extend,
+    /// Quilt write request.
+    QuiltWrite,
+    /// Quilt patch read request.
+    QuiltRead,
+    /// Blob attribute update request.
+    UpdateAttribute,
+    /// Write request through an upload relay.
+    UploadRelayWrite,
 }
 
 impl RequestTypeDistributionConfig {
+    /// Returns the weight of each request type.
+    fn weights(&self) -> [(RequestType, u32); 9] {
+        [
+            (RequestType::Read, self.read_weight),
+            (RequestType::WritePermanent, self.write_permanent_weight),
+            (RequestType::WriteDeletable, self.write_deletable_weight),
+            (RequestType::Delete, self.delete_weight),
+            (RequestType::Extend, self.extend_weight),
+            (RequestType::QuiltWrite, self.quilt_write_weight),
+            (RequestType::QuiltRead, self.quilt_read_weight),
+            (RequestType::UpdateAttribute, self.update_attribute_weight),
+            (
+                RequestType::UploadRelayWrite,
+                self.upload_relay_write_weight,
+            ),
+        ]
+    }
+
     /// Calculate the total weight across all request types
     pub fn total_weight(&self) -> u32 {
-        self.read_weight
-            + self.write_permanent_weight
-            + self.write_deletable_weight
-            + self.delete_weight
-            + self.extend_weight
+        self.weights().iter().map(|(_, weight)| weight).sum()
     }
 
     /// Validate that at least one weight is greater than 0
@@ -56,26 +86,14 @@ impl RequestTypeDistributionConfig {
 
     /// Sample a request type based on the weight distribution.
     pub fn sample<R: Rng>(&self, rng: &mut R) -> RequestType {
-        let total_weight = self.total_weight();
-        let random_value = rng.gen_range(0..total_weight);
-        if random_value < self.read_weight {
-            RequestType::Read
-        } else if random_value < self.read_weight + self.write_permanent_weight {
-            RequestType::WritePermanent
-        } else if random_value
-            < self.read_weight + self.write_permanent_weight + self.write_deletable_weight
-        {
-            RequestType::WriteDeletable
-        } else if random_value
-            < self.read_weight
-                + self.write_permanent_weight
-                + self.write_deletable_weight
-                + self.delete_weight
-        {
-            RequestType::Delete
-        } else {
-            RequestType::Extend
+        let mut random_value = rng.gen_range(0..self.total_weight());
+        for (request_type, weight) in self.weights() {
+            if random_value < weight {
+                return request_type;
+            }
+            random_value -= weight;
         }
+        unreachable!("the random value is less than the total weight")
     }
 }
 
@@ -194,6 +212,10 @@ impl StoreLengthDistributionConfig {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashSet;
+
+    use rand::{SeedableRng, rngs::StdRng};
+
     use super::*;
 
     #[test]
@@ -204,6 +226,10 @@ mod tests {
             write_deletable_weight: 0,
             delete_weight: 0,
             extend_weight: 0,
+            quilt_write_weight: 0,
+            quilt_read_weight: 0,
+            update_attribute_weight: 0,
+            upload_relay_write_weight: 0,
         };
         let result = config.validate();
         assert!(result.is_err());
@@ -221,6 +247,10 @@ mod tests {
             write_deletable_weight: 3,
             delete_weight: 1,
             extend_weight: 1,
+            quilt_write_weight: 0,
+            quilt_read_weight: 0,
+            update_attribute_weight: 0,
+            upload_relay_write_weight: 0,
         };
         assert!(config.validate().is_ok());
     }
@@ -233,6 +263,10 @@ mod tests {
             write_deletable_weight: 0,
             delete_weight: 0,
             extend_weight: 0,
+            quilt_write_weight: 0,
+            quilt_read_weight: 0,
+            update_attribute_weight: 0,
+            upload_relay_write_weight: 0,
         };
         let result = config.validate();
         assert!(result.is_err());
@@ -242,6 +276,30 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_sample_only_request_types_with_weight() {
+        let config = RequestTypeDistributionConfig {
+            read_weight: 0,
+            write_permanent_weight: 0,
+            write_deletable_weight: 0,
+            delete_weight: 0,
+            extend_weight: 0,
+            quilt_write_weight: 1,
+            quilt_read_weight: 0,
+            update_attribute_weight: 3,
+            upload_relay_write_weight: 0,
+        };
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut sampled = HashSet::new();
+        for _ in 0..100 {
+            sampled.insert(config.sample(&mut rng));
+        }
+        assert_eq!(
+            sampled,
+            [RequestType::QuiltWrite, RequestType::UpdateAttribute].into()
+        );
+    }
+
     #[test]
     fn test_uniform_size_validate_success() {
         let config = SizeDistributionConfig::Uniform {
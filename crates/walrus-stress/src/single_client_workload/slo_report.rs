@@ -0,0 +1,212 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Service-level objective (SLO) report of a workload run.
+
+use std::{collections::BTreeMap, time::Duration};
+
+use serde::Serialize;
+use walrus_sdk::error::ClientError;
+use walrus_utils::nearest_rank;
+
+use super::blob_pool::DataMismatchError;
+
+/// The number of bytes in a GB.
+const BYTES_PER_GB: f64 = 1e9;
+/// The number of FROST in a WAL, and of MIST in a SUI.
+const UNITS_PER_COIN: f64 = 1e9;
+
+/// The SLO report of a workload run, serialized as JSON to track regressions between releases.
+#[derive(Debug, Clone, Serialize)]
+pub struct SloReport {
+    /// The duration of the run in seconds.
+    pub duration_secs: f64,
+    /// The statistics of each operation type.
+    pub operations: BTreeMap<&'static str, OperationSlo>,
+    /// The cost of the data stored during the run.
+    pub cost: CostReport,
+}
+
+/// The latency percentiles and errors of an operation type.
+#[derive(Debug, Clone, Serialize)]
+pub struct OperationSlo {
+    /// The number of successful operations.
+    pub succeeded: usize,
+    /// The number of failed operations.
+    pub failed: usize,
+    /// The median latency of the successful operations in milliseconds.
+    pub p50_ms: Option<f64>,
+    /// The 95th percentile latency of the successful operations in milliseconds.
+    pub p95_ms: Option<f64>,
+    /// The 99th percentile latency of the successful operations in milliseconds.
+    pub p99_ms: Option<f64>,
+    /// The number of failed operations by `ClientErrorKind`.
+    ///
+    /// Reads returning data that does not match the data written are counted as `DataMismatch`.
+    /// Other errors that are not client errors, e.g., errors of Sui transactions, are counted as
+    /// `Other`.
+    pub errors: BTreeMap<&'static str, usize>,
+}
+
+/// The cost of the data stored during a run.
+#[derive(Debug, Clone, Serialize)]
+pub struct CostReport {
+    /// The number of unencoded bytes stored successfully.
+    pub bytes_stored: u64,
+    /// The WAL spent during the run, in FROST.
+    pub wal_spent: u64,
+    /// The SUI spent during the run, in MIST.
+    pub sui_spent: u64,
+    /// The WAL spent per GB of stored data.
+    pub wal_per_gb: Option<f64>,
+    /// The SUI spent per GB of stored data.
+    pub sui_per_gb: Option<f64>,
+}
+
+#[derive(Debug, Default)]
+struct OperationRecord {
+    latencies: Vec<Duration>,
+    errors: BTreeMap<&'static str, usize>,
+}
+
+/// Records the outcomes of the operations of a run.
+#[derive(Debug, Default)]
+pub(crate) struct SloRecorder {
+    operations: BTreeMap<&'static str, OperationRecord>,
+    bytes_stored: u64,
+}
+
+impl SloRecorder {
+    /// Records a successful operation, which stored `bytes_stored` bytes.
+    pub fn record_success(&mut self, op: &'static str, latency: Duration, bytes_stored: u64) {
+        self.operations
+            .entry(op)
+            .or_default()
+            .latencies
+            .push(latency);
+        self.bytes_stored += bytes_stored;
+    }
+
+    /// Records a failed operation.
+    pub fn record_failure(&mut self, op: &'static str, error: &anyhow::Error) {
+        *self
+            .operations
+            .entry(op)
+            .or_default()
+            .errors
+            .entry(error_kind(error))
+            .or_default() += 1;
+    }
+
+    /// Creates the report of the run, given the WAL and SUI spent during the run.
+    pub fn report(&self, duration: Duration, wal_spent: u64, sui_spent: u64) -> SloReport {
+        let operations = self
+            .operations
+            .iter()
+            .map(|(op, record)| {
+                let mut latencies = record.latencies.clone();
+                latencies.sort_unstable();
+                let percentile_ms = |percentile| {
+                    nearest_rank(&latencies, percentile)
+                        .map(|latency| latency.as_micros() as f64 / 1000.0)
+                };
+                let slo = OperationSlo {
+                    succeeded: latencies.len(),
+                    failed: record.errors.values().sum(),
+                    p50_ms: percentile_ms(50.0),
+                    p95_ms: percentile_ms(95.0),
+                    p99_ms: percentile_ms(99.0),
+                    errors: record.errors.clone(),
+                };
+                (*op, slo)
+            })
+            .collect();
+
+        let cost_per_gb = |spent: u64| {
+            (self.bytes_stored > 0).then(|| {
+                (spent as f64 / UNITS_PER_COIN) / (self.bytes_stored as f64 / BYTES_PER_GB)
+            })
+        };
+        SloReport {
+            duration_secs: duration.as_secs_f64(),
+            operations,
+            cost: CostReport {
+                bytes_stored: self.bytes_stored,
+                wal_spent,
+                sui_spent,
+                wal_per_gb: cost_per_gb(wal_spent),
+                sui_per_gb: cost_per_gb(sui_spent),
+            },
+        }
+    }
+}
+
+/// Returns the name of the `ClientErrorKind` of the error.
+fn error_kind(error: &anyhow::Error) -> &'static str {
+    if error
+        .chain()
+        .any(|cause| cause.downcast_ref::<DataMismatchError>().is_some())
+    {
+        return "DataMismatch";
+    }
+    error
+        .chain()
+        .find_map(|cause| cause.downcast_ref::<ClientError>())
+        .map_or("Other", |client_error| client_error.kind().name())
+}
+
+#[cfg(test)]
+mod tests {
+    use walrus_sdk::error::ClientErrorKind;
+
+    use super::*;
+
+    #[test]
+    fn test_slo_report() {
+        let mut recorder = SloRecorder::default();
+        for millis in 1..=100 {
+            recorder.record_success("write", Duration::from_millis(millis), 10_000_000);
+        }
+        recorder.record_failure(
+            "write",
+            &anyhow::Error::from(ClientError::from(ClientErrorKind::NotEnoughConfirmations(
+                1, 2,
+            ))),
+        );
+        recorder.record_failure(
+            "read",
+            &anyhow::Error::from(ClientError::from(ClientErrorKind::NotEnoughSlivers))
+                .context("reading the blob failed"),
+        );
+        recorder.record_failure("read", &anyhow::anyhow!("transaction failed"));
+        recorder.record_failure(
+            "read",
+            &anyhow::Error::from(DataMismatchError::for_testing()).context("checking the read"),
+        );
+
+        let report = recorder.report(Duration::from_secs(60), 2_000_000_000, 500_000_000);
+
+        let write = &report.operations["write"];
+        assert_eq!((write.succeeded, write.failed), (100, 1));
+        assert_eq!(write.p50_ms, Some(50.0));
+        assert_eq!(write.p95_ms, Some(95.0));
+        assert_eq!(write.p99_ms, Some(99.0));
+        assert_eq!(
+            write.errors,
+            BTreeMap::from([("NotEnoughConfirmations", 1)])
+        );
+
+        let read = &report.operations["read"];
+        assert_eq!((read.succeeded, read.failed), (0, 3));
+        assert_eq!(read.p50_ms, None);
+        assert_eq!(
+            read.errors,
+            BTreeMap::from([("DataMismatch", 1), ("NotEnoughSlivers", 1), ("Other", 1)])
+        );
+
+        // 1 GB stored for 2 WAL and 0.5 SUI.
+        assert_eq!(report.cost.bytes_stored, 1_000_000_000);
+        assert_eq!(report.cost.wal_per_gb, Some(2.0));
+        assert_eq!(report.cost.sui_per_gb, Some(0.5));
+    }
+}
@@ -0,0 +1,674 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Trace-replay workload.
+//!
+//! Replays a [trace](trace) of timestamped operations, captured from the access logs of
+//! publishers and aggregators or written by hand, and reports the latency percentiles of each
+//! operation type.
+
+use std::{
+    collections::HashMap,
+    num::NonZeroUsize,
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use anyhow::Context;
+use latency_report::{LatencyReport, OpOutcome};
+use rand::{seq::SliceRandom, thread_rng};
+use tokio::{
+    sync::{Semaphore, watch},
+    task::JoinSet,
+};
+use trace::{TraceEntry, TraceOp};
+use walrus_core::{
+    BlobId,
+    DEFAULT_ENCODING,
+    EpochCount,
+    QuiltPatchId,
+    encoding::{
+        Primary,
+        quilt_encoding::{QuiltStoreBlob, QuiltVersionV1},
+    },
+};
+use walrus_sdk::{
+    ObjectID,
+    client::{
+        StoreArgs,
+        WalrusNodeClient,
+        client_types::StoredQuiltPatch,
+        metrics::ClientMetrics,
+        responses::BlobStoreResult,
+    },
+    store_optimizations::StoreOptimizations,
+};
+use walrus_sui::client::{BlobPersistence, CreditsPolicy, PostStoreAction, SuiContractClient};
+use walrus_test_utils::random_data_from_rng;
+
+pub(crate) mod access_log;
+pub mod latency_report;
+pub mod trace;
+pub mod trace_replay_arg;
+
+/// The configuration of a trace replay.
+#[derive(Debug, Clone)]
+pub struct TraceReplayConfig {
+    /// The factor by which the replay is sped up compared to the trace.
+    pub speedup: f64,
+    /// The maximum number of operations executed concurrently.
+    ///
+    /// Operations are started at their scheduled time only if fewer operations are in flight;
+    /// otherwise, they are delayed until an operation completes.
+    pub max_concurrency: NonZeroUsize,
+    /// Whether to replace reads of blobs and quilts that were not stored during the replay with
+    /// reads of random blobs and quilts that were.
+    pub substitute_unknown_reads: bool,
+}
+
+/// A workload replaying a trace of operations.
+#[derive(Debug)]
+pub struct TraceReplay {
+    config: TraceReplayConfig,
+    executor: Arc<OpExecutor>,
+}
+
+impl TraceReplay {
+    /// Creates a new trace replay.
+    pub fn new(
+        client: WalrusNodeClient<SuiContractClient>,
+        config: TraceReplayConfig,
+        metrics: Arc<ClientMetrics>,
+    ) -> Self {
+        let executor = OpExecutor {
+            client,
+            metrics,
+            substitute_unknown_reads: config.substitute_unknown_reads,
+            state: ReplayState::default(),
+        };
+        Self {
+            config,
+            executor: Arc::new(executor),
+        }
+    }
+
+    /// Replays the trace and returns the latencies of the operations.
+    ///
+    /// The entries of the trace must be ordered by timestamp.
+    pub async fn run(&self, trace: Vec<TraceEntry>) -> anyhow::Result<LatencyReport> {
+        tracing::info!(n_entries = trace.len(), config = ?self.config, "starting trace replay");
+
+        let semaphore = Arc::new(Semaphore::new(self.config.max_concurrency.get()));
+        let mut report = LatencyReport::default();
+        let mut ops = JoinSet::new();
+        let start = Instant::now();
+
+        for entry in trace {
+            let scheduled = start + self.scaled_offset(entry.timestamp_ms);
+            tokio::time::sleep_until(scheduled.into()).await;
+            let permit = semaphore
+                .clone()
+                .acquire_owned()
+                .await
+                .expect("the semaphore is never closed");
+            report.record_start_delay(scheduled.elapsed());
+
+            while let Some(result) = ops.try_join_next() {
+                let (kind, outcome) = result.context("replayed operation panicked")?;
+                report.record(kind, outcome);
+            }
+
+            let kind = entry.op.kind();
+            let op = self.executor.state.dispatch(entry.op);
+            let executor = self.executor.clone();
+            ops.spawn(async move {
+                let outcome = executor.execute(op).await;
+                drop(permit);
+                (kind, outcome)
+            });
+        }
+
+        while let Some(result) = ops.join_next().await {
+            let (kind, outcome) = result.context("replayed operation panicked")?;
+            report.record(kind, outcome);
+        }
+        tracing::info!(elapsed = ?start.elapsed(), "trace replay completed");
+        Ok(report)
+    }
+
+    /// Returns the offset from the start of the replay at which an entry is scheduled.
+    fn scaled_offset(&self, timestamp_ms: u64) -> Duration {
+        Duration::from_millis(timestamp_ms).div_f64(self.config.speedup)
+    }
+}
+
+/// The status of a blob or quilt stored during the replay.
+#[derive(Debug, Clone)]
+enum StoreStatus {
+    Pending,
+    Stored(StoredBlob),
+    Failed,
+}
+
+/// A blob or quilt stored during the replay.
+#[derive(Debug, Clone)]
+struct StoredBlob {
+    blob_id: BlobId,
+    /// The blob object, if it was created by the replay.
+    object_id: Option<ObjectID>,
+    /// The patches, if the blob is a quilt.
+    patches: Vec<StoredQuiltPatch>,
+}
+
+/// A reference of an operation to a blob or quilt, resolved when the operation is dispatched.
+#[derive(Debug)]
+enum BlobRef {
+    /// A blob or quilt stored during the replay.
+    Labeled(watch::Receiver<StoreStatus>),
+    /// A reference that does not match any label, e.g., the ID of an existing blob.
+    Unknown(String),
+}
+
+/// A trace operation whose references have been resolved.
+#[derive(Debug)]
+enum DispatchedOp {
+    Store {
+        size: usize,
+        epochs: EpochCount,
+        deletable: bool,
+        status: Option<watch::Sender<StoreStatus>>,
+    },
+    Read {
+        blob: BlobRef,
+    },
+    Extend {
+        blob: BlobRef,
+        epochs: EpochCount,
+    },
+    Delete {
+        blob: BlobRef,
+    },
+    QuiltStore {
+        patch_sizes: Vec<usize>,
+        epochs: EpochCount,
+        status: Option<watch::Sender<StoreStatus>>,
+    },
+    QuiltRead {
+        quilt: BlobRef,
+        identifier: String,
+    },
+    QuiltReadPatch {
+        patch_id: String,
+    },
+}
+
+/// The blobs and quilts stored during the replay.
+#[derive(Debug, Default)]
+struct ReplayState {
+    /// The status of the labeled stores, by label.
+    labels: Mutex<HashMap<String, watch::Receiver<StoreStatus>>>,
+    /// The blobs stored successfully.
+    blobs: Mutex<Vec<BlobId>>,
+    /// The quilts stored successfully.
+    quilts: Mutex<Vec<StoredBlob>>,
+}
+
+impl ReplayState {
+    /// Resolves the references of the operation.
+    ///
+    /// Operations are dispatched in the order of the trace, so that an operation references the
+    /// latest store with the label that precedes it in the trace.
+    fn dispatch(&self, op: TraceOp) -> DispatchedOp {
+        match op {
+            TraceOp::Store {
+                size,
+                epochs,
+                deletable,
+                label,
+            } => DispatchedOp::Store {
+                size,
+                epochs,
+                deletable,
+                status: self.register(label),
+            },
+            TraceOp::Read { blob } => DispatchedOp::Read {
+                blob: self.resolve(blob),
+            },
+            TraceOp::Extend { blob, epochs } => DispatchedOp::Extend {
+                blob: self.resolve(blob),
+                epochs,
+            },
+            TraceOp::Delete { blob } => DispatchedOp::Delete {
+                blob: self.resolve(blob),
+            },
+            TraceOp::QuiltStore {
+                patch_sizes,
+                epochs,
+                label,
+            } => DispatchedOp::QuiltStore {
+                patch_sizes,
+                epochs,
+                status: self.register(label),
+            },
+            TraceOp::QuiltRead { quilt, identifier } => DispatchedOp::QuiltRead {
+                quilt: self.resolve(quilt),
+                identifier,
+            },
+            TraceOp::QuiltReadPatch { patch_id } => DispatchedOp::QuiltReadPatch { patch_id },
+        }
+    }
+
+    fn register(&self, label: Option<String>) -> Option<watch::Sender<StoreStatus>> {
+        let (sender, receiver) = watch::channel(StoreStatus::Pending);
+        self.labels
+            .lock()
+            .expect("mutex should not be poisoned")
+            .insert(label?, receiver);
+        Some(sender)
+    }
+
+    fn resolve(&self, reference: String) -> BlobRef {
+        match self
+            .labels
+            .lock()
+            .expect("mutex should not be poisoned")
+            .get(&reference)
+        {
+            Some(receiver) => BlobRef::Labeled(receiver.clone()),
+            None => BlobRef::Unknown(reference),
+        }
+    }
+
+    fn random_blob(&self) -> Option<BlobId> {
+        self.blobs
+            .lock()
+            .expect("mutex should not be poisoned")
+            .choose(&mut thread_rng())
+            .copied()
+    }
+
+    fn random_quilt(&self) -> Option<StoredBlob> {
+        self.quilts
+            .lock()
+            .expect("mutex should not be poisoned")
+            .choose(&mut thread_rng())
+            .cloned()
+    }
+}
+
+/// Executes the operations of a trace replay.
+#[derive(Debug)]
+struct OpExecutor {
+    client: WalrusNodeClient<SuiContractClient>,
+    metrics: Arc<ClientMetrics>,
+    substitute_unknown_reads: bool,
+    state: ReplayState,
+}
+
+impl OpExecutor {
+    async fn execute(&self, op: DispatchedOp) -> OpOutcome {
+        match self.try_execute(op).await {
+            Ok(Some(latency)) => OpOutcome::Succeeded(latency),
+            Ok(None) => OpOutcome::Skipped,
+            Err(error) => {
+                tracing::warn!(?error, "replayed operation failed");
+                OpOutcome::Failed
+            }
+        }
+    }
+
+    /// Executes the operation and returns its latency, or `None` if the operation is skipped.
+    async fn try_execute(&self, op: DispatchedOp) -> anyhow::Result<Option<Duration>> {
+        match op {
+            DispatchedOp::Store {
+                size,
+                epochs,
+                deletable,
+                status,
+            } => {
+                let blob = random_data_from_rng(size, &mut thread_rng());
+                let now = Instant::now();
+                let result = self.store(&blob, epochs, deletable).await;
+                let latency = now.elapsed();
+                if let Ok(stored) = &result {
+                    self.state
+                        .blobs
+                        .lock()
+                        .expect("mutex should not be poisoned")
+                        .push(stored.blob_id);
+                }
+                publish_status(status, &result);
+                result?;
+                self.metrics.observe_latency("replay_store", latency);
+                Ok(Some(latency))
+            }
+            DispatchedOp::Read { blob } => {
+                let blob_id = match blob {
+                    BlobRef::Labeled(mut receiver) => {
+                        let Some(stored) = wait_stored(&mut receiver).await else {
+                            return Ok(None);
+                        };
+                        stored.blob_id
+                    }
+                    BlobRef::Unknown(_) if self.substitute_unknown_reads => {
+                        let Some(blob_id) = self.state.random_blob() else {
+                            return Ok(None);
+                        };
+                        blob_id
+                    }
+                    BlobRef::Unknown(reference) => BlobId::from_str(&reference)
+                        .with_context(|| format!("invalid blob reference '{reference}'"))?,
+                };
+                let now = Instant::now();
+                self.client.read_blob::<Primary>(&blob_id).await?;
+                let latency = now.elapsed();
+                self.metrics.observe_latency("replay_read", latency);
+                Ok(Some(latency))
+            }
+            DispatchedOp::Extend { blob, epochs } => {
+                let Some(object_id) = self.owned_object(blob).await else {
+                    return Ok(None);
+                };
+                let now = Instant::now();
+                self.client
+                    .sui_client()
+                    .extend_blob(object_id, epochs, CreditsPolicy::Auto)
+                    .await?;
+                let latency = now.elapsed();
+                self.metrics.observe_latency("replay_extend", latency);
+                Ok(Some(latency))
+            }
+            DispatchedOp::Delete { blob } => {
+                let Some(object_id) = self.owned_object(blob).await else {
+                    return Ok(None);
+                };
+                let now = Instant::now();
+                self.client.delete_owned_blob_by_object(object_id).await?;
+                let latency = now.elapsed();
+                self.metrics.observe_latency("replay_delete", latency);
+                Ok(Some(latency))
+            }
+            DispatchedOp::QuiltStore {
+                patch_sizes,
+                epochs,
+                status,
+            } => {
+                let patches: Vec<_> = patch_sizes
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, size)| {
+                        QuiltStoreBlob::new_owned(
+                            random_data_from_rng(size, &mut thread_rng()),
+                            format!("patch-{index}"),
+                        )
+                    })
+                    .collect::<Result<_, _>>()?;
+                let now = Instant::now();
+                let result = self.store_quilt(&patches, epochs).await;
+                let latency = now.elapsed();
+                if let Ok(stored) = &result {
+                    self.state
+                        .quilts
+                        .lock()
+                        .expect("mutex should not be poisoned")
+                        .push(stored.clone());
+                }
+                publish_status(status, &result);
+                result?;
+                self.metrics.observe_latency("replay_quilt_store", latency);
+                Ok(Some(latency))
+            }
+            DispatchedOp::QuiltRead { quilt, identifier } => {
+                let (quilt_id, identifier) = match quilt {
+                    BlobRef::Labeled(mut receiver) => {
+                        let Some(stored) = wait_stored(&mut receiver).await else {
+                            return Ok(None);
+                        };
+                        (stored.blob_id, identifier)
+                    }
+                    BlobRef::Unknown(_) if self.substitute_unknown_reads => {
+                        let Some((quilt_id, patch)) = self.random_quilt_patch() else {
+                            return Ok(None);
+                        };
+                        (quilt_id, patch.identifier)
+                    }
+                    BlobRef::Unknown(reference) => (
+                        BlobId::from_str(&reference)
+                            .with_context(|| format!("invalid quilt reference '{reference}'"))?,
+                        identifier,
+                    ),
+                };
+                let now = Instant::now();
+                self.client
+                    .quilt_client()
+                    .get_blobs_by_identifiers(&quilt_id, &[identifier.as_str()])
+                    .await?;
+                let latency = now.elapsed();
+                self.metrics.observe_latency("replay_quilt_read", latency);
+                Ok(Some(latency))
+            }
+            DispatchedOp::QuiltReadPatch { patch_id } => {
+                let patch_id = if self.substitute_unknown_reads {
+                    let Some((_, patch)) = self.random_quilt_patch() else {
+                        return Ok(None);
+                    };
+                    patch.quilt_patch_id
+                } else {
+                    patch_id
+                };
+                let patch_id = QuiltPatchId::from_str(&patch_id)
+                    .with_context(|| format!("invalid quilt patch ID '{patch_id}'"))?;
+                let now = Instant::now();
+                self.client
+                    .quilt_client()
+                    .get_blobs_by_ids(&[patch_id])
+                    .await?;
+                let latency = now.elapsed();
+                self.metrics.observe_latency("replay_quilt_read", latency);
+                Ok(Some(latency))
+            }
+        }
+    }
+
+    async fn store(
+        &self,
+        blob: &[u8],
+        epochs: EpochCount,
+        deletable: bool,
+    ) -> anyhow::Result<StoredBlob> {
+        let store_args = StoreArgs::new(
+            DEFAULT_ENCODING,
+            epochs,
+            StoreOptimizations::none(),
+            BlobPersistence::from_deletable_and_permanent(deletable, !deletable)?,
+            PostStoreAction::Keep,
+        )
+        .with_metrics(self.metrics.clone());
+        let mut results = self
+            .client
+            .reserve_and_store_blobs_retry_committees(&[blob], &[], &store_args)
+            .await?;
+        let result = results
+            .pop()
+            .context("no store result for the stored blob")?;
+        stored_blob(result, vec![])
+    }
+
+    async fn store_quilt(
+        &self,
+        patches: &[QuiltStoreBlob<'_>],
+        epochs: EpochCount,
+    ) -> anyhow::Result<StoredBlob> {
+        let quilt_client = self.client.quilt_client();
+        let quilt = quilt_client
+            .construct_quilt::<QuiltVersionV1>(patches, DEFAULT_ENCODING)
+            .await?;
+        let store_args = StoreArgs::default_with_epochs(epochs)
+            .no_store_optimizations()
+            .with_metrics(self.metrics.clone());
+        let result = quilt_client
+            .reserve_and_store_quilt::<QuiltVersionV1>(&quilt, &store_args)
+            .await?;
+        stored_blob(result.blob_store_result, result.stored_quilt_blobs)
+    }
+
+    /// Returns the object of a blob stored during the replay, or `None` if the operation must be
+    /// skipped.
+    async fn owned_object(&self, blob: BlobRef) -> Option<ObjectID> {
+        match blob {
+            BlobRef::Labeled(mut receiver) => wait_stored(&mut receiver).await?.object_id,
+            BlobRef::Unknown(reference) => {
+                tracing::warn!(
+                    reference,
+                    "skipping operation on a blob not stored by the replay"
+                );
+                None
+            }
+        }
+    }
+
+    fn random_quilt_patch(&self) -> Option<(BlobId, StoredQuiltPatch)> {
+        let quilt = self.state.random_quilt()?;
+        let patch = quilt.patches.choose(&mut thread_rng())?.clone();
+        Some((quilt.blob_id, patch))
+    }
+}
+
+fn stored_blob(
+    result: BlobStoreResult,
+    patches: Vec<StoredQuiltPatch>,
+) -> anyhow::Result<StoredBlob> {
+    let object_id = match &result {
+        BlobStoreResult::NewlyCreated { blob_object, .. } => Some(blob_object.id),
+        BlobStoreResult::AlreadyCertified { .. } => None,
+        _ => anyhow::bail!("unexpected store result: {result:?}"),
+    };
+    let blob_id = result
+        .blob_id()
+        .context("the store result does not contain a blob ID")?;
+    Ok(StoredBlob {
+        blob_id,
+        object_id,
+        patches,
+    })
+}
+
+/// Waits for a labeled store to complete and returns the stored blob, if successful.
+async fn wait_stored(receiver: &mut watch::Receiver<StoreStatus>) -> Option<StoredBlob> {
+    let status = receiver
+        .wait_for(|status| !matches!(status, StoreStatus::Pending))
+        .await
+        .ok()?;
+    match &*status {
+        StoreStatus::Stored(blob) => Some(blob.clone()),
+        StoreStatus::Pending | StoreStatus::Failed => None,
+    }
+}
+
+/// Notifies the operations referencing a labeled store of its completion.
+fn publish_status(status: Option<watch::Sender<StoreStatus>>, result: &anyhow::Result<StoredBlob>) {
+    if let Some(status) = status {
+        // The receivers may all have been dropped, if no later operation references the label.
+        let _ = status.send(match result {
+            Ok(stored) => StoreStatus::Stored(stored.clone()),
+            Err(_) => StoreStatus::Failed,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(blob: &str) -> TraceOp {
+        TraceOp::Read {
+            blob: blob.to_owned(),
+        }
+    }
+
+    fn store(label: &str) -> TraceOp {
+        TraceOp::Store {
+            size: 1,
+            epochs: 1,
+            deletable: false,
+            label: Some(label.to_owned()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_resolves_latest_preceding_label() {
+        let state = ReplayState::default();
+
+        let DispatchedOp::Read {
+            blob: BlobRef::Unknown(reference),
+        } = state.dispatch(read("a"))
+        else {
+            panic!("a read before the store should not be resolved");
+        };
+        assert_eq!(reference, "a");
+
+        let DispatchedOp::Store {
+            status: Some(first),
+            ..
+        } = state.dispatch(store("a"))
+        else {
+            panic!("a labeled store should register its status");
+        };
+        let DispatchedOp::Read {
+            blob: BlobRef::Labeled(mut first_receiver),
+        } = state.dispatch(read("a"))
+        else {
+            panic!("a read after the store should be resolved");
+        };
+
+        let DispatchedOp::Store {
+            status: Some(second),
+            ..
+        } = state.dispatch(store("a"))
+        else {
+            panic!("a labeled store should register its status");
+        };
+        let DispatchedOp::Read {
+            blob: BlobRef::Labeled(mut second_receiver),
+        } = state.dispatch(read("a"))
+        else {
+            panic!("a read after the store should be resolved");
+        };
+
+        let blob_id = BlobId([1; 32]);
+        publish_status(
+            Some(second),
+            &Ok(StoredBlob {
+                blob_id,
+                object_id: None,
+                patches: vec![],
+            }),
+        );
+        publish_status(Some(first), &Err(anyhow::anyhow!("store failed")));
+
+        assert!(wait_stored(&mut first_receiver).await.is_none());
+        assert_eq!(
+            wait_stored(&mut second_receiver)
+                .await
+                .map(|stored| stored.blob_id),
+            Some(blob_id)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_wait_stored_fails_if_store_is_dropped() {
+        let state = ReplayState::default();
+        let DispatchedOp::Store { status, .. } = state.dispatch(store("a")) else {
+            panic!("expected a store");
+        };
+        let DispatchedOp::Read {
+            blob: BlobRef::Labeled(mut receiver),
+        } = state.dispatch(read("a"))
+        else {
+            panic!("a read after the store should be resolved");
+        };
+        drop(status);
+        assert!(wait_stored(&mut receiver).await.is_none());
+    }
+}
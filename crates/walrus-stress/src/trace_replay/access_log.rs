@@ -0,0 +1,211 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Conversion of publisher and aggregator access logs into trace entries.
+//!
+//! The access logs are expected in the Common Log Format (or a format extending it, such as the
+//! Combined Log Format), as written by a reverse proxy in front of the publisher or aggregator.
+//! As the Common Log Format does not record the size of the request body, the size of stored
+//! blobs is taken from the last field of the line, which must then contain the request length.
+//! With nginx, for example, the following format can be used:
+//!
+//! ```text
+//! log_format walrus '$remote_addr - $remote_user [$time_local] "$request" '
+//!                   '$status $body_bytes_sent $request_length';
+//! ```
+//!
+//! Only successful requests to the following endpoints are converted:
+//!
+//! - `PUT /v1/blobs`, converted to a store of the size of the request;
+//! - `PUT /v1/quilts`, converted to a store of a quilt with a single patch of the size of the
+//!   request, as the sizes of the individual patches are not logged;
+//! - `GET /v1/blobs/{blob_id}`, converted to a read;
+//! - `GET /v1/blobs/by-quilt-patch-id/{quilt_patch_id}` and
+//!   `GET /v1/blobs/by-quilt-id/{quilt_id}/{identifier}`, converted to quilt reads.
+
+use anyhow::{Context, anyhow, bail};
+use chrono::DateTime;
+use walrus_core::EpochCount;
+
+use super::trace::{TraceEntry, TraceOp};
+
+/// The number of epochs used by the publisher if the request does not specify it.
+const DEFAULT_EPOCHS: EpochCount = 1;
+
+/// Parses a line of an access log.
+///
+/// Returns `None` if the line corresponds to a request that is not replayed, and an error if the
+/// line is malformed.
+pub(crate) fn parse_line(line: &str) -> anyhow::Result<Option<TraceEntry>> {
+    let (_, rest) = line
+        .split_once('[')
+        .ok_or_else(|| anyhow!("missing timestamp"))?;
+    let (timestamp, rest) = rest
+        .split_once(']')
+        .ok_or_else(|| anyhow!("unterminated timestamp"))?;
+    let timestamp_ms = DateTime::parse_from_str(timestamp, "%d/%b/%Y:%H:%M:%S %z")
+        .with_context(|| format!("invalid timestamp '{timestamp}'"))?
+        .timestamp_millis()
+        .try_into()
+        .context("timestamp before the unix epoch")?;
+
+    let (_, rest) = rest
+        .split_once('"')
+        .ok_or_else(|| anyhow!("missing request"))?;
+    let (request, rest) = rest
+        .split_once('"')
+        .ok_or_else(|| anyhow!("unterminated request"))?;
+    let mut fields = rest.split_whitespace();
+    let status: u16 = fields
+        .next()
+        .ok_or_else(|| anyhow!("missing status"))?
+        .parse()
+        .context("invalid status")?;
+    if !(200..300).contains(&status) {
+        return Ok(None);
+    }
+    // Skip the body bytes sent; the request length, if logged, is the last field.
+    let request_length = fields.nth(1).and(rest.split_whitespace().last());
+
+    let mut request_fields = request.split_whitespace();
+    let (Some(method), Some(target)) = (request_fields.next(), request_fields.next()) else {
+        bail!("invalid request '{request}'");
+    };
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let segments: Vec<_> = path.trim_matches('/').split('/').collect();
+
+    let op = match (method, segments.as_slice()) {
+        ("PUT", ["v1", "blobs"]) => {
+            let (epochs, deletable) = parse_store_query(query)?;
+            TraceOp::Store {
+                size: parse_request_length(request_length)?,
+                epochs,
+                deletable,
+                label: None,
+            }
+        }
+        ("PUT", ["v1", "quilts"]) => TraceOp::QuiltStore {
+            patch_sizes: vec![parse_request_length(request_length)?],
+            epochs: parse_store_query(query)?.0,
+            label: None,
+        },
+        ("GET", ["v1", "blobs", "by-quilt-patch-id", patch_id]) => TraceOp::QuiltReadPatch {
+            patch_id: (*patch_id).to_owned(),
+        },
+        ("GET", ["v1", "blobs", "by-quilt-id", quilt_id, identifier]) => TraceOp::QuiltRead {
+            quilt: (*quilt_id).to_owned(),
+            identifier: (*identifier).to_owned(),
+        },
+        ("GET", ["v1", "blobs", blob_id]) => TraceOp::Read {
+            blob: (*blob_id).to_owned(),
+        },
+        _ => return Ok(None),
+    };
+    Ok(Some(TraceEntry { timestamp_ms, op }))
+}
+
+/// Returns the number of epochs and whether the blob is deletable from the query of a store.
+fn parse_store_query(query: &str) -> anyhow::Result<(EpochCount, bool)> {
+    let mut epochs = DEFAULT_EPOCHS;
+    let mut deletable = false;
+    for (key, value) in query.split('&').filter_map(|pair| pair.split_once('=')) {
+        match key {
+            "epochs" => epochs = value.parse().context("invalid number of epochs")?,
+            "deletable" => deletable = value.parse().context("invalid deletable flag")?,
+            _ => (),
+        }
+    }
+    Ok((epochs, deletable))
+}
+
+fn parse_request_length(request_length: Option<&str>) -> anyhow::Result<usize> {
+    request_length
+        .ok_or_else(|| anyhow!("the request length is not logged"))?
+        .parse()
+        .context("invalid request length")
+}
+
+#[cfg(test)]
+mod tests {
+    use walrus_test_utils::param_test;
+
+    use super::*;
+
+    const TIMESTAMP: &str = "[10/Oct/2025:13:55:36 +0000]";
+    const TIMESTAMP_MS: u64 = 1_760_104_536_000;
+
+    param_test! {
+        test_parse_line: [
+            store: (
+                r#""PUT /v1/blobs?epochs=5&deletable=true HTTP/1.1" 200 512 1048576"#,
+                Some(TraceOp::Store {
+                    size: 1048576,
+                    epochs: 5,
+                    deletable: true,
+                    label: None,
+                }),
+            ),
+            store_default_epochs: (
+                r#""PUT /v1/blobs HTTP/1.1" 200 512 "-" "curl/8.5.0" 100"#,
+                Some(TraceOp::Store {
+                    size: 100,
+                    epochs: 1,
+                    deletable: false,
+                    label: None,
+                }),
+            ),
+            quilt_store: (
+                r#""PUT /v1/quilts?epochs=3 HTTP/1.1" 200 512 4096"#,
+                Some(TraceOp::QuiltStore {
+                    patch_sizes: vec![4096],
+                    epochs: 3,
+                    label: None,
+                }),
+            ),
+            read: (
+                r#""GET /v1/blobs/some-blob-id HTTP/1.1" 200 1024"#,
+                Some(TraceOp::Read {
+                    blob: "some-blob-id".to_owned(),
+                }),
+            ),
+            quilt_read_by_patch_id: (
+                r#""GET /v1/blobs/by-quilt-patch-id/some-patch-id HTTP/1.1" 200 1024"#,
+                Some(TraceOp::QuiltReadPatch {
+                    patch_id: "some-patch-id".to_owned(),
+                }),
+            ),
+            quilt_read_by_identifier: (
+                r#""GET /v1/blobs/by-quilt-id/some-quilt-id/image.png HTTP/1.1" 200 1024"#,
+                Some(TraceOp::QuiltRead {
+                    quilt: "some-quilt-id".to_owned(),
+                    identifier: "image.png".to_owned(),
+                }),
+            ),
+            failed_request: (r#""GET /v1/blobs/some-blob-id HTTP/1.1" 404 0"#, None),
+            other_endpoint: (r#""GET /v1/api HTTP/1.1" 200 2048"#, None),
+        ]
+    }
+    fn test_parse_line(request: &str, expected: Option<TraceOp>) {
+        let line = format!("127.0.0.1 - - {TIMESTAMP} {request}");
+        let entry = parse_line(&line).expect("line should be valid");
+        assert_eq!(
+            entry,
+            expected.map(|op| TraceEntry {
+                timestamp_ms: TIMESTAMP_MS,
+                op,
+            })
+        );
+    }
+
+    param_test! {
+        test_parse_line_fails: [
+            missing_request_length: (r#""PUT /v1/blobs HTTP/1.1" 200 512"#),
+            invalid_epochs: (r#""PUT /v1/blobs?epochs=many HTTP/1.1" 200 512 100"#),
+            missing_status: (r#""GET /v1/blobs/some-blob-id HTTP/1.1""#),
+        ]
+    }
+    fn test_parse_line_fails(request: &str) {
+        let line = format!("127.0.0.1 - - {TIMESTAMP} {request}");
+        assert!(parse_line(&line).is_err());
+    }
+}
@@ -0,0 +1,147 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Latency statistics of a trace replay.
+
+use std::{collections::BTreeMap, fmt, time::Duration};
+
+use walrus_utils::nearest_rank;
+
+/// The outcome of a replayed operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpOutcome {
+    /// The operation succeeded after the given latency.
+    Succeeded(Duration),
+    /// The operation failed.
+    Failed,
+    /// The operation was not executed, e.g., because the blob it references could not be stored.
+    Skipped,
+}
+
+/// The statistics of a single operation type.
+#[derive(Debug, Clone, Default)]
+struct OpStats {
+    /// The latencies of the successful operations.
+    latencies: Vec<Duration>,
+    n_failed: usize,
+    n_skipped: usize,
+}
+
+/// The latency percentiles and outcome counts of a trace replay, per operation type.
+#[derive(Debug, Clone, Default)]
+pub struct LatencyReport {
+    stats: BTreeMap<&'static str, OpStats>,
+    /// The maximum delay between the scheduled time of an operation and its start.
+    max_start_delay: Duration,
+}
+
+impl LatencyReport {
+    /// Records the outcome of an operation of the given type.
+    pub fn record(&mut self, kind: &'static str, outcome: OpOutcome) {
+        let stats = self.stats.entry(kind).or_default();
+        match outcome {
+            OpOutcome::Succeeded(latency) => stats.latencies.push(latency),
+            OpOutcome::Failed => stats.n_failed += 1,
+            OpOutcome::Skipped => stats.n_skipped += 1,
+        }
+    }
+
+    /// Records the delay between the scheduled time of an operation and its start.
+    pub fn record_start_delay(&mut self, delay: Duration) {
+        self.max_start_delay = self.max_start_delay.max(delay);
+    }
+
+    /// Returns the given percentile of the latencies of the operation type, or `None` if no
+    /// operation of that type succeeded.
+    ///
+    /// Uses the nearest-rank method; `percentile` must be in `(0, 100]`.
+    pub fn percentile(&mut self, kind: &str, percentile: f64) -> Option<Duration> {
+        let latencies = &mut self.stats.get_mut(kind)?.latencies;
+        latencies.sort_unstable();
+        nearest_rank(latencies, percentile)
+    }
+}
+
+impl fmt::Display for LatencyReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<12} {:>8} {:>8} {:>8} {:>10} {:>10} {:>10} {:>10}",
+            "operation", "ok", "failed", "skipped", "p50", "p90", "p99", "max"
+        )?;
+        for (kind, stats) in &self.stats {
+            let mut latencies = stats.latencies.clone();
+            latencies.sort_unstable();
+            let format_percentile = |percentile| {
+                nearest_rank(&latencies, percentile)
+                    .map(|latency| format!("{}ms", latency.as_millis()))
+                    .unwrap_or_else(|| "-".to_owned())
+            };
+            writeln!(
+                f,
+                "{:<12} {:>8} {:>8} {:>8} {:>10} {:>10} {:>10} {:>10}",
+                kind,
+                latencies.len(),
+                stats.n_failed,
+                stats.n_skipped,
+                format_percentile(50.0),
+                format_percentile(90.0),
+                format_percentile(99.0),
+                format_percentile(100.0),
+            )?;
+        }
+        write!(
+            f,
+            "maximum delay of an operation start: {}ms",
+            self.max_start_delay.as_millis()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentiles() {
+        let mut report = LatencyReport::default();
+        for millis in (1..=100).rev() {
+            report.record("read", OpOutcome::Succeeded(Duration::from_millis(millis)));
+        }
+        report.record("read", OpOutcome::Failed);
+
+        assert_eq!(
+            report.percentile("read", 50.0),
+            Some(Duration::from_millis(50))
+        );
+        assert_eq!(
+            report.percentile("read", 99.0),
+            Some(Duration::from_millis(99))
+        );
+        assert_eq!(
+            report.percentile("read", 100.0),
+            Some(Duration::from_millis(100))
+        );
+        assert_eq!(
+            report.percentile("read", 0.1),
+            Some(Duration::from_millis(1))
+        );
+        assert_eq!(report.percentile("store", 50.0), None);
+    }
+
+    #[test]
+    fn test_report_counts_outcomes() {
+        let mut report = LatencyReport::default();
+        report.record("store", OpOutcome::Succeeded(Duration::from_millis(10)));
+        report.record("store", OpOutcome::Failed);
+        report.record("extend", OpOutcome::Skipped);
+
+        let lines: Vec<_> = report
+            .to_string()
+            .lines()
+            .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+            .collect();
+        assert_eq!(lines[1], "extend 0 0 1 - - - -");
+        assert_eq!(lines[2], "store 1 1 0 10ms 10ms 10ms 10ms");
+    }
+}
@@ -0,0 +1,222 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! The trace format replayed by the trace-replay workload.
+//!
+//! A trace is a JSON-lines file, where each line holds one timestamped operation, for example:
+//!
+//! ```text
+//! {"timestamp_ms": 0, "op": "store", "size": 1048576, "epochs": 5, "label": "archive"}
+//! {"timestamp_ms": 120, "op": "quilt_store", "patch_sizes": [512, 64], "epochs": 1, "label": "q"}
+//! {"timestamp_ms": 250, "op": "read", "blob": "archive"}
+//! {"timestamp_ms": 300, "op": "quilt_read", "quilt": "q", "identifier": "patch-1"}
+//! {"timestamp_ms": 900, "op": "extend", "blob": "archive", "epochs": 2}
+//! {"timestamp_ms": 1000, "op": "delete", "blob": "archive"}
+//! ```
+//!
+//! Blobs and quilts stored during the replay can be referenced by later operations through their
+//! `label`. The patches of a quilt stored during the replay are identified as `patch-0`,
+//! `patch-1`, and so on. Read operations can also reference existing blobs and quilts by their ID.
+
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
+};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use walrus_core::EpochCount;
+
+use super::access_log;
+
+/// The format of a trace file.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TraceFormat {
+    /// A JSON-lines file of [`TraceEntry`].
+    #[default]
+    Json,
+    /// An access log of a publisher or aggregator; see [`access_log`] for the expected format.
+    AccessLog,
+}
+
+/// A timestamped operation of a trace.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TraceEntry {
+    /// The time of the operation, in milliseconds.
+    ///
+    /// Only the differences between the timestamps of the entries are relevant for the replay.
+    pub timestamp_ms: u64,
+    /// The operation.
+    #[serde(flatten)]
+    pub op: TraceOp,
+}
+
+/// An operation of a trace.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum TraceOp {
+    /// Store a blob of random data.
+    Store {
+        /// The size of the blob in bytes.
+        size: usize,
+        /// The number of epochs for which to store the blob.
+        epochs: EpochCount,
+        /// Whether the blob is deletable.
+        #[serde(default)]
+        deletable: bool,
+        /// The label through which later operations reference the blob.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        label: Option<String>,
+    },
+    /// Read a blob.
+    Read {
+        /// The label of a blob stored during the replay, or the ID of an existing blob.
+        blob: String,
+    },
+    /// Extend the lifetime of a blob stored during the replay.
+    Extend {
+        /// The label of the blob.
+        blob: String,
+        /// The number of epochs by which to extend the blob.
+        epochs: EpochCount,
+    },
+    /// Delete a deletable blob stored during the replay.
+    Delete {
+        /// The label of the blob.
+        blob: String,
+    },
+    /// Store a quilt whose patches contain random data.
+    QuiltStore {
+        /// The sizes of the patches in bytes.
+        patch_sizes: Vec<usize>,
+        /// The number of epochs for which to store the quilt.
+        epochs: EpochCount,
+        /// The label through which later operations reference the quilt.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        label: Option<String>,
+    },
+    /// Read a quilt patch by its identifier.
+    QuiltRead {
+        /// The label of a quilt stored during the replay, or the ID of an existing quilt.
+        quilt: String,
+        /// The identifier of the patch within the quilt.
+        identifier: String,
+    },
+    /// Read an existing quilt patch by its quilt patch ID.
+    QuiltReadPatch {
+        /// The quilt patch ID.
+        patch_id: String,
+    },
+}
+
+impl TraceOp {
+    /// Returns the name of the operation type, used to aggregate latencies.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Store { .. } => "store",
+            Self::Read { .. } => "read",
+            Self::Extend { .. } => "extend",
+            Self::Delete { .. } => "delete",
+            Self::QuiltStore { .. } => "quilt_store",
+            Self::QuiltRead { .. } | Self::QuiltReadPatch { .. } => "quilt_read",
+        }
+    }
+}
+
+/// Loads the trace at the provided path.
+///
+/// The entries are returned ordered by timestamp, with timestamps relative to the first entry.
+pub fn load_trace(path: &Path, format: TraceFormat) -> anyhow::Result<Vec<TraceEntry>> {
+    let file = File::open(path)
+        .with_context(|| format!("failed to open the trace file '{}'", path.display()))?;
+    let mut entries = Vec::new();
+    let mut n_skipped = 0;
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.context("failed to read the trace file")?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match format {
+            TraceFormat::Json => entries.push(
+                serde_json::from_str(&line)
+                    .with_context(|| format!("invalid trace entry on line {}", index + 1))?,
+            ),
+            TraceFormat::AccessLog => match access_log::parse_line(&line) {
+                Ok(Some(entry)) => entries.push(entry),
+                Ok(None) => n_skipped += 1,
+                Err(error) => {
+                    tracing::warn!(
+                        line = index + 1,
+                        ?error,
+                        "skipping malformed access log line"
+                    );
+                    n_skipped += 1;
+                }
+            },
+        }
+    }
+    if n_skipped > 0 {
+        tracing::info!(
+            n_skipped,
+            "skipped access log lines that cannot be replayed"
+        );
+    }
+
+    normalize(&mut entries);
+    Ok(entries)
+}
+
+/// Sorts the entries by timestamp and makes the timestamps relative to the first entry.
+fn normalize(entries: &mut [TraceEntry]) {
+    entries.sort_by_key(|entry| entry.timestamp_ms);
+    if let Some(start) = entries.first().map(|entry| entry.timestamp_ms) {
+        entries
+            .iter_mut()
+            .for_each(|entry| entry.timestamp_ms -= start);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_trace_entries() {
+        let store: TraceEntry = serde_json::from_str(
+            r#"{"timestamp_ms": 10, "op": "store", "size": 1024, "epochs": 5, "label": "a"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            store,
+            TraceEntry {
+                timestamp_ms: 10,
+                op: TraceOp::Store {
+                    size: 1024,
+                    epochs: 5,
+                    deletable: false,
+                    label: Some("a".to_owned()),
+                },
+            }
+        );
+
+        let quilt_read: TraceEntry = serde_json::from_str(
+            r#"{"timestamp_ms": 20, "op": "quilt_read", "quilt": "q", "identifier": "patch-0"}"#,
+        )
+        .unwrap();
+        assert_eq!(quilt_read.op.kind(), "quilt_read");
+    }
+
+    #[test]
+    fn test_normalize_sorts_and_rebases_timestamps() {
+        let read = |timestamp_ms, blob: &str| TraceEntry {
+            timestamp_ms,
+            op: TraceOp::Read {
+                blob: blob.to_owned(),
+            },
+        };
+        let mut entries = vec![read(1500, "c"), read(1000, "a"), read(1200, "b")];
+        normalize(&mut entries);
+        assert_eq!(entries, vec![read(0, "a"), read(200, "b"), read(500, "c")]);
+    }
+}
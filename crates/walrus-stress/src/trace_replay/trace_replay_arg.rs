@@ -0,0 +1,52 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Command line arguments for the trace-replay workload.
+
+use std::{num::NonZeroUsize, path::PathBuf};
+
+use clap::Parser;
+
+use super::{TraceReplayConfig, trace::TraceFormat};
+
+/// Arguments for the trace-replay workload.
+#[derive(Parser, Debug, Clone)]
+#[command(rename_all = "kebab-case")]
+pub struct TraceReplayArgs {
+    /// The path to the trace file.
+    #[arg(long)]
+    pub trace_path: PathBuf,
+    /// The format of the trace file.
+    #[arg(long, value_enum, default_value_t = TraceFormat::Json)]
+    pub trace_format: TraceFormat,
+    /// The factor by which to speed up the replay compared to the trace.
+    ///
+    /// For example, a speedup of 2 replays the trace twice as fast, and a speedup of 0.5 replays
+    /// it at half speed.
+    #[arg(long, default_value_t = 1.0)]
+    pub speedup: f64,
+    /// The maximum number of operations executed concurrently.
+    #[arg(long, default_value = "16")]
+    pub max_concurrency: NonZeroUsize,
+    /// Replace reads of blobs and quilts that were not stored during the replay with reads of
+    /// random blobs and quilts that were.
+    ///
+    /// This allows replaying access logs on a network that does not contain the blobs of the
+    /// original traffic.
+    #[arg(long)]
+    pub substitute_unknown_reads: bool,
+}
+
+impl TraceReplayArgs {
+    /// Convert to configuration for use by the trace replay.
+    pub fn to_config(&self) -> anyhow::Result<TraceReplayConfig> {
+        if !(self.speedup.is_finite() && self.speedup > 0.0) {
+            anyhow::bail!("the speedup must be a positive number");
+        }
+        Ok(TraceReplayConfig {
+            speedup: self.speedup,
+            max_concurrency: self.max_concurrency,
+            substitute_unknown_reads: self.substitute_unknown_reads,
+        })
+    }
+}
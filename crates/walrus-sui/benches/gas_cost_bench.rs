@@ -29,6 +29,7 @@ use walrus_sui::{
         BlobObjectMetadata,
         BlobPersistence,
         CertifyAndExtendBlobParams,
+        CreditsPolicy,
         PostStoreAction,
         SuiContractClient,
     },
@@ -188,8 +189,10 @@ async fn gas_cost_for_contract_calls(args: Args) -> anyhow::Result<()> {
                 reserve_epochs,
                 blob_metadata_vec,
                 BlobPersistence::Permanent,
+                CreditsPolicy::Auto,
             )
-            .await?;
+            .await?
+            .blobs;
 
         if !args.extend {
             write_data_entry(
@@ -250,7 +253,11 @@ async fn gas_cost_for_contract_calls(args: Args) -> anyhow::Result<()> {
                     .collect();
                 walrus_client
                     .as_ref()
-                    .certify_and_extend_blobs(&certify_and_extend_params, PostStoreAction::Keep)
+                    .certify_and_extend_blobs(
+                        &certify_and_extend_params,
+                        PostStoreAction::Keep,
+                        CreditsPolicy::Auto,
+                    )
                     .await?;
                 write_data_entry(
                     &mut out_file,
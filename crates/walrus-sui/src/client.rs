@@ -23,11 +23,23 @@ use sui_sdk::{
     rpc_types::{SuiExecutionStatus, SuiTransactionBlockEffectsAPI, SuiTransactionBlockResponse},
     types::base_types::ObjectID,
 };
-use sui_types::{TypeTag, base_types::SuiAddress, event::EventID, transaction::TransactionData};
+use sui_types::{
+    TypeTag,
+    base_types::SuiAddress,
+    digests::TransactionDigest,
+    event::EventID,
+    programmable_transaction_builder::ProgrammableTransactionBuilder,
+    transaction::{Argument, Command, TransactionData},
+};
 use tokio::sync::Mutex;
 use tokio_stream::Stream;
 use tracing::Level;
-use transaction_builder::{MAX_BURNS_PER_PTB, WalrusPtbBuilder};
+use transaction_builder::{
+    MAX_BURNS_PER_PTB,
+    MAX_TRANSFERS_PER_PTB,
+    WalrusPtbBuilder,
+    build_transaction_data_with_min_gas_balance,
+};
 use walrus_core::{
     BlobId,
     EncodingType,
@@ -88,10 +100,16 @@ pub mod rpc_client;
 pub mod rpc_config;
 
 pub mod transaction_builder;
+mod write_client;
+pub use write_client::WriteClient;
+
 use crate::types::move_structs::EventBlob;
 
 pub mod contract_config;
 
+mod gas_accounting;
+pub use gas_accounting::{in_current_gas_accounting_scope, with_gas_accounting};
+
 mod metrics;
 pub use metrics::SuiClientMetricSet;
 
@@ -249,6 +267,10 @@ pub struct CertifyAndExtendBlobResult {
     pub blob_object_id: ObjectID,
     /// The result of the post store action.
     pub post_store_action_result: PostStoreActionResult,
+    /// Whether the extension of the blob was paid through Walrus credits.
+    ///
+    /// This is `false` if the blob was not extended.
+    pub paid_with_credits: bool,
 }
 
 impl CertifyAndExtendBlobResult {
@@ -264,6 +286,15 @@ impl CertifyAndExtendBlobResult {
     }
 }
 
+/// Result of registering blobs.
+#[derive(Debug, Clone)]
+pub struct RegisterBlobsResult {
+    /// The registered blob objects.
+    pub blobs: Vec<Blob>,
+    /// Whether the storage and registration fees were paid through Walrus credits.
+    pub paid_with_credits: bool,
+}
+
 /// The object ID of a shared object with the object ID of an associated admin cap.
 #[derive(Debug, Clone)]
 pub struct SharedObjectWithAdminCap {
@@ -347,6 +378,53 @@ impl ExpirySelectionPolicy {
     }
 }
 
+/// Determines whether storage and write fees are paid through the Walrus credits
+/// (`subsidies::Subsidies` in Move) object.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CreditsPolicy {
+    /// Use credits if a credits object is configured for the client, and fall back to direct
+    /// payment if the credits package is outdated.
+    #[default]
+    Auto,
+    /// Always use credits; fails if no credits object is configured or the credits call fails.
+    Force,
+    /// Never use credits, even if a credits object is configured.
+    Disable,
+}
+
+impl CreditsPolicy {
+    /// Constructs [`Self`] based on the value of the `use_credits` and `no_credits` flags.
+    ///
+    /// If neither flag is set, returns [`Self::Auto`]. If both are set, `no_credits` takes
+    /// precedence.
+    pub fn from_use_and_no_credits_flags(use_credits: bool, no_credits: bool) -> Self {
+        match (use_credits, no_credits) {
+            (_, true) => Self::Disable,
+            (true, false) => Self::Force,
+            (false, false) => Self::Auto,
+        }
+    }
+
+    /// Returns whether credits should be used, given whether a credits object is configured.
+    ///
+    /// Returns [`SuiClientError::CreditsNotEnabled`] if the policy is [`Self::Force`] and no
+    /// credits object is configured.
+    pub fn use_credits(&self, credits_configured: bool) -> SuiClientResult<bool> {
+        match self {
+            Self::Auto => Ok(credits_configured),
+            Self::Force if credits_configured => Ok(true),
+            Self::Force => Err(SuiClientError::CreditsNotEnabled),
+            Self::Disable => Ok(false),
+        }
+    }
+
+    /// Returns `true` if a failed credits call may fall back to a direct contract call.
+    pub fn allows_fallback(&self) -> bool {
+        matches!(self, Self::Auto)
+    }
+}
+
 impl BlobPersistence {
     /// Returns `true` if the blob is deletable.
     pub fn is_deletable(&self) -> bool {
@@ -385,7 +463,8 @@ impl BlobPersistence {
 pub struct InvalidBlobPersistenceError;
 
 /// The action to be performed for newly-created blobs.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub enum PostStoreAction {
     /// Burn the blob object.
     Burn,
@@ -572,35 +651,65 @@ impl SuiContractClient {
             .await
     }
 
+    /// Returns the buyer subsidy rate (in basis points) of the credits object, if credits are
+    /// used under the given `credits_policy`.
+    ///
+    /// Returns `None` if credits are not used.
+    pub async fn credits_buyer_subsidy_rate(
+        &self,
+        credits_policy: CreditsPolicy,
+    ) -> SuiClientResult<Option<u16>> {
+        let credits_object_id = self.read_client.get_credits_object_id();
+        if !credits_policy.use_credits(credits_object_id.is_some())? {
+            return Ok(None);
+        }
+        let Some(credits_object_id) = credits_object_id else {
+            return Ok(None);
+        };
+        let credits_object = self
+            .retriable_sui_client()
+            .get_credits_object(credits_object_id)
+            .await?;
+        Ok(Some(credits_object.buyer_subsidy_rate))
+    }
+
     /// Purchases blob storage for the next `epochs_ahead` Walrus epochs and an encoded
     /// size of `encoded_size` and returns the created storage resource.
+    ///
+    /// The `credits_policy` determines whether the storage is paid for through credits.
     pub async fn reserve_space(
         &self,
         encoded_size: u64,
         epochs_ahead: EpochCount,
+        credits_policy: CreditsPolicy,
     ) -> SuiClientResult<StorageResource> {
         self.retry_on_wrong_version(|| async {
             self.inner
                 .lock()
                 .await
-                .reserve_space(encoded_size, epochs_ahead)
+                .reserve_space(encoded_size, epochs_ahead, credits_policy)
                 .await
         })
         .await
     }
 
     /// Registers blobs with the specified [`BlobObjectMetadata`] and [`StorageResource`]s,
-    /// and returns the created blob objects.
+    /// and returns the created blob objects and whether they were paid through credits.
     pub async fn register_blobs(
         &self,
         blob_metadata_and_storage: Vec<(BlobObjectMetadata, StorageResource)>,
         persistence: BlobPersistence,
-    ) -> SuiClientResult<Vec<Blob>> {
+        credits_policy: CreditsPolicy,
+    ) -> SuiClientResult<RegisterBlobsResult> {
         self.retry_on_wrong_version(|| async {
             self.inner
                 .lock()
                 .await
-                .register_blobs(blob_metadata_and_storage.clone(), persistence)
+                .register_blobs(
+                    blob_metadata_and_storage.clone(),
+                    persistence,
+                    credits_policy,
+                )
                 .await
         })
         .await
@@ -616,12 +725,18 @@ impl SuiContractClient {
         epochs_ahead: EpochCount,
         blob_metadata_list: Vec<BlobObjectMetadata>,
         persistence: BlobPersistence,
-    ) -> SuiClientResult<Vec<Blob>> {
+        credits_policy: CreditsPolicy,
+    ) -> SuiClientResult<RegisterBlobsResult> {
         self.retry_on_wrong_version(|| async {
             self.inner
                 .lock()
                 .await
-                .reserve_and_register_blobs(epochs_ahead, blob_metadata_list.clone(), persistence)
+                .reserve_and_register_blobs(
+                    epochs_ahead,
+                    blob_metadata_list.clone(),
+                    persistence,
+                    credits_policy,
+                )
                 .await
         })
         .await
@@ -1075,6 +1190,24 @@ impl SuiContractClient {
         .await
     }
 
+    /// Transfers the blob objects with the given object IDs to the `recipient` address.
+    ///
+    /// May use multiple PTBs in sequence to transfer all the given object IDs.
+    pub async fn transfer_blobs(
+        &self,
+        blob_object_ids: &[ObjectID],
+        recipient: SuiAddress,
+    ) -> SuiClientResult<()> {
+        self.retry_on_wrong_version(|| async {
+            self.inner
+                .lock()
+                .await
+                .transfer_blobs(blob_object_ids, recipient)
+                .await
+        })
+        .await
+    }
+
     /// Funds the shared blob object.
     pub async fn fund_shared_blob(
         &self,
@@ -1124,16 +1257,19 @@ impl SuiContractClient {
     }
 
     /// Extends the owned blob object by `epochs_extended` epochs.
+    ///
+    /// The `credits_policy` determines whether the extension is paid for through credits.
     pub async fn extend_blob(
         &self,
         blob_obj_id: ObjectID,
         epochs_extended: EpochCount,
+        credits_policy: CreditsPolicy,
     ) -> SuiClientResult<()> {
         self.retry_on_wrong_version(|| async {
             self.inner
                 .lock()
                 .await
-                .extend_blob(blob_obj_id, epochs_extended)
+                .extend_blob(blob_obj_id, epochs_extended, credits_policy)
                 .await
         })
         .await
@@ -1325,12 +1461,13 @@ impl SuiContractClient {
         &self,
         blobs_with_certificates: &[CertifyAndExtendBlobParams<'_>],
         post_store: PostStoreAction,
+        credits_policy: CreditsPolicy,
     ) -> SuiClientResult<Vec<CertifyAndExtendBlobResult>> {
         self.retry_on_wrong_version(|| async {
             self.inner
                 .lock()
                 .await
-                .certify_and_extend_blobs(blobs_with_certificates, post_store)
+                .certify_and_extend_blobs(blobs_with_certificates, post_store, credits_policy)
                 .await
         })
         .await
@@ -1496,8 +1633,9 @@ impl SuiContractClientInner {
         &mut self,
         encoded_size: u64,
         epochs_ahead: EpochCount,
+        credits_policy: CreditsPolicy,
     ) -> SuiClientResult<StorageResource> {
-        if self.read_client.get_credits_object_id().is_some() {
+        if credits_policy.use_credits(self.read_client.get_credits_object_id().is_some())? {
             match self
                 .reserve_space_with_credits(encoded_size, epochs_ahead)
                 .await
@@ -1505,7 +1643,7 @@ impl SuiContractClientInner {
                 Ok(arg) => return Ok(arg),
                 Err(SuiClientError::TransactionExecutionError(MoveExecutionError::System(
                     SystemError::EWrongVersion(_),
-                ))) => {
+                ))) if credits_policy.allows_fallback() => {
                     tracing::warn!(
                         "Walrus package version mismatch in credits call,
                             falling back to direct contract call"
@@ -1590,13 +1728,18 @@ impl SuiContractClientInner {
         &mut self,
         blob_metadata_and_storage: Vec<(BlobObjectMetadata, StorageResource)>,
         persistence: BlobPersistence,
-    ) -> SuiClientResult<Vec<Blob>> {
+        credits_policy: CreditsPolicy,
+    ) -> SuiClientResult<RegisterBlobsResult> {
         if blob_metadata_and_storage.is_empty() {
             tracing::debug!("no blobs to register");
-            return Ok(vec![]);
+            return Ok(RegisterBlobsResult {
+                blobs: vec![],
+                paid_with_credits: false,
+            });
         }
 
-        let with_credits = self.read_client.get_credits_object_id().is_some();
+        let with_credits =
+            credits_policy.use_credits(self.read_client.get_credits_object_id().is_some())?;
 
         let expected_num_blobs = blob_metadata_and_storage.len();
         tracing::debug!(num_blobs = expected_num_blobs, "starting to register blobs");
@@ -1629,9 +1772,13 @@ impl SuiContractClientInner {
             expected_num_blobs
         );
 
-        self.retriable_sui_client()
-            .get_sui_objects(&blob_obj_ids)
-            .await
+        Ok(RegisterBlobsResult {
+            blobs: self
+                .retriable_sui_client()
+                .get_sui_objects(&blob_obj_ids)
+                .await?,
+            paid_with_credits: with_credits,
+        })
     }
 
     /// Purchases blob storage for the next `epochs_ahead` Walrus epochs and uses the resulting
@@ -1645,8 +1792,10 @@ impl SuiContractClientInner {
         epochs_ahead: EpochCount,
         blob_metadata_list: Vec<BlobObjectMetadata>,
         persistence: BlobPersistence,
-    ) -> SuiClientResult<Vec<Blob>> {
-        let with_credits = self.read_client.get_credits_object_id().is_some();
+        credits_policy: CreditsPolicy,
+    ) -> SuiClientResult<RegisterBlobsResult> {
+        let with_credits =
+            credits_policy.use_credits(self.read_client.get_credits_object_id().is_some())?;
         if with_credits {
             match self
                 .reserve_and_register_blobs_inner(
@@ -1657,10 +1806,15 @@ impl SuiContractClientInner {
                 )
                 .await
             {
-                Ok(result) => return Ok(result),
+                Ok(blobs) => {
+                    return Ok(RegisterBlobsResult {
+                        blobs,
+                        paid_with_credits: true,
+                    });
+                }
                 Err(SuiClientError::TransactionExecutionError(MoveExecutionError::System(
                     SystemError::EWrongVersion(_),
-                ))) => {
+                ))) if credits_policy.allows_fallback() => {
                     tracing::warn!(
                         "Walrus package version mismatch in credits call, \
                             falling back to direct contract call"
@@ -1669,8 +1823,13 @@ impl SuiContractClientInner {
                 Err(e) => return Err(e),
             }
         }
-        self.reserve_and_register_blobs_inner(epochs_ahead, blob_metadata_list, persistence, false)
-            .await
+        let blobs = self
+            .reserve_and_register_blobs_inner(epochs_ahead, blob_metadata_list, persistence, false)
+            .await?;
+        Ok(RegisterBlobsResult {
+            blobs,
+            paid_with_credits: false,
+        })
     }
 
     /// reserve and register blobs inner
@@ -2343,6 +2502,7 @@ impl SuiContractClientInner {
             .retriable_sui_client()
             .execute_transaction(signed_transaction, method)
             .await?;
+        gas_accounting::record_gas_usage(&response);
 
         // Check transaction execution status from effects
         match response
@@ -2450,6 +2610,29 @@ impl SuiContractClientInner {
         Ok(())
     }
 
+    /// Transfers the blob objects with the given object IDs to the `recipient` address.
+    ///
+    /// May use multiple PTBs in sequence to transfer all the given object IDs.
+    pub async fn transfer_blobs(
+        &mut self,
+        blob_object_ids: &[ObjectID],
+        recipient: SuiAddress,
+    ) -> SuiClientResult<()> {
+        tracing::debug!(n_blobs = blob_object_ids.len(), %recipient, "transferring blobs");
+
+        for id_block in blob_object_ids.chunks(MAX_TRANSFERS_PER_PTB) {
+            let mut pt_builder = self.transaction_builder()?;
+            pt_builder
+                .transfer(Some(recipient), id_block.iter().map(|id| id.into()))
+                .await?;
+            let transaction = pt_builder.build_transaction_data(self.gas_budget).await?;
+            self.sign_and_send_transaction(transaction, "transfer_blobs")
+                .await?;
+        }
+
+        Ok(())
+    }
+
     /// Funds the shared blob object.
     pub async fn fund_shared_blob(
         &mut self,
@@ -2576,8 +2759,10 @@ impl SuiContractClientInner {
         &mut self,
         blob_obj_id: ObjectID,
         epochs_extended: EpochCount,
+        credits_policy: CreditsPolicy,
     ) -> SuiClientResult<()> {
-        let with_credits = self.read_client.get_credits_package_id().is_some();
+        let with_credits =
+            credits_policy.use_credits(self.read_client.get_credits_package_id().is_some())?;
         if with_credits {
             match self
                 .extend_blob_with_credits(blob_obj_id, epochs_extended)
@@ -2586,7 +2771,7 @@ impl SuiContractClientInner {
                 Ok(_) => return Ok(()),
                 Err(SuiClientError::TransactionExecutionError(MoveExecutionError::System(
                     SystemError::EWrongVersion(_),
-                ))) => {
+                ))) if credits_policy.allows_fallback() => {
                     tracing::warn!(
                         "Walrus package version mismatch in credits call, \
                         call, falling back to direct contract call"
@@ -2682,8 +2867,13 @@ impl SuiContractClientInner {
         &mut self,
         blobs_with_certificates: &[CertifyAndExtendBlobParams<'_>],
         post_store: PostStoreAction,
+        credits_policy: CreditsPolicy,
     ) -> SuiClientResult<Vec<CertifyAndExtendBlobResult>> {
-        let with_credits = self.read_client.get_credits_package_id().is_some();
+        // Credits are only relevant if at least one of the blobs is extended.
+        let with_credits = blobs_with_certificates
+            .iter()
+            .any(|blob_params| blob_params.epochs_extended.is_some())
+            && credits_policy.use_credits(self.read_client.get_credits_package_id().is_some())?;
         if with_credits {
             match self
                 .certify_and_extend_blobs_inner(blobs_with_certificates, post_store, true)
@@ -2693,7 +2883,7 @@ impl SuiContractClientInner {
                 Err(SuiClientError::TransactionExecutionError(
                     MoveExecutionError::Staking(StakingError::EWrongVersion(_))
                     | MoveExecutionError::System(SystemError::EWrongVersion(_)),
-                )) => {
+                )) if credits_policy.allows_fallback() => {
                     tracing::warn!(
                         "Walrus package version mismatch in credits call, \
                             falling back to direct contract call"
@@ -2775,6 +2965,7 @@ impl SuiContractClientInner {
             .map(|(blob_params, r)| CertifyAndExtendBlobResult {
                 blob_object_id: blob_params.blob.id,
                 post_store_action_result: r,
+                paid_with_credits: with_credits && blob_params.epochs_extended.is_some(),
             })
             .collect();
 
@@ -3066,6 +3257,127 @@ impl ReadClient for SuiContractClient {
     }
 }
 
+impl WriteClient for SuiContractClient {
+    fn address(&self) -> SuiAddress {
+        self.wallet_address
+    }
+
+    async fn register_blobs(
+        &self,
+        blob_metadata_and_storage: Vec<(BlobObjectMetadata, StorageResource)>,
+        persistence: BlobPersistence,
+        credits_policy: CreditsPolicy,
+    ) -> SuiClientResult<RegisterBlobsResult> {
+        self.register_blobs(blob_metadata_and_storage, persistence, credits_policy)
+            .await
+    }
+
+    async fn reserve_and_register_blobs(
+        &self,
+        epochs_ahead: EpochCount,
+        blob_metadata_list: Vec<BlobObjectMetadata>,
+        persistence: BlobPersistence,
+        credits_policy: CreditsPolicy,
+    ) -> SuiClientResult<RegisterBlobsResult> {
+        self.reserve_and_register_blobs(
+            epochs_ahead,
+            blob_metadata_list,
+            persistence,
+            credits_policy,
+        )
+        .await
+    }
+
+    async fn certify_and_extend_blobs(
+        &self,
+        blobs_with_certificates: &[CertifyAndExtendBlobParams<'_>],
+        post_store: PostStoreAction,
+        credits_policy: CreditsPolicy,
+    ) -> SuiClientResult<Vec<CertifyAndExtendBlobResult>> {
+        self.certify_and_extend_blobs(blobs_with_certificates, post_store, credits_policy)
+            .await
+    }
+
+    async fn credits_buyer_subsidy_rate(
+        &self,
+        credits_policy: CreditsPolicy,
+    ) -> SuiClientResult<Option<u16>> {
+        self.credits_buyer_subsidy_rate(credits_policy).await
+    }
+
+    async fn owned_blobs(
+        &self,
+        owner: Option<SuiAddress>,
+        selection_policy: ExpirySelectionPolicy,
+    ) -> SuiClientResult<Vec<Blob>> {
+        self.owned_blobs(owner, selection_policy).await
+    }
+
+    async fn owned_storage(
+        &self,
+        selection_policy: ExpirySelectionPolicy,
+    ) -> SuiClientResult<Vec<StorageResource>> {
+        self.owned_storage(selection_policy).await
+    }
+
+    async fn delete_blob(&self, blob_object_id: ObjectID) -> SuiClientResult<()> {
+        self.delete_blob(blob_object_id).await
+    }
+
+    async fn transfer_blobs(
+        &self,
+        blob_object_ids: &[ObjectID],
+        recipient: SuiAddress,
+    ) -> SuiClientResult<()> {
+        self.transfer_blobs(blob_object_ids, recipient).await
+    }
+
+    async fn stake_with_pools(
+        &self,
+        node_ids_with_amounts: &[(ObjectID, u64)],
+    ) -> SuiClientResult<Vec<StakedWal>> {
+        self.stake_with_pools(node_ids_with_amounts).await
+    }
+
+    async fn pay_tip<A: Serialize + Sync>(
+        &self,
+        recipient: SuiAddress,
+        auth_package: &A,
+        amount: u64,
+        gas_budget: Option<u64>,
+    ) -> SuiClientResult<TransactionDigest> {
+        let mut pt_builder = ProgrammableTransactionBuilder::new();
+
+        // The first input is the authentication package.
+        pt_builder.pure(auth_package)?;
+
+        // Pay the tip.
+        let amount_arg = pt_builder.pure(amount)?;
+        let split_coin =
+            pt_builder.command(Command::SplitCoins(Argument::GasCoin, vec![amount_arg]));
+        pt_builder.transfer_arg(recipient, split_coin);
+
+        // Sign and execute.
+        let gas_price = self.read_client.get_reference_gas_price().await?;
+        let transaction_data = build_transaction_data_with_min_gas_balance(
+            pt_builder.finish(),
+            gas_price,
+            &self.read_client,
+            self.wallet_address,
+            gas_budget,
+            0, // No additional gas budget.
+            amount,
+        )
+        .await?;
+
+        let response = self
+            .sign_and_send_transaction(transaction_data, "pay_tip")
+            .await?;
+
+        Ok(response.digest)
+    }
+}
+
 impl fmt::Debug for SuiContractClient {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("SuiContractClient")
@@ -3099,4 +3411,17 @@ mod tests {
                 ).unwrap()
         ));
     }
+
+    #[test]
+    fn test_credits_policy_use_credits() {
+        assert!(CreditsPolicy::Auto.use_credits(true).unwrap());
+        assert!(!CreditsPolicy::Auto.use_credits(false).unwrap());
+        assert!(CreditsPolicy::Force.use_credits(true).unwrap());
+        assert!(matches!(
+            CreditsPolicy::Force.use_credits(false),
+            Err(SuiClientError::CreditsNotEnabled)
+        ));
+        assert!(!CreditsPolicy::Disable.use_credits(true).unwrap());
+        assert!(!CreditsPolicy::Disable.use_credits(false).unwrap());
+    }
 }
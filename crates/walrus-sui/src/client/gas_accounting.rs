@@ -0,0 +1,111 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Attribution of the gas spent by the transactions of a
+//! [`SuiContractClient`][super::SuiContractClient] to a scope of asynchronous work.
+//!
+//! This allows, e.g., a publisher serving multiple tenants to determine how much SUI was spent for
+//! a single request, even if the same client is used concurrently for other requests.
+//!
+//! The accounting scope is bound to the task polling the future. Futures that are spawned as
+//! separate tasks (e.g., with [`tokio::spawn`]) must be wrapped with
+//! [`in_current_gas_accounting_scope`] to attribute their transactions to the scope of the
+//! spawning task.
+
+use std::{
+    future::Future,
+    sync::{
+        Arc,
+        atomic::{AtomicI64, Ordering},
+    },
+};
+
+use sui_sdk::rpc_types::{SuiTransactionBlockEffectsAPI, SuiTransactionBlockResponse};
+
+tokio::task_local! {
+    static GAS_ACCOUNTING: Arc<AtomicI64>;
+}
+
+/// Runs the provided future and returns its output together with the net gas (in MIST) spent by
+/// all transactions executed by a [`SuiContractClient`][super::SuiContractClient] while polling it.
+///
+/// The net gas usage includes computation and storage costs minus storage rebates and may
+/// therefore be negative. Transactions executed in tasks spawned by the future are only accounted
+/// if the spawned futures are wrapped with [`in_current_gas_accounting_scope`]; the returned gas is
+/// read once the future completes, so such tasks must be awaited by the future.
+pub async fn with_gas_accounting<F: Future>(future: F) -> (F::Output, i64) {
+    let accumulator = Arc::new(AtomicI64::new(0));
+    let output = GAS_ACCOUNTING.scope(accumulator.clone(), future).await;
+    (output, accumulator.load(Ordering::Relaxed))
+}
+
+/// Wraps the future such that the gas spent while polling it is attributed to the accounting scope
+/// of the current task, if any.
+///
+/// This must be used for futures that are spawned as separate tasks within a scope of
+/// [`with_gas_accounting`], as task-local scopes are not inherited by spawned tasks.
+pub fn in_current_gas_accounting_scope<F: Future>(future: F) -> impl Future<Output = F::Output> {
+    let accumulator = GAS_ACCOUNTING.try_with(Arc::clone).ok();
+    async move {
+        match accumulator {
+            Some(accumulator) => GAS_ACCOUNTING.scope(accumulator, future).await,
+            None => future.await,
+        }
+    }
+}
+
+/// Adds the net gas usage of the transaction to the current accounting scope, if any.
+pub(crate) fn record_gas_usage(response: &SuiTransactionBlockResponse) {
+    let Some(effects) = response.effects.as_ref() else {
+        return;
+    };
+    let net_gas_usage = effects.gas_cost_summary().net_gas_usage();
+    // Outside of a scope, `try_with` returns an error, which we can safely ignore.
+    let _ = GAS_ACCOUNTING.try_with(|accumulator| {
+        accumulator.fetch_add(net_gas_usage, Ordering::Relaxed);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn gas_is_only_accounted_within_scope() {
+        let add = |amount| {
+            GAS_ACCOUNTING
+                .try_with(|accumulator| accumulator.fetch_add(amount, Ordering::Relaxed))
+                .is_ok()
+        };
+
+        assert!(!add(5));
+        let (in_scope, gas) = with_gas_accounting(async { add(7) && add(-2) }).await;
+        assert!(in_scope);
+        assert_eq!(gas, 5);
+    }
+
+    #[tokio::test]
+    async fn gas_of_spawned_tasks_is_accounted_if_propagated() {
+        let add = |amount| async move {
+            GAS_ACCOUNTING
+                .try_with(|accumulator| accumulator.fetch_add(amount, Ordering::Relaxed))
+                .is_ok()
+        };
+
+        let ((propagated, detached), gas) = with_gas_accounting(async {
+            let propagated = tokio::spawn(in_current_gas_accounting_scope(add(3)));
+            let detached = tokio::spawn(add(11));
+            (
+                propagated.await.expect("the task does not panic"),
+                detached.await.expect("the task does not panic"),
+            )
+        })
+        .await;
+        assert!(propagated);
+        assert!(!detached);
+        assert_eq!(gas, 3);
+
+        // Outside of a scope, the future is polled without accounting.
+        assert!(!in_current_gas_accounting_scope(add(5)).await);
+    }
+}
@@ -75,6 +75,12 @@ const CLOCK_OBJECT_ARG: ObjectArg = ObjectArg::SharedObject {
 // NB: this should be kept in sync with the maximum number of commands in the Sui `ProtocolConfig`.
 pub const MAX_BURNS_PER_PTB: usize = 1000;
 
+/// The maximum number of blobs that can be transferred in a single PTB.
+/// All objects are transferred with a single `TransferObjects` command, so this number is chosen
+/// just below the maximum number of arguments of a single command in a PTB (512).
+// NB: this should be kept in sync with the maximum number of arguments in the Sui `ProtocolConfig`.
+pub const MAX_TRANSFERS_PER_PTB: usize = 500;
+
 #[derive(Debug, Clone, Copy)]
 /// A wrapper around an [`Argument`] or an [`ObjectID`] for use in [`WalrusPtbBuilder`].
 pub enum ArgumentOrOwnedObject {
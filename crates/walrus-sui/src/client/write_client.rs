@@ -0,0 +1,118 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Trait for the contract calls used to store and manage blobs.
+
+use std::future::Future;
+
+use serde::Serialize;
+use sui_types::{
+    base_types::{ObjectID, SuiAddress},
+    digests::TransactionDigest,
+};
+use walrus_core::EpochCount;
+
+use super::{
+    BlobObjectMetadata,
+    BlobPersistence,
+    CertifyAndExtendBlobParams,
+    CertifyAndExtendBlobResult,
+    CreditsPolicy,
+    ExpirySelectionPolicy,
+    PostStoreAction,
+    ReadClient,
+    RegisterBlobsResult,
+    SuiClientResult,
+};
+use crate::types::{Blob, StakedWal, StorageResource};
+
+/// Trait to send the transactions needed to store and manage blobs.
+///
+/// This is implemented by [`SuiContractClient`][super::SuiContractClient] and, for tests, by the
+/// simulated contract client, so that the store flow of the client can run against either.
+pub trait WriteClient: ReadClient {
+    /// Returns the address that signs the transactions of the client.
+    fn address(&self) -> SuiAddress;
+
+    /// Registers blobs with the specified [`BlobObjectMetadata`] and [`StorageResource`]s,
+    /// and returns the created blob objects and whether they were paid through credits.
+    fn register_blobs(
+        &self,
+        blob_metadata_and_storage: Vec<(BlobObjectMetadata, StorageResource)>,
+        persistence: BlobPersistence,
+        credits_policy: CreditsPolicy,
+    ) -> impl Future<Output = SuiClientResult<RegisterBlobsResult>> + Send;
+
+    /// Purchases blob storage for the next `epochs_ahead` Walrus epochs and uses the resulting
+    /// storage resources to register blobs with the provided metadata.
+    fn reserve_and_register_blobs(
+        &self,
+        epochs_ahead: EpochCount,
+        blob_metadata_list: Vec<BlobObjectMetadata>,
+        persistence: BlobPersistence,
+        credits_policy: CreditsPolicy,
+    ) -> impl Future<Output = SuiClientResult<RegisterBlobsResult>> + Send;
+
+    /// Certifies and extends the specified blobs on Walrus in a single transaction, and applies
+    /// the `post_store` action to the certified blobs.
+    fn certify_and_extend_blobs(
+        &self,
+        blobs_with_certificates: &[CertifyAndExtendBlobParams<'_>],
+        post_store: PostStoreAction,
+        credits_policy: CreditsPolicy,
+    ) -> impl Future<Output = SuiClientResult<Vec<CertifyAndExtendBlobResult>>> + Send;
+
+    /// Returns the buyer subsidy rate, in basis points, of the credits object used under the
+    /// given policy, or `None` if no credits are used.
+    fn credits_buyer_subsidy_rate(
+        &self,
+        credits_policy: CreditsPolicy,
+    ) -> impl Future<Output = SuiClientResult<Option<u16>>> + Send;
+
+    /// Returns the list of [`Blob`] objects owned by `owner`, or by the client if `owner` is
+    /// `None`, filtered by the selection policy.
+    fn owned_blobs(
+        &self,
+        owner: Option<SuiAddress>,
+        selection_policy: ExpirySelectionPolicy,
+    ) -> impl Future<Output = SuiClientResult<Vec<Blob>>> + Send;
+
+    /// Returns the list of [`StorageResource`] objects owned by the client, filtered by the
+    /// selection policy.
+    fn owned_storage(
+        &self,
+        selection_policy: ExpirySelectionPolicy,
+    ) -> impl Future<Output = SuiClientResult<Vec<StorageResource>>> + Send;
+
+    /// Deletes the specified blob from the wallet's storage.
+    fn delete_blob(
+        &self,
+        blob_object_id: ObjectID,
+    ) -> impl Future<Output = SuiClientResult<()>> + Send;
+
+    /// Transfers the specified blob objects to `recipient`.
+    fn transfer_blobs(
+        &self,
+        blob_object_ids: &[ObjectID],
+        recipient: SuiAddress,
+    ) -> impl Future<Output = SuiClientResult<()>> + Send;
+
+    /// Stakes the given amounts of WAL with the respective storage node pools.
+    fn stake_with_pools(
+        &self,
+        node_ids_with_amounts: &[(ObjectID, u64)],
+    ) -> impl Future<Output = SuiClientResult<Vec<StakedWal>>> + Send;
+
+    /// Transfers `amount` MIST to `recipient` in a transaction whose first input is the
+    /// BCS-encoded `auth_package`, and returns the digest of the transaction.
+    ///
+    /// This is used to pay the tip of an upload relay. If no `gas_budget` is provided, the budget
+    /// is estimated.
+    fn pay_tip<A: Serialize + Sync>(
+        &self,
+        recipient: SuiAddress,
+        auth_package: &A,
+        amount: u64,
+        gas_budget: Option<u64>,
+    ) -> impl Future<Output = SuiClientResult<TransactionDigest>> + Send;
+}
@@ -3,6 +3,7 @@
 
 //! Test utilities for `walrus-sui`.
 
+pub mod simulated_chain;
 pub mod system_setup;
 
 #[cfg(not(msim))]
@@ -61,6 +62,7 @@ use crate::{
         BlobDeleted,
         BlobRegistered,
         Committee,
+        DenyListBlobDeleted,
         InvalidBlobId,
         NetworkAddress,
         StorageNode,
@@ -638,6 +640,16 @@ impl EventForTesting for InvalidBlobId {
     }
 }
 
+impl EventForTesting for DenyListBlobDeleted {
+    fn for_testing(blob_id: BlobId) -> Self {
+        Self {
+            epoch: 1,
+            blob_id,
+            event_id: event_id_for_testing(),
+        }
+    }
+}
+
 /// Creates a new StorageNode object representing on chain storage node for testing.
 pub fn new_move_storage_node_for_testing() -> StorageNode {
     StorageNode {
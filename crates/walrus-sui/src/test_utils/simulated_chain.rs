@@ -0,0 +1,2275 @@
+// Copyright (c) Walrus Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! An in-memory simulation of the Walrus contracts on Sui.
+//!
+//! The [`SimulatedChain`] keeps the on-chain state of Walrus (committees, epochs, storage
+//! resources, blobs, attributes, stake, and events) in memory and applies the same checks as the
+//! Move contracts. [`SimulatedContractClient`]s implement [`ReadClient`] and mirror the write
+//! methods of [`SuiContractClient`][crate::client::SuiContractClient], which allows running
+//! clients and storage nodes end to end without a Sui network.
+//!
+//! All object IDs, addresses, and transaction digests are derived from the seed in the
+//! [`SimulatedChainConfig`], so that a sequence of operations always results in the same state.
+//! Transactions are atomic: if any check fails, none of the changes of the transaction are
+//! applied. Epochs only advance when requested, either through the epoch-change calls of the
+//! clients or with [`SimulatedChain::advance_epoch`]. Gas is not charged.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    num::NonZeroU16,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Utc};
+use fastcrypto::{
+    hash::{Blake2b256, HashFunction},
+    traits::{AggregateAuthenticator as _, VerifyingKey as _},
+};
+use serde::{Serialize, de::DeserializeOwned};
+use sui_types::{
+    base_types::{ObjectID, SuiAddress},
+    digests::TransactionDigest,
+    event::EventID,
+};
+use tokio::sync::mpsc;
+use tokio_stream::{Stream, wrappers::UnboundedReceiverStream};
+use walrus_core::{
+    BlobId,
+    Epoch,
+    EpochCount,
+    ShardIndex,
+    encoding::encoded_blob_length_for_n_shards,
+    messages::{
+        Confirmation,
+        ConfirmationCertificate,
+        InvalidBlobCertificate,
+        InvalidBlobIdMsg,
+        ProofOfPossession,
+        ProofOfPossessionMsg,
+        ProtocolMessageCertificate,
+    },
+};
+
+use crate::{
+    client::{
+        BlobObjectMetadata,
+        BlobPersistence,
+        CertifyAndExtendBlobParams,
+        CertifyAndExtendBlobResult,
+        CoinType,
+        CommitteesAndState,
+        CreditsPolicy,
+        ExpirySelectionPolicy,
+        FixedSystemParameters,
+        GetSharedBlobResult,
+        MIN_STAKING_THRESHOLD,
+        PostStoreAction,
+        PostStoreActionResult,
+        ReadClient,
+        RegisterBlobsResult,
+        SuiClientError,
+        SuiClientResult,
+        WriteClient,
+    },
+    test_utils::system_setup::DEFAULT_MAX_EPOCHS_AHEAD,
+    types::{
+        BlobCertified,
+        BlobDeleted,
+        BlobEvent,
+        BlobRegistered,
+        Committee,
+        ContractEvent,
+        EpochChangeDone,
+        EpochChangeEvent,
+        EpochChangeStart,
+        EpochParametersSelected,
+        InvalidBlobId,
+        NodeRegistrationParams,
+        StakedWal,
+        StorageNode,
+        StorageNodeCap,
+        StorageResource,
+        move_structs::{
+            Blob,
+            BlobAttribute,
+            BlobWithAttribute,
+            EpochState,
+            EventBlob,
+            EventBlobAttestation,
+            StakedWalState,
+        },
+    },
+    utils::{price_for_encoded_length, storage_units_from_size, write_price_for_encoded_length},
+};
+
+/// Domain separators for the identifiers derived from the seed.
+const OBJECT_ID_DOMAIN: u8 = 0;
+const ADDRESS_DOMAIN: u8 = 1;
+const TRANSACTION_DIGEST_DOMAIN: u8 = 2;
+
+/// The number of epochs for which certified event blobs are stored.
+const EVENT_BLOB_EPOCHS_AHEAD: EpochCount = 2;
+
+/// Configuration of a [`SimulatedChain`].
+#[derive(Debug, Clone)]
+pub struct SimulatedChainConfig {
+    /// The number of shards in the system.
+    pub n_shards: NonZeroU16,
+    /// The maximum number of epochs ahead for which storage can be reserved.
+    pub max_epochs_ahead: EpochCount,
+    /// The duration of an epoch for epochs 1 onwards.
+    pub epoch_duration: Duration,
+    /// The time at which epoch 1 starts.
+    pub genesis_time: DateTime<Utc>,
+    /// The price in FROST for one unit of storage per epoch.
+    pub storage_price_per_unit_size: u64,
+    /// The price in FROST to write one unit of storage.
+    pub write_price_per_unit_size: u64,
+    /// The seed from which all object IDs, addresses, and transaction digests are derived.
+    pub seed: u64,
+}
+
+impl Default for SimulatedChainConfig {
+    fn default() -> Self {
+        Self {
+            n_shards: NonZeroU16::new(10).expect("10 is non-zero"),
+            max_epochs_ahead: DEFAULT_MAX_EPOCHS_AHEAD,
+            epoch_duration: Duration::from_secs(3600),
+            genesis_time: Utc::now(),
+            storage_price_per_unit_size: 100,
+            write_price_per_unit_size: 10,
+            seed: 0,
+        }
+    }
+}
+
+/// The owner of a blob object.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BlobOwner {
+    /// The blob is owned by an address.
+    Address(SuiAddress),
+    /// The blob is wrapped in the shared blob object with the given ID.
+    Shared(ObjectID),
+}
+
+/// A blob object together with its owner and attribute.
+#[derive(Debug, Clone)]
+struct BlobEntry {
+    owner: BlobOwner,
+    blob: Blob,
+    attribute: Option<BlobAttribute>,
+}
+
+impl BlobEntry {
+    fn with_attribute(&self) -> BlobWithAttribute {
+        BlobWithAttribute {
+            blob: self.blob.clone(),
+            attribute: self.attribute.clone(),
+        }
+    }
+}
+
+/// A registered storage node and the owner of its capability object.
+#[derive(Debug, Clone)]
+struct NodeEntry {
+    info: StorageNode,
+    cap: StorageNodeCap,
+    cap_owner: SuiAddress,
+}
+
+/// The WAL and SUI balances of an address.
+#[derive(Debug, Clone, Copy, Default)]
+struct Balances {
+    wal: u64,
+    sui: u64,
+}
+
+/// The complete state of the simulated chain.
+#[derive(Debug, Clone)]
+struct ChainState {
+    config: SimulatedChainConfig,
+    /// Counter from which object IDs, addresses, and digests are derived.
+    nonce: u64,
+    /// Incremented on every successful transaction.
+    version: u64,
+    current_transaction: TransactionDigest,
+    next_event_seq: u64,
+    epoch: Epoch,
+    epoch_start: DateTime<Utc>,
+    epoch_state: EpochState,
+    current_committee: Committee,
+    previous_committee: Committee,
+    next_committee: Option<Committee>,
+    nodes: BTreeMap<ObjectID, NodeEntry>,
+    staked_wal: BTreeMap<ObjectID, (SuiAddress, StakedWal)>,
+    balances: HashMap<SuiAddress, Balances>,
+    storage: BTreeMap<ObjectID, (SuiAddress, StorageResource)>,
+    blobs: BTreeMap<ObjectID, BlobEntry>,
+    shared_blobs: BTreeMap<ObjectID, ObjectID>,
+    event_blob_weights: BTreeMap<(BlobId, u64), usize>,
+    last_certified_event_blob: Option<EventBlob>,
+    events: Vec<ContractEvent>,
+}
+
+fn abort(message: impl Into<String>) -> SuiClientError {
+    SuiClientError::Internal(anyhow!(
+        "simulated contract call failed: {}",
+        message.into()
+    ))
+}
+
+impl ChainState {
+    fn new(config: SimulatedChainConfig) -> Self {
+        let genesis_committee = Committee::new(vec![], 0, config.n_shards)
+            .expect("an empty committee is valid in epoch 0");
+        Self {
+            nonce: 0,
+            version: 1,
+            current_transaction: TransactionDigest::new([0; 32]),
+            next_event_seq: 0,
+            epoch: 0,
+            epoch_start: config.genesis_time,
+            epoch_state: EpochState::EpochChangeDone(config.genesis_time),
+            current_committee: genesis_committee.clone(),
+            previous_committee: genesis_committee,
+            next_committee: None,
+            nodes: BTreeMap::new(),
+            staked_wal: BTreeMap::new(),
+            balances: HashMap::new(),
+            storage: BTreeMap::new(),
+            blobs: BTreeMap::new(),
+            shared_blobs: BTreeMap::new(),
+            event_blob_weights: BTreeMap::new(),
+            last_certified_event_blob: None,
+            events: vec![],
+            config,
+        }
+    }
+
+    fn derive_bytes(&mut self, domain: u8) -> [u8; 32] {
+        self.nonce += 1;
+        let mut hasher = Blake2b256::default();
+        hasher.update(self.config.seed.to_le_bytes());
+        hasher.update([domain]);
+        hasher.update(self.nonce.to_le_bytes());
+        hasher.finalize().digest
+    }
+
+    fn new_object_id(&mut self) -> ObjectID {
+        ObjectID::new(self.derive_bytes(OBJECT_ID_DOMAIN))
+    }
+
+    fn new_address(&mut self) -> SuiAddress {
+        SuiAddress::from(ObjectID::new(self.derive_bytes(ADDRESS_DOMAIN)))
+    }
+
+    fn begin_transaction(&mut self) {
+        self.current_transaction =
+            TransactionDigest::new(self.derive_bytes(TRANSACTION_DIGEST_DOMAIN));
+        self.next_event_seq = 0;
+        self.version += 1;
+    }
+
+    fn next_event_id(&mut self) -> EventID {
+        let event_id = EventID {
+            tx_digest: self.current_transaction,
+            event_seq: self.next_event_seq,
+        };
+        self.next_event_seq += 1;
+        event_id
+    }
+
+    fn emit_blob_event(&mut self, event: BlobEvent) {
+        self.events.push(ContractEvent::BlobEvent(event));
+    }
+
+    fn emit_epoch_change_event(&mut self, event: EpochChangeEvent) {
+        self.events.push(ContractEvent::EpochChangeEvent(event));
+    }
+
+    fn balances_mut(&mut self, address: SuiAddress) -> &mut Balances {
+        self.balances.entry(address).or_default()
+    }
+
+    fn balance(&self, address: SuiAddress, coin_type: CoinType) -> u64 {
+        let balances = self.balances.get(&address).copied().unwrap_or_default();
+        match coin_type {
+            CoinType::Wal => balances.wal,
+            CoinType::Sui => balances.sui,
+        }
+    }
+
+    fn pay_wal(&mut self, payer: SuiAddress, amount: u64) -> SuiClientResult<()> {
+        let balances = self.balances_mut(payer);
+        balances.wal = balances
+            .wal
+            .checked_sub(amount)
+            .ok_or(SuiClientError::NoCompatibleWalCoins)?;
+        Ok(())
+    }
+
+    fn transfer(
+        &mut self,
+        sender: SuiAddress,
+        recipient: SuiAddress,
+        coin_type: CoinType,
+        amount: u64,
+    ) -> SuiClientResult<()> {
+        let sender_balances = self.balances_mut(sender);
+        let balance = match coin_type {
+            CoinType::Wal => &mut sender_balances.wal,
+            CoinType::Sui => &mut sender_balances.sui,
+        };
+        *balance = balance.checked_sub(amount).ok_or(match coin_type {
+            CoinType::Wal => SuiClientError::NoCompatibleWalCoins,
+            CoinType::Sui => SuiClientError::NoCompatibleGasCoins(Some(amount.into())),
+        })?;
+        let recipient_balances = self.balances_mut(recipient);
+        match coin_type {
+            CoinType::Wal => recipient_balances.wal += amount,
+            CoinType::Sui => recipient_balances.sui += amount,
+        }
+        Ok(())
+    }
+
+    fn fixed_system_parameters(&self) -> FixedSystemParameters {
+        FixedSystemParameters {
+            n_shards: self.config.n_shards,
+            max_epochs_ahead: self.config.max_epochs_ahead,
+            epoch_duration: self.config.epoch_duration,
+            epoch_zero_end: self.config.genesis_time,
+        }
+    }
+
+    /// Returns the epoch from which stake added or withdrawn now takes effect.
+    fn stake_activation_epoch(&self) -> Epoch {
+        if matches!(self.epoch_state, EpochState::NextParamsSelected(_)) {
+            self.epoch + 2
+        } else {
+            self.epoch + 1
+        }
+    }
+
+    /// Returns the stake per node that is active in the given epoch.
+    fn stake_in_epoch(&self, epoch: Epoch) -> BTreeMap<ObjectID, u64> {
+        let mut stake: BTreeMap<_, _> = self.nodes.keys().map(|node_id| (*node_id, 0)).collect();
+        for (_, staked_wal) in self.staked_wal.values() {
+            let is_active = match staked_wal.state {
+                StakedWalState::Staked => staked_wal.activation_epoch <= epoch,
+                StakedWalState::Withdrawing(withdraw_epoch, _) => {
+                    staked_wal.activation_epoch <= epoch && epoch < withdraw_epoch
+                }
+            };
+            if is_active {
+                *stake.entry(staked_wal.node_id).or_default() += staked_wal.principal;
+            }
+        }
+        stake
+    }
+
+    /// Returns the number of shards of the node in the current committee.
+    fn weight_in_current_committee(&self, node_id: &ObjectID) -> usize {
+        self.current_committee
+            .find(node_id)
+            .map_or(0, |node| node.shard_ids.len())
+    }
+
+    fn node_cap_owned_by(
+        &self,
+        sender: SuiAddress,
+        node_capability_object_id: ObjectID,
+    ) -> SuiClientResult<ObjectID> {
+        self.nodes
+            .values()
+            .find(|node| node.cap.id == node_capability_object_id && node.cap_owner == sender)
+            .map(|node| node.info.node_id)
+            .ok_or_else(|| {
+                abort(format!(
+                    "the storage node capability {node_capability_object_id} is not owned by \
+                    {sender}"
+                ))
+            })
+    }
+
+    /// Computes the committee for the next epoch, assigning the shards proportionally to the
+    /// stake of the nodes.
+    ///
+    /// Shards that remain with the same node are kept to minimize shard movement; the remaining
+    /// shards are assigned to the nodes in the order of their IDs.
+    fn select_next_committee(&self) -> SuiClientResult<Committee> {
+        let next_epoch = self.epoch + 1;
+        let stake: Vec<_> = self
+            .stake_in_epoch(next_epoch)
+            .into_iter()
+            .filter(|(_, stake)| *stake > 0)
+            .collect();
+        let total_stake: u128 = stake.iter().map(|(_, stake)| u128::from(*stake)).sum();
+        if total_stake == 0 {
+            return Err(abort("no stake is assigned to any storage node"));
+        }
+
+        let n_shards = u128::from(self.config.n_shards.get());
+        let mut targets: Vec<(ObjectID, usize, u128)> = stake
+            .iter()
+            .map(|(node_id, stake)| {
+                let quota = u128::from(*stake) * n_shards;
+                let target = usize::try_from(quota / total_stake).expect("bounded by n_shards");
+                (*node_id, target, quota % total_stake)
+            })
+            .collect();
+        let assigned: usize = targets.iter().map(|(_, target, _)| target).sum();
+        let mut by_remainder: Vec<_> = (0..targets.len()).collect();
+        by_remainder.sort_by(|&a, &b| targets[b].2.cmp(&targets[a].2).then(a.cmp(&b)));
+        for index in by_remainder
+            .into_iter()
+            .take(usize::from(self.config.n_shards.get()) - assigned)
+        {
+            targets[index].1 += 1;
+        }
+
+        let mut assignment: BTreeMap<ObjectID, Vec<ShardIndex>> = BTreeMap::new();
+        let mut unassigned = vec![];
+        let previous_owners: HashMap<ShardIndex, ObjectID> = self
+            .current_committee
+            .members()
+            .iter()
+            .flat_map(|node| node.shard_ids.iter().map(|shard| (*shard, node.node_id)))
+            .collect();
+        let target_of: HashMap<_, _> = targets
+            .iter()
+            .map(|(node_id, target, _)| (*node_id, *target))
+            .collect();
+        for shard in ShardIndex::range(..self.config.n_shards.get()) {
+            let kept_by = previous_owners.get(&shard).filter(|node_id| {
+                let kept = assignment.get(*node_id).map_or(0, Vec::len);
+                kept < target_of.get(*node_id).copied().unwrap_or(0)
+            });
+            match kept_by {
+                Some(node_id) => assignment.entry(*node_id).or_default().push(shard),
+                None => unassigned.push(shard),
+            }
+        }
+        let mut unassigned = unassigned.into_iter();
+        for (node_id, target, _) in &targets {
+            let shards = assignment.entry(*node_id).or_default();
+            let missing = target - shards.len();
+            shards.extend(unassigned.by_ref().take(missing));
+            shards.sort();
+        }
+
+        let members = assignment
+            .into_iter()
+            .filter(|(_, shards)| !shards.is_empty())
+            .map(|(node_id, shard_ids)| StorageNode {
+                shard_ids,
+                ..self.nodes[&node_id].info.clone()
+            })
+            .collect();
+        Committee::new(members, next_epoch, self.config.n_shards)
+            .map_err(|error| SuiClientError::Internal(error.into()))
+    }
+
+    /// Checks that the certificate is signed by a quorum of the current committee and returns
+    /// the certified message.
+    fn verify_certificate<T: DeserializeOwned>(
+        &self,
+        certificate: &ProtocolMessageCertificate<T>,
+    ) -> SuiClientResult<T> {
+        let committee = &self.current_committee;
+        let mut weight = 0;
+        let mut public_keys = Vec::with_capacity(certificate.signers.len());
+        for (position, signer) in certificate.signers.iter().enumerate() {
+            if certificate.signers[..position].contains(signer) {
+                return Err(abort("the certificate contains duplicate signers"));
+            }
+            let member = committee
+                .members()
+                .get(usize::from(*signer))
+                .ok_or_else(|| abort(format!("the signer index {signer} is invalid")))?;
+            weight += member.shard_ids.len();
+            public_keys.push(member.public_key.clone());
+        }
+        if !committee.is_quorum(weight) {
+            return Err(abort("the certificate is not signed by a quorum of shards"));
+        }
+        certificate
+            .signature
+            .verify(&public_keys, &certificate.serialized_message)
+            .map_err(|_| abort("the certificate signature is invalid"))?;
+        bcs::from_bytes(&certificate.serialized_message)
+            .map_err(|error| abort(format!("the certified message is malformed: {error}")))
+    }
+
+    fn blob_owned_by(&self, sender: SuiAddress, blob_object_id: ObjectID) -> SuiClientResult<()> {
+        match self.blobs.get(&blob_object_id) {
+            Some(entry) if entry.owner == BlobOwner::Address(sender) => Ok(()),
+            Some(_) => Err(abort(format!(
+                "the blob object {blob_object_id} is not owned by {sender}"
+            ))),
+            None => Err(abort(format!(
+                "the blob object {blob_object_id} does not exist"
+            ))),
+        }
+    }
+
+    fn blob_mut(&mut self, blob_object_id: ObjectID) -> &mut BlobEntry {
+        self.blobs
+            .get_mut(&blob_object_id)
+            .expect("the blob existence is checked before")
+    }
+
+    fn reserve_space(
+        &mut self,
+        sender: SuiAddress,
+        encoded_size: u64,
+        epochs_ahead: EpochCount,
+    ) -> SuiClientResult<StorageResource> {
+        if epochs_ahead == 0 || epochs_ahead > self.config.max_epochs_ahead {
+            return Err(abort(format!(
+                "storage can only be reserved for 1 to {} epochs ahead",
+                self.config.max_epochs_ahead
+            )));
+        }
+        let price = price_for_encoded_length(
+            encoded_size,
+            self.config.storage_price_per_unit_size,
+            epochs_ahead,
+        );
+        self.pay_wal(sender, price)?;
+        let storage = StorageResource {
+            id: self.new_object_id(),
+            start_epoch: self.epoch,
+            end_epoch: self.epoch + epochs_ahead,
+            storage_size: storage_units_from_size(encoded_size) * crate::utils::BYTES_PER_UNIT_SIZE,
+        };
+        self.storage.insert(storage.id, (sender, storage.clone()));
+        Ok(storage)
+    }
+
+    fn register_blob(
+        &mut self,
+        sender: SuiAddress,
+        metadata: BlobObjectMetadata,
+        storage: StorageResource,
+        persistence: BlobPersistence,
+    ) -> SuiClientResult<Blob> {
+        let Some((owner, storage)) = self.storage.remove(&storage.id) else {
+            return Err(abort(format!(
+                "the storage resource {} does not exist",
+                storage.id
+            )));
+        };
+        if owner != sender {
+            return Err(abort(format!(
+                "the storage resource {} is not owned by {sender}",
+                storage.id
+            )));
+        }
+        if !(storage.start_epoch..storage.end_epoch).contains(&self.epoch) {
+            return Err(abort(
+                "the storage resource is not valid in the current epoch",
+            ));
+        }
+        let expected_encoded_size = encoded_blob_length_for_n_shards(
+            self.config.n_shards,
+            metadata.unencoded_size,
+            metadata.encoding_type,
+        )
+        .ok_or_else(|| abort("the blob is too large to be encoded"))?;
+        if metadata.encoded_size != expected_encoded_size {
+            return Err(abort("the encoded size does not match the unencoded size"));
+        }
+        if storage.storage_size < metadata.encoded_size {
+            return Err(abort("the storage resource is too small for the blob"));
+        }
+        self.pay_wal(
+            sender,
+            write_price_for_encoded_length(
+                metadata.encoded_size,
+                self.config.write_price_per_unit_size,
+            ),
+        )?;
+
+        let blob = Blob {
+            id: self.new_object_id(),
+            registered_epoch: self.epoch,
+            blob_id: metadata.blob_id,
+            size: metadata.unencoded_size,
+            encoding_type: metadata.encoding_type,
+            certified_epoch: None,
+            storage,
+            deletable: persistence == BlobPersistence::Deletable,
+        };
+        let event_id = self.next_event_id();
+        self.emit_blob_event(
+            BlobRegistered {
+                epoch: self.epoch,
+                blob_id: blob.blob_id,
+                size: blob.size,
+                encoding_type: blob.encoding_type,
+                end_epoch: blob.storage.end_epoch,
+                deletable: blob.deletable,
+                object_id: blob.id,
+                event_id,
+            }
+            .into(),
+        );
+        self.blobs.insert(
+            blob.id,
+            BlobEntry {
+                owner: BlobOwner::Address(sender),
+                blob: blob.clone(),
+                attribute: None,
+            },
+        );
+        Ok(blob)
+    }
+
+    fn certify_blob(
+        &mut self,
+        sender: SuiAddress,
+        blob_object_id: ObjectID,
+        certificate: &ConfirmationCertificate,
+    ) -> SuiClientResult<()> {
+        self.blob_owned_by(sender, blob_object_id)?;
+        let blob = self.blobs[&blob_object_id].blob.clone();
+        if blob.certified_epoch.is_some() {
+            return Err(abort(format!(
+                "the blob {} is already certified",
+                blob.blob_id
+            )));
+        }
+        if blob.storage.end_epoch <= self.epoch {
+            return Err(abort(format!("the blob {} is expired", blob.blob_id)));
+        }
+        let confirmation: Confirmation = self.verify_certificate(certificate)?;
+        let message = confirmation.as_ref();
+        if message.epoch() != self.epoch {
+            return Err(abort("the certificate is not for the current epoch"));
+        }
+        if message.contents().blob_id != blob.blob_id
+            || message.contents().blob_type != blob.blob_persistence_type()
+        {
+            return Err(abort("the certificate does not match the blob"));
+        }
+
+        self.blob_mut(blob_object_id).blob.certified_epoch = Some(self.epoch);
+        let event_id = self.next_event_id();
+        self.emit_blob_event(
+            BlobCertified {
+                epoch: self.epoch,
+                blob_id: blob.blob_id,
+                end_epoch: blob.storage.end_epoch,
+                deletable: blob.deletable,
+                object_id: blob.id,
+                is_extension: false,
+                event_id,
+            }
+            .into(),
+        );
+        Ok(())
+    }
+
+    fn extend_blob(
+        &mut self,
+        sender: SuiAddress,
+        blob_object_id: ObjectID,
+        epochs_extended: EpochCount,
+    ) -> SuiClientResult<()> {
+        self.blob_owned_by(sender, blob_object_id)?;
+        let blob = self.blobs[&blob_object_id].blob.clone();
+        let Some(certified_epoch) = blob.certified_epoch else {
+            return Err(abort(format!("the blob {} is not certified", blob.blob_id)));
+        };
+        if blob.storage.end_epoch <= self.epoch {
+            return Err(abort(format!("the blob {} is expired", blob.blob_id)));
+        }
+        let end_epoch = blob.storage.end_epoch + epochs_extended;
+        if epochs_extended == 0 || end_epoch - self.epoch > self.config.max_epochs_ahead {
+            return Err(abort(format!(
+                "blobs can only be extended to at most {} epochs ahead",
+                self.config.max_epochs_ahead
+            )));
+        }
+        self.pay_wal(
+            sender,
+            price_for_encoded_length(
+                blob.storage.storage_size,
+                self.config.storage_price_per_unit_size,
+                epochs_extended,
+            ),
+        )?;
+
+        self.blob_mut(blob_object_id).blob.storage.end_epoch = end_epoch;
+        let event_id = self.next_event_id();
+        self.emit_blob_event(
+            BlobCertified {
+                epoch: certified_epoch,
+                blob_id: blob.blob_id,
+                end_epoch,
+                deletable: blob.deletable,
+                object_id: blob.id,
+                is_extension: true,
+                event_id,
+            }
+            .into(),
+        );
+        Ok(())
+    }
+
+    /// Applies the post-store action to the blob and returns the ID of the shared blob object,
+    /// if the blob was shared.
+    fn apply_post_store_action(
+        &mut self,
+        blob_object_id: ObjectID,
+        post_store: &PostStoreAction,
+    ) -> Option<ObjectID> {
+        match post_store {
+            PostStoreAction::Burn => {
+                self.blobs.remove(&blob_object_id);
+                None
+            }
+            PostStoreAction::TransferTo(recipient) => {
+                self.blob_mut(blob_object_id).owner = BlobOwner::Address(*recipient);
+                None
+            }
+            PostStoreAction::Keep => None,
+            PostStoreAction::Share => {
+                let shared_blob_id = self.new_object_id();
+                self.blob_mut(blob_object_id).owner = BlobOwner::Shared(shared_blob_id);
+                self.shared_blobs.insert(shared_blob_id, blob_object_id);
+                Some(shared_blob_id)
+            }
+        }
+    }
+
+    fn attribute_mut(
+        &mut self,
+        sender: SuiAddress,
+        blob_object_id: ObjectID,
+    ) -> SuiClientResult<&mut Option<BlobAttribute>> {
+        self.blob_owned_by(sender, blob_object_id)?;
+        Ok(&mut self.blob_mut(blob_object_id).attribute)
+    }
+
+    fn end_voting(&mut self) -> SuiClientResult<()> {
+        if !matches!(self.epoch_state, EpochState::EpochChangeDone(_)) {
+            return Err(abort(
+                "voting can only end after the previous epoch change is done",
+            ));
+        }
+        self.next_committee = Some(self.select_next_committee()?);
+        self.epoch_state = EpochState::NextParamsSelected(self.epoch_start);
+        let event_id = self.next_event_id();
+        self.emit_epoch_change_event(EpochChangeEvent::EpochParametersSelected(
+            EpochParametersSelected {
+                next_epoch: self.epoch + 1,
+                event_id,
+            },
+        ));
+        Ok(())
+    }
+
+    fn initiate_epoch_change(&mut self) -> SuiClientResult<()> {
+        if !matches!(self.epoch_state, EpochState::NextParamsSelected(_)) {
+            return Err(abort(
+                "the epoch change can only start after the next committee is selected",
+            ));
+        }
+        let next_committee = self
+            .next_committee
+            .take()
+            .expect("the next committee is set when the parameters are selected");
+        self.previous_committee = std::mem::replace(&mut self.current_committee, next_committee);
+        self.epoch += 1;
+        if self.epoch > 1 {
+            self.epoch_start += self.config.epoch_duration;
+        }
+        self.event_blob_weights.clear();
+
+        let event_id = self.next_event_id();
+        self.emit_epoch_change_event(EpochChangeEvent::EpochChangeStart(EpochChangeStart {
+            epoch: self.epoch,
+            event_id,
+        }));
+        if self.previous_committee.n_members() == 0 {
+            // There are no shards to transfer from the genesis committee.
+            self.complete_epoch_change();
+        } else {
+            self.epoch_state = EpochState::EpochChangeSync(0);
+        }
+        Ok(())
+    }
+
+    fn complete_epoch_change(&mut self) {
+        self.epoch_state = EpochState::EpochChangeDone(self.epoch_start);
+        let event_id = self.next_event_id();
+        self.emit_epoch_change_event(EpochChangeEvent::EpochChangeDone(EpochChangeDone {
+            epoch: self.epoch,
+            event_id,
+        }));
+    }
+
+    fn epoch_sync_done(
+        &mut self,
+        sender: SuiAddress,
+        epoch: Epoch,
+        node_capability_object_id: ObjectID,
+    ) -> SuiClientResult<()> {
+        let node_id = self.node_cap_owned_by(sender, node_capability_object_id)?;
+        let cap = &mut self
+            .nodes
+            .get_mut(&node_id)
+            .expect("the node exists for the capability")
+            .cap;
+        if cap.last_epoch_sync_done >= epoch {
+            return Err(SuiClientError::LatestAttestedIsMoreRecent);
+        }
+        if epoch != self.epoch {
+            return Err(abort(format!(
+                "cannot attest to the sync of epoch {epoch} in epoch {}",
+                self.epoch
+            )));
+        }
+        cap.last_epoch_sync_done = epoch;
+
+        if let EpochState::EpochChangeSync(weight) = self.epoch_state {
+            let weight = usize::from(weight) + self.weight_in_current_committee(&node_id);
+            if self.current_committee.is_quorum(weight) {
+                self.complete_epoch_change();
+            } else {
+                self.epoch_state = EpochState::EpochChangeSync(
+                    u16::try_from(weight).expect("bounded by the number of shards"),
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn certify_event_blob(
+        &mut self,
+        sender: SuiAddress,
+        blob_metadata: BlobObjectMetadata,
+        ending_checkpoint_seq_num: u64,
+        epoch: Epoch,
+        node_capability_object_id: ObjectID,
+    ) -> SuiClientResult<()> {
+        let node_id = self.node_cap_owned_by(sender, node_capability_object_id)?;
+        if epoch != self.epoch {
+            return Err(abort(
+                "event blobs can only be certified in the current epoch",
+            ));
+        }
+        let weight = self.weight_in_current_committee(&node_id);
+        if weight == 0 {
+            return Err(abort("the storage node is not in the current committee"));
+        }
+        let cap = &mut self
+            .nodes
+            .get_mut(&node_id)
+            .expect("the node exists for the capability")
+            .cap;
+        if cap
+            .last_event_blob_attestation
+            .as_ref()
+            .is_some_and(|attestation| {
+                attestation.epoch == epoch
+                    && attestation.checkpoint_sequence_num >= ending_checkpoint_seq_num
+            })
+        {
+            return Err(abort(
+                "the storage node already attested to this event blob",
+            ));
+        }
+        cap.last_event_blob_attestation = Some(EventBlobAttestation {
+            checkpoint_sequence_num: ending_checkpoint_seq_num,
+            epoch,
+        });
+
+        let total_weight = self
+            .event_blob_weights
+            .entry((blob_metadata.blob_id, ending_checkpoint_seq_num))
+            .or_default();
+        *total_weight += weight;
+        if !self.current_committee.is_quorum(*total_weight) {
+            return Ok(());
+        }
+
+        self.event_blob_weights.clear();
+        self.last_certified_event_blob = Some(EventBlob {
+            blob_id: blob_metadata.blob_id,
+            ending_checkpoint_sequence_number: ending_checkpoint_seq_num,
+        });
+        let object_id = self.new_object_id();
+        let end_epoch = self.epoch + EVENT_BLOB_EPOCHS_AHEAD;
+        let event_id = self.next_event_id();
+        self.emit_blob_event(
+            BlobRegistered {
+                epoch: self.epoch,
+                blob_id: blob_metadata.blob_id,
+                size: blob_metadata.unencoded_size,
+                encoding_type: blob_metadata.encoding_type,
+                end_epoch,
+                deletable: false,
+                object_id,
+                event_id,
+            }
+            .into(),
+        );
+        let event_id = self.next_event_id();
+        self.emit_blob_event(
+            BlobCertified {
+                epoch: self.epoch,
+                blob_id: blob_metadata.blob_id,
+                end_epoch,
+                deletable: false,
+                object_id,
+                is_extension: false,
+                event_id,
+            }
+            .into(),
+        );
+        Ok(())
+    }
+}
+
+/// Shared state of the chain and the subscribers to its events.
+#[derive(Debug)]
+struct ChainInner {
+    state: ChainState,
+    subscribers: Vec<mpsc::UnboundedSender<ContractEvent>>,
+}
+
+/// An in-memory simulation of the Walrus contracts.
+///
+/// The chain can be cloned cheaply; all clones share the same state.
+#[derive(Debug, Clone)]
+pub struct SimulatedChain {
+    inner: Arc<Mutex<ChainInner>>,
+}
+
+impl Default for SimulatedChain {
+    fn default() -> Self {
+        Self::new(SimulatedChainConfig::default())
+    }
+}
+
+impl SimulatedChain {
+    /// Creates a new chain in the genesis epoch 0 with an empty committee.
+    pub fn new(config: SimulatedChainConfig) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(ChainInner {
+                state: ChainState::new(config),
+                subscribers: vec![],
+            })),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, ChainInner> {
+        self.inner.lock().expect("the mutex should not be poisoned")
+    }
+
+    fn read<T>(&self, f: impl FnOnce(&ChainState) -> T) -> T {
+        f(&self.lock().state)
+    }
+
+    /// Executes `f` as an atomic transaction.
+    ///
+    /// The changes to the state are only applied and the emitted events are only sent to the
+    /// subscribers if `f` succeeds.
+    fn execute<T>(
+        &self,
+        f: impl FnOnce(&mut ChainState) -> SuiClientResult<T>,
+    ) -> SuiClientResult<T> {
+        let mut inner = self.lock();
+        let mut state = inner.state.clone();
+        state.begin_transaction();
+        let n_events_before = state.events.len();
+        let result = f(&mut state)?;
+
+        inner.state = state;
+        let ChainInner { state, subscribers } = &mut *inner;
+        for event in &state.events[n_events_before..] {
+            subscribers.retain(|subscriber| subscriber.send(event.clone()).is_ok());
+        }
+        Ok(result)
+    }
+
+    /// Returns a new client with a fresh address.
+    pub fn new_client(&self) -> SimulatedContractClient {
+        let address = self.lock().state.new_address();
+        self.client_for_address(address)
+    }
+
+    /// Returns a client that sends transactions from the given address.
+    pub fn client_for_address(&self, address: SuiAddress) -> SimulatedContractClient {
+        SimulatedContractClient {
+            chain: self.clone(),
+            address,
+        }
+    }
+
+    /// Mints `amount` coins of the given type to the address.
+    pub fn fund(&self, address: SuiAddress, coin_type: CoinType, amount: u64) {
+        let mut inner = self.lock();
+        let balances = inner.state.balances_mut(address);
+        match coin_type {
+            CoinType::Wal => balances.wal += amount,
+            CoinType::Sui => balances.sui += amount,
+        }
+    }
+
+    /// Ends the voting, starts the epoch change, and completes it on behalf of all storage nodes.
+    ///
+    /// Returns the new epoch.
+    pub fn advance_epoch(&self) -> SuiClientResult<Epoch> {
+        self.execute(|state| {
+            if matches!(state.epoch_state, EpochState::EpochChangeDone(_)) {
+                state.end_voting()?;
+            }
+            state.initiate_epoch_change()?;
+            if state.epoch_state.is_transitioning() {
+                state.complete_epoch_change();
+            }
+            Ok(state.epoch)
+        })
+    }
+
+    /// Returns the current epoch.
+    pub fn current_epoch(&self) -> Epoch {
+        self.read(|state| state.epoch)
+    }
+
+    /// Returns all events emitted so far.
+    pub fn events(&self) -> Vec<ContractEvent> {
+        self.read(|state| state.events.clone())
+    }
+
+    /// Returns a stream of the events emitted after the event with the given ID, followed by all
+    /// events emitted in the future.
+    ///
+    /// If no cursor is provided or the cursor is unknown, the stream starts with the first event.
+    pub fn subscribe(&self, cursor: Option<EventID>) -> UnboundedReceiverStream<ContractEvent> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let mut inner = self.lock();
+        let start = cursor
+            .and_then(|cursor| {
+                inner
+                    .state
+                    .events
+                    .iter()
+                    .position(|event| event.event_id() == cursor)
+            })
+            .map_or(0, |position| position + 1);
+        for event in &inner.state.events[start..] {
+            sender
+                .send(event.clone())
+                .expect("the receiver is not dropped");
+        }
+        inner.subscribers.push(sender);
+        UnboundedReceiverStream::new(receiver)
+    }
+}
+
+/// A client to read from and send transactions to a [`SimulatedChain`].
+///
+/// The write methods mirror those of [`SuiContractClient`][crate::client::SuiContractClient], and
+/// the ones used to store blobs are available through [`WriteClient`]. Credits are not simulated;
+/// the `credits_policy` arguments are ignored.
+#[derive(Debug, Clone)]
+pub struct SimulatedContractClient {
+    chain: SimulatedChain,
+    address: SuiAddress,
+}
+
+impl SimulatedContractClient {
+    /// Returns the chain the client is connected to.
+    pub fn chain(&self) -> &SimulatedChain {
+        &self.chain
+    }
+
+    /// Returns the active address of the client.
+    pub fn address(&self) -> SuiAddress {
+        self.address
+    }
+
+    /// Returns the balance of the client for the given coin type.
+    pub async fn balance(&self, coin_type: CoinType) -> SuiClientResult<u64> {
+        Ok(self
+            .chain
+            .read(|state| state.balance(self.address, coin_type)))
+    }
+
+    /// Sends the `amount` SUI to the provided `address`.
+    pub async fn send_sui(&self, amount: u64, address: SuiAddress) -> SuiClientResult<()> {
+        self.chain
+            .execute(|state| state.transfer(self.address, address, CoinType::Sui, amount))
+    }
+
+    /// Sends the `amount` WAL to the provided `address`.
+    pub async fn send_wal(&self, amount: u64, address: SuiAddress) -> SuiClientResult<()> {
+        self.chain
+            .execute(|state| state.transfer(self.address, address, CoinType::Wal, amount))
+    }
+
+    /// Purchases blob storage for the next `epochs_ahead` Walrus epochs and an encoded
+    /// size of `encoded_size` and returns the created storage resource.
+    pub async fn reserve_space(
+        &self,
+        encoded_size: u64,
+        epochs_ahead: EpochCount,
+        _credits_policy: CreditsPolicy,
+    ) -> SuiClientResult<StorageResource> {
+        self.chain
+            .execute(|state| state.reserve_space(self.address, encoded_size, epochs_ahead))
+    }
+
+    /// Registers blobs with the specified [`BlobObjectMetadata`] and [`StorageResource`]s,
+    /// and returns the created blob objects.
+    pub async fn register_blobs(
+        &self,
+        blob_metadata_and_storage: Vec<(BlobObjectMetadata, StorageResource)>,
+        persistence: BlobPersistence,
+        _credits_policy: CreditsPolicy,
+    ) -> SuiClientResult<Vec<Blob>> {
+        self.chain.execute(|state| {
+            blob_metadata_and_storage
+                .into_iter()
+                .map(|(metadata, storage)| {
+                    state.register_blob(self.address, metadata, storage, persistence)
+                })
+                .collect()
+        })
+    }
+
+    /// Purchases blob storage for the next `epochs_ahead` Walrus epochs and uses the resulting
+    /// storage resource to register a blob with the provided `blob_metadata`.
+    pub async fn reserve_and_register_blobs(
+        &self,
+        epochs_ahead: EpochCount,
+        blob_metadata_list: Vec<BlobObjectMetadata>,
+        persistence: BlobPersistence,
+        _credits_policy: CreditsPolicy,
+    ) -> SuiClientResult<Vec<Blob>> {
+        self.chain.execute(|state| {
+            blob_metadata_list
+                .into_iter()
+                .map(|metadata| {
+                    let storage =
+                        state.reserve_space(self.address, metadata.encoded_size, epochs_ahead)?;
+                    state.register_blob(self.address, metadata, storage, persistence)
+                })
+                .collect()
+        })
+    }
+
+    /// Certifies the specified blobs, given certificates that confirm their storage.
+    ///
+    /// If the post store action is `share`, returns a mapping blob ID -> shared_blob_object_id.
+    pub async fn certify_blobs(
+        &self,
+        blobs_with_certificates: &[(&BlobWithAttribute, ConfirmationCertificate)],
+        post_store: PostStoreAction,
+    ) -> SuiClientResult<HashMap<BlobId, ObjectID>> {
+        self.chain.execute(|state| {
+            let mut shared_blobs = HashMap::new();
+            for (blob, certificate) in blobs_with_certificates {
+                state.certify_blob(self.address, blob.blob.id, certificate)?;
+                if let Some(shared_blob_id) =
+                    state.apply_post_store_action(blob.blob.id, &post_store)
+                {
+                    shared_blobs.insert(blob.blob.blob_id, shared_blob_id);
+                }
+            }
+            Ok(shared_blobs)
+        })
+    }
+
+    /// Certifies and extends the specified blobs in a single transaction.
+    pub async fn certify_and_extend_blobs(
+        &self,
+        blobs_with_certificates: &[CertifyAndExtendBlobParams<'_>],
+        post_store: PostStoreAction,
+        _credits_policy: CreditsPolicy,
+    ) -> SuiClientResult<Vec<CertifyAndExtendBlobResult>> {
+        self.chain.execute(|state| {
+            blobs_with_certificates
+                .iter()
+                .map(|params| {
+                    if let Some(certificate) = &params.certificate {
+                        state.certify_blob(self.address, params.blob.id, certificate)?;
+                    }
+                    if !params.attribute.is_empty() {
+                        let attribute = state
+                            .attribute_mut(self.address, params.blob.id)?
+                            .get_or_insert_with(BlobAttribute::default);
+                        for (key, value) in params.attribute {
+                            attribute.insert(key.clone(), value.clone());
+                        }
+                    }
+                    if let Some(epochs_extended) = params.epochs_extended {
+                        state.extend_blob(self.address, params.blob.id, epochs_extended)?;
+                    }
+                    let shared_blob_id = state.apply_post_store_action(params.blob.id, &post_store);
+                    Ok(CertifyAndExtendBlobResult {
+                        blob_object_id: params.blob.id,
+                        post_store_action_result: PostStoreActionResult::new(
+                            &post_store,
+                            shared_blob_id.map(GetSharedBlobResult::Success),
+                        ),
+                        paid_with_credits: false,
+                    })
+                })
+                .collect()
+        })
+    }
+
+    /// Extends the owned blob object by `epochs_extended` epochs.
+    pub async fn extend_blob(
+        &self,
+        blob_obj_id: ObjectID,
+        epochs_extended: EpochCount,
+        _credits_policy: CreditsPolicy,
+    ) -> SuiClientResult<()> {
+        self.chain
+            .execute(|state| state.extend_blob(self.address, blob_obj_id, epochs_extended))
+    }
+
+    /// Deletes the specified deletable blob and returns its storage resource to the owner.
+    pub async fn delete_blob(&self, blob_object_id: ObjectID) -> SuiClientResult<()> {
+        self.chain.execute(|state| {
+            state.blob_owned_by(self.address, blob_object_id)?;
+            let blob = state
+                .blobs
+                .remove(&blob_object_id)
+                .expect("the blob existence is checked before")
+                .blob;
+            if !blob.deletable {
+                return Err(abort(format!("the blob {} is not deletable", blob.blob_id)));
+            }
+            if blob.storage.end_epoch <= state.epoch {
+                return Err(abort(format!("the blob {} is expired", blob.blob_id)));
+            }
+            let event_id = state.next_event_id();
+            state.emit_blob_event(
+                BlobDeleted {
+                    epoch: state.epoch,
+                    blob_id: blob.blob_id,
+                    end_epoch: blob.storage.end_epoch,
+                    object_id: blob.id,
+                    was_certified: blob.certified_epoch.is_some(),
+                    event_id,
+                }
+                .into(),
+            );
+            state
+                .storage
+                .insert(blob.storage.id, (self.address, blob.storage));
+            Ok(())
+        })
+    }
+
+    /// Burns the blob objects with the given object IDs.
+    pub async fn burn_blobs(&self, blob_object_ids: &[ObjectID]) -> SuiClientResult<()> {
+        self.chain.execute(|state| {
+            for blob_object_id in blob_object_ids {
+                state.blob_owned_by(self.address, *blob_object_id)?;
+                state.blobs.remove(blob_object_id);
+            }
+            Ok(())
+        })
+    }
+
+    /// Transfers the blob objects with the given object IDs to the `recipient` address.
+    pub async fn transfer_blobs(
+        &self,
+        blob_object_ids: &[ObjectID],
+        recipient: SuiAddress,
+    ) -> SuiClientResult<()> {
+        self.chain.execute(|state| {
+            for blob_object_id in blob_object_ids {
+                state.blob_owned_by(self.address, *blob_object_id)?;
+                state.blob_mut(*blob_object_id).owner = BlobOwner::Address(recipient);
+            }
+            Ok(())
+        })
+    }
+
+    /// Returns the list of [`Blob`] objects owned by `owner`, or by the client if `owner` is
+    /// `None`.
+    pub async fn owned_blobs(
+        &self,
+        owner: Option<SuiAddress>,
+        selection_policy: ExpirySelectionPolicy,
+    ) -> SuiClientResult<Vec<Blob>> {
+        let owner = BlobOwner::Address(owner.unwrap_or(self.address));
+        Ok(self.chain.read(|state| {
+            state
+                .blobs
+                .values()
+                .filter(|entry| {
+                    entry.owner == owner
+                        && selection_policy.matches(entry.blob.storage.end_epoch, state.epoch)
+                })
+                .map(|entry| entry.blob.clone())
+                .collect()
+        }))
+    }
+
+    /// Returns the list of [`StorageResource`] objects owned by the client.
+    pub async fn owned_storage(
+        &self,
+        selection_policy: ExpirySelectionPolicy,
+    ) -> SuiClientResult<Vec<StorageResource>> {
+        Ok(self.chain.read(|state| {
+            state
+                .storage
+                .values()
+                .filter(|(owner, storage)| {
+                    *owner == self.address
+                        && selection_policy.matches(storage.end_epoch, state.epoch)
+                })
+                .map(|(_, storage)| storage.clone())
+                .collect()
+        }))
+    }
+
+    /// Adds an attribute to a blob object.
+    ///
+    /// If the attribute already exists, an error is returned unless `force` is true, in which
+    /// case the attribute is updated with the given key-value pairs.
+    pub async fn add_blob_attribute(
+        &self,
+        blob_obj_id: ObjectID,
+        blob_attribute: BlobAttribute,
+        force: bool,
+    ) -> SuiClientResult<()> {
+        self.chain.execute(|state| {
+            let attribute = state.attribute_mut(self.address, blob_obj_id)?;
+            if attribute.is_some() && !force {
+                return Err(SuiClientError::AttributeAlreadyExists);
+            }
+            let existing = attribute.get_or_insert_with(BlobAttribute::default);
+            for (key, value) in &blob_attribute {
+                existing.insert(key.clone(), value.clone());
+            }
+            Ok(())
+        })
+    }
+
+    /// Removes the attribute from a blob object.
+    pub async fn remove_blob_attribute(&self, blob_obj_id: ObjectID) -> SuiClientResult<()> {
+        self.chain.execute(|state| {
+            state
+                .attribute_mut(self.address, blob_obj_id)?
+                .take()
+                .map(|_| ())
+                .ok_or(SuiClientError::AttributeDoesNotExist)
+        })
+    }
+
+    /// Inserts or updates key-value pairs in the blob's attribute.
+    ///
+    /// If the attribute does not exist, it is created if `force` is true and an error is
+    /// returned otherwise.
+    pub async fn insert_or_update_blob_attribute_pairs<I, T>(
+        &self,
+        blob_obj_id: ObjectID,
+        pairs: I,
+        force: bool,
+    ) -> SuiClientResult<()>
+    where
+        I: IntoIterator<Item = (T, T)>,
+        T: Into<String>,
+    {
+        let pairs: Vec<(String, String)> = pairs
+            .into_iter()
+            .map(|(key, value)| (key.into(), value.into()))
+            .collect();
+        self.chain.execute(|state| {
+            let attribute = state.attribute_mut(self.address, blob_obj_id)?;
+            if attribute.is_none() && !force {
+                return Err(SuiClientError::AttributeDoesNotExist);
+            }
+            let attribute = attribute.get_or_insert_with(BlobAttribute::default);
+            for (key, value) in pairs {
+                attribute.insert(key, value);
+            }
+            Ok(())
+        })
+    }
+
+    /// Removes key-value pairs from the blob's attribute.
+    ///
+    /// If the attribute or any of the keys does not exist, an error is returned.
+    pub async fn remove_blob_attribute_pairs<I, T>(
+        &self,
+        blob_obj_id: ObjectID,
+        keys: I,
+    ) -> SuiClientResult<()>
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<str>,
+    {
+        let keys: Vec<String> = keys
+            .into_iter()
+            .map(|key| key.as_ref().to_owned())
+            .collect();
+        self.chain.execute(|state| {
+            let attribute = state
+                .attribute_mut(self.address, blob_obj_id)?
+                .as_mut()
+                .ok_or(SuiClientError::AttributeDoesNotExist)?;
+            if keys.iter().any(|key| attribute.get(key).is_none()) {
+                return Err(SuiClientError::AttributeDoesNotExist);
+            }
+            *attribute = BlobAttribute::from(
+                attribute
+                    .iter()
+                    .filter(|(key, _)| !keys.contains(*key))
+                    .map(|(key, value)| (key.clone(), value.clone()))
+                    .collect::<Vec<_>>(),
+            );
+            Ok(())
+        })
+    }
+
+    /// Invalidates the specified blob ID, given a certificate that confirms that it is invalid.
+    pub async fn invalidate_blob_id(
+        &self,
+        certificate: &InvalidBlobCertificate,
+    ) -> SuiClientResult<()> {
+        self.chain.execute(|state| {
+            let message: InvalidBlobIdMsg = state.verify_certificate(certificate)?;
+            let message = message.as_ref();
+            if message.epoch() != state.epoch {
+                return Err(abort("the certificate is not for the current epoch"));
+            }
+            let event_id = state.next_event_id();
+            state.emit_blob_event(
+                InvalidBlobId {
+                    epoch: state.epoch,
+                    blob_id: *message.contents(),
+                    event_id,
+                }
+                .into(),
+            );
+            Ok(())
+        })
+    }
+
+    /// Registers a candidate node and returns its capability object.
+    pub async fn register_candidate(
+        &self,
+        node_parameters: &NodeRegistrationParams,
+        proof_of_possession: ProofOfPossession,
+    ) -> SuiClientResult<StorageNodeCap> {
+        self.chain.execute(|state| {
+            if let Some(node) = state
+                .nodes
+                .values()
+                .find(|node| node.cap_owner == self.address)
+            {
+                return Err(SuiClientError::CapabilityObjectAlreadyExists(node.cap.id));
+            }
+            let expected_message = ProofOfPossessionMsg::new(
+                state.epoch,
+                self.address.to_inner(),
+                node_parameters.public_key.clone(),
+            );
+            if proof_of_possession.serialized_message
+                != bcs::to_bytes(&expected_message).expect("the message can be serialized")
+                || node_parameters
+                    .public_key
+                    .verify(
+                        &proof_of_possession.serialized_message,
+                        &proof_of_possession.signature,
+                    )
+                    .is_err()
+            {
+                return Err(abort("the proof of possession is invalid"));
+            }
+
+            let node_id = state.new_object_id();
+            let info = StorageNode {
+                name: node_parameters.name.clone(),
+                node_id,
+                network_address: node_parameters.network_address.clone(),
+                public_key: node_parameters.public_key.clone(),
+                next_epoch_public_key: None,
+                network_public_key: node_parameters.network_public_key.clone(),
+                metadata: state.new_object_id(),
+                shard_ids: vec![],
+            };
+            let cap = StorageNodeCap {
+                id: state.new_object_id(),
+                node_id,
+                last_epoch_sync_done: state.epoch,
+                last_event_blob_attestation: None,
+                deny_list_root: [0; 32],
+                deny_list_sequence_number: 0,
+                deny_list_size: 0,
+            };
+            state.nodes.insert(
+                node_id,
+                NodeEntry {
+                    info,
+                    cap: cap.clone(),
+                    cap_owner: self.address,
+                },
+            );
+            Ok(cap)
+        })
+    }
+
+    /// Returns the storage node capability objects owned by the client.
+    pub async fn owned_storage_node_caps(&self) -> SuiClientResult<Vec<StorageNodeCap>> {
+        Ok(self.chain.read(|state| {
+            state
+                .nodes
+                .values()
+                .filter(|node| node.cap_owner == self.address)
+                .map(|node| node.cap.clone())
+                .collect()
+        }))
+    }
+
+    /// For each entry in `node_ids_with_amounts`, stakes the amount of WAL specified by the second
+    /// element of the pair with the node represented by the first element of the pair.
+    pub async fn stake_with_pools(
+        &self,
+        node_ids_with_amounts: &[(ObjectID, u64)],
+    ) -> SuiClientResult<Vec<StakedWal>> {
+        self.chain.execute(|state| {
+            node_ids_with_amounts
+                .iter()
+                .map(|(node_id, amount)| {
+                    if *amount < MIN_STAKING_THRESHOLD {
+                        return Err(SuiClientError::StakeBelowThreshold(*amount));
+                    }
+                    if !state.nodes.contains_key(node_id) {
+                        return Err(abort(format!("the storage node {node_id} does not exist")));
+                    }
+                    state.pay_wal(self.address, *amount)?;
+                    let staked_wal = StakedWal {
+                        id: state.new_object_id(),
+                        state: StakedWalState::Staked,
+                        node_id: *node_id,
+                        principal: *amount,
+                        activation_epoch: state.stake_activation_epoch(),
+                    };
+                    state
+                        .staked_wal
+                        .insert(staked_wal.id, (self.address, staked_wal.clone()));
+                    Ok(staked_wal)
+                })
+                .collect()
+        })
+    }
+
+    /// Returns the staked WAL objects owned by the client.
+    pub async fn owned_staked_wal(&self) -> SuiClientResult<Vec<StakedWal>> {
+        Ok(self.chain.read(|state| {
+            state
+                .staked_wal
+                .values()
+                .filter(|(owner, _)| *owner == self.address)
+                .map(|(_, staked_wal)| staked_wal.clone())
+                .collect()
+        }))
+    }
+
+    /// Requests a withdrawal of staked WAL.
+    pub async fn request_withdraw_stake(&self, staked_wal_id: ObjectID) -> SuiClientResult<()> {
+        self.chain.execute(|state| {
+            let withdraw_epoch = state.stake_activation_epoch();
+            let staked_wal = owned_staked_wal_mut(state, self.address, staked_wal_id)?;
+            if staked_wal.state != StakedWalState::Staked {
+                return Err(abort("the withdrawal of the stake was already requested"));
+            }
+            staked_wal.state = StakedWalState::Withdrawing(withdraw_epoch, None);
+            Ok(())
+        })
+    }
+
+    /// Withdraws staked WAL that has already been requested.
+    pub async fn withdraw_stake(&self, staked_wal_id: ObjectID) -> SuiClientResult<()> {
+        self.chain.execute(|state| {
+            let epoch = state.epoch;
+            let staked_wal = owned_staked_wal_mut(state, self.address, staked_wal_id)?;
+            match staked_wal.state {
+                StakedWalState::Withdrawing(withdraw_epoch, _) if withdraw_epoch <= epoch => (),
+                _ => return Err(abort("the stake cannot be withdrawn yet")),
+            }
+            let principal = staked_wal.principal;
+            state.staked_wal.remove(&staked_wal_id);
+            state.balances_mut(self.address).wal += principal;
+            Ok(())
+        })
+    }
+
+    /// Ends voting and selects the committee for the next epoch.
+    pub async fn voting_end(&self) -> SuiClientResult<()> {
+        self.chain.execute(ChainState::end_voting)
+    }
+
+    /// Initiates the epoch change.
+    pub async fn initiate_epoch_change(&self) -> SuiClientResult<()> {
+        self.chain.execute(ChainState::initiate_epoch_change)
+    }
+
+    /// Notifies the contract that the node is done syncing the specified epoch.
+    pub async fn epoch_sync_done(
+        &self,
+        epoch: Epoch,
+        node_capability_object_id: ObjectID,
+    ) -> SuiClientResult<()> {
+        self.chain
+            .execute(|state| state.epoch_sync_done(self.address, epoch, node_capability_object_id))
+    }
+
+    /// Attests to the specified event blob as the node with the given capability.
+    ///
+    /// The event blob is certified once a quorum of shards has attested to it.
+    pub async fn certify_event_blob(
+        &self,
+        blob_metadata: BlobObjectMetadata,
+        ending_checkpoint_seq_num: u64,
+        epoch: u32,
+        node_capability_object_id: ObjectID,
+    ) -> SuiClientResult<()> {
+        self.chain.execute(|state| {
+            state.certify_event_blob(
+                self.address,
+                blob_metadata,
+                ending_checkpoint_seq_num,
+                epoch,
+                node_capability_object_id,
+            )
+        })
+    }
+}
+
+fn owned_staked_wal_mut(
+    state: &mut ChainState,
+    owner: SuiAddress,
+    staked_wal_id: ObjectID,
+) -> SuiClientResult<&mut StakedWal> {
+    match state.staked_wal.get_mut(&staked_wal_id) {
+        Some((staked_wal_owner, staked_wal)) if *staked_wal_owner == owner => Ok(staked_wal),
+        _ => Err(abort(format!(
+            "the staked WAL {staked_wal_id} is not owned by {owner}"
+        ))),
+    }
+}
+
+impl ReadClient for SimulatedContractClient {
+    async fn storage_price_per_unit_size(&self) -> SuiClientResult<u64> {
+        Ok(self
+            .chain
+            .read(|state| state.config.storage_price_per_unit_size))
+    }
+
+    async fn write_price_per_unit_size(&self) -> SuiClientResult<u64> {
+        Ok(self
+            .chain
+            .read(|state| state.config.write_price_per_unit_size))
+    }
+
+    async fn storage_and_write_price_per_unit_size(&self) -> SuiClientResult<(u64, u64)> {
+        Ok(self.chain.read(|state| {
+            (
+                state.config.storage_price_per_unit_size,
+                state.config.write_price_per_unit_size,
+            )
+        }))
+    }
+
+    async fn event_stream(
+        &self,
+        _polling_interval: Duration,
+        cursor: Option<EventID>,
+    ) -> SuiClientResult<impl Stream<Item = ContractEvent> + Send> {
+        Ok(self.chain.subscribe(cursor))
+    }
+
+    async fn get_blob_event(&self, event_id: EventID) -> SuiClientResult<BlobEvent> {
+        self.chain
+            .read(|state| {
+                state.events.iter().find_map(|event| match event {
+                    ContractEvent::BlobEvent(blob_event) if blob_event.event_id() == event_id => {
+                        Some(blob_event.clone())
+                    }
+                    _ => None,
+                })
+            })
+            .ok_or(SuiClientError::NoCorrespondingBlobEvent(event_id))
+    }
+
+    async fn current_committee(&self) -> SuiClientResult<Committee> {
+        Ok(self.chain.read(|state| state.current_committee.clone()))
+    }
+
+    async fn previous_committee(&self) -> SuiClientResult<Committee> {
+        Ok(self.chain.read(|state| state.previous_committee.clone()))
+    }
+
+    async fn next_committee(&self) -> SuiClientResult<Option<Committee>> {
+        Ok(self.chain.read(|state| state.next_committee.clone()))
+    }
+
+    async fn get_storage_nodes_from_active_set(&self) -> Result<Vec<StorageNode>> {
+        Ok(self.chain.read(|state| {
+            state
+                .stake_in_epoch(state.epoch + 1)
+                .into_iter()
+                .filter(|(_, stake)| *stake > 0)
+                .map(|(node_id, _)| state.nodes[&node_id].info.clone())
+                .collect()
+        }))
+    }
+
+    async fn get_storage_nodes_from_committee(&self) -> SuiClientResult<Vec<StorageNode>> {
+        Ok(self
+            .chain
+            .read(|state| state.current_committee.members().to_vec()))
+    }
+
+    async fn get_storage_nodes_by_ids(&self, node_ids: &[ObjectID]) -> Result<Vec<StorageNode>> {
+        self.chain.read(|state| {
+            node_ids
+                .iter()
+                .map(|node_id| {
+                    state
+                        .nodes
+                        .get(node_id)
+                        .map(|node| node.info.clone())
+                        .ok_or_else(|| anyhow!("the storage node {node_id} does not exist"))
+                })
+                .collect()
+        })
+    }
+
+    async fn get_blob_attribute(
+        &self,
+        blob_object_id: &ObjectID,
+    ) -> SuiClientResult<Option<BlobAttribute>> {
+        self.chain.read(|state| {
+            state
+                .blobs
+                .get(blob_object_id)
+                .map(|entry| entry.attribute.clone())
+                .ok_or_else(|| abort(format!("the blob object {blob_object_id} does not exist")))
+        })
+    }
+
+    async fn get_blob_by_object_id(
+        &self,
+        blob_id: &ObjectID,
+    ) -> SuiClientResult<BlobWithAttribute> {
+        self.chain.read(|state| {
+            let blob_object_id = state.shared_blobs.get(blob_id).unwrap_or(blob_id);
+            state
+                .blobs
+                .get(blob_object_id)
+                .map(BlobEntry::with_attribute)
+                .ok_or_else(|| abort(format!("the blob object {blob_id} does not exist")))
+        })
+    }
+
+    async fn epoch_state(&self) -> SuiClientResult<EpochState> {
+        Ok(self.chain.read(|state| state.epoch_state.clone()))
+    }
+
+    async fn current_epoch(&self) -> SuiClientResult<Epoch> {
+        Ok(self.chain.read(|state| state.epoch))
+    }
+
+    async fn get_committees_and_state(&self) -> SuiClientResult<CommitteesAndState> {
+        Ok(self.chain.read(|state| CommitteesAndState {
+            current: state.current_committee.clone(),
+            previous: (state.epoch > 0).then(|| state.previous_committee.clone()),
+            next: state.next_committee.clone(),
+            epoch_state: state.epoch_state.clone(),
+        }))
+    }
+
+    async fn fixed_system_parameters(&self) -> SuiClientResult<FixedSystemParameters> {
+        Ok(self.chain.read(ChainState::fixed_system_parameters))
+    }
+
+    async fn stake_assignment(&self) -> SuiClientResult<HashMap<ObjectID, u64>> {
+        Ok(self
+            .chain
+            .read(|state| state.stake_in_epoch(state.epoch + 1).into_iter().collect()))
+    }
+
+    async fn last_certified_event_blob(&self) -> SuiClientResult<Option<EventBlob>> {
+        Ok(self
+            .chain
+            .read(|state| state.last_certified_event_blob.clone()))
+    }
+
+    async fn refresh_package_id(&self) -> SuiClientResult<()> {
+        Ok(())
+    }
+
+    async fn refresh_credits_package_id(&self) -> SuiClientResult<()> {
+        Ok(())
+    }
+
+    async fn refresh_walrus_subsidies_package_id(&self) -> SuiClientResult<()> {
+        Ok(())
+    }
+
+    async fn system_object_version(&self) -> SuiClientResult<u64> {
+        Ok(self.chain.read(|state| state.version))
+    }
+
+    async fn flush_cache(&self) {}
+}
+
+impl WriteClient for SimulatedContractClient {
+    fn address(&self) -> SuiAddress {
+        self.address
+    }
+
+    async fn register_blobs(
+        &self,
+        blob_metadata_and_storage: Vec<(BlobObjectMetadata, StorageResource)>,
+        persistence: BlobPersistence,
+        credits_policy: CreditsPolicy,
+    ) -> SuiClientResult<RegisterBlobsResult> {
+        Ok(RegisterBlobsResult {
+            blobs: self
+                .register_blobs(blob_metadata_and_storage, persistence, credits_policy)
+                .await?,
+            paid_with_credits: false,
+        })
+    }
+
+    async fn reserve_and_register_blobs(
+        &self,
+        epochs_ahead: EpochCount,
+        blob_metadata_list: Vec<BlobObjectMetadata>,
+        persistence: BlobPersistence,
+        credits_policy: CreditsPolicy,
+    ) -> SuiClientResult<RegisterBlobsResult> {
+        Ok(RegisterBlobsResult {
+            blobs: self
+                .reserve_and_register_blobs(
+                    epochs_ahead,
+                    blob_metadata_list,
+                    persistence,
+                    credits_policy,
+                )
+                .await?,
+            paid_with_credits: false,
+        })
+    }
+
+    async fn certify_and_extend_blobs(
+        &self,
+        blobs_with_certificates: &[CertifyAndExtendBlobParams<'_>],
+        post_store: PostStoreAction,
+        credits_policy: CreditsPolicy,
+    ) -> SuiClientResult<Vec<CertifyAndExtendBlobResult>> {
+        self.certify_and_extend_blobs(blobs_with_certificates, post_store, credits_policy)
+            .await
+    }
+
+    async fn credits_buyer_subsidy_rate(
+        &self,
+        _credits_policy: CreditsPolicy,
+    ) -> SuiClientResult<Option<u16>> {
+        Ok(None)
+    }
+
+    async fn owned_blobs(
+        &self,
+        owner: Option<SuiAddress>,
+        selection_policy: ExpirySelectionPolicy,
+    ) -> SuiClientResult<Vec<Blob>> {
+        self.owned_blobs(owner, selection_policy).await
+    }
+
+    async fn owned_storage(
+        &self,
+        selection_policy: ExpirySelectionPolicy,
+    ) -> SuiClientResult<Vec<StorageResource>> {
+        self.owned_storage(selection_policy).await
+    }
+
+    async fn delete_blob(&self, blob_object_id: ObjectID) -> SuiClientResult<()> {
+        self.delete_blob(blob_object_id).await
+    }
+
+    async fn transfer_blobs(
+        &self,
+        blob_object_ids: &[ObjectID],
+        recipient: SuiAddress,
+    ) -> SuiClientResult<()> {
+        self.transfer_blobs(blob_object_ids, recipient).await
+    }
+
+    async fn stake_with_pools(
+        &self,
+        node_ids_with_amounts: &[(ObjectID, u64)],
+    ) -> SuiClientResult<Vec<StakedWal>> {
+        self.stake_with_pools(node_ids_with_amounts).await
+    }
+
+    async fn pay_tip<A: Serialize + Sync>(
+        &self,
+        recipient: SuiAddress,
+        _auth_package: &A,
+        amount: u64,
+        _gas_budget: Option<u64>,
+    ) -> SuiClientResult<TransactionDigest> {
+        self.chain.execute(|state| {
+            state.transfer(self.address, recipient, CoinType::Sui, amount)?;
+            Ok(state.current_transaction)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::StreamExt as _;
+    use walrus_core::{
+        EncodingType,
+        keys::{NetworkKeyPair, ProtocolKeyPair},
+        merkle::Node as MerkleNode,
+        messages::BlobPersistenceType,
+        test_utils,
+    };
+
+    use super::*;
+    use crate::{types::NetworkAddress, utils::generate_proof_of_possession_for_address};
+
+    const WAL: u64 = 1_000_000_000;
+
+    struct TestNode {
+        client: SimulatedContractClient,
+        key_pair: ProtocolKeyPair,
+        cap: StorageNodeCap,
+    }
+
+    async fn register_node(chain: &SimulatedChain, stake: u64) -> TestNode {
+        let client = chain.new_client();
+        chain.fund(client.address(), CoinType::Wal, stake);
+        let key_pair = ProtocolKeyPair::generate();
+        let params = NodeRegistrationParams {
+            network_address: NetworkAddress("127.0.0.1:9185".to_owned()),
+            ..NodeRegistrationParams::new_for_test(
+                key_pair.public(),
+                NetworkKeyPair::generate().public(),
+            )
+        };
+        let epoch = client.current_epoch().await.unwrap();
+        let proof_of_possession =
+            generate_proof_of_possession_for_address(&key_pair, client.address(), epoch);
+        let cap = client
+            .register_candidate(&params, proof_of_possession)
+            .await
+            .unwrap();
+        client
+            .stake_with_pools(&[(cap.node_id, stake)])
+            .await
+            .unwrap();
+        TestNode {
+            client,
+            key_pair,
+            cap,
+        }
+    }
+
+    async fn chain_with_nodes(stakes: &[u64]) -> (SimulatedChain, Vec<TestNode>) {
+        let chain = SimulatedChain::new(SimulatedChainConfig {
+            seed: 42,
+            ..Default::default()
+        });
+        let mut nodes = vec![];
+        for stake in stakes {
+            nodes.push(register_node(&chain, *stake).await);
+        }
+        chain.advance_epoch().unwrap();
+        (chain, nodes)
+    }
+
+    fn blob_metadata(chain: &SimulatedChain, unencoded_size: u64) -> BlobObjectMetadata {
+        let n_shards = chain.read(|state| state.config.n_shards);
+        BlobObjectMetadata {
+            blob_id: test_utils::random_blob_id(),
+            root_hash: MerkleNode::Digest([0; 32]),
+            unencoded_size,
+            encoded_size: encoded_blob_length_for_n_shards(
+                n_shards,
+                unencoded_size,
+                EncodingType::RS2,
+            )
+            .unwrap(),
+            encoding_type: EncodingType::RS2,
+        }
+    }
+
+    async fn certificate(nodes: &[TestNode], blob: &Blob) -> ConfirmationCertificate {
+        let committee = nodes[0].client.current_committee().await.unwrap();
+        let confirmation =
+            Confirmation::new(committee.epoch, blob.blob_id, blob.blob_persistence_type());
+        let (indices, messages): (Vec<_>, Vec<_>) = nodes
+            .iter()
+            .map(|node| {
+                let index = committee
+                    .members()
+                    .iter()
+                    .position(|member| member.public_key == *node.key_pair.public())
+                    .unwrap();
+                (
+                    u16::try_from(index).unwrap(),
+                    node.key_pair.sign_message(&confirmation),
+                )
+            })
+            .unzip();
+        ConfirmationCertificate::from_signed_messages_and_indices(messages, indices).unwrap()
+    }
+
+    #[tokio::test]
+    async fn assigns_shards_proportionally_to_stake() {
+        let (chain, nodes) = chain_with_nodes(&[WAL, WAL, 2 * WAL]).await;
+        let client = chain.new_client();
+        let committee = client.current_committee().await.unwrap();
+
+        assert_eq!(committee.epoch, 1);
+        let shards_of = |node: &TestNode| {
+            committee
+                .shards_for_node_public_key(node.key_pair.public())
+                .len()
+        };
+        let mut shards: Vec<_> = nodes.iter().map(shards_of).collect();
+        shards.sort();
+        assert_eq!(shards, [2, 3, 5]);
+        assert!(matches!(
+            client.epoch_state().await.unwrap(),
+            EpochState::EpochChangeDone(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn derives_identical_state_from_the_same_seed() {
+        let (first, _) = chain_with_nodes(&[WAL, WAL]).await;
+        let (second, _) = chain_with_nodes(&[WAL, WAL]).await;
+
+        let committee_ids = |chain: &SimulatedChain| {
+            chain.read(|state| {
+                state
+                    .current_committee
+                    .members()
+                    .iter()
+                    .map(|node| node.node_id)
+                    .collect::<Vec<_>>()
+            })
+        };
+        assert_eq!(committee_ids(&first), committee_ids(&second));
+        assert_eq!(
+            first
+                .events()
+                .iter()
+                .map(ContractEvent::event_id)
+                .collect::<Vec<_>>(),
+            second
+                .events()
+                .iter()
+                .map(ContractEvent::event_id)
+                .collect::<Vec<_>>(),
+        );
+    }
+
+    #[tokio::test]
+    async fn stores_extends_and_deletes_blob() {
+        let (chain, nodes) = chain_with_nodes(&[WAL, WAL, WAL, WAL]).await;
+        let client = chain.new_client();
+        chain.fund(client.address(), CoinType::Wal, 1_000 * WAL);
+        let mut events = client
+            .event_stream(
+                Duration::from_millis(10),
+                chain.events().last().map(|e| e.event_id()),
+            )
+            .await
+            .unwrap();
+
+        let metadata = blob_metadata(&chain, 10_000);
+        let blob = client
+            .reserve_and_register_blobs(
+                2,
+                vec![metadata.clone()],
+                BlobPersistence::Deletable,
+                CreditsPolicy::Disable,
+            )
+            .await
+            .unwrap()
+            .remove(0);
+        assert_eq!(
+            blob.blob_persistence_type(),
+            BlobPersistenceType::Deletable {
+                object_id: blob.id.into()
+            }
+        );
+
+        let blob_with_attribute = client.get_blob_by_object_id(&blob.id).await.unwrap();
+        let certificate = certificate(&nodes[..3], &blob).await;
+        client
+            .certify_blobs(
+                &[(&blob_with_attribute, certificate)],
+                PostStoreAction::Keep,
+            )
+            .await
+            .unwrap();
+        client
+            .extend_blob(blob.id, 3, CreditsPolicy::Disable)
+            .await
+            .unwrap();
+        client
+            .insert_or_update_blob_attribute_pairs(blob.id, [("key", "value")], true)
+            .await
+            .unwrap();
+
+        let stored = client.get_blob_by_object_id(&blob.id).await.unwrap();
+        assert_eq!(stored.blob.certified_epoch, Some(1));
+        assert_eq!(stored.blob.storage.end_epoch, 6);
+        assert_eq!(stored.attribute.unwrap().get("key"), Some("value"));
+
+        client.delete_blob(blob.id).await.unwrap();
+        assert!(
+            client
+                .owned_blobs(None, ExpirySelectionPolicy::All)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(
+            client
+                .owned_storage(ExpirySelectionPolicy::Valid)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        let mut blob_events = vec![];
+        for _ in 0..4 {
+            let Some(ContractEvent::BlobEvent(event)) = events.next().await else {
+                panic!("expected a blob event");
+            };
+            blob_events.push(event);
+        }
+        assert!(matches!(
+            blob_events.as_slice(),
+            [
+                BlobEvent::Registered(_),
+                BlobEvent::Certified(BlobCertified {
+                    is_extension: false,
+                    ..
+                }),
+                BlobEvent::Certified(BlobCertified {
+                    is_extension: true,
+                    end_epoch: 6,
+                    ..
+                }),
+                BlobEvent::Deleted(BlobDeleted {
+                    was_certified: true,
+                    ..
+                }),
+            ]
+        ));
+    }
+
+    #[tokio::test]
+    async fn rejects_certificate_without_quorum_atomically() {
+        let (chain, nodes) = chain_with_nodes(&[WAL, WAL, WAL, WAL]).await;
+        let client = chain.new_client();
+        chain.fund(client.address(), CoinType::Wal, 1_000 * WAL);
+        let blob = client
+            .reserve_and_register_blobs(
+                1,
+                vec![blob_metadata(&chain, 100)],
+                BlobPersistence::Permanent,
+                CreditsPolicy::Disable,
+            )
+            .await
+            .unwrap()
+            .remove(0);
+        let blob_with_attribute = client.get_blob_by_object_id(&blob.id).await.unwrap();
+        let version = client.system_object_version().await.unwrap();
+
+        let certificate = certificate(&nodes[..2], &blob).await;
+        assert!(
+            client
+                .certify_blobs(
+                    &[(&blob_with_attribute, certificate)],
+                    PostStoreAction::Burn
+                )
+                .await
+                .is_err()
+        );
+        assert_eq!(client.system_object_version().await.unwrap(), version);
+        let stored = client.get_blob_by_object_id(&blob.id).await.unwrap();
+        assert_eq!(stored.blob.certified_epoch, None);
+    }
+
+    #[tokio::test]
+    async fn pays_tip_in_sui() {
+        let chain = SimulatedChain::new(SimulatedChainConfig::default());
+        let client = chain.new_client();
+        let relay = chain.new_client();
+        chain.fund(client.address(), CoinType::Sui, 1_000);
+
+        let digest = client
+            .pay_tip(relay.address(), &[0u8; 32], 300, None)
+            .await
+            .unwrap();
+        let second_digest = client
+            .pay_tip(relay.address(), &[0u8; 32], 300, None)
+            .await
+            .unwrap();
+
+        assert_ne!(digest, second_digest);
+        assert_eq!(client.balance(CoinType::Sui).await.unwrap(), 400);
+        assert_eq!(relay.balance(CoinType::Sui).await.unwrap(), 600);
+        assert!(
+            client
+                .pay_tip(relay.address(), &[0u8; 32], 500, None)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn completes_epoch_change_after_quorum_of_sync_done() {
+        let (chain, nodes) = chain_with_nodes(&[WAL, WAL, WAL, WAL]).await;
+        let client = chain.new_client();
+        client.voting_end().await.unwrap();
+        client.initiate_epoch_change().await.unwrap();
+        assert_eq!(
+            client.epoch_state().await.unwrap(),
+            EpochState::EpochChangeSync(0)
+        );
+
+        for node in &nodes[..3] {
+            assert!(client.epoch_state().await.unwrap().is_transitioning());
+            node.client.epoch_sync_done(2, node.cap.id).await.unwrap();
+        }
+        assert!(matches!(
+            client.epoch_state().await.unwrap(),
+            EpochState::EpochChangeDone(_)
+        ));
+        assert!(matches!(
+            nodes[0].client.epoch_sync_done(2, nodes[0].cap.id).await,
+            Err(SuiClientError::LatestAttestedIsMoreRecent)
+        ));
+        assert_eq!(client.previous_committee().await.unwrap().epoch, 1);
+    }
+}
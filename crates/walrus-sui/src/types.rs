@@ -22,6 +22,7 @@ pub use events::{
     BlobRegistered,
     ContractEvent,
     ContractUpgradedEvent,
+    DenyListBlobDeleted,
     DenyListEvent,
     DenyListUpdateEvent,
     EpochChangeDone,
@@ -25,6 +25,7 @@ use walrus_sui::{
         BlobObjectMetadata,
         BlobPersistence,
         CoinType,
+        CreditsPolicy,
         PostStoreAction,
         ReadClient,
         SuiContractClient,
@@ -108,7 +109,7 @@ async fn test_register_certify_blob_100_percent_buyer_credits() -> anyhow::Resul
         .unwrap();
     let storage_resource = walrus_client
         .as_ref()
-        .reserve_space(resource_size, 3)
+        .reserve_space(resource_size, 3, CreditsPolicy::Auto)
         .await?;
     assert_eq!(storage_resource.start_epoch, 1);
     assert_eq!(storage_resource.end_epoch, 4);
@@ -144,7 +145,7 @@ async fn test_register_certify_blob() -> anyhow::Result<()> {
         .unwrap();
     let storage_resource = walrus_client
         .as_ref()
-        .reserve_space(resource_size, 3)
+        .reserve_space(resource_size, 3, CreditsPolicy::Auto)
         .await?;
     assert_eq!(storage_resource.start_epoch, 1);
     assert_eq!(storage_resource.end_epoch, 4);
@@ -172,8 +173,10 @@ async fn test_register_certify_blob() -> anyhow::Result<()> {
         .register_blobs(
             vec![(blob_metadata, storage_resource.clone())],
             BlobPersistence::Permanent,
+            CreditsPolicy::Auto,
         )
         .await?
+        .blobs
         .into_iter()
         .next()
         .expect("expected one blob object");
@@ -240,7 +243,7 @@ async fn test_register_certify_blob() -> anyhow::Result<()> {
     // we receive the event
     let storage_resource = walrus_client
         .as_ref()
-        .reserve_space(resource_size, 3)
+        .reserve_space(resource_size, 3, CreditsPolicy::Auto)
         .await?;
     #[rustfmt::skip]
     let root_hash = [
@@ -263,8 +266,10 @@ async fn test_register_certify_blob() -> anyhow::Result<()> {
         .register_blobs(
             vec![(blob_metadata, storage_resource)],
             BlobPersistence::Permanent,
+            CreditsPolicy::Auto,
         )
         .await?
+        .blobs
         .into_iter()
         .next()
         .expect("expected one blob object");
@@ -504,7 +509,7 @@ async fn test_collect_commission() -> anyhow::Result<()> {
     let resource_size = 1_000_000_000_000;
     let _storage_resource = walrus_client
         .as_ref()
-        .reserve_space(resource_size, 1)
+        .reserve_space(resource_size, 1, CreditsPolicy::Auto)
         .await?;
 
     // Change epoch to allow collecting commission.
@@ -62,6 +62,19 @@ pub fn read_blob_from_file(path: impl AsRef<Path>) -> anyhow::Result<Vec<u8>> {
     ))
 }
 
+/// Returns the given percentile of the sorted values, using the nearest-rank method.
+///
+/// Returns `None` if there are no values; `percentile` must be in `(0, 100]`.
+pub fn nearest_rank<T: Copy>(sorted: &[T], percentile: f64) -> Option<T> {
+    if sorted.is_empty() {
+        return None;
+    }
+    // The rank is at least 1 and at most `sorted.len()`, so the conversions are lossless.
+    #[allow(clippy::cast_possible_truncation)]
+    let rank = ((percentile / 100.0) * sorted.len() as f64).ceil().max(1.0) as usize;
+    Some(sorted[rank.min(sorted.len()) - 1])
+}
+
 /// A macro to print a crumb of information to the console. This is useful for debugging.
 #[macro_export]
 macro_rules! crumb {